@@ -15,13 +15,14 @@ use crunch_shared::{
             Function as MirFunction, Instruction, Mir, Rval, Terminator, Type, Value, VarId,
             Variable,
         },
-        CallConv,
+        CallConv, Sign,
     },
     utils::{Either, HashMap, Hasher, Upcast},
     visitors::mir::MirVisitor,
 };
 use llvm::{
     module::{BuildingBlock, FunctionBuilder, Linkage, Module},
+    target_machine::{Target, TargetConf, TargetData, TargetMachine},
     types::{ArrayType, IntType, SealedAnyType, Type as LLVMType, VoidType, I1},
     utils::{AddressSpace, CallingConvention, IntOperand, EMPTY_CSTR},
     values::{
@@ -36,12 +37,18 @@ use std::sync::Arc;
 pub struct BundledModule {
     context: Option<Context>,
     module: Option<Module<'static>>,
+    target_machine: TargetMachine,
 }
 
 impl BundledModule {
     pub fn get(&self) -> &Module<'_> {
         self.module.as_ref().unwrap()
     }
+
+    /// The machine the module was laid out for, which it should be emitted with
+    pub fn target_machine(&self) -> &TargetMachine {
+        &self.target_machine
+    }
 }
 
 impl Drop for BundledModule {
@@ -72,12 +79,18 @@ fn generate_module(
     let config = db.config();
     let mir = db.lower_mir(file)?;
 
+    Target::init_native(TargetConf::all()).unwrap();
+    let target_machine = TargetMachine::default();
+
     let context = Context::new().unwrap();
     let module =
         crunch_shared::allocator::CRUNCHC_ALLOCATOR.record_region("code generation", || {
             let module = context.module(&*db.file_name(file)).unwrap();
-            CodeGenerator::new(db, &*mir, &context, &module)
-                .generate()
+            target_machine.configure_module(&module).unwrap();
+
+            let target_data = target_machine.target_data().unwrap();
+            CodeGenerator::new(db, &*mir, &context, &module, target_data)
+                .and_then(CodeGenerator::generate)
                 .unwrap();
 
             // FIXME: Actual lifetimes when salsa allows + a context database?
@@ -121,6 +134,7 @@ fn generate_module(
     Ok(Arc::new(BundledModule {
         context: Some(context),
         module: Some(module),
+        target_machine,
     }))
 }
 
@@ -147,6 +161,8 @@ pub struct CodeGenerator<'db> {
     current_function: Option<FuncId>,
     context: &'db Context,
     module: &'db Module<'db>,
+    /// The module's data layout, used to find the sizes of types when laying out enums
+    target_data: TargetData,
     mir: &'db Mir,
     db: &'db dyn CodegenDatabase,
 }
@@ -157,8 +173,9 @@ impl<'db> CodeGenerator<'db> {
         mir: &'db Mir,
         context: &'db Context,
         module: &'db Module<'db>,
-    ) -> Self {
-        Self {
+        target_data: TargetData,
+    ) -> LLVMResult<Self> {
+        Ok(Self {
             module,
             values: HashMap::with_hasher(Hasher::default()),
             blocks: HashMap::with_hasher(Hasher::default()),
@@ -168,9 +185,109 @@ impl<'db> CodeGenerator<'db> {
             current_block: None,
             current_function: None,
             context,
+            target_data,
             mir,
             db,
+        })
+    }
+
+    /// Lays out an enum as its discriminant followed by enough space to hold the payload
+    /// of any of its variants
+    fn enum_type(&mut self, variants: &[Vec<Type>]) -> LLVMResult<LLVMType<'db>> {
+        let mut payload_size = 0;
+        for variant in variants {
+            let payload = self.payload_type(variant)?;
+            payload_size = payload_size.max(self.target_data.abi_size_of(payload));
+        }
+
+        // The payload is made of words so that it's aligned for any variant
+        let word: LLVMType<'db> = IntType::u64(&self.context)?.into();
+        let payload = word.make_array(((payload_size + 7) / 8) as u32)?.into();
+
+        self.module
+            .create_struct(&[self.visit_type(&Type::DISCRIMINANT)?, payload], false)
+    }
+
+    /// The type of a single variant's payload
+    fn payload_type(&mut self, elements: &[Type]) -> LLVMResult<LLVMType<'db>> {
+        let elements = elements
+            .iter()
+            .map(|ty| self.visit_type(ty))
+            .collect::<LLVMResult<Vec<_>>>()?;
+
+        self.module.create_struct(&elements, false)
+    }
+
+    /// Allocates a stack slot at the start of the current function's entry block, so that
+    /// it's allocated once per call instead of every time the block using it runs
+    unsafe fn entry_alloca(&self, ty: *mut llvm_sys::LLVMType) -> *mut llvm_sys::LLVMValue {
+        let entry = llvm_sys::core::LLVMGetEntryBasicBlock(self.current_function().as_mut_ptr());
+        let builder = llvm_sys::core::LLVMCreateBuilderInContext(self.context.as_mut_ptr());
+
+        let first = llvm_sys::core::LLVMGetFirstInstruction(entry);
+        if first.is_null() {
+            llvm_sys::core::LLVMPositionBuilderAtEnd(builder, entry);
+        } else {
+            llvm_sys::core::LLVMPositionBuilderBefore(builder, first);
+        }
+
+        let slot = llvm_sys::core::LLVMBuildAlloca(builder, ty, EMPTY_CSTR);
+        llvm_sys::core::LLVMDisposeBuilder(builder);
+
+        slot
+    }
+
+    /// Views the payload of the enum stored in `slot` as the payload of one of its variants
+    unsafe fn payload_ptr(
+        &self,
+        slot: *mut llvm_sys::LLVMValue,
+        payload: LLVMType<'db>,
+    ) -> *mut llvm_sys::LLVMValue {
+        let builder = self.get_block_builder().builder().as_mut_ptr();
+        let payload_ptr = llvm_sys::core::LLVMBuildStructGEP(builder, slot, 1, EMPTY_CSTR);
+
+        llvm_sys::core::LLVMBuildBitCast(
+            builder,
+            payload_ptr,
+            llvm_sys::core::LLVMPointerType(payload.as_mut_ptr(), 0),
+            EMPTY_CSTR,
+        )
+    }
+
+    /// Returns `true` if the value is a pointer, which arrays and tuples are when they're
+    /// global constants or are behind a reference
+    unsafe fn is_pointer(value: *mut llvm_sys::LLVMValue) -> bool {
+        llvm_sys::core::LLVMGetTypeKind(llvm_sys::core::LLVMTypeOf(value))
+            == llvm_sys::LLVMTypeKind::LLVMPointerTypeKind
+    }
+
+    /// Gets the value of an array or tuple, loading it if it's held in memory
+    unsafe fn aggregate_value(&self, var: VarId) -> *mut llvm_sys::LLVMValue {
+        let value = self.get_var_value(var).as_ptr();
+        if !Self::is_pointer(value) {
+            return value;
+        }
+
+        let builder = self.get_block_builder().builder().as_mut_ptr();
+        llvm_sys::core::LLVMBuildLoad(builder, value, EMPTY_CSTR)
+    }
+
+    /// Gets a pointer to an array or tuple, spilling it onto the stack if it isn't
+    /// already held in memory
+    unsafe fn aggregate_ptr(&self, var: VarId) -> *mut llvm_sys::LLVMValue {
+        let value = self.get_var_value(var).as_ptr();
+        if Self::is_pointer(value) {
+            return value;
         }
+
+        let slot = self.entry_alloca(llvm_sys::core::LLVMTypeOf(value));
+        llvm_sys::core::LLVMBuildStore(
+            self.get_block_builder().builder().as_mut_ptr(),
+            value,
+            slot,
+        );
+
+        slot
     }
 
     pub fn generate(mut self) -> LLVMResult<()> {
@@ -387,161 +504,63 @@ impl<'db> CodeGenerator<'db> {
         Ok(result)
     }
 
-    // TODO: Query this shit
-    // TODO: Macro this shit
-
-    fn add(&self, lhs: LLVMValue<'db>, rhs: LLVMValue<'db>) -> LLVMResult<LLVMValue<'db>> {
-        let block_builder = self.get_block_builder();
-        let val = match (lhs, rhs) {
-            (LLVMValue::U8(lhs), LLVMValue::U8(rhs)) => LLVMValue::U8(block_builder.add(lhs, rhs)?),
-            (LLVMValue::I8(lhs), LLVMValue::I8(rhs)) => LLVMValue::I8(block_builder.add(lhs, rhs)?),
-
-            (LLVMValue::U16(lhs), LLVMValue::U16(rhs)) => {
-                LLVMValue::U16(block_builder.add(lhs, rhs)?)
-            }
-            (LLVMValue::I16(lhs), LLVMValue::I16(rhs)) => {
-                LLVMValue::I16(block_builder.add(lhs, rhs)?)
-            }
-
-            (LLVMValue::U32(lhs), LLVMValue::U32(rhs)) => {
-                LLVMValue::U32(block_builder.add(lhs, rhs)?)
-            }
-            (LLVMValue::I32(lhs), LLVMValue::I32(rhs)) => {
-                LLVMValue::I32(block_builder.add(lhs, rhs)?)
-            }
-
-            (LLVMValue::U64(lhs), LLVMValue::U64(rhs)) => {
-                LLVMValue::U64(block_builder.add(lhs, rhs)?)
-            }
-            (LLVMValue::I64(lhs), LLVMValue::I64(rhs)) => {
-                LLVMValue::I64(block_builder.add(lhs, rhs)?)
-            }
-
-            (LLVMValue::Raw(lhs), LLVMValue::Raw(rhs)) => {
-                crunch_shared::warn!("Preforming addition on two unchecked values");
-                LLVMValue::Raw(block_builder.add(lhs, rhs)?)
-            }
-
-            (lhs, rhs) => panic!("Illegal instruction: Add {:?} by {:?}", lhs, rhs),
-        };
-
-        Ok(val)
-    }
-
-    fn sub(&self, lhs: LLVMValue<'db>, rhs: LLVMValue<'db>) -> LLVMResult<LLVMValue<'db>> {
-        let block_builder = self.get_block_builder();
-
-        Ok(match (lhs, rhs) {
-            (LLVMValue::U8(lhs), LLVMValue::U8(rhs)) => LLVMValue::U8(block_builder.sub(lhs, rhs)?),
-            (LLVMValue::I8(lhs), LLVMValue::I8(rhs)) => LLVMValue::I8(block_builder.sub(lhs, rhs)?),
-
-            (LLVMValue::U16(lhs), LLVMValue::U16(rhs)) => {
-                LLVMValue::U16(block_builder.sub(lhs, rhs)?)
-            }
-            (LLVMValue::I16(lhs), LLVMValue::I16(rhs)) => {
-                LLVMValue::I16(block_builder.sub(lhs, rhs)?)
-            }
-
-            (LLVMValue::U32(lhs), LLVMValue::U32(rhs)) => {
-                LLVMValue::U32(block_builder.sub(lhs, rhs)?)
-            }
-            (LLVMValue::I32(lhs), LLVMValue::I32(rhs)) => {
-                LLVMValue::I32(block_builder.sub(lhs, rhs)?)
-            }
-
-            (LLVMValue::U64(lhs), LLVMValue::U64(rhs)) => {
-                LLVMValue::U64(block_builder.sub(lhs, rhs)?)
-            }
-            (LLVMValue::I64(lhs), LLVMValue::I64(rhs)) => {
-                LLVMValue::I64(block_builder.sub(lhs, rhs)?)
-            }
-
-            (LLVMValue::Raw(lhs), LLVMValue::Raw(rhs)) => {
-                crunch_shared::warn!("Preforming subtraction on two unchecked values");
-                LLVMValue::Raw(block_builder.sub(lhs, rhs)?)
-            }
-
-            (lhs, rhs) => panic!("Illegal instruction: Subtract {:?} by {:?}", lhs, rhs),
-        })
+    /// Builds an integer operation between two variables, `build` is also given whether
+    /// the operands are signed for the operations that care about it
+    fn binop<F>(&self, lhs: VarId, rhs: VarId, ty: &Type, build: F) -> LLVMResult<LLVMValue<'db>>
+    where
+        F: FnOnce(
+            &BuildingBlock<'db>,
+            RawLLVMValue<'db>,
+            RawLLVMValue<'db>,
+            bool,
+        ) -> LLVMResult<RawLLVMValue<'db>>,
+    {
+        let signed = self.values[&lhs].1.is_signed();
+        let value = build(
+            self.get_block_builder(),
+            self.get_var_value(lhs).as_value(),
+            self.get_var_value(rhs).as_value(),
+            signed,
+        )?;
+
+        Ok(LLVMValue::new(value, ty))
     }
 
-    fn mul(&self, lhs: LLVMValue<'db>, rhs: LLVMValue<'db>) -> LLVMResult<LLVMValue<'db>> {
-        let block_builder = self.get_block_builder();
-
-        Ok(match (lhs, rhs) {
-            (LLVMValue::U8(lhs), LLVMValue::U8(rhs)) => LLVMValue::U8(block_builder.mul(lhs, rhs)?),
-            (LLVMValue::I8(lhs), LLVMValue::I8(rhs)) => LLVMValue::I8(block_builder.mul(lhs, rhs)?),
-
-            (LLVMValue::U16(lhs), LLVMValue::U16(rhs)) => {
-                LLVMValue::U16(block_builder.mul(lhs, rhs)?)
-            }
-            (LLVMValue::I16(lhs), LLVMValue::I16(rhs)) => {
-                LLVMValue::I16(block_builder.mul(lhs, rhs)?)
-            }
-
-            (LLVMValue::U32(lhs), LLVMValue::U32(rhs)) => {
-                LLVMValue::U32(block_builder.mul(lhs, rhs)?)
-            }
-            (LLVMValue::I32(lhs), LLVMValue::I32(rhs)) => {
-                LLVMValue::I32(block_builder.mul(lhs, rhs)?)
-            }
-
-            (LLVMValue::U64(lhs), LLVMValue::U64(rhs)) => {
-                LLVMValue::U64(block_builder.mul(lhs, rhs)?)
-            }
-            (LLVMValue::I64(lhs), LLVMValue::I64(rhs)) => {
-                LLVMValue::I64(block_builder.mul(lhs, rhs)?)
-            }
-
-            (LLVMValue::Raw(lhs), LLVMValue::Raw(rhs)) => {
-                crunch_shared::warn!("Preforming multiplication on two unchecked values");
-                LLVMValue::Raw(block_builder.mul(lhs, rhs)?)
-            }
-
-            (lhs, rhs) => panic!("Illegal instruction: Multiply {:?} by {:?}", lhs, rhs),
-        })
+    /// Wraps a shift amount to the width of the shifted integer, since LLVM gives poison
+    /// when shifting by the integer's width or more
+    fn shift_amount(
+        builder: &BuildingBlock<'db>,
+        amount: RawLLVMValue<'db>,
+    ) -> LLVMResult<RawLLVMValue<'db>> {
+        unsafe {
+            let ty = llvm_sys::core::LLVMTypeOf(amount.as_mut_ptr());
+            let width = llvm_sys::core::LLVMGetIntTypeWidth(ty);
+            let mask = llvm_sys::core::LLVMConstInt(ty, u64::from(width - 1), 0);
+
+            builder.and(amount, RawLLVMValue::from_raw(mask)?)
+        }
     }
 
-    fn div(&self, lhs: LLVMValue<'db>, rhs: LLVMValue<'db>) -> LLVMResult<LLVMValue<'db>> {
-        let block_builder = self.get_block_builder();
-
-        Ok(match (lhs, rhs) {
-            (LLVMValue::U8(lhs), LLVMValue::U8(rhs)) => {
-                LLVMValue::U8(block_builder.udiv(lhs, rhs)?)
-            }
-
-            (LLVMValue::I8(lhs), LLVMValue::I8(rhs)) => {
-                LLVMValue::I8(block_builder.sdiv(lhs, rhs)?)
-            }
-
-            (LLVMValue::U16(lhs), LLVMValue::U16(rhs)) => {
-                LLVMValue::U16(block_builder.udiv(lhs, rhs)?)
-            }
-            (LLVMValue::I16(lhs), LLVMValue::I16(rhs)) => {
-                LLVMValue::I16(block_builder.sdiv(lhs, rhs)?)
-            }
-
-            (LLVMValue::U32(lhs), LLVMValue::U32(rhs)) => {
-                LLVMValue::U32(block_builder.udiv(lhs, rhs)?)
-            }
-            (LLVMValue::I32(lhs), LLVMValue::I32(rhs)) => {
-                LLVMValue::I32(block_builder.sdiv(lhs, rhs)?)
-            }
-
-            (LLVMValue::U64(lhs), LLVMValue::U64(rhs)) => {
-                LLVMValue::U64(block_builder.udiv(lhs, rhs)?)
-            }
-            (LLVMValue::I64(lhs), LLVMValue::I64(rhs)) => {
-                LLVMValue::I64(block_builder.sdiv(lhs, rhs)?)
-            }
-
-            (LLVMValue::Raw(lhs), LLVMValue::Raw(rhs)) => {
-                crunch_shared::warn!("Preforming signed division on two unchecked values");
-                LLVMValue::Raw(block_builder.sdiv(lhs, rhs)?)
-            }
+    /// Compares two variables, using the signed or unsigned predicate depending on the
+    /// operands' type
+    fn compare(
+        &self,
+        lhs: VarId,
+        rhs: VarId,
+        signed: IntOperand,
+        unsigned: IntOperand,
+    ) -> LLVMResult<LLVMValue<'db>> {
+        let operand = if self.values[&lhs].1.is_signed() {
+            signed
+        } else {
+            unsigned
+        };
 
-            (lhs, rhs) => panic!("Illegal instruction: Signed divide {:?} by {:?}", lhs, rhs),
-        })
+        Ok(LLVMValue::Bool(self.get_block_builder().icmp(
+            self.get_var_value(lhs),
+            operand,
+            self.get_var_value(rhs),
+        )?))
     }
 
     fn ret(&self, val: Option<LLVMValue<'db>>) -> LLVMResult<InstructionValue<'db>> {
@@ -550,16 +569,6 @@ impl<'db> CodeGenerator<'db> {
         // TODO: Verify return type against current function's return type
         self.get_block_builder().ret(val.map(|v| v.as_value()))
     }
-
-    fn eq(&self, lhs: LLVMValue<'db>, rhs: LLVMValue<'db>) -> LLVMResult<LLVMValue<'db>> {
-        crunch_shared::warn!("Check that lhs and rhs have the same types in Eq");
-
-        Ok(LLVMValue::Bool(self.get_block_builder().icmp(
-            lhs,
-            IntOperand::Equal,
-            rhs,
-        )?))
-    }
 }
 
 impl<'db> MirVisitor for CodeGenerator<'db> {
@@ -583,7 +592,16 @@ impl<'db> MirVisitor for CodeGenerator<'db> {
                     .insert(*id, (LLVMValue::new(val, &ty), ty.clone()));
             }
 
-            // This will cascade and allow all needed blocks to be compiled
+            // Blocks are compiled after their predecessors so that the values they use have
+            // been compiled, the exception being the jumps back to the start of loops
+            for id in function.reverse_postorder() {
+                if !this.block_has_compiled(id) {
+                    this.visit_block(&function.blocks[&id])?;
+                }
+            }
+
+            // Unreachable blocks are still compiled, this will cascade and allow all needed
+            // blocks to be compiled
             while let Some((_, block)) = function
                 .blocks
                 .iter()
@@ -690,14 +708,14 @@ impl<'db> MirVisitor for CodeGenerator<'db> {
         })?
     }
 
-    type InstructionOutput = LLVMResult<Either<VarId, CallSiteValue<'db>>>;
+    type InstructionOutput = LLVMResult<Option<Either<VarId, CallSiteValue<'db>>>>;
     fn visit_instruction(&mut self, instruction: &Instruction) -> Self::InstructionOutput {
         match instruction {
             Instruction::Assign(Assign { var, val, ty }) => {
                 let val = self.visit_rval(val)?;
                 self.values.insert(*var, (val, ty.clone()));
 
-                Ok(Either::Left(*var))
+                Ok(Some(Either::Left(*var)))
             }
 
             Instruction::Call(FnCall { function, args }) => {
@@ -706,8 +724,26 @@ impl<'db> MirVisitor for CodeGenerator<'db> {
                     .get_block_builder()
                     .call(self.get_function_value(*function), args)?;
 
-                Ok(Either::Right(call))
+                Ok(Some(Either::Right(call)))
             }
+
+            &Instruction::Store { pointer, value } => unsafe {
+                // Arrays and tuples may be global constants, which are loaded before being copied
+                let stored = match self.values[&value].1 {
+                    Type::Pointer { .. } | Type::Reference { .. } => {
+                        self.get_var_value(value).as_ptr()
+                    }
+                    _ => self.aggregate_value(value),
+                };
+
+                llvm_sys::core::LLVMBuildStore(
+                    self.get_block_builder().builder().as_mut_ptr(),
+                    stored,
+                    self.get_var_value(pointer).as_ptr(),
+                );
+
+                Ok(None)
+            },
         }
     }
 
@@ -759,11 +795,90 @@ impl<'db> MirVisitor for CodeGenerator<'db> {
         match val {
             &Value::Variable(id) => Ok(self.get_var_value(id)),
             Value::Const(constant) => self.visit_constant(constant, ty),
-            &Value::Add(lhs, rhs) => self.add(self.get_var_value(lhs), self.get_var_value(rhs)),
-            &Value::Sub(lhs, rhs) => self.sub(self.get_var_value(lhs), self.get_var_value(rhs)),
-            &Value::Mul(lhs, rhs) => self.mul(self.get_var_value(lhs), self.get_var_value(rhs)),
-            &Value::Div(lhs, rhs) => self.div(self.get_var_value(lhs), self.get_var_value(rhs)),
-            &Value::Eq(lhs, rhs) => self.eq(self.get_var_value(lhs), self.get_var_value(rhs)),
+            &Value::Add(lhs, rhs) => self.binop(lhs, rhs, ty, |b, l, r, _| b.add(l, r)),
+            &Value::Sub(lhs, rhs) => self.binop(lhs, rhs, ty, |b, l, r, _| b.sub(l, r)),
+            &Value::Mul(lhs, rhs) => self.binop(lhs, rhs, ty, |b, l, r, _| b.mul(l, r)),
+            &Value::Div(lhs, rhs) => self.binop(lhs, rhs, ty, |b, l, r, signed| {
+                if signed {
+                    b.sdiv(l, r)
+                } else {
+                    b.udiv(l, r)
+                }
+            }),
+            &Value::Rem(lhs, rhs) => self.binop(lhs, rhs, ty, |b, l, r, signed| {
+                if signed {
+                    b.srem(l, r)
+                } else {
+                    b.urem(l, r)
+                }
+            }),
+            &Value::BitAnd(lhs, rhs) => self.binop(lhs, rhs, ty, |b, l, r, _| b.and(l, r)),
+            &Value::BitOr(lhs, rhs) => self.binop(lhs, rhs, ty, |b, l, r, _| b.or(l, r)),
+            &Value::BitXor(lhs, rhs) => self.binop(lhs, rhs, ty, |b, l, r, _| b.xor(l, r)),
+            &Value::Shl(lhs, rhs) => self.binop(lhs, rhs, ty, |b, l, r, _| {
+                b.shl(l, Self::shift_amount(b, r)?)
+            }),
+            &Value::Shr(lhs, rhs) => self.binop(lhs, rhs, ty, |b, l, r, signed| {
+                let r = Self::shift_amount(b, r)?;
+                if signed {
+                    b.ashr(l, r)
+                } else {
+                    b.lshr(l, r)
+                }
+            }),
+
+            &Value::Eq(lhs, rhs) => self.compare(lhs, rhs, IntOperand::Equal, IntOperand::Equal),
+            &Value::Neq(lhs, rhs) => {
+                self.compare(lhs, rhs, IntOperand::NotEqual, IntOperand::NotEqual)
+            }
+            &Value::Less(lhs, rhs) => self.compare(
+                lhs,
+                rhs,
+                IntOperand::SignedLessThan,
+                IntOperand::UnsignedLessThan,
+            ),
+            &Value::LessEq(lhs, rhs) => self.compare(
+                lhs,
+                rhs,
+                IntOperand::SignedLessThanEqual,
+                IntOperand::UnsignedLessThanEqual,
+            ),
+            &Value::Greater(lhs, rhs) => self.compare(
+                lhs,
+                rhs,
+                IntOperand::SignedGreaterThan,
+                IntOperand::UnsignedGreaterThan,
+            ),
+            &Value::GreaterEq(lhs, rhs) => self.compare(
+                lhs,
+                rhs,
+                IntOperand::SignedGreaterThanEqual,
+                IntOperand::UnsignedGreaterThanEqual,
+            ),
+
+            &Value::Neg(var) => unsafe {
+                let negated = llvm_sys::core::LLVMBuildNeg(
+                    self.get_block_builder().builder().as_mut_ptr(),
+                    self.get_var_value(var).as_ptr(),
+                    EMPTY_CSTR,
+                );
+
+                Ok(LLVMValue::new(RawLLVMValue::from_raw(negated)?, ty))
+            },
+
+            // Flipping every bit is the bitwise not of integers and the logical not of booleans
+            &Value::Not(var) => unsafe {
+                let value = self.get_var_value(var).as_ptr();
+                let ones = llvm_sys::core::LLVMConstAllOnes(llvm_sys::core::LLVMTypeOf(value));
+                let inverted = llvm_sys::core::LLVMBuildXor(
+                    self.get_block_builder().builder().as_mut_ptr(),
+                    value,
+                    ones,
+                    EMPTY_CSTR,
+                );
+
+                Ok(LLVMValue::new(RawLLVMValue::from_raw(inverted)?, ty))
+            },
 
             Value::Call(FnCall { function, args }) => {
                 let args = args.iter().map(|arg| self.get_var_value(*arg).as_value());
@@ -775,12 +890,29 @@ impl<'db> MirVisitor for CodeGenerator<'db> {
             }
 
             // FIXME: This is so incredibly not good
-            &Value::GetPointer { var, .. } => {
+            &Value::GetPointer { var, .. } => unsafe {
                 crunch_shared::warn!("Value::GetPointer is the sketchiest shit alive");
                 let val = self.get_var_value(var);
 
-                Ok(val)
-            }
+                // Values that already live in memory, like global constants, are their own
+                // pointers. Otherwise references are only made for the receivers of methods,
+                // which are spilled onto the stack
+                let value_type = llvm_sys::core::LLVMTypeOf(val.as_ptr());
+                let is_pointer = llvm_sys::core::LLVMGetTypeKind(value_type)
+                    == llvm_sys::LLVMTypeKind::LLVMPointerTypeKind;
+                if !ty.is_reference() || is_pointer {
+                    return Ok(val);
+                }
+
+                let slot = self.entry_alloca(value_type);
+                llvm_sys::core::LLVMBuildStore(
+                    self.get_block_builder().builder().as_mut_ptr(),
+                    val.as_ptr(),
+                    slot,
+                );
+
+                Ok(LLVMValue::new(RawLLVMValue::from_raw(slot)?, ty))
+            },
 
             Value::Cast(casted, ty) => unsafe {
                 Ok(LLVMValue::Raw(RawLLVMValue::from_raw(
@@ -792,6 +924,248 @@ impl<'db> MirVisitor for CodeGenerator<'db> {
                     ),
                 )?))
             },
+
+            &Value::Extract(aggregate, index) => unsafe {
+                let element = RawLLVMValue::from_raw(llvm_sys::core::LLVMBuildExtractValue(
+                    self.get_block_builder().builder().as_mut_ptr(),
+                    self.aggregate_value(aggregate),
+                    index as u32,
+                    EMPTY_CSTR,
+                ))?;
+
+                Ok(LLVMValue::new(element, ty))
+            },
+
+            Value::Aggregate(elements) => unsafe {
+                let builder = self.get_block_builder().builder().as_mut_ptr();
+
+                // Aggregates are filled in one element at a time, starting from an undefined value
+                let mut aggregate = llvm_sys::core::LLVMGetUndef(self.visit_type(ty)?.as_mut_ptr());
+                for (index, &element) in elements.iter().enumerate() {
+                    aggregate = llvm_sys::core::LLVMBuildInsertValue(
+                        builder,
+                        aggregate,
+                        self.get_var_value(element).as_ptr(),
+                        index as u32,
+                        EMPTY_CSTR,
+                    );
+                }
+
+                Ok(LLVMValue::new(RawLLVMValue::from_raw(aggregate)?, ty))
+            },
+
+            &Value::Index(var, index) => unsafe {
+                let builder = self.get_block_builder().builder().as_mut_ptr();
+                let index = self.get_var_value(index).as_ptr();
+
+                let mut indexee = &self.values[&var].1;
+                while let Type::Reference { referee, .. } = indexee {
+                    indexee = referee;
+                }
+
+                let element = if indexee.is_slice() {
+                    // Slices hold a pointer to their first element
+                    let elements = llvm_sys::core::LLVMBuildExtractValue(
+                        builder,
+                        self.aggregate_value(var),
+                        0,
+                        EMPTY_CSTR,
+                    );
+                    let mut indices = [index];
+
+                    llvm_sys::core::LLVMBuildInBoundsGEP(
+                        builder,
+                        elements,
+                        indices.as_mut_ptr(),
+                        indices.len() as u32,
+                        EMPTY_CSTR,
+                    )
+                } else {
+                    let zero =
+                        llvm_sys::core::LLVMConstInt(llvm_sys::core::LLVMTypeOf(index), 0, 0);
+                    let mut indices = [zero, index];
+
+                    llvm_sys::core::LLVMBuildInBoundsGEP(
+                        builder,
+                        self.aggregate_ptr(var),
+                        indices.as_mut_ptr(),
+                        indices.len() as u32,
+                        EMPTY_CSTR,
+                    )
+                };
+                let element = llvm_sys::core::LLVMBuildLoad(builder, element, EMPTY_CSTR);
+
+                Ok(LLVMValue::new(RawLLVMValue::from_raw(element)?, ty))
+            },
+
+            Value::Alloca(slot) => unsafe {
+                let slot = self.entry_alloca(self.visit_type(slot)?.as_mut_ptr());
+
+                Ok(LLVMValue::new(RawLLVMValue::from_raw(slot)?, ty))
+            },
+
+            &Value::Load(pointer) => unsafe {
+                let value = llvm_sys::core::LLVMBuildLoad(
+                    self.get_block_builder().builder().as_mut_ptr(),
+                    self.get_var_value(pointer).as_ptr(),
+                    EMPTY_CSTR,
+                );
+
+                Ok(LLVMValue::new(RawLLVMValue::from_raw(value)?, ty))
+            },
+
+            &Value::FieldPtr(pointer, index) => unsafe {
+                // Tuples are structs, which can only be indexed by 32 bit constants
+                let i32_type = self.visit_type(&Type::I32)?.as_mut_ptr();
+                let builder = self.get_block_builder().builder().as_mut_ptr();
+                let mut indices = [
+                    llvm_sys::core::LLVMConstInt(i32_type, 0, 0),
+                    llvm_sys::core::LLVMConstInt(i32_type, index, 0),
+                ];
+
+                let field = llvm_sys::core::LLVMBuildInBoundsGEP(
+                    builder,
+                    self.get_var_value(pointer).as_ptr(),
+                    indices.as_mut_ptr(),
+                    indices.len() as u32,
+                    EMPTY_CSTR,
+                );
+
+                Ok(LLVMValue::new(RawLLVMValue::from_raw(field)?, ty))
+            },
+
+            &Value::IndexPtr(indexee, index) => unsafe {
+                let builder = self.get_block_builder().builder().as_mut_ptr();
+                let index = self.get_var_value(index).as_ptr();
+
+                let element = if self.values[&indexee].1.is_slice() {
+                    // Slices hold a pointer to their first element
+                    let elements = llvm_sys::core::LLVMBuildExtractValue(
+                        builder,
+                        self.get_var_value(indexee).as_ptr(),
+                        0,
+                        EMPTY_CSTR,
+                    );
+                    let mut indices = [index];
+
+                    llvm_sys::core::LLVMBuildInBoundsGEP(
+                        builder,
+                        elements,
+                        indices.as_mut_ptr(),
+                        indices.len() as u32,
+                        EMPTY_CSTR,
+                    )
+                } else {
+                    let zero =
+                        llvm_sys::core::LLVMConstInt(llvm_sys::core::LLVMTypeOf(index), 0, 0);
+                    let mut indices = [zero, index];
+
+                    llvm_sys::core::LLVMBuildInBoundsGEP(
+                        builder,
+                        self.get_var_value(indexee).as_ptr(),
+                        indices.as_mut_ptr(),
+                        indices.len() as u32,
+                        EMPTY_CSTR,
+                    )
+                };
+
+                Ok(LLVMValue::new(RawLLVMValue::from_raw(element)?, ty))
+            },
+
+            Value::Variant {
+                discriminant,
+                payload,
+            } => unsafe {
+                let variants = ty
+                    .enum_variants()
+                    .expect("Variants always have an enum type");
+                let enum_type = self.visit_type(ty)?;
+                let payload_type = self.payload_type(&variants[*discriminant as usize])?;
+                let tag = self.visit_constant(
+                    &Constant::Integer {
+                        sign: Sign::Positive,
+                        bits: *discriminant as u128,
+                    },
+                    &Type::DISCRIMINANT,
+                )?;
+
+                let builder = self.get_block_builder().builder().as_mut_ptr();
+                let slot = self.entry_alloca(enum_type.as_mut_ptr());
+                let tag_ptr = llvm_sys::core::LLVMBuildStructGEP(builder, slot, 0, EMPTY_CSTR);
+                llvm_sys::core::LLVMBuildStore(builder, tag.as_ptr(), tag_ptr);
+
+                let fields = self.payload_ptr(slot, payload_type);
+                for (index, &var) in payload.iter().enumerate() {
+                    let field = llvm_sys::core::LLVMBuildStructGEP(
+                        builder,
+                        fields,
+                        index as u32,
+                        EMPTY_CSTR,
+                    );
+                    llvm_sys::core::LLVMBuildStore(
+                        builder,
+                        self.get_var_value(var).as_ptr(),
+                        field,
+                    );
+                }
+
+                let variant = llvm_sys::core::LLVMBuildLoad(builder, slot, EMPTY_CSTR);
+                Ok(LLVMValue::new(RawLLVMValue::from_raw(variant)?, ty))
+            },
+
+            &Value::Discriminant(var) => unsafe {
+                let builder = self.get_block_builder().builder().as_mut_ptr();
+
+                // Enums matched through references are loaded first
+                let mut value = self.get_var_value(var).as_ptr();
+                if self.values[&var].1.is_reference() {
+                    value = llvm_sys::core::LLVMBuildLoad(builder, value, EMPTY_CSTR);
+                }
+
+                let tag = RawLLVMValue::from_raw(llvm_sys::core::LLVMBuildExtractValue(
+                    builder, value, 0, EMPTY_CSTR,
+                ))?;
+
+                Ok(LLVMValue::new(tag, ty))
+            },
+
+            // Payloads can be any of the variants' layouts, so they're reinterpreted
+            // through memory
+            &Value::Payload {
+                var,
+                discriminant,
+                index,
+            } => unsafe {
+                let builder = self.get_block_builder().builder().as_mut_ptr();
+
+                // Enums behind references are already in memory
+                let (enum_ty, slot) = match self.values[&var].1.clone() {
+                    Type::Reference { referee, .. } => {
+                        ((*referee).clone(), self.get_var_value(var).as_ptr())
+                    }
+
+                    enum_ty => {
+                        let enum_type = self.visit_type(&enum_ty)?;
+                        let slot = self.entry_alloca(enum_type.as_mut_ptr());
+                        llvm_sys::core::LLVMBuildStore(
+                            builder,
+                            self.get_var_value(var).as_ptr(),
+                            slot,
+                        );
+
+                        (enum_ty, slot)
+                    }
+                };
+                let variants = enum_ty.enum_variants().expect("Only enums have payloads");
+                let payload_type = self.payload_type(&variants[discriminant as usize])?;
+
+                let fields = self.payload_ptr(slot, payload_type);
+                let field =
+                    llvm_sys::core::LLVMBuildStructGEP(builder, fields, index as u32, EMPTY_CSTR);
+                let element = llvm_sys::core::LLVMBuildLoad(builder, field, EMPTY_CSTR);
+
+                Ok(LLVMValue::new(RawLLVMValue::from_raw(element)?, ty))
+            },
         }
     }
 
@@ -832,18 +1206,23 @@ impl<'db> MirVisitor for CodeGenerator<'db> {
             Constant::String(string) => {
                 assert!(ty.is_string());
 
-                let string = ArrayValue::const_string(self.module.context(), string, false)?;
+                // Strings are passed around as pointers to their first byte, so they're
+                // null terminated to allow the runtime to find their end
+                let string = ArrayValue::const_string(self.module.context(), string, true)?;
                 let string_type: ArrayType<'db> = string.as_type()?.try_into()?;
-                println!(
-                    "Made a constant string, LLVM typed it as {:?} while the type's value is {:?}",
-                    string_type,
-                    self.visit_type(ty).unwrap(),
-                );
 
-                self.module
+                let global = self
+                    .module
                     .add_global(string_type, None, "")?
                     .with_initializer(string.as_value())
-                    .as_value()
+                    .as_value();
+
+                unsafe {
+                    RawLLVMValue::from_raw(llvm_sys::core::LLVMConstBitCast(
+                        global.as_mut_ptr(),
+                        self.visit_type(ty)?.as_mut_ptr(),
+                    ))?
+                }
             }
 
             Constant::Array(array) => {
@@ -879,7 +1258,7 @@ impl<'db> MirVisitor for CodeGenerator<'db> {
         let ty = match ty {
             Type::Bool   => IntType::i1(&self.context)?.into(),
             Type::Unit   => VoidType::new(&self.context)?.into(),
-            Type::String => IntType::i8(&self.context)?.into(),
+            Type::String => IntType::i8(&self.context)?.make_pointer(AddressSpace::Generic)?.into(),
             Type::Absurd => VoidType::new(&self.context)?.into(), // TODO: ???
             Type::U8     => IntType::u8(&self.context)?.into(),
             Type::I8     => IntType::i8(&self.context)?.into(),
@@ -901,6 +1280,8 @@ impl<'db> MirVisitor for CodeGenerator<'db> {
                 .visit_type(referee)?
                 .make_pointer(AddressSpace::Generic)?
                 .into(),
+            Type::Tuple(elements) => self.payload_type(elements)?,
+            Type::Enum { variants } => self.enum_type(variants)?,
         };
 
         Ok(ty)
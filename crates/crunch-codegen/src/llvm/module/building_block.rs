@@ -20,15 +20,18 @@ use core::{
 };
 use llvm_sys::{
     core::{
-        LLVMAddCase, LLVMBuildAdd, LLVMBuildBitCast, LLVMBuildBr, LLVMBuildCall2,
-        LLVMBuildExactSDiv, LLVMBuildExactUDiv, LLVMBuildFDiv, LLVMBuildGlobalString,
-        LLVMBuildGlobalStringPtr, LLVMBuildICmp, LLVMBuildMul, LLVMBuildNSWAdd, LLVMBuildNSWMul,
-        LLVMBuildNSWSub, LLVMBuildNUWAdd, LLVMBuildNUWMul, LLVMBuildNUWSub, LLVMBuildPointerCast,
-        LLVMBuildRet, LLVMBuildRetVoid, LLVMBuildSDiv, LLVMBuildSub, LLVMBuildSwitch,
-        LLVMBuildUDiv, LLVMBuildUnreachable, LLVMConstAdd, LLVMConstExactSDiv, LLVMConstExactUDiv,
-        LLVMConstICmp, LLVMConstMul, LLVMConstNSWAdd, LLVMConstNSWMul, LLVMConstNSWSub,
-        LLVMConstNUWAdd, LLVMConstNUWMul, LLVMConstNUWSub, LLVMConstSDiv, LLVMConstSub,
-        LLVMConstUDiv,
+        LLVMAddCase, LLVMBuildAShr, LLVMBuildAdd, LLVMBuildAnd, LLVMBuildBitCast, LLVMBuildBr,
+        LLVMBuildCall2, LLVMBuildExactSDiv, LLVMBuildExactUDiv, LLVMBuildFDiv,
+        LLVMBuildGlobalString, LLVMBuildGlobalStringPtr, LLVMBuildICmp, LLVMBuildLShr,
+        LLVMBuildMul, LLVMBuildNSWAdd, LLVMBuildNSWMul, LLVMBuildNSWSub, LLVMBuildNUWAdd,
+        LLVMBuildNUWMul, LLVMBuildNUWSub, LLVMBuildOr, LLVMBuildPointerCast, LLVMBuildRet,
+        LLVMBuildRetVoid, LLVMBuildSDiv, LLVMBuildSRem, LLVMBuildShl, LLVMBuildSub,
+        LLVMBuildSwitch, LLVMBuildUDiv, LLVMBuildURem, LLVMBuildUnreachable, LLVMBuildXor,
+        LLVMConstAShr, LLVMConstAdd, LLVMConstAnd, LLVMConstExactSDiv, LLVMConstExactUDiv,
+        LLVMConstICmp, LLVMConstLShr, LLVMConstMul, LLVMConstNSWAdd, LLVMConstNSWMul,
+        LLVMConstNSWSub, LLVMConstNUWAdd, LLVMConstNUWMul, LLVMConstNUWSub, LLVMConstOr,
+        LLVMConstSDiv, LLVMConstSRem, LLVMConstShl, LLVMConstSub, LLVMConstUDiv, LLVMConstURem,
+        LLVMConstXor,
     },
     LLVMValue,
 };
@@ -391,6 +394,222 @@ impl<'ctx> BuildingBlock<'ctx> {
         Ok(mult)
     }
 
+    /// Get the remainder of dividing two unsigned integers, returning the result
+    ///
+    /// Note: If both provided arguments are constants, then the `*ConstURem` function will be used
+    ///
+    /// [Docs](https://llvm.org/docs/LangRef.html#urem-instruction)
+    pub fn urem<Left, Right>(&self, lhs: Left, rhs: Right) -> Result<Value<'ctx>>
+    where
+        Left: Into<Value<'ctx>>,
+        Right: Into<Value<'ctx>>,
+    {
+        let (lhs, rhs) = (lhs.into(), rhs.into());
+        let is_const = lhs.is_const() && rhs.is_const();
+
+        unsafe {
+            let (lhs, rhs) = (lhs.as_mut_ptr(), rhs.as_mut_ptr());
+            let builder = self.builder.as_mut_ptr();
+
+            let urem = if is_const {
+                LLVMConstURem(lhs, rhs)
+            } else {
+                LLVMBuildURem(builder, lhs, rhs, EMPTY_CSTR)
+            };
+
+            Value::from_raw(urem)
+        }
+    }
+
+    /// Get the remainder of dividing two signed integers, which takes the sign of `lhs`, returning the result
+    ///
+    /// Note: If both provided arguments are constants, then the `*ConstSRem` function will be used
+    ///
+    /// [Docs](https://llvm.org/docs/LangRef.html#srem-instruction)
+    pub fn srem<Left, Right>(&self, lhs: Left, rhs: Right) -> Result<Value<'ctx>>
+    where
+        Left: Into<Value<'ctx>>,
+        Right: Into<Value<'ctx>>,
+    {
+        let (lhs, rhs) = (lhs.into(), rhs.into());
+        let is_const = lhs.is_const() && rhs.is_const();
+
+        unsafe {
+            let (lhs, rhs) = (lhs.as_mut_ptr(), rhs.as_mut_ptr());
+            let builder = self.builder.as_mut_ptr();
+
+            let srem = if is_const {
+                LLVMConstSRem(lhs, rhs)
+            } else {
+                LLVMBuildSRem(builder, lhs, rhs, EMPTY_CSTR)
+            };
+
+            Value::from_raw(srem)
+        }
+    }
+
+    /// Get the bitwise and of two integers, returning the result
+    ///
+    /// Note: If both provided arguments are constants, then the `*ConstAnd` function will be used
+    ///
+    /// [Docs](https://llvm.org/docs/LangRef.html#and-instruction)
+    pub fn and<Left, Right>(&self, lhs: Left, rhs: Right) -> Result<Value<'ctx>>
+    where
+        Left: Into<Value<'ctx>>,
+        Right: Into<Value<'ctx>>,
+    {
+        let (lhs, rhs) = (lhs.into(), rhs.into());
+        let is_const = lhs.is_const() && rhs.is_const();
+
+        unsafe {
+            let (lhs, rhs) = (lhs.as_mut_ptr(), rhs.as_mut_ptr());
+            let builder = self.builder.as_mut_ptr();
+
+            let and = if is_const {
+                LLVMConstAnd(lhs, rhs)
+            } else {
+                LLVMBuildAnd(builder, lhs, rhs, EMPTY_CSTR)
+            };
+
+            Value::from_raw(and)
+        }
+    }
+
+    /// Get the bitwise or of two integers, returning the result
+    ///
+    /// Note: If both provided arguments are constants, then the `*ConstOr` function will be used
+    ///
+    /// [Docs](https://llvm.org/docs/LangRef.html#or-instruction)
+    pub fn or<Left, Right>(&self, lhs: Left, rhs: Right) -> Result<Value<'ctx>>
+    where
+        Left: Into<Value<'ctx>>,
+        Right: Into<Value<'ctx>>,
+    {
+        let (lhs, rhs) = (lhs.into(), rhs.into());
+        let is_const = lhs.is_const() && rhs.is_const();
+
+        unsafe {
+            let (lhs, rhs) = (lhs.as_mut_ptr(), rhs.as_mut_ptr());
+            let builder = self.builder.as_mut_ptr();
+
+            let or = if is_const {
+                LLVMConstOr(lhs, rhs)
+            } else {
+                LLVMBuildOr(builder, lhs, rhs, EMPTY_CSTR)
+            };
+
+            Value::from_raw(or)
+        }
+    }
+
+    /// Get the bitwise xor of two integers, returning the result
+    ///
+    /// Note: If both provided arguments are constants, then the `*ConstXor` function will be used
+    ///
+    /// [Docs](https://llvm.org/docs/LangRef.html#xor-instruction)
+    pub fn xor<Left, Right>(&self, lhs: Left, rhs: Right) -> Result<Value<'ctx>>
+    where
+        Left: Into<Value<'ctx>>,
+        Right: Into<Value<'ctx>>,
+    {
+        let (lhs, rhs) = (lhs.into(), rhs.into());
+        let is_const = lhs.is_const() && rhs.is_const();
+
+        unsafe {
+            let (lhs, rhs) = (lhs.as_mut_ptr(), rhs.as_mut_ptr());
+            let builder = self.builder.as_mut_ptr();
+
+            let xor = if is_const {
+                LLVMConstXor(lhs, rhs)
+            } else {
+                LLVMBuildXor(builder, lhs, rhs, EMPTY_CSTR)
+            };
+
+            Value::from_raw(xor)
+        }
+    }
+
+    /// Shift an integer left by `rhs` bits, returning the result
+    ///
+    /// Note: If both provided arguments are constants, then the `*ConstShl` function will be used
+    ///
+    /// [Docs](https://llvm.org/docs/LangRef.html#shl-instruction)
+    pub fn shl<Left, Right>(&self, lhs: Left, rhs: Right) -> Result<Value<'ctx>>
+    where
+        Left: Into<Value<'ctx>>,
+        Right: Into<Value<'ctx>>,
+    {
+        let (lhs, rhs) = (lhs.into(), rhs.into());
+        let is_const = lhs.is_const() && rhs.is_const();
+
+        unsafe {
+            let (lhs, rhs) = (lhs.as_mut_ptr(), rhs.as_mut_ptr());
+            let builder = self.builder.as_mut_ptr();
+
+            let shl = if is_const {
+                LLVMConstShl(lhs, rhs)
+            } else {
+                LLVMBuildShl(builder, lhs, rhs, EMPTY_CSTR)
+            };
+
+            Value::from_raw(shl)
+        }
+    }
+
+    /// Shift an integer right by `rhs` bits, filling the vacated bits with zeros, returning the result
+    ///
+    /// Note: If both provided arguments are constants, then the `*ConstLShr` function will be used
+    ///
+    /// [Docs](https://llvm.org/docs/LangRef.html#lshr-instruction)
+    pub fn lshr<Left, Right>(&self, lhs: Left, rhs: Right) -> Result<Value<'ctx>>
+    where
+        Left: Into<Value<'ctx>>,
+        Right: Into<Value<'ctx>>,
+    {
+        let (lhs, rhs) = (lhs.into(), rhs.into());
+        let is_const = lhs.is_const() && rhs.is_const();
+
+        unsafe {
+            let (lhs, rhs) = (lhs.as_mut_ptr(), rhs.as_mut_ptr());
+            let builder = self.builder.as_mut_ptr();
+
+            let lshr = if is_const {
+                LLVMConstLShr(lhs, rhs)
+            } else {
+                LLVMBuildLShr(builder, lhs, rhs, EMPTY_CSTR)
+            };
+
+            Value::from_raw(lshr)
+        }
+    }
+
+    /// Shift an integer right by `rhs` bits, filling the vacated bits with its sign bit, returning the result
+    ///
+    /// Note: If both provided arguments are constants, then the `*ConstAShr` function will be used
+    ///
+    /// [Docs](https://llvm.org/docs/LangRef.html#ashr-instruction)
+    pub fn ashr<Left, Right>(&self, lhs: Left, rhs: Right) -> Result<Value<'ctx>>
+    where
+        Left: Into<Value<'ctx>>,
+        Right: Into<Value<'ctx>>,
+    {
+        let (lhs, rhs) = (lhs.into(), rhs.into());
+        let is_const = lhs.is_const() && rhs.is_const();
+
+        unsafe {
+            let (lhs, rhs) = (lhs.as_mut_ptr(), rhs.as_mut_ptr());
+            let builder = self.builder.as_mut_ptr();
+
+            let ashr = if is_const {
+                LLVMConstAShr(lhs, rhs)
+            } else {
+                LLVMBuildAShr(builder, lhs, rhs, EMPTY_CSTR)
+            };
+
+            Value::from_raw(ashr)
+        }
+    }
+
     /// Return a value from the current function
     ///
     /// If a value is provided, that will be returned. If the value is `None`,
@@ -1,18 +1,20 @@
 use crate::llvm::{
     error::ErrorString,
     module::Module,
+    types::Type,
     utils::{to_non_nul, LLVMString},
     Error, ErrorKind, Result,
 };
 use llvm_sys::{
+    core::LLVMSetTarget,
     target::{
-        LLVMCreateTargetData, LLVMDisposeTargetData, LLVMOpaqueTargetData,
-        LLVM_InitializeNativeTarget,
+        LLVMABISizeOfType, LLVMCreateTargetData, LLVMDisposeTargetData, LLVMOpaqueTargetData,
+        LLVMSetModuleDataLayout, LLVM_InitializeNativeTarget,
     },
     target_machine::{
-        LLVMCodeGenFileType, LLVMCodeGenOptLevel, LLVMCodeModel, LLVMCreateTargetMachine,
-        LLVMDisposeTargetMachine, LLVMGetDefaultTargetTriple, LLVMGetFirstTarget,
-        LLVMGetHostCPUFeatures, LLVMGetHostCPUName, LLVMGetTargetFromTriple,
+        LLVMCodeGenFileType, LLVMCodeGenOptLevel, LLVMCodeModel, LLVMCreateTargetDataLayout,
+        LLVMCreateTargetMachine, LLVMDisposeTargetMachine, LLVMGetDefaultTargetTriple,
+        LLVMGetFirstTarget, LLVMGetHostCPUFeatures, LLVMGetHostCPUName, LLVMGetTargetFromTriple,
         LLVMGetTargetMachineCPU, LLVMGetTargetMachineFeatureString, LLVMGetTargetMachineTarget,
         LLVMGetTargetMachineTriple, LLVMOpaqueTargetMachine, LLVMRelocMode, LLVMTarget,
         LLVMTargetMachineEmitToFile,
@@ -163,16 +165,36 @@ pub struct TargetData {
 }
 
 impl TargetData {
-    pub fn new(triple: &str) -> Result<Self> {
+    /// Creates target data from an LLVM data layout string
+    pub fn new(layout: &str) -> Result<Self> {
         unsafe {
             Ok(Self {
                 target_data: to_non_nul(
-                    LLVMCreateTargetData(CString::new(triple)?.as_ptr()),
+                    LLVMCreateTargetData(CString::new(layout)?.as_ptr()),
                     "Failed to create LLVM target data",
                 )?,
             })
         }
     }
+
+    /// The number of bytes a value of the given type takes up in memory
+    pub fn abi_size_of(&self, ty: Type<'_>) -> u64 {
+        unsafe { LLVMABISizeOfType(self.target_data.as_ptr(), ty.as_mut_ptr()) }
+    }
+}
+
+impl TargetData {
+    #[inline]
+    pub(crate) unsafe fn from_raw(raw: *mut LLVMOpaqueTargetData) -> Result<Self> {
+        let target_data = to_non_nul(raw, "Failed to create LLVM target data")?;
+
+        Ok(Self { target_data })
+    }
+
+    #[inline]
+    pub(crate) const fn as_mut_ptr(&self) -> *mut LLVMOpaqueTargetData {
+        self.target_data.as_ptr()
+    }
 }
 
 impl Drop for TargetData {
@@ -181,7 +203,7 @@ impl Drop for TargetData {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct TargetMachine {
     machine: NonNull<LLVMOpaqueTargetMachine>,
 }
@@ -258,6 +280,26 @@ impl TargetMachine {
         }
     }
 
+    /// The data layout of the machine's target
+    #[inline]
+    pub fn target_data(&self) -> Result<TargetData> {
+        unsafe { TargetData::from_raw(LLVMCreateTargetDataLayout(self.as_mut_ptr())) }
+    }
+
+    /// Sets the module's target triple and data layout to the machine's, which the module's
+    /// types must be laid out with before it can be emitted by the machine
+    #[inline]
+    pub fn configure_module(&self, module: &Module<'_>) -> Result<()> {
+        let target_data = self.target_data()?;
+
+        unsafe {
+            LLVMSetTarget(module.as_mut_ptr(), self.target_triple()?.as_ptr());
+            LLVMSetModuleDataLayout(module.as_mut_ptr(), target_data.as_mut_ptr());
+        }
+
+        Ok(())
+    }
+
     #[inline]
     pub fn target(&self) -> Result<Target> {
         unsafe { Target::from_raw(LLVMGetTargetMachineTarget(self.as_mut_ptr())) }
@@ -0,0 +1,40 @@
+// Runtime support for exponentiation, `base ** exponent`
+//
+// Every integer width is widened to 64 bits before calling `crunch_pow_i64()` or
+// `crunch_pow_u64()`, and overflow wraps around just like multiplication does
+
+#include <stdint.h>
+
+static uint64_t crunch_pow_wrapping(uint64_t base, uint64_t exponent) {
+    uint64_t result = 1;
+
+    while (exponent != 0) {
+        if (exponent & 1) {
+            result *= base;
+        }
+
+        base *= base;
+        exponent >>= 1;
+    }
+
+    return result;
+}
+
+int64_t crunch_pow_i64(int64_t base, int64_t exponent) {
+    // Negative exponents truncate towards zero unless the base's magnitude is one
+    if (exponent < 0) {
+        if (base == 1) {
+            return 1;
+        } else if (base == -1) {
+            return (exponent & 1) ? -1 : 1;
+        } else {
+            return 0;
+        }
+    }
+
+    return (int64_t)crunch_pow_wrapping((uint64_t)base, (uint64_t)exponent);
+}
+
+uint64_t crunch_pow_u64(uint64_t base, uint64_t exponent) {
+    return crunch_pow_wrapping(base, exponent);
+}
@@ -0,0 +1,40 @@
+// Runtime support for indexing arrays and slices, `array[index]`
+//
+// Indices that aren't known at compile time are checked with `crunch_check_bounds()`
+// before they're used, which stops the program if the index is out of bounds
+
+#include <inttypes.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+void crunch_check_bounds(uint64_t index, uint64_t length) {
+    if (index < length) {
+        return;
+    }
+
+    fprintf(
+        stderr,
+        "index out of bounds: the length is %" PRIu64 " but the index is %" PRIu64 "\n",
+        length,
+        index
+    );
+    exit(101);
+}
+
+// Slices taken with a range are checked with `crunch_check_slice()`, which stops the program
+// if the range ends before it starts or goes past the end of the sliced value
+void crunch_check_slice(uint64_t start, uint64_t end, uint64_t length) {
+    if (start <= end && end <= length) {
+        return;
+    }
+
+    fprintf(
+        stderr,
+        "range out of bounds: the length is %" PRIu64 " but the range is %" PRIu64 "..%" PRIu64 "\n",
+        length,
+        start,
+        end
+    );
+    exit(101);
+}
@@ -0,0 +1,143 @@
+// Runtime support for interpolated strings, `"Hello, {name}!"`
+//
+// Each interpolated string creates a formatter with `crunch_fmt_new()`, pushes each of
+// its pieces onto it and then calls `crunch_fmt_finish()` to get the final null
+// terminated string.
+//
+// Finishing a formatter hands its string over to the caller, which frees it with
+// `crunch_fmt_free()` once it's done with it
+
+#include <inttypes.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef struct crunch_formatter {
+    char *data;
+    size_t length;
+    size_t capacity;
+} crunch_formatter;
+
+static void crunch_fmt_reserve(crunch_formatter *fmt, size_t additional) {
+    size_t required = fmt->length + additional + 1;
+    if (required <= fmt->capacity) {
+        return;
+    }
+
+    size_t capacity = fmt->capacity * 2;
+    if (capacity < required) {
+        capacity = required;
+    }
+
+    fmt->data = realloc(fmt->data, capacity);
+    if (fmt->data == NULL) {
+        abort();
+    }
+    fmt->capacity = capacity;
+}
+
+static void crunch_fmt_push_bytes(crunch_formatter *fmt, const char *bytes, size_t length) {
+    crunch_fmt_reserve(fmt, length);
+    memcpy(fmt->data + fmt->length, bytes, length);
+
+    fmt->length += length;
+    fmt->data[fmt->length] = '\0';
+}
+
+crunch_formatter *crunch_fmt_new(void) {
+    crunch_formatter *fmt = malloc(sizeof(crunch_formatter));
+    if (fmt == NULL) {
+        abort();
+    }
+
+    fmt->data = NULL;
+    fmt->length = 0;
+    fmt->capacity = 0;
+    crunch_fmt_reserve(fmt, 16);
+    fmt->data[0] = '\0';
+
+    return fmt;
+}
+
+void crunch_fmt_push_str(crunch_formatter *fmt, const char *string) {
+    crunch_fmt_push_bytes(fmt, string, strlen(string));
+}
+
+void crunch_fmt_display_str(crunch_formatter *fmt, const char *string) {
+    crunch_fmt_push_str(fmt, string);
+}
+
+void crunch_fmt_debug_str(crunch_formatter *fmt, const char *string) {
+    crunch_fmt_push_bytes(fmt, "\"", 1);
+
+    for (const char *c = string; *c != '\0'; c++) {
+        switch (*c) {
+            case '"':  crunch_fmt_push_bytes(fmt, "\\\"", 2); break;
+            case '\\': crunch_fmt_push_bytes(fmt, "\\\\", 2); break;
+            case '\n': crunch_fmt_push_bytes(fmt, "\\n", 2); break;
+            case '\r': crunch_fmt_push_bytes(fmt, "\\r", 2); break;
+            case '\t': crunch_fmt_push_bytes(fmt, "\\t", 2); break;
+
+            default: {
+                unsigned char byte = (unsigned char)*c;
+
+                if (byte < 0x20 || byte == 0x7F) {
+                    char escape[7];
+                    int length = snprintf(escape, sizeof(escape), "\\x{%02X}", byte);
+                    crunch_fmt_push_bytes(fmt, escape, (size_t)length);
+                } else {
+                    crunch_fmt_push_bytes(fmt, c, 1);
+                }
+            }
+        }
+    }
+
+    crunch_fmt_push_bytes(fmt, "\"", 1);
+}
+
+void crunch_fmt_display_bool(crunch_formatter *fmt, bool boolean) {
+    if (boolean) {
+        crunch_fmt_push_bytes(fmt, "true", 4);
+    } else {
+        crunch_fmt_push_bytes(fmt, "false", 5);
+    }
+}
+
+// Debug formatting writes values the way they're written in source, which for booleans
+// and integers is the same as how they're displayed
+void crunch_fmt_debug_bool(crunch_formatter *fmt, bool boolean) {
+    crunch_fmt_display_bool(fmt, boolean);
+}
+
+#define CRUNCH_FMT_INTEGER(name, type, format)                              \
+    void crunch_fmt_display_##name(crunch_formatter *fmt, type value) {     \
+        char buffer[24];                                                    \
+        int length = snprintf(buffer, sizeof(buffer), "%" format, value);   \
+        crunch_fmt_push_bytes(fmt, buffer, (size_t)length);                 \
+    }                                                                       \
+                                                                            \
+    void crunch_fmt_debug_##name(crunch_formatter *fmt, type value) {       \
+        crunch_fmt_display_##name(fmt, value);                              \
+    }
+
+CRUNCH_FMT_INTEGER(u8, uint8_t, PRIu8)
+CRUNCH_FMT_INTEGER(i8, int8_t, PRId8)
+CRUNCH_FMT_INTEGER(u16, uint16_t, PRIu16)
+CRUNCH_FMT_INTEGER(i16, int16_t, PRId16)
+CRUNCH_FMT_INTEGER(u32, uint32_t, PRIu32)
+CRUNCH_FMT_INTEGER(i32, int32_t, PRId32)
+CRUNCH_FMT_INTEGER(u64, uint64_t, PRIu64)
+CRUNCH_FMT_INTEGER(i64, int64_t, PRId64)
+
+const char *crunch_fmt_finish(crunch_formatter *fmt) {
+    char *string = fmt->data;
+    free(fmt);
+
+    return string;
+}
+
+void crunch_fmt_free(const char *string) {
+    free((char *)string);
+}
@@ -1,11 +1,16 @@
-use crunch_codegen::llvm::target_machine::{CodegenFileKind, Target, TargetConf, TargetMachine};
-use crunch_database::{CodegenDatabase, ConfigDatabase, CrunchDatabase, SourceDatabase};
+use crunch_codegen::llvm::target_machine::CodegenFileKind;
+use crunch_database::{
+    CodegenDatabase, ConfigDatabase, CrunchDatabase, HirDatabase, ParseDatabase, SourceDatabase,
+    TypecheckDatabase,
+};
 use crunch_shared::{
     allocator::{CrunchcAllocator, CRUNCHC_ALLOCATOR},
     codespan_reporting::term::{termcolor::StandardStream, Config as TermConfig},
     config::{BuildOptions, CrunchcOpts, EmissionKind, TermColor},
-    context::{Arenas, Context, ContextDatabase, OwnedArenas},
-    files::FileCache,
+    context::{Arenas, Context, ContextDatabase, OwnedArenas, Snapshot},
+    error::{Edit, ErrorHandler},
+    files::{FileCache, FileId},
+    trees::printer::PrettyPrint,
     utils::DbgWrap,
 };
 use std::{
@@ -19,6 +24,14 @@ use std::{
 #[global_allocator]
 static GLOBAL_ALLOCATOR: CrunchcAllocator = CRUNCHC_ALLOCATOR;
 
+/// The runtime's source code, compiled alongside every program
+// TODO: Precompile the runtime instead of building it during every link
+static RUNTIME_SOURCE: &str = concat!(
+    include_str!("../runtime/fmt.c"),
+    include_str!("../runtime/bounds.c"),
+    include_str!("../runtime/arith.c"),
+);
+
 fn main() {
     let code = {
         let args = CrunchcOpts::from_args();
@@ -92,6 +105,10 @@ fn run<'ctx>(
 ) -> Result<ExitStatus, ExitStatus> {
     let start_time = Instant::now();
 
+    if let CrunchcOpts::Inspect { .. } = args {
+        return inspect(&options);
+    }
+
     let writer = StandardStream::stderr(options.color.into());
     let stdout_conf = TermConfig::default();
 
@@ -107,7 +124,9 @@ fn run<'ctx>(
         })?
         .to_string_lossy();
     let out_file = options.out_dir.join(source_file.as_ref());
-    stderr.write(|| format!("Compiling '{}.crunch'\n", &source_file));
+    if !matches!(args, CrunchcOpts::Fix { .. }) {
+        stderr.write(|| format!("Compiling '{}.crunch'\n", &source_file));
+    }
 
     // Check that the given file has the `.crunch` extension
     {
@@ -153,6 +172,31 @@ fn run<'ctx>(
         core::mem::transmute::<&'ctx Context<'ctx>, &'static Context<'static>>(context)
     });
     database.set_file_path(file_id, Arc::new(options.target_file.clone()));
+    database.set_file_edit(file_id, None);
+
+    if let CrunchcOpts::Fix { .. } = args {
+        return fix(&database, file_id, &options, &writer, &stdout_conf);
+    }
+
+    if options.emit.contains(&EmissionKind::Snapshot) {
+        // Files that can't be lowered don't get a snapshot, their errors are reported below
+        if let Some(snapshot) = take_snapshot(&database, file_id) {
+            let path = out_file.with_extension("snapshot");
+
+            fs::File::create(&path)
+                .map_err(serde_json::Error::io)
+                .and_then(|file| {
+                    save_snapshot(database.context(), &snapshot, io::BufWriter::new(file))
+                })
+                .map_err(|err| {
+                    ExitStatus::message(format!(
+                        "encountered an error while saving a snapshot to '{}': {:?}",
+                        path.display(),
+                        err
+                    ))
+                })?;
+        }
+    }
 
     // Check types and update the hir with concrete types
     let module = match database.generate_module(file_id) {
@@ -166,11 +210,8 @@ fn run<'ctx>(
         }
     };
 
-    // TODO: User input for all of this
-    // FIXME: This is really funky with initializing and may not even work correctly
-
-    Target::init_native(TargetConf::all()).unwrap();
-    let target_machine = TargetMachine::default();
+    // The module is emitted by the machine it was laid out for
+    let target_machine = module.target_machine();
 
     // Emit to an object file so we can link it
     let object_file = out_file.with_extension("o");
@@ -219,10 +260,20 @@ fn run<'ctx>(
         out_file
     };
 
+    let runtime_file = out_file.with_extension("runtime.c");
+    fs::write(&runtime_file, RUNTIME_SOURCE).map_err(|err| {
+        ExitStatus::message(format!(
+            "encountered an error while writing the runtime to '{}': {:?}",
+            runtime_file.display(),
+            err
+        ))
+    })?;
+
     // TODO: Use `cc` to get the relevant linkers
     GLOBAL_ALLOCATOR.record_region("linking", || {
         std::process::Command::new("clang")
             .arg(&object_file)
+            .arg(&runtime_file)
             .arg("-o")
             .arg(&exe_path)
             .spawn()
@@ -260,6 +311,115 @@ fn run<'ctx>(
     Ok(ExitStatus::default())
 }
 
+/// Applies every fix that was suggested while checking the file and can be applied
+/// automatically, then emits whatever errors are left over
+fn fix(
+    database: &CrunchDatabase,
+    file: FileId,
+    options: &BuildOptions,
+    writer: &StandardStream,
+    stdout_conf: &TermConfig,
+) -> Result<ExitStatus, ExitStatus> {
+    let errors = match database.typecheck(file) {
+        Ok(()) => return Ok(ExitStatus::message("Found nothing to fix")),
+        Err(errors) => errors,
+    };
+
+    let (mut edits, mut remaining) = (Vec::new(), ErrorHandler::new());
+    for err in errors.errors() {
+        match err
+            .suggestion()
+            .filter(|suggestion| suggestion.is_applicable())
+        {
+            Some(suggestion) => edits.extend(
+                suggestion
+                    .edits()
+                    .iter()
+                    .filter(|edit| edit.location.file() == file),
+            ),
+            None => remaining.push_err(err.clone()),
+        }
+    }
+
+    let (fixed, applied) = Edit::apply_all(&database.source_text(file), edits);
+    if applied != 0 {
+        fs::write(&options.target_file, fixed).map_err(|err| {
+            ExitStatus::message(format!(
+                "failed to write the fixed source to '{}': {:?}",
+                options.target_file.display(),
+                err,
+            ))
+        })?;
+    }
+
+    let message = format!(
+        "Applied {} fix{} to '{}'",
+        applied,
+        if applied == 1 { "" } else { "es" },
+        options.target_file.display(),
+    );
+
+    if remaining.is_fatal() {
+        remaining.emit(&FileCache::upcast(database), writer, stdout_conf);
+        Ok(ExitStatus::new(message, 101))
+    } else {
+        Ok(ExitStatus::message(message))
+    }
+}
+
+/// The file's AST and HIR, or `None` if the file couldn't be lowered to HIR
+fn take_snapshot(database: &CrunchDatabase, file: FileId) -> Option<Snapshot<'static>> {
+    let ast = database.parse(file).ok()?;
+    let hir = database.lower_hir(file).ok()?;
+
+    Some(Snapshot {
+        ast: ast.to_vec(),
+        hir: hir.to_vec(),
+    })
+}
+
+fn save_snapshot<'ctx, W: Write>(
+    context: &Context<'ctx>,
+    snapshot: &Snapshot<'ctx>,
+    writer: W,
+) -> serde_json::Result<()> {
+    context.save(snapshot, &mut serde_json::Serializer::new(writer))
+}
+
+fn load_snapshot<'ctx, R: io::Read>(
+    arenas: Arenas<'ctx>,
+    reader: R,
+) -> serde_json::Result<(Context<'ctx>, Snapshot<'ctx>)> {
+    Context::load(arenas, &mut serde_json::Deserializer::from_reader(reader))
+}
+
+/// Loads a snapshot saved with `--emit snapshot` and prints the trees within it, the AST as
+/// source code and the HIR the same way `--print hir` does
+fn inspect(options: &BuildOptions) -> Result<ExitStatus, ExitStatus> {
+    let file = fs::File::open(&options.target_file).map_err(|err| {
+        ExitStatus::message(format!(
+            "failed to open the snapshot '{}': {:?}",
+            options.target_file.display(),
+            err,
+        ))
+    })?;
+
+    let owned_arenas = OwnedArenas::default();
+    let (context, snapshot) = load_snapshot(Arenas::from(&owned_arenas), io::BufReader::new(file))
+        .map_err(|err| {
+            ExitStatus::message(format!(
+                "failed to load the snapshot '{}': {:?}",
+                options.target_file.display(),
+                err,
+            ))
+        })?;
+
+    println!("{}", snapshot.ast[..].display(context.strings()));
+    println!("{:#?}", snapshot.hir);
+
+    Ok(ExitStatus::default())
+}
+
 struct Stderr {
     stderr: Option<io::Stderr>,
 }
@@ -313,3 +473,57 @@ impl ExitStatus {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crunch_shared::testing;
+
+    #[test]
+    fn snapshot_round_trip() {
+        let source = "
+            enum Shape
+                Square(i32)
+                Rect(i32, i32)
+            end
+
+            fn area(shape: Shape) -> i32
+                return match shape
+                    Shape.Square(side) =>
+                        side * side
+                    end
+                    Shape.Rect(width, height) =>
+                        width * height
+                    end
+                end
+            end
+
+            fn main() -> i32
+                return area(Shape.Rect(2, 3))
+            end
+        ";
+
+        testing::with_database(source, |database: &mut CrunchDatabase, file| {
+            database.set_file_edit(file, None);
+
+            let snapshot = take_snapshot(database, file).unwrap();
+            let mut saved = Vec::new();
+            save_snapshot(database.context(), &snapshot, &mut saved).unwrap();
+
+            let owned_arenas = OwnedArenas::default();
+            let (loaded, loaded_snapshot) =
+                load_snapshot(Arenas::from(&owned_arenas), &saved[..]).unwrap();
+            assert_eq!(loaded_snapshot, snapshot);
+
+            // Strings are resolved through the loaded context, so they have to print the same
+            assert_eq!(
+                loaded_snapshot.ast[..]
+                    .display(loaded.strings())
+                    .to_string(),
+                snapshot.ast[..]
+                    .display(database.context().strings())
+                    .to_string(),
+            );
+        });
+    }
+}
@@ -1 +1,4 @@
-RUSTFLAGS="-Clink-arg=-fuse-ld=gold" HFUZZ_RUN_ARGS="--linux_perf_instr --timeout 1 --threads 15 --max_file_size 2048 --linux_perf_branch --input /data/corpus --output /data/corpus --crashdir /data/crashes" cargo hfuzz run parse
\ No newline at end of file
+# Usage: ./fuzz.sh <target>, where target is one of parse, parse_generated, lower_hir, typecheck or lower_mir
+TARGET="${1:-parse}"
+
+RUSTFLAGS="-Clink-arg=-fuse-ld=gold" HFUZZ_RUN_ARGS="--linux_perf_instr --timeout 1 --threads 15 --max_file_size 2048 --linux_perf_branch --input /data/corpus/$TARGET --output /data/corpus/$TARGET --crashdir /data/crashes/$TARGET" cargo hfuzz run "$TARGET"
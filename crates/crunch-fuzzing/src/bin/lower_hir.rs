@@ -0,0 +1,17 @@
+use arbitrary::{Arbitrary, Unstructured};
+use crunch_fuzzing::Program;
+use honggfuzz::fuzz;
+use ladder::HirDatabase;
+
+fn main() {
+    loop {
+        fuzz!(|bytes: &[u8]| {
+            if let Ok(program) = Program::arbitrary(&mut Unstructured::new(bytes)) {
+                // Errors are fine, but panics aren't
+                crunch_fuzzing::with_database(program.source(), |db, file| {
+                    let _ = db.lower_hir(file);
+                });
+            }
+        });
+    }
+}
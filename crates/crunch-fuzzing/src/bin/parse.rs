@@ -0,0 +1,15 @@
+use crunch_shared::context::{Arenas, Context, OwnedArenas};
+use honggfuzz::fuzz;
+
+fn main() {
+    loop {
+        fuzz!(|bytes: &[u8]| {
+            if let Ok(source) = std::str::from_utf8(bytes) {
+                let owned_arenas = OwnedArenas::default();
+                let context = Context::new(Arenas::from(&owned_arenas));
+
+                let _ = crunch_fuzzing::parse(source, &context);
+            }
+        });
+    }
+}
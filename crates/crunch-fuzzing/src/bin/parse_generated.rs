@@ -0,0 +1,23 @@
+use arbitrary::{Arbitrary, Unstructured};
+use crunch_fuzzing::Program;
+use crunch_shared::context::{Arenas, Context, OwnedArenas};
+use honggfuzz::fuzz;
+
+fn main() {
+    loop {
+        fuzz!(|bytes: &[u8]| {
+            if let Ok(program) = Program::arbitrary(&mut Unstructured::new(bytes)) {
+                let owned_arenas = OwnedArenas::default();
+                let context = Context::new(Arenas::from(&owned_arenas));
+
+                // Every generated program is valid, so the parser should always accept them
+                if let Err(errors) = crunch_fuzzing::parse(program.source(), &context) {
+                    panic!(
+                        "failed to parse a generated program:\n{}\n{:#?}",
+                        program, errors,
+                    );
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,501 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+use std::fmt::{self, Write};
+
+/// The deepest expressions will be nested
+const MAX_EXPR_DEPTH: usize = 4;
+/// The deepest blocks will be nested
+const MAX_BLOCK_DEPTH: usize = 3;
+/// The most statements within a single block
+const MAX_STMTS: u8 = 6;
+/// The most functions within a single program
+const MAX_FUNCTIONS: u8 = 5;
+/// The most arguments a single function can take
+const MAX_ARGS: u8 = 4;
+
+/// A syntactically valid Crunch program, generated from the fuzzer's input.
+///
+/// Programs aren't always well typed, but the types of their variables and functions are tracked
+/// so that most of them make it past type checking and into the later stages of the compiler
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Program {
+    source: String,
+}
+
+impl Program {
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl Arbitrary for Program {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        Generator::new(u).program()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Ty {
+    Int,
+    Bool,
+    Unit,
+}
+
+impl Ty {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Int => "i32",
+            Self::Bool => "bool",
+            Self::Unit => "unit",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Function {
+    name: String,
+    args: Vec<Ty>,
+    ret: Ty,
+}
+
+#[derive(Debug)]
+struct Variable {
+    name: String,
+    ty: Ty,
+    mutable: bool,
+}
+
+struct Generator<'a, 'u> {
+    u: &'a mut Unstructured<'u>,
+    source: String,
+    indent: usize,
+    functions: Vec<Function>,
+    scopes: Vec<Vec<Variable>>,
+    /// The return type of the current function
+    ret: Ty,
+    /// The number of loops the current statement is within
+    loops: usize,
+    /// Used to create unique variable names
+    names: usize,
+}
+
+impl<'a, 'u> Generator<'a, 'u> {
+    fn new(u: &'a mut Unstructured<'u>) -> Self {
+        Self {
+            u,
+            source: String::new(),
+            indent: 0,
+            functions: Vec::new(),
+            scopes: Vec::new(),
+            ret: Ty::Unit,
+            loops: 0,
+            names: 0,
+        }
+    }
+
+    fn program(mut self) -> Result<Program> {
+        // Signatures are generated up front so that any function can call any other
+        self.functions.push(Function {
+            name: "main".to_owned(),
+            args: Vec::new(),
+            ret: Ty::Int,
+        });
+
+        for idx in 1..=self.u.int_in_range(0..=MAX_FUNCTIONS - 1)? {
+            let args = (0..self.u.int_in_range(0..=MAX_ARGS)?)
+                .map(|_| self.value_ty())
+                .collect::<Result<Vec<_>>>()?;
+            let ret = if self.u.arbitrary()? {
+                Ty::Unit
+            } else {
+                self.value_ty()?
+            };
+
+            self.functions.push(Function {
+                name: format!("f{}", idx),
+                args,
+                ret,
+            });
+        }
+
+        for idx in 0..self.functions.len() {
+            self.function(idx)?;
+        }
+
+        Ok(Program {
+            source: self.source,
+        })
+    }
+
+    fn function(&mut self, idx: usize) -> Result<()> {
+        let function = &self.functions[idx];
+        let args: Vec<Variable> = function
+            .args
+            .iter()
+            .enumerate()
+            .map(|(idx, &ty)| Variable {
+                name: format!("a{}", idx),
+                ty,
+                mutable: false,
+            })
+            .collect();
+
+        let mut signature = format!(
+            "fn {}({})",
+            function.name,
+            args.iter()
+                .map(|arg| format!("{}: {}", arg.name, arg.ty.name()))
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+        if function.ret != Ty::Unit {
+            write!(signature, " -> {}", function.ret.name()).unwrap();
+        }
+
+        self.ret = function.ret;
+        self.line(&signature);
+
+        self.scopes.push(args);
+        self.indent += 1;
+        for _ in 0..self.u.int_in_range(0..=MAX_STMTS)? {
+            self.stmt()?;
+        }
+
+        // Make sure functions that return values always do so
+        let ret = self.ret;
+        if ret != Ty::Unit {
+            let value = self.expr(ret, 0)?;
+            self.line(&format!("return {}", value));
+        }
+        self.indent -= 1;
+        self.scopes.pop();
+
+        self.line("end");
+        self.line("");
+
+        Ok(())
+    }
+
+    fn block(&mut self) -> Result<()> {
+        self.scopes.push(Vec::new());
+        self.indent += 1;
+
+        let max = MAX_STMTS.saturating_sub(self.indent as u8);
+        for _ in 0..self.u.int_in_range(0..=max)? {
+            self.stmt()?;
+        }
+
+        self.indent -= 1;
+        self.scopes.pop();
+
+        Ok(())
+    }
+
+    fn stmt(&mut self) -> Result<()> {
+        // The function's body is already one block deep
+        let nested = self.indent <= MAX_BLOCK_DEPTH;
+        let choices: u8 = if nested { 9 } else { 4 };
+
+        match self.u.int_in_range(0..=choices - 1)? {
+            0 => {
+                let ty = self.value_ty()?;
+                let mutable = self.u.arbitrary()?;
+                let name = self.fresh_name();
+
+                let mut decl = format!("let {}{}", if mutable { "mut " } else { "" }, name);
+                if self.u.arbitrary()? {
+                    write!(decl, ": {}", ty.name()).unwrap();
+                }
+                write!(decl, " := {}", self.expr(ty, 0)?).unwrap();
+                self.line(&decl);
+
+                self.scopes
+                    .last_mut()
+                    .expect("there's always a scope")
+                    .push(Variable { name, ty, mutable });
+            }
+
+            1 => {
+                let mutable: Vec<(String, Ty)> = self
+                    .scopes
+                    .iter()
+                    .flatten()
+                    .filter(|var| var.mutable)
+                    .map(|var| (var.name.clone(), var.ty))
+                    .collect();
+
+                if mutable.is_empty() {
+                    let call = self.call(None, 0)?;
+                    self.line(&call);
+                } else {
+                    let (name, ty) = self.u.choose(&mutable)?.clone();
+                    let op = if ty == Ty::Int {
+                        *self.u.choose(&[":=", "+=", "-=", "*=", "/="])?
+                    } else {
+                        ":="
+                    };
+                    let value = self.expr(ty, 0)?;
+
+                    self.line(&format!("{} {} {}", name, op, value));
+                }
+            }
+
+            2 => {
+                if self.ret == Ty::Unit {
+                    self.line("return");
+                } else {
+                    let value = self.expr(self.ret, 0)?;
+                    self.line(&format!("return {}", value));
+                }
+            }
+
+            3 => {
+                if self.loops != 0 && self.u.arbitrary()? {
+                    let control = *self.u.choose(&["break", "continue"])?;
+                    self.line(control);
+                } else {
+                    let call = self.call(None, 0)?;
+                    self.line(&call);
+                }
+            }
+
+            4 => {
+                let cond = self.expr(Ty::Bool, 0)?;
+                self.line(&format!("if {}", cond));
+                self.block()?;
+
+                while self.u.arbitrary()? {
+                    let cond = self.expr(Ty::Bool, 0)?;
+                    self.line(&format!("else if {}", cond));
+                    self.block()?;
+                }
+
+                if self.u.arbitrary()? {
+                    self.line("else");
+                    self.block()?;
+                }
+                self.line("end");
+            }
+
+            5 => {
+                let cond = self.expr(Ty::Bool, 0)?;
+                self.line(&format!("while {}", cond));
+                self.loop_body()?;
+            }
+
+            6 => {
+                self.line("loop");
+                self.loop_body()?;
+            }
+
+            7 => {
+                let start = self.u.int_in_range(0..=100u8)?;
+                let end = self.u.int_in_range(start..=200u8)?;
+                let name = self.fresh_name();
+
+                self.line(&format!("for {} in {}..{}", name, start, end));
+                self.scopes.push(vec![Variable {
+                    name,
+                    ty: Ty::Int,
+                    mutable: false,
+                }]);
+                self.loop_body()?;
+                self.scopes.pop();
+            }
+
+            _ => self.match_stmt()?,
+        }
+
+        Ok(())
+    }
+
+    fn loop_body(&mut self) -> Result<()> {
+        self.loops += 1;
+        self.block()?;
+        self.loops -= 1;
+
+        self.line("end");
+
+        Ok(())
+    }
+
+    fn match_stmt(&mut self) -> Result<()> {
+        let value = self.expr(Ty::Int, 0)?;
+        self.line(&format!("match {}", value));
+        self.indent += 1;
+
+        for _ in 0..self.u.int_in_range(0..=3u8)? {
+            let pattern = match self.u.int_in_range(0..=2u8)? {
+                0 => self.u.int_in_range(0..=100u8)?.to_string(),
+                1 => format!(
+                    "{} | {}",
+                    self.u.int_in_range(0..=100u8)?,
+                    self.u.int_in_range(0..=100u8)?,
+                ),
+                _ => format!(
+                    "{}..={}",
+                    self.u.int_in_range(0..=50u8)?,
+                    self.u.int_in_range(50..=100u8)?,
+                ),
+            };
+
+            self.line(&format!("{} =>", pattern));
+            self.block()?;
+            self.line("end");
+        }
+
+        // Every match ends with a catch-all arm so that it's exhaustive
+        let name = self.fresh_name();
+        self.line(&format!("{} =>", name));
+        self.scopes.push(vec![Variable {
+            name,
+            ty: Ty::Int,
+            mutable: false,
+        }]);
+        self.block()?;
+        self.scopes.pop();
+        self.line("end");
+
+        self.indent -= 1;
+        self.line("end");
+
+        Ok(())
+    }
+
+    fn expr(&mut self, ty: Ty, depth: usize) -> Result<String> {
+        if ty == Ty::Unit {
+            return self.call(Some(Ty::Unit), depth);
+        }
+
+        let leaf = depth >= MAX_EXPR_DEPTH || !self.u.arbitrary()?;
+        if leaf {
+            let vars: Vec<String> = self
+                .scopes
+                .iter()
+                .flatten()
+                .filter(|var| var.ty == ty)
+                .map(|var| var.name.clone())
+                .collect();
+
+            if !vars.is_empty() && self.u.arbitrary()? {
+                return Ok(self.u.choose(&vars)?.clone());
+            }
+
+            return Ok(match ty {
+                Ty::Int => self.u.int_in_range(0..=u16::max_value())?.to_string(),
+                Ty::Bool => self.u.arbitrary::<bool>()?.to_string(),
+                Ty::Unit => unreachable!(),
+            });
+        }
+
+        let depth = depth + 1;
+        let expr = match (ty, self.u.int_in_range(0..=3u8)?) {
+            (_, 0) => self.call(Some(ty), depth)?,
+            (_, 1) => format!("({})", self.expr(ty, depth)?),
+
+            (Ty::Int, 2) => format!("-({})", self.expr(ty, depth)?),
+            (Ty::Int, _) => {
+                let op = *self
+                    .u
+                    .choose(&["+", "-", "*", "/", "%", "**", "&", "|", "^", "<<", ">>"])?;
+
+                format!(
+                    "({} {} {})",
+                    self.expr(Ty::Int, depth)?,
+                    op,
+                    self.expr(Ty::Int, depth)?,
+                )
+            }
+
+            (Ty::Bool, 2) => format!("!({})", self.expr(ty, depth)?),
+            (Ty::Bool, _) => {
+                let operand = self.value_ty()?;
+                let op = if operand == Ty::Int {
+                    *self.u.choose(&["==", "!=", "<", ">", "<=", ">="])?
+                } else {
+                    *self.u.choose(&["==", "!="])?
+                };
+
+                format!(
+                    "({} {} {})",
+                    self.expr(operand, depth)?,
+                    op,
+                    self.expr(operand, depth)?,
+                )
+            }
+
+            (Ty::Unit, _) => unreachable!(),
+        };
+
+        Ok(expr)
+    }
+
+    /// Calls a function returning `ty`, or any function if `ty` is `None`
+    fn call(&mut self, ty: Option<Ty>, depth: usize) -> Result<String> {
+        let candidates: Vec<usize> = self
+            .functions
+            .iter()
+            .enumerate()
+            .filter(|(_, func)| ty.map_or(true, |ty| func.ret == ty))
+            .map(|(idx, _)| idx)
+            .collect();
+
+        if candidates.is_empty() {
+            return Ok(match ty {
+                Some(Ty::Int) => "0".to_owned(),
+                Some(Ty::Bool) => "false".to_owned(),
+                // Every program has `main`, so there's always something to call
+                Some(Ty::Unit) | None => "main()".to_owned(),
+            });
+        }
+
+        let func = *self.u.choose(&candidates)?;
+        let (name, arg_types) = (
+            self.functions[func].name.clone(),
+            self.functions[func].args.clone(),
+        );
+
+        // Pass the trailing arguments by name, since named arguments can't come before
+        // positional ones
+        let positional = self.u.int_in_range(0..=arg_types.len())?;
+
+        let mut args = Vec::with_capacity(arg_types.len());
+        for (idx, ty) in arg_types.into_iter().enumerate() {
+            // Keep arguments shallow so that calls don't blow up the size of the program
+            let value = self.expr(ty, (depth + 1).max(MAX_EXPR_DEPTH - 1))?;
+
+            if idx < positional {
+                args.push(value);
+            } else {
+                args.push(format!("a{} := {}", idx, value));
+            }
+        }
+
+        Ok(format!("{}({})", name, args.join(", ")))
+    }
+
+    fn value_ty(&mut self) -> Result<Ty> {
+        Ok(*self.u.choose(&[Ty::Int, Ty::Bool])?)
+    }
+
+    fn fresh_name(&mut self) -> String {
+        self.names += 1;
+        format!("v{}", self.names)
+    }
+
+    fn line(&mut self, line: &str) {
+        if !line.is_empty() {
+            for _ in 0..self.indent {
+                self.source.push_str("    ");
+            }
+            self.source.push_str(line);
+        }
+
+        self.source.push('\n');
+    }
+}
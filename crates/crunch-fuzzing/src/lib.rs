@@ -0,0 +1,117 @@
+#![warn(
+    missing_copy_implementations,
+    missing_debug_implementations,
+    clippy::dbg_macro,
+    clippy::missing_safety_doc,
+    clippy::wildcard_imports,
+    clippy::shadow_unrelated
+)]
+
+mod generate;
+
+pub use generate::Program;
+
+use crunch_mir::{MirDatabase, MirDatabaseStorage};
+use crunch_parser::{
+    database::{ParseDatabase, ParseDatabaseStorage},
+    Parser, ParserReturn,
+};
+use crunch_shared::{
+    config::{ConfigDatabase, ConfigDatabaseStorage},
+    context::{Context, ContextDatabase, ContextDatabaseStorage},
+    databases::{SourceDatabase, SourceDatabaseStorage},
+    error::ErrorHandler,
+    files::{CurrentFile, FileId},
+    salsa::{self, Database, Storage},
+    testing,
+    utils::Upcast,
+};
+use crunch_typecheck::{TypecheckDatabase, TypecheckDatabaseStorage};
+use ladder::{HirDatabase, HirDatabaseStorage};
+use std::sync::Arc;
+
+/// Parses `source` on its own, without any database
+pub fn parse<'ctx>(
+    source: &str,
+    context: &'ctx Context<'ctx>,
+) -> Result<ParserReturn<'ctx>, ErrorHandler> {
+    Parser::new(
+        source,
+        Arc::new(testing::options()),
+        CurrentFile::new(FileId::new(0), source.len()),
+        context,
+    )
+    .parse()
+}
+
+/// Runs `query` on a fresh database that contains only `source`, for pushing programs
+/// through the stages of the compiler that come after parsing
+pub fn with_database<F>(source: &str, query: F)
+where
+    F: FnOnce(&FuzzDatabase, FileId),
+{
+    testing::with_database(source, |database: &mut FuzzDatabase, file| {
+        database.set_file_edit(file, None);
+        query(database, file);
+    });
+}
+
+/// A database containing every stage of the compiler up to mir lowering
+#[salsa::database(
+    ConfigDatabaseStorage,
+    ContextDatabaseStorage,
+    SourceDatabaseStorage,
+    ParseDatabaseStorage,
+    HirDatabaseStorage,
+    TypecheckDatabaseStorage,
+    MirDatabaseStorage
+)]
+#[derive(Default)]
+#[allow(missing_debug_implementations)]
+pub struct FuzzDatabase {
+    storage: Storage<Self>,
+}
+
+impl Upcast<dyn ConfigDatabase> for FuzzDatabase {
+    fn upcast(&self) -> &dyn ConfigDatabase {
+        &*self
+    }
+}
+
+impl Upcast<dyn ContextDatabase> for FuzzDatabase {
+    fn upcast(&self) -> &dyn ContextDatabase {
+        &*self
+    }
+}
+
+impl Upcast<dyn SourceDatabase> for FuzzDatabase {
+    fn upcast(&self) -> &dyn SourceDatabase {
+        &*self
+    }
+}
+
+impl Upcast<dyn ParseDatabase> for FuzzDatabase {
+    fn upcast(&self) -> &dyn ParseDatabase {
+        &*self
+    }
+}
+
+impl Upcast<dyn HirDatabase> for FuzzDatabase {
+    fn upcast(&self) -> &dyn HirDatabase {
+        &*self
+    }
+}
+
+impl Upcast<dyn TypecheckDatabase> for FuzzDatabase {
+    fn upcast(&self) -> &dyn TypecheckDatabase {
+        &*self
+    }
+}
+
+impl Upcast<dyn MirDatabase> for FuzzDatabase {
+    fn upcast(&self) -> &dyn MirDatabase {
+        &*self
+    }
+}
+
+impl Database for FuzzDatabase {}
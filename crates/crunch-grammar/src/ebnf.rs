@@ -0,0 +1,606 @@
+//! A reader for the subset of the [W3C EBNF notation] used by `Crunch.ebnf`
+//!
+//! Productions marked with a trailing `/* ws: explicit */` comment describe single tokens,
+//! so no whitespace may appear between the pieces of them
+//!
+//! [W3C EBNF notation]: https://www.w3.org/TR/2010/REC-xquery-20101214/#EBNFNotation
+
+use std::{collections::HashMap, fmt};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    /// `a | b | c`
+    Choice(Vec<Rule>),
+    /// `a b c`
+    Sequence(Vec<Rule>),
+    /// `a?`
+    Optional(Box<Rule>),
+    /// `a*`
+    ZeroOrMore(Box<Rule>),
+    /// `a+`
+    OneOrMore(Box<Rule>),
+    /// `'text'` or `"text"`
+    Terminal(String),
+    /// `[a-z_]` or `[^"]`
+    Class(CharClass),
+    /// The name of another production
+    Reference(String),
+}
+
+impl Rule {
+    /// Whether the rule directly refers to the production `name`
+    pub fn references(&self, name: &str) -> bool {
+        match self {
+            Self::Choice(rules) | Self::Sequence(rules) => {
+                rules.iter().any(|rule| rule.references(name))
+            }
+            Self::Optional(rule) | Self::ZeroOrMore(rule) | Self::OneOrMore(rule) => {
+                rule.references(name)
+            }
+            Self::Reference(reference) => reference == name,
+            Self::Terminal(_) | Self::Class(_) => false,
+        }
+    }
+
+    /// Calls `visit` on every production the rule refers to
+    pub fn for_each_reference<'a, F>(&'a self, visit: &mut F)
+    where
+        F: FnMut(&'a str),
+    {
+        match self {
+            Self::Choice(rules) | Self::Sequence(rules) => {
+                for rule in rules {
+                    rule.for_each_reference(visit);
+                }
+            }
+            Self::Optional(rule) | Self::ZeroOrMore(rule) | Self::OneOrMore(rule) => {
+                rule.for_each_reference(visit)
+            }
+            Self::Reference(reference) => visit(reference),
+            Self::Terminal(_) | Self::Class(_) => {}
+        }
+    }
+
+    /// Calls `visit` on every terminal within the rule
+    pub fn for_each_terminal<'a, F>(&'a self, visit: &mut F)
+    where
+        F: FnMut(&'a str),
+    {
+        match self {
+            Self::Choice(rules) | Self::Sequence(rules) => {
+                for rule in rules {
+                    rule.for_each_terminal(visit);
+                }
+            }
+            Self::Optional(rule) | Self::ZeroOrMore(rule) | Self::OneOrMore(rule) => {
+                rule.for_each_terminal(visit)
+            }
+            Self::Terminal(terminal) => visit(terminal),
+            Self::Reference(_) | Self::Class(_) => {}
+        }
+    }
+
+    /// Writes the rule, parenthesizing it if it isn't a single atom
+    fn fmt_atom(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Choice(_) | Self::Sequence(_) => write!(f, "( {} )", self),
+            rule => write!(f, "{}", rule),
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Choice(rules) => {
+                for (idx, rule) in rules.iter().enumerate() {
+                    if idx != 0 {
+                        f.write_str(" | ")?;
+                    }
+
+                    if let Self::Choice(_) = rule {
+                        rule.fmt_atom(f)?;
+                    } else {
+                        write!(f, "{}", rule)?;
+                    }
+                }
+
+                Ok(())
+            }
+
+            Self::Sequence(rules) => {
+                for (idx, rule) in rules.iter().enumerate() {
+                    if idx != 0 {
+                        f.write_str(" ")?;
+                    }
+                    rule.fmt_atom(f)?;
+                }
+
+                Ok(())
+            }
+
+            Self::Optional(rule) => {
+                rule.fmt_atom(f)?;
+                f.write_str("?")
+            }
+            Self::ZeroOrMore(rule) => {
+                rule.fmt_atom(f)?;
+                f.write_str("*")
+            }
+            Self::OneOrMore(rule) => {
+                rule.fmt_atom(f)?;
+                f.write_str("+")
+            }
+
+            Self::Terminal(terminal) => {
+                let escaped = terminal.replace('\n', "\\n");
+                if terminal.contains('\'') {
+                    write!(f, "\"{}\"", escaped)
+                } else {
+                    write!(f, "'{}'", escaped)
+                }
+            }
+            Self::Class(class) => write!(f, "{}", class),
+            Self::Reference(name) => f.write_str(name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharClass {
+    pub negated: bool,
+    /// Inclusive ranges of characters
+    pub ranges: Vec<(char, char)>,
+}
+
+impl CharClass {
+    pub fn contains(&self, c: char) -> bool {
+        self.ranges
+            .iter()
+            .any(|&(start, end)| start <= c && c <= end)
+            != self.negated
+    }
+}
+
+impl fmt::Display for CharClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.negated { "[^" } else { "[" })?;
+        for &(start, end) in self.ranges.iter() {
+            if start == end {
+                write!(f, "{}", start.escape_default())?;
+            } else {
+                write!(f, "{}-{}", start.escape_default(), end.escape_default())?;
+            }
+        }
+
+        f.write_str("]")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Production {
+    pub name: String,
+    pub rule: Rule,
+    /// Whether whitespace is forbidden between the pieces of the production
+    pub explicit_whitespace: bool,
+    /// The line the production starts on
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Grammar {
+    pub productions: Vec<Production>,
+    index: HashMap<String, usize>,
+}
+
+impl Grammar {
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        let tokens = lex(source)?;
+        let mut grammar = Self::default();
+
+        let mut idx = 0;
+        while idx < tokens.len() {
+            let (name, line) = match &tokens[idx] {
+                (Token::Name(name), line)
+                    if tokens.get(idx + 1).map(|(t, _)| t) == Some(&Token::Define) =>
+                {
+                    (name.clone(), *line)
+                }
+                (Token::Comment(_), _) => {
+                    idx += 1;
+                    continue;
+                }
+                (token, line) => {
+                    return Err(ParseError {
+                        line: *line,
+                        message: format!("expected the start of a production, got {}", token),
+                    });
+                }
+            };
+            idx += 2;
+
+            // A production runs until the start of the next one
+            let mut end = idx;
+            while end < tokens.len() {
+                if let Token::Name(_) = tokens[end].0 {
+                    if tokens.get(end + 1).map(|(t, _)| t) == Some(&Token::Define) {
+                        break;
+                    }
+                }
+                end += 1;
+            }
+
+            let explicit_whitespace = tokens[idx..end].iter().any(
+                |(token, _)| matches!(token, Token::Comment(comment) if comment == "ws: explicit"),
+            );
+            let body: Vec<(Token, usize)> = tokens[idx..end]
+                .iter()
+                .filter(|(token, _)| !matches!(token, Token::Comment(_)))
+                .cloned()
+                .collect();
+
+            let mut parser = RuleParser {
+                tokens: &body,
+                idx: 0,
+                line,
+            };
+            let rule = parser.choice()?;
+            if let Some((token, line)) = body.get(parser.idx) {
+                return Err(ParseError {
+                    line: *line,
+                    message: format!("unexpected {} in the production {}", token, name),
+                });
+            }
+
+            if grammar.index.contains_key(&name) {
+                return Err(ParseError {
+                    line,
+                    message: format!("the production {} is defined more than once", name),
+                });
+            }
+
+            grammar
+                .index
+                .insert(name.clone(), grammar.productions.len());
+            grammar.productions.push(Production {
+                name,
+                rule,
+                explicit_whitespace,
+                line,
+            });
+            idx = end;
+        }
+
+        Ok(grammar)
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Production> {
+        self.index_of(name).map(|idx| &self.productions[idx])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Name(String),
+    Define,
+    Pipe,
+    OpenParen,
+    CloseParen,
+    Question,
+    Star,
+    Plus,
+    Terminal(String),
+    Class(CharClass),
+    Comment(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "`{}`", name),
+            Self::Define => f.write_str("`::=`"),
+            Self::Pipe => f.write_str("`|`"),
+            Self::OpenParen => f.write_str("`(`"),
+            Self::CloseParen => f.write_str("`)`"),
+            Self::Question => f.write_str("`?`"),
+            Self::Star => f.write_str("`*`"),
+            Self::Plus => f.write_str("`+`"),
+            Self::Terminal(terminal) => write!(f, "`{}`", Rule::Terminal(terminal.clone())),
+            Self::Class(class) => write!(f, "`{}`", class),
+            Self::Comment(_) => f.write_str("a comment"),
+        }
+    }
+}
+
+fn unescape(c: char) -> char {
+    match c {
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        c => c,
+    }
+}
+
+fn lex(source: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    let mut line = 1;
+
+    let error = |line, message: &str| ParseError {
+        line,
+        message: message.to_owned(),
+    };
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '\n' => {
+                line += 1;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+
+                let (start, mut comment, mut prev) = (line, String::new(), '\0');
+                loop {
+                    match chars.next() {
+                        Some('/') if prev == '*' => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+
+                            comment.push(c);
+                            prev = c;
+                        }
+                        None => return Err(error(start, "unterminated comment")),
+                    }
+                }
+                comment.pop();
+
+                tokens.push((Token::Comment(comment.trim().to_owned()), start));
+                continue;
+            }
+
+            ':' => {
+                if chars.next() == Some(':') && chars.next() == Some('=') {
+                    Token::Define
+                } else {
+                    return Err(error(line, "expected `::=`"));
+                }
+            }
+
+            '|' => Token::Pipe,
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
+            '?' => Token::Question,
+            '*' => Token::Star,
+            '+' => Token::Plus,
+
+            quote @ '\'' | quote @ '"' => {
+                let mut terminal = String::new();
+                loop {
+                    match chars.next() {
+                        Some(c) if c == quote => break,
+                        Some('\\') => terminal.push(unescape(chars.next().unwrap_or('\\'))),
+                        Some('\n') | None => return Err(error(line, "unterminated terminal")),
+                        Some(c) => terminal.push(c),
+                    }
+                }
+
+                Token::Terminal(terminal)
+            }
+
+            '[' => {
+                let negated = chars.peek() == Some(&'^');
+                if negated {
+                    chars.next();
+                }
+
+                let mut members = Vec::new();
+                loop {
+                    match chars.next() {
+                        Some(']') => break,
+                        Some('\\') => members.push(unescape(chars.next().unwrap_or('\\'))),
+                        Some('\n') | None => return Err(error(line, "unterminated class")),
+                        Some(c) => members.push(c),
+                    }
+                }
+
+                let mut ranges = Vec::new();
+                let mut idx = 0;
+                while idx < members.len() {
+                    if members.get(idx + 1) == Some(&'-') && idx + 2 < members.len() {
+                        ranges.push((members[idx], members[idx + 2]));
+                        idx += 3;
+                    } else {
+                        ranges.push((members[idx], members[idx]));
+                        idx += 1;
+                    }
+                }
+
+                Token::Class(CharClass { negated, ranges })
+            }
+
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut name = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        name.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+
+                Token::Name(name)
+            }
+
+            c => return Err(error(line, &format!("unexpected character {:?}", c))),
+        };
+
+        tokens.push((token, line));
+    }
+
+    Ok(tokens)
+}
+
+struct RuleParser<'a> {
+    tokens: &'a [(Token, usize)],
+    idx: usize,
+    /// The line of the production, used for errors at the end of it
+    line: usize,
+}
+
+impl<'a> RuleParser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.idx).map(|(token, _)| token)
+    }
+
+    fn choice(&mut self) -> Result<Rule, ParseError> {
+        let mut options = vec![self.sequence()?];
+        while self.peek() == Some(&Token::Pipe) {
+            self.idx += 1;
+            options.push(self.sequence()?);
+        }
+
+        Ok(flatten(options, Rule::Choice))
+    }
+
+    fn sequence(&mut self) -> Result<Rule, ParseError> {
+        let mut items = Vec::new();
+        while let Some(token) = self.peek() {
+            if matches!(token, Token::Pipe | Token::CloseParen) {
+                break;
+            }
+
+            items.push(self.postfix()?);
+        }
+
+        Ok(flatten(items, Rule::Sequence))
+    }
+
+    fn postfix(&mut self) -> Result<Rule, ParseError> {
+        let mut rule = self.atom()?;
+        loop {
+            rule = match self.peek() {
+                Some(Token::Question) => Rule::Optional(Box::new(rule)),
+                Some(Token::Star) => Rule::ZeroOrMore(Box::new(rule)),
+                Some(Token::Plus) => Rule::OneOrMore(Box::new(rule)),
+                _ => return Ok(rule),
+            };
+            self.idx += 1;
+        }
+    }
+
+    fn atom(&mut self) -> Result<Rule, ParseError> {
+        let (token, line) = self.tokens.get(self.idx).ok_or_else(|| ParseError {
+            line: self.line,
+            message: "unexpected end of production".to_owned(),
+        })?;
+        self.idx += 1;
+
+        let rule = match token {
+            Token::Name(name) => Rule::Reference(name.clone()),
+            Token::Terminal(terminal) => Rule::Terminal(terminal.clone()),
+            Token::Class(class) => Rule::Class(class.clone()),
+            Token::OpenParen => {
+                let rule = self.choice()?;
+                if self.peek() != Some(&Token::CloseParen) {
+                    return Err(ParseError {
+                        line: *line,
+                        message: "unclosed parenthesis".to_owned(),
+                    });
+                }
+                self.idx += 1;
+
+                rule
+            }
+
+            token => {
+                return Err(ParseError {
+                    line: *line,
+                    message: format!("unexpected {}", token),
+                });
+            }
+        };
+
+        Ok(rule)
+    }
+}
+
+/// Collapses single-element choices and sequences and merges nested ones, so that two
+/// rules written differently but meaning the same thing compare as equal
+fn flatten(rules: Vec<Rule>, kind: fn(Vec<Rule>) -> Rule) -> Rule {
+    let mut flattened = Vec::with_capacity(rules.len());
+    for rule in rules {
+        match (kind(Vec::new()), rule) {
+            (Rule::Choice(_), Rule::Choice(inner)) | (Rule::Sequence(_), Rule::Sequence(inner)) => {
+                flattened.extend(inner)
+            }
+            (_, rule) => flattened.push(rule),
+        }
+    }
+
+    if flattened.len() == 1 {
+        flattened.pop().unwrap()
+    } else {
+        kind(flattened)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn productions() {
+        let grammar = Grammar::parse(
+            "/* Comment */\n\
+             A ::= 'a' B? ( 'c' | \"'\" )* /* ws: explicit */\n\
+             B ::= [a-z_] | [^\"] '\\n'+",
+        )
+        .unwrap();
+
+        let a = grammar.get("A").unwrap();
+        assert!(a.explicit_whitespace);
+        assert_eq!(a.line, 2);
+        assert_eq!(a.rule.to_string(), "'a' B? ( 'c' | \"'\" )*");
+        assert!(a.rule.references("B"));
+
+        let b = grammar.get("B").unwrap();
+        assert!(!b.explicit_whitespace);
+        assert_eq!(b.rule.to_string(), "[a-z_] | [^\\\"] '\\n'+");
+        assert!(!b.rule.references("A"));
+    }
+
+    #[test]
+    fn equivalent_rules() {
+        let grammar = Grammar::parse(
+            "A ::= ( 'a' ( 'b' 'c' ) ) | ( 'd' | 'e' )\nB ::= 'a' 'b' 'c' | 'd' | 'e'",
+        )
+        .unwrap();
+
+        assert_eq!(
+            grammar.get("A").unwrap().rule,
+            grammar.get("B").unwrap().rule
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! Generates random sentences from a grammar, recording which production derived each
+//! piece of the sentence so that parse errors can be traced back to the grammar
+
+use crate::ebnf::{Grammar, Rule};
+use std::ops::Range;
+
+/// The printable characters that negated character classes are sampled from
+const ALPHABET: &str =
+    "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_ .,:;!?-+*/=<>()[]";
+
+/// How many times a single-token production is regenerated before giving up on
+/// avoiding reserved words
+const MAX_RETRIES: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct Sentence {
+    pub text: String,
+    pub derivations: Vec<Derivation>,
+}
+
+impl Sentence {
+    /// The innermost production that derived the text at `offset`
+    pub fn blame(&self, offset: usize) -> Option<&Derivation> {
+        self.derivations
+            .iter()
+            .filter(|derivation| derivation.range.start <= offset && offset <= derivation.range.end)
+            .max_by_key(|derivation| (derivation.depth, derivation.range.start))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Derivation {
+    /// The index of the production within the grammar
+    pub production: usize,
+    pub range: Range<usize>,
+    pub depth: usize,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // Xorshift gets stuck on a zero state
+        Self(seed ^ 0x2545_F491_4F6C_DD1D)
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A random number in `0..max`
+    pub fn below(&mut self, max: usize) -> usize {
+        if max == 0 {
+            0
+        } else {
+            (self.next() % max as u64) as usize
+        }
+    }
+
+    pub fn bool(&mut self) -> bool {
+        self.next() & 1 == 1
+    }
+}
+
+pub struct Generator<'a> {
+    grammar: &'a Grammar,
+    /// The minimum number of productions needed to fully derive each production
+    heights: Vec<usize>,
+    rng: Rng,
+    /// The depth at which the generator stops taking optional paths
+    max_depth: usize,
+    /// Words that single-token productions may not produce, such as keywords
+    is_reserved: &'a dyn Fn(&str) -> bool,
+}
+
+impl<'a> Generator<'a> {
+    pub fn new(
+        grammar: &'a Grammar,
+        seed: u64,
+        max_depth: usize,
+        is_reserved: &'a dyn Fn(&str) -> bool,
+    ) -> Self {
+        let mut heights = vec![usize::max_value(); grammar.productions.len()];
+        loop {
+            let mut changed = false;
+            for (idx, production) in grammar.productions.iter().enumerate() {
+                let height = height(grammar, &heights, &production.rule);
+                if height < heights[idx] {
+                    heights[idx] = height;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        Self {
+            grammar,
+            heights,
+            rng: Rng::new(seed),
+            max_depth,
+            is_reserved,
+        }
+    }
+
+    /// Whether the production can ever finish deriving a sentence
+    pub fn terminates(&self, production: usize) -> bool {
+        self.heights[production] != usize::max_value()
+    }
+
+    /// Generates a sentence starting at `root` that passes through every production within
+    /// `path` in order
+    pub fn sentence(&mut self, root: usize, path: &[usize]) -> Sentence {
+        let mut state = State {
+            text: String::new(),
+            derivations: Vec::new(),
+            explicit: 0,
+        };
+        self.production(&mut state, root, 0, path);
+
+        Sentence {
+            text: state.text,
+            derivations: state.derivations,
+        }
+    }
+
+    fn production(&mut self, state: &mut State, idx: usize, depth: usize, path: &[usize]) {
+        let production = &self.grammar.productions[idx];
+
+        let token = production.explicit_whitespace && state.explicit == 0;
+        if token {
+            state.separate();
+        }
+        if production.explicit_whitespace {
+            state.explicit += 1;
+        }
+
+        let (start, derivations) = (state.text.len(), state.derivations.len());
+        let mut retries = 0;
+        loop {
+            self.rule(state, &production.rule, depth, path);
+
+            let generated = &state.text[start..];
+            if !token || retries == MAX_RETRIES || !(self.is_reserved)(generated) {
+                break;
+            }
+
+            state.text.truncate(start);
+            state.derivations.truncate(derivations);
+            retries += 1;
+        }
+
+        if production.explicit_whitespace {
+            state.explicit -= 1;
+        }
+
+        let generated = &state.text[start..];
+        let start = start + (generated.len() - generated.trim_start().len());
+        state.derivations.push(Derivation {
+            production: idx,
+            range: start..state.text.trim_end().len().max(start),
+            depth,
+        });
+    }
+
+    fn rule(&mut self, state: &mut State, rule: &Rule, depth: usize, path: &[usize]) {
+        let focus = path
+            .first()
+            .map(|&idx| self.grammar.productions[idx].name.as_str());
+        let leads_to_focus = |rule: &Rule| focus.map_or(false, |focus| rule.references(focus));
+        let exhausted = depth >= self.max_depth;
+
+        match rule {
+            Rule::Choice(options) => {
+                let focused: Vec<&Rule> = options.iter().filter(|o| leads_to_focus(o)).collect();
+
+                let chosen = if !focused.is_empty() {
+                    focused[self.rng.below(focused.len())]
+                } else if exhausted {
+                    options
+                        .iter()
+                        .min_by_key(|option| height(self.grammar, &self.heights, option))
+                        .unwrap()
+                } else {
+                    &options[self.rng.below(options.len())]
+                };
+
+                let path = if leads_to_focus(chosen) { path } else { &[] };
+                self.rule(state, chosen, depth, path);
+            }
+
+            Rule::Sequence(items) => {
+                let mut path = path;
+                for item in items {
+                    if leads_to_focus(item) {
+                        self.rule(state, item, depth, path);
+                        path = &[];
+                    } else {
+                        self.rule(state, item, depth, &[]);
+                    }
+                }
+            }
+
+            Rule::Optional(inner) => {
+                if leads_to_focus(inner) {
+                    self.rule(state, inner, depth, path);
+                } else if !exhausted && self.rng.bool() {
+                    self.rule(state, inner, depth, &[]);
+                }
+            }
+
+            Rule::ZeroOrMore(inner) | Rule::OneOrMore(inner) => {
+                let mut repeats = if exhausted { 0 } else { self.rng.below(3) };
+                if let Rule::OneOrMore(_) = rule {
+                    repeats = repeats.max(1);
+                }
+
+                if leads_to_focus(inner) {
+                    self.rule(state, inner, depth, path);
+                    repeats = repeats.saturating_sub(1);
+                }
+
+                for _ in 0..repeats {
+                    self.rule(state, inner, depth, &[]);
+                }
+            }
+
+            Rule::Terminal(terminal) => state.push(terminal),
+
+            Rule::Class(class) => {
+                let c = if class.negated {
+                    let members: Vec<char> =
+                        ALPHABET.chars().filter(|&c| class.contains(c)).collect();
+                    if members.is_empty() {
+                        return;
+                    }
+
+                    members[self.rng.below(members.len())]
+                } else {
+                    let (start, end) = class.ranges[self.rng.below(class.ranges.len())];
+                    let offset = self.rng.below(end as usize - start as usize + 1) as u32;
+
+                    std::char::from_u32(start as u32 + offset).unwrap_or(start)
+                };
+
+                state.push(c.encode_utf8(&mut [0; 4]));
+            }
+
+            // References to undefined or endlessly recursive productions are reported
+            // separately, so they're simply skipped here
+            Rule::Reference(name) => {
+                if let Some(idx) = self
+                    .grammar
+                    .index_of(name)
+                    .filter(|&idx| self.terminates(idx))
+                {
+                    if path.first() == Some(&idx) {
+                        self.production(state, idx, depth + 1, &path[1..]);
+                    } else {
+                        self.production(state, idx, depth + 1, &[]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+struct State {
+    text: String,
+    derivations: Vec<Derivation>,
+    /// The number of explicit-whitespace productions currently being generated
+    explicit: usize,
+}
+
+impl State {
+    fn push(&mut self, text: &str) {
+        if self.explicit == 0 {
+            self.separate();
+        }
+
+        self.text.push_str(text);
+    }
+
+    fn separate(&mut self) {
+        if self
+            .text
+            .chars()
+            .last()
+            .map_or(false, |c| !c.is_whitespace())
+        {
+            self.text.push(' ');
+        }
+    }
+}
+
+fn height(grammar: &Grammar, heights: &[usize], rule: &Rule) -> usize {
+    match rule {
+        Rule::Choice(options) => options
+            .iter()
+            .map(|option| height(grammar, heights, option))
+            .min()
+            .unwrap_or(0),
+        Rule::Sequence(items) => items
+            .iter()
+            .map(|item| height(grammar, heights, item))
+            .max()
+            .unwrap_or(0),
+        Rule::OneOrMore(inner) => height(grammar, heights, inner),
+        Rule::Optional(_) | Rule::ZeroOrMore(_) | Rule::Terminal(_) | Rule::Class(_) => 0,
+        Rule::Reference(name) => grammar
+            .index_of(name)
+            .map_or(0, |idx| heights[idx].saturating_add(1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reaches_focus() {
+        let grammar = Grammar::parse(
+            "Root ::= ( A | B )*\n\
+             A ::= 'a'\n\
+             B ::= 'b' C?\n\
+             C ::= Word+\n\
+             Word ::= [a-z]+ /* ws: explicit */",
+        )
+        .unwrap();
+        let is_reserved = |word: &str| word == "a";
+
+        let path = [
+            grammar.index_of("B").unwrap(),
+            grammar.index_of("C").unwrap(),
+        ];
+        for seed in 0..32 {
+            let mut generator = Generator::new(&grammar, seed, 8, &is_reserved);
+            let sentence = generator.sentence(grammar.index_of("Root").unwrap(), &path);
+
+            assert!(sentence.text.contains('b'));
+            let words = sentence
+                .derivations
+                .iter()
+                .filter(|derivation| derivation.production == grammar.index_of("Word").unwrap());
+            for word in words {
+                assert_ne!(&sentence.text[word.range.clone()], "a");
+                assert!(!sentence.text[word.range.clone()].contains(' '));
+            }
+        }
+    }
+
+    #[test]
+    fn terminates() {
+        let grammar = Grammar::parse("A ::= 'a' | '(' A ')' A\nB ::= B 'b'").unwrap();
+        let is_reserved = |_: &str| false;
+        let mut generator = Generator::new(&grammar, 7, 4, &is_reserved);
+
+        assert!(generator.terminates(0));
+        assert!(!generator.terminates(1));
+        for _ in 0..32 {
+            let sentence = generator.sentence(0, &[]);
+            assert!(sentence.text.starts_with('a') || sentence.text.starts_with('('));
+        }
+    }
+}
@@ -0,0 +1,111 @@
+#![warn(
+    missing_copy_implementations,
+    missing_debug_implementations,
+    clippy::dbg_macro,
+    clippy::missing_safety_doc,
+    clippy::wildcard_imports,
+    clippy::shadow_unrelated
+)]
+
+//! Checks `Crunch.ebnf` against the parser by generating sentences for every production
+//! and parsing them, then reports where the grammar and the parser disagree
+
+mod ebnf;
+mod generate;
+mod report;
+
+use ebnf::Grammar;
+use report::Report;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+const USAGE: &str = "\
+Usage: crunch-grammar [options]
+
+Options:
+    --grammar <path>    The grammar to check [default: Crunch.ebnf]
+    --parser <path>     The parser sources to read documented grammar from
+                        [default: crates/crunch-parser/src/parser]
+    --examples <path>   The programs to check the grammar's tokens against [default: examples]
+    --samples <n>       The number of sentences generated for each production [default: 16]
+    --depth <n>         The depth at which generation stops taking optional paths [default: 8]
+    --seed <n>          The seed for the random generator [default: 0]";
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub grammar: PathBuf,
+    pub parser: PathBuf,
+    pub examples: PathBuf,
+    pub samples: usize,
+    pub depth: usize,
+    pub seed: u64,
+}
+
+impl Options {
+    fn from_args() -> Result<Self, String> {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let mut options = Self {
+            grammar: root.join("Crunch.ebnf"),
+            parser: root.join("crates/crunch-parser/src/parser"),
+            examples: root.join("examples"),
+            samples: 16,
+            depth: 8,
+            seed: 0,
+        };
+
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            if arg == "--help" || arg == "-h" {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Expected a value for `{}`", arg))?;
+            let number = || {
+                value
+                    .parse::<u64>()
+                    .map_err(|_| format!("Expected a number for `{}`, got `{}`", arg, value))
+            };
+
+            match arg.as_str() {
+                "--grammar" => options.grammar = PathBuf::from(&value),
+                "--parser" => options.parser = PathBuf::from(&value),
+                "--examples" => options.examples = PathBuf::from(&value),
+                "--samples" => options.samples = number()? as usize,
+                "--depth" => options.depth = number()? as usize,
+                "--seed" => options.seed = number()?,
+                _ => return Err(format!("Unknown option `{}`", arg)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn main() {
+    let options = Options::from_args().unwrap_or_else(|err| {
+        eprintln!("{}\n\n{}", err, USAGE);
+        process::exit(2);
+    });
+
+    let source = fs::read_to_string(&options.grammar).unwrap_or_else(|err| {
+        eprintln!("Failed to read {}: {}", options.grammar.display(), err);
+        process::exit(2);
+    });
+    let grammar = Grammar::parse(&source).unwrap_or_else(|err| {
+        eprintln!("Failed to parse {}: {}", options.grammar.display(), err);
+        process::exit(2);
+    });
+
+    let report = Report::new(&grammar, &options);
+    print!("{}", report);
+
+    if !report.is_clean() {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,481 @@
+use crate::{
+    ebnf::Grammar,
+    generate::{Generator, Sentence},
+    Options,
+};
+use crunch_parser::{
+    token::{TokenStream, TokenType},
+    Parser,
+};
+use crunch_shared::{
+    config::{BuildOptions, TermColor},
+    context::{Arenas, Context, OwnedArenas},
+    files::{CurrentFile, FileId},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet, VecDeque},
+    fmt, fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// The production every generated sentence starts from
+const ROOT: &str = "Program";
+
+#[derive(Debug, Default)]
+pub struct Report {
+    generated: usize,
+    accepted: usize,
+    missing_root: bool,
+    undefined: Vec<Undefined>,
+    endless: Vec<String>,
+    unreachable: Vec<String>,
+    rejected: BTreeMap<String, Rejection>,
+    unreadable: Vec<(Documented, String)>,
+    undocumented: Vec<Documented>,
+    mismatched: Vec<(Documented, String)>,
+    unknown_tokens: BTreeMap<String, PathBuf>,
+}
+
+impl Report {
+    pub fn new(grammar: &Grammar, options: &Options) -> Self {
+        let mut report = Self::default();
+
+        report.check_references(grammar);
+        report.check_sentences(grammar, options);
+        report.check_documented(grammar, &options.parser);
+        report.check_tokens(grammar, &options.examples);
+
+        report
+    }
+
+    /// Whether the grammar and the parser fully agree
+    pub fn is_clean(&self) -> bool {
+        !self.missing_root
+            && self.undefined.is_empty()
+            && self.endless.is_empty()
+            && self.rejected.is_empty()
+            && self.unreadable.is_empty()
+            && self.undocumented.is_empty()
+            && self.mismatched.is_empty()
+            && self.unknown_tokens.is_empty()
+    }
+
+    fn check_references(&mut self, grammar: &Grammar) {
+        for production in grammar.productions.iter() {
+            production.rule.for_each_reference(&mut |name| {
+                if grammar.index_of(name).is_none() {
+                    self.undefined.push(Undefined {
+                        name: name.to_owned(),
+                        referenced_by: production.name.clone(),
+                        line: production.line,
+                    });
+                }
+            });
+        }
+    }
+
+    fn check_sentences(&mut self, grammar: &Grammar, options: &Options) {
+        let root = if let Some(root) = grammar.index_of(ROOT) {
+            root
+        } else {
+            self.missing_root = true;
+            return;
+        };
+
+        let mut generator = Generator::new(grammar, options.seed, options.depth, &is_reserved);
+        let paths = paths_from(grammar, root);
+
+        // Panics are reported as rejections, so the default hook would only add noise
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+
+        for (idx, production) in grammar.productions.iter().enumerate() {
+            if !generator.terminates(idx) {
+                self.endless.push(production.name.clone());
+                continue;
+            }
+
+            let path = if let Some(path) = &paths[idx] {
+                path
+            } else {
+                self.unreachable.push(production.name.clone());
+                continue;
+            };
+
+            for _ in 0..options.samples {
+                let sentence = generator.sentence(root, path);
+                self.generated += 1;
+
+                match parse(&sentence.text) {
+                    Ok(()) => self.accepted += 1,
+                    Err((offset, message)) => {
+                        let blamed = offset
+                            .and_then(|offset| sentence.blame(offset))
+                            .map_or(idx, |derivation| derivation.production);
+
+                        self.reject(&grammar.productions[blamed].name, sentence, offset, message);
+                    }
+                }
+            }
+        }
+
+        panic::set_hook(hook);
+    }
+
+    fn reject(&mut self, name: &str, sentence: Sentence, offset: Option<usize>, message: String) {
+        let rejection = self
+            .rejected
+            .entry(name.to_owned())
+            .or_insert_with(|| Rejection {
+                count: 0,
+                example: sentence.text.clone(),
+                offset,
+                message: message.clone(),
+            });
+
+        rejection.count += 1;
+        if sentence.text.len() < rejection.example.len() {
+            rejection.example = sentence.text;
+            rejection.offset = offset;
+            rejection.message = message;
+        }
+    }
+
+    /// Compares the grammar against the snippets of it documented on the parser's functions
+    fn check_documented(&mut self, grammar: &Grammar, parser: &Path) {
+        for snippet in documented_snippets(parser) {
+            let documented = match Grammar::parse(&snippet.rule) {
+                Ok(documented) => documented,
+                Err(err) => {
+                    self.unreadable.push((snippet, err.message));
+                    continue;
+                }
+            };
+
+            for production in documented.productions {
+                let documented = Documented {
+                    name: production.name.clone(),
+                    rule: production.rule.to_string(),
+                    file: snippet.file.clone(),
+                    line: snippet.line + production.line - 1,
+                };
+
+                match grammar.get(&production.name) {
+                    Some(specified) if specified.rule == production.rule => {}
+                    Some(specified) => self
+                        .mismatched
+                        .push((documented, specified.rule.to_string())),
+                    None => self.undocumented.push(documented),
+                }
+            }
+        }
+    }
+
+    /// Finds the keywords and punctuation used by the example programs that no
+    /// terminal of the grammar contains
+    fn check_tokens(&mut self, grammar: &Grammar, examples: &Path) {
+        let mut specified = HashSet::new();
+        for production in grammar.productions.iter() {
+            production.rule.for_each_terminal(&mut |terminal| {
+                specified.extend(structural_tokens(terminal));
+            });
+        }
+
+        for file in files_with_extension(examples, "crunch") {
+            let source = if let Ok(source) = fs::read_to_string(&file) {
+                source
+            } else {
+                continue;
+            };
+
+            for token in structural_tokens(&source) {
+                if !specified.contains(&token) {
+                    self.unknown_tokens
+                        .entry(token)
+                        .or_insert_with(|| file.clone());
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.missing_root {
+            writeln!(
+                f,
+                "error: the grammar has no `{}` production to start from\n",
+                ROOT
+            )?;
+        }
+
+        for undefined in self.undefined.iter() {
+            writeln!(
+                f,
+                "error: `{}` is referenced by `{}` on line {} but never defined\n",
+                undefined.name, undefined.referenced_by, undefined.line,
+            )?;
+        }
+
+        for name in self.endless.iter() {
+            writeln!(
+                f,
+                "error: `{}` can never finish deriving a sentence\n",
+                name
+            )?;
+        }
+
+        for name in self.unreachable.iter() {
+            writeln!(f, "warning: `{}` can't be reached from `{}`\n", name, ROOT)?;
+        }
+
+        for (name, rejection) in self.rejected.iter() {
+            writeln!(
+                f,
+                "error: the parser rejected {} sentence{} because of `{}`",
+                rejection.count,
+                if rejection.count == 1 { "" } else { "s" },
+                name,
+            )?;
+            for line in rejection.example.lines() {
+                writeln!(f, "    | {}", line)?;
+            }
+
+            match rejection.offset {
+                Some(offset) => {
+                    let before = &rejection.example[..offset.min(rejection.example.len())];
+                    let line = before.matches('\n').count() + 1;
+                    let column = before.len() - before.rfind('\n').map_or(0, |idx| idx + 1) + 1;
+
+                    writeln!(f, "    {}:{}: {}\n", line, column, rejection.message)?;
+                }
+                None => writeln!(f, "    {}\n", rejection.message)?,
+            }
+        }
+
+        for (documented, message) in self.unreadable.iter() {
+            writeln!(
+                f,
+                "error: the grammar documented at {}:{} couldn't be read: {}\n",
+                documented.file.display(),
+                documented.line,
+                message,
+            )?;
+        }
+
+        for documented in self.undocumented.iter() {
+            writeln!(
+                f,
+                "error: `{}` is parsed at {}:{} but missing from the grammar",
+                documented.name,
+                documented.file.display(),
+                documented.line,
+            )?;
+            writeln!(f, "    parser:  {}\n", documented.rule)?;
+        }
+
+        for (documented, specified) in self.mismatched.iter() {
+            writeln!(
+                f,
+                "error: `{}` is parsed differently at {}:{} than the grammar describes",
+                documented.name,
+                documented.file.display(),
+                documented.line,
+            )?;
+            writeln!(f, "    grammar: {}", specified)?;
+            writeln!(f, "    parser:  {}\n", documented.rule)?;
+        }
+
+        for (token, file) in self.unknown_tokens.iter() {
+            writeln!(
+                f,
+                "error: `{}` is used by {} but no production of the grammar contains it\n",
+                token,
+                file.display(),
+            )?;
+        }
+
+        writeln!(
+            f,
+            "The parser accepted {} of {} generated sentences",
+            self.accepted, self.generated,
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Undefined {
+    name: String,
+    referenced_by: String,
+    line: usize,
+}
+
+#[derive(Debug)]
+struct Rejection {
+    count: usize,
+    /// The shortest rejected sentence
+    example: String,
+    /// The offset of the error within the example, if the parser didn't panic
+    offset: Option<usize>,
+    message: String,
+}
+
+#[derive(Debug)]
+struct Documented {
+    name: String,
+    rule: String,
+    file: PathBuf,
+    line: usize,
+}
+
+/// Finds the shortest chain of productions leading from `root` to each production,
+/// excluding `root` itself
+fn paths_from(grammar: &Grammar, root: usize) -> Vec<Option<Vec<usize>>> {
+    let mut paths = vec![None; grammar.productions.len()];
+    paths[root] = Some(Vec::new());
+
+    let mut queue = VecDeque::new();
+    queue.push_back(root);
+    while let Some(idx) = queue.pop_front() {
+        let path = paths[idx].clone().unwrap_or_default();
+
+        grammar.productions[idx]
+            .rule
+            .for_each_reference(&mut |name| {
+                if let Some(next) = grammar.index_of(name) {
+                    if paths[next].is_none() {
+                        let mut next_path = path.clone();
+                        next_path.push(next);
+
+                        paths[next] = Some(next_path);
+                        queue.push_back(next);
+                    }
+                }
+            });
+    }
+
+    paths
+}
+
+/// Parses a sentence, returning the offset and message of the first error if it fails
+fn parse(text: &str) -> Result<(), (Option<usize>, String)> {
+    let owned_arenas = OwnedArenas::default();
+    let context = Context::new(Arenas::from(&owned_arenas));
+
+    let mut config = BuildOptions::new("grammar.crunch");
+    config.quiet = true;
+    config.color = TermColor::None;
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        Parser::new(
+            text,
+            Arc::new(config),
+            CurrentFile::new(FileId::new(0), text.len()),
+            &context,
+        )
+        .parse()
+        .map(drop)
+        .map_err(|errors| {
+            errors.errors().next().map_or_else(
+                || (None, "the parser failed without an error".to_owned()),
+                |err| (Some(err.range().start), err.data().to_string()),
+            )
+        })
+    }));
+
+    result.unwrap_or_else(|_| Err((None, "the parser panicked".to_owned())))
+}
+
+/// Words the lexer gives a meaning to, which identifiers may not be
+fn is_reserved(word: &str) -> bool {
+    if !word.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        return false;
+    }
+
+    let mut tokens = TokenStream::new(word, false, false);
+    match (tokens.next(), tokens.next()) {
+        (Some(token), None) => token.ty() != TokenType::Ident,
+        _ => false,
+    }
+}
+
+/// The keywords and punctuation within `source`, leaving out identifiers, literals,
+/// comments and whitespace
+fn structural_tokens(source: &str) -> BTreeSet<String> {
+    TokenStream::new(source, true, true)
+        .filter(|token| {
+            !matches!(
+                token.ty(),
+                TokenType::Error
+                    | TokenType::Comment
+                    | TokenType::DocComment
+                    | TokenType::Newline
+                    | TokenType::Space
+                    | TokenType::Rune
+                    | TokenType::String
+                    | TokenType::Float
+                    | TokenType::Int
+                    | TokenType::Bool
+                    | TokenType::Ident
+            )
+        })
+        .map(|token| token.source().to_owned())
+        .collect()
+}
+
+/// Extracts the ```` ```ebnf ```` blocks from the doc comments of every file in `parser`
+fn documented_snippets(parser: &Path) -> Vec<Documented> {
+    let mut snippets = Vec::new();
+
+    for file in files_with_extension(parser, "rs") {
+        let source = if let Ok(source) = fs::read_to_string(&file) {
+            source
+        } else {
+            continue;
+        };
+
+        let mut current: Option<Documented> = None;
+        for (idx, line) in source.lines().enumerate() {
+            let doc = match line.trim_start().strip_prefix("///") {
+                Some(doc) => doc.strip_prefix(' ').unwrap_or(doc),
+                None => {
+                    current = None;
+                    continue;
+                }
+            };
+
+            match (&mut current, doc.trim()) {
+                (None, "```ebnf") => {
+                    current = Some(Documented {
+                        name: String::new(),
+                        rule: String::new(),
+                        file: file.clone(),
+                        line: idx + 2,
+                    });
+                }
+                (Some(_), "```") => snippets.extend(current.take()),
+                (Some(snippet), _) => {
+                    snippet.rule.push_str(doc);
+                    snippet.rule.push('\n');
+                }
+                (None, _) => {}
+            }
+        }
+    }
+
+    snippets
+}
+
+fn files_with_extension(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().map_or(false, |ext| ext == extension))
+        .collect();
+    files.sort();
+
+    files
+}
@@ -3,7 +3,7 @@
 extern crate alloc;
 
 use alloc::{sync::Arc, vec, vec::Vec};
-use core::{fmt, iter::FromIterator};
+use core::{fmt, iter::FromIterator, mem};
 use crunch_shared::{
     config::EmissionKind,
     context::ContextDatabase,
@@ -13,22 +13,24 @@ use crunch_shared::{
     trees::{
         ast::Integer,
         hir::{
-            BinaryOp, Binding, Block as HirBlock, Block, Break, Cast, CompOp, Expr,
-            ExternFunc as HirExternFunc, FuncArg, FuncCall, Function as HirFunction, Item,
-            Literal as HirLiteral, LiteralVal as HirLiteralVal, Match, MatchArm, Pattern,
-            Reference, Return, Stmt, TypeId, TypeKind as HirTypeKind, Var as HirVar, VarDecl,
+            ArgValue, BinaryOp, Binding, Block as HirBlock, Block, Break, Cast, CompOp,
+            Enum as HirEnum, Expr, ExprKind, ExtendBlock, ExternFunc as HirExternFunc,
+            FormatSegment, FormatSpec, FuncArg, FuncCall, Function as HirFunction, Item, ItemId,
+            Literal as HirLiteral, LiteralVal as HirLiteralVal, Loop, Match, MatchArm, MethodCall,
+            Pattern, Place, PlaceKind, Range, Reference, Return, Stmt, Trait, TypeId,
+            TypeKind as HirTypeKind, UnaryOp, Var as HirVar, VarDecl, VariantCtor,
         },
         mir::{
             Assign, BasicBlock, BlockId, Constant, DefaultSwitchCase, ExternFunc, FnCall, FuncId,
             Function, Instruction, Mir, Rval, SwitchCase, Terminator, Type, Value, Var, VarId,
             Variable,
         },
-        ItemPath, Ref,
+        CallConv, ItemPath, Ref, Sign,
     },
-    utils::{HashMap, Hasher, Upcast},
+    utils::{HashMap, HashSet, Hasher, Upcast},
     visitors::hir::{ExprVisitor, ItemVisitor, StmtVisitor, TypeVisitor},
 };
-use crunch_typecheck::TypecheckDatabase;
+use crunch_typecheck::{Impls, TypecheckDatabase};
 use ladder::HirDatabase;
 
 #[salsa::query_group(MirDatabaseStorage)]
@@ -83,6 +85,17 @@ struct BlockState {
     current_block: BlockId,
 }
 
+/// The blocks that `break` and `continue` jump to within a loop
+#[derive(Debug, Clone)]
+struct LoopContext {
+    /// The start of each iteration, which checks the loop's condition if it has one
+    header: BlockId,
+    /// The block jumped to by a `break`
+    broken: BlockId,
+    /// The argument the broken value is passed to, if the loop has a value
+    broken_arg: Option<(VarId, Type)>,
+}
+
 pub struct MirBuilder<'db> {
     functions: Vec<Function>,
     external_functions: Vec<ExternFunc>,
@@ -91,8 +104,29 @@ pub struct MirBuilder<'db> {
     current_block: BlockId,
     // TODO: Custom struct w/ function arg & ret types
     function_names: HashMap<ItemPath, (FuncId, Type)>,
+    /// The arguments of every function, used to match up calls with named, default and
+    /// variadic arguments
+    function_args: HashMap<ItemPath, &'db [FuncArg<'db>]>,
+    /// Every enum that's been declared, used to lay out their types and find the
+    /// discriminants of their variants
+    enums: HashMap<ItemId, HirEnum>,
+    /// The methods of every type, used to find the function a method call refers to
+    impls: Impls<'db>,
+    /// Every generic function, which are only lowered once for each set of types they're
+    /// called with
+    generic_functions: HashMap<ItemPath, HirFunction<'db>>,
+    /// Instances of generic functions that have been called but not lowered yet, along
+    /// with their mangled names and the types their generics were given
+    instances: Vec<(HirFunction<'db>, ItemPath, HashMap<ItemId, TypeId>)>,
+    /// The types given to the generics of the function instance currently being lowered
+    subst: HashMap<ItemId, TypeId>,
+    /// The loops enclosing the statement currently being lowered, innermost last
+    loops: Vec<LoopContext>,
     func_counter: FuncId,
     variables: Vec<HashMap<Var, Variable>>,
+    /// The mutable variables, which are held as pointers to the stack slots that their
+    /// values live within
+    stack_slots: HashSet<VarId>,
     var_counter: VarId,
     // TODO: Give MirBuilder access to the type engine for type resolution or make a final pass in the engine to resolve types
     // TODO: Salsa for types?
@@ -107,8 +141,16 @@ impl<'db> MirBuilder<'db> {
             blocks: Vec::new(),
             current_block: BlockId::new(0),
             function_names: HashMap::with_hasher(Hasher::default()),
+            function_args: HashMap::with_hasher(Hasher::default()),
+            enums: HashMap::with_hasher(Hasher::default()),
+            impls: Impls::new(),
+            generic_functions: HashMap::with_hasher(Hasher::default()),
+            instances: Vec::new(),
+            subst: HashMap::with_hasher(Hasher::default()),
+            loops: Vec::new(),
             func_counter: FuncId::new(0),
             variables: Vec::new(),
+            stack_slots: HashSet::with_hasher(Hasher::default()),
             var_counter: VarId::new(0),
             db,
         }
@@ -116,18 +158,69 @@ impl<'db> MirBuilder<'db> {
 
     pub fn lower(mut self, items: &[&'db Item<'db>]) -> MirResult<Mir> {
         self.with_scope(|builder| {
-            builder.function_names = HashMap::from_iter(items.iter().map(|item| match item {
-                &&Item::Function(HirFunction { ref name, ret, .. })
-                | &&Item::ExternFunc(HirExternFunc { ref name, ret, .. }) => (
-                    name.clone(),
-                    (builder.next_func_id(), builder.visit_type(ret)),
-                ),
+            // Enums need to be known before any function signatures can be lowered
+            builder.enums = HashMap::from_iter(items.iter().filter_map(|item| match item {
+                Item::Enum(enum_) => Some((enum_.id, enum_.clone())),
+                _ => None,
             }));
 
+            // Generic functions are given ids as they're instantiated
+            builder.generic_functions =
+                HashMap::from_iter(items.iter().filter_map(|item| match item {
+                    Item::Function(func) if func.is_generic() => {
+                        Some((func.name.clone(), func.clone()))
+                    }
+                    _ => None,
+                }));
+
+            builder.function_names =
+                HashMap::from_iter(items.iter().filter_map(|item| match item {
+                    Item::Function(func) if func.is_generic() => None,
+                    &&Item::Function(HirFunction { ref name, ret, .. })
+                    | &&Item::ExternFunc(HirExternFunc { ref name, ret, .. }) => Some((
+                        name.clone(),
+                        (builder.next_func_id(), builder.visit_type(ret)),
+                    )),
+                    Item::Enum(..) | Item::Trait(..) | Item::ExtendBlock(..) => None,
+                }));
+            builder.function_args =
+                HashMap::from_iter(items.iter().filter_map(|&item| match item {
+                    Item::Function(HirFunction { name, args, .. })
+                    | Item::ExternFunc(HirExternFunc { name, args, .. }) => {
+                        Some((name.clone(), &args[..]))
+                    }
+                    Item::Enum(..) | Item::Trait(..) | Item::ExtendBlock(..) => None,
+                }));
+
+            // Methods are lowered as plain functions that take their receiver first
+            builder.impls = Impls::from_items(items);
+            for item in items {
+                if let Item::ExtendBlock(extend) = item {
+                    for method in extend.methods.iter() {
+                        let id = builder.next_func_id();
+                        let ret = builder.visit_type(method.ret);
+
+                        builder
+                            .function_names
+                            .insert(method.name.clone(), (id, ret));
+                        builder
+                            .function_args
+                            .insert(method.name.clone(), &method.args[..]);
+                    }
+                }
+            }
+
             for item in items {
                 builder.visit_item(item)?;
             }
 
+            // Instances can call other generic functions, queueing up even more instances
+            while let Some((func, name, subst)) = builder.instances.pop() {
+                let outer = mem::replace(&mut builder.subst, subst);
+                builder.lower_function(&func, name)?;
+                builder.subst = outer;
+            }
+
             Ok(())
         })?;
 
@@ -238,22 +331,1251 @@ impl<'db> MirBuilder<'db> {
         self.variables.iter().rev().find_map(|vars| vars.get(&var))
     }
 
-    fn make_block(&mut self, current_block: BlockId, arm: &MatchArm<'db>) -> MirResult<BlockId> {
-        let block = self.next_block();
+    /// Gets the current value of a variable, loading it from its stack slot if it has one
+    fn read_variable(&mut self, var: HirVar) -> Variable {
+        let variable = self
+            .get_variable(var.into())
+            .expect("Attempted to get a variable that doesn't exist")
+            .clone();
+        if !self.stack_slots.contains(&variable.id) {
+            return variable;
+        }
 
-        self.move_to_block(block);
-        self.with_scope(|builder| {
-            for stmt in arm.body.iter() {
-                builder.visit_stmt(stmt)?;
+        let ty = Self::pointee(&variable.ty).clone();
+        let id = self.make_assignment(None, Rval::new(Value::Load(variable.id), ty.clone()));
+
+        Variable::new(id, ty)
+    }
+
+    /// Declares a mutable variable, which lives within a stack slot so that assignments
+    /// to it are seen from every block of the function
+    fn make_stack_slot(&mut self, name: Var, value: Rval) {
+        let value_ty = value.ty.clone();
+        let value = self.make_assignment(None, value);
+
+        let ty = Type::Pointer {
+            pointee: Ref::new(value_ty.clone()),
+            mutable: true,
+        };
+        let slot = self.next_var();
+        self.stack_slots.insert(slot);
+        self.insert_variable(name, Variable::new(slot, ty.clone()));
+
+        // Slots are reserved within the function's entry block so that variables declared
+        // within loops don't grow the stack on every iteration
+        self.get_block_mut(BlockId::new(0))
+            .unwrap()
+            .push(Instruction::Assign(Assign {
+                var: slot,
+                val: Rval::new(Value::Alloca(value_ty), ty.clone()),
+                ty,
+            }));
+        self.current_block_mut().push(Instruction::Store {
+            pointer: slot,
+            value,
+        });
+    }
+
+    /// References a mutable variable by its stack slot so that writes through the reference
+    /// are seen by the variable, returning `None` if `expr` isn't a mutable variable
+    fn slot_reference(&self, expr: &Expr<'db>, mutable: bool) -> Option<Rval> {
+        let var = match expr.kind {
+            ExprKind::Variable(var, _) => var,
+            _ => return None,
+        };
+        let slot = self
+            .get_variable(var.into())
+            .expect("Attempted to get a variable that doesn't exist");
+        if !self.stack_slots.contains(&slot.id) {
+            return None;
+        }
+
+        let ty = Type::Reference {
+            referee: Ref::new(Self::pointee(&slot.ty).clone()),
+            mutable,
+        };
+        Some(Rval::new(Value::Variable(slot.id), ty))
+    }
+
+    /// Gets a pointer to the place being assigned to
+    fn place_ptr(&mut self, place: &Place<'db>) -> MirResult<Variable> {
+        let (address, element) = match &place.kind {
+            &PlaceKind::Variable(var) => {
+                let slot = self
+                    .get_variable(var.into())
+                    .expect("Attempted to get a variable that doesn't exist")
+                    .clone();
+                debug_assert!(
+                    self.stack_slots.contains(&slot.id),
+                    "Only mutable variables can be assigned to",
+                );
+
+                return Ok(slot);
             }
 
-            Ok(())
-        })?;
+            PlaceKind::Deref(pointer) => {
+                let pointer = self
+                    .visit_expr(pointer)?
+                    .expect("Received no value where one was expected");
+                let ty = pointer.ty.clone();
+
+                return Ok(Variable::new(self.make_assignment(None, pointer), ty));
+            }
+
+            PlaceKind::Field { base, index } => {
+                let tuple = self.projected_ptr(base)?;
+                let element = Self::pointee(&tuple.ty)
+                    .tuple_elements()
+                    .expect("Only tuples have fields")[*index as usize]
+                    .clone();
+
+                (Value::FieldPtr(tuple.id, *index), element)
+            }
+
+            PlaceKind::Index { base, index } => {
+                let indexee = self.projected_ptr(base)?;
+                let indexee_ty = Self::pointee(&indexee.ty).clone();
+                let element = Self::indexed_element(&indexee_ty).clone();
+
+                // Constant indices into arrays were already bounds checked during type checking
+                if let (Some(index), Type::Array { .. }) =
+                    (Self::constant_index(index), &indexee_ty)
+                {
+                    (Value::FieldPtr(indexee.id, index), element)
+                } else {
+                    // Slices already point to their elements, so the slice itself is indexed
+                    let indexee = if let Type::Slice { .. } = indexee_ty {
+                        let slice = Rval::new(Value::Load(indexee.id), indexee_ty.clone());
+                        Variable::new(self.make_assignment(None, slice), indexee_ty)
+                    } else {
+                        indexee
+                    };
+                    let index = self.checked_index(&indexee, index)?;
+
+                    (Value::IndexPtr(indexee.id, index), element)
+                }
+            }
+        };
+
+        let ty = Type::Pointer {
+            pointee: Ref::new(element),
+            mutable: true,
+        };
+        let id = self.make_assignment(None, Rval::new(address, ty.clone()));
+
+        Ok(Variable::new(id, ty))
+    }
+
+    /// Gets a pointer to the tuple, array or slice that a field or element is taken from,
+    /// following any references that it's behind
+    fn projected_ptr(&mut self, base: &Place<'db>) -> MirResult<Variable> {
+        let mut pointer = match base.kind {
+            // Mutable variables are pointers to their stack slots and immutable ones can only
+            // be projected out of when they hold a reference, so both are already pointers
+            PlaceKind::Variable(var) => self
+                .get_variable(var.into())
+                .expect("Attempted to get a variable that doesn't exist")
+                .clone(),
+
+            _ => self.place_ptr(base)?,
+        };
+
+        while let Type::Reference { .. } = Self::pointee(&pointer.ty) {
+            let reference = Self::pointee(&pointer.ty).clone();
+            let id =
+                self.make_assignment(None, Rval::new(Value::Load(pointer.id), reference.clone()));
+
+            pointer = Variable::new(id, reference);
+        }
+
+        Ok(pointer)
+    }
+
+    fn make_block(
+        &mut self,
+        current_block: BlockId,
+        arm: &MatchArm<'db>,
+        end_block: BlockId,
+        return_arg: Option<&(VarId, Type)>,
+    ) -> MirResult<BlockId> {
+        let block = self.next_block();
+
+        self.move_to_block(block);
+        let passed_val = self.lower_block_value(&arm.body)?;
+        self.jump_with_value(end_block, return_arg, passed_val);
 
         self.move_to_block(current_block);
         Ok(block)
     }
 
+    /// Lowers a block within its own scope, returning the value of its last statement
+    fn lower_block_value(&mut self, block: &Block<&'db Stmt<'db>>) -> MirResult<Option<Rval>> {
+        self.with_scope(|builder| match block.block.split_last() {
+            Some((last, stmts)) => {
+                for stmt in stmts {
+                    builder.lower_stmt(stmt)?;
+                }
+
+                builder.visit_stmt(last)
+            }
+
+            None => Ok(None),
+        })
+    }
+
+    /// Lowers a statement whose value goes unused, keeping it around for its side effects
+    fn lower_stmt(&mut self, stmt: &'db Stmt<'db>) -> MirResult<()> {
+        if let Some(value) = self.visit_stmt(stmt)? {
+            self.make_assignment(None, value);
+        }
+
+        Ok(())
+    }
+
+    /// Ends the current block with a jump to `target`, passing `value` as the target's
+    /// argument if it takes one. When there's no value to pass the current block must have
+    /// diverged, so nothing can reach the end of it
+    fn jump_with_value(
+        &mut self,
+        target: BlockId,
+        target_arg: Option<&(VarId, Type)>,
+        value: Option<Rval>,
+    ) {
+        match (target_arg, value) {
+            (Some((arg, ty)), Some(value)) => {
+                let value = self.make_assignment(None, value);
+                let predecessor = self.current_block;
+
+                self.current_block_mut()
+                    .set_terminator(Terminator::Jump(target, vec![value]));
+                self.get_block_mut(target).unwrap().push_argument(
+                    *arg,
+                    Variable::new(value, ty.clone()),
+                    predecessor,
+                );
+            }
+
+            (Some(_), None) => self
+                .current_block_mut()
+                .set_terminator(Terminator::Unreachable),
+
+            (None, _) => self
+                .current_block_mut()
+                .set_terminator(Terminator::Jump(target, Vec::new())),
+        }
+    }
+
+    /// Gives a block an argument of the given type, unless the type has no values to pass
+    fn block_argument(
+        &mut self,
+        block: BlockId,
+        ty: &Type,
+        predecessors: usize,
+    ) -> Option<(VarId, Type)> {
+        if ty.is_unit() || ty.is_absurd() {
+            return None;
+        }
+
+        let id = self.next_var();
+        self.get_block_mut(block)
+            .unwrap()
+            .make_argument(Variable::new(id, ty.clone()), predecessors);
+
+        Some((id, ty.clone()))
+    }
+
+    /// Gets the id of a function provided by the runtime, declaring it the first time
+    /// it's used
+    fn runtime_function(&mut self, name: &str, args: &[Type], ret: Type) -> FuncId {
+        let name = ItemPath::new(self.db.context().strings().intern(name));
+        if let Some(&(id, _)) = self.function_names.get(&name) {
+            return id;
+        }
+
+        let id = self.next_func_id();
+        let args = args
+            .iter()
+            .map(|ty| Variable {
+                id: self.next_var(),
+                ty: ty.clone(),
+            })
+            .collect();
+
+        self.function_names.insert(name.clone(), (id, ret.clone()));
+        self.external_functions.push(ExternFunc {
+            id,
+            name,
+            args,
+            ret,
+            callconv: CallConv::C,
+        });
+
+        id
+    }
+
+    /// The value of a binary operation between two variables of type `ty`
+    #[rustfmt::skip]
+    fn binop_value(&mut self, op: BinaryOp, lhs: VarId, rhs: VarId, ty: &Type) -> Value {
+        match op {
+            BinaryOp::Add    => Value::Add(lhs, rhs),
+            BinaryOp::Sub    => Value::Sub(lhs, rhs),
+            BinaryOp::Mult   => Value::Mul(lhs, rhs),
+            BinaryOp::Div    => Value::Div(lhs, rhs),
+            BinaryOp::Mod    => Value::Rem(lhs, rhs),
+            BinaryOp::BitAnd => Value::BitAnd(lhs, rhs),
+            BinaryOp::BitOr  => Value::BitOr(lhs, rhs),
+            BinaryOp::BitXor => Value::BitXor(lhs, rhs),
+            BinaryOp::Shl    => Value::Shl(lhs, rhs),
+            BinaryOp::Shr    => Value::Shr(lhs, rhs),
+            BinaryOp::Pow    => self.pow(lhs, rhs, ty),
+
+            BinaryOp::And | BinaryOp::Or => {
+                unreachable!("short-circuiting operators are desugared during lowering")
+            }
+        }
+    }
+
+    /// Raises `base` to the power of `exponent` with a runtime call, widening both to
+    /// 64 bits and narrowing the result back to `ty`
+    fn pow(&mut self, base: VarId, exponent: VarId, ty: &Type) -> Value {
+        let (name, wide) = if ty.is_signed() {
+            ("crunch_pow_i64", Type::I64)
+        } else {
+            ("crunch_pow_u64", Type::U64)
+        };
+
+        let (base, exponent) = if *ty == wide {
+            (base, exponent)
+        } else {
+            (
+                self.make_assignment(
+                    None,
+                    Rval::new(Value::Cast(base, wide.clone()), wide.clone()),
+                ),
+                self.make_assignment(
+                    None,
+                    Rval::new(Value::Cast(exponent, wide.clone()), wide.clone()),
+                ),
+            )
+        };
+
+        let function = self.runtime_function(name, &[wide.clone(), wide.clone()], wide.clone());
+        let call = Value::Call(FnCall {
+            function,
+            args: vec![base, exponent],
+        });
+
+        if *ty == wide {
+            call
+        } else {
+            let result = self.make_assignment(None, Rval::new(call, wide));
+            Value::Cast(result, ty.clone())
+        }
+    }
+
+    /// The value of a comparison between two variables
+    #[rustfmt::skip]
+    fn comparison_value(op: CompOp, lhs: VarId, rhs: VarId) -> Value {
+        match op {
+            CompOp::Equal        => Value::Eq(lhs, rhs),
+            CompOp::NotEqual     => Value::Neq(lhs, rhs),
+            CompOp::Less         => Value::Less(lhs, rhs),
+            CompOp::LessEqual    => Value::LessEq(lhs, rhs),
+            CompOp::Greater      => Value::Greater(lhs, rhs),
+            CompOp::GreaterEqual => Value::GreaterEq(lhs, rhs),
+        }
+    }
+
+    /// Gets the value of an index that's known at compile time
+    fn constant_index(index: &Expr<'db>) -> Option<u64> {
+        match index.kind {
+            ExprKind::Literal(HirLiteral {
+                val: HirLiteralVal::Integer(Integer { bits, .. }),
+                ..
+            }) => Some(bits as u64),
+            _ => None,
+        }
+    }
+
+    /// Gets the type of the value behind a pointer or reference
+    fn pointee(ty: &Type) -> &Type {
+        match ty {
+            Type::Pointer { pointee, .. } => pointee,
+            Type::Reference { referee, .. } => referee,
+
+            ty => unreachable!(
+                "Only pointers and references can be dereferenced, not {:?}",
+                ty
+            ),
+        }
+    }
+
+    /// Gets the type of the elements of an array or slice, which may be behind a reference
+    fn indexed_element(ty: &Type) -> &Type {
+        match ty {
+            Type::Array { element, .. } | Type::Slice { element } => element,
+            Type::Reference { referee, .. } => Self::indexed_element(referee),
+            Type::Pointer { pointee, .. } => Self::indexed_element(pointee),
+
+            ty => unreachable!("Only arrays and slices can be indexed, not {:?}", ty),
+        }
+    }
+
+    /// Gets the number of elements within an array or slice, which may be behind a reference
+    fn length(var: &Variable) -> Rval {
+        let mut ty = &var.ty;
+        while let Type::Reference { referee: inner, .. } | Type::Pointer { pointee: inner, .. } = ty
+        {
+            ty = inner;
+        }
+
+        match *ty {
+            Type::Array { length, .. } => Rval::new(
+                Value::Const(Constant::Integer {
+                    sign: Sign::Positive,
+                    bits: length as u128,
+                }),
+                Type::U64,
+            ),
+
+            // Slices are a pointer to their first element followed by their length
+            Type::Slice { .. } => Rval::new(Value::Extract(var.id, 1), Type::U64),
+
+            ref ty => unreachable!("Only arrays and slices have a length, not {:?}", ty),
+        }
+    }
+
+    /// Evaluates an index into `indexee`, stopping the program at runtime if it's out
+    /// of bounds
+    fn checked_index(&mut self, indexee: &Variable, index: &'db Expr<'db>) -> MirResult<VarId> {
+        let index = self.unsigned_index(index)?;
+        let length = Self::length(indexee);
+        let length = self.make_assignment(None, length);
+
+        let check =
+            self.runtime_function("crunch_check_bounds", &[Type::U64, Type::U64], Type::Unit);
+        self.current_block_mut().push(Instruction::Call(FnCall {
+            function: check,
+            args: vec![index, length],
+        }));
+
+        Ok(index)
+    }
+
+    /// Takes the elements of the array or slice held by `var` that are within `range` as
+    /// a slice, stopping the program at runtime if the range is out of bounds
+    fn slice(&mut self, var: HirVar, range: &Range<'db>) -> MirResult<Rval> {
+        let sliceable = self.sliceable_variable(var);
+        let length = Self::length(&sliceable);
+        let length = self.make_assignment(None, length);
+
+        let start = match range.start {
+            Some(start) => self.unsigned_index(start)?,
+            None => self.make_assignment(None, Self::u64_const(0)),
+        };
+        let end = match range.end {
+            Some(end) if range.kind.is_inclusive() => {
+                let end = self.unsigned_index(end)?;
+                let one = self.make_assignment(None, Self::u64_const(1));
+
+                self.make_assignment(None, Rval::new(Value::Add(end, one), Type::U64))
+            }
+            Some(end) => self.unsigned_index(end)?,
+            None => length,
+        };
+
+        let check = self.runtime_function(
+            "crunch_check_slice",
+            &[Type::U64, Type::U64, Type::U64],
+            Type::Unit,
+        );
+        self.current_block_mut().push(Instruction::Call(FnCall {
+            function: check,
+            args: vec![start, end, length],
+        }));
+
+        Ok(self.make_slice(&sliceable, start, end))
+    }
+
+    /// Makes a slice of the elements of `sliceable` from `start` up to `end`, which must
+    /// already be known to be in bounds
+    fn make_slice(&mut self, sliceable: &Variable, start: VarId, end: VarId) -> Rval {
+        let element = Self::indexed_element(&sliceable.ty).clone();
+
+        let elements = Type::Pointer {
+            pointee: Ref::new(element.clone()),
+            mutable: false,
+        };
+        let elements = self.make_assignment(
+            None,
+            Rval::new(Value::IndexPtr(sliceable.id, start), elements),
+        );
+        let length = self.make_assignment(None, Rval::new(Value::Sub(end, start), Type::U64));
+
+        Rval::new(
+            Value::Aggregate(vec![elements, length]),
+            Type::Slice {
+                element: Ref::new(element),
+            },
+        )
+    }
+
+    /// Gets the array or slice held by a variable in a form that can be sliced, which is
+    /// either a pointer to an array or a slice itself, following any references that it's
+    /// behind
+    fn sliceable_variable(&mut self, var: HirVar) -> Variable {
+        let variable = self
+            .get_variable(var.into())
+            .expect("Attempted to get a variable that doesn't exist")
+            .clone();
+
+        // Mutable variables already live within their stack slots
+        let mut pointer = if self.stack_slots.contains(&variable.id) {
+            variable
+        } else {
+            self.pointer_to(variable)
+        };
+
+        while let Type::Reference { .. } = Self::pointee(&pointer.ty) {
+            let reference = Self::pointee(&pointer.ty).clone();
+            let id =
+                self.make_assignment(None, Rval::new(Value::Load(pointer.id), reference.clone()));
+
+            pointer = Variable::new(id, reference);
+        }
+
+        // Slices already point to their elements, so the slice itself is indexed
+        if let Type::Slice { .. } = Self::pointee(&pointer.ty) {
+            let ty = Self::pointee(&pointer.ty).clone();
+            let slice = self.make_assignment(None, Rval::new(Value::Load(pointer.id), ty.clone()));
+
+            pointer = Variable::new(slice, ty);
+        }
+
+        pointer
+    }
+
+    /// Gets the value of an array or slice in a form that can be sliced, see
+    /// `sliceable_variable`
+    fn sliceable_value(&mut self, value: Variable) -> Variable {
+        if let Type::Slice { .. } = value.ty {
+            value
+        } else {
+            self.pointer_to(value)
+        }
+    }
+
+    /// Makes a reference to a value, which is spilled onto the stack if it isn't already
+    /// held in memory
+    fn pointer_to(&mut self, value: Variable) -> Variable {
+        let ty = Type::Reference {
+            referee: Ref::new(value.ty),
+            mutable: false,
+        };
+        let pointer = Value::GetPointer {
+            var: value.id,
+            mutable: false,
+            aliasable: true,
+        };
+
+        Variable::new(
+            self.make_assignment(None, Rval::new(pointer, ty.clone())),
+            ty,
+        )
+    }
+
+    /// Evaluates an index and casts it to a `u64`
+    fn unsigned_index(&mut self, index: &'db Expr<'db>) -> MirResult<VarId> {
+        let index = self
+            .visit_expr(index)?
+            .expect("Received no value where one was expected");
+        let index_ty = index.ty.clone();
+        let index = self.make_assignment(None, index);
+
+        // Negative indices become huge when cast, so they're caught along with the rest
+        if index_ty.is_u64() {
+            Ok(index)
+        } else {
+            Ok(self.make_assignment(None, Rval::new(Value::Cast(index, Type::U64), Type::U64)))
+        }
+    }
+
+    fn u64_const(value: u64) -> Rval {
+        Rval::new(
+            Value::Const(Constant::Integer {
+                sign: Sign::Positive,
+                bits: value as u128,
+            }),
+            Type::U64,
+        )
+    }
+
+    /// Branches to `failure` if `condition` is false, otherwise continuing in a new block
+    fn branch_or_fail(&mut self, condition: VarId, failure: BlockId) {
+        let current_block = self.current_block;
+        let matched = self.next_block();
+
+        self.get_block_mut(current_block)
+            .unwrap()
+            .set_terminator(Terminator::Branch {
+                condition,
+                truthy: matched,
+                falsy: failure,
+            });
+        self.move_to_block(matched);
+    }
+
+    /// Lays out an enum as a tagged union of its variants' payloads, with the enum's
+    /// generics replaced by the types they were given
+    fn enum_type(&mut self, id: ItemId, generics: &[TypeId]) -> Type {
+        let enum_ = &self.enums[&id];
+        let variants: Vec<Vec<TypeId>> = enum_
+            .variants
+            .iter()
+            .map(|variant| variant.elements.clone())
+            .collect();
+
+        let mut subst = self.subst.clone();
+        subst.extend(
+            enum_
+                .generics
+                .iter()
+                .map(|generic| generic.id)
+                .zip(generics.iter().copied()),
+        );
+        let outer = mem::replace(&mut self.subst, subst);
+
+        // FIXME: Recursive enums will never finish lowering
+        let ty = Type::Enum {
+            variants: variants
+                .into_iter()
+                .map(|elements| elements.into_iter().map(|ty| self.visit_type(ty)).collect())
+                .collect(),
+        };
+        self.subst = outer;
+
+        ty
+    }
+
+    /// Gets the discriminant and payload types of the variant a pattern's path refers to,
+    /// taking the payload types from the type of the matched value
+    fn pattern_variant(&self, path: &ItemPath, scrutinee: &Type) -> (u64, Vec<Type>) {
+        let (&variant, enum_path) = path.split_last().expect("Paths are never empty");
+        let (discriminant, _) = self
+            .enums
+            .values()
+            .find(|enum_| *enum_.name == *enum_path)
+            .and_then(|enum_| enum_.variant(variant))
+            .expect("Variant patterns are resolved during type checking");
+
+        // Variants can be matched through references to them
+        let mut scrutinee = scrutinee;
+        while let Type::Reference { referee, .. } = scrutinee {
+            scrutinee = referee;
+        }
+
+        let payload = scrutinee
+            .enum_variants()
+            .and_then(|variants| variants.get(discriminant))
+            .cloned()
+            .expect("Variant patterns are only matched against enums");
+
+        (discriminant as u64, payload)
+    }
+
+    /// Gets the function a call to a generic function refers to, queueing up an instance
+    /// of the function for the types its generics were given if there isn't one yet
+    fn instantiate(&mut self, func: &ItemPath, generics: &[TypeId]) -> ItemPath {
+        let generic = self.generic_functions[func].clone();
+        let context = self.db.context();
+
+        // The given types may refer to the generics of the function being lowered
+        let generics: Vec<TypeId> = generics
+            .iter()
+            .map(|&ty| crunch_typecheck::substitute(context, ty, &self.subst, None))
+            .collect();
+        let name = crunch_typecheck::mangle(context, &self.enums, func, &generics);
+
+        if !self.function_names.contains_key(&name) {
+            let subst = self.call_subst(func, &generics);
+
+            let id = self.next_func_id();
+            let outer = mem::replace(&mut self.subst, subst);
+            let ret = self.visit_type(generic.ret);
+            let subst = mem::replace(&mut self.subst, outer);
+
+            self.function_names.insert(name.clone(), (id, ret));
+            self.instances.push((generic, name.clone(), subst));
+        }
+
+        name
+    }
+
+    /// Maps the generics of the function being called to the types given to them by the
+    /// call, where a function without generics has nothing to fill in
+    fn call_subst(&self, func: &ItemPath, generics: &[TypeId]) -> HashMap<ItemId, TypeId> {
+        match self.generic_functions.get(func) {
+            Some(generic) => generic
+                .generics
+                .iter()
+                .map(|generic| generic.id)
+                .zip(generics.iter().map(|&ty| {
+                    crunch_typecheck::substitute(self.db.context(), ty, &self.subst, None)
+                }))
+                .collect(),
+
+            None => HashMap::with_hasher(Hasher::default()),
+        }
+    }
+
+    /// Makes a constant holding an enum discriminant
+    fn discriminant_const(&mut self, discriminant: u64) -> VarId {
+        self.make_assignment(
+            None,
+            Rval::new(
+                Value::Const(Constant::Integer {
+                    sign: Sign::Positive,
+                    bits: discriminant as u128,
+                }),
+                Type::DISCRIMINANT,
+            ),
+        )
+    }
+
+    /// Lowers the tests required for `pattern` to match `scrutinee`, jumping to `failure`
+    /// if any of them fail. Afterwards the current block is the one where the pattern has
+    /// matched and all of its bindings are in scope
+    fn lower_pattern(
+        &mut self,
+        loc: Location,
+        pattern: &Pattern,
+        scrutinee: VarId,
+        ty: &Type,
+        failure: BlockId,
+    ) -> MirResult<()> {
+        match pattern {
+            Pattern::Wildcard | Pattern::Rest => {}
+
+            &Pattern::Ident(ident) => {
+                self.make_assignment(
+                    Var::User(ident),
+                    Rval::new(Value::Variable(scrutinee), ty.clone()),
+                );
+            }
+
+            Pattern::Bind(ident, pattern) => {
+                self.make_assignment(
+                    Var::User(*ident),
+                    Rval::new(Value::Variable(scrutinee), ty.clone()),
+                );
+                self.lower_pattern(loc, pattern, scrutinee, ty, failure)?;
+            }
+
+            Pattern::Literal(lit) => {
+                let case = self.visit_literal(loc, lit)?.unwrap();
+                let case = self.make_assignment(None, case);
+                let condition =
+                    self.make_assignment(None, Rval::new(Value::Eq(scrutinee, case), Type::Bool));
+
+                self.branch_or_fail(condition, failure);
+            }
+
+            Pattern::Slice(elements) => {
+                let element = Self::indexed_element(ty).clone();
+                let rest = elements.iter().position(|p| match p {
+                    Pattern::Rest => true,
+                    Pattern::Bind(_, p) => p.is_rest(),
+                    _ => false,
+                });
+
+                let length = Self::length(&Variable::new(scrutinee, ty.clone()));
+                let length = self.make_assignment(None, length);
+
+                // The lengths of arrays are checked during type checking, but the lengths of
+                // slices are only known at runtime
+                if let Type::Slice { .. } = ty {
+                    let required = elements.len() - rest.iter().count();
+                    let required = self.make_assignment(None, Self::u64_const(required as u64));
+                    let comparison = if rest.is_some() {
+                        Value::GreaterEq(length, required)
+                    } else {
+                        Value::Eq(length, required)
+                    };
+                    let condition = self.make_assignment(None, Rval::new(comparison, Type::Bool));
+
+                    self.branch_or_fail(condition, failure);
+                }
+
+                for (idx, pattern) in elements.iter().enumerate() {
+                    // Elements after the rest pattern are counted from the end
+                    let from_end = match rest {
+                        Some(rest) if idx == rest => {
+                            // `rest @ ..` binds the elements no other pattern matched as a slice
+                            if let &Pattern::Bind(name, _) = pattern {
+                                let start = self.make_assignment(None, Self::u64_const(idx as u64));
+                                let after = (elements.len() - idx - 1) as u64;
+                                let after = self.make_assignment(None, Self::u64_const(after));
+                                let end = self.make_assignment(
+                                    None,
+                                    Rval::new(Value::Sub(length, after), Type::U64),
+                                );
+
+                                let sliceable =
+                                    self.sliceable_value(Variable::new(scrutinee, ty.clone()));
+                                let slice = self.make_slice(&sliceable, start, end);
+                                self.make_assignment(Var::User(name), slice);
+                            }
+
+                            continue;
+                        }
+                        Some(rest) if idx > rest => Some((elements.len() - idx) as u64),
+                        _ => None,
+                    };
+
+                    let value = match (ty, from_end) {
+                        (&Type::Array { length, .. }, Some(from_end)) => {
+                            Value::Extract(scrutinee, length - from_end)
+                        }
+                        (Type::Array { .. }, None) => Value::Extract(scrutinee, idx as u64),
+
+                        (_, Some(from_end)) => {
+                            let from_end = self.make_assignment(None, Self::u64_const(from_end));
+                            let index = self.make_assignment(
+                                None,
+                                Rval::new(Value::Sub(length, from_end), Type::U64),
+                            );
+
+                            Value::Index(scrutinee, index)
+                        }
+                        (_, None) => {
+                            let index = self.make_assignment(None, Self::u64_const(idx as u64));
+                            Value::Index(scrutinee, index)
+                        }
+                    };
+
+                    let value = self.make_assignment(None, Rval::new(value, element.clone()));
+                    self.lower_pattern(loc, pattern, value, &element, failure)?;
+                }
+            }
+
+            Pattern::Or(alternatives) => {
+                let mut bindings = Vec::new();
+                pattern.bindings(&mut bindings);
+
+                let mut alternative_block = self.current_block;
+                let join = self.next_block();
+                let mut join_args = Vec::with_capacity(bindings.len());
+
+                // Pass every bound variable into the joining block as an argument so that
+                // the arm's body sees the same variable no matter which alternative matched
+                for (idx, alternative) in alternatives.iter().enumerate() {
+                    let next_alternative = if idx + 1 == alternatives.len() {
+                        failure
+                    } else {
+                        self.next_block()
+                    };
+
+                    self.move_to_block(alternative_block);
+                    let args = self.with_scope(|builder| -> MirResult<Vec<Variable>> {
+                        builder.lower_pattern(loc, alternative, scrutinee, ty, next_alternative)?;
+
+                        Ok(bindings
+                            .iter()
+                            .map(|&var| builder.get_variable(Var::User(var)).unwrap().clone())
+                            .collect::<Vec<_>>())
+                    })?;
+
+                    if idx == 0 {
+                        for arg in args.iter() {
+                            let id = self.next_var();
+                            self.get_block_mut(join).unwrap().make_argument(
+                                Variable::new(id, arg.ty.clone()),
+                                alternatives.len(),
+                            );
+
+                            join_args.push(Variable::new(id, arg.ty.clone()));
+                        }
+                    }
+
+                    let predecessor = self.current_block;
+                    for (join_arg, arg) in join_args.iter().zip(args.iter()) {
+                        self.get_block_mut(join).unwrap().push_argument(
+                            join_arg.id,
+                            arg.clone(),
+                            predecessor,
+                        );
+                    }
+                    self.current_block_mut().set_terminator(Terminator::Jump(
+                        join,
+                        args.iter().map(|arg| arg.id).collect(),
+                    ));
+
+                    alternative_block = next_alternative;
+                }
+
+                self.move_to_block(join);
+                for (&var, arg) in bindings.iter().zip(join_args) {
+                    self.insert_variable(Var::User(var), arg);
+                }
+            }
+
+            Pattern::Range { start, end, kind } => {
+                if let Some(start) = start {
+                    let start = self.visit_literal(loc, start)?.unwrap();
+                    let start = self.make_assignment(None, start);
+                    let condition = self.make_assignment(
+                        None,
+                        Rval::new(Value::GreaterEq(scrutinee, start), Type::Bool),
+                    );
+
+                    self.branch_or_fail(condition, failure);
+                }
+
+                if let Some(end) = end {
+                    let end = self.visit_literal(loc, end)?.unwrap();
+                    let end = self.make_assignment(None, end);
+                    let comparison = if kind.is_inclusive() {
+                        Value::LessEq(scrutinee, end)
+                    } else {
+                        Value::Less(scrutinee, end)
+                    };
+                    let condition = self.make_assignment(None, Rval::new(comparison, Type::Bool));
+
+                    self.branch_or_fail(condition, failure);
+                }
+            }
+
+            Pattern::ItemPath(path) | Pattern::Variant { path, .. } => {
+                let (discriminant, payload) = self.pattern_variant(path, ty);
+
+                let tag = self.make_assignment(
+                    None,
+                    Rval::new(Value::Discriminant(scrutinee), Type::DISCRIMINANT),
+                );
+                let expected = self.discriminant_const(discriminant);
+                let condition =
+                    self.make_assignment(None, Rval::new(Value::Eq(tag, expected), Type::Bool));
+                self.branch_or_fail(condition, failure);
+
+                if let Pattern::Variant { elements, .. } = pattern {
+                    self.lower_payload(loc, elements, &payload, scrutinee, discriminant, failure)?;
+                }
+            }
+
+            Pattern::Tuple(elements) => {
+                let fields = ty
+                    .tuple_elements()
+                    .expect("Tuple patterns always match against tuples")
+                    .to_vec();
+
+                // Elements after a `..` are matched against the last fields of the tuple
+                let rest = elements.iter().position(Pattern::is_rest);
+                let (prefix, suffix) = match rest {
+                    Some(rest) => (&elements[..rest], &elements[rest + 1..]),
+                    None => (&elements[..], &[][..]),
+                };
+                let suffix_start = fields.len() - suffix.len();
+
+                let indexed = prefix.iter().enumerate().chain(
+                    suffix
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, element)| (suffix_start + idx, element)),
+                );
+                for (index, element) in indexed {
+                    let field = &fields[index];
+                    let value = self.make_assignment(
+                        None,
+                        Rval::new(Value::Extract(scrutinee, index as u64), field.clone()),
+                    );
+                    self.lower_pattern(loc, element, value, field, failure)?;
+                }
+            }
+
+            // Structs are rejected during type checking
+            Pattern::Struct { .. } => unreachable!("Unsupported pattern: {:?}", pattern),
+        }
+
+        Ok(())
+    }
+
+    /// Binds and tests the payload of a variant that `scrutinee` is already known to hold
+    fn lower_payload(
+        &mut self,
+        loc: Location,
+        elements: &[Pattern],
+        payload: &[Type],
+        scrutinee: VarId,
+        discriminant: u64,
+        failure: BlockId,
+    ) -> MirResult<()> {
+        for (index, (element, ty)) in elements.iter().zip(payload).enumerate() {
+            let value = self.make_assignment(
+                None,
+                Rval::new(
+                    Value::Payload {
+                        var: scrutinee,
+                        discriminant,
+                        index: index as u64,
+                    },
+                    ty.clone(),
+                ),
+            );
+
+            self.lower_pattern(loc, element, value, ty, failure)?;
+        }
+
+        Ok(())
+    }
+
+    /// Lowers a match over an enum into a switch on its discriminant, which is possible
+    /// when no arm has a guard and every arm either matches anything or matches a variant
+    /// while only binding its payload
+    fn lower_variant_switch(
+        &mut self,
+        loc: Location,
+        arms: &[MatchArm<'db>],
+        condition: VarId,
+        condition_type: &Type,
+        end_block: BlockId,
+        return_arg: Option<&(VarId, Type)>,
+    ) -> MirResult<()> {
+        let switch_block = self.current_block;
+        let tag = self.make_assignment(
+            None,
+            Rval::new(Value::Discriminant(condition), Type::DISCRIMINANT),
+        );
+
+        let mut cases = Vec::with_capacity(arms.len());
+        let mut matched = Vec::with_capacity(arms.len());
+        let mut default = None;
+
+        for arm in arms {
+            // Nothing after a catch-all arm or an already matched variant can be reached
+            if default.is_some() {
+                break;
+            }
+
+            let variant = match &arm.bind.pattern {
+                Pattern::ItemPath(path) | Pattern::Variant { path, .. } => {
+                    let (discriminant, payload) = self.pattern_variant(path, condition_type);
+                    if matched.contains(&discriminant) {
+                        continue;
+                    }
+                    matched.push(discriminant);
+
+                    Some((discriminant, payload))
+                }
+                _ => None,
+            };
+
+            self.move_to_block(switch_block);
+            let case = variant
+                .as_ref()
+                .map(|&(discriminant, _)| self.discriminant_const(discriminant));
+            let case_block = self.next_block();
+
+            match case {
+                Some(case) => cases.push(SwitchCase {
+                    condition: case,
+                    block: case_block,
+                    args: Vec::new(),
+                }),
+                None => {
+                    default = Some(DefaultSwitchCase {
+                        block: case_block,
+                        args: Vec::new(),
+                    })
+                }
+            }
+
+            self.with_scope(|builder| -> MirResult<()> {
+                match (&arm.bind.pattern, variant) {
+                    (Pattern::Variant { elements, .. }, Some((discriminant, payload))) => {
+                        // Payload patterns are irrefutable, so they can never fail
+                        builder.lower_payload(
+                            loc,
+                            elements,
+                            &payload,
+                            condition,
+                            discriminant,
+                            case_block,
+                        )?;
+                    }
+
+                    (pattern, None) => builder.lower_pattern(
+                        loc,
+                        pattern,
+                        condition,
+                        condition_type,
+                        case_block,
+                    )?,
+
+                    _ => {}
+                }
+
+                let passed_val = builder.lower_block_value(&arm.body)?;
+                builder.jump_with_value(end_block, return_arg, passed_val);
+
+                Ok(())
+            })?;
+        }
+
+        // Matches are exhaustive, so without a catch-all arm every variant has a case
+        let default = match default {
+            Some(default) => default,
+            None => {
+                let unreachable = self.next_block();
+                self.current_block_mut()
+                    .set_terminator(Terminator::Unreachable);
+
+                DefaultSwitchCase {
+                    block: unreachable,
+                    args: Vec::new(),
+                }
+            }
+        };
+
+        self.move_to_block(switch_block);
+        self.current_block_mut().set_terminator(Terminator::Switch {
+            condition: tag,
+            default,
+            cases,
+        });
+
+        Ok(())
+    }
+
+    /// Lowers a match into a chain of tests, where each arm tests its pattern and guard
+    /// and falls through to the next arm if either of them fail
+    fn lower_match_arms(
+        &mut self,
+        loc: Location,
+        arms: &[MatchArm<'db>],
+        condition: VarId,
+        condition_type: &Type,
+        end_block: BlockId,
+        return_arg: Option<&(VarId, Type)>,
+    ) -> MirResult<()> {
+        let mut test_block = self.current_block;
+        for arm in arms {
+            let next_arm = self.next_block();
+            self.move_to_block(test_block);
+
+            self.with_scope(|builder| -> MirResult<()> {
+                builder.lower_pattern(
+                    loc,
+                    &arm.bind.pattern,
+                    condition,
+                    condition_type,
+                    next_arm,
+                )?;
+
+                if let Some(guard) = arm.guard {
+                    let guard = builder
+                        .visit_expr(guard)?
+                        .expect("Received nothing where a value was expected");
+                    let guard = builder.make_assignment(None, guard);
+
+                    builder.branch_or_fail(guard, next_arm);
+                }
+
+                let passed_val = builder.lower_block_value(&arm.body)?;
+                builder.jump_with_value(end_block, return_arg, passed_val);
+
+                Ok(())
+            })?;
+
+            test_block = next_arm;
+        }
+        self.move_to_block(test_block);
+
+        // Matches are exhaustive, so falling through every arm is impossible
+        self.current_block_mut()
+            .set_terminator(Terminator::Unreachable);
+
+        Ok(())
+    }
+
+    /// Lowers the values given to a function's arguments, where `subst` fills in the
+    /// generics of the function being called
+    fn lower_args(
+        &mut self,
+        values: Vec<ArgValue<'db>>,
+        params: &[FuncArg<'db>],
+        subst: HashMap<ItemId, TypeId>,
+    ) -> MirResult<Vec<VarId>> {
+        // The arguments given so far, which are what the callee's defaults are able to see
+        let mut callee_scope = HashMap::with_hasher(Hasher::default());
+        let mut args = Vec::with_capacity(values.len());
+
+        for (value, param) in values.into_iter().zip(params) {
+            let arg = match value {
+                ArgValue::Value(expr) => {
+                    let val = self
+                        .visit_expr(expr)?
+                        .expect("Received no value where one was expected");
+
+                    let ty = val.ty.clone();
+                    Variable::new(self.make_assignment(None, val), ty)
+                }
+
+                // Defaults belong to the callee, so they're lowered with only its bindings in
+                // scope instead of the caller's variables
+                ArgValue::Default(expr) => {
+                    let outer_variables =
+                        mem::replace(&mut self.variables, vec![callee_scope.clone()]);
+                    let outer_subst = mem::replace(&mut self.subst, subst.clone());
+                    let val = self.visit_expr(expr);
+                    self.subst = outer_subst;
+                    self.variables = outer_variables;
+
+                    let val = val?.expect("Received no value where one was expected");
+                    let ty = val.ty.clone();
+                    Variable::new(self.make_assignment(None, val), ty)
+                }
+
+                // Variadic arguments are collected into an array on the stack, which the
+                // function is given a slice of
+                ArgValue::Variadic(exprs) => {
+                    let mut elements = Vec::with_capacity(exprs.len());
+                    for expr in exprs {
+                        let element = self
+                            .visit_expr(expr)?
+                            .expect("Received no value where one was expected");
+                        elements.push(self.make_assignment(None, element));
+                    }
+
+                    let outer = mem::replace(&mut self.subst, subst.clone());
+                    let slice_ty = self.visit_type(param.kind);
+                    self.subst = outer;
+
+                    let array_ty = Type::Array {
+                        element: Ref::new(Self::indexed_element(&slice_ty).clone()),
+                        length: elements.len() as u64,
+                    };
+                    let length = self.make_assignment(None, Self::u64_const(elements.len() as u64));
+                    let array = self.make_assignment(
+                        None,
+                        Rval::new(Value::Aggregate(elements), array_ty.clone()),
+                    );
+
+                    let array = self.pointer_to(Variable::new(array, array_ty));
+                    let start = self.make_assignment(None, Self::u64_const(0));
+                    let slice = self.make_slice(&array, start, length);
+
+                    let ty = slice.ty.clone();
+                    Variable::new(self.make_assignment(None, slice), ty)
+                }
+            };
+
+            callee_scope.insert(param.name.into(), arg.clone());
+            args.push(arg.id);
+        }
+
+        Ok(args)
+    }
+
     fn push_scope(&mut self) {
         self.variables.push(HashMap::with_hasher(Hasher::default()));
     }
@@ -296,16 +1618,14 @@ impl<'db> MirBuilder<'db> {
 
         blocks(self, popped, result)
     }
-}
 
-impl<'db> ItemVisitor<'db> for MirBuilder<'db> {
-    type Output = MirResult<()>;
-
-    fn visit_func(&mut self, func: &HirFunction<'db>) -> Self::Output {
+    /// Lowers a function's body under the given name, which differs from the function's
+    /// own for instances of generic functions
+    fn lower_function(&mut self, func: &HirFunction<'db>, name: ItemPath) -> MirResult<()> {
         self.with_blocks(
             |builder| {
                 builder.with_scope(|builder| {
-                    let id = builder.get_function_id(&func.name);
+                    let id = builder.get_function_id(&name);
 
                     let mut args = Vec::with_capacity(func.args.len());
                     for &FuncArg { name, kind, .. } in func.args.iter() {
@@ -316,14 +1636,27 @@ impl<'db> ItemVisitor<'db> for MirBuilder<'db> {
                     }
 
                     for stmt in func.body.iter() {
-                        builder.visit_stmt(stmt)?;
+                        builder.lower_stmt(stmt)?;
                     }
 
-                    Ok((id, func.name.clone(), args, builder.visit_type(func.ret)))
+                    // Unit functions return once the end of their body is reached, any other
+                    // function has to return before then
+                    let ret = builder.visit_type(func.ret);
+                    let end = builder.current_block_mut();
+                    if end.terminator.is_none() {
+                        if ret.is_unit() {
+                            end.set_terminator(Terminator::Return(None));
+                        } else if !end.is_empty() {
+                            end.set_terminator(Terminator::Unreachable);
+                        }
+                    }
+
+                    Ok((id, name, args, ret))
                 })
             },
-            |builder, blocks, res| {
+            |builder, mut blocks, res| {
                 let (id, name, args, ret) = res?;
+                builder.free_formatted_strings(&mut blocks);
 
                 // FIXME: Use a better system of a "current block" that's an `Option<BlockId>` with operations
                 //        automatically creating a new one if needed and not relying on one to already exist.
@@ -331,7 +1664,7 @@ impl<'db> ItemVisitor<'db> for MirBuilder<'db> {
                 //        creating blocks for future use
                 // FIXME: https://github.com/rust-lang/rust/issues/43244
                 let blocks = HashMap::from_iter(blocks.into_iter().filter_map(|block| {
-                    if block.is_empty() {
+                    if block.is_empty() && block.terminator.is_none() {
                         crunch_shared::warn!(
                             "Generated an empty MIR basic block: {} in {}",
                             block.id,
@@ -342,24 +1675,274 @@ impl<'db> ItemVisitor<'db> for MirBuilder<'db> {
                     } else {
                         Some((block.id, block))
                     }
-                }));
+                }));
+
+                let func = Function {
+                    id,
+                    name,
+                    args,
+                    ret,
+                    blocks,
+                };
+                builder.functions.push(func);
+
+                // TODO: Return the function's id?
+                Ok(())
+            },
+        )
+    }
+
+    /// Frees the strings built by format strings once they're no longer used
+    ///
+    /// A formatted string belongs to the function that built it, which frees it right after
+    /// the last use of it or any of its copies. Strings that escape the function by being
+    /// returned, stored, passed between blocks or given to a call that could hand them back
+    /// are left alone, since nothing tracks what happens to them after that
+    fn free_formatted_strings(&mut self, blocks: &mut Vec<BasicBlock>) {
+        let finish = ItemPath::new(self.db.context().strings().intern("crunch_fmt_finish"));
+        let finish = match self.function_names.get(&finish) {
+            Some(&(finish, _)) => finish,
+            None => return,
+        };
+
+        // Maps every variable holding a formatted string to the variable it was built into
+        let mut strings: HashMap<VarId, VarId> = HashMap::with_hasher(Hasher::default());
+        for inst in blocks.iter().flat_map(|block| block.instructions.iter()) {
+            if let Instruction::Assign(Assign { var, val, .. }) = inst {
+                if matches!(&val.val, Value::Call(call) if call.function == finish) {
+                    strings.insert(*var, *var);
+                }
+            }
+        }
+
+        // Copies can come before the variables they copy from, so this runs until no new
+        // copies are found
+        loop {
+            let mut changed = false;
+            for inst in blocks.iter().flat_map(|block| block.instructions.iter()) {
+                if let Instruction::Assign(Assign { var, val, .. }) = inst {
+                    if let Value::Variable(copied) = val.val {
+                        if let Some(&string) = strings.get(&copied) {
+                            changed |= strings.insert(*var, string).is_none();
+                        }
+                    }
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        let mut usages = Vec::new();
+        for block in blocks.iter() {
+            for (_, incoming) in block.args.iter() {
+                usages.extend(incoming.iter().map(|(var, _)| var.id));
+            }
+
+            for inst in block.instructions.iter() {
+                match inst {
+                    Instruction::Assign(Assign { val, ty, .. }) => match &val.val {
+                        Value::Variable(_) => {}
+                        // Calls that return scalars can't give the string back
+                        Value::Call(_)
+                            if ty.is_integer() || matches!(ty, Type::Bool | Type::Unit) => {}
+                        value => value.variable_usages(&mut usages),
+                    },
+                    Instruction::Call(_) => {}
+                    Instruction::Store { .. } => inst.variable_usages(&mut usages),
+                }
+            }
+
+            match &block.terminator {
+                Some(Terminator::Return(Some(var))) => usages.push(*var),
+                Some(Terminator::Jump(_, args)) => usages.extend(args.iter().copied()),
+                Some(Terminator::Switch { cases, default, .. }) => {
+                    for case in cases.iter() {
+                        usages.extend(case.args.iter().copied());
+                    }
+                    usages.extend(default.args.iter().copied());
+                }
+                _ => {}
+            }
+        }
+
+        let escaped: HashSet<VarId> = HashSet::from_iter(
+            usages
+                .drain(..)
+                .filter_map(|var| strings.get(&var).copied()),
+        );
+        strings.retain(|_, string| !escaped.contains(string));
+        if strings.is_empty() {
+            return;
+        }
+
+        // The strings each block uses before building them itself and the ones it builds
+        let mut used = Vec::with_capacity(blocks.len());
+        let mut built = Vec::with_capacity(blocks.len());
+        for block in blocks.iter() {
+            let mut used_here = HashSet::with_hasher(Hasher::default());
+            let mut built_here = HashSet::with_hasher(Hasher::default());
+
+            for inst in block.instructions.iter() {
+                inst.variable_usages(&mut usages);
+                for var in usages.drain(..) {
+                    if let Some(&string) = strings.get(&var) {
+                        if !built_here.contains(&string) {
+                            used_here.insert(string);
+                        }
+                    }
+                }
+
+                if let Instruction::Assign(Assign { var, .. }) = inst {
+                    if strings.get(var) == Some(var) {
+                        built_here.insert(*var);
+                    }
+                }
+            }
+
+            used.push(used_here);
+            built.push(built_here);
+        }
+
+        let successors = |block: &BasicBlock| {
+            block
+                .terminator
+                .as_ref()
+                .map_or_else(Vec::new, Terminator::successors)
+        };
+
+        // The strings that are still needed when entering and leaving each block
+        let mut live_in = vec![HashSet::with_hasher(Hasher::default()); blocks.len()];
+        let mut live_out = vec![HashSet::with_hasher(Hasher::default()); blocks.len()];
+        loop {
+            let mut changed = false;
+            for (idx, block) in blocks.iter().enumerate().rev() {
+                let out: HashSet<VarId> = successors(block)
+                    .into_iter()
+                    .flat_map(|successor| live_in[successor.0 as usize].iter().copied())
+                    .collect();
+                let mut in_ = used[idx].clone();
+                in_.extend(out.difference(&built[idx]).copied());
+
+                if in_ != live_in[idx] || out != live_out[idx] {
+                    live_in[idx] = in_;
+                    live_out[idx] = out;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        let free = self.runtime_function("crunch_fmt_free", &[Type::String], Type::Unit);
+        let free_string = |string| {
+            Instruction::Call(FnCall {
+                function: free,
+                args: vec![string],
+            })
+        };
+
+        for idx in 0..live_in.len() {
+            // Strings that aren't needed after the block are freed after their last use
+            let mut frees = Vec::new();
+            for &string in live_in[idx].iter().chain(built[idx].iter()) {
+                if live_out[idx].contains(&string) {
+                    continue;
+                }
+
+                let last_use = blocks[idx].instructions.iter().rposition(|inst| {
+                    inst.variable_usages(&mut usages);
+                    let used = usages
+                        .drain(..)
+                        .any(|var| strings.get(&var) == Some(&string));
+
+                    used || matches!(inst, Instruction::Assign(Assign { var, .. }) if *var == string)
+                });
+                frees.push((last_use.map_or(0, |last_use| last_use + 1), string));
+            }
+
+            // Inserting from the back keeps the earlier positions valid
+            frees.sort_unstable_by(|lhs, rhs| rhs.cmp(lhs));
+            for (position, string) in frees {
+                blocks[idx]
+                    .instructions
+                    .insert(position, free_string(string));
+            }
+
+            // Strings that some successors still need but others don't are freed on the way to
+            // the ones that don't. Jumps only have a single successor, so they never need this
+            let from = blocks[idx].id;
+            let mut terminator = blocks[idx].terminator.take();
+            let mut edges = Vec::new();
+            match terminator.as_mut() {
+                Some(Terminator::Branch { truthy, falsy, .. }) => {
+                    edges.push((truthy, None));
+                    edges.push((falsy, None));
+                }
+                Some(Terminator::Switch { cases, default, .. }) => {
+                    for case in cases.iter_mut() {
+                        edges.push((&mut case.block, Some(&mut case.args)));
+                    }
+                    edges.push((&mut default.block, Some(&mut default.args)));
+                }
+                _ => {}
+            }
+
+            for (target, args) in edges {
+                let mut dead: Vec<VarId> = live_out[idx]
+                    .difference(&live_in[target.0 as usize])
+                    .copied()
+                    .collect();
+                if dead.is_empty() {
+                    continue;
+                }
+                dead.sort_unstable();
+
+                let edge = BlockId(blocks.len() as u64);
+                let mut block = BasicBlock::new(edge, None);
+                block.instructions.extend(dead.into_iter().map(free_string));
+                block.set_terminator(Terminator::Jump(
+                    *target,
+                    args.map_or_else(Vec::new, mem::take),
+                ));
+
+                for (_, incoming) in blocks[target.0 as usize].args.iter_mut() {
+                    for (_, predecessor) in incoming.iter_mut() {
+                        if *predecessor == from {
+                            *predecessor = edge;
+                        }
+                    }
+                }
 
-                let func = Function {
-                    id,
-                    name,
-                    args,
-                    ret,
-                    blocks,
-                };
-                builder.functions.push(func);
+                blocks.push(block);
+                *target = edge;
+            }
 
-                // TODO: Return the function's id?
-                Ok(())
-            },
-        )
+            blocks[idx].terminator = terminator;
+        }
+    }
+}
+
+impl<'db> ItemVisitor<'db> for MirBuilder<'db> {
+    type Output = MirResult<()>;
+
+    fn visit_func(&mut self, func: &HirFunction<'db>) -> Self::Output {
+        // Generic functions are lowered once they're called
+        if func.is_generic() {
+            self.generic_functions
+                .entry(func.name.clone())
+                .or_insert_with(|| func.clone());
+
+            return Ok(());
+        }
+
+        self.lower_function(func, func.name.clone())
     }
 
-    fn visit_extern_func(&mut self, func: &HirExternFunc) -> Self::Output {
+    fn visit_extern_func(&mut self, func: &HirExternFunc<'db>) -> Self::Output {
         let id = self.get_function_id(&func.name);
 
         let mut args = Vec::with_capacity(func.args.len());
@@ -370,9 +1953,13 @@ impl<'db> ItemVisitor<'db> for MirBuilder<'db> {
             args.push(Variable { id, ty });
         }
 
+        // External functions are linked by their own name, not the path of the module
+        // they were declared in
+        let name = ItemPath::new(*func.name.last().expect("Functions should have a name"));
+
         let func = ExternFunc {
             id,
-            name: func.name.clone(),
+            name,
             args,
             ret: self.visit_type(func.ret),
             callconv: func.callconv,
@@ -382,6 +1969,26 @@ impl<'db> ItemVisitor<'db> for MirBuilder<'db> {
         // TODO: Return the function's id
         Ok(())
     }
+
+    // Enums have no code of their own, they only need to be known for their types
+    fn visit_enum(&mut self, enum_: &HirEnum) -> Self::Output {
+        self.enums.entry(enum_.id).or_insert_with(|| enum_.clone());
+
+        Ok(())
+    }
+
+    // Trait methods are only signatures, their implementations are lowered instead
+    fn visit_trait(&mut self, _trait: &Trait<'db>) -> Self::Output {
+        Ok(())
+    }
+
+    fn visit_extend_block(&mut self, extend: &ExtendBlock<'db>) -> Self::Output {
+        for method in extend.methods.iter() {
+            self.visit_func(method)?;
+        }
+
+        Ok(())
+    }
 }
 
 impl<'db> StmtVisitor<'db> for MirBuilder<'db> {
@@ -402,7 +2009,12 @@ impl<'db> StmtVisitor<'db> for MirBuilder<'db> {
         let val = self
             .visit_expr(var.value)?
             .expect("Assigned nothing to a variable");
-        self.make_assignment(Some(var.name.into()), val);
+
+        if var.mutable {
+            self.make_stack_slot(var.name.into(), val);
+        } else {
+            self.make_assignment(Some(var.name.into()), val);
+        }
 
         Ok(None)
     }
@@ -437,16 +2049,134 @@ impl<'db> ExprVisitor<'db> for MirBuilder<'db> {
         Ok(None)
     }
 
-    fn visit_break(&mut self, _loc: Location, _value: &Break<'_>) -> Self::Output {
-        todo!()
+    fn visit_break(&mut self, _loc: Location, value: &Break<'db>) -> Self::Output {
+        let value = match value.val {
+            Some(value) => self.visit_expr(value)?,
+            None => None,
+        };
+
+        let LoopContext {
+            broken, broken_arg, ..
+        } = self
+            .loops
+            .last()
+            .cloned()
+            .expect("Breaks are only lowered within loops");
+        self.jump_with_value(broken, broken_arg.as_ref(), value);
+
+        self.verify_current_block()?;
+        self.next_block();
+
+        Ok(None)
     }
 
     fn visit_continue(&mut self, _loc: Location) -> Self::Output {
-        todo!()
+        let header = self
+            .loops
+            .last()
+            .expect("Continues are only lowered within loops")
+            .header;
+        self.current_block_mut()
+            .set_terminator(Terminator::Jump(header, Vec::new()));
+
+        self.verify_current_block()?;
+        self.next_block();
+
+        Ok(None)
     }
 
-    fn visit_loop(&mut self, _loc: Location, _body: &Block<&'db Stmt<'db>>) -> Self::Output {
-        todo!()
+    fn visit_loop(
+        &mut self,
+        _loc: Location,
+        &Loop {
+            cond,
+            ref body,
+            ref then,
+            ref else_,
+            ty,
+        }: &Loop<'db>,
+    ) -> Self::Output {
+        let current_block = self.current_block;
+        let loop_type = self.visit_type(ty);
+
+        let header = self.next_block();
+        let end_block = self.next_block();
+        let end_arg = self.block_argument(end_block, &loop_type, 1);
+
+        // Breaks run the `else` clause before leaving the loop, passing the broken value
+        // through it
+        let (broken, broken_arg) = if else_.is_some() {
+            let else_block = self.next_block();
+            let else_arg = self.block_argument(else_block, &loop_type, 1);
+
+            (else_block, else_arg)
+        } else {
+            (end_block, end_arg.clone())
+        };
+
+        self.move_to_block(current_block);
+        self.current_block_mut()
+            .set_terminator(Terminator::Jump(header, Vec::new()));
+        self.move_to_block(header);
+
+        // Once the condition is false the `then` clause is run, giving the loop its value
+        if let Some(cond) = cond {
+            let cond = self
+                .visit_expr(cond)?
+                .expect("Received nothing where a value was expected");
+            let cond = self.make_assignment(None, cond);
+
+            let cond_block = self.current_block;
+            let finished = self.next_block();
+            self.move_to_block(cond_block);
+            self.branch_or_fail(cond, finished);
+            let body_block = self.current_block;
+
+            self.move_to_block(finished);
+            let value = match then {
+                Some(then) => self.lower_block_value(then)?,
+                None => None,
+            };
+            self.jump_with_value(end_block, end_arg.as_ref(), value);
+
+            self.move_to_block(body_block);
+        }
+
+        self.loops.push(LoopContext {
+            header,
+            broken,
+            broken_arg: broken_arg.clone(),
+        });
+        let lowered = self.with_scope(|builder| {
+            for stmt in body.iter() {
+                builder.lower_stmt(stmt)?;
+            }
+
+            Ok(())
+        });
+        self.loops.pop();
+        lowered?;
+
+        // Reaching the end of the body starts the next iteration
+        self.current_block_mut()
+            .set_terminator(Terminator::Jump(header, Vec::new()));
+
+        if let Some(else_) = else_ {
+            self.move_to_block(broken);
+            self.with_scope(|builder| {
+                for stmt in else_.iter() {
+                    builder.lower_stmt(stmt)?;
+                }
+
+                Ok(())
+            })?;
+
+            let value = broken_arg.map(|(arg, ty)| Rval::new(Value::Variable(arg), ty));
+            self.jump_with_value(end_block, end_arg.as_ref(), value);
+        }
+
+        self.move_to_block(end_block);
+        Ok(end_arg.map(|(arg, ty)| Rval::new(Value::Variable(arg), ty)))
     }
 
     fn visit_match(
@@ -459,19 +2189,8 @@ impl<'db> ExprVisitor<'db> for MirBuilder<'db> {
         let current_block = self.current_block;
         let end_block = self.next_block();
 
-        let (return_var, return_arg_id) = {
-            let return_type = self.visit_type(ty);
-
-            if return_type.is_unit() {
-                (None, None)
-            } else {
-                let id = self.next_var();
-                let block = self.get_block_mut(end_block).unwrap();
-                block.make_argument(Variable::new(id, return_type.clone()), arms.len());
-
-                (Some(Rval::new(Value::Variable(id), return_type)), Some(id))
-            }
-        };
+        let return_type = self.visit_type(ty);
+        let return_arg = self.block_argument(end_block, &return_type, arms.len());
 
         self.move_to_block(current_block);
 
@@ -495,12 +2214,61 @@ impl<'db> ExprVisitor<'db> for MirBuilder<'db> {
 
             (cond, cond_ty)
         };
+        // The condition may have branched, like when it contains a short-circuiting
+        // operator, so the match starts from wherever the condition ended up
+        let current_block = self.current_block;
+
+        // Matches that only pick apart variants can switch on the enum's discriminant
+        let is_enum = match &condition_type {
+            Type::Reference { referee, .. } => referee.is_enum(),
+            ty => ty.is_enum(),
+        };
+        let is_variant_switch = is_enum
+            && arms.iter().all(|arm| {
+                arm.guard.is_none()
+                    && match &arm.bind.pattern {
+                        Pattern::Ident(_) | Pattern::Wildcard | Pattern::ItemPath(_) => true,
+                        Pattern::Variant { elements, .. } => elements
+                            .iter()
+                            .all(|p| matches!(p, Pattern::Ident(_) | Pattern::Wildcard)),
+                        _ => false,
+                    }
+            });
+
+        // Anything beyond simple literal switches is lowered into a chain of tests
+        let needs_tests = arms.iter().any(|arm| {
+            arm.guard.is_some()
+                || !matches!(
+                    arm.bind.pattern,
+                    Pattern::Literal(_) | Pattern::Ident(_) | Pattern::Wildcard
+                )
+        });
 
-        if arms.len() == 2 && condition_type.is_bool() {
+        if is_variant_switch || needs_tests {
+            if is_variant_switch {
+                self.lower_variant_switch(
+                    loc,
+                    arms,
+                    condition,
+                    &condition_type,
+                    end_block,
+                    return_arg.as_ref(),
+                )?;
+            } else {
+                self.lower_match_arms(
+                    loc,
+                    arms,
+                    condition,
+                    &condition_type,
+                    end_block,
+                    return_arg.as_ref(),
+                )?;
+            }
+        } else if arms.len() == 2 && condition_type.is_bool() {
             let (truthy, falsy) = if let [truthy, falsy] = arms.as_slice() {
                 (
-                    self.make_block(current_block, truthy)?,
-                    self.make_block(current_block, falsy)?,
+                    self.make_block(current_block, truthy, end_block, return_arg.as_ref())?,
+                    self.make_block(current_block, falsy, end_block, return_arg.as_ref())?,
                 )
             } else {
                 unreachable!();
@@ -569,33 +2337,12 @@ impl<'db> ExprVisitor<'db> for MirBuilder<'db> {
                         );
                     }
 
-                    Pattern::ItemPath(..) => todo!(),
+                    _ => unreachable!("Complex patterns are lowered by `lower_match_arms`"),
                 }
 
                 self.move_to_block(case_block);
-                let passed_val = self.with_scope(|builder| {
-                    body.iter()
-                        .map(|stmt| builder.visit_stmt(stmt).transpose())
-                        .last()
-                        .flatten()
-                        .transpose()
-                })?;
-
-                if let Some(ret_arg) = return_arg_id {
-                    assert!(return_var.is_some());
-
-                    let passed_val = passed_val.expect("Expected to pass a value to a child block");
-                    let passed_val = self.make_assignment(None, passed_val);
-
-                    self.get_block_mut(case_block)
-                        .unwrap()
-                        .set_terminator(Terminator::Jump(end_block, vec![passed_val]));
-                    self.get_block_mut(end_block).unwrap().push_argument(
-                        ret_arg,
-                        Variable::new(passed_val, condition_type.clone()),
-                        case_block,
-                    );
-                }
+                let passed_val = self.lower_block_value(body)?;
+                self.jump_with_value(end_block, return_arg.as_ref(), passed_val);
             }
 
             self.move_to_block(current_block);
@@ -610,20 +2357,15 @@ impl<'db> ExprVisitor<'db> for MirBuilder<'db> {
         self.move_to_block(end_block);
 
         // TODO: Maybe return unit?
-        Ok(return_var)
+        Ok(return_arg.map(|(arg, ty)| Rval::new(Value::Variable(arg), ty)))
     }
 
     fn visit_variable(&mut self, _loc: Location, var: HirVar, _ty: TypeId) -> Self::Output {
-        let &Variable {
-            id: val,
-            ty: ref var_ty,
-        } = self
-            .get_variable(var.into())
-            .expect("Attempted to get a variable that doesn't exist");
+        let Variable { id, ty } = self.read_variable(var);
 
         Ok(Some(Rval {
-            val: Value::Variable(val),
-            ty: var_ty.clone(),
+            val: Value::Variable(id),
+            ty,
         }))
     }
 
@@ -676,33 +2418,187 @@ impl<'db> ExprVisitor<'db> for MirBuilder<'db> {
         }
     }
 
+    /// Builds up the string piece by piece using the runtime's formatter
+    fn visit_format_string(
+        &mut self,
+        _loc: Location,
+        segments: &[FormatSegment<'db>],
+    ) -> Self::Output {
+        let formatter_ty = Type::Pointer {
+            pointee: Ref::new(Type::U8),
+            mutable: true,
+        };
+
+        let new = self.runtime_function("crunch_fmt_new", &[], formatter_ty.clone());
+        let formatter = self.make_assignment(
+            None,
+            Rval {
+                ty: formatter_ty.clone(),
+                val: Value::Call(FnCall {
+                    function: new,
+                    args: Vec::new(),
+                }),
+            },
+        );
+
+        for segment in segments {
+            let (function, value) = match segment {
+                FormatSegment::Text(text) => {
+                    let function = self.runtime_function(
+                        "crunch_fmt_push_str",
+                        &[formatter_ty.clone(), Type::String],
+                        Type::Unit,
+                    );
+                    let text = self.make_assignment(
+                        None,
+                        Rval {
+                            ty: Type::String,
+                            val: Value::Const(Constant::String(text.to_bytes())),
+                        },
+                    );
+
+                    (function, text)
+                }
+
+                &FormatSegment::Value { expr, spec } => {
+                    let value = self
+                        .visit_expr(expr)?
+                        .expect("Received no value where one was expected");
+
+                    #[rustfmt::skip]
+                    let name = match (spec, &value.ty) {
+                        (FormatSpec::Display, Type::String) => "crunch_fmt_display_str",
+                        (FormatSpec::Display, Type::Bool)   => "crunch_fmt_display_bool",
+                        (FormatSpec::Display, Type::U8)     => "crunch_fmt_display_u8",
+                        (FormatSpec::Display, Type::I8)     => "crunch_fmt_display_i8",
+                        (FormatSpec::Display, Type::U16)    => "crunch_fmt_display_u16",
+                        (FormatSpec::Display, Type::I16)    => "crunch_fmt_display_i16",
+                        (FormatSpec::Display, Type::U32)    => "crunch_fmt_display_u32",
+                        (FormatSpec::Display, Type::I32)    => "crunch_fmt_display_i32",
+                        (FormatSpec::Display, Type::U64)    => "crunch_fmt_display_u64",
+                        (FormatSpec::Display, Type::I64)    => "crunch_fmt_display_i64",
+
+                        (FormatSpec::Debug, Type::String) => "crunch_fmt_debug_str",
+                        (FormatSpec::Debug, Type::Bool)   => "crunch_fmt_debug_bool",
+                        (FormatSpec::Debug, Type::U8)     => "crunch_fmt_debug_u8",
+                        (FormatSpec::Debug, Type::I8)     => "crunch_fmt_debug_i8",
+                        (FormatSpec::Debug, Type::U16)    => "crunch_fmt_debug_u16",
+                        (FormatSpec::Debug, Type::I16)    => "crunch_fmt_debug_i16",
+                        (FormatSpec::Debug, Type::U32)    => "crunch_fmt_debug_u32",
+                        (FormatSpec::Debug, Type::I32)    => "crunch_fmt_debug_i32",
+                        (FormatSpec::Debug, Type::U64)    => "crunch_fmt_debug_u64",
+                        (FormatSpec::Debug, Type::I64)    => "crunch_fmt_debug_i64",
+
+                        (_, ty) => unreachable!("Attempted to format a value of type {:?}", ty),
+                    };
+
+                    let function = self.runtime_function(
+                        name,
+                        &[formatter_ty.clone(), value.ty.clone()],
+                        Type::Unit,
+                    );
+
+                    (function, self.make_assignment(None, value))
+                }
+            };
+
+            self.current_block_mut().push(Instruction::Call(FnCall {
+                function,
+                args: vec![formatter, value],
+            }));
+        }
+
+        let finish = self.runtime_function("crunch_fmt_finish", &[formatter_ty], Type::String);
+        let val = Value::Call(FnCall {
+            function: finish,
+            args: vec![formatter],
+        });
+
+        Ok(Some(Rval {
+            ty: Type::String,
+            val,
+        }))
+    }
+
     fn visit_scope(&mut self, _loc: Location, body: &HirBlock<&'db Stmt<'db>>) -> Self::Output {
-        self.with_scope(|builder| {
-            body.iter()
-                .filter_map(|s| builder.visit_stmt(s).transpose())
-                .last()
-                .transpose()
-        })
+        self.lower_block_value(body)
     }
 
     fn visit_func_call(&mut self, _loc: Location, call: &FuncCall<'db>) -> Self::Output {
+        let name = if self.generic_functions.contains_key(&call.func) {
+            self.instantiate(&call.func, &call.generics)
+        } else {
+            call.func.clone()
+        };
+
         let (function, ty) = self
             .function_names
-            .get(&call.func)
+            .get(&name)
             .expect("Attempted to call a function that doesn't exist")
             .clone();
 
-        let args: Vec<VarId> = call
-            .args
-            .iter()
-            .map(|a| {
-                let val = self
-                    .visit_expr(a)?
-                    .expect("Received no value where one was expected");
+        let params = self
+            .function_args
+            .get(&call.func)
+            .copied()
+            .expect("Attempted to call a function that doesn't exist");
+        let values = call
+            .match_args(params)
+            .expect("Function arguments should have been checked during type checking");
+        let subst = self.call_subst(&call.func, &call.generics);
+        let args = self.lower_args(values, params, subst)?;
 
-                Ok(self.make_assignment(None, val))
-            })
-            .collect::<MirResult<_>>()?;
+        let val = Value::Call(FnCall { function, args });
+
+        Ok(Some(Rval { ty, val }))
+    }
+
+    fn visit_method_call(&mut self, _loc: Location, call: &MethodCall<'db>) -> Self::Output {
+        // Calls on generics are resolved to the methods of the types they were given
+        let receiver_ty =
+            crunch_typecheck::substitute(self.db.context(), call.receiver_ty, &self.subst, None);
+        let name = self
+            .impls
+            .resolve(self.db.context(), receiver_ty, *call.method, call.trait_)
+            .expect("Methods are resolved during type checking")
+            .name
+            .clone();
+        let (function, ty) = self.function_names[&name].clone();
+        let (receiver_arg, params) = self.function_args[&name]
+            .split_first()
+            .expect("Methods always take a receiver");
+
+        let receiver_ty = self.visit_type(receiver_arg.kind);
+        let receiver = match receiver_ty {
+            Type::Reference { mutable, .. } => self.slot_reference(call.receiver, mutable),
+            _ => None,
+        };
+        let receiver = match receiver {
+            Some(receiver) => receiver,
+            None => self
+                .visit_expr(call.receiver)?
+                .expect("Received no value where one was expected"),
+        };
+
+        // Methods taking `&self` can be called on values, which are implicitly referenced
+        let auto_ref = receiver_ty.is_reference() && !receiver.ty.is_reference();
+        let mut receiver = self.make_assignment(None, receiver);
+        if auto_ref {
+            let mutable = matches!(receiver_ty, Type::Reference { mutable: true, .. });
+            let reference = Value::GetPointer {
+                var: receiver,
+                mutable,
+                aliasable: false,
+            };
+
+            receiver = self.make_assignment(None, Rval::new(reference, receiver_ty));
+        }
+
+        let values = call
+            .match_args(params)
+            .expect("Method arguments should have been checked during type checking");
+        let mut args = vec![receiver];
+        args.extend(self.lower_args(values, params, HashMap::with_hasher(Hasher::default()))?);
 
         let val = Value::Call(FnCall { function, args });
 
@@ -712,26 +2608,57 @@ impl<'db> ExprVisitor<'db> for MirBuilder<'db> {
     fn visit_comparison(
         &mut self,
         _loc: Location,
-        _lhs: &'db Expr<'db>,
-        _op: CompOp,
-        _rhs: &'db Expr<'db>,
+        lhs: &'db Expr<'db>,
+        op: CompOp,
+        rhs: &'db Expr<'db>,
     ) -> Self::Output {
-        todo!()
+        let (lhs, rhs) = (
+            self.visit_expr(lhs)?
+                .expect("Received no value where one was expected"),
+            self.visit_expr(rhs)?
+                .expect("Received no value where one was expected"),
+        );
+        assert_eq!(lhs.ty, rhs.ty);
+
+        let (lhs, rhs) = (
+            self.make_assignment(None, lhs),
+            self.make_assignment(None, rhs),
+        );
+
+        Ok(Some(Rval::new(
+            Self::comparison_value(op, lhs, rhs),
+            Type::Bool,
+        )))
     }
 
-    fn visit_assign(&mut self, _loc: Location, var: HirVar, value: &'db Expr<'db>) -> Self::Output {
-        let old_var = self
-            .get_variable(var.into())
-            .expect("Attempted to get a variable that doesn't exist")
-            .clone();
+    fn visit_assign(
+        &mut self,
+        _loc: Location,
+        place: &Place<'db>,
+        op: Option<BinaryOp>,
+        value: &'db Expr<'db>,
+    ) -> Self::Output {
+        let pointer = self.place_ptr(place)?;
+        let ty = Self::pointee(&pointer.ty).clone();
+
         let rval = self
             .visit_expr(value)?
             .expect("Received no value where one was expected");
-        assert_eq!(rval.ty, old_var.ty);
+        assert_eq!(rval.ty, ty);
+        let mut value = self.make_assignment(None, rval);
+
+        // Compound assignments apply their operator to the place's current value
+        if let Some(op) = op {
+            let current =
+                self.make_assignment(None, Rval::new(Value::Load(pointer.id), ty.clone()));
+            let combined = self.binop_value(op, current, value, &ty);
+            value = self.make_assignment(None, Rval::new(combined, ty));
+        }
 
-        let ty = rval.ty.clone();
-        let id = self.make_assignment(None, rval);
-        self.insert_variable(var.into(), Variable { id, ty });
+        self.current_block_mut().push(Instruction::Store {
+            pointer: pointer.id,
+            value,
+        });
 
         // TODO: Return unit?
         Ok(None)
@@ -758,14 +2685,25 @@ impl<'db> ExprVisitor<'db> for MirBuilder<'db> {
             self.make_assignment(None, rhs),
         );
 
-        #[rustfmt::skip]
-        let val = match op {
-            BinaryOp::Add  => Value::Add(lhs, rhs),
-            BinaryOp::Sub  => Value::Sub(lhs, rhs),
-            BinaryOp::Mult => Value::Mul(lhs, rhs),
-            BinaryOp::Div  => Value::Div(lhs, rhs),
+        let val = self.binop_value(op, lhs, rhs, &ty);
+        Ok(Some(Rval { ty, val }))
+    }
+
+    fn visit_unary(
+        &mut self,
+        _loc: Location,
+        op: UnaryOp,
+        operand: &'db Expr<'db>,
+    ) -> Self::Output {
+        let operand = self
+            .visit_expr(operand)?
+            .expect("Received no value where one was expected");
+        let ty = operand.ty.clone();
 
-            _ => todo!(),
+        let val = match op {
+            UnaryOp::Positive => return Ok(Some(operand)),
+            UnaryOp::Negative => Value::Neg(self.make_assignment(None, operand)),
+            UnaryOp::Not => Value::Not(self.make_assignment(None, operand)),
         };
 
         Ok(Some(Rval { ty, val }))
@@ -786,10 +2724,17 @@ impl<'db> ExprVisitor<'db> for MirBuilder<'db> {
         _loc: Location,
         Reference { mutable, reference }: &Reference<'db>,
     ) -> Self::Output {
+        if let Some(reference) = self.slot_reference(reference, *mutable) {
+            return Ok(Some(reference));
+        }
+
         let reference = self
             .visit_expr(reference)?
             .expect("Received no value where one was expected");
-        let ty = reference.ty.clone();
+        let ty = Type::Reference {
+            referee: Ref::new(reference.ty.clone()),
+            mutable: *mutable,
+        };
 
         let pointee = self.make_assignment(None, reference);
         let val = Value::GetPointer {
@@ -801,8 +2746,131 @@ impl<'db> ExprVisitor<'db> for MirBuilder<'db> {
         Ok(Some(Rval { ty, val }))
     }
 
-    fn visit_index(&mut self, _loc: Location, _var: HirVar, _index: &Expr<'db>) -> Self::Output {
-        todo!()
+    fn visit_deref(&mut self, _loc: Location, pointer: &'db Expr<'db>) -> Self::Output {
+        let pointer = self
+            .visit_expr(pointer)?
+            .expect("Received no value where one was expected");
+        let ty = Self::pointee(&pointer.ty).clone();
+        let pointer = self.make_assignment(None, pointer);
+
+        Ok(Some(Rval::new(Value::Load(pointer), ty)))
+    }
+
+    fn visit_index(&mut self, _loc: Location, var: HirVar, index: &'db Expr<'db>) -> Self::Output {
+        if let ExprKind::Range(range) = &index.kind {
+            return self.slice(var, range).map(Some);
+        }
+
+        let indexee = self.read_variable(var);
+        let element = Self::indexed_element(&indexee.ty).clone();
+
+        // Constant indices into arrays were already bounds checked during type checking
+        if let (Some(index), Type::Array { .. }) = (Self::constant_index(index), &indexee.ty) {
+            return Ok(Some(Rval::new(Value::Extract(indexee.id, index), element)));
+        }
+
+        let index = self.checked_index(&indexee, index)?;
+        Ok(Some(Rval::new(Value::Index(indexee.id, index), element)))
+    }
+
+    fn visit_length(&mut self, _loc: Location, var: HirVar) -> Self::Output {
+        let var = self.read_variable(var);
+
+        Ok(Some(Self::length(&var)))
+    }
+
+    fn visit_variant(
+        &mut self,
+        _loc: Location,
+        &VariantCtor {
+            enum_id,
+            variant,
+            ref args,
+            ty,
+        }: &VariantCtor<'db>,
+    ) -> Self::Output {
+        let (discriminant, _) = self.enums[&enum_id]
+            .variant(variant)
+            .expect("Variants are resolved during type checking");
+
+        let mut payload = Vec::with_capacity(args.len());
+        for &arg in args.iter() {
+            let arg = self
+                .visit_expr(arg)?
+                .expect("Received nothing where a value was expected");
+            payload.push(self.make_assignment(None, arg));
+        }
+
+        Ok(Some(Rval::new(
+            Value::Variant {
+                discriminant: discriminant as u64,
+                payload,
+            },
+            self.visit_type(ty),
+        )))
+    }
+
+    fn visit_range(&mut self, _loc: Location, _range: &Range<'db>) -> Self::Output {
+        unreachable!("Ranges are only used as indices, which are lowered by `visit_index`")
+    }
+
+    fn visit_array(
+        &mut self,
+        _loc: Location,
+        elements: &[&'db Expr<'db>],
+        element: TypeId,
+    ) -> Self::Output {
+        let ty = Type::Array {
+            element: Ref::new(self.visit_type(element)),
+            length: elements.len() as u64,
+        };
+
+        let mut values = Vec::with_capacity(elements.len());
+        for &elem in elements {
+            let elem = self
+                .visit_expr(elem)?
+                .expect("Received nothing where a value was expected");
+            values.push(self.make_assignment(None, elem));
+        }
+
+        Ok(Some(Rval::new(Value::Aggregate(values), ty)))
+    }
+
+    fn visit_tuple(&mut self, _loc: Location, elements: &[&'db Expr<'db>]) -> Self::Output {
+        let mut fields = Vec::with_capacity(elements.len());
+        let mut types = Vec::with_capacity(elements.len());
+        for &elem in elements {
+            let elem = self
+                .visit_expr(elem)?
+                .expect("Received nothing where a value was expected");
+
+            types.push(elem.ty.clone());
+            fields.push(self.make_assignment(None, elem));
+        }
+
+        Ok(Some(Rval::new(
+            Value::Aggregate(fields),
+            Type::Tuple(types),
+        )))
+    }
+
+    fn visit_tuple_field(
+        &mut self,
+        _loc: Location,
+        tuple: &'db Expr<'db>,
+        index: u64,
+    ) -> Self::Output {
+        let tuple = self
+            .visit_expr(tuple)?
+            .expect("Received nothing where a value was expected");
+        let field = tuple
+            .ty
+            .tuple_elements()
+            .expect("Fields can only be taken from tuples")[index as usize]
+            .clone();
+
+        let tuple = self.make_assignment(None, tuple);
+        Ok(Some(Rval::new(Value::Extract(tuple, index), field)))
     }
 }
 
@@ -810,7 +2878,7 @@ impl<'db> TypeVisitor<'db> for MirBuilder<'db> {
     type Output = Type;
 
     fn visit_type(&mut self, ty: TypeId) -> Self::Output {
-        match self.db.context().get_hir_type(ty).unwrap().kind {
+        match self.db.context().get_hir_type(ty).unwrap().kind.clone() {
             HirTypeKind::Variable(ty) => self.visit_type(ty),
             HirTypeKind::Integer { signed, width } => {
                 match (signed.unwrap_or(true), width.unwrap_or(32)) {
@@ -841,10 +2909,37 @@ impl<'db> TypeVisitor<'db> for MirBuilder<'db> {
             HirTypeKind::Slice { element } => Type::Slice {
                 element: Ref::new(self.visit_type(element)),
             },
+            HirTypeKind::Tuple(elements) => Type::Tuple(
+                elements
+                    .into_iter()
+                    .map(|element| self.visit_type(element))
+                    .collect(),
+            ),
             HirTypeKind::Reference { referee, mutable } => Type::Reference {
                 referee: Ref::new(self.visit_type(referee)),
                 mutable,
             },
+            HirTypeKind::Enum { id, generics } => self.enum_type(id, &generics),
+            HirTypeKind::Generic { id, .. } => {
+                let given = *self
+                    .subst
+                    .get(&id)
+                    .expect("Generics are only used within instances of generic items");
+
+                self.visit_type(given)
+            }
+
+            HirTypeKind::Rune
+            | HirTypeKind::Float { .. }
+            | HirTypeKind::Func { .. }
+            | HirTypeKind::Trait(_)
+            | HirTypeKind::Union(_)
+            | HirTypeKind::Intersection(_)
+            | HirTypeKind::Not(_)
+            | HirTypeKind::Const { .. }
+            | HirTypeKind::Range { .. } => {
+                unreachable!("Unsupported types are reported during type checking")
+            }
 
             // FIXME: This should be an error for the user
             HirTypeKind::Unknown => {
@@ -862,6 +2957,7 @@ impl fmt::Debug for MirBuilder<'_> {
             .field("external_functions", &self.external_functions)
             .field("blocks", &self.blocks)
             .field("function_names", &self.function_names)
+            .field("function_args", &self.function_args)
             .field("func_counter", &self.func_counter)
             .field("var_counter", &self.var_counter)
             .finish()
@@ -1,5 +1,6 @@
-use crate::{FlattenExternals, Parser as ParserBackend};
-use alloc::sync::Arc;
+use crate::{FlattenExternals, Parser as ParserBackend, ParserReturn, TextEdit};
+use alloc::{sync::Arc, vec::Vec};
+use core::hash::BuildHasher;
 use crunch_shared::{
     config::{ConfigDatabase, EmissionKind},
     context::ContextDatabase,
@@ -8,7 +9,7 @@ use crunch_shared::{
     files::{CurrentFile, FileCache, FileId},
     salsa,
     trees::ast::Item,
-    utils::Upcast,
+    utils::{Hasher, Upcast},
 };
 
 type ArcError = Arc<ErrorHandler>;
@@ -17,48 +18,195 @@ type ArcError = Arc<ErrorHandler>;
 pub trait ParseDatabase:
     salsa::Database + ConfigDatabase + SourceDatabase + ContextDatabase + Upcast<dyn SourceDatabase>
 {
+    /// The last edit made to a file, if any, which lets the file be reparsed incrementally.
+    /// Set through [`record_edit`] so that the file's source is reread
+    #[salsa::input]
+    fn file_edit(&self, file: FileId) -> Option<Arc<FileEdit>>;
+
+    /// Parses a single source file without flattening its externals, only reparsing the
+    /// items touched by the file's last edit when there is one
+    // FIXME: Real lifetime when salsa allows
+    fn parse_items(&self, file: FileId) -> Result<Arc<ParserReturn<'static>>, ArcError>;
+
     /// Parses a single source file, returning the result
     // FIXME: Real lifetime when salsa allows
     fn parse(&self, file: FileId) -> Result<Arc<Vec<&'static Item<'static>>>, ArcError>;
 }
 
-#[inline]
-fn parse(
+/// An edit made to a file along with the items the file held before it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEdit {
+    /// The file's unflattened items from before the edit
+    pub previous: Arc<ParserReturn<'static>>,
+    /// A hash of the source that the edit produces, the edit is only used if the file's
+    /// current source matches it
+    pub edited_hash: u64,
+    pub edit: TextEdit,
+}
+
+fn source_hash(source: &str) -> u64 {
+    Hasher::default().hash_one(source)
+}
+
+/// Records that `edit` was made to `file`, which must already have been written to disk.
+/// The file has to have been parsed before the edit so that its previous items can be reused,
+/// otherwise it'll be fully reparsed
+pub fn record_edit<DB>(db: &mut DB, file: FileId, edit: TextEdit)
+where
+    DB: ParseDatabase + ?Sized,
+{
+    // The source is still the one from before the edit unless the file was never read, in which
+    // case the hash won't match the edited file and it'll be fully parsed
+    let source = db.source_text(file);
+    let edit = source
+        .get(edit.range.clone())
+        .and_then(|_| db.parse_items(file).ok())
+        .map(|previous| {
+            Arc::new(FileEdit {
+                previous,
+                edited_hash: source_hash(&edit.apply(&source)),
+                edit,
+            })
+        });
+
+    // Setting the path again invalidates the file's source so that the edit is read from disk
+    let path = db.file_path(file);
+    db.set_file_path(file, path);
+    db.set_file_edit(file, edit);
+}
+
+fn parse_items(
     db: &dyn ParseDatabase,
     file: FileId,
-) -> Result<Arc<Vec<&'static Item<'static>>>, ArcError> {
+) -> Result<Arc<ParserReturn<'static>>, ArcError> {
     let current_file = CurrentFile::new(file, db.source_length(file));
     let source = db.source_text(file);
     let config = db.config();
 
-    let parser = ParserBackend::new(&source, config.clone(), current_file, &db.context());
-
     crunch_shared::allocator::CRUNCHC_ALLOCATOR
-        .record_region("parsing", || parser.parse())
-        .map(|(ast, mut warnings)| {
-            warnings.emit(
-                &FileCache::upcast(db),
-                &**db.writer(),
-                &**db.stdout_config(),
-            );
-
-            let ast = FlattenExternals::new().flatten(ast);
-
-            if config.emit.contains(&EmissionKind::Ast) {
-                let path = db
-                    .config()
-                    .out_dir
-                    .join(&*db.file_name(file))
-                    .with_extension("ast");
-
-                std::fs::write(&path, format!("{:#?}", &ast)).unwrap();
-            }
-
-            if config.print.contains(&EmissionKind::Ast) {
-                println!("{:#?}", &ast);
-            }
-
-            Arc::new(ast)
+        .record_region("parsing", || match db.file_edit(file) {
+            // An edit is only usable if it actually produced the current source
+            Some(edit) if edit.edited_hash == source_hash(&source) => ParserBackend::reparse(
+                &edit.previous,
+                &source,
+                &edit.edit,
+                config,
+                current_file,
+                db.context(),
+            ),
+
+            _ => ParserBackend::new(&source, config, current_file, db.context()).parse(),
         })
+        .map(Arc::new)
         .map_err(Arc::new)
 }
+
+#[inline]
+fn parse(
+    db: &dyn ParseDatabase,
+    file: FileId,
+) -> Result<Arc<Vec<&'static Item<'static>>>, ArcError> {
+    let config = db.config();
+
+    db.parse_items(file).map(|parsed| {
+        let (ast, mut warnings) = (*parsed).clone();
+        warnings.emit(
+            &FileCache::upcast(db),
+            &**db.writer(),
+            &**db.stdout_config(),
+        );
+
+        let ast = FlattenExternals::new().flatten(ast);
+
+        if config.emit.contains(&EmissionKind::Ast) {
+            let path = db
+                .config()
+                .out_dir
+                .join(&*db.file_name(file))
+                .with_extension("ast");
+
+            std::fs::write(&path, format!("{:#?}", &ast)).unwrap();
+        }
+
+        if config.print.contains(&EmissionKind::Ast) {
+            println!("{:#?}", &ast);
+        }
+
+        Arc::new(ast)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crunch_shared::{
+        config::ConfigDatabaseStorage, context::ContextDatabaseStorage,
+        databases::SourceDatabaseStorage, salsa::Storage, testing,
+    };
+
+    #[salsa::database(
+        ConfigDatabaseStorage,
+        ContextDatabaseStorage,
+        SourceDatabaseStorage,
+        ParseDatabaseStorage
+    )]
+    #[derive(Default)]
+    struct TestDatabase {
+        storage: Storage<Self>,
+    }
+
+    impl salsa::Database for TestDatabase {}
+
+    impl Upcast<dyn SourceDatabase> for TestDatabase {
+        fn upcast(&self) -> &dyn SourceDatabase {
+            &*self
+        }
+    }
+
+    const SOURCE: &str = "fn first() -> i32\n    return 1\nend\n\n\
+        fn second() -> i32\n    return 2\nend\n";
+
+    #[test]
+    fn reparses_recorded_edits() {
+        testing::with_database(SOURCE, |database: &mut TestDatabase, file| {
+            database.set_file_edit(file, None);
+            let path = database.file_path(file);
+            let previous = database.parse_items(file).unwrap();
+
+            let two = SOURCE.find("return 2").unwrap() + "return ".len();
+            let edit = TextEdit::new(two..two + 1, "2 + 40");
+            let source = edit.apply(SOURCE);
+            std::fs::write(&*path, &source).unwrap();
+            record_edit(database, file, edit);
+
+            let reparsed = database.parse_items(file).unwrap();
+
+            assert_eq!(*database.source_text(file), source);
+            assert!(core::ptr::eq(reparsed.0[0], previous.0[0]));
+            assert!(!core::ptr::eq(reparsed.0[1], previous.0[1]));
+            assert_eq!(reparsed.0, full_parse(database, file).0);
+
+            // The file on disk doesn't match the edit outside of the edited item, so the first
+            // item can't be reused even though the file is the length the edit says it'll be
+            let edit = TextEdit::new(two..two + 1, "3");
+            std::fs::write(&*path, source.replace("return 1", "return 7")).unwrap();
+            record_edit(database, file, edit);
+
+            let mismatched = database.parse_items(file).unwrap();
+            assert!(!core::ptr::eq(mismatched.0[0], previous.0[0]));
+            assert_eq!(mismatched.0, full_parse(database, file).0);
+        });
+    }
+
+    fn full_parse(database: &TestDatabase, file: FileId) -> ParserReturn<'static> {
+        let source = database.source_text(file);
+        ParserBackend::new(
+            &source,
+            database.config(),
+            CurrentFile::new(file, source.len()),
+            database.context(),
+        )
+        .parse()
+        .unwrap()
+    }
+}
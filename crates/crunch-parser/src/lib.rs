@@ -13,9 +13,11 @@ extern crate alloc;
 pub mod database;
 pub mod parser;
 #[cfg(test)]
+mod round_trip;
+#[cfg(test)]
 mod tests;
-mod token;
+pub mod token;
 mod unnest_externs;
 
-pub use parser::{Parser, ParserReturn};
+pub use parser::{Parser, ParserReturn, TextEdit};
 pub use unnest_externs::FlattenExternals;
@@ -1,15 +1,21 @@
 use crate::{
-    parser::Parser,
-    token::{Token, TokenType},
+    parser::{
+        string_escapes::{self, Segment, StringLiteral},
+        Parser,
+    },
+    token::{Token, TokenStream, TokenType},
 };
-use alloc::{format, vec::Vec};
+use alloc::{format, string::String, vec, vec::Vec};
 use core::convert::TryFrom;
 use crunch_shared::{
     crunch_proc::recursion_guard,
-    error::{Error, Locatable, Location, ParseResult, Span, SyntaxError},
+    error::{Error, ErrorHandler, Locatable, Location, ParseResult, Span, SyntaxError},
     tracing,
     trees::{
-        ast::{Arm, Block, Expr, ExprKind, For, If, IfCond, Loop, Match, While},
+        ast::{
+            Arm, AssignKind, Block, CallArg, Expr, ExprKind, For, FormatSegment, If, IfCond, Loop,
+            Match, Range, RangeKind, Text, While, With,
+        },
         Sided,
     },
 };
@@ -48,24 +54,21 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         if let Some(prefix) = prefix {
             let mut left = prefix(self, token)?;
 
-            if let Ok(peek) = self.peek() {
-                let postfix = Self::expr_postfix(peek);
-
-                if let Some(postfix) = postfix {
-                    token = self.next()?;
-                    left = postfix(self, token, left)?;
-                }
+            // Calls, indexing and member accesses can be chained, `a.b(c)[d]`
+            while let Some(postfix) = self.peek().ok().and_then(Self::expr_postfix) {
+                token = self.next()?;
+                left = postfix(self, token, left)?;
             }
 
             while precedence < self.expr_precedence() {
-                token = self.next()?;
-
-                let infix = Self::expr_infix(token);
-                if let Some(infix) = infix {
-                    left = infix(self, token, left)?;
+                let infix = if let Some(infix) = self.peek().ok().and_then(Self::expr_infix) {
+                    infix
                 } else {
                     break;
-                }
+                };
+
+                token = self.next()?;
+                left = infix(self, token, left)?;
             }
 
             Ok(left)
@@ -91,11 +94,15 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
             TokenType::While     => Self::while_expr,
             TokenType::Loop      => Self::loop_expr,
             TokenType::For       => Self::for_expr,
+            TokenType::With      => Self::with_expr,
             TokenType::Return    => Self::return_expr,
             TokenType::Break     => Self::break_expr,
             TokenType::Continue  => Self::continue_expr,
             TokenType::LeftParen => Self::paren_expr,
             TokenType::Ampersand => Self::reference,
+            TokenType::Star      => Self::dereference,
+            TokenType::DoubleDot
+            | TokenType::DoubleDotEqual => Self::range_to,
             TokenType::Minus
             | TokenType::Bang
             | TokenType::Plus    => Self::postfix_expr,
@@ -115,21 +122,7 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         let postfix: PostfixParselet<'_, '_> = match token.ty() {
             TokenType::LeftParen   => Self::function_call,
             TokenType::Dot         => Self::dotted_call,
-            TokenType::DoubleDot   => Self::ranges,
             TokenType::LeftBrace   => Self::index_array,
-            TokenType::As          => Self::as_cast,
-            TokenType::Colon       => Self::assignment,
-            TokenType::AddAssign
-            | TokenType::SubAssign
-            | TokenType::MultAssign
-            | TokenType::DivAssign
-            | TokenType::ModAssign
-            | TokenType::PowAssign
-            | TokenType::ShlAssign
-            | TokenType::ShrAssign
-            | TokenType::OrAssign
-            | TokenType::AndAssign
-            | TokenType::XorAssign => Self::exotic_assignment,
             _                      => return None,
         };
 
@@ -141,6 +134,8 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         let infix: InfixParselet<'_, '_> = match token.ty() {
             TokenType::LeftBrace    => Self::index_array,
             TokenType::As           => Self::as_cast,
+            TokenType::DoubleDot
+            | TokenType::DoubleDotEqual => Self::range_from,
             TokenType::RightCaret
             | TokenType::LeftCaret
             | TokenType::GreaterThanEqual
@@ -157,8 +152,11 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
             | TokenType::Pipe
             | TokenType::Caret
             | TokenType::Shl
-            | TokenType::Shr        => Self::binary_operation,
+            | TokenType::Shr
+            | TokenType::And
+            | TokenType::Or         => Self::binary_operation,
             TokenType::Colon        => Self::assignment,
+            TokenType::Equal        => Self::mistaken_assignment,
             TokenType::AddAssign
             | TokenType::SubAssign
             | TokenType::MultAssign
@@ -182,7 +180,7 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         comparison: Token<'src>,
         lhs: &'ctx Expr<'ctx>,
     ) -> ParseResult<&'ctx Expr<'ctx>> {
-        let rhs = self.expr()?;
+        let rhs = self.operand(comparison)?;
 
         let loc = Location::new(Span::merge(lhs.span(), rhs.span()), self.current_file);
         let kind = ExprKind::Comparison(Sided {
@@ -200,7 +198,7 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         operand: Token<'src>,
         lhs: &'ctx Expr<'ctx>,
     ) -> ParseResult<&'ctx Expr<'ctx>> {
-        let rhs = self.expr()?;
+        let rhs = self.operand(operand)?;
 
         let loc = Location::new(Span::merge(lhs.span(), rhs.span()), self.current_file);
         let kind = ExprKind::BinaryOp(Sided {
@@ -232,6 +230,26 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         Ok(self.context.ast_expr(Expr { kind, loc }))
     }
 
+    /// `x = 10`, which is recorded as an error and then parsed as `x := 10`
+    #[recursion_guard]
+    fn mistaken_assignment(
+        &mut self,
+        equal: Token<'src>,
+        lhs: &'ctx Expr<'ctx>,
+    ) -> ParseResult<&'ctx Expr<'ctx>> {
+        self.mistaken_assign(equal);
+        let rhs = self.expr()?;
+
+        let loc = Location::new(Span::merge(lhs.span(), rhs.span()), self.current_file);
+        let kind = ExprKind::Assign(Sided {
+            lhs,
+            op: AssignKind::Normal,
+            rhs,
+        });
+
+        Ok(self.context.ast_expr(Expr { kind, loc }))
+    }
+
     #[recursion_guard]
     fn exotic_assignment(
         &mut self,
@@ -251,17 +269,63 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         Ok(self.context.ast_expr(Expr { kind, loc }))
     }
 
+    /// Parses the right hand side of an operator, only taking operators that bind tighter
+    /// than it so that `a - b - c` is `(a - b) - c` and `a + b..c` is `(a + b)..c`
+    #[recursion_guard]
+    fn operand(&mut self, operator: Token<'src>) -> ParseResult<&'ctx Expr<'ctx>> {
+        let precedence = ExprPrecedence::try_from(operator.ty())
+            .map(|p| p.precedence())
+            .unwrap_or(0);
+
+        self.parse_expr(precedence)
+    }
+
     #[recursion_guard]
-    fn ranges(
+    fn range_from(
         &mut self,
-        _double_dot: Token<'src>,
+        dots: Token<'src>,
         start: &'ctx Expr<'ctx>,
     ) -> ParseResult<&'ctx Expr<'ctx>> {
-        let end = self.expr()?;
+        self.range(dots, Some(start))
+    }
 
-        let loc = Location::new(Span::merge(start.span(), end.span()), self.current_file);
-        let kind = ExprKind::Range(start, end);
+    #[recursion_guard]
+    fn range_to(&mut self, dots: Token<'src>) -> ParseResult<&'ctx Expr<'ctx>> {
+        self.range(dots, None)
+    }
 
+    #[recursion_guard]
+    fn range(
+        &mut self,
+        dots: Token<'src>,
+        start: Option<&'ctx Expr<'ctx>>,
+    ) -> ParseResult<&'ctx Expr<'ctx>> {
+        let kind = if dots.ty() == TokenType::DoubleDotEqual {
+            RangeKind::Inclusive
+        } else {
+            RangeKind::Exclusive
+        };
+
+        // Ranges are unbounded if nothing that could start an expression follows them,
+        // e.g. `array[1..]` or `for i in 0..`
+        let end = if self.value_follows() {
+            Some(self.parse_expr(ExprPrecedence::Range.precedence())?)
+        } else {
+            None
+        };
+
+        let start_span = start.map_or(dots.span(), |start| start.span());
+        let end_span = end.map_or(dots.span(), |end| end.span());
+        let loc = Location::new(Span::merge(start_span, end_span), self.current_file);
+
+        if kind.is_inclusive() && end.is_none() {
+            return Err(Locatable::new(
+                Error::Syntax(SyntaxError::InclusiveRangeWithoutEnd),
+                loc,
+            ));
+        }
+
+        let kind = ExprKind::Range(Range { start, end, kind });
         Ok(self.context.ast_expr(Expr { kind, loc }))
     }
 
@@ -271,7 +335,8 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         _dot: Token<'src>,
         member: &'ctx Expr<'ctx>,
     ) -> ParseResult<&'ctx Expr<'ctx>> {
-        let func = self.expr()?;
+        // Only take the accessed member so that `a.b() + c` isn't parsed as `a.(b() + c)`
+        let func = self.parse_expr(ExprPrecedence::As.precedence())?;
 
         let loc = Location::new(Span::merge(member.span(), func.span()), self.current_file);
         let kind = ExprKind::MemberFuncCall { member, func };
@@ -290,8 +355,39 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
             self.eat(TokenType::Newline, [])?;
         }
 
+        let mut named = false;
         while self.peek()?.ty() != TokenType::RightParen {
-            let arg = self.expr()?;
+            let arg = if self.peek()?.ty() == TokenType::TripleDot {
+                self.eat(TokenType::TripleDot, [TokenType::Newline])?;
+                CallArg::Spread(self.expr()?)
+            } else {
+                let arg = self.expr()?;
+
+                // Named arguments, `name := value`, are parsed as assignments
+                match arg.kind {
+                    ExprKind::Assign(Sided {
+                        lhs:
+                            &Expr {
+                                kind: ExprKind::Variable(name),
+                                ..
+                            },
+                        op: AssignKind::Normal,
+                        rhs: value,
+                    }) => {
+                        named = true;
+                        CallArg::Named { name, value }
+                    }
+
+                    _ if named => {
+                        return Err(Locatable::new(
+                            Error::Syntax(SyntaxError::PositionalAfterNamed),
+                            arg.location(),
+                        ));
+                    }
+
+                    _ => CallArg::Positional(arg),
+                }
+            };
             args.push(arg);
 
             if self.peek()?.ty() == TokenType::Comma {
@@ -323,7 +419,8 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         } else {
             false
         };
-        let expr = self.expr()?;
+        // Only take the referenced value so that `&a + b` is `(&a) + b`
+        let expr = self.parse_expr(ExprPrecedence::As.precedence())?;
         let loc = Location::new(Span::merge(amp.span(), expr.span()), self.current_file);
 
         Ok(self.context.ast_expr(Expr {
@@ -332,6 +429,18 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         }))
     }
 
+    #[recursion_guard]
+    fn dereference(&mut self, star: Token<'src>) -> ParseResult<&'ctx Expr<'ctx>> {
+        // Only take the dereferenced value so that `*a + b` is `(*a) + b`
+        let pointer = self.parse_expr(ExprPrecedence::As.precedence())?;
+        let loc = Location::new(Span::merge(star.span(), pointer.span()), self.current_file);
+
+        Ok(self.context.ast_expr(Expr {
+            kind: ExprKind::Deref(pointer),
+            loc,
+        }))
+    }
+
     #[recursion_guard]
     fn paren_expr(&mut self, paren: Token<'src>) -> ParseResult<&'ctx Expr<'ctx>> {
         let expr = self.expr()?;
@@ -347,7 +456,8 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
 
     #[recursion_guard]
     fn postfix_expr(&mut self, token: Token<'src>) -> ParseResult<&'ctx Expr<'ctx>> {
-        let operand = self.expr()?;
+        // Only take the operand so that `-a + b` is `(-a) + b`
+        let operand = self.parse_expr(ExprPrecedence::As.precedence())?;
         let loc = Location::new(Span::merge(token.span(), operand.span()), self.current_file);
         let kind = ExprKind::UnaryOp(self.unary_op(&token, self.current_file)?, operand);
 
@@ -356,6 +466,20 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
 
     #[recursion_guard]
     fn literal_expr(&mut self, lit: Token<'src>) -> ParseResult<&'ctx Expr<'ctx>> {
+        if lit.ty() == TokenType::String {
+            let literal = StringLiteral::new(lit.source());
+
+            // Byte and raw strings are never interpolated
+            if !literal.byte && !literal.raw {
+                let start = lit.range().start + literal.offset;
+
+                let segments = self.string_segments(&literal, start, self.current_file)?;
+                if string_escapes::is_interpolated(&segments) {
+                    return self.format_string(lit, literal.contents, start, segments);
+                }
+            }
+        }
+
         let literal = ExprKind::Literal(Locatable::new(
             self.literal(&lit, self.current_file)?,
             Location::new(lit.span(), self.current_file),
@@ -367,6 +491,87 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         }))
     }
 
+    /// Builds a string with interpolated values, parsing each of the values as a full
+    /// expression
+    #[recursion_guard]
+    fn format_string(
+        &mut self,
+        lit: Token<'src>,
+        contents: &'src str,
+        start: usize,
+        segments: Vec<Segment>,
+    ) -> ParseResult<&'ctx Expr<'ctx>> {
+        let mut format = Vec::with_capacity(segments.len());
+
+        for segment in segments {
+            match segment {
+                Segment::Text(range) => {
+                    let text = self.unescape_text(
+                        &contents[range.clone()],
+                        start + range.start,
+                        self.current_file,
+                    )?;
+
+                    // Escaped braces split the text up, so stitch it back together
+                    if let Some(FormatSegment::Text(prev)) = format.last_mut() {
+                        let mut joined = String::from(prev.as_str());
+                        joined.push_str(text.as_str());
+                        *prev = Text::new(joined);
+                    } else {
+                        format.push(FormatSegment::Text(text));
+                    }
+                }
+
+                Segment::Hole { expr, spec } => {
+                    let expr =
+                        self.interpolated_expr(&contents[expr.clone()], start + expr.start)?;
+                    format.push(FormatSegment::Value { expr, spec });
+                }
+            }
+        }
+
+        Ok(self.context.ast_expr(Expr {
+            kind: ExprKind::FormatString(format),
+            loc: Location::new(lit.span(), self.current_file),
+        }))
+    }
+
+    /// Parses a value interpolated into a string, `start` being the position of the
+    /// value within the source file
+    fn interpolated_expr(
+        &mut self,
+        source: &'src str,
+        start: usize,
+    ) -> ParseResult<&'ctx Expr<'ctx>> {
+        let mut token_stream = TokenStream::with_offset(source, start, true, true);
+        let peek = token_stream.next();
+
+        let mut parser = Parser {
+            token_stream,
+            next: None,
+            peek,
+            error_handler: ErrorHandler::new(),
+            stack_frames: self.stack_frames.clone(),
+            current_file: self.current_file,
+            context: self.context,
+            config: self.config.clone(),
+        };
+
+        let expr = parser.expr()?;
+        if let Ok(token) = parser.peek() {
+            return Err(Locatable::new(
+                Error::Syntax(SyntaxError::Generic(format!(
+                    "Expected the end of an interpolated value, got {}",
+                    token.ty(),
+                ))),
+                Location::new(token.span(), self.current_file),
+            ));
+        }
+        self.error_handler.extend(parser.error_handler);
+
+        Ok(expr)
+    }
+
     #[recursion_guard]
     fn variable(&mut self, ident_tok: Token<'src>) -> ParseResult<&'ctx Expr<'ctx>> {
         let ident = Locatable::new(
@@ -382,8 +587,6 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
 
     #[recursion_guard]
     fn continue_expr(&mut self, token: Token<'src>) -> ParseResult<&'ctx Expr<'ctx>> {
-        self.eat(TokenType::Newline, [])?;
-
         let expr = Expr {
             kind: ExprKind::Continue,
             loc: Location::new(token.span(), self.current_file),
@@ -394,7 +597,7 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
 
     #[recursion_guard]
     fn break_expr(&mut self, token: Token<'src>) -> ParseResult<&'ctx Expr<'ctx>> {
-        if self.peek()?.ty() == TokenType::Newline {
+        if !self.value_follows() {
             Ok(self.context.ast_expr(Expr {
                 kind: ExprKind::Break(None),
                 loc: Location::new(token.span(), self.current_file),
@@ -410,9 +613,15 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         }
     }
 
+    /// Whether the next token starts an expression, used for things like `return` that
+    /// only sometimes take a value
+    fn value_follows(&self) -> bool {
+        matches!(self.peek(), Ok(peek) if Self::expr_prefix(peek).is_some())
+    }
+
     #[recursion_guard]
     fn return_expr(&mut self, token: Token<'src>) -> ParseResult<&'ctx Expr<'ctx>> {
-        if self.peek()?.ty() == TokenType::Newline {
+        if !self.value_follows() {
             Ok(self.context.ast_expr(Expr {
                 kind: ExprKind::Return(None),
                 loc: Location::new(token.span(), self.current_file),
@@ -501,7 +710,7 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
 
         let (body, mut delimiter) = self.block_returning(&[TokenType::End, TokenType::Else], 10)?;
 
-        let mut clauses = Vec::new();
+        let mut clauses = vec![IfCond { cond, body }];
         let mut else_ = None;
         let end;
         loop {
@@ -537,7 +746,6 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         }
 
         let loc = Location::new(Span::merge(cond.span(), end), self.current_file);
-        clauses.push(IfCond { cond, body });
         let kind = ExprKind::If(If { clauses, else_ });
 
         Ok(self.context.ast_expr(Expr { kind, loc }))
@@ -582,14 +790,43 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         Ok(self.context.ast_expr(expr))
     }
 
+    #[recursion_guard]
+    fn with_expr(&mut self, with: Token<'src>) -> ParseResult<&'ctx Expr<'ctx>> {
+        // Binary operations must be parenthesized, otherwise the `as` would be parsed as a cast
+        let value = self.parse_expr(ExprPrecedence::As.precedence())?;
+        self.eat(TokenType::As, [TokenType::Newline])?;
+
+        let name = {
+            let ident = self.eat(TokenType::Ident, [TokenType::Newline])?;
+            Locatable::new(
+                self.intern_ident(ident),
+                Location::new(ident.span(), self.current_file),
+            )
+        };
+        self.eat(TokenType::Newline, [])?;
+
+        let body = self.block(&[TokenType::End], 10)?;
+
+        let loc = Location::new(
+            Span::merge(with.span(), body.location().span()),
+            self.current_file,
+        );
+        let expr = Expr {
+            kind: ExprKind::With(With { value, name, body }),
+            loc,
+        };
+
+        Ok(self.context.ast_expr(expr))
+    }
+
     #[recursion_guard]
     fn while_expr(&mut self, _token: Token<'src>) -> ParseResult<&'ctx Expr<'ctx>> {
         let cond = self.expr()?;
         self.eat(TokenType::Newline, [])?;
 
-        let body = self.block(&[TokenType::End, TokenType::Then], 10)?;
-        let then = self.then_block()?;
-        let else_ = self.else_block()?;
+        let (body, delimiter) =
+            self.block_returning(&[TokenType::End, TokenType::Then, TokenType::Else], 10)?;
+        let (then, else_) = self.loop_clauses(delimiter)?;
         let end = else_
             .as_ref()
             .map(|e| e.location())
@@ -618,8 +855,9 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
     fn loop_expr(&mut self, _token: Token<'src>) -> ParseResult<&'ctx Expr<'ctx>> {
         let start = self.eat(TokenType::Newline, [])?.span();
 
-        let body = self.block(&[TokenType::End, TokenType::Then], 10)?;
-        let else_ = self.else_block()?;
+        // Loops can only be exited by a `break`, so they can't have a `then` clause
+        let (body, delimiter) = self.block_returning(&[TokenType::End, TokenType::Else], 10)?;
+        let (_, else_) = self.loop_clauses(delimiter)?;
         let end = else_
             .as_ref()
             .map(|e| e.location())
@@ -641,9 +879,9 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         let cond = self.expr()?;
         self.eat(TokenType::Newline, [])?;
 
-        let body = self.block(&[TokenType::End, TokenType::Then], 10)?;
-        let then = self.then_block()?;
-        let else_ = self.else_block()?;
+        let (body, delimiter) =
+            self.block_returning(&[TokenType::End, TokenType::Then, TokenType::Else], 10)?;
+        let (then, else_) = self.loop_clauses(delimiter)?;
         let end = else_
             .as_ref()
             .map(|e| e.location())
@@ -669,26 +907,30 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         Ok(self.context.ast_expr(expr))
     }
 
+    /// Parses the `then` and `else` clauses of a loop, `delimiter` being the token that
+    /// ended the loop's body. A single `end` closes the loop and all of its clauses
     #[recursion_guard]
-    fn then_block(&mut self) -> ParseResult<Option<Block<'ctx>>> {
-        if self.peek()?.ty() == TokenType::Then {
-            let then = self.block(&[TokenType::End, TokenType::Else], 3)?;
+    #[allow(clippy::type_complexity)]
+    fn loop_clauses(
+        &mut self,
+        mut delimiter: Token<'src>,
+    ) -> ParseResult<(Option<Block<'ctx>>, Option<Block<'ctx>>)> {
+        let then = if delimiter.ty() == TokenType::Then {
+            let (then, delim) = self.block_returning(&[TokenType::End, TokenType::Else], 3)?;
+            delimiter = delim;
 
-            Ok(Some(then))
+            Some(then)
         } else {
-            Ok(None)
-        }
-    }
-
-    #[recursion_guard]
-    fn else_block(&mut self) -> ParseResult<Option<Block<'ctx>>> {
-        if self.peek()?.ty() == TokenType::Else {
-            let else_clause = self.block(&[TokenType::End], 3)?;
+            None
+        };
 
-            Ok(Some(else_clause))
+        let else_ = if delimiter.ty() == TokenType::Else {
+            Some(self.block(&[TokenType::End], 3)?)
         } else {
-            Ok(None)
-        }
+            None
+        };
+
+        Ok((then, else_))
     }
 }
 
@@ -706,6 +948,7 @@ pub enum ExprPrecedence {
     BitOr,
     LogAnd,
     LogOr,
+    Range,
     Ternary,
     Assignment,
 }
@@ -714,23 +957,24 @@ impl ExprPrecedence {
     #[rustfmt::skip]
     pub fn precedence(self) -> usize {
         match self {
-            Self::As              => 13,
+            Self::As              => 14,
             Self::Mul
             | Self::Div
             | Self::Mod
-            | Self::Pow           => 12,
-            Self::Add | Self::Sub => 11,
-            Self::Shl | Self::Shr => 10,
+            | Self::Pow           => 13,
+            Self::Add | Self::Sub => 12,
+            Self::Shl | Self::Shr => 11,
             Self::Less
             | Self::Greater
             | Self::LessEq
-            | Self::GreaterEq     => 9,
-            Self::Eq | Self::Ne   => 8,
-            Self::BitAnd          => 7,
-            Self::BitXor          => 6,
-            Self::BitOr           => 5,
-            Self::LogAnd          => 4,
-            Self::LogOr           => 3,
+            | Self::GreaterEq     => 10,
+            Self::Eq | Self::Ne   => 9,
+            Self::BitAnd          => 8,
+            Self::BitXor          => 7,
+            Self::BitOr           => 6,
+            Self::LogAnd          => 5,
+            Self::LogOr           => 4,
+            Self::Range           => 3,
             Self::Ternary         => 2,
             Self::Assignment      => 1,
         }
@@ -763,12 +1007,16 @@ impl TryFrom<TokenType> for ExprPrecedence {
             TokenType::Pipe             => Self::BitOr,
             TokenType::And              => Self::LogAnd,
             TokenType::Or               => Self::LogOr,
+            TokenType::DoubleDot
+            | TokenType::DoubleDotEqual => Self::Range,
             TokenType::Colon
+            | TokenType::Equal
             | TokenType::AddAssign
             | TokenType::SubAssign
             | TokenType::MultAssign
             | TokenType::DivAssign
             | TokenType::ModAssign
+            | TokenType::PowAssign
             | TokenType::ShlAssign
             | TokenType::ShrAssign
             | TokenType::OrAssign
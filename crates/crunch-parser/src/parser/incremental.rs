@@ -0,0 +1,418 @@
+use crate::{
+    parser::{utils::StackGuard, Parser, ParserReturn},
+    token::TokenStream,
+};
+use alloc::{string::String, sync::Arc, vec::Vec};
+use core::ops::Range;
+use crunch_shared::{
+    config::BuildOptions,
+    context::Context,
+    error::{ErrorHandler, Location, Span},
+    files::CurrentFile,
+    trees::ast::{Expr, ExprKind, Type},
+    visitors::ast::{walk_expr, walk_type, Fold},
+};
+
+/// A single change to a source file, replacing the bytes in `range` with `replacement`.
+/// The range is given in terms of the source *before* the edit was applied
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    #[inline]
+    pub fn new(range: Range<usize>, replacement: impl Into<String>) -> Self {
+        Self {
+            range,
+            replacement: replacement.into(),
+        }
+    }
+
+    /// Applies the edit to the source it was made against
+    pub fn apply(&self, source: &str) -> String {
+        let mut edited = String::with_capacity(source.len() + self.replacement.len());
+        edited.push_str(&source[..self.range.start]);
+        edited.push_str(&self.replacement);
+        edited.push_str(&source[self.range.end..]);
+
+        edited
+    }
+
+    /// The number of bytes the edit grows (or shrinks) the source by
+    #[inline]
+    pub fn delta(&self) -> isize {
+        self.replacement.len() as isize - (self.range.end - self.range.start) as isize
+    }
+}
+
+/// Incremental reparsing
+impl<'src, 'ctx> Parser<'src, 'ctx> {
+    /// Reparses a file after `edit` was made to it, reusing as much of `previous` as possible.
+    ///
+    /// `previous` must be what parsing the file before the edit produced (before its items
+    /// were flattened by [`FlattenExternals`]) and `source` is the file's source *after* the edit.
+    /// Every top-level item owns the source between the end of the item before it and its own end,
+    /// so only the items whose region overlaps the edit are relexed and reparsed. Items before
+    /// the edit are returned untouched and items after it are reused with their locations moved
+    /// by however much the edit grew or shrank the file, which keeps their `FileId`s and relative
+    /// positions stable so that later queries on them can be reused. The diagnostics of the reused
+    /// items are kept along with them, and the reparsed items get fresh ones.
+    ///
+    /// If a token runs past the end of the relexed region, like a string the edit left unclosed,
+    /// the items after it no longer lex the same way and the whole file is parsed again
+    ///
+    /// [`FlattenExternals`]: crate::FlattenExternals
+    #[crunch_shared::instrument(name = "reparsing", skip(previous, source, config, context))]
+    pub fn reparse(
+        previous: &ParserReturn<'ctx>,
+        source: &'src str,
+        edit: &TextEdit,
+        config: Arc<BuildOptions>,
+        current_file: CurrentFile,
+        context: &'ctx Context<'ctx>,
+    ) -> Result<ParserReturn<'ctx>, ErrorHandler> {
+        let (previous, diagnostics) = (&previous.0, &previous.1);
+        let end = |idx: usize| previous[idx].span().end();
+
+        // The first item that ends at or after the start of the edit, everything before it is untouched
+        let first = previous
+            .iter()
+            .position(|item| item.span().end() >= edit.range.start)
+            .unwrap_or(previous.len());
+
+        // The first item whose region starts after the end of the edit, it and everything after
+        // it only has to be moved
+        let after = (first..previous.len())
+            .find(|&idx| idx != 0 && end(idx - 1) > edit.range.end)
+            .unwrap_or(previous.len());
+
+        let delta = edit.delta();
+        let start = if first == 0 { 0 } else { end(first - 1) };
+        let stop = if after == previous.len() {
+            source.len()
+        } else {
+            (end(after - 1) as isize + delta) as usize
+        };
+
+        if stop != source.len() && Self::crosses_boundary(source, start, stop) {
+            crunch_shared::trace!("a token runs past byte {}, reparsing the whole file", stop);
+            return Self::new(source, config, current_file, context).parse();
+        }
+
+        crunch_shared::trace!(
+            "reparsing items {}..{} of {}, covering bytes {}..{}",
+            first,
+            after,
+            previous.len(),
+            start,
+            stop,
+        );
+
+        let mut relocate = Relocate::shift(context, delta);
+
+        // Diagnostics are kept in source order, so the reused ones are split around the reparsed
+        let (mut errors, mut moved) = (ErrorHandler::new(), ErrorHandler::new());
+        for warning in diagnostics.warnings() {
+            if warning.span().end() <= start {
+                errors.push_warning(warning.clone());
+            } else if after != previous.len() && warning.span().start() >= end(after - 1) {
+                moved.push_warning(
+                    warning
+                        .clone()
+                        .map_location(|loc| relocate.fold_location(loc)),
+                );
+            }
+        }
+
+        let token_stream = TokenStream::with_offset(&source[start..stop], start, true, true);
+        let reparsed = match Self::with_tokens(token_stream, config, current_file, context).parse()
+        {
+            Ok((reparsed, reparsed_errors)) => {
+                errors.extend(reparsed_errors);
+                reparsed
+            }
+
+            Err(reparsed_errors) => {
+                errors.extend(reparsed_errors);
+                errors.extend(moved);
+
+                return Err(errors);
+            }
+        };
+        errors.extend(moved);
+
+        let mut items = Vec::with_capacity(first + reparsed.len() + previous.len() - after);
+        items.extend_from_slice(&previous[..first]);
+        items.extend(reparsed);
+
+        if delta == 0 {
+            items.extend_from_slice(&previous[after..]);
+        } else {
+            items.extend(
+                previous[after..]
+                    .iter()
+                    .map(|item| relocate.fold_item(item)),
+            );
+        }
+
+        Ok((items, errors))
+    }
+
+    /// Whether a token of `source` that starts between `start` and `stop` ends after `stop`,
+    /// meaning that `stop` isn't a token boundary of the full source
+    fn crosses_boundary(source: &str, start: usize, stop: usize) -> bool {
+        TokenStream::with_offset(&source[start..], start, false, false)
+            .map(|token| token.span)
+            .take_while(|span| span.start() < stop)
+            .any(|span| span.end() > stop)
+    }
+
+    fn with_tokens(
+        mut token_stream: TokenStream<'src>,
+        config: Arc<BuildOptions>,
+        current_file: CurrentFile,
+        context: &'ctx Context<'ctx>,
+    ) -> Self {
+        let peek = token_stream.next();
+
+        Self {
+            token_stream,
+            next: None,
+            peek,
+            error_handler: ErrorHandler::new(),
+            stack_frames: StackGuard::new(),
+            current_file,
+            context,
+            config,
+        }
+    }
+}
+
+/// Rebuilds items with every location within them changed, used to move the items that
+/// come after an edit
+pub(crate) struct Relocate<'ctx> {
+    context: &'ctx Context<'ctx>,
+    relocation: Relocation,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Relocation {
+    /// Moves every location by the given number of bytes
+    Shift(isize),
+    /// Clears every location and drops all parentheses, leaving only the tree's structure
+    /// so that trees from different sources can be compared
+    #[cfg_attr(not(test), allow(dead_code))]
+    Erase,
+}
+
+impl<'ctx> Relocate<'ctx> {
+    pub(crate) fn shift(context: &'ctx Context<'ctx>, delta: isize) -> Self {
+        Self {
+            context,
+            relocation: Relocation::Shift(delta),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn erase(context: &'ctx Context<'ctx>) -> Self {
+        Self {
+            context,
+            relocation: Relocation::Erase,
+        }
+    }
+}
+
+impl<'ctx> Fold<'ctx> for Relocate<'ctx> {
+    fn context(&self) -> &'ctx Context<'ctx> {
+        self.context
+    }
+
+    fn fold_location(&mut self, loc: Location) -> Location {
+        match self.relocation {
+            Relocation::Shift(delta) => {
+                let shift = |pos: usize| (pos as isize + delta) as usize;
+                loc.map_span(|span| Span::new(shift(span.start()), shift(span.end())))
+            }
+            Relocation::Erase => loc.map_span(|_| Span::new(0, 0)),
+        }
+    }
+
+    fn fold_expr(&mut self, expr: &'ctx Expr<'ctx>) -> &'ctx Expr<'ctx> {
+        if let (Relocation::Erase, ExprKind::Paren(inner)) = (self.relocation, &expr.kind) {
+            return self.fold_expr(inner);
+        }
+
+        walk_expr(self, expr)
+    }
+
+    fn fold_type(&mut self, ty: &'ctx Type<'ctx>) -> &'ctx Type<'ctx> {
+        if let (Relocation::Erase, Type::Paren(inner)) = (self.relocation, ty) {
+            return self.fold_type(**inner);
+        }
+
+        walk_type(self, ty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crunch_shared::{
+        context::{Arenas, OwnedArenas},
+        files::FileId,
+        trees::ast::Item,
+    };
+
+    const SOURCE: &str = "fn first() -> i32\n    return 1\nend\n\n\
+        fn second() -> i32\n    return 2\nend\n\n\
+        fn third() -> i32\n    return 3\nend\n";
+
+    fn parse<'ctx>(source: &str, context: &'ctx Context<'ctx>) -> ParserReturn<'ctx> {
+        Parser::new(
+            source,
+            Arc::new(BuildOptions::new("incremental.crunch")),
+            CurrentFile::new(FileId::new(0), source.len()),
+            context,
+        )
+        .parse()
+        .unwrap()
+    }
+
+    fn reparse<'ctx>(
+        previous: &ParserReturn<'ctx>,
+        edit: &TextEdit,
+        context: &'ctx Context<'ctx>,
+    ) -> (String, Vec<&'ctx Item<'ctx>>) {
+        let source = edit.apply(SOURCE);
+        let items = Parser::reparse(
+            previous,
+            &source,
+            edit,
+            Arc::new(BuildOptions::new("incremental.crunch")),
+            CurrentFile::new(FileId::new(0), source.len()),
+            context,
+        )
+        .unwrap()
+        .0;
+
+        (source, items)
+    }
+
+    #[test]
+    fn matches_full_parse() {
+        let owned_arenas = OwnedArenas::default();
+        let context = Context::new(Arenas::from(&owned_arenas));
+        let previous = parse(SOURCE, &context);
+
+        let one = SOURCE.find("return 1").unwrap();
+        let two = SOURCE.find("return 2").unwrap() + "return ".len();
+        let edits = [
+            // Same length, inside of the second function
+            TextEdit::new(two..two + 1, "4"),
+            // Growing the second function
+            TextEdit::new(two..two + 1, "2 + 40"),
+            // Shrinking the first function
+            TextEdit::new(one..one + "return 1".len(), "0"),
+            // Inserting an entirely new function between two others
+            TextEdit::new(two + 5..two + 5, "\nfn inserted()\nend\n"),
+            // Appending to the end of the file
+            TextEdit::new(SOURCE.len()..SOURCE.len(), "\nfn fourth()\nend\n"),
+        ];
+
+        for edit in edits.iter() {
+            let (source, incremental) = reparse(&previous, edit, &context);
+            assert_eq!(incremental, parse(&source, &context).0, "{:?}", edit);
+        }
+    }
+
+    #[test]
+    fn reuses_untouched_items() {
+        let owned_arenas = OwnedArenas::default();
+        let context = Context::new(Arenas::from(&owned_arenas));
+        let previous = parse(SOURCE, &context);
+
+        let two = SOURCE.find("return 2").unwrap() + "return ".len();
+        let (_, same_length) = reparse(&previous, &TextEdit::new(two..two + 1, "4"), &context);
+        assert!(core::ptr::eq(same_length[0], previous.0[0]));
+        assert!(!core::ptr::eq(same_length[1], previous.0[1]));
+        assert!(core::ptr::eq(same_length[2], previous.0[2]));
+
+        let (_, grown) = reparse(&previous, &TextEdit::new(two..two + 1, "20"), &context);
+        assert!(core::ptr::eq(grown[0], previous.0[0]));
+        assert_eq!(grown[2].span().start(), previous.0[2].span().start() + 1);
+    }
+
+    #[test]
+    fn keeps_diagnostics() {
+        let owned_arenas = OwnedArenas::default();
+        let context = Context::new(Arenas::from(&owned_arenas));
+
+        // Every function has a literal that warns about its underscores
+        let source = SOURCE
+            .replace("return 1", "return 1__0")
+            .replace("return 3", "return 3__0");
+        let previous = parse(&source, &context);
+
+        let two = source.find("return 2").unwrap() + "return ".len();
+        for replacement in ["2__0", "2", "2 + 40__0"].iter() {
+            let edit = TextEdit::new(two..two + 1, *replacement);
+            let edited = edit.apply(&source);
+            let incremental = Parser::reparse(
+                &previous,
+                &edited,
+                &edit,
+                Arc::new(BuildOptions::new("incremental.crunch")),
+                CurrentFile::new(FileId::new(0), edited.len()),
+                &context,
+            )
+            .unwrap();
+
+            let full = parse(&edited, &context);
+            assert_eq!(
+                incremental.1.warnings().collect::<Vec<_>>(),
+                full.1.warnings().collect::<Vec<_>>(),
+                "{:?}",
+                edit,
+            );
+        }
+    }
+
+    #[test]
+    fn relexes_unclosed_tokens() {
+        let owned_arenas = OwnedArenas::default();
+        let context = Context::new(Arenas::from(&owned_arenas));
+        let source = SOURCE.replace("-> i32\n    return 3", "-> str\n    return \"3\"");
+        let previous = parse(&source, &context);
+
+        // The opened string is closed by the one in the third function, so the string crosses
+        // out of the relexed region and only a full parse sees it
+        let two = source.find("return 2").unwrap() + "return ".len();
+        let edit = TextEdit::new(two..two + 1, "\"2");
+        let edited = edit.apply(&source);
+
+        let incremental = Parser::reparse(
+            &previous,
+            &edited,
+            &edit,
+            Arc::new(BuildOptions::new("incremental.crunch")),
+            CurrentFile::new(FileId::new(0), edited.len()),
+            &context,
+        )
+        .unwrap_err();
+        let full = Parser::new(
+            &edited,
+            Arc::new(BuildOptions::new("incremental.crunch")),
+            CurrentFile::new(FileId::new(0), edited.len()),
+            &context,
+        )
+        .parse()
+        .unwrap_err();
+
+        assert_eq!(
+            incremental.errors().collect::<Vec<_>>(),
+            full.errors().collect::<Vec<_>>(),
+        );
+    }
+}
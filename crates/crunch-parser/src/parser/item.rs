@@ -11,9 +11,9 @@ use crunch_shared::{
     trees::{
         ast::{
             Attribute, Decorator, Dest, Exposure, ExtendBlock, ExternBlock, ExternFunc, FuncArg,
-            Item, ItemKind, Type, TypeMember, Variant, Vis,
+            Item, ItemKind, Type, TypeMember, TypeOp, Variant, Vis,
         },
-        CallConv,
+        CallConv, ItemPath, Sided,
     },
 };
 
@@ -157,16 +157,64 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
                 }
             }
 
+            TokenType::Module => {
+                let module = self.module(
+                    mem::take(decorators),
+                    mem::take(attributes),
+                    vis.take().unwrap_or_default(),
+                )?;
+
+                Ok(Some(module))
+            }
+
             TokenType::Newline | TokenType::Space => {
                 self.next()?;
                 Ok(None)
             }
 
-            ty => Err(Locatable::new(
-                Error::Syntax(SyntaxError::InvalidTopLevel(format!("{}", ty))),
-                Location::new(&self.peek()?, self.current_file),
-            )),
+            _ => Err(self.invalid_top_level(peek)),
+        }
+    }
+
+    /// ```ebnf
+    /// Module ::= Decorator* Attribute* 'module' Ident '\n' Item* 'end'
+    /// ```
+    #[recursion_guard]
+    #[crunch_shared::instrument(name = "module", skip(self, decorators, attrs, vis))]
+    fn module(
+        &mut self,
+        decorators: Vec<Decorator<'ctx>>,
+        attrs: Vec<Attribute>,
+        vis: Vis,
+    ) -> ParseResult<&'ctx Item<'ctx>> {
+        let start_span = self.eat(TokenType::Module, [TokenType::Newline])?.span();
+        let name = {
+            let ident = self.eat(TokenType::Ident, [TokenType::Newline])?;
+            self.intern_ident(ident)
+        };
+        self.eat(TokenType::Newline, [])?;
+
+        let (mut item_decorators, mut item_attributes, mut item_vis) =
+            (Vec::with_capacity(5), Vec::with_capacity(5), None);
+
+        let mut items = Vec::with_capacity(10);
+        while self.peek()?.ty() != TokenType::End {
+            if let Some(item) =
+                self.item_impl(&mut item_decorators, &mut item_attributes, &mut item_vis)?
+            {
+                items.push(item);
+            }
         }
+        let end_span = self.eat(TokenType::End, [TokenType::Newline])?.span();
+
+        Ok(self.context.ast_item(Item {
+            kind: ItemKind::Module(items),
+            decorators,
+            attrs,
+            name: Some(name),
+            loc: Location::new(Span::merge(start_span, end_span), self.current_file),
+            vis: Some(vis),
+        }))
     }
 
     #[recursion_guard]
@@ -326,7 +374,7 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
                     let method = self.function(
                         mem::take(&mut method_decorators),
                         mem::take(&mut method_attributes),
-                        method_vis.unwrap_or_default(),
+                        method_vis.take().unwrap_or_default(),
                     )?;
 
                     methods.push(method);
@@ -786,9 +834,10 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
     }
 
     /// ```ebnf
-    /// FunctionArgs ::= '(' Args? ')'
-    /// Args ::= Argument | Argument ',' Args
-    /// Argument ::= Ident ':' Type
+    /// FunctionArgs ::= '(' (Receiver ','?)? Args? ')'
+    /// Args ::= Argument | Argument ',' Args | VariadicArgument
+    /// Argument ::= Ident ':' Type ( ':=' Expr )?
+    /// VariadicArgument ::= Ident ':' '..' Type
     /// ```
     #[recursion_guard]
     fn function_args(&mut self) -> ParseResult<Locatable<Vec<FuncArg<'ctx>>>> {
@@ -797,6 +846,16 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         let start = self.eat(TokenType::LeftParen, [TokenType::Newline])?.span();
 
         let mut args = Vec::with_capacity(7);
+        if self.peek()?.ty() == TokenType::Ampersand
+            || (self.peek()?.ty() == TokenType::Ident && self.peek()?.source() == "self")
+        {
+            args.push(self.receiver()?);
+
+            if self.peek()?.ty() == TokenType::Comma {
+                self.eat(TokenType::Comma, [TokenType::Newline])?;
+            }
+        }
+
         while self.peek()?.ty() != TokenType::RightParen {
             let (name, name_span) = match self
                 .eat_of([TokenType::Ident, TokenType::Const], [TokenType::Newline])?
@@ -813,11 +872,39 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
             };
 
             self.eat(TokenType::Colon, [TokenType::Newline])?;
-            let ty = self.ascribed_type()?;
+
+            // Variadic arguments are sugar for a slice of their elements
+            let variadic = self.peek()?.ty() == TokenType::DoubleDot;
+            let ty = if variadic {
+                let dots = self.eat(TokenType::DoubleDot, [TokenType::Newline])?;
+                let element = self.ascribed_type()?;
+
+                Locatable::new(
+                    self.context.ast_type(Type::Slice { element }),
+                    Location::new(Span::merge(dots.span(), element.span()), self.current_file),
+                )
+            } else {
+                self.ascribed_type()?
+            };
+
+            let default = if !variadic && self.peek()?.ty() == TokenType::Colon {
+                self.eat(TokenType::Colon, [TokenType::Newline])?;
+                self.eat(TokenType::Equal, [TokenType::Newline])?;
+
+                Some(self.expr()?)
+            } else {
+                None
+            };
 
             // FIXME: Type span
             let loc = Location::new(name_span, self.current_file);
-            let arg = FuncArg { name, ty, loc };
+            let arg = FuncArg {
+                name,
+                ty,
+                default,
+                variadic,
+                loc,
+            };
 
             args.push(arg);
 
@@ -826,6 +913,13 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
             } else {
                 break;
             }
+
+            if variadic && self.peek()?.ty() != TokenType::RightParen {
+                return Err(Locatable::new(
+                    Error::Syntax(SyntaxError::VariadicNotLast),
+                    loc,
+                ));
+            }
         }
         let end = self
             .eat(TokenType::RightParen, [TokenType::Newline])?
@@ -837,6 +931,72 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         ))
     }
 
+    /// Methods take their receiver as `self`, `&self` or `&mut self`, which are sugar
+    /// for an argument named `self` with the type `Self`, `&Self` or `&mut Self`
+    ///
+    /// ```ebnf
+    /// Receiver ::= ('&' 'mut'?)? 'self' | 'self' ':' Type
+    /// ```
+    #[recursion_guard]
+    fn receiver(&mut self) -> ParseResult<FuncArg<'ctx>> {
+        let reference = if self.peek()?.ty() == TokenType::Ampersand {
+            let ampersand = self.eat(TokenType::Ampersand, [TokenType::Newline])?.span();
+            let mutable = if self.peek()?.ty() == TokenType::Mut {
+                self.eat(TokenType::Mut, [TokenType::Newline])?;
+                true
+            } else {
+                false
+            };
+
+            Some((ampersand, mutable))
+        } else {
+            None
+        };
+
+        let token = self.eat(TokenType::Ident, [TokenType::Newline])?;
+        let loc = Location::new(
+            reference.map_or(token.span(), |(start, _)| Span::merge(start, token.span())),
+            self.current_file,
+        );
+        if token.source() != "self" {
+            return Err(Locatable::new(
+                Error::Syntax(SyntaxError::Generic(format!(
+                    "Expected `self` after `&`, got `{}`",
+                    token.source(),
+                ))),
+                loc,
+            ));
+        }
+
+        let self_type = self.context.strings().intern_static("Self");
+        let mut ty = Locatable::new(
+            self.context
+                .ast_type(Type::ItemPath(ItemPath::new(self_type))),
+            loc,
+        );
+        if let Some((_, mutable)) = reference {
+            ty = Locatable::new(
+                self.context.ast_type(Type::Reference {
+                    referee: ty,
+                    mutable,
+                }),
+                loc,
+            );
+        } else if self.peek()?.ty() == TokenType::Colon {
+            // Receivers can also be given their type explicitly, `self: &Self`
+            self.eat(TokenType::Colon, [TokenType::Newline])?;
+            ty = self.ascribed_type()?;
+        }
+
+        Ok(FuncArg {
+            name: self.intern_ident(token),
+            ty,
+            default: None,
+            variadic: false,
+            loc,
+        })
+    }
+
     /// ```ebnf
     /// ExternBlock ::=
     ///     Decorator* Attribute* 'extern'
@@ -868,7 +1028,7 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
                     let func = self.extern_func(
                         mem::take(&mut item_decorators),
                         mem::take(&mut item_attributes),
-                        item_vis.unwrap_or_default(),
+                        item_vis.take().unwrap_or_default(),
                     )?;
 
                     items.push(func);
@@ -1003,7 +1163,8 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
 
     /// ```ebnf
     /// Generics ::= '[' GenericArgs? ']'
-    /// GenericArgs ::= Type | Type ',' GenericArgs
+    /// GenericArgs ::= GenericArg | GenericArg ',' GenericArgs
+    /// GenericArg ::= Type ( ':' Type )?
     /// ```
     #[recursion_guard]
     pub(super) fn generics(
@@ -1022,7 +1183,34 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
 
             let mut generics = Vec::with_capacity(5);
             while self.peek()?.ty() != TokenType::RightBrace {
-                generics.push(self.ascribed_type()?);
+                let generic = self.ascribed_type()?;
+
+                // Bounded generics, `T: Trait & Other`
+                if self.peek()?.ty() == TokenType::Colon {
+                    let path = match *generic {
+                        Type::ItemPath(path) => path.clone(),
+                        _ => {
+                            return Err(Locatable::new(
+                                Error::Syntax(SyntaxError::Generic(
+                                    "Only named generics can have bounds".to_string(),
+                                )),
+                                generic.location(),
+                            ));
+                        }
+                    };
+                    self.eat(TokenType::Colon, [TokenType::Newline])?;
+
+                    let bound = self.ascribed_type()?;
+                    let mut bounds = Vec::with_capacity(2);
+                    Self::flatten_bounds(bound, &mut bounds);
+
+                    generics.push(Locatable::new(
+                        self.context.ast_type(Type::Bounded { path, bounds }),
+                        generic.location().merge(bound.location()),
+                    ));
+                } else {
+                    generics.push(generic);
+                }
 
                 if self.peek()?.ty() == TokenType::Comma {
                     self.eat(TokenType::Comma, [TokenType::Newline])?;
@@ -1048,6 +1236,25 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         }
     }
 
+    /// Splits the bounds of a generic, `A & B & C`, into each of the traits it's bound by
+    fn flatten_bounds(
+        bound: Locatable<&'ctx Type<'ctx>>,
+        bounds: &mut Vec<Locatable<&'ctx Type<'ctx>>>,
+    ) {
+        match *bound {
+            Type::Operand(Sided {
+                lhs,
+                op: TypeOp::And,
+                rhs,
+            }) => {
+                Self::flatten_bounds(*lhs, bounds);
+                Self::flatten_bounds(*rhs, bounds);
+            }
+
+            _ => bounds.push(bound),
+        }
+    }
+
     /// ```ebnf
     /// Attribute ::= 'const'
     /// ```
@@ -11,13 +11,16 @@ use crunch_shared::{
 };
 
 mod expr;
+pub(crate) mod incremental;
 mod item;
 mod patterns;
 mod stmt;
 mod string_escapes;
+mod suggestions;
 mod types;
 mod utils;
 
+pub use incremental::TextEdit;
 use utils::StackGuard;
 
 pub type ParserReturn<'ctx> = (Vec<&'ctx Item<'ctx>>, ErrorHandler);
@@ -149,14 +152,16 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
                 ty if ty == expected => return Ok(token),
                 ignored if ignoring.contains(&ignored) => {}
                 _ => {
-                    return Err(Locatable::new(
+                    let error = Locatable::new(
                         Error::Syntax(SyntaxError::Generic(format!(
                             "Expected {:?}, got {:?}",
                             expected.to_str(),
                             token.source()
                         ))),
                         Location::new(&token, self.current_file.file()),
-                    ));
+                    );
+
+                    return self.unexpected_token(token, &[expected], error);
                 }
             }
 
@@ -188,20 +193,22 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
                 ty if expected.contains(&ty) => return Ok(token),
                 ignored if ignoring.contains(&ignored) => {}
                 _ => {
-                    let expected = expected
+                    let expected_list = expected
                         .iter()
                         .map(|t| format!("{:?}", t.to_str()))
                         .collect::<Vec<_>>()
                         .join(", ");
 
-                    return Err(Locatable::new(
+                    let error = Locatable::new(
                         Error::Syntax(SyntaxError::Generic(format!(
                             "Expected one of {}, got {:?}",
-                            expected,
+                            expected_list,
                             token.source()
                         ))),
                         Location::new(&token, self.current_file.file()),
-                    ));
+                    );
+
+                    return self.unexpected_token(token, expected, error);
                 }
             }
 
@@ -1,13 +1,20 @@
-use crate::{parser::Parser, token::TokenType};
+use crate::{
+    parser::Parser,
+    token::{Token, TokenType},
+};
+#[cfg(feature = "no-std")]
+use alloc::{vec, vec::Vec};
 use crunch_shared::{
     crunch_proc::recursion_guard,
-    error::ParseResult,
+    error::{Error, Locatable, Location, ParseResult, Span, SyntaxError},
     tracing,
-    trees::ast::{Binding, Pattern},
+    trees::{
+        ast::{Binding, FieldPattern, Literal, Pattern, RangeKind},
+        ItemPath, Ref,
+    },
 };
 
 impl<'src, 'ctx> Parser<'src, 'ctx> {
-    // TODO: Binding via patterns
     /// ```ebnf
     /// Binding ::= 'ref'? 'mut'? Pattern (':' Type)?
     /// ```
@@ -34,7 +41,15 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
             _ => {}
         }
 
+        let start = self.peek()?.span();
         let pattern = self.pattern()?;
+        if pattern.is_rest() {
+            return Err(Locatable::new(
+                Error::Syntax(SyntaxError::MisplacedRestPattern),
+                Location::new(start, self.current_file),
+            ));
+        }
+
         let ty = if self.peek().map(|t| t.ty()) == Ok(TokenType::Colon) {
             self.eat(TokenType::Colon, [])?;
             Some(self.ascribed_type()?)
@@ -51,11 +66,61 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
     }
 
     /// ```ebnf
-    /// Pattern ::= Literal | Ident | ItemPath
+    /// Pattern ::= BindPattern ('|' BindPattern)*
     /// ```
     #[recursion_guard]
     #[crunch_shared::instrument(name = "pattern", skip(self))]
     fn pattern(&mut self) -> ParseResult<Pattern<'ctx>> {
+        let first = self.bind_pattern()?;
+
+        if self.peek().map(|t| t.ty()) == Ok(TokenType::Pipe) {
+            let mut alternatives = vec![first];
+            while self.peek().map(|t| t.ty()) == Ok(TokenType::Pipe) {
+                self.eat(TokenType::Pipe, [TokenType::Newline])?;
+                alternatives.push(self.bind_pattern()?);
+            }
+
+            Ok(Pattern::Or(alternatives))
+        } else {
+            Ok(first)
+        }
+    }
+
+    /// ```ebnf
+    /// BindPattern ::= Ident '@' BindPattern | AtomPattern
+    /// ```
+    #[recursion_guard]
+    fn bind_pattern(&mut self) -> ParseResult<Pattern<'ctx>> {
+        let pattern = self.atom_pattern()?;
+
+        match pattern {
+            Pattern::Ident(ident) if self.peek().map(|t| t.ty()) == Ok(TokenType::AtSign) => {
+                self.eat(TokenType::AtSign, [TokenType::Newline])?;
+                let bound = self.bind_pattern()?;
+
+                Ok(Pattern::Bind(ident, Ref::new(bound)))
+            }
+
+            pattern => Ok(pattern),
+        }
+    }
+
+    /// ```ebnf
+    /// AtomPattern ::=
+    ///     RangePattern
+    ///     | Literal
+    ///     | '_'
+    ///     | '..'
+    ///     | '(' Pattern ')'
+    ///     | 'tup' '[' PatternList ']'
+    ///     | ('arr' | 'slice') '[' PatternList ']'
+    ///     | ItemPath '(' PatternList ')'
+    ///     | ItemPath '{' FieldPatternList '}'
+    ///     | ItemPath
+    ///     | Ident
+    /// ```
+    #[recursion_guard]
+    fn atom_pattern(&mut self) -> ParseResult<Pattern<'ctx>> {
         let token = self.eat_of(
             [
                 TokenType::Ident,
@@ -64,6 +129,10 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
                 TokenType::Float,
                 TokenType::String,
                 TokenType::Rune,
+                TokenType::Underscore,
+                TokenType::DoubleDot,
+                TokenType::DoubleDotEqual,
+                TokenType::LeftParen,
             ],
             [TokenType::Newline],
         )?;
@@ -73,15 +142,82 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
             | TokenType::Bool
             | TokenType::Float
             | TokenType::String
-            | TokenType::Rune => Pattern::Literal(self.literal(&token, self.current_file)?),
+            | TokenType::Rune => {
+                let literal = self.literal(&token, self.current_file)?;
+
+                match self.peek().map(|t| t.ty()) {
+                    Ok(TokenType::DoubleDot) | Ok(TokenType::DoubleDotEqual) => {
+                        let dots = self.next()?;
+                        self.range_pattern(Some(literal), dots)?
+                    }
+                    _ => Pattern::Literal(literal),
+                }
+            }
+
+            TokenType::Underscore => Pattern::Wildcard,
+
+            // `..` followed by a literal is a range with no start, otherwise it's a rest pattern
+            TokenType::DoubleDot | TokenType::DoubleDotEqual
+                if self.peek().map_or(false, |t| is_literal(t.ty())) =>
+            {
+                self.range_pattern(None, token)?
+            }
+            TokenType::DoubleDot => Pattern::Rest,
+            TokenType::DoubleDotEqual => {
+                return Err(Locatable::new(
+                    Error::Syntax(SyntaxError::InclusiveRangeWithoutEnd),
+                    Location::new(token.span(), self.current_file),
+                ));
+            }
+
+            TokenType::LeftParen => {
+                let pattern = self.pattern()?;
+                self.eat(TokenType::RightParen, [TokenType::Newline])?;
+
+                pattern
+            }
+
+            TokenType::Ident
+                if matches!(token.source(), "tup" | "arr" | "slice")
+                    && self.peek().map(|t| t.ty()) == Ok(TokenType::LeftBrace) =>
+            {
+                self.eat(TokenType::LeftBrace, [TokenType::Newline])?;
+                let elements = self.pattern_list(TokenType::RightBrace)?;
+
+                if token.source() == "tup" {
+                    Pattern::Tuple(elements)
+                } else {
+                    Pattern::Slice(elements)
+                }
+            }
 
             TokenType::Ident => {
                 let ident = self.intern_ident(token);
 
-                if self.peek().map(|t| t.ty()) == Ok(TokenType::Dot) {
-                    Pattern::ItemPath(self.item_path(ident)?)
+                let is_path = self.peek().map(|t| t.ty()) == Ok(TokenType::Dot);
+                let path = if is_path {
+                    self.item_path(ident)?
                 } else {
-                    Pattern::Ident(ident)
+                    ItemPath::new(ident)
+                };
+
+                match self.peek().map(|t| t.ty()) {
+                    Ok(TokenType::LeftParen) => {
+                        self.eat(TokenType::LeftParen, [TokenType::Newline])?;
+                        let elements = self.pattern_list(TokenType::RightParen)?;
+
+                        Pattern::Variant { path, elements }
+                    }
+
+                    Ok(TokenType::LeftBracket) => {
+                        self.eat(TokenType::LeftBracket, [TokenType::Newline])?;
+                        let (fields, rest) = self.field_patterns()?;
+
+                        Pattern::Struct { path, fields, rest }
+                    }
+
+                    _ if is_path => Pattern::ItemPath(path),
+                    _ => Pattern::Ident(ident),
                 }
             }
 
@@ -90,4 +226,233 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
 
         Ok(pattern)
     }
+
+    /// Parses the rest of a range pattern after its `..` or `..=`, where the end is optional
+    /// for exclusive ranges with a start
+    ///
+    /// ```ebnf
+    /// RangePattern ::= Literal ('..' | '..=') Literal | Literal '..' | ('..' | '..=') Literal
+    /// ```
+    #[recursion_guard]
+    fn range_pattern(
+        &mut self,
+        start: Option<Literal<'ctx>>,
+        dots: Token<'_>,
+    ) -> ParseResult<Pattern<'ctx>> {
+        let kind = if dots.ty() == TokenType::DoubleDotEqual {
+            RangeKind::Inclusive
+        } else {
+            RangeKind::Exclusive
+        };
+
+        let end = if self.peek().map_or(false, |t| is_literal(t.ty())) {
+            let token = self.next()?;
+            Some(self.literal(&token, self.current_file)?)
+        } else if kind.is_inclusive() {
+            return Err(Locatable::new(
+                Error::Syntax(SyntaxError::InclusiveRangeWithoutEnd),
+                Location::new(dots.span(), self.current_file),
+            ));
+        } else {
+            None
+        };
+
+        Ok(Pattern::Range { start, end, kind })
+    }
+
+    /// Parses comma-separated patterns up to and including the closing delimiter,
+    /// allowing at most one `..` rest pattern within them
+    ///
+    /// ```ebnf
+    /// PatternList ::= (Pattern (',' Pattern)* ','?)?
+    /// ```
+    #[recursion_guard]
+    fn pattern_list(&mut self, close: TokenType) -> ParseResult<Vec<Pattern<'ctx>>> {
+        let mut elements = Vec::with_capacity(4);
+        let mut rest = None;
+
+        while self.peek()?.ty() != close {
+            if self.peek()?.ty() == TokenType::Newline {
+                self.eat(TokenType::Newline, [])?;
+                continue;
+            }
+
+            let span = self.peek()?.span();
+            let element = self.pattern()?;
+
+            if element.is_rest() {
+                if let Some(first) = rest.replace(span) {
+                    return Err(Locatable::new(
+                        Error::Syntax(SyntaxError::MultipleRestPatterns),
+                        Location::new(Span::merge(first, span), self.current_file),
+                    ));
+                }
+            }
+            elements.push(element);
+
+            if self.peek()?.ty() == TokenType::Comma {
+                self.eat(TokenType::Comma, [TokenType::Newline])?;
+            } else {
+                break;
+            }
+        }
+
+        self.eat(close, [TokenType::Newline])?;
+
+        Ok(elements)
+    }
+
+    /// ```ebnf
+    /// FieldPatternList ::= (FieldPattern (',' FieldPattern)* (',' '..')? ','?)? '}'
+    /// FieldPattern ::= Ident (':' Pattern)?
+    /// ```
+    #[recursion_guard]
+    fn field_patterns(&mut self) -> ParseResult<(Vec<FieldPattern<'ctx>>, bool)> {
+        let mut fields = Vec::with_capacity(4);
+        let mut rest = false;
+
+        while self.peek()?.ty() != TokenType::RightBracket {
+            if self.peek()?.ty() == TokenType::Newline {
+                self.eat(TokenType::Newline, [])?;
+                continue;
+            }
+
+            let token = self.eat_of(
+                [TokenType::Ident, TokenType::DoubleDot],
+                [TokenType::Newline],
+            )?;
+
+            if token.ty() == TokenType::DoubleDot {
+                if rest {
+                    return Err(Locatable::new(
+                        Error::Syntax(SyntaxError::MultipleRestPatterns),
+                        Location::new(token.span(), self.current_file),
+                    ));
+                }
+
+                rest = true;
+            } else {
+                let name = self.intern_ident(token);
+
+                // `Type { field }` is shorthand for `Type { field: field }`
+                let pattern = if self.peek()?.ty() == TokenType::Colon {
+                    self.eat(TokenType::Colon, [TokenType::Newline])?;
+                    self.pattern()?
+                } else {
+                    Pattern::Ident(name)
+                };
+
+                fields.push(FieldPattern { name, pattern });
+            }
+
+            if self.peek()?.ty() == TokenType::Comma {
+                self.eat(TokenType::Comma, [TokenType::Newline])?;
+            } else {
+                break;
+            }
+        }
+        self.eat(TokenType::RightBracket, [TokenType::Newline])?;
+
+        Ok((fields, rest))
+    }
+}
+
+fn is_literal(ty: TokenType) -> bool {
+    matches!(
+        ty,
+        TokenType::Int | TokenType::Bool | TokenType::Float | TokenType::String | TokenType::Rune
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, sync::Arc, vec::Vec};
+    use crunch_shared::{
+        config::BuildOptions,
+        context::{Arenas, Context, OwnedArenas},
+        error::ErrorHandler,
+        files::{CurrentFile, FileId},
+        trees::ast::{ExprKind, ItemKind, StmtKind},
+    };
+
+    /// Parses the pattern of a match arm
+    fn parse<'ctx>(
+        pattern: &str,
+        context: &'ctx Context<'ctx>,
+    ) -> Result<Pattern<'ctx>, ErrorHandler> {
+        let source = format!(
+            "fn main()\n    match x\n        {} =>\n            empty\n        end\n    end\nend\n",
+            pattern,
+        );
+
+        let (items, _) = Parser::new(
+            &source,
+            Arc::new(BuildOptions::new("patterns.crunch")),
+            CurrentFile::new(FileId::new(0), source.len()),
+            context,
+        )
+        .parse()?;
+
+        match &items[0].kind {
+            ItemKind::Func { body, .. } => match &body.stmts[0].kind {
+                StmtKind::Expr(expr) => match &expr.kind {
+                    ExprKind::Match(match_) => Ok(match_.arms[0].bind.pattern.clone()),
+                    kind => panic!("expected a match, got {:?}", kind),
+                },
+                kind => panic!("expected an expression, got {:?}", kind),
+            },
+            kind => panic!("expected a function, got {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn slices() {
+        let owned_arenas = OwnedArenas::default();
+        let context = Context::new(Arenas::from(&owned_arenas));
+        let ident = |name| Pattern::Ident(context.strings().intern(name));
+
+        assert_eq!(
+            parse("slice[first, rest @ .., last]", &context).unwrap(),
+            Pattern::Slice(vec![
+                ident("first"),
+                Pattern::Bind(context.strings().intern("rest"), Ref::new(Pattern::Rest)),
+                ident("last"),
+            ]),
+        );
+        assert_eq!(
+            parse("arr[.., last]", &context).unwrap(),
+            Pattern::Slice(vec![Pattern::Rest, ident("last")]),
+        );
+        assert_eq!(
+            parse("slice[]", &context).unwrap(),
+            Pattern::Slice(Vec::new()),
+        );
+    }
+
+    #[test]
+    fn tuples_and_alternatives() {
+        let owned_arenas = OwnedArenas::default();
+        let context = Context::new(Arenas::from(&owned_arenas));
+        let ident = |name| Pattern::Ident(context.strings().intern(name));
+
+        assert_eq!(
+            parse("tup[a, _] | tup[_, a]", &context).unwrap(),
+            Pattern::Or(vec![
+                Pattern::Tuple(vec![ident("a"), Pattern::Wildcard]),
+                Pattern::Tuple(vec![Pattern::Wildcard, ident("a")]),
+            ]),
+        );
+    }
+
+    #[test]
+    fn rest_outside_of_a_list() {
+        let owned_arenas = OwnedArenas::default();
+        let context = Context::new(Arenas::from(&owned_arenas));
+
+        let errors = parse("..", &context).unwrap_err();
+        assert!(errors
+            .errors()
+            .any(|err| **err == Error::Syntax(SyntaxError::MisplacedRestPattern)));
+    }
 }
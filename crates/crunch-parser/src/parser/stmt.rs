@@ -24,6 +24,13 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
                 Ok(None)
             }
 
+            // `empty` marks a block that intentionally does nothing
+            TokenType::Empty => {
+                self.eat(TokenType::Empty, [])?;
+                self.eat(TokenType::Newline, [])?;
+                Ok(None)
+            }
+
             TokenType::Let | TokenType::Const => {
                 let start_token =
                     self.eat_of([TokenType::Let, TokenType::Const], [TokenType::Newline])?;
@@ -39,21 +46,40 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
                     (self.intern_ident(ident), ident.span())
                 };
 
-                self.eat(TokenType::Colon, [TokenType::Newline])?;
-                if self.peek()?.ty() == TokenType::Newline {
-                    self.eat(TokenType::Newline, [])?;
-                }
+                // `let x = 10` is a common enough mistake that it's parsed as `let x := 10`
+                let untyped = if self.peek()?.ty() == TokenType::Equal {
+                    let equal = self.eat(TokenType::Equal, [])?;
+                    self.mistaken_assign(equal);
+
+                    true
+                } else {
+                    self.eat(TokenType::Colon, [TokenType::Newline])?;
+                    if self.peek()?.ty() == TokenType::Newline {
+                        self.eat(TokenType::Newline, [])?;
+                    }
+
+                    if self.peek()?.ty() == TokenType::Equal {
+                        self.eat(TokenType::Equal, [])?;
+                        true
+                    } else {
+                        false
+                    }
+                };
 
-                let ty = if self.peek()?.ty() == TokenType::Equal {
-                    self.eat(TokenType::Equal, [])?;
+                let ty = if untyped {
                     Locatable::new(
                         self.context.ast_type(Type::Unknown),
                         Location::new(span, self.current_file),
                     )
                 } else {
                     let ty = self.ascribed_type()?;
-                    self.eat(TokenType::Colon, [TokenType::Newline])?;
-                    self.eat(TokenType::Equal, [])?;
+                    if self.peek()?.ty() == TokenType::Equal {
+                        let equal = self.eat(TokenType::Equal, [])?;
+                        self.mistaken_assign(equal);
+                    } else {
+                        self.eat(TokenType::Colon, [TokenType::Newline])?;
+                        self.eat(TokenType::Equal, [])?;
+                    }
 
                     ty
                 };
@@ -135,6 +161,11 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
 
         let mut stmts = Vec::with_capacity(capacity);
         while let Ok(true) = self.peek().map(|p| !breaks.contains(&p.ty())) {
+            // Leave braces used in place of `end` for `eat_of()` to point out
+            if self.peek()?.ty() == TokenType::RightBracket && breaks.contains(&TokenType::End) {
+                break;
+            }
+
             let stmt = self.stmt()?;
 
             if let Some(stmt) = stmt {
@@ -1,11 +1,13 @@
 use alloc::{
     format,
     string::{String, ToString},
+    vec,
+    vec::Vec,
 };
 use core::{char, ops::Range};
 use crunch_shared::{
     error::{Error, SyntaxError},
-    trees::ast::{Rune, Text},
+    trees::ast::{FormatSpec, Rune, Text},
 };
 
 // TODO: Finish logos string lexer and add string escapes
@@ -44,9 +46,263 @@ use crunch_shared::{
 //     Other,
 // }
 
+/// The pieces of a string literal's source, its prefixes, delimiters and contents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct StringLiteral<'a> {
+    /// Whether the string is a byte string, `b"..."`
+    pub byte: bool,
+    /// Whether the string is raw and skips escapes and interpolation, `r"..."`
+    pub raw: bool,
+    /// The text between the string's delimiters
+    pub contents: &'a str,
+    /// The offset of the contents from the start of the literal
+    pub offset: usize,
+    /// The lines that make up the string as ranges within the contents, multi-line strings
+    /// have their indentation stripped so their lines aren't contiguous
+    pub lines: Vec<Range<usize>>,
+}
+
+impl<'a> StringLiteral<'a> {
+    /// Splits the source of a string literal token into its pieces
+    pub fn new(source: &'a str) -> Self {
+        let mut offset = 0;
+
+        let byte = source[offset..].starts_with('b');
+        if byte {
+            offset += 1;
+        }
+
+        let raw = source[offset..].starts_with('r');
+        if raw {
+            offset += 1;
+        }
+
+        let delimiter =
+            if source[offset..].starts_with("\"\"\"") || source[offset..].starts_with("'''") {
+                3
+            } else {
+                1
+            };
+
+        let contents = &source[offset + delimiter..source.len() - delimiter];
+        let lines = if delimiter == 3 {
+            dedent(contents)
+        } else {
+            vec![0..contents.len()]
+        };
+
+        Self {
+            byte,
+            raw,
+            contents,
+            offset: offset + delimiter,
+            lines,
+        }
+    }
+
+    /// Splits the string into text and interpolated expressions line by line, so an
+    /// interpolated value can't span multiple lines
+    pub fn segments(&self) -> Result<Vec<Segment>, (Error, Range<usize>)> {
+        let mut segments = Vec::new();
+
+        for line in self.lines.iter() {
+            let offset = |range: Range<usize>| line.start + range.start..line.start + range.end;
+
+            for segment in split_interpolations(&self.contents[line.clone()])
+                .map_err(|(err, range)| (err, offset(range)))?
+            {
+                segments.push(match segment {
+                    Segment::Text(range) => Segment::Text(offset(range)),
+                    Segment::Hole { expr, spec } => Segment::Hole {
+                        expr: offset(expr),
+                        spec,
+                    },
+                });
+            }
+        }
+
+        Ok(segments)
+    }
+}
+
+/// Strips the common indentation from the lines of a multi-line string along with the
+/// line breaks directly after the opening delimiter and directly before the closing one,
+/// returning the remaining text of each line (including its line break)
+fn dedent(contents: &str) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for (idx, _) in contents.match_indices('\n') {
+        lines.push(start..idx + 1);
+        start = idx + 1;
+    }
+    lines.push(start..contents.len());
+
+    // Strings written on a single line are kept as-is
+    if lines.len() == 1 {
+        return lines;
+    }
+
+    let is_blank = |line: &Range<usize>| contents[line.clone()].trim().is_empty();
+    if is_blank(&lines[0]) {
+        lines.remove(0);
+    }
+
+    if lines.last().map_or(false, is_blank) {
+        lines.pop();
+
+        if let Some(last) = lines.last_mut() {
+            let line = &contents[last.clone()];
+            last.end -= line.len() - line.trim_end_matches(&['\r', '\n'][..]).len();
+        }
+    }
+
+    let indentation = |line: &Range<usize>| {
+        contents[line.clone()]
+            .bytes()
+            .take_while(|&byte| byte == b' ' || byte == b'\t')
+            .count()
+    };
+    let indent = lines
+        .iter()
+        .filter(|line| !is_blank(line))
+        .map(indentation)
+        .min()
+        .unwrap_or(0);
+
+    lines
+        .into_iter()
+        .map(|line| {
+            let strip = indentation(&line).min(indent);
+            line.start + strip..line.end
+        })
+        .collect()
+}
+
+/// A single piece of a string literal, with all ranges relative to the start of the
+/// string's contents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Segment {
+    /// Raw text that still contains escape sequences
+    Text(Range<usize>),
+    /// An interpolated expression, `{expr}` or `{expr:spec}`
+    Hole {
+        expr: Range<usize>,
+        spec: FormatSpec,
+    },
+}
+
+/// Splits a string's contents into raw text and interpolated expressions, turning `{{`
+/// and `}}` into literal braces. Empty holes such as `{}` and `{:?}` are rejected since
+/// there are no positional arguments to fill them in with
+pub(super) fn split_interpolations(source: &str) -> Result<Vec<Segment>, (Error, Range<usize>)> {
+    let bytes = source.as_bytes();
+    let mut segments = Vec::new();
+    let (mut start, mut index) = (0, 0);
+
+    while index < bytes.len() {
+        match bytes[index] {
+            // Skip over escape sequences so that the braces of `\u{...}` and friends
+            // aren't mistaken for interpolations
+            b'\\' => {
+                index += 1;
+
+                if matches!(
+                    bytes.get(index),
+                    Some(b'x') | Some(b'u') | Some(b'U') | Some(b'o') | Some(b'b')
+                ) && bytes.get(index + 1) == Some(&b'{')
+                {
+                    while index < bytes.len() && bytes[index] != b'}' {
+                        index += 1;
+                    }
+                }
+
+                index += 1;
+            }
+
+            b'{' if bytes.get(index + 1) == Some(&b'{') => {
+                segments.push(Segment::Text(start..index + 1));
+                index += 2;
+                start = index;
+            }
+
+            b'}' if bytes.get(index + 1) == Some(&b'}') => {
+                segments.push(Segment::Text(start..index + 1));
+                index += 2;
+                start = index;
+            }
+
+            b'}' => {
+                return Err((
+                    Error::Syntax(SyntaxError::UnmatchedFormatBrace),
+                    index..index + 1,
+                ));
+            }
+
+            b'{' => {
+                let open = index;
+                let close = source[open..].find('}').map(|close| open + close).ok_or((
+                    Error::Syntax(SyntaxError::UnclosedFormatBrace),
+                    open..open + 1,
+                ))?;
+
+                let hole = &source[open + 1..close];
+                let (expr, spec) = match hole
+                    .char_indices()
+                    .find(|&(i, c)| c == ':' && hole.as_bytes().get(i + 1) != Some(&b'='))
+                {
+                    Some((colon, _)) => (&hole[..colon], &hole[colon + 1..]),
+                    None => (hole, ""),
+                };
+
+                let spec = match spec.trim() {
+                    "" => FormatSpec::Display,
+                    "?" => FormatSpec::Debug,
+                    spec => {
+                        let spec_start = open + 1 + expr.len() + 1;
+
+                        return Err((
+                            Error::Syntax(SyntaxError::InvalidFormatSpec(spec.to_string())),
+                            spec_start..close,
+                        ));
+                    }
+                };
+
+                if expr.trim().is_empty() {
+                    return Err((Error::Syntax(SyntaxError::EmptyFormatHole), open..close + 1));
+                }
+
+                if start != open {
+                    segments.push(Segment::Text(start..open));
+                }
+                segments.push(Segment::Hole {
+                    expr: open + 1..open + 1 + expr.len(),
+                    spec,
+                });
+
+                index = close + 1;
+                start = index;
+            }
+
+            _ => index += 1,
+        }
+    }
+
+    if start < bytes.len() || segments.is_empty() {
+        segments.push(Segment::Text(start..bytes.len()));
+    }
+
+    Ok(segments)
+}
+
+/// Returns `true` if any of the given segments contain an interpolated expression
+pub(super) fn is_interpolated(segments: &[Segment]) -> bool {
+    segments
+        .iter()
+        .any(|segment| matches!(segment, Segment::Hole { .. }))
+}
+
 // TODO: Verify that these all work and are actually recognized by the parser/lexer and supported by
 //       the backends
-// TODO: Have this process inlined variables & formatting
 /// Turns all escape sequences in a string into their actual representation
 #[inline]
 pub(super) fn unescape_string<I: Iterator<Item = char>>(
@@ -356,6 +612,81 @@ impl<I: Iterator<Item = char>> CharStream<I> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn interpolations() {
+        assert_eq!(
+            split_interpolations("Hello, {name}!"),
+            Ok(vec![
+                Segment::Text(0..7),
+                Segment::Hole {
+                    expr: 8..12,
+                    spec: FormatSpec::Display,
+                },
+                Segment::Text(13..14),
+            ]),
+        );
+        assert_eq!(
+            split_interpolations("{value:?}"),
+            Ok(vec![Segment::Hole {
+                expr: 1..6,
+                spec: FormatSpec::Debug,
+            }]),
+        );
+        assert_eq!(
+            split_interpolations("{{}} {{:?}}"),
+            Ok(vec![
+                Segment::Text(0..1),
+                Segment::Text(2..3),
+                Segment::Text(4..6),
+                Segment::Text(7..10),
+            ]),
+        );
+        assert_eq!(
+            split_interpolations(r"\u{0020}"),
+            Ok(vec![Segment::Text(0..8)]),
+        );
+        assert!(split_interpolations("{value").is_err());
+        assert!(split_interpolations("value}").is_err());
+        assert!(split_interpolations("{value:x}").is_err());
+        assert_eq!(
+            split_interpolations("Hello, {}!"),
+            Err((Error::Syntax(SyntaxError::EmptyFormatHole), 7..9)),
+        );
+        assert_eq!(
+            split_interpolations("{ :?}"),
+            Err((Error::Syntax(SyntaxError::EmptyFormatHole), 0..5)),
+        );
+    }
+
+    #[test]
+    fn string_literals() {
+        let literal = StringLiteral::new(r#"br"raw \n""#);
+        assert!(literal.byte && literal.raw);
+        assert_eq!(literal.contents, r"raw \n");
+        assert_eq!(literal.offset, 3);
+
+        let source = "\"\"\"\n    SELECT *\n      FROM users\n\n    WHERE id = {id}\n    \"\"\"";
+        let literal = StringLiteral::new(source);
+        let text: String = literal
+            .lines
+            .iter()
+            .map(|line| &literal.contents[line.clone()])
+            .collect();
+        assert_eq!(text, "SELECT *\n  FROM users\n\nWHERE id = {id}");
+
+        let segments = literal.segments().unwrap();
+        match segments.last() {
+            Some(Segment::Hole { expr, .. }) => {
+                assert_eq!(&literal.contents[expr.clone()], "id");
+            }
+            segment => panic!("expected an interpolated value, got {:?}", segment),
+        }
+
+        let literal = StringLiteral::new("'''  single line'''");
+        assert_eq!(literal.lines, vec![0..13]);
+    }
 
     #[test]
     fn unicode_16bit() {
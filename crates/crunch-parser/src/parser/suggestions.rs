@@ -0,0 +1,250 @@
+use crate::{
+    parser::Parser,
+    token::{Token, TokenType},
+};
+use alloc::{borrow::ToOwned, format};
+use crunch_shared::{
+    error::{Error, Locatable, Location, ParseResult, Span, Suggestion, SyntaxError},
+    utils,
+};
+
+/// Keywords from other languages along with the keyword they're usually meant as
+const FOREIGN_KEYWORDS: &[(&str, TokenType)] = &[
+    ("def", TokenType::Function),
+    ("func", TokenType::Function),
+    ("function", TokenType::Function),
+    ("struct", TokenType::Type),
+    ("class", TokenType::Type),
+    ("interface", TokenType::Trait),
+    ("impl", TokenType::Extend),
+    ("use", TokenType::Import),
+    ("pub", TokenType::Exposed),
+    ("mod", TokenType::Module),
+];
+
+/// The keywords that can start an item
+const ITEM_KEYWORDS: &[TokenType] = &[
+    TokenType::Function,
+    TokenType::Type,
+    TokenType::Enum,
+    TokenType::Trait,
+    TokenType::Extend,
+    TokenType::Import,
+    TokenType::Alias,
+    TokenType::Extern,
+    TokenType::Module,
+    TokenType::Exposed,
+    TokenType::Package,
+    TokenType::Const,
+];
+
+/// Suggestions for common mistakes
+impl<'src, 'ctx> Parser<'src, 'ctx> {
+    /// Handles `token` showing up where one of `expected` should have been. Mistakes that
+    /// the parser can see past are recorded and the token that should have been there is
+    /// returned, anything else becomes `error` with a fix attached where one is known
+    pub(super) fn unexpected_token(
+        &mut self,
+        token: Token<'src>,
+        expected: &[TokenType],
+        error: Locatable<Error>,
+    ) -> ParseResult<Token<'src>> {
+        let location = Location::new(&token, self.current_file);
+
+        match token.ty() {
+            // `fn main() {`
+            TokenType::LeftBracket
+                if expected.contains(&TokenType::Newline)
+                    && self.peek().map(|peek| peek.ty()) == Ok(TokenType::Newline) =>
+            {
+                let suggestion = Suggestion::new("blocks start on the next line, remove the `{`")
+                    .with_edit(location, "");
+                self.error_handler
+                    .push_err(error.map(|err| err.with_suggestion(suggestion)));
+
+                self.next()
+            }
+
+            // `}` closing a block
+            TokenType::RightBracket if expected.contains(&TokenType::End) => {
+                let suggestion =
+                    Suggestion::new("blocks are closed with `end`").with_edit(location, "end");
+                self.error_handler
+                    .push_err(error.map(|err| err.with_suggestion(suggestion)));
+
+                Ok(Token::new(TokenType::End, token.source(), token.range()))
+            }
+
+            TokenType::LeftBracket if expected.contains(&TokenType::Newline) => {
+                Err(error.map(|err| {
+                    err.with_suggestion(Suggestion::new(
+                        "blocks start on the next line and are closed with `end` instead of braces",
+                    ))
+                }))
+            }
+
+            TokenType::Equal
+                if expected.contains(&TokenType::Colon)
+                    || expected.contains(&TokenType::Newline) =>
+            {
+                Err(error.map(|err| err.with_suggestion(assign_suggestion(location))))
+            }
+
+            TokenType::Ident => match self.suggest_keyword(token, expected) {
+                Some(suggestion) => Err(error.map(|err| err.with_suggestion(suggestion))),
+                None => Err(error),
+            },
+
+            _ => Err(error),
+        }
+    }
+
+    /// Records an `=` that was written in place of `:=`, after which parsing carries on
+    /// as if it were a `:=`
+    pub(super) fn mistaken_assign(&mut self, equal: Token<'src>) {
+        let location = Location::new(&equal, self.current_file);
+
+        self.error_handler.push_err(Locatable::new(
+            Error::Syntax(SyntaxError::Generic(
+                "Expected \":=\", got \"=\"".to_owned(),
+            ))
+            .with_suggestion(assign_suggestion(location)),
+            location,
+        ));
+    }
+
+    /// Creates the error for a token that can't start an item, suggesting the keyword that's
+    /// likely missing or misspelled
+    pub(super) fn invalid_top_level(&self, token: Token<'src>) -> Locatable<Error> {
+        let location = Location::new(&token, self.current_file);
+        let error = Error::Syntax(SyntaxError::InvalidTopLevel(format!("{}", token.ty())));
+
+        if token.ty() != TokenType::Ident {
+            return Locatable::new(error, location);
+        }
+
+        let suggestion = match self.token_stream.clone().next() {
+            // `main()` without the `fn`
+            Some(next) if next.ty() == TokenType::LeftParen => {
+                let start = Location::new(
+                    Span::new(token.span().start(), token.span().start()),
+                    self.current_file,
+                );
+
+                Some(Suggestion::new("functions are declared with `fn`").with_edit(start, "fn "))
+            }
+
+            _ => self.suggest_keyword(token, ITEM_KEYWORDS),
+        };
+
+        match suggestion {
+            Some(suggestion) => Locatable::new(error.with_suggestion(suggestion), location),
+            None => Locatable::new(error, location),
+        }
+    }
+
+    /// Suggests the keyword that an identifier was likely meant to be
+    fn suggest_keyword(&self, ident: Token<'src>, expected: &[TokenType]) -> Option<Suggestion> {
+        let keyword = FOREIGN_KEYWORDS
+            .iter()
+            .find(|(word, keyword)| *word == ident.source() && expected.contains(keyword))
+            .map(|(_, keyword)| keyword.to_str())
+            .or_else(|| {
+                let keywords = expected
+                    .iter()
+                    .filter(|&&ty| is_keyword(ty))
+                    .map(|ty| ty.to_str());
+
+                utils::closest_match(ident.source(), keywords)
+            })?;
+
+        Some(
+            Suggestion::new(format!("did you mean `{}`?", keyword))
+                .with_edit(Location::new(&ident, self.current_file), keyword),
+        )
+    }
+}
+
+fn is_keyword(ty: TokenType) -> bool {
+    !matches!(
+        ty,
+        TokenType::Error
+            | TokenType::Comment
+            | TokenType::DocComment
+            | TokenType::Newline
+            | TokenType::Space
+            | TokenType::Rune
+            | TokenType::String
+            | TokenType::Float
+            | TokenType::Int
+            | TokenType::Bool
+            | TokenType::Ident
+    ) && ty.to_str().chars().all(char::is_alphabetic)
+}
+
+fn assign_suggestion(location: Location) -> Suggestion {
+    Suggestion::new("values are assigned with `:=`").with_edit(location, ":=")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::String, sync::Arc, vec::Vec};
+    use crunch_shared::{
+        config::BuildOptions,
+        context::{Arenas, Context, OwnedArenas},
+        error::Edit,
+        files::{CurrentFile, FileId},
+    };
+
+    /// Applies every fix suggested while parsing `source`
+    fn fix(source: &str) -> String {
+        let owned_arenas = OwnedArenas::default();
+        let context = Context::new(Arenas::from(&owned_arenas));
+
+        let errors = Parser::new(
+            source,
+            Arc::new(BuildOptions::new("suggestions.crunch")),
+            CurrentFile::new(FileId::new(0), source.len()),
+            &context,
+        )
+        .parse()
+        .unwrap_err();
+
+        let edits: Vec<&Edit> = errors
+            .errors()
+            .filter_map(|err| err.suggestion())
+            .flat_map(|suggestion| suggestion.edits())
+            .collect();
+
+        Edit::apply_all(source, edits).0
+    }
+
+    #[test]
+    fn braces_and_equals() {
+        assert_eq!(
+            fix("fn main() {\n    let x = 10\n    x = x + 1\n}\n"),
+            "fn main() \n    let x := 10\n    x := x + 1\nend\n",
+        );
+    }
+
+    #[test]
+    fn missing_fn() {
+        assert_eq!(
+            fix("main()\n    return\nend\n"),
+            "fn main()\n    return\nend\n",
+        );
+    }
+
+    #[test]
+    fn misspelled_keywords() {
+        assert_eq!(
+            fix("fnn main()\n    return\nend\n"),
+            "fn main()\n    return\nend\n"
+        );
+        assert_eq!(
+            fix("def main()\n    return\nend\n"),
+            "fn main()\n    return\nend\n"
+        );
+    }
+}
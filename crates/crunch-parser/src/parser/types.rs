@@ -42,6 +42,7 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
     ///     | Path '[' Type ']'
     ///     | 'type' '[' Type ']'
     ///     | 'slice' '[' Type ']'
+    ///     | 'range' '[' Type ']'
     ///     | 'const' Ident (':' Type)?
     ///     | Type 'if' Expr 'else' Type
     ///     | 'arr' '[' Literal, Type ']'
@@ -56,6 +57,11 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         self.ascribed_type_internal(0)
     }
 
+    /// Parses the type a prefix operator is applied to, so that `!A & B` is `(!A) & B`
+    fn type_operand(&mut self) -> ParseResult<Locatable<&'ctx Type<'ctx>>> {
+        self.ascribed_type_internal(TypePrecedence::And.precedence())
+    }
+
     #[inline(always)]
     fn type_precedence(&self) -> usize {
         self.peek
@@ -87,14 +93,18 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
             }
 
             while precedence < self.type_precedence() {
-                token = self.next()?;
-
-                let infix = Self::type_infix(token.ty());
-                if let Some(infix) = infix {
-                    left = infix(self, token, left)?;
+                let infix = if let Some(infix) = self
+                    .peek()
+                    .ok()
+                    .and_then(|peek| Self::type_infix(peek.ty()))
+                {
+                    infix
                 } else {
                     break;
-                }
+                };
+
+                token = self.next()?;
+                left = infix(self, token, left)?;
             }
 
             Ok(left)
@@ -183,6 +193,16 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
                         (Type::Slice { element }, Some(end))
                     }
 
+                    "range" => {
+                        parser.eat(TokenType::LeftBrace, [TokenType::Newline])?;
+                        let element = parser.ascribed_type()?;
+                        let end = parser
+                            .eat(TokenType::RightBrace, [TokenType::Newline])?
+                            .span();
+
+                        (Type::Range { element }, Some(end))
+                    }
+
                     "tup" => {
                         parser.eat(TokenType::LeftBrace, [TokenType::Newline])?;
 
@@ -324,7 +344,7 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
             // Negation
             TokenType::Bang => |parser, bang| {
                 let _frame = parser.add_stack_frame()?;
-                let ty = parser.ascribed_type()?;
+                let ty = parser.type_operand()?;
                 let loc = Location::new(Span::merge(bang.span(), ty.span()), parser.current_file);
 
                 Ok(Locatable::new(parser.context.ast_type(Type::Not(ty)), loc))
@@ -357,12 +377,19 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
                     }
 
                     params.push(parser.ascribed_type()?);
+
+                    if parser.peek()?.ty() == TokenType::Comma {
+                        parser.eat(TokenType::Comma, [TokenType::Newline])?;
+                    } else {
+                        break;
+                    }
                 }
 
                 let end = parser
                     .eat(TokenType::RightParen, [TokenType::Newline])?
                     .span();
                 let ret = if parser.peek().map(|t| t.ty()) == Ok(TokenType::RightArrow) {
+                    parser.eat(TokenType::RightArrow, [])?;
                     parser.ascribed_type()?
                 } else {
                     Locatable::new(
@@ -434,7 +461,7 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
                 } else {
                     false
                 };
-                let referee = parser.ascribed_type()?;
+                let referee = parser.type_operand()?;
                 let loc = Location::new(
                     Span::merge(star.span(), referee.span()),
                     parser.current_file,
@@ -454,7 +481,7 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
 
                 let mutable =
                     parser.eat_of([TokenType::Const, TokenType::Mut], [])?.ty() == TokenType::Mut;
-                let pointee = parser.ascribed_type()?;
+                let pointee = parser.type_operand()?;
                 let loc = Location::new(
                     Span::merge(star.span(), pointee.span()),
                     parser.current_file,
@@ -481,7 +508,11 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
             TokenType::Ampersand | TokenType::Pipe => |parser, operand, lhs| {
                 let _frame = parser.add_stack_frame()?;
 
-                let rhs = parser.ascribed_type()?;
+                // Only take operators that bind tighter so that `A | B | C` is `(A | B) | C`
+                let precedence = TypePrecedence::try_from(operand.ty())
+                    .map(|p| p.precedence())
+                    .unwrap_or(0);
+                let rhs = parser.ascribed_type_internal(precedence)?;
 
                 let loc = Location::new(Span::merge(lhs.span(), rhs.span()), parser.current_file);
                 let ty = Type::Operand(Sided {
@@ -1,15 +1,26 @@
 use crate::{
-    parser::{string_escapes, Parser},
+    parser::{
+        string_escapes::{self, Segment, StringLiteral},
+        Parser,
+    },
     token::{Token, TokenType},
 };
-use alloc::{format, rc::Rc, string::ToString, vec, vec::Vec};
+use alloc::{
+    format,
+    rc::Rc,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
 use crunch_shared::{
     crunch_proc::recursion_guard,
     error::{Error, Locatable, Location, ParseResult, Span, SyntaxError, Warning},
     files::CurrentFile,
     strings::StrT,
     trees::{
-        ast::{AssignKind, BinaryOp, CompOp, Float, Integer, Literal, LiteralVal, Type, UnaryOp},
+        ast::{
+            AssignKind, BinaryOp, CompOp, Float, Integer, Literal, LiteralVal, Text, Type, UnaryOp,
+        },
         ItemPath, Sign,
     },
 };
@@ -72,6 +83,38 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
         Ok(ItemPath::new(path))
     }
 
+    /// Splits the contents of a string literal into text and interpolated values, `start`
+    /// being the position of the contents within the source file
+    pub(super) fn string_segments(
+        &self,
+        literal: &StringLiteral<'_>,
+        start: usize,
+        file: CurrentFile,
+    ) -> ParseResult<Vec<Segment>> {
+        literal.segments().map_err(|(err, range)| {
+            Locatable::new(
+                err,
+                Location::new((start + range.start, start + range.end), file),
+            )
+        })
+    }
+
+    /// Processes the escape sequences within a run of string text, `start` being the
+    /// position of the text within the source file
+    pub(super) fn unescape_text(
+        &self,
+        text: &str,
+        start: usize,
+        file: CurrentFile,
+    ) -> ParseResult<Text> {
+        string_escapes::unescape_string(text.chars()).map_err(|(err, range)| {
+            Locatable::new(
+                err,
+                Location::new((start + range.start, start + range.end), file),
+            )
+        })
+    }
+
     #[recursion_guard]
     pub(crate) fn literal(
         &mut self,
@@ -193,31 +236,51 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
             }
 
             TokenType::String => {
-                let byte_str = if source.starts_with('b') {
-                    source = &source[1..];
-                    true
+                let literal = StringLiteral::new(source);
+                let byte_str = literal.byte;
+                let start = token.range().start + literal.offset;
+
+                let string = if literal.raw {
+                    // Raw strings are taken verbatim, escapes, braces and all
+                    Text::new(
+                        literal
+                            .lines
+                            .iter()
+                            .map(|line| &literal.contents[line.clone()])
+                            .collect(),
+                    )
+
+                // Byte strings are never interpolated, so their braces are kept as-is
+                } else if byte_str {
+                    let mut string = String::with_capacity(literal.contents.len());
+                    for line in literal.lines.iter() {
+                        let text = &literal.contents[line.clone()];
+                        string
+                            .push_str(self.unescape_text(text, start + line.start, file)?.as_str());
+                    }
+
+                    Text::new(string)
                 } else {
-                    false
-                };
+                    let segments = self.string_segments(&literal, start, file)?;
+                    if string_escapes::is_interpolated(&segments) {
+                        return Err(Locatable::new(
+                            Error::Syntax(SyntaxError::InterpolationNotAllowed),
+                            Location::new(token.span(), file),
+                        ));
+                    }
 
-                let string = match (source.chars().next(), source.chars().last()) {
-                    (Some('"'), Some('"')) => {
-                        string_escapes::unescape_string(source[1..source.len() - 1].chars())
-                            .map_err(|(err, range)| {
-                                Locatable::new(
-                                    err,
-                                    Location::new(
-                                        (
-                                            token.range().start + 1 + range.start,
-                                            token.range().start + 1 + range.end,
-                                        ),
-                                        file,
-                                    ),
-                                )
-                            })?
+                    let mut string = String::with_capacity(literal.contents.len());
+                    for segment in segments {
+                        if let Segment::Text(range) = segment {
+                            let text = &literal.contents[range.clone()];
+                            string.push_str(
+                                self.unescape_text(text, start + range.start, file)?
+                                    .as_str(),
+                            );
+                        }
                     }
 
-                    _ => unreachable!(),
+                    Text::new(string)
                 };
 
                 let loc = Location::new(token.span(), self.current_file);
@@ -441,6 +504,8 @@ impl<'src, 'ctx> Parser<'src, 'ctx> {
             TokenType::Caret      => BinaryOp::BitXor,
             TokenType::Shl        => BinaryOp::Shl,
             TokenType::Shr        => BinaryOp::Shr,
+            TokenType::And        => BinaryOp::And,
+            TokenType::Or         => BinaryOp::Or,
             ty                    => {
                 return Err(Locatable::new(
                     Error::Syntax(SyntaxError::Generic(format!(
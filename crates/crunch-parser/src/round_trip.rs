@@ -0,0 +1,311 @@
+//! Tests that printing a parsed tree and parsing it again gives back the same tree, and that
+//! folding a tree without changing anything rebuilds it exactly
+
+use crate::parser::{incremental::Relocate, Parser};
+use alloc::{string::String, sync::Arc, vec::Vec};
+use crunch_shared::{
+    config::BuildOptions,
+    context::{Arenas, Context, OwnedArenas},
+    files::{CurrentFile, FileId},
+    trees::{ast::Item, printer::PrettyPrint},
+    visitors::ast::Fold,
+};
+
+/// Rebuilds trees without changing them
+struct Identity<'ctx> {
+    context: &'ctx Context<'ctx>,
+}
+
+impl<'ctx> Fold<'ctx> for Identity<'ctx> {
+    fn context(&self) -> &'ctx Context<'ctx> {
+        self.context
+    }
+}
+
+fn parse<'ctx>(source: &str, context: &'ctx Context<'ctx>) -> Vec<&'ctx Item<'ctx>> {
+    let items = Parser::new(
+        source,
+        Arc::new(BuildOptions::new("round_trip.crunch")),
+        CurrentFile::new(FileId::new(0), source.len()),
+        context,
+    )
+    .parse()
+    .unwrap_or_else(|err| panic!("failed to parse:\n{}\n{:?}", source, err))
+    .0;
+
+    let mut identity = Identity { context };
+    let folded: Vec<_> = items.iter().map(|item| identity.fold_item(item)).collect();
+    assert_eq!(items, folded, "folding changed the tree of:\n{}", source);
+
+    // Locations and parentheses depend on how the source was written, so only the
+    // structure of the tree is compared
+    let mut erase = Relocate::erase(context);
+    items.iter().map(|item| erase.fold_item(item)).collect()
+}
+
+fn print(items: &[&Item<'_>], context: &Context<'_>) -> String {
+    alloc::format!("{}", items.display(context.strings()))
+}
+
+/// Parses the source, prints it back out and checks that the printed source parses into
+/// the same tree and is printed the same way again
+fn round_trip(source: &str) {
+    let owned_arenas = OwnedArenas::default();
+    let context = Context::new(Arenas::from(&owned_arenas));
+
+    let items = parse(source, &context);
+    let printed = print(&items, &context);
+    let reparsed = parse(&printed, &context);
+
+    assert_eq!(items, reparsed, "printed source:\n{}", printed);
+    assert_eq!(printed, print(&reparsed, &context));
+}
+
+fn in_function(body: &str) -> String {
+    alloc::format!("fn main()\n    {}\nend\n", body)
+}
+
+#[test]
+fn items() {
+    round_trip(
+        "import std.io.stdout lib\n\
+         import some.place as other\n\
+         import some.other pkg exposing *\n\
+         import some.thing pkg exposing Foo, Bar as Baz\n\
+         \n\
+         @inline\n\
+         exposed fn add[T](a: T, b: T, rest: ..T) -> T\n    return a + b\nend\n\
+         \n\
+         fn largest[T: Compare & Clone, U](a: T, b: T) -> T\n    return a\nend\n\
+         \n\
+         type Point[T]\n    @hidden\n    x: T,\n    y: T,\nend\n\
+         \n\
+         enum Shape\n    Circle(f64)\n    Square\nend\n\
+         \n\
+         trait Area\n    fn area(self: &Self) -> f64\n    end\nend\n\
+         \n\
+         extend Shape with Area\n    fn area(self: &Shape) -> f64\n        return 0.0\n    end\nend\n\
+         \n\
+         extern\n    @callconv(\"C\")\n    fn puts(string: *const u8) -> i32;\nend\n\
+         \n\
+         alias Bytes = slice[u8]\n",
+    );
+}
+
+#[test]
+fn statements() {
+    round_trip(
+        "fn main()\n\
+         \x20   let mut x: i32 := 10\n\
+         \x20   let y := x * 2\n\
+         \x20   const Z: u8 := 0\n\
+         \x20   x += y\n\
+         \x20   x := Z\n\
+         end\n",
+    );
+}
+
+#[test]
+fn precedence() {
+    for expr in [
+        "a + b * c",
+        "(a + b) * c",
+        "a - (b - c)",
+        "(a - b) - c",
+        "a / b / c",
+        "a ** (b ** c)",
+        "-(a + b)",
+        "!a == b",
+        "a < b == (c > d)",
+        "a & b | c ^ d",
+        "a and b or c",
+        "a and (b or c)",
+        "!a or b == c and d",
+        "a << b + c",
+        "(a as i32) + b",
+        "a as i32 as u8",
+        "(a + b) as u8",
+        "a.b.c(d)",
+        "a.b(c).d(e)",
+        "(a + b).c()",
+        "a[b][c + d]",
+        "f(a, b)(c)",
+        "f(name := 10, other := x)",
+        "f((a := 10))",
+        "a..b",
+        "a..=b + c",
+        "&mut a",
+        "&(a + b)",
+        "*a + b",
+        "a * *b",
+        "*(a + b)",
+        "*a.b := c",
+        "(*a)[b] += c",
+        "1.5e10 + -(1)",
+        "arr[1, 2, 3]",
+        "tup[a, (b + c)]",
+    ]
+    .iter()
+    {
+        round_trip(&in_function(expr));
+    }
+}
+
+#[test]
+fn literals() {
+    for expr in [
+        "\"a \\\"string\\\"\\n\"",
+        "b\"bytes\\x{FF}\"",
+        "'a'",
+        "'\\u{0027}'",
+        "true != false",
+        "\"{x} and {y:?} and {{braces}}\"",
+        "0x10 + 0b11 + 10_000",
+        "1.0 + inf + NaN",
+    ]
+    .iter()
+    {
+        round_trip(&in_function(expr));
+    }
+}
+
+#[test]
+fn control_flow() {
+    round_trip(
+        "fn main()\n\
+         \x20   if a\n        b\n    else if c\n        d\n    else\n        e\n    end\n\
+         \x20   while x < 10\n        x += 1\n    then\n        done()\n    else\n        failed()\n    end\n\
+         \x20   loop\n        break x\n    else\n        failed()\n    end\n\
+         \x20   for i in 0..10\n        continue\n    then\n        done()\n    end\n\
+         \x20   match x\n        Some(y) where y > 10 =>\n            y\n        end\n\
+         \x20       a | b =>\n            c\n        end\n\
+         \x20       _ =>\n            return\n        end\n    end\n\
+         \x20   with open(file) as f\n        f.read()\n    end\n\
+         \x20   return x + 1\n\
+         end\n",
+    );
+}
+
+#[test]
+fn patterns() {
+    for pattern in [
+        "tup[a, _, ..]",
+        "arr[first, .., last]",
+        "slice[head, rest @ ..]",
+        "slice[]",
+        "Shape.Rect(width, height) | Shape.Square(width)",
+        "small @ (1 | 2)",
+        "1..10",
+        "..=5",
+        "Point { x, y: 0, .. }",
+    ]
+    .iter()
+    {
+        round_trip(&in_function(&alloc::format!(
+            "match x\n        {} =>\n            y\n        end\n    end",
+            pattern,
+        )));
+    }
+}
+
+#[test]
+fn types() {
+    for ty in [
+        "A & B | C",
+        "A & (B | C)",
+        "(A | B) & C",
+        "!A & B",
+        "!(A & B)",
+        "&mut i32",
+        "*const *mut u8",
+        "arr[i32; 10]",
+        "slice[&str]",
+        "tup[i32, bool]",
+        "fn(i32, u8) -> bool",
+        "Foo[T, U]",
+        "std.io.Writer",
+    ]
+    .iter()
+    {
+        round_trip(&alloc::format!("fn main(x: {})\nend\n", ty));
+    }
+}
+
+#[cfg(not(any(target_arch = "wasm32", miri)))]
+mod proptests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn name() -> impl Strategy<Value = String> {
+        prop::sample::select(&["a", "b", "foo", "bar"][..]).prop_map(String::from)
+    }
+
+    fn expr() -> impl Strategy<Value = String> {
+        let leaf = prop_oneof![
+            name(),
+            (0u32..1000).prop_map(|int| int.to_string()),
+            any::<bool>().prop_map(|boolean| boolean.to_string()),
+        ];
+
+        leaf.prop_recursive(6, 64, 4, |inner| {
+            let binary_op = prop::sample::select(
+                &[
+                    "+", "-", "*", "/", "%", "**", "&", "|", "^", "<<", ">>", "==", "!=", "<", ">",
+                    "<=", ">=", "and", "or",
+                ][..],
+            );
+
+            prop_oneof![
+                (inner.clone(), binary_op, inner.clone())
+                    .prop_map(|(lhs, op, rhs)| format!("{} {} {}", lhs, op, rhs)),
+                inner.clone().prop_map(|expr| format!("({})", expr)),
+                inner.clone().prop_map(|expr| format!("!({})", expr)),
+                inner.clone().prop_map(|expr| format!("&({})", expr)),
+                inner.clone().prop_map(|expr| format!("*({})", expr)),
+                inner
+                    .clone()
+                    .prop_map(|expr| format!("(({}) as i32)", expr)),
+                (inner.clone(), inner.clone())
+                    .prop_map(|(array, index)| format!("({})[{}]", array, index)),
+                (name(), prop::collection::vec(inner.clone(), 0..3))
+                    .prop_map(|(func, args)| format!("{}({})", func, args.join(", "))),
+                (inner.clone(), name(), prop::collection::vec(inner, 0..3)).prop_map(
+                    |(receiver, func, args)| {
+                        format!("({}).{}({})", receiver, func, args.join(", "))
+                    },
+                ),
+            ]
+        })
+    }
+
+    fn ty() -> impl Strategy<Value = String> {
+        let leaf =
+            prop::sample::select(&["i32", "u8", "bool", "Foo", "str"][..]).prop_map(String::from);
+
+        leaf.prop_recursive(4, 32, 3, |inner| {
+            prop_oneof![
+                (inner.clone(), inner.clone()).prop_map(|(lhs, rhs)| format!("{} & {}", lhs, rhs)),
+                (inner.clone(), inner.clone()).prop_map(|(lhs, rhs)| format!("{} | {}", lhs, rhs)),
+                inner.clone().prop_map(|ty| format!("({})", ty)),
+                inner.clone().prop_map(|ty| format!("!({})", ty)),
+                inner.clone().prop_map(|ty| format!("&({})", ty)),
+                inner.clone().prop_map(|ty| format!("slice[{}]", ty)),
+                (inner.clone(), 1u32..10).prop_map(|(ty, len)| format!("arr[{}; {}]", ty, len)),
+                prop::collection::vec(inner, 1..3)
+                    .prop_map(|elements| format!("tup[{}]", elements.join(", "))),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn expressions(expr in expr()) {
+            round_trip(&in_function(&expr));
+        }
+
+        #[test]
+        fn types(ty in ty()) {
+            round_trip(&alloc::format!("fn main(x: {})\nend\n", ty));
+        }
+    }
+}
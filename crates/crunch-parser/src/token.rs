@@ -24,6 +24,9 @@ pub enum TokenType {
     #[regex("b?'[^']*'")]
     Rune,
     #[regex(r#"b?"(\\.|[^\\"])*""#)] // " <- This is here to restore syntax highlighting
+    #[regex(r#"b?r"[^"]*""#)]
+    #[regex(r#"b?r?"""([^"]|"[^"]|""[^"])*"?"?""""#)]
+    #[regex(r#"b?r?'''([^']|'[^']|''[^'])*'?'?'''"#)]
     String,
     #[token("inf")]
     #[token("NaN")]
@@ -69,6 +72,8 @@ pub enum TokenType {
     Ref,
     #[token("extern")]
     Extern,
+    #[token("module")]
+    Module,
 
     #[token("in")]
     In,
@@ -212,6 +217,12 @@ pub enum TokenType {
     Dot,
     #[token("..")]
     DoubleDot,
+    #[token("..=")]
+    DoubleDotEqual,
+    #[token("...")]
+    TripleDot,
+    #[token("_")]
+    Underscore,
 }
 
 impl TokenType {
@@ -267,6 +278,7 @@ impl TokenType {
             Self::Mut => "mut",
             Self::Ref => "ref",
             Self::Extern => "extern",
+            Self::Module => "module",
 
             Self::Equal => "=",
             Self::AddAssign => "+=",
@@ -318,6 +330,9 @@ impl TokenType {
             Self::Colon => ":",
             Self::Dot => ".",
             Self::DoubleDot => "..",
+            Self::DoubleDotEqual => "..=",
+            Self::TripleDot => "...",
+            Self::Underscore => "_",
         }
     }
 }
@@ -333,14 +348,27 @@ pub struct TokenStream<'a> {
     lexer: Lexer<'a, TokenType>,
     skip_comments: bool,
     skip_doc_comments: bool,
+    offset: usize,
 }
 
 impl<'a> TokenStream<'a> {
     pub fn new(input: &'a str, skip_comments: bool, skip_doc_comments: bool) -> Self {
+        Self::with_offset(input, 0, skip_comments, skip_doc_comments)
+    }
+
+    /// Lexes a slice of a larger source, shifting all token spans by `offset` so that
+    /// they still point into the original source
+    pub fn with_offset(
+        input: &'a str,
+        offset: usize,
+        skip_comments: bool,
+        skip_doc_comments: bool,
+    ) -> Self {
         Self {
             lexer: TokenType::lexer(input),
             skip_comments,
             skip_doc_comments,
+            offset,
         }
     }
 }
@@ -350,7 +378,12 @@ impl<'a> Iterator for TokenStream<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.lexer.next().and_then(|token| {
-            let token = Token::new(token, self.lexer.slice(), self.lexer.span());
+            let span = self.lexer.span();
+            let token = Token::new(
+                token,
+                self.lexer.slice(),
+                span.start + self.offset..span.end + self.offset,
+            );
             match token.ty {
                 TokenType::Space => self.next(),
                 TokenType::Comment if self.skip_comments => self.next(),
@@ -370,6 +403,7 @@ impl fmt::Debug for TokenStream<'_> {
             .field("lexer", &tokens)
             .field("skip_comments", &self.skip_comments)
             .field("skip_doc_comments", &self.skip_doc_comments)
+            .field("offset", &self.offset)
             .finish()
     }
 }
@@ -558,6 +592,44 @@ mod tests {
         assert_eq!(stream.next(), None);
     }
 
+    #[test]
+    fn multiline_strings_ending_in_quotes() {
+        for source in [
+            r#""""say "hi"""""#,
+            r#""""say ""hi""""""#,
+            r#"""""quoted""""#,
+            "'''it's'''",
+            "'''it''''",
+            "'''it'''''",
+        ]
+        .iter()
+        {
+            let mut stream = TokenStream::new(source, true, true);
+
+            assert_eq!(
+                stream.next(),
+                Some(Token {
+                    ty: TokenType::String,
+                    source,
+                    span: Span::new(0, source.len()),
+                }),
+            );
+            assert_eq!(stream.next(), None);
+        }
+
+        // Anything after the closing quotes isn't part of the string
+        let mut stream = TokenStream::new(r#""""a""" """b""""#, true, true);
+        assert_eq!(
+            stream.next().map(|token| token.source()),
+            Some(r#""""a""""#)
+        );
+        assert_eq!(
+            stream.next().map(|token| token.source()),
+            Some(r#""""b""""#)
+        );
+        assert_eq!(stream.next(), None);
+    }
+
     #[cfg(not(any(target_arch = "wasm32", miri)))]
     mod proptests {
         use super::*;
@@ -573,6 +645,24 @@ mod tests {
                 prop_assert_eq!(stream.count(), 0);
             }
 
+            #[test]
+            fn raw_strings(s in r#"b?r"[^"]*""#) {
+                let mut stream = TokenStream::new(&s, true, true);
+
+                let cond = matches!(stream.next(), Some(Token { ty: _ty @ TokenType::String, .. }));
+                prop_assert!(cond);
+                prop_assert_eq!(stream.count(), 0);
+            }
+
+            #[test]
+            fn multiline_strings(s in r#"b?r?"""[^"]*""""#) {
+                let mut stream = TokenStream::new(&s, true, true);
+
+                let cond = matches!(stream.next(), Some(Token { ty: _ty @ TokenType::String, .. }));
+                prop_assert!(cond);
+                prop_assert_eq!(stream.count(), 0);
+            }
+
             #[test]
             fn runes(s in "b?'[^']*'") {
                 let mut stream = TokenStream::new(&s, true, true);
@@ -107,4 +107,14 @@ impl<'ctx> ItemVisitorMut<'ctx> for FlattenExternals {
     ) -> Self::Output {
         true
     }
+
+    // Modules are flattened separately when they're lowered, since their items can't be
+    // hoisted out of them
+    fn visit_module(
+        &mut self,
+        _items: &mut Vec<&'ctx Item<'ctx>>,
+        _item: &'ctx Item<'ctx>,
+    ) -> Self::Output {
+        true
+    }
 }
@@ -2,10 +2,11 @@ use proc_macro::TokenStream as TokenStream1;
 use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote};
 use syn::{
-    parse_macro_input, parse_quote, spanned::Spanned, AttributeArgs, Error, ItemEnum, ItemFn, Lit,
-    Meta, MetaNameValue, NestedMeta, Result,
+    parse_macro_input, parse_quote, spanned::Spanned, AttributeArgs, DeriveInput, Error, ItemEnum,
+    ItemFn, Lit, Meta, MetaNameValue, NestedMeta, Result,
 };
 
+mod load;
 mod nanopass;
 
 use nanopass::Nanopass;
@@ -103,3 +104,13 @@ pub fn nanopass(args: TokenStream1, input: TokenStream1) -> TokenStream1 {
         .unwrap_or_else(|err| err.to_compile_error())
         .into()
 }
+
+/// Derives `Load` for tree nodes within `crunch-shared`, which deserializes them the same
+/// way serde does while allocating every node they refer to within the arenas of the
+/// context being loaded
+#[proc_macro_derive(Load)]
+pub fn load(input: TokenStream1) -> TokenStream1 {
+    load::derive(parse_macro_input!(input as DeriveInput))
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
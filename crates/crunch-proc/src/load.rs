@@ -0,0 +1,412 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    ext::IdentExt, parse_quote, Data, DeriveInput, Error, Fields, GenericParam, Generics, Ident,
+    LitStr, Result,
+};
+
+/// Derives `crate::context::Load` for a struct or enum, deserializing it the same way that
+/// serde's derived `Deserialize` would while handing the arenas being loaded into down
+/// to every field through `crate::context::LoadSeed`s
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let generics = load_generics(&input.generics)?;
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let value = quote! { #name #ty_generics };
+
+    let body = match &input.data {
+        Data::Struct(data) => load_struct(&generics, &value, name, &data.fields),
+        Data::Enum(data) => load_enum(
+            &generics,
+            &value,
+            name,
+            data.variants
+                .iter()
+                .map(|variant| (&variant.ident, &variant.fields)),
+        ),
+        Data::Union(_) => {
+            return Err(Error::new(
+                Span::call_site(),
+                "unions can't be loaded from a context snapshot",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics crate::context::Load<'ctx> for #value #where_clause {
+            fn load<'de, D>(
+                arenas: &crate::context::Arenas<'ctx>,
+                deserializer: D,
+            ) -> ::core::result::Result<Self, D::Error>
+            where
+                D: ::serde::de::Deserializer<'de>,
+            {
+                #body
+            }
+        }
+    })
+}
+
+/// The generics of the `Load` impl, which is always over `'ctx` and requires every type
+/// parameter to be loadable
+fn load_generics(generics: &Generics) -> Result<Generics> {
+    let mut generics = generics.clone();
+
+    let mut has_ctx = false;
+    for param in generics.params.iter_mut() {
+        match param {
+            GenericParam::Lifetime(lifetime) if lifetime.lifetime.ident == "ctx" => has_ctx = true,
+            GenericParam::Lifetime(lifetime) => {
+                return Err(Error::new_spanned(
+                    lifetime,
+                    "only the `'ctx` lifetime can be loaded from a context snapshot",
+                ));
+            }
+            GenericParam::Type(ty) => ty.bounds.push(parse_quote!(crate::context::Load<'ctx>)),
+            GenericParam::Const(_) => {}
+        }
+    }
+
+    if !has_ctx {
+        generics.params.insert(0, parse_quote!('ctx));
+    }
+
+    Ok(generics)
+}
+
+/// A visitor type along with its `Visitor` impl, which only lacks the visiting functions
+fn visitor(
+    generics: &Generics,
+    value: &TokenStream,
+    visitor: &Ident,
+    expecting: &str,
+    visit: TokenStream,
+) -> TokenStream {
+    let mut visitor_generics = generics.clone();
+    visitor_generics.params.insert(0, parse_quote!('__a));
+    let (visitor_impl_generics, visitor_ty_generics, where_clause) =
+        visitor_generics.split_for_impl();
+
+    let mut de_generics = visitor_generics.clone();
+    de_generics.params.insert(0, parse_quote!('de));
+    let (de_impl_generics, _, _) = de_generics.split_for_impl();
+
+    quote! {
+        struct #visitor #visitor_impl_generics #where_clause {
+            arenas: &'__a crate::context::Arenas<'ctx>,
+            marker: ::core::marker::PhantomData<fn() -> #value>,
+        }
+
+        impl #de_impl_generics ::serde::de::Visitor<'de> for #visitor #visitor_ty_generics
+            #where_clause
+        {
+            type Value = #value;
+
+            fn expecting(
+                &self,
+                f: &mut ::core::fmt::Formatter<'_>,
+            ) -> ::core::fmt::Result {
+                f.write_str(#expecting)
+            }
+
+            #visit
+        }
+    }
+}
+
+fn construct_visitor(visitor: &Ident) -> TokenStream {
+    quote! {
+        #visitor {
+            arenas,
+            marker: ::core::marker::PhantomData,
+        }
+    }
+}
+
+fn load_struct(
+    generics: &Generics,
+    value: &TokenStream,
+    name: &Ident,
+    fields: &Fields,
+) -> TokenStream {
+    let name_str = LitStr::new(&name.to_string(), name.span());
+    let visitor_ident = format_ident!("__{}Visitor", name);
+    let construct = construct_visitor(&visitor_ident);
+    let expecting = format!("struct {}", name);
+
+    match fields {
+        Fields::Named(_) => {
+            let field_names = field_names(fields);
+            let visitor = visitor(
+                generics,
+                value,
+                &visitor_ident,
+                &expecting,
+                visit_fields(quote! { #name }, fields),
+            );
+
+            quote! {
+                #visitor
+
+                deserializer.deserialize_struct(#name_str, &[#(#field_names),*], #construct)
+            }
+        }
+
+        // Newtypes are deserialized as whatever they contain
+        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+            let visitor = visitor(generics, value, &visitor_ident, &expecting, {
+                let visit_seq = visit_fields(quote! { #name }, fields);
+
+                quote! {
+                    fn visit_newtype_struct<__D>(
+                        self,
+                        deserializer: __D,
+                    ) -> ::core::result::Result<Self::Value, __D::Error>
+                    where
+                        __D: ::serde::de::Deserializer<'de>,
+                    {
+                        crate::context::Load::load(self.arenas, deserializer).map(#name)
+                    }
+
+                    #visit_seq
+                }
+            });
+
+            quote! {
+                #visitor
+
+                deserializer.deserialize_newtype_struct(#name_str, #construct)
+            }
+        }
+
+        Fields::Unnamed(unnamed) => {
+            let len = unnamed.unnamed.len();
+            let visitor = visitor(
+                generics,
+                value,
+                &visitor_ident,
+                &expecting,
+                visit_fields(quote! { #name }, fields),
+            );
+
+            quote! {
+                #visitor
+
+                deserializer.deserialize_tuple_struct(#name_str, #len, #construct)
+            }
+        }
+
+        Fields::Unit => {
+            let visitor = visitor(
+                generics,
+                value,
+                &visitor_ident,
+                &expecting,
+                quote! {
+                    fn visit_unit<__E>(self) -> ::core::result::Result<Self::Value, __E>
+                    where
+                        __E: ::serde::de::Error,
+                    {
+                        ::core::result::Result::Ok(#name)
+                    }
+                },
+            );
+
+            quote! {
+                #visitor
+
+                deserializer.deserialize_unit_struct(#name_str, #construct)
+            }
+        }
+    }
+}
+
+fn load_enum<'a>(
+    generics: &Generics,
+    value: &TokenStream,
+    name: &Ident,
+    variants: impl Iterator<Item = (&'a Ident, &'a Fields)>,
+) -> TokenStream {
+    let name_str = LitStr::new(&name.to_string(), name.span());
+    let visitor_ident = format_ident!("__{}Visitor", name);
+    let construct = construct_visitor(&visitor_ident);
+    let expecting = format!("enum {}", name);
+
+    let mut variant_names = Vec::new();
+    let mut arms = Vec::new();
+    for (idx, (variant, fields)) in variants.enumerate() {
+        variant_names.push(LitStr::new(&variant.unraw().to_string(), variant.span()));
+        let path = quote! { #name::#variant };
+
+        let arm = match fields {
+            Fields::Unit => quote! {
+                ::serde::de::VariantAccess::unit_variant(variant)?;
+                ::core::result::Result::Ok(#path)
+            },
+
+            Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => quote! {
+                ::serde::de::VariantAccess::newtype_variant_seed(
+                    variant,
+                    crate::context::LoadSeed::new(self.arenas),
+                )
+                .map(#path)
+            },
+
+            Fields::Unnamed(_) | Fields::Named(_) => {
+                let variant_visitor_ident = format_ident!("__{}{}Visitor", name, variant);
+                let variant_visitor = visitor(
+                    generics,
+                    value,
+                    &variant_visitor_ident,
+                    &format!("variant {}::{}", name, variant),
+                    visit_fields(path, fields),
+                );
+                let construct = construct_visitor(&variant_visitor_ident);
+
+                let access = if let Fields::Unnamed(unnamed) = fields {
+                    let len = unnamed.unnamed.len();
+                    quote! { ::serde::de::VariantAccess::tuple_variant(variant, #len, #construct) }
+                } else {
+                    let field_names = field_names(fields);
+                    quote! {
+                        ::serde::de::VariantAccess::struct_variant(
+                            variant,
+                            &[#(#field_names),*],
+                            #construct,
+                        )
+                    }
+                };
+
+                quote! {
+                    #variant_visitor
+
+                    let arenas = self.arenas;
+                    #access
+                }
+            }
+        };
+
+        arms.push(quote! { #idx => { #arm } });
+    }
+
+    let visitor = visitor(
+        generics,
+        value,
+        &visitor_ident,
+        &expecting,
+        quote! {
+            fn visit_enum<__A>(self, data: __A) -> ::core::result::Result<Self::Value, __A::Error>
+            where
+                __A: ::serde::de::EnumAccess<'de>,
+            {
+                let (idx, variant): (usize, _) =
+                    data.variant_seed(crate::context::VariantIdent(VARIANTS))?;
+
+                match idx {
+                    #(#arms)*
+                    _ => ::core::unreachable!("variant identifiers are always in bounds"),
+                }
+            }
+        },
+    );
+
+    quote! {
+        const VARIANTS: &[&str] = &[#(#variant_names),*];
+
+        #visitor
+
+        deserializer.deserialize_enum(#name_str, VARIANTS, #construct)
+    }
+}
+
+fn field_names(fields: &Fields) -> Vec<LitStr> {
+    fields
+        .iter()
+        .filter_map(|field| field.ident.as_ref())
+        .map(|ident| LitStr::new(&ident.unraw().to_string(), ident.span()))
+        .collect()
+}
+
+/// The `visit_seq` and (for named fields) `visit_map` functions of a visitor that builds `path`
+/// out of `fields`
+fn visit_fields(path: TokenStream, fields: &Fields) -> TokenStream {
+    let bindings: Vec<Ident> = (0..fields.len())
+        .map(|idx| format_ident!("__field{}", idx))
+        .collect();
+    let indices = 0..fields.len();
+
+    let construct = match fields {
+        Fields::Named(_) => {
+            let idents = fields.iter().map(|field| field.ident.as_ref().unwrap());
+            quote! { #path { #(#idents: #bindings),* } }
+        }
+        Fields::Unnamed(_) => quote! { #path(#(#bindings),*) },
+        Fields::Unit => quote! { #path },
+    };
+
+    let visit_seq = quote! {
+        fn visit_seq<__A>(self, mut seq: __A) -> ::core::result::Result<Self::Value, __A::Error>
+        where
+            __A: ::serde::de::SeqAccess<'de>,
+        {
+            #(
+                let #bindings = seq
+                    .next_element_seed(crate::context::LoadSeed::new(self.arenas))?
+                    .ok_or_else(|| ::serde::de::Error::invalid_length(#indices, &self))?;
+            )*
+
+            ::core::result::Result::Ok(#construct)
+        }
+    };
+
+    if let Fields::Named(_) = fields {
+        let field_names = field_names(fields);
+        let indices = 0..fields.len();
+
+        quote! {
+            #visit_seq
+
+            fn visit_map<__A>(self, mut map: __A) -> ::core::result::Result<Self::Value, __A::Error>
+            where
+                __A: ::serde::de::MapAccess<'de>,
+            {
+                const FIELDS: &[&str] = &[#(#field_names),*];
+                #(let mut #bindings = ::core::option::Option::None;)*
+
+                while let ::core::option::Option::Some(field) =
+                    map.next_key_seed(crate::context::FieldIdent(FIELDS))?
+                {
+                    match field {
+                        #(
+                            ::core::option::Option::Some(#indices) => {
+                                if #bindings.is_some() {
+                                    return ::core::result::Result::Err(
+                                        ::serde::de::Error::duplicate_field(#field_names),
+                                    );
+                                }
+
+                                #bindings = ::core::option::Option::Some(
+                                    map.next_value_seed(crate::context::LoadSeed::new(self.arenas))?,
+                                );
+                            }
+                        )*
+                        _ => {
+                            map.next_value::<::serde::de::IgnoredAny>()?;
+                        }
+                    }
+                }
+
+                #(
+                    let #bindings = #bindings
+                        .ok_or_else(|| ::serde::de::Error::missing_field(#field_names))?;
+                )*
+
+                ::core::result::Result::Ok(#construct)
+            }
+        }
+    } else {
+        visit_seq
+    }
+}
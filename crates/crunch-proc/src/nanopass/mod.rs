@@ -2,6 +2,7 @@ use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote, ToTokens};
 use std::{
     collections::{HashMap, HashSet},
+    fmt::Display,
     mem,
 };
 use syn::{
@@ -30,15 +31,28 @@ pub struct Nanopass {
     input_enum: ItemEnum,
     /// The enum that will be created by the current pass
     output_enum: Option<ItemEnum>,
-    // FIXME: Better error spans
+    /// The span of the `file` attribute. Errors within the pass file point here and carry
+    /// their location within the file in their message
     file_span: Span,
     /// Every enum produced
     enums: HashMap<Ident, ItemEnum>,
+    /// Enums that are given passes by the `#[nanopass]` attributes of other enums, along
+    /// with every enum produced from them
+    foreign_enums: HashSet<Ident>,
 }
 
 impl Nanopass {
     pub fn compile(mut self) -> Result<TokenStream> {
         for pass in mem::take(&mut self.passes.passes) {
+            // Passes over other enums are compiled by the attributes on those enums
+            if self.foreign_enums.contains(&pass.input_enum) {
+                if let Some(ref output_enum) = pass.output_enum {
+                    self.foreign_enums.insert(output_enum.clone());
+                }
+
+                continue;
+            }
+
             self.compile_pass(pass)?;
         }
 
@@ -47,60 +61,87 @@ impl Nanopass {
 
     /// Preforms some sanity checks on a pass to make sure it's alright
     ///
-    /// * Variants are not mutated multiple times
-    /// * Mutation must always produce a new enum
+    /// * All referenced variants exist
+    /// * Variants are not mutated multiple times, scanning them any number of times is fine
+    /// * Mutation must always produce a new enum that doesn't already exist
     ///
-    // TODO: All referenced variants exist
-    // TODO: Scanning and mutating different variants is ok
-    fn verify_pass(&self, pass: &Pass) -> Result<()> {
+    fn verify_pass(&self, pass: &Pass, input_enum: &ItemEnum) -> Result<()> {
         let mut mutated_variants = HashSet::new();
-        let all_scan = pass.transformations.iter().all(|t| t.is_scan());
-
-        // If the pass just scans then this can be skipped
-        if !all_scan {
-            for transform in pass.transformations.iter() {
-                // If an enum is output and this isn't a scan-only pass
-                if pass.output_enum.is_some() {
-                    // If this variant has been touched before, throw an error
-                    // FIXME: Will throw an error for passes that scan one variant multiple times but mutate others
-                    if !mutated_variants.insert(&transform.input_variant) {
-                        return Err(Error::new(
-                            self.file_span,
-                            format!(
-                                "The '{}' pass mutates the '{}' variant of the '{}' enum multiple times",
-                                pass.name, transform.input_variant, pass.input_enum,
-                            ),
-                        ));
-                    }
-                }
+
+        for transform in pass.transformations.iter() {
+            if find_variant(input_enum, &transform.input_variant).is_none() {
+                return Err(self.error(
+                    &transform.location,
+                    format!(
+                        "The '{}' pass references the '{}' variant, which does not exist on the '{}' enum",
+                        pass.name, transform.input_variant, pass.input_enum,
+                    ),
+                ));
+            }
+
+            // If this variant has been mutated before, throw an error
+            if !transform.is_scan() && !mutated_variants.insert(&transform.input_variant) {
+                return Err(self.error(
+                    &transform.location,
+                    format!(
+                        "The '{}' pass mutates the '{}' variant of the '{}' enum multiple times",
+                        pass.name, transform.input_variant, pass.input_enum,
+                    ),
+                ));
             }
         }
 
-        // If the pass mutates yet doesn't produce a new enum throw an error
-        if !all_scan && pass.output_enum.is_none() {
-            Err(Error::new(
-                self.file_span,
+        match pass.output_enum {
+            // If the pass mutates yet doesn't produce a new enum throw an error
+            None if !mutated_variants.is_empty() => Err(self.error(
+                &pass.location,
                 format!(
                     "Multiple of the transformations for the '{}' pass mutate the '{}' enum, but outputEnum is not given\n\
                      Creating an output enum is required for passes that change the enum they operate on in any way, if \
                      you don't want to mutate the enum you're operating on then use `Operation.Scan`",
                     pass.name,
                     pass.input_enum,
-                )))
-        } else {
-            Ok(())
+                ),
+            )),
+
+            Some(ref output_enum)
+                if self.enums.contains_key(output_enum) || self.foreign_enums.contains(output_enum) =>
+            {
+                Err(self.error(
+                    &pass.location,
+                    format!(
+                        "The '{}' pass outputs the '{}' enum, but an enum named '{}' already exists",
+                        pass.name, output_enum, output_enum,
+                    ),
+                ))
+            }
+
+            _ => Ok(()),
         }
     }
 
     fn compile_pass(&mut self, mut pass: Pass) -> Result<()> {
+        // Get the most recent enum we created or used
+        let input_enum = self
+            .enums
+            .get(&pass.input_enum)
+            .ok_or_else(|| {
+                self.error(
+                    &pass.location,
+                    format!(
+                        "An enum named '{}' does not exist for pass '{}'",
+                        pass.input_enum, pass.name
+                    ),
+                )
+            })?
+            .clone();
+
         // Make sure the pass looks correct
-        self.verify_pass(&pass)?;
+        self.verify_pass(&pass, &input_enum)?;
 
-        // Clear out the last pass's variant bookkeeping
+        // Fill the current variants with metadata for the set of variants we'll operate on
         self.current_variants.clear();
-
-        // Fill the current variants with metadata for the current set of variants we'll operate on
-        let variants = self.passes.base_enum.variants.iter().map(|var| {
+        let variants = input_enum.variants.iter().map(|var| {
             (
                 var.ident.clone(),
                 VariantData {
@@ -111,44 +152,100 @@ impl Nanopass {
         });
         self.current_variants.extend(variants);
 
-        // If the pass has an output enum then set self.output_enum to that w/ a copy of the input enum preloaded
-        // Additionally, load self.input_enum with the correct enum
+        // If the pass has an output enum then it starts off as a copy of the input enum
+        self.output_enum = pass.output_enum.as_ref().map(|enum_name| {
+            let mut output_enum = input_enum.clone();
+            output_enum.ident = enum_name.clone();
 
-        // Get the most recent enum we created or used
-        let mut input_enum = self
-            .enums
-            .get(&pass.input_enum)
-            .ok_or_else(|| {
-                Error::new(
-                    pass.input_enum.span(),
-                    format!(
-                        "An enum named '{}' does not exist for pass '{}'",
-                        pass.input_enum, pass.name
-                    ),
-                )
-            })?
-            .clone();
+            output_enum
+        });
+        self.input_enum = input_enum;
+
+        // Scans happen before anything else is done to their variant, so they're gathered up
+        // front. Scanning functions are given references to the variant's fields
+        let mut scans: HashMap<Ident, TokenStream> = HashMap::new();
+        for transform in pass.transformations.iter().filter(|t| t.is_scan()) {
+            let fields = field_idents(
+                &self.current_variants[&transform.input_variant]
+                    .variant
+                    .fields,
+            );
+            let fields = if pass.output_enum.is_some() {
+                quote! { #( &#fields , )* }
+            } else {
+                // Scan-only passes match on `&self`, so the fields are already references
+                quote! { #( #fields , )* }
+            };
+
+            let user_function = &transform.user_function;
+            let context = context_arg(&pass);
+            scans
+                .entry(transform.input_variant.clone())
+                .or_default()
+                .extend(quote! { #user_function(#context #fields); });
+        }
 
-        match pass.output_enum {
-            // We're creating a new enum, so we need to do the setup for that
-            Some(ref enum_name) => {
-                self.input_enum = input_enum.clone();
+        let function_context = match pass.function_context {
+            Context::None => quote! {},
+            Context::Mutable(ref ctx) => quote! { __user_context: &mut #ctx },
+            Context::Immutable(ref ctx) => quote! { __user_context: &#ctx },
+        };
 
-                // Set the output enum's ident
-                input_enum.ident = enum_name.clone();
-                self.output_enum = Some(input_enum);
-            }
+        let vis = &pass.function_vis;
+        let pass_name = &pass.function_name;
+        let input_enum_name = &pass.input_enum;
+        let generics = self.input_enum.generics.clone();
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let docs = pass
+            .description
+            .as_ref()
+            .map(|description| quote! { #[doc = #description] });
+
+        // Passes that only scan leave the enum as it is, so they just look at it
+        let output_enum_name = match pass.output_enum {
+            Some(ref output_enum_name) => output_enum_name.clone(),
 
-            // Set the input enum to the correct one and output enum to None
             None => {
-                self.input_enum = input_enum;
-                self.output_enum.take();
+                let match_arms = self.input_enum.variants.iter().filter_map(|variant| {
+                    let calls = scans.get(&variant.ident)?;
+                    let ident = &variant.ident;
+                    let fields = get_field_names(&variant.fields, true);
+
+                    Some(quote! {
+                        #input_enum_name::#ident #fields => { #calls }
+                    })
+                });
+
+                self.generated_code.extend(quote! {
+                    #[automatically_derived]
+                    impl #impl_generics #input_enum_name #ty_generics #where_clause {
+                        #docs
+                        #[allow(unreachable_patterns)]
+                        #vis fn #pass_name(&self, #function_context) {
+                            match self {
+                                #( #match_arms )*
+                                _ => {}
+                            }
+                        }
+                    }
+                });
+
+                return Ok(());
             }
-        }
+        };
+
+        // Deleting variants means that there isn't always something to output
+        let deletes = pass
+            .transformations
+            .iter()
+            .any(|transform| transform.operation.is_delete());
 
         let mut match_arms = TokenStream::new();
         for transform in mem::take(&mut pass.transformations) {
-            self.compile_transform(&mut match_arms, &pass, transform)?;
+            if !transform.is_scan() {
+                let scans = scans.remove(&transform.input_variant);
+                self.compile_transform(&mut match_arms, &pass, transform, scans, deletes)?;
+            }
         }
 
         // For all unchanged variants, generate a straight conversion, e.g.
@@ -158,49 +255,34 @@ impl Nanopass {
         // A::Tuple(_0, _1) => B::Tuple(_0, _1),
         // A::Struct { a, b } => B::Struct { a, b },
         // ```
-        if let Some(ref output_enum) = pass.output_enum {
-            let input_enum = &pass.input_enum;
+        for Variant { ident, fields, .. } in self.input_enum.variants.iter() {
+            if !self.current_variants[ident].modified {
+                let fields = get_field_names(fields, true);
+                let scans = scans.remove(ident);
+                let output = wrap_output(quote! { #output_enum_name::#ident #fields }, deletes);
 
-            for (
-                ident,
-                VariantData {
-                    variant: Variant { fields, .. },
-                    modified,
-                },
-            ) in self.current_variants.iter()
-            {
-                if !modified {
-                    let fields = get_field_names(fields, true);
-
-                    match_arms.extend(quote! {
-                        #input_enum::#ident #fields => #output_enum::#ident #fields,
-                    });
-                }
+                match_arms.extend(quote! {
+                    #input_enum_name::#ident #fields => { #scans #output }
+                });
             }
         }
 
-        let function_context = match pass.function_context {
-            Context::None => quote! {},
-            Context::Mutable(ref ctx) => quote! { __user_context: &mut #ctx },
-            Context::Immutable(ref ctx) => quote! { __user_context: &#ctx },
-        };
-
-        let output_enum = &self.output_enum;
-        let vis = &pass.function_vis;
-
-        let pass_name = &pass.function_name;
-        let input_enum_name = &pass.input_enum;
-        let output_enum_name = match pass.output_enum {
-            Some(ref out) => out,
-            // If the pass is all scans then the inputted enum is spat out
-            None => &pass.input_enum,
+        let output_enum = self
+            .output_enum
+            .take()
+            .expect("Mutating passes have an output");
+        let output_type = if deletes {
+            quote! { Option<#output_enum_name #ty_generics> }
+        } else {
+            quote! { #output_enum_name #ty_generics }
         };
 
         // Add the newly generated pass to the total code
         self.generated_code.extend(quote! {
             #[automatically_derived]
-            impl #input_enum_name {
-                #vis fn #pass_name(self, #function_context) -> #output_enum_name {
+            impl #impl_generics #input_enum_name #ty_generics #where_clause {
+                #docs
+                #vis fn #pass_name(self, #function_context) -> #output_type {
                     match self {
                         #match_arms
                     }
@@ -211,6 +293,9 @@ impl Nanopass {
             #output_enum
         });
 
+        // Later passes can operate on the produced enum
+        self.enums.insert(output_enum_name, output_enum);
+
         Ok(())
     }
 
@@ -219,125 +304,129 @@ impl Nanopass {
         match_arms: &mut TokenStream,
         pass: &Pass,
         transform: Transformation,
+        scans: Option<TokenStream>,
+        deletes: bool,
     ) -> Result<()> {
-        if !transform.operation.is_scan() {
-            self.current_variants
-                .get_mut(&transform.input_variant)
-                .ok_or_else(|| {
-                    Error::new(
-                        transform.input_variant.span(),
-                        format!(
-                            "The variant '{}' does not exist on '{}'",
-                            transform.input_variant, pass.input_enum,
-                        ),
-                    )
-                })?
-                .modified = true;
-        }
+        let input_variant = &transform.input_variant;
+        let input_fields = {
+            let data = self
+                .current_variants
+                .get_mut(input_variant)
+                .expect("Variants are checked while verifying the pass");
+            data.modified = true;
+
+            data.variant.fields.clone()
+        };
+
+        // The user's function is given the variant's fields by value
+        let user_function = &transform.user_function;
+        let context = context_arg(pass);
+        let arguments = get_field_names(&input_fields, false);
+        let call = quote! { #user_function(#context #arguments) };
 
-        match transform.operation {
+        let output = match transform.operation {
             Operation::Create(ref variant) => {
-                let output_enum = self.output_enum.as_mut().expect("Create require an output");
-
-                preform_operation(
-                    match_arms,
-                    pass,
-                    &transform,
-                    output_enum,
-                    &variant.fields,
-                    &variant.ident,
-                )?;
-
-                // TODO: I don't like the clone
+                self.check_vacant(pass, &transform, &variant.ident)?;
+
+                let output_enum = self
+                    .output_enum
+                    .as_mut()
+                    .expect("Create requires an output");
+                let output = construct_variant(&output_enum.ident, variant, call);
                 output_enum.variants.push(variant.clone());
+
+                wrap_output(output, deletes)
             }
 
             Operation::Replace(ref variant) => {
+                // Remove the old variant
                 let output_enum = self
                     .output_enum
                     .as_mut()
                     .expect("Replace requires an output");
+                remove_variant(output_enum, input_variant)
+                    .expect("Mutated variants are only removed once");
 
-                // Remove the old variant
-                // FIXME: Good error here
-                assert!(remove_variant(output_enum, &transform.input_variant).is_some());
-
-                preform_operation(
-                    match_arms,
-                    pass,
-                    &transform,
-                    output_enum,
-                    &variant.fields,
-                    &variant.ident,
-                )?;
+                self.check_vacant(pass, &transform, &variant.ident)?;
 
                 // Add the new variant since we're replacing one
-                // TODO: I don't like the clone
+                let output_enum = self
+                    .output_enum
+                    .as_mut()
+                    .expect("Replace requires an output");
+                let output = construct_variant(&output_enum.ident, variant, call);
                 output_enum.variants.push(variant.clone());
+
+                wrap_output(output, deletes)
             }
 
             Operation::Merge(ref output_variant) => {
-                let output_enum = self.output_enum.as_ref().expect("Merge require an output");
-
-                preform_operation(
-                    match_arms,
-                    pass,
-                    &transform,
-                    output_enum,
-                    // FIXME: Good error here
-                    output_enum
-                        .variants
-                        .iter()
-                        .find_map(|v| {
-                            if v.ident == *output_variant {
-                                Some(&v.fields)
-                            } else {
-                                None
-                            }
-                        })
-                        .expect("No output variant provided for a merge"),
-                    output_variant,
-                )?;
+                let output_enum = self.output_enum.as_ref().expect("Merge requires an output");
+                let variant = find_variant(output_enum, output_variant).ok_or_else(|| {
+                    self.error(
+                        &transform.location,
+                        format!(
+                            "The '{}' pass merges '{}' into the '{}' variant, which does not exist on the '{}' enum",
+                            pass.name, input_variant, output_variant, output_enum.ident,
+                        ),
+                    )
+                })?;
+
+                wrap_output(
+                    construct_variant(&output_enum.ident, variant, call),
+                    deletes,
+                )
             }
 
             Operation::Delete => {
-                let output_enum = self.output_enum.as_ref().expect("Merge require an output");
-
-                let fields = output_enum
-                    .variants
-                    .iter()
-                    .find_map(|v| {
-                        if v.ident == transform.input_variant {
-                            Some(&v.fields)
-                        } else {
-                            None
-                        }
-                    })
-                    .expect("No output variant provided for a merge");
+                let output_enum = self
+                    .output_enum
+                    .as_mut()
+                    .expect("Delete requires an output");
+                remove_variant(output_enum, input_variant)
+                    .expect("Mutated variants are only removed once");
+
+                quote! {
+                    #call;
+                    None
+                }
+            }
 
-                // We need bracing for the pattern match but not for the function call
-                let fields_braced = get_field_names(fields, true);
-                let fields_unbraced = get_field_names(fields, false);
+            Operation::Scan => unreachable!("Scans are compiled along with their pass"),
+        };
 
-                // Get all the stuff for quoting
-                let user_function = &transform.user_function;
-                let (input_enum, input_variant) = (&pass.input_enum, &transform.input_variant);
-                let context = if pass.function_context.is_none() {
-                    quote! {}
-                } else {
-                    quote! { __user_context, }
-                };
+        let input_enum = &pass.input_enum;
+        let fields = get_field_names(&input_fields, true);
+        match_arms.extend(quote! {
+            #input_enum::#input_variant #fields => { #scans #output }
+        });
 
-                match_arms.extend(quote! {
-                    #input_enum::#input_variant #fields_braced => #user_function(#context #fields_unbraced),
-                });
-            }
+        Ok(())
+    }
+
+    /// Makes sure that a variant being added to the output enum doesn't already exist
+    fn check_vacant(&self, pass: &Pass, transform: &Transformation, variant: &Ident) -> Result<()> {
+        let output_enum = self
+            .output_enum
+            .as_ref()
+            .expect("Only output enums are added to");
 
-            // FIXME: Some sort of bookkeeping for all the functions that want to scan each variant has to happen
-            Operation::Scan => todo!("Scan is unimplemented"),
+        if find_variant(output_enum, variant).is_some() {
+            Err(self.error(
+                &transform.location,
+                format!(
+                    "The '{}' pass adds the '{}' variant to the '{}' enum, but it already has a variant named '{}'",
+                    pass.name, variant, output_enum.ident, variant,
+                ),
+            ))
+        } else {
+            Ok(())
         }
+    }
 
-        Ok(())
+    /// Creates an error for something at `location` within the pass file
+    fn error<M: Display>(&self, location: &str, message: M) -> Error {
+        Error::new(self.file_span, format!("{}: {}", location, message))
     }
 
     pub fn parse_from_attr(args: AttributeArgs, base_enum: ItemEnum) -> Result<Self> {
@@ -352,48 +441,90 @@ impl Nanopass {
         let mut enums = HashMap::with_capacity(passes.passes.len());
         enums.insert(passes.base_enum.ident.clone(), passes.base_enum.clone());
 
+        let foreign_enums = passes
+            .config
+            .enums
+            .iter()
+            .filter(|name| passes.base_enum.ident != name)
+            .map(|name| {
+                syn::parse_str(name).map_err(|_| {
+                    Error::new(
+                        file_span,
+                        format!(
+                            "{}: config.enums must only contain valid Rust idents, got '{}'",
+                            passes.file_path, name,
+                        ),
+                    )
+                })
+            })
+            .collect::<Result<_>>()?;
+
         Ok(Self {
             passes,
             generated_code,
             current_variants,
             input_enum,
             output_enum: None,
-            enums,
             file_span,
+            enums,
+            foreign_enums,
         })
     }
 }
 
-fn preform_operation(
-    match_arms: &mut TokenStream,
-    pass: &Pass,
-    transform: &Transformation,
-    output_enum: &ItemEnum,
-    fields: &Fields,
-    output_variant: &Ident,
-) -> Result<()> {
-    // We need bracing for the pattern match but not for the function call
-    let fields_braced = get_field_names(fields, true);
-    let fields_unbraced = get_field_names(fields, false);
-
-    // Get all the stuff for quoting
-    let user_function = &transform.user_function;
-    let (input_enum, input_variant, output_enum) = (
-        &pass.input_enum,
-        &transform.input_variant,
-        &output_enum.ident,
-    );
-    let context = if pass.function_context.is_none() {
+/// The user's context as the first argument of a call, if the pass has one
+fn context_arg(pass: &Pass) -> TokenStream {
+    if pass.function_context.is_none() {
         quote! {}
     } else {
         quote! { __user_context, }
-    };
+    }
+}
 
-    match_arms.extend(quote! {
-        #input_enum::#input_variant #fields_braced => #output_enum::#output_variant(#user_function(#context #fields_unbraced)),
-    });
+/// Builds `variant` of `output_enum` from the output of `call`. The user's function returns
+/// the variant's only field directly, while variants with multiple fields are returned as a
+/// tuple of their fields in order
+fn construct_variant(output_enum: &Ident, variant: &Variant, call: TokenStream) -> TokenStream {
+    let ident = &variant.ident;
+
+    match variant.fields {
+        Fields::Unit => quote! {
+            #call;
+            #output_enum::#ident
+        },
+
+        Fields::Unnamed(FieldsUnnamed { ref unnamed, .. }) if unnamed.len() == 1 => quote! {
+            #output_enum::#ident(#call)
+        },
+
+        ref fields => {
+            let (unbraced, braced) = (
+                get_field_names(fields, false),
+                get_field_names(fields, true),
+            );
+
+            quote! {
+                let (#unbraced) = #call;
+                #output_enum::#ident #braced
+            }
+        }
+    }
+}
 
-    Ok(())
+/// Wraps the output of a match arm in `Some` if the pass deletes any variants
+fn wrap_output(output: TokenStream, deletes: bool) -> TokenStream {
+    if deletes {
+        quote! { Some({ #output }) }
+    } else {
+        output
+    }
+}
+
+fn find_variant<'a>(enumeration: &'a ItemEnum, ident: &Ident) -> Option<&'a Variant> {
+    enumeration
+        .variants
+        .iter()
+        .find(|variant| variant.ident == *ident)
 }
 
 fn remove_variant(enumeration: &mut ItemEnum, ident: &Ident) -> Option<Variant> {
@@ -423,18 +554,18 @@ fn remove_variant(enumeration: &mut ItemEnum, ident: &Ident) -> Option<Variant>
 /// is true then the proper bracing will be included (Parens for tuples & braces for
 /// struct variants)
 ///
-/// ```rust
+/// ```text
 /// UnitVariant
 /// // Becomes nothing, since it has no fields
 /// ```
 ///
-/// ```rust
+/// ```text
 /// TupleVariant(usize, usize)
 /// // Becomes
 /// (_0, _1,)
 /// ```
 ///
-/// ```rust
+/// ```text
 /// StructVariant {
 ///     a: isize,
 ///     b: u8,
@@ -480,3 +611,19 @@ fn get_field_names(fields: &Fields, bracing: bool) -> TokenStream {
         Fields::Unit => quote! {},
     }
 }
+
+/// The names that [`get_field_names`] gives to a variant's fields
+fn field_idents(fields: &Fields) -> Vec<Ident> {
+    match fields {
+        Fields::Named(FieldsNamed { named: fields, .. }) => fields
+            .iter()
+            .map(|Field { ident, .. }| ident.clone().expect("Failed to get field name"))
+            .collect(),
+
+        Fields::Unnamed(FieldsUnnamed {
+            unnamed: fields, ..
+        }) => (0..fields.len()).map(|i| format_ident!("_{}", i)).collect(),
+
+        Fields::Unit => Vec::new(),
+    }
+}
@@ -3,7 +3,7 @@ use serde::Deserialize;
 use std::{fs, path::PathBuf};
 use syn::{
     spanned::Spanned, AttributeArgs, Error, Ident, ItemEnum, Lit, Meta, MetaNameValue, NestedMeta,
-    Path, Result, Variant,
+    Path, Result, Type, Variant,
 };
 
 #[derive(Debug)]
@@ -11,6 +11,8 @@ pub struct Passes {
     pub base_enum: ItemEnum,
     pub config: NanopassConfig,
     pub passes: Vec<Pass>,
+    /// The path of the pass file, relative to the crate's manifest
+    pub file_path: String,
 }
 
 impl Passes {
@@ -43,11 +45,15 @@ impl Passes {
             )
         })?;
         let raw::RawNanopass { config, passes } = toml::from_str(&toml)
-            .map_err(|err| Error::new(file_span, format!("Toml error: {}", err)))?;
+            .map_err(|err| Error::new(file_span, format!("{}: {}", file_path, err)))?;
 
+        let source = raw::Source {
+            path: &file_path,
+            toml: &toml,
+        };
         let passes = passes
             .into_iter()
-            .map(|pass| pass.parse())
+            .map(|pass| pass.parse(&source))
             .collect::<Result<_>>()?;
 
         Ok((
@@ -55,6 +61,7 @@ impl Passes {
                 base_enum,
                 config,
                 passes,
+                file_path,
             },
             file_span,
         ))
@@ -64,6 +71,8 @@ impl Passes {
 #[derive(Debug)]
 pub struct Pass {
     pub name: String,
+    /// Where the pass is declared, as `file:line:column`
+    pub location: String,
     pub description: Option<String>,
     pub function_name: Ident,
     pub function_vis: TokenStream,
@@ -76,8 +85,8 @@ pub struct Pass {
 #[derive(Debug)]
 pub enum Context {
     None,
-    Mutable(Path),
-    Immutable(Path),
+    Mutable(Type),
+    Immutable(Type),
 }
 
 impl Context {
@@ -88,6 +97,8 @@ impl Context {
 
 #[derive(Debug)]
 pub struct Transformation {
+    /// Where the transformation is declared, as `file:line:column`
+    pub location: String,
     pub input_variant: Ident,
     pub operation: Operation,
     pub user_function: Path,
@@ -112,12 +123,20 @@ impl Operation {
     pub fn is_scan(&self) -> bool {
         matches!(self, Self::Scan)
     }
+
+    pub fn is_delete(&self) -> bool {
+        matches!(self, Self::Delete)
+    }
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct NanopassConfig {
     pub logging: bool,
+    /// The enums that passes within the file may start from, allowing one file to hold
+    /// the passes of several enums that each have their own `#[nanopass]` attribute
+    #[serde(default)]
+    pub enums: Vec<String>,
 }
 
 mod raw {
@@ -125,10 +144,28 @@ mod raw {
     use serde::Deserialize;
     use std::fmt::Display;
     use syn::{parse::Parse, Error, Result};
+    use toml::Spanned;
+
+    /// The pass file being parsed
+    pub struct Source<'a> {
+        pub path: &'a str,
+        pub toml: &'a str,
+    }
+
+    impl Source<'_> {
+        /// Formats the position of a byte offset as `file:line:column`
+        fn location(&self, offset: usize) -> String {
+            let before = &self.toml[..offset.min(self.toml.len())];
+            let line = before.matches('\n').count() + 1;
+            let column = before.len() - before.rfind('\n').map_or(0, |idx| idx + 1) + 1;
+
+            format!("{}:{}:{}", self.path, line, column)
+        }
+    }
 
     fn maybe_parse<T: Parse, M: Display>(input: Option<&str>, msg: M) -> Result<Option<T>> {
         input
-            .map_or_else(|| Ok(None), |out| syn::parse_str(&out).map(Some))
+            .map_or_else(|| Ok(None), |out| syn::parse_str(out).map(Some))
             .map_err(|err| annotate_error(err, msg))
     }
 
@@ -143,6 +180,7 @@ mod raw {
     #[serde(rename = "Nanopass", rename_all = "snake_case")]
     pub struct RawNanopass {
         pub config: NanopassConfig,
+        #[serde(default)]
         pub passes: Vec<RawPass>,
     }
 
@@ -155,11 +193,14 @@ mod raw {
     }
 
     impl RawContext {
-        fn parse(self) -> Result<Context> {
+        fn parse(self, location: &str) -> Result<Context> {
             let ctx = match self {
                 Self::Immutable(ctx) => {
                     let path = syn::parse_str(&ctx).map_err(|err| {
-                        annotate_error(err, "Context.Immutable must be a valid Rust path")
+                        annotate_error(
+                            err,
+                            format!("{}: Context.Immutable must be a valid Rust type", location),
+                        )
                     })?;
 
                     Context::Immutable(path)
@@ -167,7 +208,10 @@ mod raw {
 
                 Self::Mutable(ctx) => {
                     let path = syn::parse_str(&ctx).map_err(|err| {
-                        annotate_error(err, "Context.Mutable must be a valid Rust path")
+                        annotate_error(
+                            err,
+                            format!("{}: Context.Mutable must be a valid Rust type", location),
+                        )
                     })?;
 
                     Context::Mutable(path)
@@ -183,7 +227,7 @@ mod raw {
     #[derive(Deserialize)]
     #[serde(rename = "Pass", rename_all = "snake_case")]
     pub struct RawPass {
-        name: String,
+        name: Spanned<String>,
         description: Option<String>,
         function_name: String,
         function_vis: String,
@@ -194,31 +238,41 @@ mod raw {
     }
 
     impl RawPass {
-        pub fn parse(self) -> Result<Pass> {
+        pub fn parse(self, source: &Source<'_>) -> Result<Pass> {
+            let location = source.location(self.name.start());
+
             let function_name = syn::parse_str(&self.function_name).map_err(|err| {
-                annotate_error(err, "Pass.functionName must be a valid Rust ident")
+                annotate_error(
+                    err,
+                    format!("{}: Pass.functionName must be a valid Rust ident", location),
+                )
             })?;
 
-            let input_enum = syn::parse_str(&self.input_enum)
-                .map_err(|err| annotate_error(err, "Pass.inputEnum must be a valid Rust ident"))?;
+            let input_enum = syn::parse_str(&self.input_enum).map_err(|err| {
+                annotate_error(
+                    err,
+                    format!("{}: Pass.inputEnum must be a valid Rust ident", location),
+                )
+            })?;
 
             let output_enum = maybe_parse(
                 self.output_enum.as_deref(),
-                "Pass.outputEnum must be a valid Rust ident",
+                format!("{}: Pass.outputEnum must be a valid Rust ident", location),
             )?;
 
             let transformations = self
                 .transformations
                 .into_iter()
-                .map(|transformation| transformation.parse())
+                .map(|transformation| transformation.parse(source))
                 .collect::<Result<Vec<Transformation>>>()?;
 
             Ok(Pass {
-                name: self.name,
+                name: self.name.into_inner(),
+                location: location.clone(),
                 description: self.description,
                 function_name,
                 function_vis: self.function_vis.parse()?,
-                function_context: self.function_context.parse()?,
+                function_context: self.function_context.parse(&location)?,
                 input_enum,
                 output_enum,
                 transformations,
@@ -237,25 +291,40 @@ mod raw {
     }
 
     impl RawOperation {
-        fn parse(self) -> Result<Operation> {
+        fn parse(self, location: &str) -> Result<Operation> {
             let op = match self {
                 Self::Create(variant) => {
                     let variant = syn::parse_str(&variant).map_err(|err| {
-                        annotate_error(err, "Operation.Create must be a valid Rust variant")
+                        annotate_error(
+                            err,
+                            format!(
+                                "{}: Operation.Create must be a valid Rust variant",
+                                location
+                            ),
+                        )
                     })?;
 
                     Operation::Create(variant)
                 }
                 Self::Merge(variant) => {
                     let variant = syn::parse_str(&variant).map_err(|err| {
-                        annotate_error(err, "Operation.Merge must be a valid Rust variant")
+                        annotate_error(
+                            err,
+                            format!("{}: Operation.Merge must be a valid Rust ident", location),
+                        )
                     })?;
 
                     Operation::Merge(variant)
                 }
                 Self::Replace(variant) => {
                     let variant = syn::parse_str(&variant).map_err(|err| {
-                        annotate_error(err, "Operation.Replace must be a valid Rust variant")
+                        annotate_error(
+                            err,
+                            format!(
+                                "{}: Operation.Replace must be a valid Rust variant",
+                                location
+                            ),
+                        )
                     })?;
 
                     Operation::Replace(variant)
@@ -271,27 +340,39 @@ mod raw {
     #[derive(Deserialize)]
     #[serde(rename = "Transformation", rename_all = "snake_case")]
     struct RawTransformation {
-        input_variant: String,
+        input_variant: Spanned<String>,
         operation: RawOperation,
         user_function: String,
     }
 
     impl RawTransformation {
-        fn parse(self) -> Result<Transformation> {
-            let input_variant = syn::parse_str(&self.input_variant).map_err(|err| {
+        fn parse(self, source: &Source<'_>) -> Result<Transformation> {
+            let location = source.location(self.input_variant.start());
+
+            let input_variant = syn::parse_str(self.input_variant.get_ref()).map_err(|err| {
                 annotate_error(
                     err,
-                    "Transformation.inputVariant must be a valid Rust ident",
+                    format!(
+                        "{}: Transformation.inputVariant must be a valid Rust ident",
+                        location,
+                    ),
                 )
             })?;
 
             let user_function = syn::parse_str(&self.user_function).map_err(|err| {
-                annotate_error(err, "Transformation.userFunction must be a valid Rust path")
+                annotate_error(
+                    err,
+                    format!(
+                        "{}: Transformation.userFunction must be a valid Rust path",
+                        location,
+                    ),
+                )
             })?;
 
             Ok(Transformation {
+                operation: self.operation.parse(&location)?,
+                location,
                 input_variant,
-                operation: self.operation.parse()?,
                 user_function,
             })
         }
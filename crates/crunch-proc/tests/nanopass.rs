@@ -0,0 +1,127 @@
+use crunch_proc::nanopass;
+
+#[nanopass(file = "tests/passes/nanopass.toml")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr<'a> {
+    Int(i64),
+    Name(&'a str),
+    Add(i64, i64),
+    Group { items: Vec<i64> },
+    Unit,
+}
+
+#[nanopass(file = "tests/passes/nanopass.toml")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt<'a> {
+    Let { name: &'a str, value: i64 },
+    Expr(Expr<'a>),
+}
+
+#[derive(Debug, Default)]
+pub struct Lowering {
+    deleted: usize,
+    ints: usize,
+    adds: usize,
+}
+
+fn collect_name<'a>(names: &mut Vec<&'a str>, name: &&'a str) {
+    names.push(name);
+}
+
+fn collect_binding<'a>(names: &mut Vec<&'a str>, name: &&'a str, _value: &i64) {
+    names.push(name);
+}
+
+fn delete_unit(lowering: &mut Lowering) {
+    lowering.deleted += 1;
+}
+
+fn resolve_name(_lowering: &mut Lowering, name: &str) -> i64 {
+    name.len() as i64
+}
+
+fn sum_group(_lowering: &mut Lowering, items: Vec<i64>) -> (i64, usize) {
+    (items.iter().sum(), items.len())
+}
+
+fn constant(_lowering: &mut Lowering, value: i64) -> (i64, bool) {
+    (value.abs(), value < 0)
+}
+
+fn count_int(lowering: &mut Lowering, _value: &i64) {
+    lowering.ints += 1;
+}
+
+fn count_add(lowering: &mut Lowering, _lhs: &i64, _rhs: &i64) {
+    lowering.adds += 1;
+}
+
+fn count_sum(sums: &mut usize, _sum: &i64, _len: &usize) {
+    *sums += 1;
+}
+
+#[test]
+fn scan_only() {
+    let mut names = Vec::new();
+    for expr in &[Expr::Name("a"), Expr::Int(1), Expr::Name("b")] {
+        expr.collect_names(&mut names);
+    }
+
+    assert_eq!(names, ["a", "b"]);
+}
+
+#[test]
+fn mutating() {
+    let mut lowering = Lowering::default();
+    let lowered: Vec<Lowered> = vec![
+        Expr::Int(-3),
+        Expr::Name("abc"),
+        Expr::Add(1, 2),
+        Expr::Group {
+            items: vec![1, 2, 3],
+        },
+        Expr::Unit,
+    ]
+    .into_iter()
+    .filter_map(|expr| expr.lower(&mut lowering))
+    .collect();
+
+    assert_eq!(
+        lowered,
+        [
+            Lowered::Const {
+                value: 3,
+                negative: true,
+            },
+            Lowered::Int(3),
+            Lowered::Add(1, 2),
+            Lowered::Sum(6, 3),
+        ],
+    );
+    assert_eq!(lowering.deleted, 1);
+    assert_eq!(lowering.ints, 1);
+    assert_eq!(lowering.adds, 1);
+}
+
+#[test]
+fn chained() {
+    let mut sums = 0;
+    for lowered in &[Lowered::Sum(1, 1), Lowered::Int(1), Lowered::Sum(2, 2)] {
+        lowered.count_sums(&mut sums);
+    }
+
+    assert_eq!(sums, 2);
+}
+
+#[test]
+fn multiple_enums() {
+    let mut names = Vec::new();
+    Stmt::Let {
+        name: "x",
+        value: 1,
+    }
+    .collect_bindings(&mut names);
+    Stmt::Expr(Expr::Int(1)).collect_bindings(&mut names);
+
+    assert_eq!(names, ["x"]);
+}
@@ -35,6 +35,18 @@ pub enum CrunchcOpts {
         #[structopt(flatten)]
         options: BuildOptions,
     },
+
+    /// Applies the fixes suggested by the compiler's diagnostics to a source file
+    Fix {
+        #[structopt(flatten)]
+        options: BuildOptions,
+    },
+
+    /// Prints the trees held by a snapshot saved with `--emit snapshot`
+    Inspect {
+        #[structopt(flatten)]
+        options: BuildOptions,
+    },
 }
 
 impl CrunchcOpts {
@@ -44,7 +56,10 @@ impl CrunchcOpts {
 
     pub fn build_options(&self) -> BuildOptions {
         match self {
-            Self::Build { options, .. } | Self::Run { options, .. } => options.clone(),
+            Self::Build { options, .. }
+            | Self::Run { options, .. }
+            | Self::Fix { options, .. }
+            | Self::Inspect { options, .. } => options.clone(),
         }
     }
 }
@@ -155,10 +170,15 @@ pub enum EmissionKind {
     LlvmBc,
     Object,
     Assembly,
+    /// The file's AST and HIR along with the context they were made in, taken before
+    /// type checking so that it holds exactly what type checking was given
+    Snapshot,
 }
 
 impl EmissionKind {
-    pub const VALUES: [&'static str; 7] = ["ast", "hir", "mir", "llvm-ir", "llvm-bc", "obj", "asm"];
+    pub const VALUES: [&'static str; 8] = [
+        "ast", "hir", "mir", "llvm-ir", "llvm-bc", "obj", "asm", "snapshot",
+    ];
 }
 
 impl FromStr for EmissionKind {
@@ -173,6 +193,7 @@ impl FromStr for EmissionKind {
             "llvm-bc" => Self::LlvmBc,
             "obj" => Self::Object,
             "asm" => Self::Assembly,
+            "snapshot" => Self::Snapshot,
 
             _ => return Err("Unrecognized emission kind"),
         };
@@ -1,17 +1,30 @@
 use crate::{
     allocator::CRUNCHC_ALLOCATOR,
+    error::{Location, Span},
     files::FileId,
     salsa,
-    strings::StrInterner,
+    strings::{StrInterner, StrT},
     trees::{
-        ast::{Expr as AstExpr, Item as AstItem, Stmt as AstStmt, Type as AstType},
-        hir::{Expr as HirExpr, Item as HirItem, Stmt as HirStmt, Type as HirType, TypeId},
+        ast::{self, Expr as AstExpr, Item as AstItem, Stmt as AstStmt, Type as AstType},
+        hir::{
+            self, Expr as HirExpr, Item as HirItem, ItemId, Stmt as HirStmt, Type as HirType,
+            TypeId,
+        },
+        CallConv, ItemPath, Sign, Signedness,
     },
     utils::{HashMap, Hasher},
 };
+#[cfg(feature = "no-std")]
+use alloc::{boxed::Box, string::String, vec::Vec};
 use core::{
     cell::{Cell, RefCell},
     fmt::{Debug, Formatter, Result as FmtResult},
+    marker::PhantomData,
+};
+use crunch_proc::Load;
+use serde::{
+    de::{DeserializeSeed, Error as DeError, SeqAccess, Unexpected, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
 };
 use std::sync::atomic::{AtomicU32, Ordering};
 use typed_arena::Arena;
@@ -165,6 +178,7 @@ pub struct Context<'ctx> {
     // TODO: Pull strings out of refcells
     strings: StrInterner,
     file_id: AtomicU32,
+    item_id: AtomicU32,
 }
 
 impl<'ctx> Context<'ctx> {
@@ -174,6 +188,7 @@ impl<'ctx> Context<'ctx> {
             arenas,
             strings: Self::construct_string_interner(),
             file_id: AtomicU32::new(0),
+            item_id: AtomicU32::new(0),
         }
     }
 
@@ -205,6 +220,10 @@ impl<'ctx> Context<'ctx> {
         FileId::new(self.file_id.fetch_add(1, Ordering::Relaxed))
     }
 
+    pub fn next_item_id(&self) -> ItemId {
+        ItemId::new(self.item_id.fetch_add(1, Ordering::Relaxed))
+    }
+
     // In regards to the following: Fuck you, you deal with this bullshit
     // TODO: Replace with queries?
 
@@ -271,3 +290,378 @@ impl<'ctx> Context<'ctx> {
         self.arenas.hir.type_map.borrow().get(&id).copied()
     }
 }
+
+/// The trees saved alongside a [`Context`] by [`Context::save`] and given back by [`Context::load`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot<'ctx> {
+    pub ast: Vec<&'ctx AstItem<'ctx>>,
+    pub hir: Vec<&'ctx HirItem<'ctx>>,
+}
+
+impl<'ctx> Context<'ctx> {
+    /// Serializes the context's strings, file and item ids and HIR types along with the trees of `snapshot`,
+    /// which can later be loaded back with [`Context::load`]
+    pub fn save<S: Serializer>(
+        &self,
+        snapshot: &Snapshot<'ctx>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut hir_types: Vec<(TypeId, &'ctx HirType)> = self
+            .arenas
+            .hir
+            .type_map
+            .borrow()
+            .iter()
+            .map(|(&id, &ty)| (id, ty))
+            .collect();
+        // Sort the types so that the same context always gives the same snapshot
+        hir_types.sort_unstable_by_key(|&(id, _)| id);
+
+        SavedContext {
+            strings: &self.strings,
+            next_file_id: self.file_id.load(Ordering::Relaxed),
+            next_item_id: self.item_id.load(Ordering::Relaxed),
+            hir_type_id: self.arenas.hir.type_id.get(),
+            hir_types,
+            ast: &snapshot.ast,
+            hir: &snapshot.hir,
+        }
+        .serialize(serializer)
+    }
+
+    /// Deserializes a context saved by [`Context::save`], allocating all of its trees within `arenas`.
+    /// Every [`StrT`] and [`TypeId`] from the saved context refers to the same string and type in the
+    /// loaded one
+    ///
+    /// `arenas` must not have any HIR types allocated within it yet, otherwise the saved type ids
+    /// would collide with the existing ones
+    ///
+    /// [`StrT`]: crate::strings::StrT
+    pub fn load<'de, D: Deserializer<'de>>(
+        arenas: Arenas<'ctx>,
+        deserializer: D,
+    ) -> Result<(Self, Snapshot<'ctx>), D::Error> {
+        // Checked before deserializing so that nothing gets allocated into arenas that can't be used
+        if !arenas.hir.type_map.borrow().is_empty() {
+            return Err(D::Error::custom(
+                "a context snapshot can only be loaded into arenas without any HIR types",
+            ));
+        }
+
+        let loaded = LoadedContext::load(&arenas, deserializer)?;
+
+        {
+            let mut type_map = arenas.hir.type_map.borrow_mut();
+            for (id, ty) in loaded.hir_types {
+                let ty: &'ctx HirType = arenas.hir.types.alloc(ty);
+                if type_map.insert(id, ty).is_some() {
+                    return Err(D::Error::custom(format_args!(
+                        "the HIR type {:?} was saved more than once",
+                        id,
+                    )));
+                }
+            }
+        }
+        arenas.hir.type_id.set(loaded.hir_type_id);
+
+        let context = Self {
+            arenas,
+            strings: loaded.strings,
+            file_id: AtomicU32::new(loaded.next_file_id),
+            item_id: AtomicU32::new(loaded.next_item_id),
+        };
+        let snapshot = Snapshot {
+            ast: loaded.ast,
+            hir: loaded.hir,
+        };
+
+        Ok((context, snapshot))
+    }
+}
+
+/// The serialized form of a [`Context`], its fields must be kept in sync with [`LoadedContext`]
+#[derive(Serialize)]
+struct SavedContext<'a, 'ctx> {
+    strings: &'a StrInterner,
+    next_file_id: u32,
+    next_item_id: u32,
+    hir_type_id: usize,
+    hir_types: Vec<(TypeId, &'ctx HirType)>,
+    ast: &'a [&'ctx AstItem<'ctx>],
+    hir: &'a [&'ctx HirItem<'ctx>],
+}
+
+/// The deserialized form of a [`Context`], its fields must be kept in sync with [`SavedContext`]
+#[derive(Load)]
+struct LoadedContext<'ctx> {
+    strings: StrInterner,
+    next_file_id: u32,
+    next_item_id: u32,
+    hir_type_id: usize,
+    hir_types: Vec<(TypeId, HirType)>,
+    ast: Vec<&'ctx AstItem<'ctx>>,
+    hir: Vec<&'ctx HirItem<'ctx>>,
+}
+
+/// Deserializes a value loaded by [`Context::load`], allocating every tree node it refers to within
+/// the arenas it's being loaded into. The arenas are always given explicitly so that the lifetime
+/// of the loaded nodes is the lifetime of the arenas they were allocated in.
+///
+/// Derived with `#[derive(Load)]`, which deserializes values the same way serde's derived
+/// `Deserialize` would
+pub(crate) trait Load<'ctx>: Sized {
+    fn load<'de, D>(arenas: &Arenas<'ctx>, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+}
+
+/// Loads a `T` within the given arenas
+pub(crate) struct LoadSeed<'a, 'ctx, T> {
+    arenas: &'a Arenas<'ctx>,
+    __loaded: PhantomData<fn() -> T>,
+}
+
+impl<'a, 'ctx, T> LoadSeed<'a, 'ctx, T> {
+    pub(crate) fn new(arenas: &'a Arenas<'ctx>) -> Self {
+        Self {
+            arenas,
+            __loaded: PhantomData,
+        }
+    }
+}
+
+impl<'de, 'a, 'ctx, T: Load<'ctx>> DeserializeSeed<'de> for LoadSeed<'a, 'ctx, T> {
+    type Value = T;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        T::load(self.arenas, deserializer)
+    }
+}
+
+/// Identifies the field of a loaded struct, giving `None` for unknown fields
+pub(crate) struct FieldIdent(pub(crate) &'static [&'static str]);
+
+impl<'de> DeserializeSeed<'de> for FieldIdent {
+    type Value = Option<usize>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> Visitor<'de> for FieldIdent {
+    type Value = Option<usize>;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("a field identifier")
+    }
+
+    fn visit_u64<E: DeError>(self, idx: u64) -> Result<Self::Value, E> {
+        Ok(Some(idx as usize).filter(|&idx| idx < self.0.len()))
+    }
+
+    fn visit_str<E: DeError>(self, field: &str) -> Result<Self::Value, E> {
+        Ok(self.0.iter().position(|&name| name == field))
+    }
+
+    fn visit_bytes<E: DeError>(self, field: &[u8]) -> Result<Self::Value, E> {
+        Ok(self.0.iter().position(|&name| name.as_bytes() == field))
+    }
+}
+
+/// Identifies the variant of a loaded enum
+pub(crate) struct VariantIdent(pub(crate) &'static [&'static str]);
+
+impl<'de> DeserializeSeed<'de> for VariantIdent {
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> Visitor<'de> for VariantIdent {
+    type Value = usize;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("a variant identifier")
+    }
+
+    fn visit_u64<E: DeError>(self, idx: u64) -> Result<Self::Value, E> {
+        if (idx as usize) < self.0.len() {
+            Ok(idx as usize)
+        } else {
+            Err(E::invalid_value(Unexpected::Unsigned(idx), &self))
+        }
+    }
+
+    fn visit_str<E: DeError>(self, variant: &str) -> Result<Self::Value, E> {
+        self.0
+            .iter()
+            .position(|&name| name == variant)
+            .ok_or_else(|| E::unknown_variant(variant, self.0))
+    }
+
+    fn visit_bytes<E: DeError>(self, variant: &[u8]) -> Result<Self::Value, E> {
+        self.0
+            .iter()
+            .position(|&name| name.as_bytes() == variant)
+            .ok_or_else(|| E::unknown_variant(&String::from_utf8_lossy(variant), self.0))
+    }
+}
+
+/// Types that don't hold any tree nodes are loaded with their `Deserialize` impls
+macro_rules! load_with_deserialize {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl<'ctx> Load<'ctx> for $ty {
+                fn load<'de, D>(_arenas: &Arenas<'ctx>, deserializer: D) -> Result<Self, D::Error>
+                where
+                    D: Deserializer<'de>,
+                {
+                    <$ty>::deserialize(deserializer)
+                }
+            }
+        )*
+    };
+}
+
+load_with_deserialize! {
+    bool, char, u8, u16, u32, u64, u128, usize, f32, f64, String,
+    StrT, StrInterner, FileId, Location, Span, ItemPath, CallConv, Signedness, Sign,
+    ast::Exposure, ast::Dest, ast::Attribute, ast::Vis, ast::RangeKind, ast::FormatSpec,
+    ast::Text, ast::Rune, ast::Integer, ast::Float, ast::TypeOp, ast::CompOp, ast::AssignKind,
+    ast::BinaryOp, ast::UnaryOp,
+    TypeId, ItemId, hir::Generic, hir::Enum, hir::Variant, hir::Var, hir::ArgMismatch,
+    hir::Binding, hir::Pattern, HirType, hir::TypeKind, hir::Literal, hir::LiteralVal,
+}
+
+/// Tree nodes are allocated within the arenas they're loaded into, which is what relinks the
+/// `&'ctx` references of a tree
+macro_rules! load_arena_refs {
+    ($($node:ident => $tree:ident.$arena:ident),* $(,)?) => {
+        $(
+            impl<'ctx> Load<'ctx> for &'ctx $node<'ctx> {
+                fn load<'de, D>(arenas: &Arenas<'ctx>, deserializer: D) -> Result<Self, D::Error>
+                where
+                    D: Deserializer<'de>,
+                {
+                    let node = <$node<'ctx>>::load(arenas, deserializer)?;
+                    Ok(arenas.$tree.$arena.alloc(node))
+                }
+            }
+        )*
+    };
+}
+
+load_arena_refs! {
+    AstItem => ast.item,
+    AstStmt => ast.stmt,
+    AstExpr => ast.expr,
+    AstType => ast.types,
+    HirItem => hir.item,
+    HirStmt => hir.stmt,
+    HirExpr => hir.expr,
+}
+
+impl<'ctx, T: Load<'ctx>> Load<'ctx> for Box<T> {
+    fn load<'de, D: Deserializer<'de>>(
+        arenas: &Arenas<'ctx>,
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        T::load(arenas, deserializer).map(Box::new)
+    }
+}
+
+impl<'ctx, T: Load<'ctx>> Load<'ctx> for Option<T> {
+    fn load<'de, D: Deserializer<'de>>(
+        arenas: &Arenas<'ctx>,
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        struct OptionVisitor<'a, 'ctx, T>(LoadSeed<'a, 'ctx, T>);
+
+        impl<'de, 'a, 'ctx, T: Load<'ctx>> Visitor<'de> for OptionVisitor<'a, 'ctx, T> {
+            type Value = Option<T>;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> FmtResult {
+                f.write_str("an option")
+            }
+
+            fn visit_none<E: DeError>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_unit<E: DeError>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_some<D: Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<Self::Value, D::Error> {
+                self.0.deserialize(deserializer).map(Some)
+            }
+        }
+
+        deserializer.deserialize_option(OptionVisitor(LoadSeed::new(arenas)))
+    }
+}
+
+impl<'ctx, T: Load<'ctx>> Load<'ctx> for Vec<T> {
+    fn load<'de, D: Deserializer<'de>>(
+        arenas: &Arenas<'ctx>,
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        struct VecVisitor<'a, 'ctx, T>(LoadSeed<'a, 'ctx, T>);
+
+        impl<'de, 'a, 'ctx, T: Load<'ctx>> Visitor<'de> for VecVisitor<'a, 'ctx, T> {
+            type Value = Vec<T>;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> FmtResult {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                // Don't trust the size hint too much, it comes from the serialized data
+                let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+                while let Some(element) = seq.next_element_seed(LoadSeed::new(self.0.arenas))? {
+                    elements.push(element);
+                }
+
+                Ok(elements)
+            }
+        }
+
+        deserializer.deserialize_seq(VecVisitor(LoadSeed::new(arenas)))
+    }
+}
+
+impl<'ctx, A: Load<'ctx>, B: Load<'ctx>> Load<'ctx> for (A, B) {
+    fn load<'de, D: Deserializer<'de>>(
+        arenas: &Arenas<'ctx>,
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        struct PairVisitor<'a, 'ctx, A, B>(&'a Arenas<'ctx>, PhantomData<fn() -> (A, B)>);
+
+        impl<'de, 'a, 'ctx, A: Load<'ctx>, B: Load<'ctx>> Visitor<'de> for PairVisitor<'a, 'ctx, A, B> {
+            type Value = (A, B);
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> FmtResult {
+                f.write_str("a tuple of size 2")
+            }
+
+            fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+                let first = seq
+                    .next_element_seed(LoadSeed::new(self.0))?
+                    .ok_or_else(|| S::Error::invalid_length(0, &self))?;
+                let second = seq
+                    .next_element_seed(LoadSeed::new(self.0))?
+                    .ok_or_else(|| S::Error::invalid_length(1, &self))?;
+
+                Ok((first, second))
+            }
+        }
+
+        deserializer.deserialize_tuple(2, PairVisitor(arenas, PhantomData))
+    }
+}
@@ -1,5 +1,5 @@
-use crate::files::FileId;
-use alloc::sync::Arc;
+use crate::files::{FileId, PRELUDE};
+use alloc::{borrow::ToOwned, sync::Arc};
 use core::{cmp::Ordering, ops::Range};
 use std::path::PathBuf;
 
@@ -34,6 +34,10 @@ pub trait SourceDatabase: salsa::Database {
 }
 
 fn file_name(db: &dyn SourceDatabase, file: FileId) -> Arc<String> {
+    if file == FileId::PRELUDE {
+        return Arc::new("prelude".to_owned());
+    }
+
     // FIXME: Make this get the actual relative path
     Arc::new(
         db.file_path(file)
@@ -45,6 +49,10 @@ fn file_name(db: &dyn SourceDatabase, file: FileId) -> Arc<String> {
 }
 
 fn source_text(db: &dyn SourceDatabase, file: FileId) -> Arc<String> {
+    if file == FileId::PRELUDE {
+        return Arc::new(PRELUDE.to_owned());
+    }
+
     Arc::new(std::fs::read_to_string(&*db.file_path(file)).unwrap())
 }
 
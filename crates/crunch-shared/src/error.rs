@@ -1,5 +1,6 @@
 use crate::files::FileId;
 use alloc::{
+    boxed::Box,
     collections::VecDeque,
     format,
     string::{String, ToString},
@@ -17,6 +18,7 @@ use core::{
     mem,
     ops::{Deref, DerefMut, Range},
 };
+use crunch_proc::Load;
 use derive_more::Display;
 use serde::{Deserialize, Serialize};
 
@@ -184,7 +186,9 @@ impl Into<[usize; 2]> for Span {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Load,
+)]
 pub struct Locatable<T> {
     data: T,
     loc: Option<Location>,
@@ -245,6 +249,17 @@ impl<T> Locatable<T> {
         }
     }
 
+    #[inline]
+    pub fn map_location<F>(self, map: F) -> Self
+    where
+        F: FnOnce(Location) -> Location,
+    {
+        Locatable {
+            data: self.data,
+            loc: self.loc.map(map),
+        }
+    }
+
     #[inline]
     pub fn as_ref(&self) -> Locatable<&T> {
         Locatable {
@@ -325,6 +340,16 @@ impl ErrorHandler {
         self.fatal
     }
 
+    #[inline]
+    pub fn errors(&self) -> impl Iterator<Item = &Locatable<Error>> {
+        self.errors.iter()
+    }
+
+    #[inline]
+    pub fn warnings(&self) -> impl Iterator<Item = &Locatable<Warning>> {
+        self.warnings.iter()
+    }
+
     #[inline]
     pub fn err_len(&self) -> usize {
         self.errors.len()
@@ -405,9 +430,27 @@ pub enum Error {
 
     #[display(fmt = "Unexpected end of file")]
     EndOfFile,
+
+    #[display(fmt = "{}", _0)]
+    Suggested(Box<Error>, Suggestion),
 }
 
 impl Error {
+    /// Attaches a suggested fix to the error
+    #[inline]
+    pub fn with_suggestion(self, suggestion: Suggestion) -> Self {
+        Self::Suggested(Box::new(self), suggestion)
+    }
+
+    #[inline]
+    pub fn suggestion(&self) -> Option<&Suggestion> {
+        if let Self::Suggested(_, suggestion) = self {
+            Some(suggestion)
+        } else {
+            None
+        }
+    }
+
     fn emit<'a, F>(
         &self,
         files: &'a F,
@@ -427,7 +470,99 @@ impl Error {
                     .with_message(self.to_string())
                     .with_labels(vec![Label::primary(file, span)]),
             ),
+            Self::Suggested(err, suggestion) => {
+                err.emit(files, file, span, diag);
+
+                if let Some(last) = diag.last_mut() {
+                    last.notes.push(format!("help: {}", suggestion.message()));
+                }
+            }
+        }
+    }
+}
+
+/// A fix suggested alongside an error
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Suggestion {
+    message: String,
+    edits: Vec<Edit>,
+}
+
+impl Suggestion {
+    /// Creates a suggestion that only gives advice, without any edits to apply
+    #[inline]
+    pub fn new<M>(message: M) -> Self
+    where
+        M: Into<String>,
+    {
+        Self {
+            message: message.into(),
+            edits: Vec::new(),
+        }
+    }
+
+    /// Adds an edit that replaces the source at `location` with `replacement`
+    #[inline]
+    pub fn with_edit<R>(mut self, location: Location, replacement: R) -> Self
+    where
+        R: Into<String>,
+    {
+        self.edits.push(Edit {
+            location,
+            replacement: replacement.into(),
+        });
+
+        self
+    }
+
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    #[inline]
+    pub fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+
+    /// Whether the suggestion can be applied to the source without any human input
+    #[inline]
+    pub fn is_applicable(&self) -> bool {
+        !self.edits.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Edit {
+    pub location: Location,
+    pub replacement: String,
+}
+
+impl Edit {
+    /// Applies edits to `source`, skipping any that overlap with an edit before them.
+    /// Returns the edited source and the number of edits applied
+    pub fn apply_all<'a, I>(source: &str, edits: I) -> (String, usize)
+    where
+        I: IntoIterator<Item = &'a Edit>,
+    {
+        let mut edits: Vec<&Edit> = edits.into_iter().collect();
+        edits.sort_by_key(|edit| (edit.location.span().start(), edit.location.span().end()));
+
+        let (mut edited, mut applied, mut cursor) = (String::with_capacity(source.len()), 0, 0);
+        for edit in edits {
+            let range = edit.location.range();
+            if range.start < cursor || range.end > source.len() {
+                continue;
+            }
+
+            edited.push_str(&source[cursor..range.start]);
+            edited.push_str(&edit.replacement);
+            cursor = range.end;
+            applied += 1;
         }
+        edited.push_str(&source[cursor..]);
+
+        (edited, applied)
     }
 }
 
@@ -495,6 +630,41 @@ pub enum SyntaxError {
 
     #[display(fmt = "Unrecognized calling convention: {:?}", _0)]
     UnrecognizedCallConv(String),
+
+    #[display(fmt = "Patterns may only contain a single `..`")]
+    MultipleRestPatterns,
+
+    #[display(fmt = "`..` is only allowed within tuple, slice and struct patterns")]
+    MisplacedRestPattern,
+
+    #[display(fmt = "Unclosed '{{' in string, use '{{{{' to write a literal brace")]
+    UnclosedFormatBrace,
+
+    #[display(fmt = "Unmatched '}}' in string, use '}}}}' to write a literal brace")]
+    UnmatchedFormatBrace,
+
+    #[display(
+        fmt = "Unknown format specifier {:?}, expected either nothing or '?'",
+        _0
+    )]
+    InvalidFormatSpec(String),
+
+    #[display(
+        fmt = "Empty '{{}}' in string, put the value to format between the braces or use '{{{{}}}}' to write literal braces"
+    )]
+    EmptyFormatHole,
+
+    #[display(fmt = "Strings containing interpolated values are not allowed here")]
+    InterpolationNotAllowed,
+
+    #[display(fmt = "Variadic arguments must be the last argument of a function")]
+    VariadicNotLast,
+
+    #[display(fmt = "Positional arguments cannot come after named arguments")]
+    PositionalAfterNamed,
+
+    #[display(fmt = "Inclusive ranges must have an end, `start..=end`")]
+    InclusiveRangeWithoutEnd,
 }
 
 impl SyntaxError {
@@ -561,6 +731,84 @@ pub enum SemanticError {
 
     #[display(fmt = "A constant cannot be declared as mutable")]
     MutableConstant,
+
+    #[display(
+        fmt = "'{}' is private to its module, mark it as `pkg` or `exposed` to use it here",
+        _0
+    )]
+    PrivateItem(String),
+
+    #[display(fmt = "The variable of a `for` loop must be a single name")]
+    InvalidForBinding,
+
+    #[display(fmt = "Only ranges with a start can be looped over, e.g. `start..end` or `start..`")]
+    UnboundedRangeStart,
+
+    #[display(fmt = "`{}` can only be used within a loop", _0)]
+    OutsideLoop(String),
+
+    #[display(fmt = "Only variables, tuple fields, indices and dereferences can be assigned to")]
+    InvalidAssignment,
+
+    #[display(fmt = "Enum variants can only be given positional arguments")]
+    NonPositionalVariantArg,
+
+    #[display(fmt = "The type '{}' does not exist", _0)]
+    UnknownType(String),
+
+    #[display(fmt = "The trait '{}' does not exist", _0)]
+    UnknownTrait(String),
+
+    #[display(
+        fmt = "The trait method '{}' has a body, but default methods aren't supported yet",
+        _0
+    )]
+    DefaultTraitMethod(String),
+
+    #[display(fmt = "Only methods can be declared within traits and extend blocks")]
+    NonMethodItem,
+
+    #[display(fmt = "Generic parameters must be a single name, like `T` or `T: Trait`")]
+    InvalidGeneric,
+
+    #[display(fmt = "Generic {} aren't supported yet", _0)]
+    UnsupportedGenerics(String),
+
+    #[display(
+        fmt = "'{}' takes {} generic parameters, but was given {}",
+        name,
+        expected,
+        received
+    )]
+    GenericArity {
+        name: String,
+        expected: usize,
+        received: usize,
+    },
+
+    #[display(fmt = "Aliases must be given a single name, like `alias Name = Type`")]
+    InvalidAlias,
+
+    #[display(fmt = "The alias '{}' refers to itself", _0)]
+    RecursiveAlias(String),
+
+    #[display(
+        fmt = "Only traits can be combined with other types using `&`, but '{}' is not a trait",
+        _0
+    )]
+    InvalidIntersection(String),
+
+    #[display(fmt = "The type '{}' isn't supported yet", _0)]
+    UnsupportedType(String),
+
+    #[display(fmt = "Only functions, methods and enum variants can be called")]
+    InvalidCaller,
+
+    #[display(fmt = "Expected an enum variant, like `Enum.Variant` or `Enum.Variant(value)`")]
+    InvalidVariant,
+
+    #[display(fmt = "Field accesses aren't supported yet")]
+    UnsupportedFieldAccess,
 }
 
 impl SemanticError {
@@ -649,11 +897,159 @@ pub enum TypeError {
     FuncNotInScope(String),
 
     #[display(fmt = "<Internal error, incorrectly rendered an error>")]
-    NotEnoughArgs {
+    TooManyArgs {
+        expected: usize,
+        received: usize,
+        def_site: Location,
+    },
+
+    #[display(
+        fmt = "The variable '{}' is not bound in every alternative of the pattern",
+        _0
+    )]
+    UnboundAlternative(String),
+
+    #[display(fmt = "'{}' is not a known type or enum variant", _0)]
+    UnresolvedPattern(String),
+
+    #[display(fmt = "Values of type '{}' cannot be formatted", _0)]
+    Unformattable(String),
+
+    #[display(fmt = "The argument '{}' was not given a value", _0)]
+    MissingArg(String),
+
+    #[display(fmt = "The argument '{}' was given more than once", _0)]
+    DuplicateArg(String),
+
+    #[display(fmt = "The function '{}' has no argument named '{}'", _0, _1)]
+    UnknownArg(String, String),
+
+    #[display(
+        fmt = "The function '{}' has no variadic argument for values to be spread into",
+        _0
+    )]
+    UnexpectedSpread(String),
+
+    #[display(fmt = "The enum '{}' has no variant named '{}'", _0, _1)]
+    UnknownVariant(String, String),
+
+    #[display(
+        fmt = "The variant '{}' holds {} values, but was given {}",
+        variant,
+        expected,
+        received
+    )]
+    VariantArity {
+        variant: String,
         expected: usize,
         received: usize,
+    },
+
+    #[display(fmt = "'{}' already implements the trait '{}'", ty, trait_)]
+    DuplicateImpl {
+        ty: String,
+        trait_: String,
+        first: Location,
+    },
+
+    #[display(fmt = "'{}' has multiple methods named '{}'", ty, method)]
+    DuplicateMethod {
+        ty: String,
+        method: String,
+        first: Location,
+    },
+
+    #[display(
+        fmt = "The implementation of '{}' for '{}' is missing the method '{}'",
+        trait_,
+        ty,
+        method
+    )]
+    MissingTraitMethod {
+        trait_: String,
+        ty: String,
+        method: String,
+    },
+
+    #[display(fmt = "'{}' is not a method of the trait '{}'", _0, _1)]
+    NotTraitMethod(String, String),
+
+    #[display(
+        fmt = "The signature of '{}' doesn't match its declaration in the trait '{}'",
+        method,
+        trait_
+    )]
+    TraitMethodSignature {
+        method: String,
+        trait_: String,
         def_site: Location,
     },
+
+    #[display(fmt = "The type '{}' has no method named '{}'", _0, _1)]
+    UnknownMethod(String, String),
+
+    #[display(
+        fmt = "The method '{}' on '{}' is given by multiple traits, so the call is ambiguous",
+        _1,
+        _0
+    )]
+    AmbiguousMethod(String, String),
+
+    #[display(
+        fmt = "'{}' takes no `self` argument, so it can't be called as a method",
+        _0
+    )]
+    NotAMethod(String),
+
+    #[display(fmt = "The type '{}' does not implement the trait '{}'", ty, trait_)]
+    UnsatisfiedBound {
+        ty: String,
+        trait_: String,
+        def_site: Location,
+    },
+
+    #[display(fmt = "Failed to infer the type given to the generic '{}'", _0)]
+    UninferredGeneric(String),
+
+    #[display(
+        fmt = "The index {} is out of bounds for '{}', which has a length of {}",
+        index,
+        ty,
+        length
+    )]
+    IndexOutOfBounds {
+        index: u128,
+        ty: String,
+        length: u64,
+    },
+
+    #[display(fmt = "The tuple '{}' has no field {}", _0, _1)]
+    NoTupleField(String, u64),
+
+    #[display(fmt = "Cannot assign to '{}', which is not mutable", _0)]
+    ImmutableAssignment(String),
+
+    #[display(
+        fmt = "Cannot assign through '{}', which is not a mutable reference or pointer",
+        _0
+    )]
+    ImmutableDeref(String),
+
+    #[display(fmt = "Values of type '{}' cannot be dereferenced", _0)]
+    InvalidDeref(String),
+
+    #[display(
+        fmt = "The operator '{}' cannot be applied to values of type '{}'",
+        _0,
+        _1
+    )]
+    InvalidOperator(String, String),
+
+    #[display(fmt = "Ranges can only be used to slice arrays and slices or to be looped over")]
+    RangeValue,
+
+    #[display(fmt = "Values of type '{}' aren't supported yet", _0)]
+    UnsupportedType(String),
 }
 
 impl TypeError {
@@ -681,7 +1077,43 @@ impl TypeError {
                 )
             }
 
-            Self::NotEnoughArgs {
+            Self::DuplicateImpl { first, .. } | Self::DuplicateMethod { first, .. } => {
+                diag.push(
+                    Diagnostic::error()
+                        .with_message(self.to_string())
+                        .with_labels(vec![
+                            Label::primary(file, span),
+                            Label::secondary(first.file(), first.range())
+                                .with_message("First given here"),
+                        ]),
+                );
+            }
+
+            Self::TraitMethodSignature { def_site, .. } => {
+                diag.push(
+                    Diagnostic::error()
+                        .with_message(self.to_string())
+                        .with_labels(vec![
+                            Label::primary(file, span),
+                            Label::secondary(def_site.file(), def_site.range())
+                                .with_message("Declared here"),
+                        ]),
+                );
+            }
+
+            Self::UnsatisfiedBound { def_site, .. } => {
+                diag.push(
+                    Diagnostic::error()
+                        .with_message(self.to_string())
+                        .with_labels(vec![
+                            Label::primary(file, span),
+                            Label::secondary(def_site.file(), def_site.range())
+                                .with_message("Required by this bound"),
+                        ]),
+                );
+            }
+
+            Self::TooManyArgs {
                 expected,
                 received,
                 def_site,
@@ -689,7 +1121,7 @@ impl TypeError {
                 diag.push(
                     Diagnostic::error()
                         .with_message(format!(
-                            "expected {} argument{}, got {}",
+                            "expected at most {} argument{}, got {}",
                             expected,
                             if *expected == 1 { "" } else { "s" },
                             received,
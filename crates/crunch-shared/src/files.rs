@@ -8,11 +8,17 @@ use codespan_reporting::files;
 use core::{fmt, ops::Range};
 use serde::{Deserialize, Serialize};
 
+/// The source of the prelude, which holds the items every file can use without declaring them
+pub const PRELUDE: &str = include_str!("prelude.crunch");
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
 #[repr(transparent)]
 pub struct FileId(pub u32);
 
 impl FileId {
+    /// The file the prelude is parsed from, which doesn't exist on disk
+    pub const PRELUDE: Self = Self(u32::MAX);
+
     #[inline]
     pub fn new(id: u32) -> Self {
         Self(id)
@@ -31,7 +31,10 @@ pub mod error;
 pub mod file_hash;
 pub mod files;
 pub mod meta;
+pub mod passes;
 pub mod strings;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod trees;
 pub mod utils;
 pub mod visitors;
@@ -1,5 +1,7 @@
 use crate::trees::ast::{ExternBlock, Item};
+#[cfg(feature = "no-std")]
+use alloc::vec::Vec;
 
-pub fn flatten_external_blocks(items: &mut Vec<Item>, mut block: ExternBlock) {
-    items.extend(block.items.drain(..));
+pub fn flatten_external_blocks<'ctx>(items: &mut Vec<&'ctx Item<'ctx>>, block: ExternBlock<'ctx>) {
+    items.extend(block.items);
 }
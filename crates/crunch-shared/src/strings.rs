@@ -12,6 +12,7 @@ mod interner {
     use alloc::sync::Arc;
     use core::fmt::{Debug, Display};
     use lasso::{Capacity, Key, Spur, ThreadedRodeo};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
     #[derive(Debug, Clone)]
     #[repr(transparent)]
@@ -51,6 +52,22 @@ mod interner {
             Self::new()
         }
     }
+
+    impl Serialize for StrInterner {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.0.serialize(serializer)
+        }
+    }
+
+    /// Strings are re-interned in the order they were serialized in, so every [`StrT`] from the
+    /// serialized interner resolves to the same string
+    ///
+    /// [`StrT`]: super::StrT
+    impl<'de> Deserialize<'de> for StrInterner {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            ThreadedRodeo::deserialize(deserializer).map(|rodeo| Self(Arc::new(rodeo)))
+        }
+    }
 }
 
 #[cfg(not(feature = "concurrent"))]
@@ -63,6 +80,7 @@ mod interner {
         fmt::{Debug, Display},
     };
     use lasso::{Capacity, Rodeo, Spur};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
     #[derive(Debug)]
     #[repr(transparent)]
@@ -112,12 +130,27 @@ mod interner {
             Self::new()
         }
     }
+
+    impl Serialize for StrInterner {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.0.borrow().serialize(serializer)
+        }
+    }
+
+    /// Strings are re-interned in the order they were serialized in, so every [`StrT`] from the
+    /// serialized interner resolves to the same string
+    impl<'de> Deserialize<'de> for StrInterner {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            Rodeo::deserialize(deserializer).map(|rodeo| Self(Rc::new(RefCell::new(rodeo))))
+        }
+    }
 }
 
 // This is to make rust-analyzer shut up, concurrent & no-std is not supported
 #[cfg(all(feature = "concurrent", feature = "no-std"))]
 mod interner {
     use super::StrT;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
     #[derive(Debug, Clone)]
     #[allow(missing_copy_implementations)]
@@ -138,6 +171,18 @@ mod interner {
             unreachable!()
         }
     }
+
+    impl Serialize for StrInterner {
+        fn serialize<S: Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+            unreachable!()
+        }
+    }
+
+    impl<'de> Deserialize<'de> for StrInterner {
+        fn deserialize<D: Deserializer<'de>>(_deserializer: D) -> Result<Self, D::Error> {
+            unreachable!()
+        }
+    }
 }
 
 /// A token for an interned string
@@ -0,0 +1,61 @@
+//! Helpers for running the compiler's queries within tests and fuzzers
+
+use crate::{
+    codespan_reporting::term::{termcolor::StandardStream, Config as TermConfig},
+    config::{BuildOptions, ConfigDatabase, TermColor},
+    context::{Arenas, Context, ContextDatabase, OwnedArenas},
+    databases::SourceDatabase,
+    files::FileId,
+    utils::DbgWrap,
+};
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::path::PathBuf;
+
+/// Build options that keep the compiler from writing anything or coloring its diagnostics
+pub fn options() -> BuildOptions {
+    let mut options = BuildOptions::new("test.crunch");
+    options.quiet = true;
+    options.color = TermColor::None;
+
+    options
+}
+
+/// Runs `query` on a fresh database that contains only `source`, with the configuration,
+/// context and source inputs already set. Inputs of any later stages, like a file's last edit,
+/// are left for `query` to set
+pub fn with_database<DB, F, T>(source: &str, query: F) -> T
+where
+    DB: Default + ConfigDatabase + ContextDatabase + SourceDatabase,
+    F: FnOnce(&mut DB, FileId) -> T,
+{
+    // The source database reads files from disk, so every call gets its own file
+    static FILES: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!(
+        "crunch-test-{}-{}.crunch",
+        std::process::id(),
+        FILES.fetch_add(1, Ordering::Relaxed),
+    ));
+    std::fs::write(&path, source).expect("failed to write the source file");
+
+    let owned_arenas = OwnedArenas::default();
+    let context = Context::new(Arenas::from(&owned_arenas));
+    let file = context.next_file_id();
+
+    // The database is dropped before the context, so nothing it holds outlives the arenas
+    let mut database = DB::default();
+    database.set_config(Arc::new(options()));
+    database.set_writer(Arc::new(DbgWrap::new(StandardStream::stderr(
+        TermColor::None.into(),
+    ))));
+    database.set_stdout_config(Arc::new(DbgWrap::new(TermConfig::default())));
+    database.set_context(unsafe {
+        core::mem::transmute::<&Context<'_>, &'static Context<'static>>(&context)
+    });
+    database.set_file_path(file, Arc::new(PathBuf::from(&path)));
+
+    let result = query(&mut database, file);
+    let _ = std::fs::remove_file(&path);
+
+    result
+}
@@ -1,7 +1,7 @@
 use crate::{
     error::{Locatable, Location, Span},
     strings::{StrInterner, StrT},
-    trees::{CallConv, ItemPath, Sided, Sign},
+    trees::{CallConv, ItemPath, Ref, Sided, Sign},
 };
 #[cfg(feature = "no-std")]
 use alloc::{
@@ -12,8 +12,10 @@ use alloc::{
     vec::Vec,
 };
 use core::fmt::{Debug, Display, Formatter, Result, Write};
+use crunch_proc::{nanopass, Load};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct Item<'ctx> {
     pub decorators: Vec<Decorator<'ctx>>,
     pub attrs: Vec<Attribute>,
@@ -35,8 +37,8 @@ impl<'ctx> Item<'ctx> {
     }
 }
 
-// #[nanopass(file = "src/passes/ast.toml")]
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[nanopass(file = "src/passes/ast.toml")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub enum ItemKind<'ctx> {
     Func {
         generics: Option<Locatable<Vec<Locatable<&'ctx Type<'ctx>>>>>,
@@ -77,6 +79,9 @@ pub enum ItemKind<'ctx> {
 
     ExternBlock(ExternBlock<'ctx>),
     ExternFunc(ExternFunc<'ctx>),
+
+    /// An in-file module, `module <name> ... end`
+    Module(Vec<&'ctx Item<'ctx>>),
 }
 
 impl<'ctx> ItemKind<'ctx> {
@@ -116,19 +121,19 @@ impl<'ctx> ItemKind<'ctx> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct ExtendBlock<'ctx> {
     pub target: Locatable<&'ctx Type<'ctx>>,
     pub extender: Option<Locatable<&'ctx Type<'ctx>>>,
     pub items: Vec<&'ctx Item<'ctx>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct ExternBlock<'ctx> {
     pub items: Vec<&'ctx Item<'ctx>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct ExternFunc<'ctx> {
     pub generics: Option<Locatable<Vec<Locatable<&'ctx Type<'ctx>>>>>,
     pub args: Locatable<Vec<FuncArg<'ctx>>>,
@@ -136,21 +141,21 @@ pub struct ExternFunc<'ctx> {
     pub callconv: CallConv,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum Exposure {
     None(StrT),
     All,
     Items(Vec<(ItemPath, StrT)>),
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum Dest {
     NativeLib,
     Package,
     Relative,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct TypeMember<'ctx> {
     pub decorators: Vec<Decorator<'ctx>>,
     pub attrs: Vec<Attribute>,
@@ -159,7 +164,7 @@ pub struct TypeMember<'ctx> {
     // pub loc: Location,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub enum Variant<'ctx> {
     Unit {
         name: StrT,
@@ -173,7 +178,7 @@ pub enum Variant<'ctx> {
     },
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct Decorator<'ctx> {
     pub name: Locatable<StrT>,
     pub args: Vec<&'ctx Expr<'ctx>>,
@@ -186,7 +191,7 @@ impl<'ctx> Decorator<'ctx> {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum Attribute {
     Const,
     Async,
@@ -206,7 +211,9 @@ impl Display for Attribute {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// An item's visibility, items within a module that are `FileLocal` are only visible to
+/// that module and the modules nested within it
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum Vis {
     FileLocal,
     Package,
@@ -220,10 +227,15 @@ impl Default for Vis {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct FuncArg<'ctx> {
     pub name: StrT,
+    /// The argument's type, variadic arguments have the type of a slice of their elements
     pub ty: Locatable<&'ctx Type<'ctx>>,
+    /// The value given to the argument when a call doesn't supply one, `name: T := value`
+    pub default: Option<&'ctx Expr<'ctx>>,
+    /// Whether the argument collects all remaining positional arguments, `name: ..T`
+    pub variadic: bool,
     pub loc: Location,
 }
 
@@ -239,7 +251,7 @@ impl<'ctx> FuncArg<'ctx> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct Stmt<'ctx> {
     pub kind: StmtKind<'ctx>,
     pub loc: Location,
@@ -257,14 +269,14 @@ impl<'ctx> Stmt<'ctx> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub enum StmtKind<'ctx> {
     VarDecl(VarDecl<'ctx>),
     Item(&'ctx Item<'ctx>),
     Expr(&'ctx Expr<'ctx>),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct VarDecl<'ctx> {
     pub name: StrT,
     pub ty: Locatable<&'ctx Type<'ctx>>,
@@ -274,7 +286,7 @@ pub struct VarDecl<'ctx> {
     // pub loc: Location,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct Expr<'ctx> {
     pub kind: ExprKind<'ctx>,
     pub loc: Location,
@@ -301,7 +313,7 @@ impl<'ctx> Expr<'ctx> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub enum ExprKind<'ctx> {
     If(If<'ctx>),
     Return(Option<&'ctx Expr<'ctx>>),
@@ -311,8 +323,11 @@ pub enum ExprKind<'ctx> {
     Loop(Loop<'ctx>),
     For(For<'ctx>),
     Match(Match<'ctx>),
+    With(With<'ctx>),
     Variable(Locatable<StrT>),
     Literal(Locatable<Literal<'ctx>>),
+    /// A string literal with interpolated values, `"Hello, {name}!"`
+    FormatString(Vec<FormatSegment<'ctx>>),
     UnaryOp(UnaryOp, &'ctx Expr<'ctx>),
     BinaryOp(Sided<BinaryOp, &'ctx Expr<'ctx>>),
     Comparison(Sided<CompOp, &'ctx Expr<'ctx>>),
@@ -320,15 +335,14 @@ pub enum ExprKind<'ctx> {
     Paren(&'ctx Expr<'ctx>),
     Array(Vec<&'ctx Expr<'ctx>>),
     Tuple(Vec<&'ctx Expr<'ctx>>),
-    // TODO: Add range kind (inclusive, exclusive, etc.)
-    Range(&'ctx Expr<'ctx>, &'ctx Expr<'ctx>),
+    Range(Range<'ctx>),
     Index {
         var: &'ctx Expr<'ctx>,
         index: &'ctx Expr<'ctx>,
     },
     FuncCall {
         caller: &'ctx Expr<'ctx>,
-        args: Vec<&'ctx Expr<'ctx>>,
+        args: Vec<CallArg<'ctx>>,
     },
     MemberFuncCall {
         member: &'ctx Expr<'ctx>,
@@ -338,25 +352,26 @@ pub enum ExprKind<'ctx> {
         mutable: bool,
         expr: &'ctx Expr<'ctx>,
     },
+    Deref(&'ctx Expr<'ctx>),
     Cast {
         expr: &'ctx Expr<'ctx>,
         ty: Locatable<&'ctx Type<'ctx>>,
     },
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct If<'ctx> {
     pub clauses: Vec<IfCond<'ctx>>,
     pub else_: Option<Block<'ctx>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct IfCond<'ctx> {
     pub cond: &'ctx Expr<'ctx>,
     pub body: Block<'ctx>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct While<'ctx> {
     pub cond: &'ctx Expr<'ctx>,
     pub body: Block<'ctx>,
@@ -364,13 +379,13 @@ pub struct While<'ctx> {
     pub else_: Option<Block<'ctx>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct Loop<'ctx> {
     pub body: Block<'ctx>,
     pub else_: Option<Block<'ctx>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct For<'ctx> {
     pub var: &'ctx Expr<'ctx>,
     pub cond: &'ctx Expr<'ctx>,
@@ -379,20 +394,94 @@ pub struct For<'ctx> {
     pub else_: Option<Block<'ctx>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A range of values, `start..end`, `start..=end`, `start..`, `..end` or `..`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub struct Range<'ctx> {
+    pub start: Option<&'ctx Expr<'ctx>>,
+    pub end: Option<&'ctx Expr<'ctx>>,
+    pub kind: RangeKind,
+}
+
+/// Whether a range includes its upper bound
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum RangeKind {
+    /// `start..end`, which excludes `end`
+    Exclusive,
+    /// `start..=end`, which includes `end`
+    Inclusive,
+}
+
+impl RangeKind {
+    pub fn is_inclusive(self) -> bool {
+        self == Self::Inclusive
+    }
+}
+
+impl Display for RangeKind {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.write_str(match self {
+            Self::Exclusive => "..",
+            Self::Inclusive => "..=",
+        })
+    }
+}
+
+/// An argument given to a function call
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub enum CallArg<'ctx> {
+    /// An argument matched up by its position, `f(value)`
+    Positional(&'ctx Expr<'ctx>),
+    /// An argument matched up by its name, `f(name := value)`
+    Named {
+        name: Locatable<StrT>,
+        value: &'ctx Expr<'ctx>,
+    },
+    /// A slice spread into the function's variadic argument, `f(...values)`
+    Spread(&'ctx Expr<'ctx>),
+}
+
+/// A scoped block, `with <expr> as <name> ... end`, which calls the exit hook of the bound
+/// value whenever the block is exited
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub struct With<'ctx> {
+    pub value: &'ctx Expr<'ctx>,
+    pub name: Locatable<StrT>,
+    pub body: Block<'ctx>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct Match<'ctx> {
     pub var: &'ctx Expr<'ctx>,
     pub arms: Vec<Arm<'ctx>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct Arm<'ctx> {
     pub bind: Binding<'ctx>,
     pub guard: Option<&'ctx Expr<'ctx>>,
     pub body: Block<'ctx>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub enum FormatSegment<'ctx> {
+    /// Literal text with all escapes already processed
+    Text(Text),
+    /// An interpolated expression and how it should be formatted
+    Value {
+        expr: &'ctx Expr<'ctx>,
+        spec: FormatSpec,
+    },
+}
+
+/// The way an interpolated value is formatted, `{value}` or `{value:?}`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum FormatSpec {
+    Display,
+    Debug,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct Literal<'ctx> {
     pub val: LiteralVal<'ctx>,
     pub ty: &'ctx Type<'ctx>,
@@ -405,7 +494,7 @@ impl<'ctx> Literal<'ctx> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub enum LiteralVal<'ctx> {
     Integer(Integer),
     Bool(bool),
@@ -457,7 +546,7 @@ impl Display for LiteralVal<'_> {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Hash)]
+#[derive(Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 #[repr(transparent)]
 pub struct Text(String);
 
@@ -471,6 +560,11 @@ impl Text {
     pub fn to_bytes(&self) -> Vec<u8> {
         self.0.clone().into_bytes()
     }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
 }
 
 impl Debug for Text {
@@ -494,7 +588,7 @@ impl From<&str> for Text {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 #[repr(transparent)]
 pub struct Rune(u32);
 
@@ -554,7 +648,7 @@ impl Display for Rune {
         write!(f, "{}", &self.as_char())
     }
 }
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct Integer {
     pub sign: Sign,
     pub bits: u128,
@@ -567,7 +661,7 @@ impl Display for Integer {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 #[repr(transparent)]
 pub struct Float(pub u64);
 
@@ -578,7 +672,7 @@ impl Display for Float {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub enum Type<'ctx> {
     Operand(Sided<TypeOp, Locatable<&'ctx Type<'ctx>>>),
     Const(StrT, Locatable<&'ctx Type<'ctx>>),
@@ -620,6 +714,9 @@ pub enum Type<'ctx> {
     Slice {
         element: Locatable<&'ctx Type<'ctx>>,
     },
+    Range {
+        element: Locatable<&'ctx Type<'ctx>>,
+    },
     Tuple(Vec<Locatable<&'ctx Type<'ctx>>>),
     Pointer {
         pointee: Locatable<&'ctx Type<'ctx>>,
@@ -734,9 +831,10 @@ impl<'ctx> Type<'ctx> {
             Self::Unit => "unit".to_string(),
             Self::Absurd => "absurd".to_string(),
             Self::Array { element, length } => {
-                format!("arr[{}; {}]", length, element.to_string(intern))
+                format!("arr[{}; {}]", element.to_string(intern), length)
             }
             Self::Slice { element } => format!("slice[{}]", element.to_string(intern)),
+            Self::Range { element } => format!("range[{}]", element.to_string(intern)),
             Self::Tuple(types) => format!(
                 "tup[{}]",
                 types
@@ -758,7 +856,7 @@ impl<'ctx> Default for Type<'ctx> {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum TypeOp {
     And,
     Or,
@@ -776,7 +874,7 @@ impl Display for TypeOp {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct Binding<'ctx> {
     // TODO: Enum for mutability/referential status?
     pub reference: bool,
@@ -785,15 +883,54 @@ pub struct Binding<'ctx> {
     pub ty: Option<Locatable<&'ctx Type<'ctx>>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub enum Pattern<'ctx> {
     Literal(Literal<'ctx>),
     Ident(StrT),
     ItemPath(ItemPath),
     Wildcard,
+    /// The rest of a tuple or slice, `..`
+    Rest,
+    /// `tup[a, b, c]`
+    Tuple(Vec<Pattern<'ctx>>),
+    /// `arr[first, .., last]`
+    Slice(Vec<Pattern<'ctx>>),
+    /// `Type { field, other: pattern, .. }`
+    Struct {
+        path: ItemPath,
+        fields: Vec<FieldPattern<'ctx>>,
+        rest: bool,
+    },
+    /// `Enum.Variant(a, b)`
+    Variant {
+        path: ItemPath,
+        elements: Vec<Pattern<'ctx>>,
+    },
+    /// `name @ pattern`
+    Bind(StrT, Ref<Pattern<'ctx>>),
+    /// `a | b | c`
+    Or(Vec<Pattern<'ctx>>),
+    /// `start..end`, `start..=end`, `start..` or `..=end`, where both bounds are literals
+    Range {
+        start: Option<Literal<'ctx>>,
+        end: Option<Literal<'ctx>>,
+        kind: RangeKind,
+    },
+}
+
+impl<'ctx> Pattern<'ctx> {
+    pub fn is_rest(&self) -> bool {
+        matches!(self, Self::Rest)
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub struct FieldPattern<'ctx> {
+    pub name: StrT,
+    pub pattern: Pattern<'ctx>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct Block<'ctx> {
     pub stmts: Vec<&'ctx Stmt<'ctx>>,
     pub loc: Location,
@@ -826,7 +963,7 @@ impl<'ctx> Block<'ctx> {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum CompOp {
     Equal,
     NotEqual,
@@ -852,7 +989,7 @@ impl Display for CompOp {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum AssignKind {
     Normal,
     BinaryOp(BinaryOp),
@@ -868,7 +1005,7 @@ impl Display for AssignKind {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum BinaryOp {
     Mult,
     Div,
@@ -881,6 +1018,10 @@ pub enum BinaryOp {
     BitXor,
     Shl,
     Shr,
+    /// Logical and, which only evaluates its right side if its left side is true
+    And,
+    /// Logical or, which only evaluates its right side if its left side is false
+    Or,
 }
 
 impl Display for BinaryOp {
@@ -898,13 +1039,15 @@ impl Display for BinaryOp {
             Self::BitXor => "^",
             Self::Shl => "<<",
             Self::Shr => ">>",
+            Self::And => "and",
+            Self::Or => "or",
         };
 
         f.write_str(pretty)
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum UnaryOp {
     Positive,
     Negative,
@@ -1,24 +1,27 @@
 pub use crate::trees::{
     ast::{
-        BinaryOp, CompOp, Float, Integer, Literal as AstLiteral, LiteralVal as AstLiteralVal, Rune,
-        Text, Type as AstType, Vis,
+        BinaryOp, CompOp, Float, FormatSpec, Integer, Literal as AstLiteral,
+        LiteralVal as AstLiteralVal, RangeKind, Rune, Text, Type as AstType, UnaryOp, Vis,
     },
     ItemPath, Signedness,
 };
 use crate::{
     error::{Locatable, Location, Span},
     strings::{StrInterner, StrT},
-    trees::{CallConv, Sided},
+    trees::{CallConv, Ref, Sided},
 };
 #[cfg(feature = "no-std")]
 use alloc::{
     borrow::ToOwned,
     string::{String, ToString},
+    vec,
     vec::Vec,
 };
 use core::fmt::Debug;
+use crunch_proc::Load;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
 #[repr(transparent)]
 pub struct TypeId(usize);
 
@@ -28,49 +31,149 @@ impl TypeId {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// The id of a user-defined item, allowing [`TypeKind`] to refer to it without holding
+/// the entire item
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+#[repr(transparent)]
+pub struct ItemId(u32);
+
+impl ItemId {
+    pub(crate) const fn new(id: u32) -> Self {
+        Self(id)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub enum Item<'ctx> {
     Function(Function<'ctx>),
-    ExternFunc(ExternFunc),
+    ExternFunc(ExternFunc<'ctx>),
+    Enum(Enum),
+    Trait(Trait<'ctx>),
+    ExtendBlock(ExtendBlock<'ctx>),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct Function<'ctx> {
     // TODO: Make this one single StrT
     pub name: ItemPath,
     pub vis: Vis,
-    pub args: Locatable<Vec<FuncArg>>,
+    /// The generic parameters of the function, which is instantiated for every set of
+    /// types it's called with
+    pub generics: Vec<Generic>,
+    pub args: Locatable<Vec<FuncArg<'ctx>>>,
     pub body: Block<&'ctx Stmt<'ctx>>,
     pub ret: TypeId,
     pub loc: Location,
     pub sig: Location,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-pub struct FuncArg {
+impl<'ctx> Function<'ctx> {
+    /// Returns `true` if the function takes any generic parameters
+    pub fn is_generic(&self) -> bool {
+        !self.generics.is_empty()
+    }
+}
+
+/// A generic type parameter, `T` or `T: Trait & Other`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Generic {
+    /// The id used by [`TypeKind::Generic`] to refer to the parameter
+    pub id: ItemId,
+    pub name: StrT,
+    /// The traits that every type given to the parameter must implement
+    pub bounds: Vec<ItemId>,
+    pub loc: Location,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub struct FuncArg<'ctx> {
     pub name: Var,
     pub kind: TypeId,
+    /// The value given to the argument when a call doesn't supply one
+    pub default: Option<&'ctx Expr<'ctx>>,
+    /// Whether the argument collects all remaining positional arguments into a slice
+    pub variadic: bool,
     pub loc: Location,
 }
 
-impl FuncArg {
+impl<'ctx> FuncArg<'ctx> {
     pub const fn location(&self) -> Location {
         self.loc
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct ExternFunc {
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub struct ExternFunc<'ctx> {
     // TODO: Make this one single StrT
     pub name: ItemPath,
     pub vis: Vis,
-    pub args: Locatable<Vec<FuncArg>>,
+    pub args: Locatable<Vec<FuncArg<'ctx>>>,
     pub ret: TypeId,
     pub callconv: CallConv,
     pub loc: Location,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A sum type, where every value is one of its variants
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Enum {
+    pub id: ItemId,
+    pub name: ItemPath,
+    pub vis: Vis,
+    /// The generic parameters used by the payloads of the enum's variants
+    pub generics: Vec<Generic>,
+    pub variants: Vec<Variant>,
+    pub loc: Location,
+}
+
+impl Enum {
+    /// Gets the variant with the given name along with its discriminant
+    pub fn variant(&self, name: StrT) -> Option<(usize, &Variant)> {
+        self.variants
+            .iter()
+            .enumerate()
+            .find(|(_, variant)| variant.name == name)
+    }
+}
+
+/// A set of methods that types can implement, which only holds the methods' signatures
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub struct Trait<'ctx> {
+    pub id: ItemId,
+    pub name: ItemPath,
+    pub vis: Vis,
+    /// The trait's methods, where `Self` has an unknown type
+    pub methods: Vec<Function<'ctx>>,
+    pub loc: Location,
+}
+
+impl<'ctx> Trait<'ctx> {
+    pub fn method(&self, name: StrT) -> Option<&Function<'ctx>> {
+        self.methods
+            .iter()
+            .find(|method| method.name.last() == Some(&name))
+    }
+}
+
+/// Methods given to a type by an `extend` block, either its own or an implementation
+/// of a trait
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub struct ExtendBlock<'ctx> {
+    pub target: TypeId,
+    /// The trait being implemented, `None` when the methods belong to the type itself
+    pub extender: Option<ItemId>,
+    pub methods: Vec<Function<'ctx>>,
+    pub loc: Location,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Variant {
+    pub name: StrT,
+    /// The types of the variant's payload, empty for unit variants
+    pub elements: Vec<TypeId>,
+    pub loc: Location,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub enum Stmt<'ctx> {
     Item(&'ctx Item<'ctx>),
     Expr(&'ctx Expr<'ctx>),
@@ -90,7 +193,7 @@ impl<'ctx> From<&'ctx Expr<'ctx>> for Stmt<'ctx> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct Expr<'ctx> {
     pub kind: ExprKind<'ctx>,
     pub loc: Location,
@@ -102,26 +205,110 @@ impl<'ctx> Expr<'ctx> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub enum ExprKind<'ctx> {
     Match(Match<'ctx>),
     Scope(Block<&'ctx Stmt<'ctx>>),
-    Loop(Block<&'ctx Stmt<'ctx>>),
+    Loop(Loop<'ctx>),
     Return(Return<'ctx>),
     Continue,
     Break(Break<'ctx>),
     FnCall(FuncCall<'ctx>),
+    MethodCall(MethodCall<'ctx>),
     Literal(Literal),
+    FormatString(Vec<FormatSegment<'ctx>>),
     Comparison(Sided<CompOp, &'ctx Expr<'ctx>>),
     Variable(Var, TypeId),
-    Assign(Var, &'ctx Expr<'ctx>),
+    /// Writes a value to a place, `place := value`
+    Assign {
+        place: Place<'ctx>,
+        /// The operator of compound assignments, `place += value`, which is applied to
+        /// the place's current value so that the place is only evaluated once
+        op: Option<BinaryOp>,
+        value: &'ctx Expr<'ctx>,
+    },
     BinOp(Sided<BinaryOp, &'ctx Expr<'ctx>>),
+    /// A prefix operator applied to a value, `-value` or `!value`
+    UnaryOp(UnaryOp, &'ctx Expr<'ctx>),
     Cast(Cast<'ctx>),
     Reference(Reference<'ctx>),
-    Index { var: Var, index: &'ctx Expr<'ctx> },
+    /// Reads the value behind a reference or pointer, `*reference`
+    Deref(&'ctx Expr<'ctx>),
+    Index {
+        var: Var,
+        index: &'ctx Expr<'ctx>,
+    },
+    /// The number of elements within an array or slice, used by `for` loops over them
+    Length(Var),
+    Range(Range<'ctx>),
+    Variant(VariantCtor<'ctx>),
+    /// An array built from the given elements, `arr[a, b, c]`
+    Array {
+        elements: Vec<&'ctx Expr<'ctx>>,
+        /// The type of the array's elements, which is inferred during type checking
+        element: TypeId,
+    },
+    /// A tuple built from the given elements, `tup[a, b, c]`
+    Tuple(Vec<&'ctx Expr<'ctx>>),
+    /// Fetches one of the fields of a tuple, `tuple.0`
+    TupleField {
+        tuple: &'ctx Expr<'ctx>,
+        index: u64,
+    },
+}
+
+/// A location that can be assigned to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub struct Place<'ctx> {
+    pub kind: PlaceKind<'ctx>,
+    pub loc: Location,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+impl<'ctx> Place<'ctx> {
+    pub const fn new(kind: PlaceKind<'ctx>, loc: Location) -> Self {
+        Self { kind, loc }
+    }
+
+    pub const fn location(&self) -> Location {
+        self.loc
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub enum PlaceKind<'ctx> {
+    /// A variable, `variable`
+    Variable(Var),
+    /// One of the fields of a tuple, `place.0`
+    Field { base: Ref<Place<'ctx>>, index: u64 },
+    /// An element of an array or slice, `place[index]`
+    Index {
+        base: Ref<Place<'ctx>>,
+        index: &'ctx Expr<'ctx>,
+    },
+    /// The value behind a reference or pointer, `*reference`
+    Deref(&'ctx Expr<'ctx>),
+}
+
+/// The construction of an enum's variant, `Enum.Variant` or `Enum.Variant(payload)`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub struct VariantCtor<'ctx> {
+    pub enum_id: ItemId,
+    pub variant: StrT,
+    pub args: Vec<&'ctx Expr<'ctx>>,
+    /// The type of the constructed enum, which is filled in with the enum's generics
+    /// during type checking
+    pub ty: TypeId,
+}
+
+/// A range of values, where either bound may be missing for unbounded ranges
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub struct Range<'ctx> {
+    pub start: Option<&'ctx Expr<'ctx>>,
+    pub end: Option<&'ctx Expr<'ctx>>,
+    pub kind: RangeKind,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
 pub enum Var {
     User(StrT),
     // TODO: Make this a u32 so they're the same size
@@ -137,7 +324,7 @@ impl Var {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct VarDecl<'ctx> {
     pub name: Var,
     pub value: &'ctx Expr<'ctx>,
@@ -146,13 +333,163 @@ pub struct VarDecl<'ctx> {
     pub loc: Location,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct FuncCall<'ctx> {
     pub func: ItemPath,
-    pub args: Vec<&'ctx Expr<'ctx>>,
+    /// The types given to each of the function's generic parameters, which are inferred
+    /// during type checking
+    pub generics: Vec<TypeId>,
+    pub args: Vec<CallArg<'ctx>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+impl<'ctx> FuncCall<'ctx> {
+    /// Matches the call's arguments up with the arguments of the function being called,
+    /// filling in default values and collecting the values of variadic arguments
+    pub fn match_args(&self, params: &[FuncArg<'ctx>]) -> Result<Vec<ArgValue<'ctx>>, ArgMismatch> {
+        match_call_args(&self.args, params)
+    }
+}
+
+/// A call to a method, `receiver.method(args)`, which is resolved once the type of the
+/// receiver is known
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub struct MethodCall<'ctx> {
+    pub receiver: &'ctx Expr<'ctx>,
+    /// The type of the receiver, inferred during type checking
+    pub receiver_ty: TypeId,
+    pub method: Locatable<StrT>,
+    pub args: Vec<CallArg<'ctx>>,
+    /// The trait the method must come from, which is only given to the calls that the
+    /// compiler generates, like the exit hooks of `with` blocks
+    pub trait_: Option<ItemId>,
+}
+
+impl<'ctx> MethodCall<'ctx> {
+    /// Matches the call's arguments up with the arguments of the method being called,
+    /// where `params` doesn't include the method's receiver
+    pub fn match_args(&self, params: &[FuncArg<'ctx>]) -> Result<Vec<ArgValue<'ctx>>, ArgMismatch> {
+        match_call_args(&self.args, params)
+    }
+}
+
+fn match_call_args<'ctx>(
+    args: &[CallArg<'ctx>],
+    params: &[FuncArg<'ctx>],
+) -> Result<Vec<ArgValue<'ctx>>, ArgMismatch> {
+    let variadic = params.iter().position(|param| param.variadic);
+    let mut values: Vec<Option<ArgValue<'ctx>>> = vec![None; params.len()];
+    let mut collected = Vec::new();
+    let mut position = 0;
+
+    for arg in args.iter() {
+        match *arg {
+            CallArg::Positional(value) if Some(position) == variadic => {
+                if values[position].is_some() {
+                    return Err(ArgMismatch::Duplicate(
+                        params[position].name,
+                        value.location(),
+                    ));
+                }
+
+                collected.push(value);
+            }
+
+            CallArg::Positional(value) => {
+                if position >= params.len() {
+                    return Err(ArgMismatch::TooMany {
+                        expected: params.len(),
+                        received: args.len(),
+                    });
+                }
+
+                values[position] = Some(ArgValue::Value(value));
+                position += 1;
+            }
+
+            CallArg::Named { name, value } => {
+                let index = params
+                    .iter()
+                    .position(|param| param.name == Var::User(*name))
+                    .ok_or_else(|| ArgMismatch::Unknown(*name, name.location()))?;
+
+                if values[index].is_some() || (Some(index) == variadic && !collected.is_empty()) {
+                    return Err(ArgMismatch::Duplicate(params[index].name, name.location()));
+                }
+
+                values[index] = Some(ArgValue::Value(value));
+            }
+
+            CallArg::Spread(value) => {
+                let index =
+                    variadic.ok_or_else(|| ArgMismatch::UnexpectedSpread(value.location()))?;
+
+                if values[index].is_some() || !collected.is_empty() {
+                    return Err(ArgMismatch::Duplicate(params[index].name, value.location()));
+                }
+
+                values[index] = Some(ArgValue::Value(value));
+            }
+        }
+    }
+
+    params
+        .iter()
+        .zip(values)
+        .enumerate()
+        .map(|(index, (param, value))| match value {
+            Some(value) => Ok(value),
+            None if Some(index) == variadic => {
+                Ok(ArgValue::Variadic(core::mem::take(&mut collected)))
+            }
+            None => param
+                .default
+                .map(ArgValue::Default)
+                .ok_or(ArgMismatch::Missing(param.name)),
+        })
+        .collect()
+}
+
+/// An argument given to a function call
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub enum CallArg<'ctx> {
+    /// An argument matched up by its position
+    Positional(&'ctx Expr<'ctx>),
+    /// An argument matched up by its name
+    Named {
+        name: Locatable<StrT>,
+        value: &'ctx Expr<'ctx>,
+    },
+    /// A slice spread into the function's variadic argument
+    Spread(&'ctx Expr<'ctx>),
+}
+
+/// The value given to one of a function's arguments by a call
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub enum ArgValue<'ctx> {
+    /// A value given by the call
+    Value(&'ctx Expr<'ctx>),
+    /// The argument's default value
+    Default(&'ctx Expr<'ctx>),
+    /// The positional values collected by a variadic argument
+    Variadic(Vec<&'ctx Expr<'ctx>>),
+}
+
+/// The ways that a call's arguments can fail to match up with a function's arguments
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum ArgMismatch {
+    /// More positional arguments were given than the function takes
+    TooMany { expected: usize, received: usize },
+    /// The argument wasn't given a value and has no default
+    Missing(Var),
+    /// The argument was given more than one value
+    Duplicate(Var, Location),
+    /// The function has no argument with the given name
+    Unknown(StrT, Location),
+    /// A slice was spread into a function without a variadic argument
+    UnexpectedSpread(Location),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct Match<'ctx> {
     pub cond: &'ctx Expr<'ctx>,
     // TODO: Arena match arms
@@ -160,7 +497,7 @@ pub struct Match<'ctx> {
     pub ty: TypeId,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct MatchArm<'ctx> {
     // TODO: Arena & dedup bindings
     pub bind: Binding,
@@ -169,7 +506,7 @@ pub struct MatchArm<'ctx> {
     pub ty: TypeId,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct Binding {
     // TODO: Enum for mutability/referential status?
     pub reference: bool,
@@ -179,25 +516,114 @@ pub struct Binding {
 }
 
 // TODO: Arena & dedup patterns
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum Pattern {
     Literal(Literal),
     Ident(StrT),
     ItemPath(ItemPath),
     Wildcard,
+    /// The rest of a tuple or slice, `..`
+    Rest,
+    Tuple(Vec<Pattern>),
+    Slice(Vec<Pattern>),
+    Struct {
+        path: ItemPath,
+        fields: Vec<(StrT, Pattern)>,
+        rest: bool,
+    },
+    Variant {
+        path: ItemPath,
+        elements: Vec<Pattern>,
+    },
+    Bind(StrT, Ref<Pattern>),
+    Or(Vec<Pattern>),
+    /// A range of literals, where either bound may be missing
+    Range {
+        start: Option<Literal>,
+        end: Option<Literal>,
+        kind: RangeKind,
+    },
+}
+
+impl Pattern {
+    pub fn is_rest(&self) -> bool {
+        matches!(self, Self::Rest)
+    }
+
+    /// Returns `true` if the pattern can never fail to match
+    pub fn is_irrefutable(&self) -> bool {
+        match self {
+            Self::Ident(_) | Self::Wildcard | Self::Rest => true,
+            Self::Bind(_, pattern) => pattern.is_irrefutable(),
+            Self::Tuple(elements) => elements.iter().all(Self::is_irrefutable),
+            Self::Or(alternatives) => alternatives.iter().any(Self::is_irrefutable),
+            Self::Literal(_)
+            | Self::ItemPath(_)
+            | Self::Slice(_)
+            | Self::Struct { .. }
+            | Self::Variant { .. }
+            | Self::Range { .. } => false,
+        }
+    }
+
+    /// Collects every variable bound by the pattern into `buf`
+    pub fn bindings(&self, buf: &mut Vec<StrT>) {
+        match self {
+            &Self::Ident(ident) => buf.push(ident),
+            Self::Bind(ident, pattern) => {
+                buf.push(*ident);
+                pattern.bindings(buf);
+            }
+            Self::Tuple(elements) | Self::Slice(elements) | Self::Variant { elements, .. } => {
+                for element in elements {
+                    element.bindings(buf);
+                }
+            }
+            Self::Struct { fields, .. } => {
+                for (_, field) in fields {
+                    field.bindings(buf);
+                }
+            }
+            // Every alternative binds the same variables, so only the first needs to be visited
+            Self::Or(alternatives) => {
+                if let Some(first) = alternatives.first() {
+                    first.bindings(buf);
+                }
+            }
+            Self::Literal(_)
+            | Self::ItemPath(_)
+            | Self::Wildcard
+            | Self::Rest
+            | Self::Range { .. } => {}
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct Return<'ctx> {
     pub val: Option<&'ctx Expr<'ctx>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A loop, which runs until it's broken out of or until its condition is false
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub struct Loop<'ctx> {
+    /// Checked before every iteration, `while` and `for` loops exit once it's false
+    pub cond: Option<&'ctx Expr<'ctx>>,
+    pub body: Block<&'ctx Stmt<'ctx>>,
+    /// Run once the loop's condition is false, giving the loop its value
+    pub then: Option<Block<&'ctx Stmt<'ctx>>>,
+    /// Run after any `break` out of the loop
+    pub else_: Option<Block<&'ctx Stmt<'ctx>>>,
+    /// The type of the values the loop is broken with
+    pub ty: TypeId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct Break<'ctx> {
     pub val: Option<&'ctx Expr<'ctx>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Load, Serialize)]
 pub struct Block<T> {
     pub block: Vec<T>,
     pub loc: Location,
@@ -287,7 +713,7 @@ impl<T> Extend<T> for Block<T> {
 }
 
 /// A type
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct Type {
     /// The kind of type this type is
     pub kind: TypeKind,
@@ -326,7 +752,7 @@ impl Type {
 }
 
 /// The type that a type actually is
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum TypeKind {
     /// An unknown type
     Unknown,
@@ -357,6 +783,13 @@ pub enum TypeKind {
         /// The type of the slice's elements
         element: TypeId,
     },
+    /// A tuple type, tup[_, _]
+    Tuple(Vec<TypeId>),
+    /// A range type, range[_]
+    Range {
+        /// The type of the range's bounds
+        element: TypeId,
+    },
     /// A reference type, &_ or &mut _
     Reference {
         /// The type the reference points to
@@ -371,6 +804,28 @@ pub enum TypeKind {
         /// Whether the pointer is mutable or not
         mutable: bool,
     },
+    /// A user-defined enum along with the types given to its generic parameters
+    Enum { id: ItemId, generics: Vec<TypeId> },
+    /// A generic type parameter, which is replaced by the type it's given when the item
+    /// it belongs to is instantiated
+    Generic { id: ItemId, name: StrT },
+    /// A unicode scalar value, rune
+    Rune,
+    /// A floating point number of the given width, f32 or f64
+    Float { width: u16 },
+    /// A function pointer, fn(_, _) -> _
+    Func { params: Vec<TypeId>, ret: TypeId },
+    /// Any type that implements every one of the given traits along with their names,
+    /// either a trait used as a type or a trait object, type { Trait, Other }
+    Trait(Vec<(ItemId, ItemPath)>),
+    /// A type that could be any one of the given types, _ | _
+    Union(Vec<TypeId>),
+    /// A type that is all of the given types at once, _ & _
+    Intersection(Vec<TypeId>),
+    /// Any type other than the given one, !_
+    Not(TypeId),
+    /// A value known at compile time, const N: _
+    Const { name: StrT, ty: TypeId },
     /// A type with the type of another type
     Variable(TypeId),
 }
@@ -393,21 +848,76 @@ impl TypeKind {
     pub fn is_slice(&self) -> bool {
         matches!(self, Self::Slice { .. })
     }
+
+    pub fn is_tuple(&self) -> bool {
+        matches!(self, Self::Tuple(..))
+    }
+
+    /// Returns every type directly contained within the current one
+    pub fn inner_types(&self) -> Vec<TypeId> {
+        match self {
+            &Self::Array { element, .. }
+            | &Self::Slice { element }
+            | &Self::Range { element }
+            | &Self::Reference {
+                referee: element, ..
+            }
+            | &Self::Pointer {
+                pointee: element, ..
+            }
+            | &Self::Not(element)
+            | &Self::Const { ty: element, .. }
+            | &Self::Variable(element) => vec![element],
+
+            Self::Tuple(types)
+            | Self::Union(types)
+            | Self::Intersection(types)
+            | Self::Enum {
+                generics: types, ..
+            } => types.clone(),
+
+            Self::Func { params, ret } => {
+                let mut types = params.clone();
+                types.push(*ret);
+                types
+            }
+
+            Self::Unknown
+            | Self::Integer { .. }
+            | Self::String
+            | Self::Bool
+            | Self::Unit
+            | Self::Absurd
+            | Self::Generic { .. }
+            | Self::Rune
+            | Self::Float { .. }
+            | Self::Trait(_) => Vec::new(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct Cast<'ctx> {
     pub casted: &'ctx Expr<'ctx>,
     pub ty: TypeId,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
+pub enum FormatSegment<'ctx> {
+    Text(Text),
+    Value {
+        expr: &'ctx Expr<'ctx>,
+        spec: FormatSpec,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Load, Serialize)]
 pub struct Reference<'ctx> {
     pub mutable: bool,
     pub reference: &'ctx Expr<'ctx>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct Literal {
     pub val: LiteralVal,
     pub ty: TypeId,
@@ -421,7 +931,7 @@ impl Literal {
 }
 
 // TODO: Arena & dedup literals
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum LiteralVal {
     Integer(Integer),
     Bool(bool),
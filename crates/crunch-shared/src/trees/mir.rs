@@ -3,9 +3,9 @@ use crate::{
     error::{Locatable, MirError, MirResult},
     strings::{StrInterner, StrT},
     trees::{hir::Var as HirVar, CallConv, ItemPath, Ref, Sign},
-    utils::HashMap,
+    utils::{HashMap, HashSet, Hasher},
 };
-use alloc::{string::ToString, vec::Vec};
+use alloc::{string::ToString, vec, vec::Vec};
 use core::iter;
 use derive_more::Display;
 use pretty::{BoxAllocator, DocAllocator, DocBuilder};
@@ -34,6 +34,16 @@ impl Mir {
         self.external_functions.iter()
     }
 
+    /// A mutable iterator over all functions
+    pub fn functions_mut(&mut self) -> impl Iterator<Item = &mut Function> {
+        self.functions.iter_mut()
+    }
+
+    /// A mutable iterator over all external functions
+    pub fn external_functions_mut(&mut self) -> impl Iterator<Item = &mut ExternFunc> {
+        self.external_functions.iter_mut()
+    }
+
     pub fn to_doc<'a, D>(
         &self,
         alloc: &'a D,
@@ -92,6 +102,42 @@ impl Function {
         self.blocks.iter().map(|(id, block)| (*id, block))
     }
 
+    /// The blocks reachable from the function's entry in reverse postorder, where every
+    /// block comes after all of its predecessors other than the ones jumping back to it
+    /// from within a loop
+    pub fn reverse_postorder(&self) -> Vec<BlockId> {
+        let entry = BlockId::new(0);
+        let successors = |block: BlockId| {
+            self.blocks
+                .get(&block)
+                .and_then(|block| block.terminator.as_ref())
+                .map_or_else(Vec::new, Terminator::successors)
+        };
+
+        let mut visited = HashSet::with_hasher(Hasher::default());
+        visited.insert(entry);
+
+        let mut postorder = Vec::with_capacity(self.blocks.len());
+        let mut stack = vec![(entry, successors(entry))];
+        while let Some((block, unvisited)) = stack.last_mut() {
+            match unvisited.pop() {
+                Some(successor) => {
+                    if self.blocks.contains_key(&successor) && visited.insert(successor) {
+                        stack.push((successor, successors(successor)));
+                    }
+                }
+
+                None => {
+                    postorder.push(*block);
+                    stack.pop();
+                }
+            }
+        }
+
+        postorder.reverse();
+        postorder
+    }
+
     pub fn to_doc<'a, D>(
         &self,
         alloc: &'a D,
@@ -456,6 +502,20 @@ pub enum Terminator {
 }
 
 impl Terminator {
+    /// The blocks the terminator can jump to
+    pub fn successors(&self) -> Vec<BlockId> {
+        match self {
+            Self::Return(_) | Self::Unreachable => Vec::new(),
+            &Self::Jump(block, _) => vec![block],
+            &Self::Branch { truthy, falsy, .. } => vec![truthy, falsy],
+            Self::Switch { cases, default, .. } => cases
+                .iter()
+                .map(|case| case.block)
+                .chain(iter::once(default.block))
+                .collect(),
+        }
+    }
+
     pub fn to_doc<'a, D>(
         &self,
         alloc: &'a D,
@@ -622,6 +682,8 @@ pub enum Instruction {
     Assign(Assign),
     /// A standalone function call, should return void
     Call(FnCall),
+    /// Writes a value to the memory behind a pointer
+    Store { pointer: VarId, value: VarId },
 }
 
 impl Instruction {
@@ -641,6 +703,10 @@ impl Instruction {
         match self {
             Self::Assign(Assign { val, .. }) => val.val.variable_usages(buf),
             Self::Call(FnCall { args, .. }) => buf.extend(args.iter().copied()),
+            Self::Store { pointer, value } => {
+                buf.push(*pointer);
+                buf.push(*value);
+            }
         }
     }
 
@@ -657,6 +723,13 @@ impl Instruction {
         match self {
             Self::Assign(assign) => assign.to_doc(alloc, mir, interner),
             Self::Call(call) => call.to_doc(alloc, mir, interner),
+            Self::Store { pointer, value } => alloc
+                .text("store")
+                .append(alloc.space())
+                .append(pointer.to_doc(alloc, interner))
+                .append(alloc.text(","))
+                .append(alloc.space())
+                .append(value.to_doc(alloc, interner)),
         }
     }
 }
@@ -838,8 +911,35 @@ pub enum Value {
     Mul(VarId, VarId),
     /// The division of two values
     Div(VarId, VarId),
+    /// The remainder of the division of two values
+    Rem(VarId, VarId),
+    /// The bitwise and of two values
+    BitAnd(VarId, VarId),
+    /// The bitwise or of two values
+    BitOr(VarId, VarId),
+    /// The bitwise xor of two values
+    BitXor(VarId, VarId),
+    /// Shifts the left value left by the right value
+    Shl(VarId, VarId),
+    /// Shifts the left value right by the right value, arithmetically for signed integers
+    /// and logically for unsigned ones
+    Shr(VarId, VarId),
     /// Returns a `true` boolean if the values are equal
     Eq(VarId, VarId),
+    /// Returns a `true` boolean if the values are not equal
+    Neq(VarId, VarId),
+    /// Returns a `true` boolean if the left value is less than the right one
+    Less(VarId, VarId),
+    /// Returns a `true` boolean if the left value is less than or equal to the right one
+    LessEq(VarId, VarId),
+    /// Returns a `true` boolean if the left value is greater than the right one
+    Greater(VarId, VarId),
+    /// Returns a `true` boolean if the left value is greater than or equal to the right one
+    GreaterEq(VarId, VarId),
+    /// The arithmetic negation of a signed integer
+    Neg(VarId),
+    /// The logical not of a boolean or the bitwise not of an integer
+    Not(VarId),
     /// Fetches a pointer to a variable, returning a `Pointer` value
     GetPointer {
         /// The variable being pointed to
@@ -851,25 +951,79 @@ pub enum Value {
     },
     /// Casts a variable to a different type
     Cast(VarId, Type),
+    /// Extracts the element at a constant index from an array or tuple
+    Extract(VarId, u64),
+    /// Builds an array or tuple out of the given elements
+    Aggregate(Vec<VarId>),
+    /// Fetches the element at a runtime index of an array or slice, the index must
+    /// already have been bounds checked
+    Index(VarId, VarId),
+    /// Reserves space on the stack for a value of the given type, returning a pointer to it
+    Alloca(Type),
+    /// Reads the value behind a pointer
+    Load(VarId),
+    /// Gets a pointer to the element at a constant index of the array or tuple behind
+    /// a pointer
+    FieldPtr(VarId, u64),
+    /// Gets a pointer to the element at a runtime index of the array or slice behind
+    /// a pointer, the index must already have been bounds checked
+    IndexPtr(VarId, VarId),
+    /// Creates an enum holding the variant with the given discriminant and payload
+    Variant {
+        discriminant: u64,
+        payload: Vec<VarId>,
+    },
+    /// Fetches the discriminant of an enum
+    Discriminant(VarId),
+    /// Extracts an element from the payload of an enum, the enum must hold the given variant
+    Payload {
+        var: VarId,
+        discriminant: u64,
+        index: u64,
+    },
 }
 
 impl Value {
     /// Fills a vector with the ids of all variables used within the current value
     pub fn variable_usages(&self, buf: &mut Vec<VarId>) {
         match self {
-            Self::Call(FnCall { args, .. }) => buf.extend(args.iter().copied()),
+            Self::Call(FnCall { args, .. })
+            | Self::Variant { payload: args, .. }
+            | Self::Aggregate(args) => buf.extend(args.iter().copied()),
             Self::Add(lhs, rhs)
             | Self::Sub(lhs, rhs)
             | Self::Mul(lhs, rhs)
             | Self::Div(lhs, rhs)
-            | Self::Eq(lhs, rhs) => {
+            | Self::Rem(lhs, rhs)
+            | Self::BitAnd(lhs, rhs)
+            | Self::BitOr(lhs, rhs)
+            | Self::BitXor(lhs, rhs)
+            | Self::Shl(lhs, rhs)
+            | Self::Shr(lhs, rhs)
+            | Self::Eq(lhs, rhs)
+            | Self::Neq(lhs, rhs)
+            | Self::Less(lhs, rhs)
+            | Self::LessEq(lhs, rhs)
+            | Self::Greater(lhs, rhs)
+            | Self::GreaterEq(lhs, rhs)
+            | Self::Index(lhs, rhs)
+            | Self::IndexPtr(lhs, rhs) => {
                 buf.push(*lhs);
                 buf.push(*rhs);
             }
-            Self::Variable(var) | Self::GetPointer { var, .. } | Self::Cast(var, _) => {
+            Self::Variable(var)
+            | Self::GetPointer { var, .. }
+            | Self::Cast(var, _)
+            | Self::Extract(var, _)
+            | Self::Neg(var)
+            | Self::Not(var)
+            | Self::Load(var)
+            | Self::FieldPtr(var, _)
+            | Self::Discriminant(var)
+            | Self::Payload { var, .. } => {
                 buf.push(*var);
             }
-            Self::Const(_) => {}
+            Self::Const(_) | Self::Alloca(_) => {}
         }
     }
 
@@ -920,6 +1074,54 @@ impl Value {
                 .append(alloc.space())
                 .append(rhs.to_doc(alloc, interner)),
 
+            Self::Rem(lhs, rhs) => alloc
+                .text("rem")
+                .append(alloc.space())
+                .append(lhs.to_doc(alloc, interner))
+                .append(alloc.text(","))
+                .append(alloc.space())
+                .append(rhs.to_doc(alloc, interner)),
+
+            Self::BitAnd(lhs, rhs) => alloc
+                .text("and")
+                .append(alloc.space())
+                .append(lhs.to_doc(alloc, interner))
+                .append(alloc.text(","))
+                .append(alloc.space())
+                .append(rhs.to_doc(alloc, interner)),
+
+            Self::BitOr(lhs, rhs) => alloc
+                .text("or")
+                .append(alloc.space())
+                .append(lhs.to_doc(alloc, interner))
+                .append(alloc.text(","))
+                .append(alloc.space())
+                .append(rhs.to_doc(alloc, interner)),
+
+            Self::BitXor(lhs, rhs) => alloc
+                .text("xor")
+                .append(alloc.space())
+                .append(lhs.to_doc(alloc, interner))
+                .append(alloc.text(","))
+                .append(alloc.space())
+                .append(rhs.to_doc(alloc, interner)),
+
+            Self::Shl(lhs, rhs) => alloc
+                .text("shl")
+                .append(alloc.space())
+                .append(lhs.to_doc(alloc, interner))
+                .append(alloc.text(","))
+                .append(alloc.space())
+                .append(rhs.to_doc(alloc, interner)),
+
+            Self::Shr(lhs, rhs) => alloc
+                .text("shr")
+                .append(alloc.space())
+                .append(lhs.to_doc(alloc, interner))
+                .append(alloc.text(","))
+                .append(alloc.space())
+                .append(rhs.to_doc(alloc, interner)),
+
             Self::Eq(lhs, rhs) => alloc
                 .text("eq")
                 .append(alloc.space())
@@ -928,6 +1130,56 @@ impl Value {
                 .append(alloc.space())
                 .append(rhs.to_doc(alloc, interner)),
 
+            Self::Neq(lhs, rhs) => alloc
+                .text("neq")
+                .append(alloc.space())
+                .append(lhs.to_doc(alloc, interner))
+                .append(alloc.text(","))
+                .append(alloc.space())
+                .append(rhs.to_doc(alloc, interner)),
+
+            Self::Less(lhs, rhs) => alloc
+                .text("lt")
+                .append(alloc.space())
+                .append(lhs.to_doc(alloc, interner))
+                .append(alloc.text(","))
+                .append(alloc.space())
+                .append(rhs.to_doc(alloc, interner)),
+
+            Self::LessEq(lhs, rhs) => alloc
+                .text("le")
+                .append(alloc.space())
+                .append(lhs.to_doc(alloc, interner))
+                .append(alloc.text(","))
+                .append(alloc.space())
+                .append(rhs.to_doc(alloc, interner)),
+
+            Self::Greater(lhs, rhs) => alloc
+                .text("gt")
+                .append(alloc.space())
+                .append(lhs.to_doc(alloc, interner))
+                .append(alloc.text(","))
+                .append(alloc.space())
+                .append(rhs.to_doc(alloc, interner)),
+
+            Self::GreaterEq(lhs, rhs) => alloc
+                .text("ge")
+                .append(alloc.space())
+                .append(lhs.to_doc(alloc, interner))
+                .append(alloc.text(","))
+                .append(alloc.space())
+                .append(rhs.to_doc(alloc, interner)),
+
+            Self::Neg(var) => alloc
+                .text("neg")
+                .append(alloc.space())
+                .append(var.to_doc(alloc, interner)),
+
+            Self::Not(var) => alloc
+                .text("not")
+                .append(alloc.space())
+                .append(var.to_doc(alloc, interner)),
+
             Self::GetPointer {
                 var,
                 mutable,
@@ -955,6 +1207,100 @@ impl Value {
                 .append(alloc.text("as"))
                 .append(alloc.space())
                 .append(ty.to_doc(alloc, mir, interner)),
+
+            Self::Extract(var, index) => alloc
+                .text("extract")
+                .append(alloc.space())
+                .append(var.to_doc(alloc, interner))
+                .append(alloc.text(","))
+                .append(alloc.space())
+                .append(alloc.text(index.to_string())),
+
+            Self::Aggregate(elements) => alloc
+                .text("aggregate")
+                .append(alloc.space())
+                .append(alloc.text("("))
+                .append(
+                    alloc
+                        .intersperse(
+                            elements.iter().map(|var| var.to_doc(alloc, interner)),
+                            alloc.text(",").append(alloc.space()),
+                        )
+                        .group(),
+                )
+                .append(alloc.text(")")),
+
+            Self::Index(var, index) => alloc
+                .text("index")
+                .append(alloc.space())
+                .append(var.to_doc(alloc, interner))
+                .append(alloc.text(","))
+                .append(alloc.space())
+                .append(index.to_doc(alloc, interner)),
+
+            Self::Alloca(ty) => alloc
+                .text("alloca")
+                .append(alloc.space())
+                .append(ty.to_doc(alloc, mir, interner)),
+
+            Self::Load(pointer) => alloc
+                .text("load")
+                .append(alloc.space())
+                .append(pointer.to_doc(alloc, interner)),
+
+            Self::FieldPtr(pointer, index) => alloc
+                .text("fieldptr")
+                .append(alloc.space())
+                .append(pointer.to_doc(alloc, interner))
+                .append(alloc.text(","))
+                .append(alloc.space())
+                .append(alloc.text(index.to_string())),
+
+            Self::IndexPtr(pointer, index) => alloc
+                .text("indexptr")
+                .append(alloc.space())
+                .append(pointer.to_doc(alloc, interner))
+                .append(alloc.text(","))
+                .append(alloc.space())
+                .append(index.to_doc(alloc, interner)),
+
+            Self::Variant {
+                discriminant,
+                payload,
+            } => alloc
+                .text("variant")
+                .append(alloc.space())
+                .append(alloc.text(discriminant.to_string()))
+                .append(alloc.space())
+                .append(alloc.text("("))
+                .append(
+                    alloc
+                        .intersperse(
+                            payload.iter().map(|var| var.to_doc(alloc, interner)),
+                            alloc.text(",").append(alloc.space()),
+                        )
+                        .group(),
+                )
+                .append(alloc.text(")")),
+
+            Self::Discriminant(var) => alloc
+                .text("discriminant")
+                .append(alloc.space())
+                .append(var.to_doc(alloc, interner)),
+
+            Self::Payload {
+                var,
+                discriminant,
+                index,
+            } => alloc
+                .text("payload")
+                .append(alloc.space())
+                .append(var.to_doc(alloc, interner))
+                .append(alloc.text(","))
+                .append(alloc.space())
+                .append(alloc.text(discriminant.to_string()))
+                .append(alloc.text("."))
+                .append(alloc.text(index.to_string())),
         }
     }
 }
@@ -1026,15 +1372,44 @@ pub enum Type {
     I64,
     Bool,
     Unit,
-    Array { element: Ref<Type>, length: u64 },
-    Slice { element: Ref<Type> },
-    Reference { referee: Ref<Type>, mutable: bool },
-    Pointer { pointee: Ref<Type>, mutable: bool },
+    Array {
+        element: Ref<Type>,
+        length: u64,
+    },
+    Slice {
+        element: Ref<Type>,
+    },
+    Tuple(Vec<Type>),
+    Reference {
+        referee: Ref<Type>,
+        mutable: bool,
+    },
+    Pointer {
+        pointee: Ref<Type>,
+        mutable: bool,
+    },
     String,
     Absurd,
+    /// A tagged union, holding a [`Type::DISCRIMINANT`] tag that selects which one of the
+    /// variants' payloads is currently stored
+    Enum {
+        variants: Vec<Vec<Type>>,
+    },
 }
 
 impl Type {
+    /// The type of an enum's discriminant
+    pub const DISCRIMINANT: Self = Self::U32;
+
+    /// If the current type is an enum, get the payload types of its variants
+    pub fn enum_variants(&self) -> Option<&[Vec<Self>]> {
+        if let Self::Enum { variants } = self {
+            Some(variants)
+        } else {
+            None
+        }
+    }
+
     /// If the current type is an array, get the type of the elements it contains
     pub fn array_elements(&self) -> Option<&Self> {
         if let Self::Array { element, .. } = self {
@@ -1044,6 +1419,15 @@ impl Type {
         }
     }
 
+    /// If the current type is a tuple, get the types of its fields
+    pub fn tuple_elements(&self) -> Option<&[Self]> {
+        if let Self::Tuple(elements) = self {
+            Some(elements)
+        } else {
+            None
+        }
+    }
+
     pub fn is_unsigned(&self) -> bool {
         matches!(self, Self::U8 | Self::U16 | Self::U32 | Self::U64)
     }
@@ -1073,8 +1457,13 @@ impl Type {
         is_i64    => Self::I64,
         is_bool   => Self::Bool,
         is_unit   => Self::Unit,
+        is_absurd => Self::Absurd,
         is_array  => Self::Array { .. },
+        is_slice  => Self::Slice { .. },
+        is_tuple  => Self::Tuple(..),
         is_string => Self::String,
+        is_enum   => Self::Enum { .. },
+        is_reference => Self::Reference { .. },
     }
 
     pub fn to_doc<'a, D>(
@@ -1128,6 +1517,18 @@ impl Type {
                 .append(element.to_doc(alloc, mir, interner))
                 .append(alloc.text("]")),
 
+            Self::Tuple(elements) => alloc
+                .text("tup[")
+                .append(
+                    alloc
+                        .intersperse(
+                            elements.iter().map(|ty| ty.to_doc(alloc, mir, interner)),
+                            alloc.text(",").append(alloc.space()),
+                        )
+                        .group(),
+                )
+                .append(alloc.text("]")),
+
             &Self::Reference {
                 ref referee,
                 mutable,
@@ -1142,6 +1543,26 @@ impl Type {
 
             Self::String => alloc.text("str"),
             Self::Absurd => alloc.text("absurd"),
+
+            Self::Enum { variants } => alloc
+                .text("enum[")
+                .append(
+                    alloc
+                        .intersperse(
+                            variants.iter().map(|payload| {
+                                alloc
+                                    .text("(")
+                                    .append(alloc.intersperse(
+                                        payload.iter().map(|ty| ty.to_doc(alloc, mir, interner)),
+                                        alloc.text(",").append(alloc.space()),
+                                    ))
+                                    .append(alloc.text(")"))
+                            }),
+                            alloc.text(",").append(alloc.space()),
+                        )
+                        .group(),
+                )
+                .append(alloc.text("]")),
         }
     }
 }
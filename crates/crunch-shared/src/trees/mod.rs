@@ -1,6 +1,7 @@
 pub mod ast;
 pub mod hir;
 pub mod mir;
+pub mod printer;
 
 use crate::{
     error::SyntaxError,
@@ -13,17 +14,18 @@ use core::{
     ops::{Deref, DerefMut, Not},
     str::FromStr,
 };
+use crunch_proc::Load;
 use derive_more::Display;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Load, Serialize)]
 pub struct Sided<T, S> {
     pub lhs: S,
     pub op: T,
     pub rhs: S,
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[derive(Clone, PartialEq, Eq, Hash, Deserialize, Load, Serialize)]
 pub struct Ref<T>(Box<T>);
 
 impl<T> Ref<T> {
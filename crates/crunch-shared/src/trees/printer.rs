@@ -0,0 +1,1298 @@
+use crate::{
+    error::Locatable,
+    strings::{StrInterner, StrT},
+    trees::{
+        ast::{
+            Arm, AssignKind, BinaryOp, Binding, Block, CallArg, CompOp, Decorator, Dest, Exposure,
+            Expr, ExprKind, ExtendBlock, ExternBlock, ExternFunc, For, FormatSegment, FormatSpec,
+            FuncArg, If, Item, ItemKind, Literal, LiteralVal, Loop, Match, Pattern, Range, Stmt,
+            StmtKind, Type, TypeMember, TypeOp, VarDecl, Variant, Vis, While, With,
+        },
+        ItemPath, Sided,
+    },
+};
+#[cfg(feature = "no-std")]
+use alloc::{format, vec::Vec};
+use core::fmt::{Display, Formatter, Result, Write};
+
+/// The number of spaces each level of nesting is indented by
+const INDENT: usize = 4;
+
+/// Nodes of the AST that can be printed back out as source code.
+///
+/// The printed source parses back into the same tree, give or take locations and any
+/// parentheses that had no effect on it. Parentheses are only added where they're needed
+/// for the tree to parse the same way, so `(a + b) * c` keeps its parentheses while
+/// `a + (b * c)` loses them
+pub trait PrettyPrint {
+    fn pretty_print<W: Write>(&self, printer: &mut Printer<'_, W>) -> Result;
+
+    /// Allows the node to be printed with `{}`
+    #[inline]
+    fn display<'a>(&'a self, interner: &'a StrInterner) -> Pretty<'a, Self> {
+        Pretty {
+            node: self,
+            interner,
+        }
+    }
+}
+
+/// A node that can be printed with `{}`, created by [`PrettyPrint::display`]
+#[derive(Debug)]
+pub struct Pretty<'a, T: ?Sized> {
+    node: &'a T,
+    interner: &'a StrInterner,
+}
+
+impl<T: PrettyPrint + ?Sized> Display for Pretty<'_, T> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        self.node.pretty_print(&mut Printer::new(f, self.interner))
+    }
+}
+
+/// Writes AST nodes out as source code, keeping track of the current indentation
+#[derive(Debug)]
+pub struct Printer<'a, W> {
+    out: W,
+    interner: &'a StrInterner,
+    indent: usize,
+}
+
+impl<'a, W: Write> Printer<'a, W> {
+    #[inline]
+    pub fn new(out: W, interner: &'a StrInterner) -> Self {
+        Self {
+            out,
+            interner,
+            indent: 0,
+        }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    fn newline(&mut self) -> Result {
+        self.out.write_char('\n')?;
+        for _ in 0..self.indent * INDENT {
+            self.out.write_char(' ')?;
+        }
+
+        Ok(())
+    }
+
+    fn name(&mut self, name: StrT) -> Result {
+        write!(self.out, "{}", self.interner.resolve(name))
+    }
+
+    fn path(&mut self, path: &ItemPath) -> Result {
+        self.out.write_str(&path.to_string(self.interner))
+    }
+
+    fn list<T, F>(&mut self, elements: &[T], mut print: F) -> Result
+    where
+        F: FnMut(&mut Self, &T) -> Result,
+    {
+        for (idx, element) in elements.iter().enumerate() {
+            if idx != 0 {
+                self.out.write_str(", ")?;
+            }
+
+            print(self, element)?;
+        }
+
+        Ok(())
+    }
+
+    /// Prints the statements of a block on their own lines, one level deeper than the
+    /// construct that owns them. The caller is expected to print the `end`
+    fn block(&mut self, block: &Block<'_>) -> Result {
+        self.indent += 1;
+        for stmt in block.iter() {
+            self.newline()?;
+            self.stmt(stmt)?;
+        }
+        self.indent -= 1;
+
+        Ok(())
+    }
+
+    fn end(&mut self) -> Result {
+        self.newline()?;
+        self.out.write_str("end")
+    }
+
+    fn items(&mut self, items: &[&Item<'_>]) -> Result {
+        self.indent += 1;
+        for item in items {
+            self.newline()?;
+            self.item(item)?;
+        }
+        self.indent -= 1;
+
+        Ok(())
+    }
+
+    fn item(&mut self, item: &Item<'_>) -> Result {
+        for decorator in item.decorators.iter() {
+            self.decorator(decorator)?;
+            self.newline()?;
+        }
+
+        if let ItemKind::ExternFunc(ExternFunc { callconv, .. }) = item.kind {
+            write!(self.out, "@callconv(\"{}\")", callconv)?;
+            self.newline()?;
+        }
+
+        match item.vis {
+            Some(Vis::Exposed) => self.out.write_str("exposed ")?,
+            Some(Vis::Package) => self.out.write_str("pkg ")?,
+            Some(Vis::FileLocal) | None => {}
+        }
+
+        for attr in item.attrs.iter() {
+            write!(self.out, "{} ", attr)?;
+        }
+
+        match &item.kind {
+            ItemKind::Func {
+                generics,
+                args,
+                body,
+                ret,
+                ..
+            } => {
+                self.signature(item.name, generics, args, ret.data())?;
+                self.block(body)?;
+                self.end()
+            }
+
+            ItemKind::Type { generics, members } => {
+                self.out.write_str("type ")?;
+                self.item_name(item.name)?;
+                self.generics(generics)?;
+
+                self.indent += 1;
+                for TypeMember {
+                    decorators,
+                    attrs,
+                    name,
+                    ty,
+                } in members
+                {
+                    for decorator in decorators {
+                        self.newline()?;
+                        self.decorator(decorator)?;
+                    }
+
+                    self.newline()?;
+                    for attr in attrs {
+                        write!(self.out, "{} ", attr)?;
+                    }
+
+                    self.name(*name)?;
+                    self.out.write_str(": ")?;
+                    self.ty(ty, 0)?;
+                    self.out.write_char(',')?;
+                }
+                self.indent -= 1;
+
+                self.end()
+            }
+
+            ItemKind::Enum { generics, variants } => {
+                self.out.write_str("enum ")?;
+                self.item_name(item.name)?;
+                self.generics(generics)?;
+
+                self.indent += 1;
+                for variant in variants {
+                    let (name, decorators) = match variant {
+                        Variant::Unit { name, decorators } => (name, decorators),
+                        Variant::Tuple {
+                            name, decorators, ..
+                        } => (name, decorators),
+                    };
+
+                    for decorator in decorators {
+                        self.newline()?;
+                        self.decorator(decorator)?;
+                    }
+
+                    self.newline()?;
+                    self.name(*name)?;
+
+                    if let Variant::Tuple { elms, .. } = variant {
+                        self.out.write_char('(')?;
+                        self.list(elms, |printer, ty| printer.ty(ty, 0))?;
+                        self.out.write_char(')')?;
+                    }
+                }
+                self.indent -= 1;
+
+                self.end()
+            }
+
+            ItemKind::Trait { generics, methods } => {
+                self.out.write_str("trait ")?;
+                self.item_name(item.name)?;
+                self.generics(generics)?;
+                self.items(methods)?;
+                self.end()
+            }
+
+            ItemKind::Import {
+                file,
+                dest,
+                exposes,
+            } => {
+                self.out.write_str("import ")?;
+                self.path(file)?;
+
+                match dest {
+                    Dest::NativeLib => self.out.write_str(" lib")?,
+                    Dest::Package => self.out.write_str(" pkg")?,
+                    Dest::Relative => {}
+                }
+
+                match exposes {
+                    Exposure::None(alias) => self.alias(file, *alias),
+                    Exposure::All => self.out.write_str(" exposing *"),
+                    Exposure::Items(items) => {
+                        self.out.write_str(" exposing ")?;
+                        self.list(items, |printer, (member, alias)| {
+                            printer.path(member)?;
+                            printer.alias(member, *alias)
+                        })
+                    }
+                }
+            }
+
+            ItemKind::ExtendBlock(ExtendBlock {
+                target,
+                extender,
+                items,
+            }) => {
+                self.out.write_str("extend ")?;
+                self.ty(target, 0)?;
+
+                if let Some(extender) = extender {
+                    self.out.write_str(" with ")?;
+                    self.ty(extender, 0)?;
+                }
+
+                self.items(items)?;
+                self.end()
+            }
+
+            ItemKind::Alias { alias, actual } => {
+                self.out.write_str("alias ")?;
+                self.ty(alias, 0)?;
+                self.out.write_str(" = ")?;
+                self.ty(actual, 0)
+            }
+
+            ItemKind::ExternBlock(ExternBlock { items }) => {
+                self.out.write_str("extern")?;
+                self.items(items)?;
+                self.end()
+            }
+
+            ItemKind::ExternFunc(ExternFunc {
+                generics,
+                args,
+                ret,
+                ..
+            }) => {
+                self.signature(item.name, generics, args, ret.data())?;
+                self.out.write_char(';')
+            }
+
+            ItemKind::Module(items) => {
+                self.out.write_str("module ")?;
+                self.item_name(item.name)?;
+                self.items(items)?;
+                self.end()
+            }
+        }
+    }
+
+    fn item_name(&mut self, name: Option<StrT>) -> Result {
+        if let Some(name) = name {
+            self.name(name)?;
+        }
+
+        Ok(())
+    }
+
+    /// Imports are named after the last segment of their path unless they're given an alias
+    fn alias(&mut self, path: &ItemPath, alias: StrT) -> Result {
+        if path.last() != Some(&alias) {
+            self.out.write_str(" as ")?;
+            self.name(alias)?;
+        }
+
+        Ok(())
+    }
+
+    fn signature(
+        &mut self,
+        name: Option<StrT>,
+        generics: &Option<Locatable<Vec<Locatable<&Type<'_>>>>>,
+        args: &[FuncArg<'_>],
+        ret: &Type<'_>,
+    ) -> Result {
+        self.out.write_str("fn ")?;
+        self.item_name(name)?;
+        self.generics(generics)?;
+
+        self.out.write_char('(')?;
+        self.list(args, |printer, arg| {
+            printer.name(arg.name)?;
+            printer.out.write_str(": ")?;
+
+            match (arg.variadic, *arg.ty) {
+                (true, Type::Slice { element }) => {
+                    printer.out.write_str("..")?;
+                    printer.ty(element, 0)?;
+                }
+                _ => printer.ty(&arg.ty, 0)?,
+            }
+
+            if let Some(default) = arg.default {
+                printer.out.write_str(" := ")?;
+                printer.expr(default, 0)?;
+            }
+
+            Ok(())
+        })?;
+        self.out.write_char(')')?;
+
+        // Functions without a return type return `unit`
+        if !is_unit(ret) {
+            self.out.write_str(" -> ")?;
+            self.ty(ret, 0)?;
+        }
+
+        Ok(())
+    }
+
+    fn generics(&mut self, generics: &Option<Locatable<Vec<Locatable<&Type<'_>>>>>) -> Result {
+        if let Some(generics) = generics {
+            self.out.write_char('[')?;
+            self.list(generics, |printer, ty| match **ty {
+                // Bounds within generic parameters are written as `T: A & B`
+                Type::Bounded { path, bounds } => {
+                    printer.path(path)?;
+                    printer.out.write_str(": ")?;
+
+                    let precedence = type_op_precedence(TypeOp::And) + 1;
+                    for (idx, bound) in bounds.iter().enumerate() {
+                        if idx != 0 {
+                            printer.out.write_str(" & ")?;
+                        }
+
+                        printer.ty(bound, precedence)?;
+                    }
+
+                    Ok(())
+                }
+
+                _ => printer.ty(ty, 0),
+            })?;
+            self.out.write_char(']')?;
+        }
+
+        Ok(())
+    }
+
+    fn decorator(&mut self, decorator: &Decorator<'_>) -> Result {
+        self.out.write_char('@')?;
+        self.name(*decorator.name)?;
+
+        if !decorator.args.is_empty() {
+            self.out.write_char('(')?;
+            self.list(&decorator.args, |printer, arg| printer.expr(arg, 0))?;
+            self.out.write_char(')')?;
+        }
+
+        Ok(())
+    }
+
+    fn stmt(&mut self, stmt: &Stmt<'_>) -> Result {
+        match &stmt.kind {
+            StmtKind::VarDecl(VarDecl {
+                name,
+                ty,
+                val,
+                constant,
+                mutable,
+            }) => {
+                self.out
+                    .write_str(if *constant { "const " } else { "let " })?;
+                if *mutable {
+                    self.out.write_str("mut ")?;
+                }
+                self.name(*name)?;
+
+                // Variables without a type have it inferred
+                if ***ty == Type::Unknown {
+                    self.out.write_str(" := ")?;
+                } else {
+                    self.out.write_str(": ")?;
+                    self.ty(ty, 0)?;
+                    self.out.write_str(" := ")?;
+                }
+
+                self.expr(val, 0)
+            }
+
+            StmtKind::Item(item) => self.item(item),
+            StmtKind::Expr(expr) => self.expr(expr, 0),
+        }
+    }
+
+    /// Prints an expression, wrapping it in parentheses if it binds looser than `precedence`
+    fn expr(&mut self, expr: &Expr<'_>, precedence: usize) -> Result {
+        if expr_precedence(expr) < precedence {
+            self.parens(expr)
+        } else {
+            self.expr_kind(expr)
+        }
+    }
+
+    fn parens(&mut self, expr: &Expr<'_>) -> Result {
+        self.out.write_char('(')?;
+        self.expr_kind(expr)?;
+        self.out.write_char(')')
+    }
+
+    /// Prints the expression that postfix operators like calls and indexing are applied to.
+    /// Member accesses take everything after their `.` as the accessed member, so they
+    /// have to be parenthesized as well
+    fn postfix_operand(&mut self, expr: &Expr<'_>) -> Result {
+        if matches!(expr.kind, ExprKind::MemberFuncCall { .. }) {
+            self.parens(expr)
+        } else {
+            self.expr(expr, ATOM)
+        }
+    }
+
+    fn expr_kind(&mut self, expr: &Expr<'_>) -> Result {
+        match &expr.kind {
+            ExprKind::If(If { clauses, else_ }) => {
+                for (idx, clause) in clauses.iter().enumerate() {
+                    if idx != 0 {
+                        self.newline()?;
+                        self.out.write_str("else ")?;
+                    }
+
+                    self.out.write_str("if ")?;
+                    self.expr(clause.cond, 0)?;
+                    self.block(&clause.body)?;
+                }
+
+                if let Some(else_) = else_ {
+                    self.newline()?;
+                    self.out.write_str("else")?;
+                    self.block(else_)?;
+                }
+
+                self.end()
+            }
+
+            ExprKind::Return(value) => {
+                self.out.write_str("return")?;
+                if let Some(value) = value {
+                    self.out.write_char(' ')?;
+                    self.expr(value, 0)?;
+                }
+
+                Ok(())
+            }
+
+            ExprKind::Break(value) => {
+                self.out.write_str("break")?;
+                if let Some(value) = value {
+                    self.out.write_char(' ')?;
+                    self.expr(value, 0)?;
+                }
+
+                Ok(())
+            }
+
+            ExprKind::Continue => self.out.write_str("continue"),
+
+            ExprKind::While(While {
+                cond,
+                body,
+                then,
+                else_,
+            }) => {
+                self.out.write_str("while ")?;
+                self.expr(cond, 0)?;
+                self.block(body)?;
+                self.loop_clauses(then.as_ref(), else_.as_ref())
+            }
+
+            ExprKind::Loop(Loop { body, else_ }) => {
+                self.out.write_str("loop")?;
+                self.block(body)?;
+                self.loop_clauses(None, else_.as_ref())
+            }
+
+            ExprKind::For(For {
+                var,
+                cond,
+                body,
+                then,
+                else_,
+            }) => {
+                self.out.write_str("for ")?;
+                self.expr(var, 0)?;
+                self.out.write_str(" in ")?;
+                self.expr(cond, 0)?;
+                self.block(body)?;
+                self.loop_clauses(then.as_ref(), else_.as_ref())
+            }
+
+            ExprKind::Match(Match { var, arms }) => {
+                self.out.write_str("match ")?;
+                self.expr(var, 0)?;
+
+                self.indent += 1;
+                for Arm { bind, guard, body } in arms {
+                    self.newline()?;
+                    self.binding(bind)?;
+
+                    if let Some(guard) = guard {
+                        self.out.write_str(" where ")?;
+                        self.expr(guard, 0)?;
+                    }
+
+                    self.out.write_str(" =>")?;
+                    self.block(body)?;
+                    self.end()?;
+                }
+                self.indent -= 1;
+
+                self.end()
+            }
+
+            ExprKind::With(With { value, name, body }) => {
+                self.out.write_str("with ")?;
+                // The value is followed by an `as`, so anything looser than a cast is parenthesized
+                self.expr(value, CAST + 1)?;
+                self.out.write_str(" as ")?;
+                self.name(**name)?;
+                self.block(body)?;
+                self.end()
+            }
+
+            ExprKind::Variable(name) => self.name(**name),
+            ExprKind::Literal(literal) => self.literal(literal),
+
+            ExprKind::FormatString(segments) => {
+                self.out.write_char('"')?;
+                for segment in segments {
+                    match segment {
+                        FormatSegment::Text(text) => self.escaped(text.as_str(), '"', true)?,
+                        FormatSegment::Value { expr: value, spec } => {
+                            self.out.write_char('{')?;
+                            self.expr(value, 0)?;
+                            if *spec == FormatSpec::Debug {
+                                self.out.write_str(":?")?;
+                            }
+                            self.out.write_char('}')?;
+                        }
+                    }
+                }
+                self.out.write_char('"')
+            }
+
+            ExprKind::UnaryOp(op, operand) => {
+                write!(self.out, "{}", op)?;
+
+                // `-1` is a negative literal rather than the negation of one
+                if starts_with_number(operand) {
+                    self.parens(operand)
+                } else {
+                    self.expr(operand, PREFIX)
+                }
+            }
+
+            ExprKind::BinaryOp(Sided { lhs, op, rhs }) => {
+                let precedence = binary_precedence(*op);
+
+                // Casts followed by `&` or `|` would take them as part of their type
+                if matches!(op, BinaryOp::BitAnd | BinaryOp::BitOr) && ends_with_cast(lhs) {
+                    self.parens(lhs)?;
+                } else {
+                    self.expr(lhs, precedence)?;
+                }
+
+                write!(self.out, " {} ", op)?;
+                self.expr(rhs, precedence + 1)
+            }
+
+            ExprKind::Comparison(Sided { lhs, op, rhs }) => {
+                let precedence = comparison_precedence(*op);
+
+                self.expr(lhs, precedence)?;
+                write!(self.out, " {} ", op)?;
+                self.expr(rhs, precedence + 1)
+            }
+
+            ExprKind::Assign(Sided { lhs, op, rhs }) => {
+                // Assignments are right associative
+                self.expr(lhs, ASSIGN + 1)?;
+                write!(self.out, " {} ", op)?;
+                self.expr(rhs, 0)
+            }
+
+            ExprKind::Paren(inner) => {
+                self.out.write_char('(')?;
+                self.expr(inner, 0)?;
+                self.out.write_char(')')
+            }
+
+            ExprKind::Array(elements) => {
+                self.out.write_str("arr[")?;
+                self.list(elements, |printer, elm| printer.expr(elm, 0))?;
+                self.out.write_char(']')
+            }
+
+            ExprKind::Tuple(elements) => {
+                self.out.write_str("tup[")?;
+                self.list(elements, |printer, elm| printer.expr(elm, 0))?;
+                self.out.write_char(']')
+            }
+
+            ExprKind::Range(Range { start, end, kind }) => {
+                if let Some(start) = start {
+                    self.expr(start, RANGE + 1)?;
+                }
+
+                write!(self.out, "{}", kind)?;
+
+                if let Some(end) = end {
+                    self.expr(end, RANGE + 1)?;
+                }
+
+                Ok(())
+            }
+
+            ExprKind::Index { var, index } => {
+                self.postfix_operand(var)?;
+                self.out.write_char('[')?;
+                self.expr(index, 0)?;
+                self.out.write_char(']')
+            }
+
+            ExprKind::FuncCall { caller, args } => {
+                self.postfix_operand(caller)?;
+                self.out.write_char('(')?;
+                self.list(args, |printer, arg| match arg {
+                    // Assignments to a variable would be mistaken for named arguments
+                    CallArg::Positional(value) if is_named_arg(value) => printer.parens(value),
+                    CallArg::Positional(value) => printer.expr(value, 0),
+
+                    CallArg::Named { name, value } => {
+                        printer.name(**name)?;
+                        printer.out.write_str(" := ")?;
+                        printer.expr(value, 0)
+                    }
+
+                    CallArg::Spread(value) => {
+                        printer.out.write_str("...")?;
+                        printer.expr(value, 0)
+                    }
+                })?;
+                self.out.write_char(')')
+            }
+
+            ExprKind::MemberFuncCall { member, func } => {
+                self.postfix_operand(member)?;
+                self.out.write_char('.')?;
+
+                // `1.2` is a float, not a member access
+                if starts_with_number(func) {
+                    self.parens(func)
+                } else {
+                    self.expr(func, ATOM)
+                }
+            }
+
+            ExprKind::Reference { mutable, expr } => {
+                self.out.write_str(if *mutable { "&mut " } else { "&" })?;
+                self.expr(expr, PREFIX)
+            }
+
+            ExprKind::Deref(pointer) => {
+                self.out.write_char('*')?;
+
+                // `**` is the power operator, not two dereferences
+                if let ExprKind::Deref(_) = pointer.kind {
+                    self.parens(pointer)
+                } else {
+                    self.expr(pointer, PREFIX)
+                }
+            }
+
+            ExprKind::Cast { expr, ty } => {
+                self.expr(expr, CAST)?;
+                self.out.write_str(" as ")?;
+                self.ty(ty, 0)
+            }
+        }
+    }
+
+    /// Prints the `then` and `else` clauses of a loop along with its closing `end`
+    fn loop_clauses(&mut self, then: Option<&Block<'_>>, else_: Option<&Block<'_>>) -> Result {
+        if let Some(then) = then {
+            self.newline()?;
+            self.out.write_str("then")?;
+            self.block(then)?;
+        }
+
+        if let Some(else_) = else_ {
+            self.newline()?;
+            self.out.write_str("else")?;
+            self.block(else_)?;
+        }
+
+        self.end()
+    }
+
+    fn literal(&mut self, literal: &Literal<'_>) -> Result {
+        match &literal.val {
+            LiteralVal::Integer(int) => write!(self.out, "{}", int),
+            LiteralVal::Bool(boolean) => write!(self.out, "{}", boolean),
+
+            LiteralVal::String(text) => {
+                self.out.write_char('"')?;
+                self.escaped(text.as_str(), '"', true)?;
+                self.out.write_char('"')
+            }
+
+            LiteralVal::Rune(rune) => {
+                self.out.write_char('\'')?;
+                self.escaped_char(rune.as_char(), '\'', false)?;
+                self.out.write_char('\'')
+            }
+
+            LiteralVal::Float(float) => {
+                let float = f64::from_bits(float.0);
+
+                if float.is_nan() {
+                    self.out.write_str("NaN")
+                } else if float.is_infinite() {
+                    self.out.write_str(if float.is_sign_negative() {
+                        "-inf"
+                    } else {
+                        "inf"
+                    })
+                } else {
+                    // Float literals always need a fractional part, even in scientific notation
+                    let mut float = format!("{:?}", float);
+                    if !float.contains('.') {
+                        let exponent = float.find('e').unwrap_or(float.len());
+                        float.insert_str(exponent, ".0");
+                    }
+
+                    self.out.write_str(&float)
+                }
+            }
+
+            // Arrays of literals are only created by byte strings
+            LiteralVal::Array(bytes) => {
+                let bytes: Vec<u8> = bytes
+                    .iter()
+                    .map(|byte| match byte.val {
+                        LiteralVal::Integer(int) => int.bits as u8,
+                        _ => unreachable!("byte strings only contain integers"),
+                    })
+                    .collect();
+
+                // Byte strings are made from the utf-8 of their text, so any byte string
+                // that came from source is valid utf-8
+                self.out.write_str("b\"")?;
+                match core::str::from_utf8(&bytes) {
+                    Ok(text) => self.escaped(text, '"', false)?,
+                    Err(_) => {
+                        for byte in bytes {
+                            write!(self.out, "\\x{{{:02X}}}", byte)?;
+                        }
+                    }
+                }
+                self.out.write_char('"')
+            }
+        }
+    }
+
+    /// Writes out the text of a string, escaping any quotes, control characters and,
+    /// for strings that can be interpolated into, braces
+    fn escaped(&mut self, text: &str, quote: char, braces: bool) -> Result {
+        for c in text.chars() {
+            self.escaped_char(c, quote, braces)?;
+        }
+
+        Ok(())
+    }
+
+    fn escaped_char(&mut self, c: char, quote: char, braces: bool) -> Result {
+        match c {
+            '\\' => self.out.write_str("\\\\"),
+            '\n' => self.out.write_str("\\n"),
+            '\r' => self.out.write_str("\\r"),
+            '\t' => self.out.write_str("\\t"),
+            '\0' => self.out.write_str("\\0"),
+            '{' | '}' if braces => {
+                self.out.write_char(c)?;
+                self.out.write_char(c)
+            }
+
+            // Runes can't escape their quotes, so they're written as a code point instead
+            '\'' if quote == '\'' => self.out.write_str("\\u{0027}"),
+            '"' if quote == '"' => self.out.write_str("\\\""),
+
+            c if c.is_control() && (c as u32) <= 0xFFFF => {
+                write!(self.out, "\\u{{{:04X}}}", c as u32)
+            }
+            c if c.is_control() => write!(self.out, "\\U{{{:08X}}}", c as u32),
+
+            c => self.out.write_char(c),
+        }
+    }
+
+    /// Prints a type, wrapping it in parentheses if it binds looser than `precedence`
+    fn ty(&mut self, ty: &Type<'_>, precedence: usize) -> Result {
+        if type_precedence(ty) < precedence {
+            self.out.write_char('(')?;
+            self.ty_kind(ty)?;
+            self.out.write_char(')')
+        } else {
+            self.ty_kind(ty)
+        }
+    }
+
+    fn types(&mut self, types: &[Locatable<&Type<'_>>]) -> Result {
+        self.list(types, |printer, ty| printer.ty(ty, 0))
+    }
+
+    fn ty_kind(&mut self, ty: &Type<'_>) -> Result {
+        match ty {
+            Type::Operand(Sided { lhs, op, rhs }) => {
+                let precedence = type_op_precedence(*op);
+
+                self.ty(lhs, precedence)?;
+                write!(self.out, " {} ", op)?;
+                self.ty(rhs, precedence + 1)
+            }
+
+            Type::Const(name, ty) => {
+                self.out.write_str("const ")?;
+                self.name(*name)?;
+                self.out.write_str(": ")?;
+                self.ty(ty, 0)
+            }
+
+            Type::Not(ty) => {
+                self.out.write_char('!')?;
+                self.ty(ty, TYPE_PREFIX)
+            }
+
+            Type::Paren(ty) => {
+                self.out.write_char('(')?;
+                self.ty(ty, 0)?;
+                self.out.write_char(')')
+            }
+
+            Type::Func { params, ret } => {
+                self.out.write_str("fn(")?;
+                self.types(params)?;
+                self.out.write_char(')')?;
+
+                if !is_unit(ret) {
+                    self.out.write_str(" -> ")?;
+                    self.ty(ret, 0)?;
+                }
+
+                Ok(())
+            }
+
+            Type::Trait(traits) => {
+                self.out.write_str("type[")?;
+                self.types(traits)?;
+                self.out.write_char(']')
+            }
+
+            Type::Bounded { path, bounds } => {
+                self.path(path)?;
+                self.out.write_char('[')?;
+                self.types(bounds)?;
+                self.out.write_char(']')
+            }
+
+            Type::ItemPath(path) => self.path(path),
+            Type::Unknown => self.out.write_str("infer"),
+
+            Type::Integer { signed, width } => write!(
+                self.out,
+                "{}{}",
+                if signed.unwrap_or(true) { "i" } else { "u" },
+                width.unwrap_or(32),
+            ),
+            Type::IntReg { signed } => self.out.write_str(if *signed { "ireg" } else { "ureg" }),
+            Type::IntPtr { signed } => self.out.write_str(if *signed { "iptr" } else { "uptr" }),
+            Type::Float { width } => write!(self.out, "f{}", width),
+            Type::Bool => self.out.write_str("bool"),
+            Type::String => self.out.write_str("str"),
+            Type::Rune => self.out.write_str("rune"),
+            Type::Absurd => self.out.write_str("absurd"),
+            Type::Unit => self.out.write_str("unit"),
+
+            Type::Array { element, length } => {
+                self.out.write_str("arr[")?;
+                self.ty(element, 0)?;
+                write!(self.out, "; {}]", length)
+            }
+
+            Type::Slice { element } => {
+                self.out.write_str("slice[")?;
+                self.ty(element, 0)?;
+                self.out.write_char(']')
+            }
+
+            Type::Range { element } => {
+                self.out.write_str("range[")?;
+                self.ty(element, 0)?;
+                self.out.write_char(']')
+            }
+
+            Type::Tuple(elements) => {
+                self.out.write_str("tup[")?;
+                self.types(elements)?;
+                self.out.write_char(']')
+            }
+
+            Type::Pointer { pointee, mutable } => {
+                self.out
+                    .write_str(if *mutable { "*mut " } else { "*const " })?;
+                self.ty(pointee, TYPE_PREFIX)
+            }
+
+            Type::Reference { referee, mutable } => {
+                self.out.write_str(if *mutable { "&mut " } else { "&" })?;
+                self.ty(referee, TYPE_PREFIX)
+            }
+        }
+    }
+
+    fn binding(&mut self, binding: &Binding<'_>) -> Result {
+        if binding.reference {
+            self.out.write_str("ref ")?;
+        }
+        if binding.mutable {
+            self.out.write_str("mut ")?;
+        }
+
+        self.pattern(&binding.pattern, true)?;
+
+        if let Some(ty) = &binding.ty {
+            self.out.write_str(": ")?;
+            self.ty(ty, 0)?;
+        }
+
+        Ok(())
+    }
+
+    fn patterns(&mut self, patterns: &[Pattern<'_>]) -> Result {
+        self.list(patterns, |printer, pattern| printer.pattern(pattern, true))
+    }
+
+    /// Prints a pattern, parenthesizing or-patterns where they aren't allowed by `or`
+    fn pattern(&mut self, pattern: &Pattern<'_>, or: bool) -> Result {
+        match pattern {
+            Pattern::Literal(literal) => self.literal(literal),
+            Pattern::Ident(name) => self.name(*name),
+            Pattern::ItemPath(path) => self.path(path),
+            Pattern::Wildcard => self.out.write_char('_'),
+            Pattern::Rest => self.out.write_str(".."),
+
+            Pattern::Tuple(elements) => {
+                self.out.write_str("tup[")?;
+                self.patterns(elements)?;
+                self.out.write_char(']')
+            }
+
+            Pattern::Slice(elements) => {
+                self.out.write_str("arr[")?;
+                self.patterns(elements)?;
+                self.out.write_char(']')
+            }
+
+            Pattern::Struct { path, fields, rest } => {
+                self.path(path)?;
+                self.out.write_str(" { ")?;
+                self.list(fields, |printer, field| {
+                    printer.name(field.name)?;
+
+                    // `Type { field }` is shorthand for `Type { field: field }`
+                    if field.pattern != Pattern::Ident(field.name) {
+                        printer.out.write_str(": ")?;
+                        printer.pattern(&field.pattern, true)?;
+                    }
+
+                    Ok(())
+                })?;
+
+                if *rest {
+                    self.out
+                        .write_str(if fields.is_empty() { ".." } else { ", .." })?;
+                }
+
+                self.out.write_str(" }")
+            }
+
+            Pattern::Variant { path, elements } => {
+                self.path(path)?;
+                self.out.write_char('(')?;
+                self.patterns(elements)?;
+                self.out.write_char(')')
+            }
+
+            Pattern::Bind(name, pattern) => {
+                self.name(*name)?;
+                self.out.write_str(" @ ")?;
+                self.pattern(pattern, false)
+            }
+
+            Pattern::Or(patterns) if or => {
+                for (idx, alternative) in patterns.iter().enumerate() {
+                    if idx != 0 {
+                        self.out.write_str(" | ")?;
+                    }
+
+                    self.pattern(alternative, false)?;
+                }
+
+                Ok(())
+            }
+            Pattern::Or(_) => {
+                self.out.write_char('(')?;
+                self.pattern(pattern, true)?;
+                self.out.write_char(')')
+            }
+
+            Pattern::Range { start, end, kind } => {
+                if let Some(start) = start {
+                    self.literal(start)?;
+                }
+
+                write!(self.out, "{}", kind)?;
+
+                if let Some(end) = end {
+                    self.literal(end)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+// The precedences of expressions, matching the ones the parser uses for its operators
+/// Literals, variables and anything wrapped in brackets, along with calls and indexing
+const ATOM: usize = 16;
+/// Unary operators and references
+const PREFIX: usize = 15;
+const CAST: usize = 14;
+const RANGE: usize = 3;
+const ASSIGN: usize = 1;
+
+/// Prefix operators on types, which bind tighter than `&` and `|`
+const TYPE_PREFIX: usize = 7;
+const TYPE_ATOM: usize = 8;
+
+fn expr_precedence(expr: &Expr<'_>) -> usize {
+    match &expr.kind {
+        ExprKind::Variable(_)
+        | ExprKind::Literal(_)
+        | ExprKind::FormatString(_)
+        | ExprKind::Paren(_)
+        | ExprKind::Array(_)
+        | ExprKind::Tuple(_)
+        | ExprKind::Index { .. }
+        | ExprKind::FuncCall { .. }
+        | ExprKind::MemberFuncCall { .. }
+        | ExprKind::Continue => ATOM,
+
+        ExprKind::UnaryOp(..) | ExprKind::Reference { .. } | ExprKind::Deref(_) => PREFIX,
+        ExprKind::Cast { .. } => CAST,
+        ExprKind::BinaryOp(Sided { op, .. }) => binary_precedence(*op),
+        ExprKind::Comparison(Sided { op, .. }) => comparison_precedence(*op),
+        ExprKind::Range(_) => RANGE,
+        ExprKind::Assign(_) => ASSIGN,
+
+        // Blocks span multiple lines and `return` and `break` take everything after them,
+        // so they're always parenthesized when they're used within another expression
+        ExprKind::If(_)
+        | ExprKind::While(_)
+        | ExprKind::Loop(_)
+        | ExprKind::For(_)
+        | ExprKind::Match(_)
+        | ExprKind::With(_)
+        | ExprKind::Return(_)
+        | ExprKind::Break(_) => 0,
+    }
+}
+
+fn binary_precedence(op: BinaryOp) -> usize {
+    match op {
+        BinaryOp::Mult | BinaryOp::Div | BinaryOp::Mod | BinaryOp::Pow => 13,
+        BinaryOp::Add | BinaryOp::Sub => 12,
+        BinaryOp::Shl | BinaryOp::Shr => 11,
+        BinaryOp::BitAnd => 8,
+        BinaryOp::BitXor => 7,
+        BinaryOp::BitOr => 6,
+        BinaryOp::And => 5,
+        BinaryOp::Or => 4,
+    }
+}
+
+fn comparison_precedence(op: CompOp) -> usize {
+    match op {
+        CompOp::Less | CompOp::Greater | CompOp::LessEqual | CompOp::GreaterEqual => 10,
+        CompOp::Equal | CompOp::NotEqual => 9,
+    }
+}
+
+fn type_op_precedence(op: TypeOp) -> usize {
+    match op {
+        TypeOp::And => 6,
+        TypeOp::Or => 4,
+    }
+}
+
+fn type_precedence(ty: &Type<'_>) -> usize {
+    match ty {
+        Type::Operand(Sided { op, .. }) => type_op_precedence(*op),
+        Type::Not(_) | Type::Pointer { .. } | Type::Reference { .. } => TYPE_PREFIX,
+
+        // Both of these take the entire type after them
+        Type::Const(..) => 0,
+        Type::Func { ret, .. } if !is_unit(ret) => 0,
+
+        _ => TYPE_ATOM,
+    }
+}
+
+/// Whether the first token of the printed expression is a number
+fn starts_with_number(expr: &Expr<'_>) -> bool {
+    match &expr.kind {
+        ExprKind::Literal(literal) => {
+            matches!(literal.val, LiteralVal::Integer(_) | LiteralVal::Float(_))
+        }
+        ExprKind::Index { var: operand, .. }
+        | ExprKind::FuncCall {
+            caller: operand, ..
+        }
+        | ExprKind::MemberFuncCall {
+            member: operand, ..
+        } => starts_with_number(operand),
+
+        _ => false,
+    }
+}
+
+fn is_unit(ty: &Type<'_>) -> bool {
+    *ty == Type::Unit
+}
+
+/// Whether the expression would be parsed as a named argument when passed to a function
+fn is_named_arg(expr: &Expr<'_>) -> bool {
+    matches!(
+        expr.kind,
+        ExprKind::Assign(Sided {
+            lhs: Expr {
+                kind: ExprKind::Variable(_),
+                ..
+            },
+            op: AssignKind::Normal,
+            ..
+        })
+    )
+}
+
+/// Whether the printed expression ends with the type of a cast
+fn ends_with_cast(expr: &Expr<'_>) -> bool {
+    match &expr.kind {
+        ExprKind::Cast { .. } => true,
+        ExprKind::BinaryOp(Sided { rhs, .. }) | ExprKind::Comparison(Sided { rhs, .. }) => {
+            expr_precedence(rhs) > expr_precedence(expr) && ends_with_cast(rhs)
+        }
+        ExprKind::Range(Range { end: Some(end), .. }) => {
+            expr_precedence(end) > RANGE && ends_with_cast(end)
+        }
+
+        _ => false,
+    }
+}
+
+macro_rules! pretty_print {
+    ($($ty:ident => $method:ident),* $(,)?) => {
+        $(
+            impl PrettyPrint for $ty<'_> {
+                #[inline]
+                fn pretty_print<W: Write>(&self, printer: &mut Printer<'_, W>) -> Result {
+                    printer.$method(self)
+                }
+            }
+        )*
+    };
+}
+
+pretty_print! {
+    Item => item,
+    Stmt => stmt,
+    Block => block,
+    Binding => binding,
+    Literal => literal,
+    Decorator => decorator,
+}
+
+impl PrettyPrint for Expr<'_> {
+    #[inline]
+    fn pretty_print<W: Write>(&self, printer: &mut Printer<'_, W>) -> Result {
+        printer.expr(self, 0)
+    }
+}
+
+impl PrettyPrint for Type<'_> {
+    #[inline]
+    fn pretty_print<W: Write>(&self, printer: &mut Printer<'_, W>) -> Result {
+        printer.ty(self, 0)
+    }
+}
+
+impl PrettyPrint for Pattern<'_> {
+    #[inline]
+    fn pretty_print<W: Write>(&self, printer: &mut Printer<'_, W>) -> Result {
+        printer.pattern(self, true)
+    }
+}
+
+/// Prints a whole file, separating its items with blank lines
+impl PrettyPrint for [&Item<'_>] {
+    fn pretty_print<W: Write>(&self, printer: &mut Printer<'_, W>) -> Result {
+        for item in self {
+            printer.item(item)?;
+            printer.out.write_str("\n\n")?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
+use alloc::vec::Vec;
 use core::{
     fmt,
     ops::{Deref, DerefMut},
 };
+use crunch_proc::Load;
 use fxhash::FxBuildHasher;
 use serde::{Deserialize, Serialize};
 
@@ -17,7 +19,9 @@ cfg_if::cfg_if! {
 
 pub type Hasher = FxBuildHasher;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Load, Serialize,
+)]
 pub enum Either<L, R> {
     Left(L),
     Right(R),
@@ -237,3 +241,48 @@ impl<T> fmt::Debug for DbgWrap<T> {
 pub trait Upcast<T: ?Sized> {
     fn upcast(&self) -> &T;
 }
+
+/// The number of single-character insertions, deletions and substitutions needed to
+/// turn `a` into `b`
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, &b) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if a == b {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Finds the candidate closest to `name`, if any of them are close enough to plausibly
+/// be what was meant
+pub fn closest_match<'a, I>(name: &str, candidates: I) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let len = name.chars().count();
+    let max_distance = (len / 3).max(1);
+
+    candidates
+        .into_iter()
+        .filter(|&candidate| candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        // Completely replacing a short name isn't a typo
+        .filter(|&(distance, candidate)| {
+            distance <= max_distance && distance < len && distance < candidate.chars().count()
+        })
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| candidate)
+}
@@ -1,15 +1,19 @@
 use crate::{
+    context::Context,
     error::{Locatable, Location},
     strings::StrT,
     trees::{
         ast::{
-            AssignKind, BinaryOp, Binding, Block, CompOp, Dest, Exposure, Expr, ExtendBlock,
-            ExternBlock, ExternFunc, For, FuncArg, If, Item, ItemKind, Literal, LiteralVal, Loop,
-            Match, Pattern, Stmt, Type, TypeMember, UnaryOp, VarDecl, Variant, While,
+            Arm, AssignKind, BinaryOp, Binding, Block, CallArg, CompOp, Decorator, Dest, Exposure,
+            Expr, ExprKind, ExtendBlock, ExternBlock, ExternFunc, FieldPattern, For, FormatSegment,
+            FuncArg, If, IfCond, Item, ItemKind, Literal, LiteralVal, Loop, Match, Pattern, Range,
+            Stmt, StmtKind, Type, TypeMember, UnaryOp, VarDecl, Variant, While, With,
         },
-        CallConv, ItemPath,
+        CallConv, ItemPath, Ref, Sided,
     },
 };
+#[cfg(feature = "no-std")]
+use alloc::vec::Vec;
 
 pub trait ItemVisitor<'ctx> {
     type Output;
@@ -64,6 +68,7 @@ pub trait ItemVisitor<'ctx> {
                 *ret,
                 *callconv,
             ),
+            ItemKind::Module(items) => self.visit_module(item, items),
         }
     }
 
@@ -127,6 +132,7 @@ pub trait ItemVisitor<'ctx> {
         ret: Locatable<&'ctx Type<'ctx>>,
         callconv: CallConv,
     ) -> Self::Output;
+    fn visit_module(&mut self, item: &'ctx Item<'ctx>, items: &[&'ctx Item<'ctx>]) -> Self::Output;
 }
 
 pub trait StmtVisitor<'ctx> {
@@ -156,6 +162,7 @@ pub trait ExprVisitor<'ctx> {
     fn visit_loop(&mut self, expr: &'ctx Expr<'ctx>, loop_: &Loop<'ctx>) -> Self::Output;
     fn visit_for(&mut self, expr: &'ctx Expr<'ctx>, for_: &For<'ctx>) -> Self::Output;
     fn visit_match(&mut self, expr: &'ctx Expr<'ctx>, match_: &Match<'ctx>) -> Self::Output;
+    fn visit_with(&mut self, expr: &'ctx Expr<'ctx>, with: &With<'ctx>) -> Self::Output;
     fn visit_variable(&mut self, expr: &'ctx Expr<'ctx>, var: Locatable<StrT>) -> Self::Output;
 
     type LiteralOutput;
@@ -164,6 +171,12 @@ pub trait ExprVisitor<'ctx> {
     type LiteralValOutput;
     fn visit_literal_val(&mut self, val: &LiteralVal<'ctx>) -> Self::LiteralValOutput;
 
+    fn visit_format_string(
+        &mut self,
+        expr: &'ctx Expr<'ctx>,
+        segments: &[FormatSegment<'ctx>],
+    ) -> Self::Output;
+
     fn visit_unary(
         &mut self,
         expr: &'ctx Expr<'ctx>,
@@ -202,12 +215,7 @@ pub trait ExprVisitor<'ctx> {
         expr: &'ctx Expr<'ctx>,
         elements: &[&'ctx Expr<'ctx>],
     ) -> Self::Output;
-    fn visit_range(
-        &mut self,
-        expr: &'ctx Expr<'ctx>,
-        start: &'ctx Expr<'ctx>,
-        end: &'ctx Expr<'ctx>,
-    ) -> Self::Output;
+    fn visit_range(&mut self, expr: &'ctx Expr<'ctx>, range: &Range<'ctx>) -> Self::Output;
     fn visit_index(
         &mut self,
         expr: &'ctx Expr<'ctx>,
@@ -218,7 +226,7 @@ pub trait ExprVisitor<'ctx> {
         &mut self,
         expr: &'ctx Expr<'ctx>,
         caller: &'ctx Expr<'ctx>,
-        args: &[&'ctx Expr<'ctx>],
+        args: &[CallArg<'ctx>],
     ) -> Self::Output;
     fn visit_member_func_call(
         &mut self,
@@ -232,6 +240,7 @@ pub trait ExprVisitor<'ctx> {
         mutable: bool,
         reference: &'ctx Expr<'ctx>,
     ) -> Self::Output;
+    fn visit_deref(&mut self, expr: &'ctx Expr<'ctx>, pointer: &'ctx Expr<'ctx>) -> Self::Output;
     fn visit_cast(
         &mut self,
         expr: &'ctx Expr<'ctx>,
@@ -266,6 +275,7 @@ pub trait ItemVisitorMut<'ctx> {
             ItemKind::Alias { .. } => self.visit_alias(items, item),
             ItemKind::ExternBlock { .. } => self.visit_extern_block(items, item),
             ItemKind::ExternFunc { .. } => self.visit_extern_func(items, item),
+            ItemKind::Module(..) => self.visit_module(items, item),
         }
     }
 
@@ -314,6 +324,11 @@ pub trait ItemVisitorMut<'ctx> {
         items: &mut Vec<&'ctx Item<'ctx>>,
         item: &'ctx Item<'ctx>,
     ) -> Self::Output;
+    fn visit_module(
+        &mut self,
+        items: &mut Vec<&'ctx Item<'ctx>>,
+        item: &'ctx Item<'ctx>,
+    ) -> Self::Output;
 }
 
 pub trait TypeVisitor<'ctx> {
@@ -321,3 +336,600 @@ pub trait TypeVisitor<'ctx> {
 
     fn visit_type(&mut self, r#type: Locatable<&'ctx Type<'ctx>>) -> Self::Output;
 }
+
+/// Rebuilds the AST, allocating every rebuilt node within the context's arenas.
+///
+/// Each method defaults to its `walk_*` function, which rebuilds the node out of its folded
+/// children, so a fold only needs to override the nodes it actually changes. Overridden
+/// methods can call the matching `walk_*` function to continue folding into the node's
+/// children
+pub trait Fold<'ctx> {
+    fn context(&self) -> &'ctx Context<'ctx>;
+
+    #[inline]
+    fn fold_location(&mut self, loc: Location) -> Location {
+        loc
+    }
+
+    #[inline]
+    fn fold_item(&mut self, item: &'ctx Item<'ctx>) -> &'ctx Item<'ctx> {
+        walk_item(self, item)
+    }
+
+    #[inline]
+    fn fold_decorator(&mut self, decorator: &Decorator<'ctx>) -> Decorator<'ctx> {
+        walk_decorator(self, decorator)
+    }
+
+    #[inline]
+    fn fold_func_arg(&mut self, arg: &FuncArg<'ctx>) -> FuncArg<'ctx> {
+        walk_func_arg(self, arg)
+    }
+
+    #[inline]
+    fn fold_block(&mut self, block: &Block<'ctx>) -> Block<'ctx> {
+        walk_block(self, block)
+    }
+
+    #[inline]
+    fn fold_stmt(&mut self, stmt: &'ctx Stmt<'ctx>) -> &'ctx Stmt<'ctx> {
+        walk_stmt(self, stmt)
+    }
+
+    #[inline]
+    fn fold_expr(&mut self, expr: &'ctx Expr<'ctx>) -> &'ctx Expr<'ctx> {
+        walk_expr(self, expr)
+    }
+
+    #[inline]
+    fn fold_literal(&mut self, literal: &Literal<'ctx>) -> Literal<'ctx> {
+        walk_literal(self, literal)
+    }
+
+    #[inline]
+    fn fold_binding(&mut self, binding: &Binding<'ctx>) -> Binding<'ctx> {
+        walk_binding(self, binding)
+    }
+
+    #[inline]
+    fn fold_pattern(&mut self, pattern: &Pattern<'ctx>) -> Pattern<'ctx> {
+        walk_pattern(self, pattern)
+    }
+
+    #[inline]
+    fn fold_type(&mut self, ty: &'ctx Type<'ctx>) -> &'ctx Type<'ctx> {
+        walk_type(self, ty)
+    }
+}
+
+fn fold_locatable<'ctx, F, T>(fold: &mut F, locatable: Locatable<T>) -> Locatable<T>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    locatable.map_location(|loc| fold.fold_location(loc))
+}
+
+fn fold_located_type<'ctx, F>(
+    fold: &mut F,
+    ty: &Locatable<&'ctx Type<'ctx>>,
+) -> Locatable<&'ctx Type<'ctx>>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    let folded = ty.map(|ty| fold.fold_type(ty));
+    fold_locatable(fold, folded)
+}
+
+fn fold_types<'ctx, F>(
+    fold: &mut F,
+    types: &[Locatable<&'ctx Type<'ctx>>],
+) -> Vec<Locatable<&'ctx Type<'ctx>>>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    types.iter().map(|ty| fold_located_type(fold, ty)).collect()
+}
+
+#[allow(clippy::type_complexity)]
+fn fold_generics<'ctx, F>(
+    fold: &mut F,
+    generics: &Option<Locatable<Vec<Locatable<&'ctx Type<'ctx>>>>>,
+) -> Option<Locatable<Vec<Locatable<&'ctx Type<'ctx>>>>>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    generics.as_ref().map(|generics| {
+        let folded = generics.as_ref().map(|types| fold_types(fold, types));
+        fold_locatable(fold, folded)
+    })
+}
+
+fn fold_func_args<'ctx, F>(
+    fold: &mut F,
+    args: &Locatable<Vec<FuncArg<'ctx>>>,
+) -> Locatable<Vec<FuncArg<'ctx>>>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    let folded = args
+        .as_ref()
+        .map(|args| args.iter().map(|arg| fold.fold_func_arg(arg)).collect());
+    fold_locatable(fold, folded)
+}
+
+fn fold_decorators<'ctx, F>(fold: &mut F, decorators: &[Decorator<'ctx>]) -> Vec<Decorator<'ctx>>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    decorators
+        .iter()
+        .map(|decorator| fold.fold_decorator(decorator))
+        .collect()
+}
+
+fn fold_items<'ctx, F>(fold: &mut F, items: &[&'ctx Item<'ctx>]) -> Vec<&'ctx Item<'ctx>>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    items.iter().map(|item| fold.fold_item(item)).collect()
+}
+
+fn fold_exprs<'ctx, F>(fold: &mut F, exprs: &[&'ctx Expr<'ctx>]) -> Vec<&'ctx Expr<'ctx>>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    exprs.iter().map(|expr| fold.fold_expr(expr)).collect()
+}
+
+fn fold_sided<'ctx, F, T>(
+    fold: &mut F,
+    sided: &Sided<T, &'ctx Expr<'ctx>>,
+) -> Sided<T, &'ctx Expr<'ctx>>
+where
+    F: Fold<'ctx> + ?Sized,
+    T: Copy,
+{
+    Sided {
+        lhs: fold.fold_expr(sided.lhs),
+        op: sided.op,
+        rhs: fold.fold_expr(sided.rhs),
+    }
+}
+
+fn fold_patterns<'ctx, F>(fold: &mut F, patterns: &[Pattern<'ctx>]) -> Vec<Pattern<'ctx>>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    patterns
+        .iter()
+        .map(|pattern| fold.fold_pattern(pattern))
+        .collect()
+}
+
+pub fn walk_item<'ctx, F>(fold: &mut F, item: &'ctx Item<'ctx>) -> &'ctx Item<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    let kind = match &item.kind {
+        ItemKind::Func {
+            generics,
+            args,
+            body,
+            ret,
+            sig,
+        } => ItemKind::Func {
+            generics: fold_generics(fold, generics),
+            args: fold_func_args(fold, args),
+            body: fold.fold_block(body),
+            ret: fold_located_type(fold, ret),
+            sig: fold.fold_location(*sig),
+        },
+
+        ItemKind::Type { generics, members } => ItemKind::Type {
+            generics: fold_generics(fold, generics),
+            members: members
+                .iter()
+                .map(|member| TypeMember {
+                    decorators: fold_decorators(fold, &member.decorators),
+                    attrs: member.attrs.clone(),
+                    name: member.name,
+                    ty: fold_located_type(fold, &member.ty),
+                })
+                .collect(),
+        },
+
+        ItemKind::Enum { generics, variants } => ItemKind::Enum {
+            generics: fold_generics(fold, generics),
+            variants: variants
+                .iter()
+                .map(|variant| match variant {
+                    Variant::Unit { name, decorators } => Variant::Unit {
+                        name: *name,
+                        decorators: fold_decorators(fold, decorators),
+                    },
+                    Variant::Tuple {
+                        name,
+                        elms,
+                        decorators,
+                    } => Variant::Tuple {
+                        name: *name,
+                        elms: fold_types(fold, elms),
+                        decorators: fold_decorators(fold, decorators),
+                    },
+                })
+                .collect(),
+        },
+
+        ItemKind::Trait { generics, methods } => ItemKind::Trait {
+            generics: fold_generics(fold, generics),
+            methods: fold_items(fold, methods),
+        },
+
+        ItemKind::Import { .. } => item.kind.clone(),
+
+        ItemKind::ExtendBlock(ExtendBlock {
+            target,
+            extender,
+            items,
+        }) => ItemKind::ExtendBlock(ExtendBlock {
+            target: fold_located_type(fold, target),
+            extender: extender
+                .as_ref()
+                .map(|extender| fold_located_type(fold, extender)),
+            items: fold_items(fold, items),
+        }),
+
+        ItemKind::Alias { alias, actual } => ItemKind::Alias {
+            alias: fold_located_type(fold, alias),
+            actual: fold_located_type(fold, actual),
+        },
+
+        ItemKind::ExternBlock(ExternBlock { items }) => ItemKind::ExternBlock(ExternBlock {
+            items: fold_items(fold, items),
+        }),
+
+        ItemKind::ExternFunc(ExternFunc {
+            generics,
+            args,
+            ret,
+            callconv,
+        }) => ItemKind::ExternFunc(ExternFunc {
+            generics: fold_generics(fold, generics),
+            args: fold_func_args(fold, args),
+            ret: fold_located_type(fold, ret),
+            callconv: *callconv,
+        }),
+
+        ItemKind::Module(items) => ItemKind::Module(fold_items(fold, items)),
+    };
+
+    let item = Item {
+        decorators: fold_decorators(fold, &item.decorators),
+        attrs: item.attrs.clone(),
+        kind,
+        loc: fold.fold_location(item.loc),
+        name: item.name,
+        vis: item.vis,
+    };
+
+    fold.context().ast_item(item)
+}
+
+pub fn walk_decorator<'ctx, F>(fold: &mut F, decorator: &Decorator<'ctx>) -> Decorator<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    Decorator {
+        name: fold_locatable(fold, decorator.name),
+        args: fold_exprs(fold, &decorator.args),
+        loc: fold.fold_location(decorator.loc),
+    }
+}
+
+pub fn walk_func_arg<'ctx, F>(fold: &mut F, arg: &FuncArg<'ctx>) -> FuncArg<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    FuncArg {
+        name: arg.name,
+        ty: fold_located_type(fold, &arg.ty),
+        default: arg.default.map(|default| fold.fold_expr(default)),
+        variadic: arg.variadic,
+        loc: fold.fold_location(arg.loc),
+    }
+}
+
+pub fn walk_block<'ctx, F>(fold: &mut F, block: &Block<'ctx>) -> Block<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    Block {
+        stmts: block.iter().map(|stmt| fold.fold_stmt(stmt)).collect(),
+        loc: fold.fold_location(block.loc),
+    }
+}
+
+pub fn walk_stmt<'ctx, F>(fold: &mut F, stmt: &'ctx Stmt<'ctx>) -> &'ctx Stmt<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    let kind = match &stmt.kind {
+        StmtKind::VarDecl(decl) => StmtKind::VarDecl(VarDecl {
+            name: decl.name,
+            ty: fold_located_type(fold, &decl.ty),
+            val: fold.fold_expr(decl.val),
+            constant: decl.constant,
+            mutable: decl.mutable,
+        }),
+        StmtKind::Item(item) => StmtKind::Item(fold.fold_item(item)),
+        StmtKind::Expr(expr) => StmtKind::Expr(fold.fold_expr(expr)),
+    };
+
+    let stmt = Stmt {
+        kind,
+        loc: fold.fold_location(stmt.loc),
+    };
+
+    fold.context().ast_stmt(stmt)
+}
+
+pub fn walk_expr<'ctx, F>(fold: &mut F, expr: &'ctx Expr<'ctx>) -> &'ctx Expr<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    let kind = match &expr.kind {
+        ExprKind::If(If { clauses, else_ }) => ExprKind::If(If {
+            clauses: clauses
+                .iter()
+                .map(|clause| IfCond {
+                    cond: fold.fold_expr(clause.cond),
+                    body: fold.fold_block(&clause.body),
+                })
+                .collect(),
+            else_: else_.as_ref().map(|else_| fold.fold_block(else_)),
+        }),
+        ExprKind::Return(value) => ExprKind::Return(value.map(|value| fold.fold_expr(value))),
+        ExprKind::Break(value) => ExprKind::Break(value.map(|value| fold.fold_expr(value))),
+        ExprKind::Continue => ExprKind::Continue,
+        ExprKind::While(While {
+            cond,
+            body,
+            then,
+            else_,
+        }) => ExprKind::While(While {
+            cond: fold.fold_expr(cond),
+            body: fold.fold_block(body),
+            then: then.as_ref().map(|then| fold.fold_block(then)),
+            else_: else_.as_ref().map(|else_| fold.fold_block(else_)),
+        }),
+        ExprKind::Loop(Loop { body, else_ }) => ExprKind::Loop(Loop {
+            body: fold.fold_block(body),
+            else_: else_.as_ref().map(|else_| fold.fold_block(else_)),
+        }),
+        ExprKind::For(For {
+            var,
+            cond,
+            body,
+            then,
+            else_,
+        }) => ExprKind::For(For {
+            var: fold.fold_expr(var),
+            cond: fold.fold_expr(cond),
+            body: fold.fold_block(body),
+            then: then.as_ref().map(|then| fold.fold_block(then)),
+            else_: else_.as_ref().map(|else_| fold.fold_block(else_)),
+        }),
+        ExprKind::Match(Match { var, arms }) => ExprKind::Match(Match {
+            var: fold.fold_expr(var),
+            arms: arms
+                .iter()
+                .map(|arm| Arm {
+                    bind: fold.fold_binding(&arm.bind),
+                    guard: arm.guard.map(|guard| fold.fold_expr(guard)),
+                    body: fold.fold_block(&arm.body),
+                })
+                .collect(),
+        }),
+        ExprKind::With(With { value, name, body }) => ExprKind::With(With {
+            value: fold.fold_expr(value),
+            name: fold_locatable(fold, *name),
+            body: fold.fold_block(body),
+        }),
+        ExprKind::Variable(var) => ExprKind::Variable(fold_locatable(fold, *var)),
+        ExprKind::Literal(literal) => {
+            let folded = literal.as_ref().map(|literal| fold.fold_literal(literal));
+            ExprKind::Literal(fold_locatable(fold, folded))
+        }
+        ExprKind::FormatString(segments) => ExprKind::FormatString(
+            segments
+                .iter()
+                .map(|segment| match segment {
+                    FormatSegment::Text(text) => FormatSegment::Text(text.clone()),
+                    FormatSegment::Value { expr: value, spec } => FormatSegment::Value {
+                        expr: fold.fold_expr(value),
+                        spec: *spec,
+                    },
+                })
+                .collect(),
+        ),
+        ExprKind::UnaryOp(op, inner) => ExprKind::UnaryOp(*op, fold.fold_expr(inner)),
+        ExprKind::BinaryOp(sided) => ExprKind::BinaryOp(fold_sided(fold, sided)),
+        ExprKind::Comparison(sided) => ExprKind::Comparison(fold_sided(fold, sided)),
+        ExprKind::Assign(sided) => ExprKind::Assign(fold_sided(fold, sided)),
+        ExprKind::Paren(inner) => ExprKind::Paren(fold.fold_expr(inner)),
+        ExprKind::Array(elements) => ExprKind::Array(fold_exprs(fold, elements)),
+        ExprKind::Tuple(elements) => ExprKind::Tuple(fold_exprs(fold, elements)),
+        ExprKind::Range(Range { start, end, kind }) => ExprKind::Range(Range {
+            start: start.map(|start| fold.fold_expr(start)),
+            end: end.map(|end| fold.fold_expr(end)),
+            kind: *kind,
+        }),
+        ExprKind::Index { var, index } => ExprKind::Index {
+            var: fold.fold_expr(var),
+            index: fold.fold_expr(index),
+        },
+        ExprKind::FuncCall { caller, args } => ExprKind::FuncCall {
+            caller: fold.fold_expr(caller),
+            args: args
+                .iter()
+                .map(|arg| match arg {
+                    CallArg::Positional(value) => CallArg::Positional(fold.fold_expr(value)),
+                    CallArg::Named { name, value } => CallArg::Named {
+                        name: fold_locatable(fold, *name),
+                        value: fold.fold_expr(value),
+                    },
+                    CallArg::Spread(value) => CallArg::Spread(fold.fold_expr(value)),
+                })
+                .collect(),
+        },
+        ExprKind::MemberFuncCall { member, func } => ExprKind::MemberFuncCall {
+            member: fold.fold_expr(member),
+            func: fold.fold_expr(func),
+        },
+        ExprKind::Reference { mutable, expr } => ExprKind::Reference {
+            mutable: *mutable,
+            expr: fold.fold_expr(expr),
+        },
+        ExprKind::Deref(pointer) => ExprKind::Deref(fold.fold_expr(pointer)),
+        ExprKind::Cast { expr, ty } => ExprKind::Cast {
+            expr: fold.fold_expr(expr),
+            ty: fold_located_type(fold, ty),
+        },
+    };
+
+    let expr = Expr {
+        kind,
+        loc: fold.fold_location(expr.loc),
+    };
+
+    fold.context().ast_expr(expr)
+}
+
+pub fn walk_literal<'ctx, F>(fold: &mut F, literal: &Literal<'ctx>) -> Literal<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    let val = match &literal.val {
+        LiteralVal::Array(elements) => LiteralVal::Array(
+            elements
+                .iter()
+                .map(|element| fold.fold_literal(element))
+                .collect(),
+        ),
+        val => val.clone(),
+    };
+
+    Literal {
+        val,
+        ty: fold.fold_type(literal.ty),
+        loc: fold.fold_location(literal.loc),
+    }
+}
+
+pub fn walk_binding<'ctx, F>(fold: &mut F, binding: &Binding<'ctx>) -> Binding<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    Binding {
+        reference: binding.reference,
+        mutable: binding.mutable,
+        pattern: fold.fold_pattern(&binding.pattern),
+        ty: binding.ty.as_ref().map(|ty| fold_located_type(fold, ty)),
+    }
+}
+
+pub fn walk_pattern<'ctx, F>(fold: &mut F, pattern: &Pattern<'ctx>) -> Pattern<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    match pattern {
+        Pattern::Literal(literal) => Pattern::Literal(fold.fold_literal(literal)),
+        Pattern::Tuple(elements) => Pattern::Tuple(fold_patterns(fold, elements)),
+        Pattern::Slice(elements) => Pattern::Slice(fold_patterns(fold, elements)),
+        Pattern::Struct { path, fields, rest } => Pattern::Struct {
+            path: path.clone(),
+            fields: fields
+                .iter()
+                .map(|field| FieldPattern {
+                    name: field.name,
+                    pattern: fold.fold_pattern(&field.pattern),
+                })
+                .collect(),
+            rest: *rest,
+        },
+        Pattern::Variant { path, elements } => Pattern::Variant {
+            path: path.clone(),
+            elements: fold_patterns(fold, elements),
+        },
+        Pattern::Bind(name, inner) => Pattern::Bind(*name, Ref::new(fold.fold_pattern(inner))),
+        Pattern::Or(alternatives) => Pattern::Or(fold_patterns(fold, alternatives)),
+        Pattern::Range { start, end, kind } => Pattern::Range {
+            start: start.as_ref().map(|start| fold.fold_literal(start)),
+            end: end.as_ref().map(|end| fold.fold_literal(end)),
+            kind: *kind,
+        },
+
+        Pattern::Ident(_) | Pattern::ItemPath(_) | Pattern::Wildcard | Pattern::Rest => {
+            pattern.clone()
+        }
+    }
+}
+
+pub fn walk_type<'ctx, F>(fold: &mut F, ty: &'ctx Type<'ctx>) -> &'ctx Type<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    let ty = match ty {
+        Type::Operand(Sided { lhs, op, rhs }) => Type::Operand(Sided {
+            lhs: fold_located_type(fold, lhs),
+            op: *op,
+            rhs: fold_located_type(fold, rhs),
+        }),
+        Type::Const(name, inner) => Type::Const(*name, fold_located_type(fold, inner)),
+        Type::Not(inner) => Type::Not(fold_located_type(fold, inner)),
+        Type::Paren(inner) => Type::Paren(fold_located_type(fold, inner)),
+        Type::Func { params, ret } => Type::Func {
+            params: fold_types(fold, params),
+            ret: fold_located_type(fold, ret),
+        },
+        Type::Trait(traits) => Type::Trait(fold_types(fold, traits)),
+        Type::Bounded { path, bounds } => Type::Bounded {
+            path: path.clone(),
+            bounds: fold_types(fold, bounds),
+        },
+        Type::Array { element, length } => Type::Array {
+            element: fold_located_type(fold, element),
+            length: *length,
+        },
+        Type::Slice { element } => Type::Slice {
+            element: fold_located_type(fold, element),
+        },
+        Type::Range { element } => Type::Range {
+            element: fold_located_type(fold, element),
+        },
+        Type::Tuple(elements) => Type::Tuple(fold_types(fold, elements)),
+        Type::Pointer { pointee, mutable } => Type::Pointer {
+            pointee: fold_located_type(fold, pointee),
+            mutable: *mutable,
+        },
+        Type::Reference { referee, mutable } => Type::Reference {
+            referee: fold_located_type(fold, referee),
+            mutable: *mutable,
+        },
+
+        // Types without any nested types have nothing to fold
+        Type::ItemPath(_)
+        | Type::Unknown
+        | Type::Integer { .. }
+        | Type::IntReg { .. }
+        | Type::IntPtr { .. }
+        | Type::Float { .. }
+        | Type::Bool
+        | Type::String
+        | Type::Rune
+        | Type::Absurd
+        | Type::Unit => return ty,
+    };
+
+    fold.context().ast_type(ty)
+}
@@ -1,14 +1,19 @@
 use crate::{
+    context::Context,
     error::Location,
     trees::{
-        ast::BinaryOp,
+        ast::{BinaryOp, UnaryOp},
         hir::{
-            Block, Break, Cast, CompOp, Expr, ExprKind, ExternFunc, FuncCall, Function, Item,
-            Literal, Match, Reference, Return, Stmt, TypeId, Var, VarDecl,
+            Binding, Block, Break, CallArg, Cast, CompOp, Enum, Expr, ExprKind, ExtendBlock,
+            ExternFunc, FormatSegment, FuncArg, FuncCall, Function, Generic, Item, Literal,
+            LiteralVal, Loop, Match, MatchArm, MethodCall, Pattern, Place, PlaceKind, Range,
+            Reference, Return, Stmt, Trait, TypeId, Var, VarDecl, Variant, VariantCtor,
         },
-        Sided,
+        Ref, Sided,
     },
 };
+#[cfg(feature = "no-std")]
+use alloc::vec::Vec;
 
 pub trait ItemVisitor<'ctx> {
     type Output;
@@ -18,11 +23,17 @@ pub trait ItemVisitor<'ctx> {
         match item {
             Item::Function(func) => self.visit_func(func),
             Item::ExternFunc(func) => self.visit_extern_func(func),
+            Item::Enum(enum_) => self.visit_enum(enum_),
+            Item::Trait(trait_) => self.visit_trait(trait_),
+            Item::ExtendBlock(extend) => self.visit_extend_block(extend),
         }
     }
 
     fn visit_func(&mut self, func: &Function<'ctx>) -> Self::Output;
-    fn visit_extern_func(&mut self, func: &ExternFunc) -> Self::Output;
+    fn visit_extern_func(&mut self, func: &ExternFunc<'ctx>) -> Self::Output;
+    fn visit_enum(&mut self, enum_: &Enum) -> Self::Output;
+    fn visit_trait(&mut self, trait_: &Trait<'ctx>) -> Self::Output;
+    fn visit_extend_block(&mut self, extend: &ExtendBlock<'ctx>) -> Self::Output;
 }
 
 pub trait StmtVisitor<'ctx>: ItemVisitor<'ctx> + ExprVisitor<'ctx> {
@@ -43,32 +54,48 @@ pub trait ExprVisitor<'ctx> {
             ExprKind::Return(value) => self.visit_return(loc, value),
             ExprKind::Break(value) => self.visit_break(loc, value),
             ExprKind::Continue => self.visit_continue(loc),
-            ExprKind::Loop(body) => self.visit_loop(loc, body),
+            ExprKind::Loop(loop_) => self.visit_loop(loc, loop_),
             ExprKind::Match(match_) => self.visit_match(loc, match_),
             ExprKind::Variable(var, ty) => self.visit_variable(loc, *var, *ty),
             ExprKind::Literal(literal) => self.visit_literal(loc, literal),
+            ExprKind::FormatString(segments) => self.visit_format_string(loc, segments),
             ExprKind::Scope(body) => self.visit_scope(loc, body),
             ExprKind::FnCall(call) => self.visit_func_call(loc, call),
+            ExprKind::MethodCall(call) => self.visit_method_call(loc, call),
             ExprKind::Comparison(Sided { lhs, op, rhs }) => {
                 self.visit_comparison(loc, lhs, *op, rhs)
             }
-            ExprKind::Assign(var, value) => self.visit_assign(loc, *var, value),
+            ExprKind::Assign { place, op, value } => self.visit_assign(loc, place, *op, value),
             ExprKind::BinOp(Sided { lhs, op, rhs }) => self.visit_binop(loc, lhs, *op, rhs),
+            ExprKind::UnaryOp(op, operand) => self.visit_unary(loc, *op, operand),
             ExprKind::Cast(cast) => self.visit_cast(loc, cast),
             ExprKind::Reference(reference) => self.visit_reference(loc, reference),
+            ExprKind::Deref(pointer) => self.visit_deref(loc, pointer),
             ExprKind::Index { var, index } => self.visit_index(loc, *var, index),
+            ExprKind::Length(var) => self.visit_length(loc, *var),
+            ExprKind::Range(range) => self.visit_range(loc, range),
+            ExprKind::Variant(ctor) => self.visit_variant(loc, ctor),
+            ExprKind::Array { elements, element } => self.visit_array(loc, elements, *element),
+            ExprKind::Tuple(elements) => self.visit_tuple(loc, elements),
+            ExprKind::TupleField { tuple, index } => self.visit_tuple_field(loc, tuple, *index),
         }
     }
 
     fn visit_return(&mut self, loc: Location, value: &Return<'ctx>) -> Self::Output;
     fn visit_break(&mut self, loc: Location, value: &Break<'ctx>) -> Self::Output;
     fn visit_continue(&mut self, loc: Location) -> Self::Output;
-    fn visit_loop(&mut self, loc: Location, body: &Block<&'ctx Stmt<'ctx>>) -> Self::Output;
+    fn visit_loop(&mut self, loc: Location, loop_: &Loop<'ctx>) -> Self::Output;
     fn visit_match(&mut self, loc: Location, match_: &Match<'ctx>) -> Self::Output;
     fn visit_variable(&mut self, loc: Location, var: Var, ty: TypeId) -> Self::Output;
     fn visit_literal(&mut self, loc: Location, literal: &Literal) -> Self::Output;
+    fn visit_format_string(
+        &mut self,
+        loc: Location,
+        segments: &[FormatSegment<'ctx>],
+    ) -> Self::Output;
     fn visit_scope(&mut self, loc: Location, body: &Block<&'ctx Stmt<'ctx>>) -> Self::Output;
     fn visit_func_call(&mut self, loc: Location, call: &FuncCall<'ctx>) -> Self::Output;
+    fn visit_method_call(&mut self, loc: Location, call: &MethodCall<'ctx>) -> Self::Output;
     fn visit_comparison(
         &mut self,
         loc: Location,
@@ -76,7 +103,13 @@ pub trait ExprVisitor<'ctx> {
         op: CompOp,
         rhs: &'ctx Expr<'ctx>,
     ) -> Self::Output;
-    fn visit_assign(&mut self, loc: Location, var: Var, value: &'ctx Expr<'ctx>) -> Self::Output;
+    fn visit_assign(
+        &mut self,
+        loc: Location,
+        place: &Place<'ctx>,
+        op: Option<BinaryOp>,
+        value: &'ctx Expr<'ctx>,
+    ) -> Self::Output;
     fn visit_binop(
         &mut self,
         loc: Location,
@@ -84,9 +117,32 @@ pub trait ExprVisitor<'ctx> {
         op: BinaryOp,
         rhs: &'ctx Expr<'ctx>,
     ) -> Self::Output;
+    fn visit_unary(
+        &mut self,
+        loc: Location,
+        op: UnaryOp,
+        operand: &'ctx Expr<'ctx>,
+    ) -> Self::Output;
     fn visit_cast(&mut self, loc: Location, cast: &Cast<'ctx>) -> Self::Output;
     fn visit_reference(&mut self, loc: Location, reference: &Reference<'ctx>) -> Self::Output;
+    fn visit_deref(&mut self, loc: Location, pointer: &'ctx Expr<'ctx>) -> Self::Output;
     fn visit_index(&mut self, loc: Location, var: Var, index: &'ctx Expr<'ctx>) -> Self::Output;
+    fn visit_length(&mut self, loc: Location, var: Var) -> Self::Output;
+    fn visit_range(&mut self, loc: Location, range: &Range<'ctx>) -> Self::Output;
+    fn visit_variant(&mut self, loc: Location, ctor: &VariantCtor<'ctx>) -> Self::Output;
+    fn visit_array(
+        &mut self,
+        loc: Location,
+        elements: &[&'ctx Expr<'ctx>],
+        element: TypeId,
+    ) -> Self::Output;
+    fn visit_tuple(&mut self, loc: Location, elements: &[&'ctx Expr<'ctx>]) -> Self::Output;
+    fn visit_tuple_field(
+        &mut self,
+        loc: Location,
+        tuple: &'ctx Expr<'ctx>,
+        index: u64,
+    ) -> Self::Output;
 }
 
 pub trait TypeVisitor<'ctx> {
@@ -94,3 +150,542 @@ pub trait TypeVisitor<'ctx> {
 
     fn visit_type(&mut self, r#type: TypeId) -> Self::Output;
 }
+
+/// Rebuilds the HIR, allocating every rebuilt statement and expression within the
+/// context's arenas.
+///
+/// Each method defaults to its `walk_*` function, which rebuilds the node out of its folded
+/// children, so a fold only needs to override the nodes it actually changes
+pub trait Fold<'ctx> {
+    fn context(&self) -> &'ctx Context<'ctx>;
+
+    #[inline]
+    fn fold_location(&mut self, loc: Location) -> Location {
+        loc
+    }
+
+    /// Types are interned, so by default they're left as-is
+    #[inline]
+    fn fold_type(&mut self, ty: TypeId) -> TypeId {
+        ty
+    }
+
+    #[inline]
+    fn fold_var(&mut self, var: Var) -> Var {
+        var
+    }
+
+    #[inline]
+    fn fold_item(&mut self, item: &Item<'ctx>) -> Item<'ctx> {
+        walk_item(self, item)
+    }
+
+    #[inline]
+    fn fold_func(&mut self, func: &Function<'ctx>) -> Function<'ctx> {
+        walk_func(self, func)
+    }
+
+    #[inline]
+    fn fold_extern_func(&mut self, func: &ExternFunc<'ctx>) -> ExternFunc<'ctx> {
+        walk_extern_func(self, func)
+    }
+
+    #[inline]
+    fn fold_enum(&mut self, enum_: &Enum) -> Enum {
+        walk_enum(self, enum_)
+    }
+
+    #[inline]
+    fn fold_trait(&mut self, trait_: &Trait<'ctx>) -> Trait<'ctx> {
+        walk_trait(self, trait_)
+    }
+
+    #[inline]
+    fn fold_extend_block(&mut self, extend: &ExtendBlock<'ctx>) -> ExtendBlock<'ctx> {
+        walk_extend_block(self, extend)
+    }
+
+    #[inline]
+    fn fold_func_arg(&mut self, arg: &FuncArg<'ctx>) -> FuncArg<'ctx> {
+        walk_func_arg(self, arg)
+    }
+
+    #[inline]
+    fn fold_generic(&mut self, generic: &Generic) -> Generic {
+        walk_generic(self, generic)
+    }
+
+    #[inline]
+    fn fold_block(&mut self, block: &Block<&'ctx Stmt<'ctx>>) -> Block<&'ctx Stmt<'ctx>> {
+        walk_block(self, block)
+    }
+
+    #[inline]
+    fn fold_stmt(&mut self, stmt: &'ctx Stmt<'ctx>) -> &'ctx Stmt<'ctx> {
+        walk_stmt(self, stmt)
+    }
+
+    #[inline]
+    fn fold_expr(&mut self, expr: &'ctx Expr<'ctx>) -> &'ctx Expr<'ctx> {
+        walk_expr(self, expr)
+    }
+
+    #[inline]
+    fn fold_place(&mut self, place: &Place<'ctx>) -> Place<'ctx> {
+        walk_place(self, place)
+    }
+
+    #[inline]
+    fn fold_literal(&mut self, literal: &Literal) -> Literal {
+        walk_literal(self, literal)
+    }
+
+    #[inline]
+    fn fold_binding(&mut self, binding: &Binding) -> Binding {
+        walk_binding(self, binding)
+    }
+
+    #[inline]
+    fn fold_pattern(&mut self, pattern: &Pattern) -> Pattern {
+        walk_pattern(self, pattern)
+    }
+}
+
+fn fold_patterns<'ctx, F>(fold: &mut F, patterns: &[Pattern]) -> Vec<Pattern>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    patterns
+        .iter()
+        .map(|pattern| fold.fold_pattern(pattern))
+        .collect()
+}
+
+fn fold_args<'ctx, F>(fold: &mut F, args: &[CallArg<'ctx>]) -> Vec<CallArg<'ctx>>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    args.iter()
+        .map(|arg| match *arg {
+            CallArg::Positional(value) => CallArg::Positional(fold.fold_expr(value)),
+            CallArg::Named { name, value } => CallArg::Named {
+                name: name.map_location(|loc| fold.fold_location(loc)),
+                value: fold.fold_expr(value),
+            },
+            CallArg::Spread(value) => CallArg::Spread(fold.fold_expr(value)),
+        })
+        .collect()
+}
+
+fn fold_sided<'ctx, F, T>(
+    fold: &mut F,
+    sided: &Sided<T, &'ctx Expr<'ctx>>,
+) -> Sided<T, &'ctx Expr<'ctx>>
+where
+    F: Fold<'ctx> + ?Sized,
+    T: Copy,
+{
+    Sided {
+        lhs: fold.fold_expr(sided.lhs),
+        op: sided.op,
+        rhs: fold.fold_expr(sided.rhs),
+    }
+}
+
+pub fn walk_item<'ctx, F>(fold: &mut F, item: &Item<'ctx>) -> Item<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    match item {
+        Item::Function(func) => Item::Function(fold.fold_func(func)),
+        Item::ExternFunc(func) => Item::ExternFunc(fold.fold_extern_func(func)),
+        Item::Enum(enum_) => Item::Enum(fold.fold_enum(enum_)),
+        Item::Trait(trait_) => Item::Trait(fold.fold_trait(trait_)),
+        Item::ExtendBlock(extend) => Item::ExtendBlock(fold.fold_extend_block(extend)),
+    }
+}
+
+pub fn walk_func<'ctx, F>(fold: &mut F, func: &Function<'ctx>) -> Function<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    let args = func.args.as_ref().map(|args| {
+        args.iter()
+            .map(|arg| fold.fold_func_arg(arg))
+            .collect::<Vec<_>>()
+    });
+
+    Function {
+        name: func.name.clone(),
+        vis: func.vis,
+        generics: func
+            .generics
+            .iter()
+            .map(|generic| fold.fold_generic(generic))
+            .collect(),
+        args: args.map_location(|loc| fold.fold_location(loc)),
+        body: fold.fold_block(&func.body),
+        ret: fold.fold_type(func.ret),
+        loc: fold.fold_location(func.loc),
+        sig: fold.fold_location(func.sig),
+    }
+}
+
+pub fn walk_extern_func<'ctx, F>(fold: &mut F, func: &ExternFunc<'ctx>) -> ExternFunc<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    let args = func.args.as_ref().map(|args| {
+        args.iter()
+            .map(|arg| fold.fold_func_arg(arg))
+            .collect::<Vec<_>>()
+    });
+
+    ExternFunc {
+        name: func.name.clone(),
+        vis: func.vis,
+        args: args.map_location(|loc| fold.fold_location(loc)),
+        ret: fold.fold_type(func.ret),
+        callconv: func.callconv,
+        loc: fold.fold_location(func.loc),
+    }
+}
+
+pub fn walk_enum<'ctx, F>(fold: &mut F, enum_: &Enum) -> Enum
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    Enum {
+        id: enum_.id,
+        name: enum_.name.clone(),
+        vis: enum_.vis,
+        generics: enum_
+            .generics
+            .iter()
+            .map(|generic| fold.fold_generic(generic))
+            .collect(),
+        variants: enum_
+            .variants
+            .iter()
+            .map(|variant| Variant {
+                name: variant.name,
+                elements: variant
+                    .elements
+                    .iter()
+                    .map(|&element| fold.fold_type(element))
+                    .collect(),
+                loc: fold.fold_location(variant.loc),
+            })
+            .collect(),
+        loc: fold.fold_location(enum_.loc),
+    }
+}
+
+pub fn walk_trait<'ctx, F>(fold: &mut F, trait_: &Trait<'ctx>) -> Trait<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    Trait {
+        id: trait_.id,
+        name: trait_.name.clone(),
+        vis: trait_.vis,
+        methods: trait_
+            .methods
+            .iter()
+            .map(|method| fold.fold_func(method))
+            .collect(),
+        loc: fold.fold_location(trait_.loc),
+    }
+}
+
+pub fn walk_extend_block<'ctx, F>(fold: &mut F, extend: &ExtendBlock<'ctx>) -> ExtendBlock<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    ExtendBlock {
+        target: fold.fold_type(extend.target),
+        extender: extend.extender,
+        methods: extend
+            .methods
+            .iter()
+            .map(|method| fold.fold_func(method))
+            .collect(),
+        loc: fold.fold_location(extend.loc),
+    }
+}
+
+pub fn walk_generic<'ctx, F>(fold: &mut F, generic: &Generic) -> Generic
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    Generic {
+        id: generic.id,
+        name: generic.name,
+        bounds: generic.bounds.clone(),
+        loc: fold.fold_location(generic.loc),
+    }
+}
+
+pub fn walk_func_arg<'ctx, F>(fold: &mut F, arg: &FuncArg<'ctx>) -> FuncArg<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    FuncArg {
+        name: fold.fold_var(arg.name),
+        kind: fold.fold_type(arg.kind),
+        default: arg.default.map(|default| fold.fold_expr(default)),
+        variadic: arg.variadic,
+        loc: fold.fold_location(arg.loc),
+    }
+}
+
+pub fn walk_block<'ctx, F>(fold: &mut F, block: &Block<&'ctx Stmt<'ctx>>) -> Block<&'ctx Stmt<'ctx>>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    let stmts = block.iter().map(|stmt| fold.fold_stmt(stmt)).collect();
+    Block::new(stmts, fold.fold_location(block.location()))
+}
+
+pub fn walk_stmt<'ctx, F>(fold: &mut F, stmt: &'ctx Stmt<'ctx>) -> &'ctx Stmt<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    let stmt = match stmt {
+        Stmt::Item(item) => {
+            let item = fold.fold_item(item);
+            Stmt::Item(fold.context().hir_item(item))
+        }
+        Stmt::Expr(expr) => Stmt::Expr(fold.fold_expr(expr)),
+        Stmt::VarDecl(decl) => Stmt::VarDecl(VarDecl {
+            name: fold.fold_var(decl.name),
+            value: fold.fold_expr(decl.value),
+            mutable: decl.mutable,
+            ty: fold.fold_type(decl.ty),
+            loc: fold.fold_location(decl.loc),
+        }),
+    };
+
+    fold.context().hir_stmt(stmt)
+}
+
+pub fn walk_expr<'ctx, F>(fold: &mut F, expr: &'ctx Expr<'ctx>) -> &'ctx Expr<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    let kind = match &expr.kind {
+        ExprKind::Match(Match { cond, arms, ty }) => ExprKind::Match(Match {
+            cond: fold.fold_expr(cond),
+            arms: arms
+                .iter()
+                .map(|arm| MatchArm {
+                    bind: fold.fold_binding(&arm.bind),
+                    guard: arm.guard.map(|guard| fold.fold_expr(guard)),
+                    body: fold.fold_block(&arm.body),
+                    ty: fold.fold_type(arm.ty),
+                })
+                .collect(),
+            ty: fold.fold_type(*ty),
+        }),
+        ExprKind::Scope(body) => ExprKind::Scope(fold.fold_block(body)),
+        ExprKind::Loop(Loop {
+            cond,
+            body,
+            then,
+            else_,
+            ty,
+        }) => ExprKind::Loop(Loop {
+            cond: cond.map(|cond| fold.fold_expr(cond)),
+            body: fold.fold_block(body),
+            then: then.as_ref().map(|then| fold.fold_block(then)),
+            else_: else_.as_ref().map(|else_| fold.fold_block(else_)),
+            ty: fold.fold_type(*ty),
+        }),
+        ExprKind::Return(Return { val }) => ExprKind::Return(Return {
+            val: val.map(|val| fold.fold_expr(val)),
+        }),
+        ExprKind::Continue => ExprKind::Continue,
+        ExprKind::Break(Break { val }) => ExprKind::Break(Break {
+            val: val.map(|val| fold.fold_expr(val)),
+        }),
+        ExprKind::FnCall(FuncCall {
+            func,
+            generics,
+            args,
+        }) => ExprKind::FnCall(FuncCall {
+            func: func.clone(),
+            generics: generics.iter().map(|&ty| fold.fold_type(ty)).collect(),
+            args: fold_args(fold, args),
+        }),
+        ExprKind::MethodCall(MethodCall {
+            receiver,
+            receiver_ty,
+            method,
+            args,
+            trait_,
+        }) => ExprKind::MethodCall(MethodCall {
+            receiver: fold.fold_expr(receiver),
+            receiver_ty: fold.fold_type(*receiver_ty),
+            method: method.map_location(|loc| fold.fold_location(loc)),
+            args: fold_args(fold, args),
+            trait_: *trait_,
+        }),
+        ExprKind::Literal(literal) => ExprKind::Literal(fold.fold_literal(literal)),
+        ExprKind::FormatString(segments) => ExprKind::FormatString(
+            segments
+                .iter()
+                .map(|segment| match segment {
+                    FormatSegment::Text(text) => FormatSegment::Text(text.clone()),
+                    FormatSegment::Value { expr: value, spec } => FormatSegment::Value {
+                        expr: fold.fold_expr(value),
+                        spec: *spec,
+                    },
+                })
+                .collect(),
+        ),
+        ExprKind::Comparison(sided) => ExprKind::Comparison(fold_sided(fold, sided)),
+        ExprKind::Variable(var, ty) => ExprKind::Variable(fold.fold_var(*var), fold.fold_type(*ty)),
+        ExprKind::Assign { place, op, value } => ExprKind::Assign {
+            place: fold.fold_place(place),
+            op: *op,
+            value: fold.fold_expr(value),
+        },
+        ExprKind::BinOp(sided) => ExprKind::BinOp(fold_sided(fold, sided)),
+        ExprKind::UnaryOp(op, operand) => ExprKind::UnaryOp(*op, fold.fold_expr(operand)),
+        ExprKind::Cast(Cast { casted, ty }) => ExprKind::Cast(Cast {
+            casted: fold.fold_expr(casted),
+            ty: fold.fold_type(*ty),
+        }),
+        ExprKind::Reference(Reference { mutable, reference }) => ExprKind::Reference(Reference {
+            mutable: *mutable,
+            reference: fold.fold_expr(reference),
+        }),
+        ExprKind::Deref(pointer) => ExprKind::Deref(fold.fold_expr(pointer)),
+        ExprKind::Index { var, index } => ExprKind::Index {
+            var: fold.fold_var(*var),
+            index: fold.fold_expr(index),
+        },
+        ExprKind::Length(var) => ExprKind::Length(fold.fold_var(*var)),
+        ExprKind::Range(Range { start, end, kind }) => ExprKind::Range(Range {
+            start: start.map(|start| fold.fold_expr(start)),
+            end: end.map(|end| fold.fold_expr(end)),
+            kind: *kind,
+        }),
+        ExprKind::Variant(VariantCtor {
+            enum_id,
+            variant,
+            args,
+            ty,
+        }) => ExprKind::Variant(VariantCtor {
+            enum_id: *enum_id,
+            variant: *variant,
+            args: args.iter().map(|arg| fold.fold_expr(arg)).collect(),
+            ty: fold.fold_type(*ty),
+        }),
+        ExprKind::Array { elements, element } => ExprKind::Array {
+            elements: elements.iter().map(|elem| fold.fold_expr(elem)).collect(),
+            element: fold.fold_type(*element),
+        },
+        ExprKind::Tuple(elements) => {
+            ExprKind::Tuple(elements.iter().map(|elem| fold.fold_expr(elem)).collect())
+        }
+        ExprKind::TupleField { tuple, index } => ExprKind::TupleField {
+            tuple: fold.fold_expr(tuple),
+            index: *index,
+        },
+    };
+
+    let expr = Expr {
+        kind,
+        loc: fold.fold_location(expr.loc),
+    };
+
+    fold.context().hir_expr(expr)
+}
+
+pub fn walk_place<'ctx, F>(fold: &mut F, place: &Place<'ctx>) -> Place<'ctx>
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    let kind = match &place.kind {
+        PlaceKind::Variable(var) => PlaceKind::Variable(fold.fold_var(*var)),
+        PlaceKind::Field { base, index } => PlaceKind::Field {
+            base: Ref::new(fold.fold_place(base)),
+            index: *index,
+        },
+        PlaceKind::Index { base, index } => PlaceKind::Index {
+            base: Ref::new(fold.fold_place(base)),
+            index: fold.fold_expr(index),
+        },
+        PlaceKind::Deref(pointer) => PlaceKind::Deref(fold.fold_expr(pointer)),
+    };
+
+    Place::new(kind, fold.fold_location(place.loc))
+}
+
+pub fn walk_literal<'ctx, F>(fold: &mut F, literal: &Literal) -> Literal
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    let val = match &literal.val {
+        LiteralVal::Array { elements } => LiteralVal::Array {
+            elements: elements
+                .iter()
+                .map(|element| fold.fold_literal(element))
+                .collect(),
+        },
+        val => val.clone(),
+    };
+
+    Literal {
+        val,
+        ty: fold.fold_type(literal.ty),
+        loc: fold.fold_location(literal.loc),
+    }
+}
+
+pub fn walk_binding<'ctx, F>(fold: &mut F, binding: &Binding) -> Binding
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    Binding {
+        reference: binding.reference,
+        mutable: binding.mutable,
+        pattern: fold.fold_pattern(&binding.pattern),
+        ty: binding.ty.map(|ty| fold.fold_type(ty)),
+    }
+}
+
+pub fn walk_pattern<'ctx, F>(fold: &mut F, pattern: &Pattern) -> Pattern
+where
+    F: Fold<'ctx> + ?Sized,
+{
+    match pattern {
+        Pattern::Literal(literal) => Pattern::Literal(fold.fold_literal(literal)),
+        Pattern::Tuple(elements) => Pattern::Tuple(fold_patterns(fold, elements)),
+        Pattern::Slice(elements) => Pattern::Slice(fold_patterns(fold, elements)),
+        Pattern::Struct { path, fields, rest } => Pattern::Struct {
+            path: path.clone(),
+            fields: fields
+                .iter()
+                .map(|(name, field)| (*name, fold.fold_pattern(field)))
+                .collect(),
+            rest: *rest,
+        },
+        Pattern::Variant { path, elements } => Pattern::Variant {
+            path: path.clone(),
+            elements: fold_patterns(fold, elements),
+        },
+        Pattern::Bind(name, inner) => Pattern::Bind(*name, Ref::new(fold.fold_pattern(inner))),
+        Pattern::Or(alternatives) => Pattern::Or(fold_patterns(fold, alternatives)),
+        Pattern::Range { start, end, kind } => Pattern::Range {
+            start: start.as_ref().map(|start| fold.fold_literal(start)),
+            end: end.as_ref().map(|end| fold.fold_literal(end)),
+            kind: *kind,
+        },
+
+        Pattern::Ident(_) | Pattern::ItemPath(_) | Pattern::Wildcard | Pattern::Rest => {
+            pattern.clone()
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use crate::trees::mir::{BasicBlock, Constant, Function, Instruction, Rval, Terminator, Type};
+use crate::trees::mir::{
+    Assign, BasicBlock, Constant, ExternFunc, FnCall, Function, Instruction, Mir, Rval, Terminator,
+    Type, Value, VarId, Variable,
+};
 
 pub trait MirVisitor {
     type FunctionOutput;
@@ -17,3 +20,271 @@ pub trait MirVisitor {
     fn visit_constant(&mut self, constant: &Constant, ty: &Type) -> Self::ConstantOutput;
     fn visit_type(&mut self, ty: &Type) -> Self::TypeOutput;
 }
+
+/// Visits the MIR mutably so that passes can rewrite it in place.
+///
+/// Each method defaults to its `walk_*` function, which visits all of the node's children,
+/// so a pass only needs to override the nodes it actually changes. Overridden methods can
+/// call the matching `walk_*` function to continue into the node's children
+pub trait VisitMut {
+    #[inline]
+    fn visit_mir(&mut self, mir: &mut Mir) {
+        walk_mir(self, mir);
+    }
+
+    #[inline]
+    fn visit_function(&mut self, func: &mut Function) {
+        walk_function(self, func);
+    }
+
+    #[inline]
+    fn visit_extern_func(&mut self, func: &mut ExternFunc) {
+        walk_extern_func(self, func);
+    }
+
+    #[inline]
+    fn visit_variable(&mut self, var: &mut Variable) {
+        walk_variable(self, var);
+    }
+
+    #[inline]
+    fn visit_block(&mut self, block: &mut BasicBlock) {
+        walk_block(self, block);
+    }
+
+    #[inline]
+    fn visit_instruction(&mut self, instruction: &mut Instruction) {
+        walk_instruction(self, instruction);
+    }
+
+    #[inline]
+    fn visit_terminator(&mut self, terminator: &mut Terminator) {
+        walk_terminator(self, terminator);
+    }
+
+    #[inline]
+    fn visit_rval(&mut self, rval: &mut Rval) {
+        walk_rval(self, rval);
+    }
+
+    #[inline]
+    fn visit_value(&mut self, value: &mut Value) {
+        walk_value(self, value);
+    }
+
+    #[inline]
+    fn visit_fn_call(&mut self, call: &mut FnCall) {
+        walk_fn_call(self, call);
+    }
+
+    #[inline]
+    fn visit_constant(&mut self, constant: &mut Constant) {
+        walk_constant(self, constant);
+    }
+
+    #[inline]
+    fn visit_type(&mut self, ty: &mut Type) {
+        walk_type(self, ty);
+    }
+
+    /// Visits every variable that's either declared or used
+    #[inline]
+    fn visit_var_id(&mut self, _var: &mut VarId) {}
+}
+
+pub fn walk_mir<V: VisitMut + ?Sized>(visitor: &mut V, mir: &mut Mir) {
+    for func in mir.functions_mut() {
+        visitor.visit_function(func);
+    }
+
+    for func in mir.external_functions_mut() {
+        visitor.visit_extern_func(func);
+    }
+}
+
+pub fn walk_function<V: VisitMut + ?Sized>(visitor: &mut V, func: &mut Function) {
+    for arg in func.args.iter_mut() {
+        visitor.visit_variable(arg);
+    }
+    visitor.visit_type(&mut func.ret);
+
+    for block in func.blocks.values_mut() {
+        visitor.visit_block(block);
+    }
+}
+
+pub fn walk_extern_func<V: VisitMut + ?Sized>(visitor: &mut V, func: &mut ExternFunc) {
+    for arg in func.args.iter_mut() {
+        visitor.visit_variable(arg);
+    }
+    visitor.visit_type(&mut func.ret);
+}
+
+pub fn walk_variable<V: VisitMut + ?Sized>(visitor: &mut V, var: &mut Variable) {
+    visitor.visit_var_id(&mut var.id);
+    visitor.visit_type(&mut var.ty);
+}
+
+pub fn walk_block<V: VisitMut + ?Sized>(visitor: &mut V, block: &mut BasicBlock) {
+    for (arg, incoming) in block.args.iter_mut() {
+        visitor.visit_variable(arg);
+
+        for (value, _) in incoming.iter_mut() {
+            visitor.visit_variable(value);
+        }
+    }
+
+    for instruction in block.instructions.iter_mut() {
+        visitor.visit_instruction(instruction);
+    }
+
+    if let Some(terminator) = block.terminator.as_mut() {
+        visitor.visit_terminator(terminator);
+    }
+}
+
+pub fn walk_instruction<V: VisitMut + ?Sized>(visitor: &mut V, instruction: &mut Instruction) {
+    match instruction {
+        Instruction::Assign(Assign { var, val, ty }) => {
+            visitor.visit_var_id(var);
+            visitor.visit_rval(val);
+            visitor.visit_type(ty);
+        }
+        Instruction::Call(call) => visitor.visit_fn_call(call),
+        Instruction::Store { pointer, value } => {
+            visitor.visit_var_id(pointer);
+            visitor.visit_var_id(value);
+        }
+    }
+}
+
+pub fn walk_terminator<V: VisitMut + ?Sized>(visitor: &mut V, terminator: &mut Terminator) {
+    match terminator {
+        Terminator::Return(value) => {
+            if let Some(value) = value {
+                visitor.visit_var_id(value);
+            }
+        }
+        Terminator::Jump(_, args) => {
+            for arg in args.iter_mut() {
+                visitor.visit_var_id(arg);
+            }
+        }
+        Terminator::Branch { condition, .. } => visitor.visit_var_id(condition),
+        Terminator::Switch {
+            condition,
+            cases,
+            default,
+        } => {
+            visitor.visit_var_id(condition);
+
+            for case in cases.iter_mut() {
+                visitor.visit_var_id(&mut case.condition);
+                for arg in case.args.iter_mut() {
+                    visitor.visit_var_id(arg);
+                }
+            }
+
+            for arg in default.args.iter_mut() {
+                visitor.visit_var_id(arg);
+            }
+        }
+        Terminator::Unreachable => {}
+    }
+}
+
+pub fn walk_rval<V: VisitMut + ?Sized>(visitor: &mut V, rval: &mut Rval) {
+    visitor.visit_value(&mut rval.val);
+    visitor.visit_type(&mut rval.ty);
+}
+
+pub fn walk_value<V: VisitMut + ?Sized>(visitor: &mut V, value: &mut Value) {
+    match value {
+        Value::Variable(var)
+        | Value::GetPointer { var, .. }
+        | Value::Extract(var, _)
+        | Value::Neg(var)
+        | Value::Not(var)
+        | Value::Load(var)
+        | Value::FieldPtr(var, _)
+        | Value::Discriminant(var)
+        | Value::Payload { var, .. } => visitor.visit_var_id(var),
+        Value::Const(constant) => visitor.visit_constant(constant),
+        Value::Call(call) => visitor.visit_fn_call(call),
+        Value::Add(lhs, rhs)
+        | Value::Sub(lhs, rhs)
+        | Value::Mul(lhs, rhs)
+        | Value::Div(lhs, rhs)
+        | Value::Rem(lhs, rhs)
+        | Value::BitAnd(lhs, rhs)
+        | Value::BitOr(lhs, rhs)
+        | Value::BitXor(lhs, rhs)
+        | Value::Shl(lhs, rhs)
+        | Value::Shr(lhs, rhs)
+        | Value::Eq(lhs, rhs)
+        | Value::Neq(lhs, rhs)
+        | Value::Less(lhs, rhs)
+        | Value::LessEq(lhs, rhs)
+        | Value::Greater(lhs, rhs)
+        | Value::GreaterEq(lhs, rhs)
+        | Value::Index(lhs, rhs)
+        | Value::IndexPtr(lhs, rhs) => {
+            visitor.visit_var_id(lhs);
+            visitor.visit_var_id(rhs);
+        }
+        Value::Alloca(ty) => visitor.visit_type(ty),
+        Value::Cast(var, ty) => {
+            visitor.visit_var_id(var);
+            visitor.visit_type(ty);
+        }
+        Value::Variant { payload, .. } | Value::Aggregate(payload) => {
+            for var in payload.iter_mut() {
+                visitor.visit_var_id(var);
+            }
+        }
+    }
+}
+
+pub fn walk_fn_call<V: VisitMut + ?Sized>(visitor: &mut V, call: &mut FnCall) {
+    for arg in call.args.iter_mut() {
+        visitor.visit_var_id(arg);
+    }
+}
+
+pub fn walk_constant<V: VisitMut + ?Sized>(visitor: &mut V, constant: &mut Constant) {
+    if let Constant::Array(elements) = constant {
+        for element in elements.iter_mut() {
+            visitor.visit_constant(element);
+        }
+    }
+}
+
+pub fn walk_type<V: VisitMut + ?Sized>(visitor: &mut V, ty: &mut Type) {
+    match ty {
+        Type::Array { element, .. } | Type::Slice { element } => visitor.visit_type(element),
+        Type::Reference { referee, .. } => visitor.visit_type(referee),
+        Type::Pointer { pointee, .. } => visitor.visit_type(pointee),
+        Type::Enum { variants } => {
+            for ty in variants.iter_mut().flatten() {
+                visitor.visit_type(ty);
+            }
+        }
+        Type::Tuple(elements) => {
+            for element in elements.iter_mut() {
+                visitor.visit_type(element);
+            }
+        }
+        Type::U8
+        | Type::I8
+        | Type::U16
+        | Type::I16
+        | Type::U32
+        | Type::I32
+        | Type::U64
+        | Type::I64
+        | Type::Bool
+        | Type::Unit
+        | Type::String
+        | Type::Absurd => {}
+    }
+}
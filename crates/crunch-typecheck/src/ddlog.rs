@@ -9,8 +9,9 @@ use crunch_shared::{
             ExprKind as HirExprKind, FuncArg as HirFuncArg, Function as HirFunction, Integer,
             Item as HirItem, ItemPath as HirItemPath, Literal as HirLiteral,
             LiteralVal as HirLiteralVal, Match as HirMatch, MatchArm as HirMatchArm,
-            Pattern as HirPattern, Stmt as HirStmt, Type as HirType, TypeId,
-            TypeKind as HirTypeKind, Var as HirVar, VarDecl as HirVarDecl, Vis as HirVis,
+            Pattern as HirPattern, Place as HirPlace, PlaceKind as HirPlaceKind, Stmt as HirStmt,
+            Type as HirType, TypeId, TypeKind as HirTypeKind, Var as HirVar, VarDecl as HirVarDecl,
+            Vis as HirVis,
         },
         Sided,
     },
@@ -83,6 +84,9 @@ pub struct DDlogEngine<'ctx> {
     functions: DDlogTable<InputFunctions>,
     statements: DDlogTable<InputStatements>,
     expressions: DDlogTable<InputExpressions>,
+    /// The first construct found that the ddlog schema can't represent, which stops the
+    /// program from being given to ddlog
+    unsupported: Option<&'static str>,
     db: &'ctx dyn TypecheckDatabase,
 }
 
@@ -97,6 +101,7 @@ impl<'ctx> DDlogEngine<'ctx> {
             functions: DDlogTable::new(),
             statements: DDlogTable::new(),
             expressions: DDlogTable::new(),
+            unsupported: None,
             db,
         }
     }
@@ -108,6 +113,10 @@ impl<'ctx> DDlogEngine<'ctx> {
             self.visit(item);
         }
 
+        if let Some(construct) = self.unsupported.take() {
+            return Err(format!("{} can't be checked by ddlog yet", construct));
+        }
+
         crunch_shared::trace!("starting transaction");
         program.transaction_start()?;
 
@@ -159,6 +168,15 @@ impl<'ctx> DDlogEngine<'ctx> {
         }
     }
 
+    /// Records a construct that can't be represented within ddlog, returning the id
+    /// reserved for the item or expression it was found in
+    fn unsupported<Id>(&mut self, construct: &'static str, id: Id) -> Id {
+        crunch_shared::trace!("found {}, which ddlog doesn't support", construct);
+        self.unsupported.get_or_insert(construct);
+
+        id
+    }
+
     fn intern<T>(&mut self, data: T) -> Interned<T>
     where
         T: Eq + Hash + Send + Sync + Clone + 'static,
@@ -184,11 +202,14 @@ impl<'ctx> Visit<HirItem<'_>> for DDlogEngine<'ctx> {
                 }
             }
 
-            HirItem::ExternFunc(_external_function) => {
-                crunch_shared::trace!("item is an external function, visiting");
-                // self.visit(external_function)
-                todo!()
-            }
+            HirItem::ExternFunc(_) => return self.unsupported("external functions", id),
+
+            HirItem::Enum(_) => return self.unsupported("enums", id),
+
+            // Traits only hold signatures, which are checked along with the methods that
+            // implement them
+            HirItem::Trait(_) => return id,
+            HirItem::ExtendBlock(_) => return self.unsupported("extend blocks", id),
         };
 
         self.items.push(InputItems { id, item });
@@ -227,7 +248,7 @@ impl<'ctx> Visit<HirFunction<'_>> for DDlogEngine<'ctx> {
     }
 }
 
-impl<'ctx> Visit<HirFuncArg> for DDlogEngine<'ctx> {
+impl<'ctx> Visit<HirFuncArg<'_>> for DDlogEngine<'ctx> {
     type Output = FuncArg;
 
     #[crunch_shared::instrument(
@@ -235,7 +256,7 @@ impl<'ctx> Visit<HirFuncArg> for DDlogEngine<'ctx> {
         skip(self, arg),
         fields(name = ?arg.name.to_string(self.db.context().strings())),
     )]
-    fn visit(&mut self, arg: &HirFuncArg) -> Self::Output {
+    fn visit(&mut self, arg: &HirFuncArg<'_>) -> Self::Output {
         FuncArg {
             name: self.next_variable(),
             kind: self.visit(&arg.kind),
@@ -263,9 +284,9 @@ impl<'ctx> Visit<HirType> for DDlogEngine<'ctx> {
 
     #[crunch_shared::instrument(name = "type", skip(self, ty))]
     fn visit(&mut self, ty: &HirType) -> Self::Output {
-        let ty = match ty.kind {
+        let ty = match &ty.kind {
             HirTypeKind::Unknown => TypeKind::hir_Unknown,
-            HirTypeKind::Integer { signed, width } => TypeKind::hir_Int {
+            &HirTypeKind::Integer { signed, width } => TypeKind::hir_Int {
                 is_signed: signed.into(),
                 width: width.into(),
             },
@@ -378,13 +399,18 @@ impl<'ctx> Visit<HirExpr<'_>> for DDlogEngine<'ctx> {
                 (expr, Some(ty))
             }
 
-            &HirExprKind::Assign(var, rhs) => {
+            &HirExprKind::Assign {
+                place:
+                    HirPlace {
+                        kind: HirPlaceKind::Variable(var),
+                        ..
+                    },
+                op: None,
+                value: rhs,
+            } => {
                 let rhs = self.visit(rhs);
-                let variable = self
-                    .variables
-                    .get(&var)
-                    .copied()
-                    .expect("got a variable that doesn't exist");
+                // Function arguments and pattern bindings aren't declared through statements
+                let variable = self.get_or_create_var(var);
 
                 let expr = ExprKind::hir_ExprAssign {
                     variable,
@@ -422,14 +448,31 @@ impl<'ctx> Visit<HirExpr<'_>> for DDlogEngine<'ctx> {
                 None,
             ),
 
-            HirExprKind::Loop(_) => todo!(),
-            HirExprKind::Continue => todo!(),
-            HirExprKind::Break(_) => todo!(),
-            HirExprKind::FnCall(_) => todo!(),
-            HirExprKind::Comparison(_) => todo!(),
-            HirExprKind::Cast(_) => todo!(),
-            HirExprKind::Reference(_) => todo!(),
-            HirExprKind::Index { var: _, index: _ } => todo!(),
+            HirExprKind::Assign { op: Some(_), .. } => {
+                return self.unsupported("compound assignments", id)
+            }
+            HirExprKind::Assign { .. } => {
+                return self.unsupported("assignments to fields, indices and dereferences", id)
+            }
+            HirExprKind::Loop(_) => return self.unsupported("loops", id),
+            HirExprKind::Continue => return self.unsupported("`continue`", id),
+            HirExprKind::Break(_) => return self.unsupported("`break`", id),
+            HirExprKind::FnCall(_) => return self.unsupported("function calls", id),
+            HirExprKind::Comparison(_) => return self.unsupported("comparisons", id),
+            HirExprKind::Cast(_) => return self.unsupported("casts", id),
+            HirExprKind::FormatString(_) => return self.unsupported("format strings", id),
+            HirExprKind::Reference(_) => return self.unsupported("references", id),
+            HirExprKind::Deref(_) => return self.unsupported("dereferences", id),
+            HirExprKind::UnaryOp(..) => return self.unsupported("unary operations", id),
+            HirExprKind::Index { .. } => return self.unsupported("indexing", id),
+            HirExprKind::Length(_) => return self.unsupported("lengths", id),
+            HirExprKind::Array { .. } => return self.unsupported("arrays", id),
+            HirExprKind::Tuple(_) | HirExprKind::TupleField { .. } => {
+                return self.unsupported("tuples", id)
+            }
+            HirExprKind::Range(_) => return self.unsupported("ranges", id),
+            HirExprKind::Variant(_) => return self.unsupported("enum variants", id),
+            HirExprKind::MethodCall(_) => return self.unsupported("method calls", id),
         };
         let kind = self.intern(kind);
         let ty = ty.unwrap_or_else(|| self.intern(TypeKind::hir_Unknown));
@@ -468,9 +511,21 @@ impl<'ctx> Visit<HirLiteralVal> for DDlogEngine<'ctx> {
             HirLiteralVal::String(string) => Literal::hir_String {
                 r#str: string.to_string(),
             },
-            HirLiteralVal::Rune(_) => todo!(),
-            HirLiteralVal::Float(_) => todo!(),
-            HirLiteralVal::Array { elements: _ } => todo!(),
+
+            // The literal is still given a value so that the rest of the program can be
+            // translated, but it's never handed to ddlog
+            HirLiteralVal::Rune(_) => {
+                self.unsupported.get_or_insert("runes");
+                Literal::hir_Integer { int: 0 }
+            }
+            HirLiteralVal::Float(_) => {
+                self.unsupported.get_or_insert("floats");
+                Literal::hir_Integer { int: 0 }
+            }
+            HirLiteralVal::Array { .. } => {
+                self.unsupported.get_or_insert("arrays");
+                Literal::hir_Integer { int: 0 }
+            }
         }
     }
 }
@@ -588,7 +643,23 @@ impl<'ctx> Visit<HirPattern> for DDlogEngine<'ctx> {
                 let (lit, ty) = self.visit(literal);
                 Pattern::hir_PatLit { lit, ty }
             }
-            HirPattern::Ident(_) | HirPattern::ItemPath(_) | HirPattern::Wildcard => todo!(),
+            // Patterns that match anything are all the same to ddlog
+            HirPattern::Ident(_) | HirPattern::Wildcard => Pattern::hir_Ident,
+
+            // The pattern is still given a value so that the rest of the program can be
+            // translated, but it's never handed to ddlog
+            HirPattern::ItemPath(_)
+            | HirPattern::Bind(..)
+            | HirPattern::Or(_)
+            | HirPattern::Slice(_)
+            | HirPattern::Tuple(_)
+            | HirPattern::Rest
+            | HirPattern::Range { .. }
+            | HirPattern::Variant { .. }
+            | HirPattern::Struct { .. } => {
+                self.unsupported.get_or_insert("destructuring patterns");
+                Pattern::hir_Ident
+            }
         }
     }
 }
@@ -623,6 +694,9 @@ impl<'ctx> Visit<HirBinaryOp> for DDlogEngine<'ctx> {
             HirBinaryOp::BitXor => BinOp::hir_BitXor,
             HirBinaryOp::Shl => BinOp::hir_Shl,
             HirBinaryOp::Shr => BinOp::hir_Shr,
+            HirBinaryOp::And | HirBinaryOp::Or => {
+                unreachable!("short-circuiting operators are desugared during lowering")
+            }
         }
     }
 }
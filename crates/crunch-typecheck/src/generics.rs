@@ -0,0 +1,386 @@
+use core::fmt::{Result as FmtResult, Write};
+use crunch_shared::{
+    context::Context,
+    trees::{
+        hir::{Enum, ItemId, Type, TypeId, TypeKind},
+        ItemPath,
+    },
+    utils::HashMap,
+};
+
+/// Replaces every generic parameter within a type with the type it was instantiated with.
+/// Within trait method signatures `Self` is the unknown type, which is replaced with
+/// `self_type` when it's given. Types that contain nothing to replace are returned as-is,
+/// everything else is copied into a new type
+pub fn substitute(
+    context: &Context<'_>,
+    ty: TypeId,
+    subst: &HashMap<ItemId, TypeId>,
+    self_type: Option<TypeId>,
+) -> TypeId {
+    let Type { kind, loc } = context.get_hir_type(ty).unwrap().clone();
+    let replace = |inner| substitute(context, inner, subst, self_type);
+
+    let kind = match kind {
+        TypeKind::Variable(inner) => {
+            let replaced = replace(inner);
+            if replaced == inner {
+                return ty;
+            }
+
+            return replaced;
+        }
+
+        TypeKind::Generic { id, .. } => return subst.get(&id).copied().unwrap_or(ty),
+        TypeKind::Unknown => return self_type.unwrap_or(ty),
+
+        TypeKind::Array { element, length } => {
+            let replaced = replace(element);
+            if replaced == element {
+                return ty;
+            }
+
+            TypeKind::Array {
+                element: replaced,
+                length,
+            }
+        }
+
+        TypeKind::Slice { element } => {
+            let replaced = replace(element);
+            if replaced == element {
+                return ty;
+            }
+
+            TypeKind::Slice { element: replaced }
+        }
+
+        TypeKind::Tuple(elements) => {
+            let replaced: Vec<TypeId> = elements.iter().map(|&element| replace(element)).collect();
+            if replaced == elements {
+                return ty;
+            }
+
+            TypeKind::Tuple(replaced)
+        }
+
+        TypeKind::Range { element } => {
+            let replaced = replace(element);
+            if replaced == element {
+                return ty;
+            }
+
+            TypeKind::Range { element: replaced }
+        }
+
+        TypeKind::Reference { referee, mutable } => {
+            let replaced = replace(referee);
+            if replaced == referee {
+                return ty;
+            }
+
+            TypeKind::Reference {
+                referee: replaced,
+                mutable,
+            }
+        }
+
+        TypeKind::Pointer { pointee, mutable } => {
+            let replaced = replace(pointee);
+            if replaced == pointee {
+                return ty;
+            }
+
+            TypeKind::Pointer {
+                pointee: replaced,
+                mutable,
+            }
+        }
+
+        TypeKind::Enum { id, generics } => {
+            let replaced: Vec<TypeId> = generics.iter().map(|&generic| replace(generic)).collect();
+            if replaced == generics {
+                return ty;
+            }
+
+            TypeKind::Enum {
+                id,
+                generics: replaced,
+            }
+        }
+
+        TypeKind::Func { params, ret } => {
+            let replaced: Vec<TypeId> = params.iter().map(|&param| replace(param)).collect();
+            let replaced_ret = replace(ret);
+            if replaced == params && replaced_ret == ret {
+                return ty;
+            }
+
+            TypeKind::Func {
+                params: replaced,
+                ret: replaced_ret,
+            }
+        }
+
+        TypeKind::Union(types) => {
+            let replaced: Vec<TypeId> = types.iter().map(|&operand| replace(operand)).collect();
+            if replaced == types {
+                return ty;
+            }
+
+            TypeKind::Union(replaced)
+        }
+
+        TypeKind::Intersection(types) => {
+            let replaced: Vec<TypeId> = types.iter().map(|&operand| replace(operand)).collect();
+            if replaced == types {
+                return ty;
+            }
+
+            TypeKind::Intersection(replaced)
+        }
+
+        TypeKind::Not(inner) => {
+            let replaced = replace(inner);
+            if replaced == inner {
+                return ty;
+            }
+
+            TypeKind::Not(replaced)
+        }
+
+        TypeKind::Const { name, ty: inner } => {
+            let replaced = replace(inner);
+            if replaced == inner {
+                return ty;
+            }
+
+            TypeKind::Const { name, ty: replaced }
+        }
+
+        TypeKind::Integer { .. }
+        | TypeKind::String
+        | TypeKind::Bool
+        | TypeKind::Unit
+        | TypeKind::Absurd
+        | TypeKind::Rune
+        | TypeKind::Float { .. }
+        | TypeKind::Trait(_) => return ty,
+    };
+
+    context.hir_type(Type::new(kind, loc))
+}
+
+/// Gives every instantiation of a generic item its own name, `module.func[i32, bool]`
+pub fn mangle(
+    context: &Context<'_>,
+    enums: &HashMap<ItemId, Enum>,
+    name: &ItemPath,
+    generics: &[TypeId],
+) -> ItemPath {
+    let strings = context.strings();
+    let (&last, module) = name.split_last().expect("Paths are never empty");
+
+    let mut mangled = strings.resolve(last).as_ref().to_owned();
+    mangled.push('[');
+    for (i, &generic) in generics.iter().enumerate() {
+        if i != 0 {
+            mangled.push_str(", ");
+        }
+
+        display_type_inner(
+            context,
+            enums,
+            &context.get_hir_type(generic).unwrap().kind,
+            &mut mangled,
+        )
+        .expect("Failed to format type");
+    }
+    mangled.push(']');
+
+    ItemPath::new(module.to_vec()).join(strings.intern(mangled))
+}
+
+// TODO: Caching
+/// Formats a type the way it's written in source code
+pub fn display_type(context: &Context<'_>, enums: &HashMap<ItemId, Enum>, ty: &TypeKind) -> String {
+    let mut string = String::new();
+    display_type_inner(context, enums, ty, &mut string).expect("Failed to format type");
+
+    string
+}
+
+fn display_type_inner<W: Write>(
+    context: &Context<'_>,
+    enums: &HashMap<ItemId, Enum>,
+    ty: &TypeKind,
+    f: &mut W,
+) -> FmtResult {
+    let inner = |inner: TypeId, out: &mut W| {
+        display_type_inner(
+            context,
+            enums,
+            &context.get_hir_type(inner).unwrap().kind,
+            out,
+        )
+    };
+
+    match ty {
+        &TypeKind::Variable(ty) => inner(ty, f),
+        TypeKind::Unknown => f.write_str("infer"),
+        &TypeKind::Integer { signed, width } => match (signed, width) {
+            (Some(signed), Some(width)) => {
+                write!(f, "{}{}", if signed { "i" } else { "u" }, width)
+            }
+            (_, _) => f.write_str("{{integer}}"),
+        },
+        TypeKind::String => f.write_str("str"),
+        TypeKind::Bool => f.write_str("bool"),
+        TypeKind::Unit => f.write_str("unit"),
+        TypeKind::Absurd => f.write_str("absurd"),
+
+        &TypeKind::Array { element, length } => {
+            f.write_str("arr[")?;
+            inner(element, f)?;
+            f.write_str("; ")?;
+            write!(f, "{}", length)?;
+            f.write_char(']')
+        }
+
+        &TypeKind::Slice { element } => {
+            f.write_str("slice[")?;
+            inner(element, f)?;
+            f.write_char(']')
+        }
+
+        TypeKind::Tuple(elements) => {
+            f.write_str("tup[")?;
+            for (i, &element) in elements.iter().enumerate() {
+                if i != 0 {
+                    f.write_str(", ")?;
+                }
+
+                inner(element, f)?;
+            }
+            f.write_char(']')
+        }
+
+        &TypeKind::Range { element } => {
+            f.write_str("range[")?;
+            inner(element, f)?;
+            f.write_char(']')
+        }
+
+        &TypeKind::Reference { mutable, referee } => {
+            f.write_char('&')?;
+            if mutable {
+                f.write_str("mut ")?;
+            }
+
+            inner(referee, f)
+        }
+
+        &TypeKind::Pointer { mutable, pointee } => {
+            if mutable {
+                f.write_str("*const ")?;
+            } else {
+                f.write_str("*mut ")?;
+            }
+
+            inner(pointee, f)
+        }
+
+        TypeKind::Enum { id, generics } => {
+            match enums.get(id) {
+                Some(enum_) => f.write_str(&enum_.name.to_string(context.strings()))?,
+                None => f.write_str("enum")?,
+            }
+
+            if !generics.is_empty() {
+                f.write_char('[')?;
+                for (i, &generic) in generics.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(", ")?;
+                    }
+
+                    inner(generic, f)?;
+                }
+                f.write_char(']')?;
+            }
+
+            Ok(())
+        }
+
+        &TypeKind::Generic { name, .. } => f.write_str(context.strings().resolve(name).as_ref()),
+        TypeKind::Rune => f.write_str("rune"),
+        &TypeKind::Float { width } => write!(f, "f{}", width),
+
+        TypeKind::Func { params, ret } => {
+            f.write_str("fn(")?;
+            for (i, &param) in params.iter().enumerate() {
+                if i != 0 {
+                    f.write_str(", ")?;
+                }
+
+                inner(param, f)?;
+            }
+            f.write_str(") -> ")?;
+            inner(*ret, f)
+        }
+
+        TypeKind::Trait(traits) => match &traits[..] {
+            [(_, name)] => f.write_str(&name.to_string(context.strings())),
+            traits => {
+                f.write_str("type { ")?;
+                for (i, (_, name)) in traits.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(", ")?;
+                    }
+
+                    f.write_str(&name.to_string(context.strings()))?;
+                }
+                f.write_str(" }")
+            }
+        },
+
+        TypeKind::Union(types) | TypeKind::Intersection(types) => {
+            let op = if matches!(ty, TypeKind::Union(_)) {
+                " | "
+            } else {
+                " & "
+            };
+
+            for (i, &operand) in types.iter().enumerate() {
+                if i != 0 {
+                    f.write_str(op)?;
+                }
+
+                // Nested operators are parenthesized to keep their grouping
+                let mut kind = &context.get_hir_type(operand).unwrap().kind;
+                while let &TypeKind::Variable(variable) = kind {
+                    kind = &context.get_hir_type(variable).unwrap().kind;
+                }
+
+                if matches!(kind, TypeKind::Union(_) | TypeKind::Intersection(_)) {
+                    f.write_char('(')?;
+                    inner(operand, f)?;
+                    f.write_char(')')?;
+                } else {
+                    inner(operand, f)?;
+                }
+            }
+
+            Ok(())
+        }
+
+        &TypeKind::Not(ty) => {
+            f.write_char('!')?;
+            inner(ty, f)
+        }
+
+        &TypeKind::Const { name, ty } => {
+            write!(f, "const {}: ", context.strings().resolve(name).as_ref())?;
+            inner(ty, f)
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use crunch_shared::{
+    context::Context,
+    strings::StrT,
+    trees::hir::{ExtendBlock, Function, Item, ItemId, Trait, TypeId, TypeKind},
+    utils::{HashMap, Hasher},
+};
+
+/// The methods given to types by `extend` blocks, used to statically resolve method calls
+#[derive(Debug, Clone)]
+pub struct Impls<'ctx> {
+    traits: HashMap<ItemId, Trait<'ctx>>,
+    extensions: Vec<ExtendBlock<'ctx>>,
+}
+
+/// The reasons a method call can fail to resolve
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MethodError {
+    /// The type has no method by that name
+    Unknown,
+    /// More than one of the type's traits has a method by that name
+    Ambiguous,
+}
+
+impl<'ctx> Impls<'ctx> {
+    pub fn new() -> Self {
+        Self {
+            traits: HashMap::with_hasher(Hasher::default()),
+            extensions: Vec::new(),
+        }
+    }
+
+    /// Collects every trait and `extend` block within the given items
+    pub fn from_items(items: &[&Item<'ctx>]) -> Self {
+        let mut impls = Self::new();
+        for item in items {
+            match item {
+                Item::Trait(trait_) => impls.add_trait(trait_),
+                Item::ExtendBlock(extend) => impls.add_extension(extend),
+                _ => {}
+            }
+        }
+
+        impls
+    }
+
+    pub fn add_trait(&mut self, trait_: &Trait<'ctx>) {
+        self.traits.insert(trait_.id, trait_.clone());
+    }
+
+    pub fn add_extension(&mut self, extend: &ExtendBlock<'ctx>) {
+        self.extensions.push(extend.clone());
+    }
+
+    pub fn get_trait(&self, id: ItemId) -> Option<&Trait<'ctx>> {
+        self.traits.get(&id)
+    }
+
+    /// Finds the `extend` block implementing `extender` for the target type, or the type's
+    /// own methods when `extender` is `None`
+    pub fn extensions_of(
+        &self,
+        context: &Context<'_>,
+        target: TypeId,
+        extender: Option<ItemId>,
+    ) -> Vec<&ExtendBlock<'ctx>> {
+        self.extensions
+            .iter()
+            .filter(|extend| {
+                extend.extender == extender && same_type(context, extend.target, target)
+            })
+            .collect()
+    }
+
+    /// Resolves the method a call on a value of the receiver's type refers to. The type's
+    /// own methods take priority over the methods of its traits, and methods of a
+    /// reference's referee can be called through the reference. Only the methods of
+    /// `trait_` are considered if it's given
+    pub fn resolve<'a>(
+        &'a self,
+        context: &Context<'_>,
+        receiver: TypeId,
+        method: StrT,
+        trait_: Option<ItemId>,
+    ) -> Result<&'a Function<'ctx>, MethodError> {
+        match self.resolve_exact(context, receiver, method, trait_) {
+            Err(MethodError::Unknown) => match concrete_kind(context, receiver) {
+                TypeKind::Reference { referee, .. } => {
+                    self.resolve_exact(context, referee, method, trait_)
+                }
+                _ => Err(MethodError::Unknown),
+            },
+
+            resolved => resolved,
+        }
+    }
+
+    fn resolve_exact<'a>(
+        &'a self,
+        context: &Context<'_>,
+        receiver: TypeId,
+        method: StrT,
+        trait_: Option<ItemId>,
+    ) -> Result<&'a Function<'ctx>, MethodError> {
+        if trait_.is_some() {
+            return self
+                .extensions_of(context, receiver, trait_)
+                .into_iter()
+                .flat_map(|extend| extend.methods.iter())
+                .find(|func| func.name.last() == Some(&method))
+                .ok_or(MethodError::Unknown);
+        }
+
+        if let Some(func) = self
+            .extensions_of(context, receiver, None)
+            .into_iter()
+            .flat_map(|extend| extend.methods.iter())
+            .find(|func| func.name.last() == Some(&method))
+        {
+            return Ok(func);
+        }
+
+        let mut candidates = self
+            .extensions
+            .iter()
+            .filter(|extend| {
+                extend.extender.is_some() && same_type(context, extend.target, receiver)
+            })
+            .flat_map(|extend| extend.methods.iter())
+            .filter(|func| func.name.last() == Some(&method));
+
+        match (candidates.next(), candidates.next()) {
+            (Some(func), None) => Ok(func),
+            (Some(_), Some(_)) => Err(MethodError::Ambiguous),
+            (None, _) => Err(MethodError::Unknown),
+        }
+    }
+}
+
+impl Default for Impls<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Follows type variables until a concrete type is found
+fn concrete_kind(context: &Context<'_>, ty: TypeId) -> TypeKind {
+    let mut kind = context
+        .get_hir_type(ty)
+        .expect("Type ids always refer to interned types")
+        .kind
+        .clone();
+    while let TypeKind::Variable(inner) = kind {
+        kind = context
+            .get_hir_type(inner)
+            .expect("Type ids always refer to interned types")
+            .kind
+            .clone();
+    }
+
+    kind
+}
+
+/// Returns `true` if both types are structurally identical
+pub fn same_type(context: &Context<'_>, left: TypeId, right: TypeId) -> bool {
+    same_kind(
+        context,
+        concrete_kind(context, left),
+        concrete_kind(context, right),
+        None,
+    )
+}
+
+/// Returns `true` if a type in a trait method's signature matches the one given by an
+/// implementation of it, where `Self` is the unknown type and stands in for `self_type`
+pub fn matches_trait_type(
+    context: &Context<'_>,
+    trait_ty: TypeId,
+    impl_ty: TypeId,
+    self_type: TypeId,
+) -> bool {
+    same_kind(
+        context,
+        concrete_kind(context, trait_ty),
+        concrete_kind(context, impl_ty),
+        Some(self_type),
+    )
+}
+
+fn same_kind(
+    context: &Context<'_>,
+    left: TypeKind,
+    right: TypeKind,
+    self_type: Option<TypeId>,
+) -> bool {
+    let same = |left, right| {
+        same_kind(
+            context,
+            concrete_kind(context, left),
+            concrete_kind(context, right),
+            self_type,
+        )
+    };
+
+    if let (TypeKind::Unknown, Some(self_type)) = (&left, self_type) {
+        return same_kind(context, concrete_kind(context, self_type), right, None);
+    }
+
+    match (left, right) {
+        (
+            TypeKind::Array {
+                element: left,
+                length: left_len,
+            },
+            TypeKind::Array {
+                element: right,
+                length: right_len,
+            },
+        ) => left_len == right_len && same(left, right),
+
+        (TypeKind::Slice { element: left }, TypeKind::Slice { element: right })
+        | (TypeKind::Range { element: left }, TypeKind::Range { element: right }) => {
+            same(left, right)
+        }
+
+        (
+            TypeKind::Reference {
+                referee: left,
+                mutable: left_mut,
+            },
+            TypeKind::Reference {
+                referee: right,
+                mutable: right_mut,
+            },
+        )
+        | (
+            TypeKind::Pointer {
+                pointee: left,
+                mutable: left_mut,
+            },
+            TypeKind::Pointer {
+                pointee: right,
+                mutable: right_mut,
+            },
+        ) => left_mut == right_mut && same(left, right),
+
+        (
+            TypeKind::Enum {
+                id: left_id,
+                generics: left_generics,
+            },
+            TypeKind::Enum {
+                id: right_id,
+                generics: right_generics,
+            },
+        ) => {
+            left_id == right_id
+                && left_generics.len() == right_generics.len()
+                && left_generics
+                    .into_iter()
+                    .zip(right_generics)
+                    .all(|(left_generic, right_generic)| same(left_generic, right_generic))
+        }
+
+        (TypeKind::Tuple(left), TypeKind::Tuple(right)) => {
+            left.len() == right.len()
+                && left
+                    .into_iter()
+                    .zip(right)
+                    .all(|(left_elem, right_elem)| same(left_elem, right_elem))
+        }
+
+        (left, right) => left == right,
+    }
+}
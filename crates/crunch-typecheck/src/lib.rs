@@ -10,23 +10,36 @@
 extern crate alloc;
 
 mod ddlog;
+mod generics;
+mod impls;
+
+#[cfg(test)]
+mod tests;
+
+pub use generics::{display_type, mangle, substitute};
+pub use impls::{Impls, MethodError};
 
 use alloc::sync::Arc;
-use core::fmt::{self, Result as FmtResult, Write};
+use core::{fmt, mem};
 use crunch_shared::{
     context::ContextDatabase,
-    error::{ErrorHandler, Locatable, Location, Span, TypeError, TypeResult},
+    error::{
+        Error, ErrorHandler, Locatable, Location, SemanticError, Span, Suggestion, TypeError,
+        TypeResult,
+    },
     files::{FileCache, FileId},
     salsa, tracing,
     trees::{
         hir::{
-            BinaryOp, Block, Break, Cast, CompOp, Expr, ExternFunc, FuncArg, FuncCall, Function,
-            Item, Literal, LiteralVal, Match, Pattern, Reference, Return, Stmt, Type, TypeId,
-            TypeKind, Var, VarDecl,
+            ArgMismatch, ArgValue, BinaryOp, Block, Break, Cast, CompOp, Enum, Expr, ExprKind,
+            ExtendBlock, ExternFunc, FormatSegment, FuncArg, FuncCall, Function, Generic, Integer,
+            Item, ItemId, Literal, LiteralVal, Loop, Match, MethodCall, Pattern, Place, PlaceKind,
+            Range, Reference, Return, Stmt, Trait, Type, TypeId, TypeKind, UnaryOp, Var, VarDecl,
+            VariantCtor,
         },
         ItemPath,
     },
-    utils::{HashMap, Hasher},
+    utils::{self, HashMap, Hasher},
     visitors::hir::{ExprVisitor, ItemVisitor, StmtVisitor},
 };
 use ladder::HirDatabase;
@@ -73,9 +86,10 @@ fn typecheck(db: &dyn TypecheckDatabase, file: FileId) -> Result<(), ArcError> {
 }
 
 #[derive(Debug, Clone)]
-struct Func {
+struct Func<'ctx> {
     ret: TypeId,
-    args: Vec<TypeId>,
+    args: Vec<FuncArg<'ctx>>,
+    generics: Vec<Generic>,
     arg_span: Location,
     sig: Location,
 }
@@ -84,9 +98,19 @@ struct Func {
 #[derive(Clone)]
 pub struct Engine<'ctx> {
     errors: ErrorHandler,
-    current_func: Option<Func>,
-    functions: HashMap<ItemPath, Func>,
-    variables: Vec<HashMap<Var, TypeId>>,
+    current_func: Option<Func<'ctx>>,
+    functions: HashMap<ItemPath, Func<'ctx>>,
+    enums: HashMap<ItemId, Enum>,
+    generics: HashMap<ItemId, Generic>,
+    /// The generics instantiated within the current function and the types they were given,
+    /// which are checked against their bounds once the function's body has been
+    instances: Vec<(TypeId, Generic, Location)>,
+    impls: Impls<'ctx>,
+    /// The type of every variable in scope along with whether it's mutable
+    variables: Vec<HashMap<Var, (TypeId, bool)>>,
+    /// The types of the loops enclosing the current expression, which the values of any
+    /// `break`s are unified with, along with whether they've been broken out of
+    loops: Vec<(TypeId, bool)>,
     check: Option<TypeId>,
     db: &'ctx dyn TypecheckDatabase,
 }
@@ -99,7 +123,12 @@ impl<'ctx> Engine<'ctx> {
             errors: ErrorHandler::default(),
             current_func: None,
             functions: HashMap::with_hasher(Hasher::default()),
+            enums: HashMap::with_hasher(Hasher::default()),
+            generics: HashMap::with_hasher(Hasher::default()),
+            instances: Vec::new(),
+            impls: Impls::new(),
             variables: Vec::new(),
+            loops: Vec::new(),
             check: None,
             db,
         }
@@ -113,19 +142,50 @@ impl<'ctx> Engine<'ctx> {
             .iter()
             .rev()
             .find_map(|vars| vars.get(var))
-            .copied()
+            .map(|&(ty, _)| ty)
             .ok_or_else(|| {
-                Locatable::new(
-                    TypeError::VarNotInScope(var.to_string(self.db.context().strings())).into(),
-                    loc,
-                )
+                let strings = self.db.context().strings();
+                let name = var.to_string(strings);
+                let mut error: Error = TypeError::VarNotInScope(name.clone()).into();
+
+                let in_scope: Vec<String> = self
+                    .variables
+                    .iter()
+                    .flat_map(|vars| vars.keys())
+                    .filter(|var| matches!(var, Var::User(_)))
+                    .map(|var| var.to_string(strings))
+                    .collect();
+                if let Some(similar) =
+                    utils::closest_match(&name, in_scope.iter().map(String::as_str))
+                {
+                    error = error.with_suggestion(
+                        Suggestion::new(format!("did you mean '{}'?", similar))
+                            .with_edit(loc, similar),
+                    );
+                }
+
+                Locatable::new(error, loc)
             })
     }
 
-    fn insert_variable(&mut self, var: Var, type_id: TypeId) {
+    /// Returns `true` if the variable was declared as mutable
+    fn var_mutable(&self, var: &Var) -> bool {
+        self.variables
+            .iter()
+            .rev()
+            .find_map(|vars| vars.get(var))
+            .map_or(false, |&(_, mutable)| mutable)
+    }
+
+    fn insert_variable(&mut self, var: Var, type_id: TypeId, mutable: bool) {
         crunch_shared::trace!("inserting a variable {:?} with the type {:?}", var, type_id);
 
-        if let Some(old_type) = self.variables.last_mut().unwrap().insert(var, type_id) {
+        if let Some((old_type, _)) = self
+            .variables
+            .last_mut()
+            .unwrap()
+            .insert(var, (type_id, mutable))
+        {
             crunch_shared::warn!(
                 "The variable {:?} previously had the type {:?} but it was overwritten with {:?}",
                 var,
@@ -198,8 +258,8 @@ impl<'ctx> Engine<'ctx> {
                 return Ok(());
             }
 
-            match (left_ty.kind, right_ty.kind) {
-                (TypeKind::Variable(l), TypeKind::Variable(r)) => {
+            match (&left_ty.kind, &right_ty.kind) {
+                (&TypeKind::Variable(l), &TypeKind::Variable(r)) => {
                     left_ty = self.db.context().get_hir_type(l).unwrap();
                     right_ty = self.db.context().get_hir_type(r).unwrap();
                 }
@@ -215,7 +275,7 @@ impl<'ctx> Engine<'ctx> {
             }
         }
 
-        match (left_ty.kind, right_ty.kind) {
+        match (left_ty.kind.clone(), right_ty.kind.clone()) {
             // FIXME: Remove this once cycle detection works
             (TypeKind::Variable(left), _) => self.unify(left, right),
             (_, TypeKind::Variable(right)) => self.unify(left, right),
@@ -248,7 +308,8 @@ impl<'ctx> Engine<'ctx> {
             }
             (TypeKind::String, TypeKind::String)
             | (TypeKind::Bool, TypeKind::Bool)
-            | (TypeKind::Unit, TypeKind::Unit) => {
+            | (TypeKind::Unit, TypeKind::Unit)
+            | (TypeKind::Rune, TypeKind::Rune) => {
                 crunch_shared::trace!(
                     target: "type_unification",
                     "identical primitives, unifying",
@@ -256,6 +317,35 @@ impl<'ctx> Engine<'ctx> {
                 Ok(())
             }
 
+            (
+                TypeKind::Enum {
+                    id: left_enum,
+                    generics: left_generics,
+                },
+                TypeKind::Enum {
+                    id: right_enum,
+                    generics: right_generics,
+                },
+            ) if left_enum == right_enum && left_generics.len() == right_generics.len() => {
+                crunch_shared::trace!(
+                    target: "type_unification",
+                    "identical enums, unifying their generics",
+                );
+
+                for (left, right) in left_generics.into_iter().zip(right_generics) {
+                    self.unify(left, right)?;
+                }
+
+                Ok(())
+            }
+
+            (TypeKind::Generic { id: left_id, .. }, TypeKind::Generic { id: right_id, .. })
+                if left_id == right_id =>
+            {
+                crunch_shared::trace!(target: "type_unification", "identical generics, unifying");
+                Ok(())
+            }
+
             (
                 TypeKind::Integer {
                     signed: signed_a,
@@ -317,6 +407,20 @@ impl<'ctx> Engine<'ctx> {
                     _ => {}
                 }
 
+                // Keep whatever each side knows so that unifying a concrete integer with an
+                // integer literal doesn't forget the concrete one's signedness or width
+                let merged = TypeKind::Integer {
+                    signed: signed_b.or(signed_a),
+                    width: width_b.or(width_a),
+                };
+                if merged != right_ty.kind {
+                    let merged = self
+                        .db
+                        .context()
+                        .hir_type(Type::new(merged, right_ty.location()));
+                    self.db.context().overwrite_hir_type(right, merged);
+                }
+
                 let ty = self
                     .db
                     .context()
@@ -345,6 +449,32 @@ impl<'ctx> Engine<'ctx> {
                 Ok(())
             }
 
+            (TypeKind::Tuple(left_elems), TypeKind::Tuple(right_elems))
+                if left_elems.len() == right_elems.len() =>
+            {
+                crunch_shared::trace!(
+                    target: "type_unification",
+                    "tuple lengths are equal, unifying element types",
+                );
+
+                for (left_elem, right_elem) in left_elems.into_iter().zip(right_elems) {
+                    self.unify(left_elem, right_elem)?;
+                }
+
+                Ok(())
+            }
+
+            (TypeKind::Slice { element: left }, TypeKind::Slice { element: right })
+            | (TypeKind::Range { element: left }, TypeKind::Range { element: right }) => {
+                crunch_shared::trace!(
+                    target: "type_unification",
+                    "unifying slice/range element types",
+                );
+                self.unify(left, right)?;
+
+                Ok(())
+            }
+
             (
                 TypeKind::Reference {
                     referee: left,
@@ -374,6 +504,71 @@ impl<'ctx> Engine<'ctx> {
                 Ok(())
             }
 
+            (TypeKind::Float { width: left }, TypeKind::Float { width: right })
+                if left == right =>
+            {
+                crunch_shared::trace!(target: "type_unification", "identical floats, unifying");
+                Ok(())
+            }
+
+            (TypeKind::Trait(left), TypeKind::Trait(right)) if left == right => {
+                crunch_shared::trace!(target: "type_unification", "identical traits, unifying");
+                Ok(())
+            }
+
+            (
+                TypeKind::Func {
+                    params: left_params,
+                    ret: left_ret,
+                },
+                TypeKind::Func {
+                    params: right_params,
+                    ret: right_ret,
+                },
+            ) if left_params.len() == right_params.len() => {
+                crunch_shared::trace!(
+                    target: "type_unification",
+                    "parameter counts are equal, unifying parameter and return types",
+                );
+
+                for (left_param, right_param) in left_params.into_iter().zip(right_params) {
+                    self.unify(left_param, right_param)?;
+                }
+
+                self.unify(left_ret, right_ret)
+            }
+
+            (TypeKind::Union(left_types), TypeKind::Union(right_types))
+            | (TypeKind::Intersection(left_types), TypeKind::Intersection(right_types))
+                if left_types.len() == right_types.len() =>
+            {
+                crunch_shared::trace!(
+                    target: "type_unification",
+                    "unifying the operands of type operators",
+                );
+
+                for (left_type, right_type) in left_types.into_iter().zip(right_types) {
+                    self.unify(left_type, right_type)?;
+                }
+
+                Ok(())
+            }
+
+            (
+                TypeKind::Const {
+                    name: left_name,
+                    ty: left_const,
+                },
+                TypeKind::Const {
+                    name: right_name,
+                    ty: right_const,
+                },
+            ) if left_name == right_name => self.unify(left_const, right_const),
+
+            (TypeKind::Not(left_inner), TypeKind::Not(right_inner)) => {
+                self.unify(left_inner, right_inner)
+            }
+
             // If no previous attempts to unify were successful, raise an error
             (call_type, def_type) => {
                 crunch_shared::error!(
@@ -404,82 +599,59 @@ impl<'ctx> Engine<'ctx> {
                 match item {
                     &&Item::Function(Function {
                         ref name,
+                        ref generics,
                         ref args,
                         ret,
                         sig,
                         ..
-                    })
-                    | &&Item::ExternFunc(ExternFunc {
+                    }) => builder.declare_function(name, generics, args, ret, sig),
+
+                    &&Item::ExternFunc(ExternFunc {
                         ref name,
                         ref args,
                         ret,
                         loc: sig,
                         ..
-                    }) => {
-                        // TODO: Use error types as fillers here if they're unknown
-                        for arg in args.iter() {
-                            let is_unknown = builder
-                                .db
-                                .context()
-                                .get_hir_type(arg.kind)
-                                .unwrap()
-                                .is_unknown();
-
-                            if is_unknown {
-                                crunch_shared::error!(
-                                    "the function {:?} is missing a function argument type",
-                                    name.to_string(builder.db.context().strings()),
-                                );
-
-                                builder.errors.push_err(Locatable::new(
-                                    TypeError::MissingType(
-                                        "Types for function arguments".to_owned(),
-                                    )
-                                    .into(),
-                                    arg.location(),
-                                ));
-                            }
-                        }
-
-                        // TODO: Use error types as fillers here if they're unknown
-                        let ret_ty = builder.db.context().get_hir_type(ret).unwrap();
-                        if ret_ty.kind.is_unknown() {
-                            crunch_shared::error!(
-                                "the function {:?} is missing a return type",
-                                name.to_string(builder.db.context().strings()),
-                            );
-
-                            builder.errors.push_err(Locatable::new(
-                                TypeError::MissingType("Return types for functions".to_owned())
-                                    .into(),
-                                ret_ty.location(),
-                            ));
-                        }
+                    }) => builder.declare_function(name, &[], args, ret, sig),
 
-                        // TODO: Use error types as fillers here if they're unknown
-                        let arg_span = args.location();
-                        let args: Vec<TypeId> = args
-                            .iter()
-                            .map(|&FuncArg { name, kind, .. }| {
-                                builder.insert_variable(name, kind);
-                                kind
-                            })
-                            .collect();
+                    Item::Enum(enum_) => {
+                        crunch_shared::trace!(
+                            "inserting an enum into the builder: {:?}",
+                            enum_.name.to_string(builder.db.context().strings()),
+                        );
 
-                        let func = Func {
-                            ret,
-                            args,
-                            arg_span,
-                            sig,
-                        };
+                        builder.declare_generics(&enum_.generics);
+                        builder.enums.insert(enum_.id, enum_.clone());
+                    }
 
+                    Item::Trait(trait_) => {
                         crunch_shared::trace!(
-                            "inserting a function into the builder: {:?}",
-                            name.to_string(builder.db.context().strings()),
+                            "inserting a trait into the builder: {:?}",
+                            trait_.name.to_string(builder.db.context().strings()),
                         );
 
-                        builder.functions.insert(name.clone(), func);
+                        builder.impls.add_trait(trait_);
+                    }
+
+                    // Traits need to be known before their implementations can be checked
+                    Item::ExtendBlock(..) => {}
+                }
+            }
+
+            for item in items.iter() {
+                if let Item::ExtendBlock(extend) = item {
+                    for method in extend.methods.iter() {
+                        builder.declare_function(
+                            &method.name,
+                            &method.generics,
+                            &method.args,
+                            method.ret,
+                            method.sig,
+                        );
                     }
+
+                    builder.check_extension(extend);
+                    builder.impls.add_extension(extend);
                 }
             }
 
@@ -505,6 +677,376 @@ impl<'ctx> Engine<'ctx> {
         })
     }
 
+    /// Records the signature of a function so that calls to it can be checked before its
+    /// body has been
+    fn declare_function(
+        &mut self,
+        name: &ItemPath,
+        generics: &[Generic],
+        args: &Locatable<Vec<FuncArg<'ctx>>>,
+        ret: TypeId,
+        sig: Location,
+    ) {
+        // TODO: Use error types as fillers here if they're unknown
+        for arg in args.iter() {
+            let is_unknown = self
+                .db
+                .context()
+                .get_hir_type(arg.kind)
+                .unwrap()
+                .is_unknown();
+
+            if is_unknown {
+                crunch_shared::error!(
+                    "the function {:?} is missing a function argument type",
+                    name.to_string(self.db.context().strings()),
+                );
+
+                self.errors.push_err(Locatable::new(
+                    TypeError::MissingType("Types for function arguments".to_owned()).into(),
+                    arg.location(),
+                ));
+            }
+        }
+
+        // TODO: Use error types as fillers here if they're unknown
+        let ret_ty = self
+            .db
+            .context()
+            .get_hir_type(ret)
+            .expect("Type ids always refer to interned types");
+        if ret_ty.kind.is_unknown() {
+            crunch_shared::error!(
+                "the function {:?} is missing a return type",
+                name.to_string(self.db.context().strings()),
+            );
+
+            self.errors.push_err(Locatable::new(
+                TypeError::MissingType("Return types for functions".to_owned()).into(),
+                ret_ty.location(),
+            ));
+        }
+
+        let signature = args
+            .iter()
+            .map(|arg| (arg.kind, arg.location()))
+            .chain(Some((ret, ret_ty.location())));
+        for (ty, loc) in signature {
+            if let Err(err) = self.check_supported(ty, loc) {
+                self.errors.push_err(err);
+            }
+        }
+
+        // TODO: Use error types as fillers here if they're unknown
+        let arg_span = args.location();
+        let args: Vec<FuncArg<'ctx>> = args
+            .iter()
+            .map(|&arg| {
+                self.insert_variable(arg.name, arg.kind, false);
+                arg
+            })
+            .collect();
+
+        self.declare_generics(generics);
+        let func = Func {
+            ret,
+            args,
+            generics: generics.to_vec(),
+            arg_span,
+            sig,
+        };
+
+        crunch_shared::trace!(
+            "inserting a function into the builder: {:?}",
+            name.to_string(self.db.context().strings()),
+        );
+
+        self.functions.insert(name.clone(), func);
+    }
+
+    fn declare_generics(&mut self, generics: &[Generic]) {
+        for generic in generics {
+            self.generics.insert(generic.id, generic.clone());
+        }
+    }
+
+    /// Creates a copy of a generic function's signature where each of its generics are
+    /// replaced by the types they were given, recording the bounds they must satisfy
+    fn instantiate(
+        &mut self,
+        func: &Func<'ctx>,
+        name: &ItemPath,
+        generics: &[TypeId],
+        loc: Location,
+    ) -> TypeResult<Func<'ctx>> {
+        if func.generics.len() != generics.len() {
+            return Err(Locatable::new(
+                SemanticError::GenericArity {
+                    name: name.to_string(self.db.context().strings()),
+                    expected: func.generics.len(),
+                    received: generics.len(),
+                }
+                .into(),
+                loc,
+            ));
+        }
+
+        let context = self.db.context();
+        let mut subst = HashMap::with_hasher(Hasher::default());
+        for (generic, &ty) in func.generics.iter().zip(generics) {
+            subst.insert(generic.id, ty);
+            self.instances.push((ty, generic.clone(), loc));
+        }
+
+        Ok(Func {
+            ret: generics::substitute(context, func.ret, &subst, None),
+            args: func
+                .args
+                .iter()
+                .map(|arg| FuncArg {
+                    kind: generics::substitute(context, arg.kind, &subst, None),
+                    ..*arg
+                })
+                .collect(),
+            generics: Vec::new(),
+            arg_span: func.arg_span,
+            sig: func.sig,
+        })
+    }
+
+    /// Checks that the types given to generics were inferred and implement the traits
+    /// they're bound by
+    fn check_instances(&mut self, instances: Vec<(TypeId, Generic, Location)>) {
+        let context = self.db.context();
+        let strings = context.strings();
+
+        for (ty, generic, loc) in instances {
+            if self.concrete_type(ty).is_unknown() {
+                self.errors.push_err(Locatable::new(
+                    TypeError::UninferredGeneric(strings.resolve(generic.name).as_ref().to_owned())
+                        .into(),
+                    loc,
+                ));
+
+                continue;
+            }
+
+            for &bound in generic.bounds.iter() {
+                let satisfied = match self.concrete_type(ty).kind {
+                    // Generics satisfy the bounds they were declared with
+                    TypeKind::Generic { id, .. } => self
+                        .generics
+                        .get(&id)
+                        .map_or(false, |generic| generic.bounds.contains(&bound)),
+
+                    _ => !self
+                        .impls
+                        .extensions_of(context, ty, Some(bound))
+                        .is_empty(),
+                };
+
+                if satisfied {
+                    continue;
+                }
+
+                let trait_ = self
+                    .impls
+                    .get_trait(bound)
+                    .expect("Traits are resolved during lowering")
+                    .name
+                    .to_string(strings);
+
+                self.errors.push_err(Locatable::new(
+                    TypeError::UnsatisfiedBound {
+                        ty: self.display_type(&self.concrete_type(ty).kind),
+                        trait_,
+                        def_site: generic.loc,
+                    }
+                    .into(),
+                    loc,
+                ));
+            }
+        }
+    }
+
+    /// Finds a method of the traits bounding a generic, with `Self` standing in for the
+    /// generic's type
+    fn generic_method(&self, receiver: TypeId, method: &str) -> Option<(ItemPath, Func<'ctx>)> {
+        let mut ty = self.concrete_type(receiver);
+        while let TypeKind::Reference { referee, .. } = ty.kind {
+            ty = self.concrete_type(referee);
+        }
+
+        let generic = match ty.kind {
+            TypeKind::Generic { id, .. } => self.generics.get(&id)?,
+            _ => return None,
+        };
+
+        let context = self.db.context();
+        let self_type = self.db.hir_type(ty);
+        let subst = HashMap::with_hasher(Hasher::default());
+
+        generic.bounds.iter().find_map(|&bound| {
+            let trait_ = self.impls.get_trait(bound)?;
+            let func = trait_.methods.iter().find(|func| {
+                func.name.last().map_or(false, |&name| {
+                    context.strings().resolve(name).as_ref() == method
+                })
+            })?;
+
+            let instantiated = Func {
+                ret: generics::substitute(context, func.ret, &subst, Some(self_type)),
+                args: func
+                    .args
+                    .iter()
+                    .map(|arg| FuncArg {
+                        kind: generics::substitute(context, arg.kind, &subst, Some(self_type)),
+                        ..*arg
+                    })
+                    .collect(),
+                generics: Vec::new(),
+                arg_span: func.args.location(),
+                sig: func.sig,
+            };
+
+            Some((func.name.clone(), instantiated))
+        })
+    }
+
+    /// Checks that an `extend` block doesn't conflict with the ones before it and that it
+    /// implements every method of its trait
+    fn check_extension(&mut self, extend: &ExtendBlock<'ctx>) {
+        let context = self.db.context();
+        let strings = context.strings();
+        let ty = self.display_type(&self.concrete_type(extend.target).kind);
+        let method_name = |func: &Function<'_>| {
+            strings
+                .resolve(*func.name.last().expect("Functions should have a name"))
+                .as_ref()
+                .to_owned()
+        };
+
+        // A type's own methods are shared between all of its `extend` blocks
+        let mut declared: Vec<&Function<'ctx>> = Vec::new();
+        if extend.extender.is_none() {
+            declared.extend(
+                self.impls
+                    .extensions_of(context, extend.target, None)
+                    .into_iter()
+                    .flat_map(|extend| extend.methods.iter()),
+            );
+        }
+
+        for method in extend.methods.iter() {
+            if let Some(first) = declared
+                .iter()
+                .find(|declared| declared.name.last() == method.name.last())
+            {
+                self.errors.push_err(Locatable::new(
+                    TypeError::DuplicateMethod {
+                        ty: ty.clone(),
+                        method: method_name(method),
+                        first: first.sig,
+                    }
+                    .into(),
+                    method.sig,
+                ));
+            } else {
+                declared.push(method);
+            }
+        }
+
+        let trait_ = match extend.extender {
+            Some(id) => self
+                .impls
+                .get_trait(id)
+                .expect("Traits are resolved during lowering"),
+            None => return,
+        };
+        let trait_name = trait_.name.to_string(strings);
+
+        if let Some(first) = self
+            .impls
+            .extensions_of(context, extend.target, extend.extender)
+            .first()
+        {
+            self.errors.push_err(Locatable::new(
+                TypeError::DuplicateImpl {
+                    ty,
+                    trait_: trait_name,
+                    first: first.loc,
+                }
+                .into(),
+                extend.loc,
+            ));
+
+            return;
+        }
+
+        for required in trait_.methods.iter() {
+            if !extend
+                .methods
+                .iter()
+                .any(|method| method.name.last() == required.name.last())
+            {
+                self.errors.push_err(Locatable::new(
+                    TypeError::MissingTraitMethod {
+                        trait_: trait_name.clone(),
+                        ty: ty.clone(),
+                        method: method_name(required),
+                    }
+                    .into(),
+                    extend.loc,
+                ));
+            }
+        }
+
+        for method in extend.methods.iter() {
+            let name = *method.name.last().expect("Functions should have a name");
+
+            match trait_.method(name) {
+                Some(required) => {
+                    let matches = required.args.len() == method.args.len()
+                        && required
+                            .args
+                            .iter()
+                            .zip(method.args.iter())
+                            .all(|(req, arg)| {
+                                impls::matches_trait_type(
+                                    context,
+                                    req.kind,
+                                    arg.kind,
+                                    extend.target,
+                                )
+                            })
+                        && impls::matches_trait_type(
+                            context,
+                            required.ret,
+                            method.ret,
+                            extend.target,
+                        );
+
+                    if !matches {
+                        self.errors.push_err(Locatable::new(
+                            TypeError::TraitMethodSignature {
+                                method: method_name(method),
+                                trait_: trait_name.clone(),
+                                def_site: required.sig,
+                            }
+                            .into(),
+                            method.sig,
+                        ));
+                    }
+                }
+
+                None => self.errors.push_err(Locatable::new(
+                    TypeError::NotTraitMethod(method_name(method), trait_name.clone()).into(),
+                    method.sig,
+                )),
+            }
+        }
+    }
+
     // TODO: Caching
     #[crunch_shared::instrument(name = "intern literal", skip(self, val, ty, loc, _loc))]
     fn intern_literal(
@@ -546,69 +1088,824 @@ impl<'ctx> Engine<'ctx> {
         Ok(ty)
     }
 
-    // TODO: Caching
-    fn display_type(&self, ty: &TypeKind) -> String {
-        let mut string = String::new();
-        self.display_type_inner(ty, &mut string)
-            .expect("Failed to format type");
+    /// Checks the values given to a function's arguments against the argument types
+    fn check_args(
+        &mut self,
+        values: Vec<ArgValue<'ctx>>,
+        params: &[FuncArg<'ctx>],
+    ) -> TypeResult<()> {
+        for (value, arg) in values.into_iter().zip(params.iter()) {
+            match value {
+                ArgValue::Value(expr) => self.check_arg(expr, arg.kind)?,
+
+                // Defaults are checked along with the function they belong to
+                ArgValue::Default(_) => {}
+
+                ArgValue::Variadic(exprs) => {
+                    let element = match self
+                        .db
+                        .context()
+                        .get_hir_type(arg.kind)
+                        .expect("Type ids always refer to interned types")
+                        .kind
+                    {
+                        TypeKind::Slice { element } => element,
+                        _ => unreachable!("Variadic arguments are always slices"),
+                    };
+
+                    for expr in exprs {
+                        self.check_arg(expr, element)?;
+                    }
+                }
+            }
+        }
 
-        string
+        Ok(())
     }
 
-    fn display_type_inner<W: Write>(&self, ty: &TypeKind, f: &mut W) -> FmtResult {
-        match ty {
-            &TypeKind::Variable(inner) => {
-                self.display_type_inner(&self.db.context().get_hir_type(inner).unwrap().kind, f)
-            }
-            TypeKind::Unknown => f.write_str("infer"),
-            &TypeKind::Integer { signed, width } => match (signed, width) {
-                (Some(signed), Some(width)) => {
-                    write!(f, "{}{}", if signed { "i" } else { "u" }, width)
-                }
-                (_, _) => f.write_str("{{integer}}"),
-            },
-            TypeKind::String => f.write_str("str"),
-            TypeKind::Bool => f.write_str("bool"),
-            TypeKind::Unit => f.write_str("unit"),
-            TypeKind::Absurd => f.write_str("absurd"),
+    /// Follows type variables until a concrete type is found
+    fn check_arg(&mut self, expr: &'ctx Expr<'ctx>, check: TypeId) -> TypeResult<()> {
+        self.check = Some(check);
+        let expr = self.visit_expr(expr)?;
+        self.check.take();
 
-            &TypeKind::Array { element, length } => {
-                f.write_str("arr[")?;
-                self.display_type_inner(&self.db.context().get_hir_type(element).unwrap().kind, f)?;
-                f.write_str("; ")?;
-                write!(f, "{}", length)?;
-                f.write_char(']')
-            }
+        self.unify(expr, check)
+    }
+
+    /// Checks the defaults of a function's arguments, which are evaluated with the callee's
+    /// bindings and so can only see the arguments that come before them
+    fn check_defaults(&mut self, args: &[FuncArg<'ctx>]) -> TypeResult<()> {
+        let outer_variables = mem::take(&mut self.variables);
+        self.push_scope();
 
-            &TypeKind::Slice { element } => {
-                f.write_str("arr[")?;
-                self.display_type_inner(&self.db.context().get_hir_type(element).unwrap().kind, f)?;
-                f.write_char(']')
+        let checked = args.iter().try_for_each(|arg| {
+            if let Some(default) = arg.default {
+                self.check_arg(default, arg.kind)?;
             }
+            self.insert_variable(arg.name, arg.kind, false);
 
-            &TypeKind::Reference { mutable, referee } => {
-                f.write_char('&')?;
-                if mutable {
-                    f.write_str("mut ")?;
-                }
+            Ok(())
+        });
 
-                self.display_type_inner(&self.db.context().get_hir_type(referee).unwrap().kind, f)
-            }
+        self.variables = outer_variables;
+        checked
+    }
+
+    fn arg_mismatch(
+        &self,
+        mismatch: ArgMismatch,
+        name: &ItemPath,
+        func: &Func<'ctx>,
+        loc: Location,
+    ) -> Locatable<Error> {
+        let strings = self.db.context().strings();
+        let func_name = name.to_string(strings);
+
+        crunch_shared::error!(
+            "the arguments of a call to {:?} don't match its signature: {:?}",
+            func_name,
+            mismatch,
+        );
 
-            &TypeKind::Pointer { mutable, pointee } => {
-                if mutable {
-                    f.write_str("*const ")?;
+        match mismatch {
+            ArgMismatch::TooMany { expected, received } => {
+                let def_site = if func.args.is_empty() {
+                    func.arg_span
                 } else {
-                    f.write_str("*mut ")?;
-                }
+                    // If there's 1 or more args trim off the parentheses
+                    func.arg_span
+                        .map_span(|span| Span::new(span.start() + 1, span.end() - 1))
+                };
 
-                self.display_type_inner(&self.db.context().get_hir_type(pointee).unwrap().kind, f)
+                Locatable::new(
+                    TypeError::TooManyArgs {
+                        expected,
+                        received,
+                        def_site,
+                    }
+                    .into(),
+                    loc,
+                )
             }
-        }
-    }
-}
 
-impl<'ctx> ItemVisitor<'ctx> for Engine<'ctx> {
+            ArgMismatch::Missing(arg) => {
+                Locatable::new(TypeError::MissingArg(arg.to_string(strings)).into(), loc)
+            }
+
+            ArgMismatch::Duplicate(arg, arg_loc) => Locatable::new(
+                TypeError::DuplicateArg(arg.to_string(strings)).into(),
+                arg_loc,
+            ),
+
+            ArgMismatch::Unknown(arg, arg_loc) => Locatable::new(
+                TypeError::UnknownArg(func_name, strings.resolve(arg).as_ref().to_owned()).into(),
+                arg_loc,
+            ),
+
+            ArgMismatch::UnexpectedSpread(spread_loc) => {
+                Locatable::new(TypeError::UnexpectedSpread(func_name).into(), spread_loc)
+            }
+        }
+    }
+
+    fn concrete_type(&self, ty: TypeId) -> Type {
+        let mut ty = self.db.context().get_hir_type(ty).unwrap().clone();
+        while let TypeKind::Variable(inner) = ty.kind {
+            ty = self.db.context().get_hir_type(inner).unwrap().clone();
+        }
+
+        ty
+    }
+
+    /// Gets the type of an element of `indexee`, or of a slice of it when it's indexed by
+    /// a range
+    fn index_type(
+        &mut self,
+        indexee: TypeId,
+        index: &'ctx Expr<'ctx>,
+        loc: Location,
+    ) -> TypeResult<TypeId> {
+        let you_size = self.db.hir_type(Type::new(
+            TypeKind::Integer {
+                signed: None,
+                width: None,
+            },
+            index.location(),
+        ));
+
+        // Constant indices into arrays are bounds checked here instead of at runtime
+        let constant_index = match index.kind {
+            ExprKind::Literal(Literal {
+                val: LiteralVal::Integer(Integer { bits, .. }),
+                ..
+            }) => Some(bits),
+            _ => None,
+        };
+
+        // Indexing by a range takes a slice of the indexee, `array[1..4]`
+        self.check.take();
+        let index_ty = match &index.kind {
+            ExprKind::Range(range) => self.range_type(index.location(), range)?,
+            _ => self.visit_expr(index)?,
+        };
+        let sliced = matches!(self.concrete_type(index_ty).kind, TypeKind::Range { .. });
+        if sliced {
+            let range = self
+                .db
+                .hir_type(Type::new(TypeKind::Range { element: you_size }, loc));
+
+            self.unify(index_ty, range)?;
+        } else {
+            self.unify(you_size, index_ty)?;
+        }
+
+        let arr_ty = self.db.context().get_hir_type(indexee).unwrap();
+
+        let mut kind = arr_ty.kind.clone();
+        loop {
+            match kind {
+                TypeKind::Array { element, .. } | TypeKind::Slice { element } if sliced => {
+                    crunch_shared::trace!(
+                        "indexee type was a slice or array indexed by a range, returning a slice",
+                    );
+
+                    return Ok(self
+                        .db
+                        .hir_type(Type::new(TypeKind::Slice { element }, loc)));
+                }
+
+                TypeKind::Array { length, .. } if constant_index >= Some(length as u128) => {
+                    crunch_shared::error!(
+                        "the constant index {:?} is out of bounds",
+                        constant_index
+                    );
+
+                    return Err(Locatable::new(
+                        TypeError::IndexOutOfBounds {
+                            index: constant_index.unwrap(),
+                            ty: self.display_type(&kind),
+                            length,
+                        }
+                        .into(),
+                        index.location(),
+                    ));
+                }
+
+                TypeKind::Array { element, .. } | TypeKind::Slice { element } => {
+                    crunch_shared::trace!(
+                        "indexee type was a slice or array, returning the element type {:?}",
+                        element,
+                    );
+
+                    return Ok(element);
+                }
+
+                // Arrays and slices can be indexed through references
+                TypeKind::Variable(ty) | TypeKind::Reference { referee: ty, .. } => {
+                    crunch_shared::trace!("indexee type was a variable or reference, iterating");
+
+                    kind = self.db.context().get_hir_type(ty).unwrap().kind.clone()
+                }
+
+                _ => {
+                    crunch_shared::error!("invalid indexee type: {:?}", kind);
+
+                    return Err(Locatable::new(
+                        TypeError::TypeConflict {
+                            call_type: self.display_type(&arr_ty.kind),
+                            def_type: "slice or arr".to_owned(),
+                            def_site: loc,
+                        }
+                        .into(),
+                        loc,
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Gets the type of a range used to slice an array or slice, both of its bounds share
+    /// the type of its elements
+    fn range_type(&mut self, loc: Location, range: &Range<'ctx>) -> TypeResult<TypeId> {
+        let element = self.db.hir_type(Type::new(TypeKind::Unknown, loc));
+
+        for &bound in range.start.iter().chain(range.end.iter()) {
+            self.check = Some(element);
+            let bound_type = self.visit_expr(bound)?;
+            self.check.take();
+
+            self.unify(bound_type, element)?;
+        }
+
+        Ok(self
+            .db
+            .hir_type(Type::new(TypeKind::Range { element }, loc)))
+    }
+
+    /// Gets the type of one of the fields of `tuple`
+    fn field_type(
+        &self,
+        tuple: TypeId,
+        index: u64,
+        loc: Location,
+        tuple_loc: Location,
+    ) -> TypeResult<TypeId> {
+        match self.concrete_type(tuple).kind {
+            TypeKind::Tuple(elements) => elements.get(index as usize).copied().ok_or_else(|| {
+                Locatable::new(
+                    TypeError::NoTupleField(
+                        self.display_type(&TypeKind::Tuple(elements.clone())),
+                        index,
+                    )
+                    .into(),
+                    loc,
+                )
+            }),
+
+            TypeKind::Unknown => Err(Locatable::new(
+                TypeError::FailedInfer("the tuple".to_owned()).into(),
+                tuple_loc,
+            )),
+
+            ref kind => {
+                crunch_shared::error!("attempted to access a field of {:?}", kind);
+
+                Err(Locatable::new(
+                    TypeError::TypeConflict {
+                        call_type: self.display_type(kind),
+                        def_type: "tup".to_owned(),
+                        def_site: loc,
+                    }
+                    .into(),
+                    tuple_loc,
+                ))
+            }
+        }
+    }
+
+    /// Gets the type of a place that's being assigned to, along with the error to report
+    /// if the place isn't mutable
+    fn place_type(
+        &mut self,
+        place: &Place<'ctx>,
+    ) -> TypeResult<(TypeId, Option<Locatable<Error>>)> {
+        let loc = place.location();
+
+        match &place.kind {
+            &PlaceKind::Variable(var) => {
+                let ty = self.var_type(&var, loc)?;
+                let immutable = if self.var_mutable(&var) {
+                    None
+                } else {
+                    let name = var.to_string(self.db.context().strings());
+                    Some(Locatable::new(
+                        TypeError::ImmutableAssignment(name).into(),
+                        loc,
+                    ))
+                };
+
+                Ok((ty, immutable))
+            }
+
+            PlaceKind::Field { base, index } => {
+                let (tuple, immutable) = self.place_type(base)?;
+                let (tuple, immutable) = self.through_references(tuple, immutable, base.loc);
+
+                Ok((self.field_type(tuple, *index, loc, base.loc)?, immutable))
+            }
+
+            PlaceKind::Index { base, index } => {
+                if let ExprKind::Range(_) = index.kind {
+                    return Err(Locatable::new(
+                        TypeError::IncorrectType(
+                            "Only single elements can be assigned to, not slices".to_owned(),
+                        )
+                        .into(),
+                        index.location(),
+                    ));
+                }
+
+                let (indexee, immutable) = self.place_type(base)?;
+                let (indexee, immutable) = self.through_references(indexee, immutable, base.loc);
+
+                Ok((self.index_type(indexee, index, loc)?, immutable))
+            }
+
+            PlaceKind::Deref(pointer) => {
+                self.check.take();
+                let pointer_ty = self.visit_expr(pointer)?;
+
+                match self.concrete_type(pointer_ty).kind {
+                    TypeKind::Reference {
+                        referee: pointee,
+                        mutable,
+                    }
+                    | TypeKind::Pointer { pointee, mutable } => {
+                        let immutable = if mutable {
+                            None
+                        } else {
+                            Some(self.immutable_deref(pointer_ty, pointer.location()))
+                        };
+
+                        Ok((pointee, immutable))
+                    }
+
+                    kind => Err(self.invalid_deref(&kind, pointer.location())),
+                }
+            }
+        }
+    }
+
+    /// Follows the references that `ty` is behind, since fields and elements are accessed
+    /// through them. Places behind a reference are only mutable if the reference is
+    fn through_references(
+        &self,
+        mut ty: TypeId,
+        mut immutable: Option<Locatable<Error>>,
+        loc: Location,
+    ) -> (TypeId, Option<Locatable<Error>>) {
+        while let TypeKind::Reference { referee, mutable } = self.concrete_type(ty).kind {
+            immutable = if mutable {
+                None
+            } else {
+                Some(self.immutable_deref(ty, loc))
+            };
+            ty = referee;
+        }
+
+        (ty, immutable)
+    }
+
+    fn immutable_deref(&self, pointer: TypeId, loc: Location) -> Locatable<Error> {
+        let pointer = self.display_type(&self.concrete_type(pointer).kind);
+        Locatable::new(TypeError::ImmutableDeref(pointer).into(), loc)
+    }
+
+    /// Checks that a binary operator can be applied to values of the given type, which
+    /// integers always can be and booleans can be for bitwise operators
+    fn check_operator(&self, op: BinaryOp, operands: TypeId, loc: Location) -> TypeResult<()> {
+        match (op, self.concrete_type(operands).kind) {
+            (_, TypeKind::Integer { .. }) | (_, TypeKind::Unknown) => Ok(()),
+            (BinaryOp::BitAnd, TypeKind::Bool)
+            | (BinaryOp::BitOr, TypeKind::Bool)
+            | (BinaryOp::BitXor, TypeKind::Bool) => Ok(()),
+
+            (op, kind) => {
+                crunch_shared::error!("attempted to apply {:?} to {:?}", op, kind);
+
+                Err(Locatable::new(
+                    TypeError::InvalidOperator(op.to_string(), self.display_type(&kind)).into(),
+                    loc,
+                ))
+            }
+        }
+    }
+
+    /// Checks that a type can be used past type checking, since some types can be written
+    /// and checked but can't be lowered yet
+    fn check_supported(&self, ty: TypeId, loc: Location) -> TypeResult<()> {
+        if self.is_unsupported(ty) {
+            let kind = self.concrete_type(ty).kind;
+
+            return Err(Locatable::new(
+                TypeError::UnsupportedType(self.display_type(&kind)).into(),
+                loc,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn is_unsupported(&self, ty: TypeId) -> bool {
+        match self.concrete_type(ty).kind {
+            TypeKind::Rune
+            | TypeKind::Float { .. }
+            | TypeKind::Func { .. }
+            | TypeKind::Trait(_)
+            | TypeKind::Union(_)
+            | TypeKind::Intersection(_)
+            | TypeKind::Not(_)
+            | TypeKind::Const { .. }
+            | TypeKind::Range { .. } => true,
+
+            kind => kind
+                .inner_types()
+                .into_iter()
+                .any(|inner| self.is_unsupported(inner)),
+        }
+    }
+
+    fn invalid_deref(&self, kind: &TypeKind, loc: Location) -> Locatable<Error> {
+        if let TypeKind::Unknown = kind {
+            return Locatable::new(
+                TypeError::FailedInfer("the dereferenced value".to_owned()).into(),
+                loc,
+            );
+        }
+
+        crunch_shared::error!("attempted to dereference {:?}", kind);
+        Locatable::new(TypeError::InvalidDeref(self.display_type(kind)).into(), loc)
+    }
+
+    /// Checks that a pattern can match against a value of the given type, binding
+    /// all variables the pattern introduces into the current scope
+    #[crunch_shared::instrument(name = "pattern", skip(self, pattern, scrutinee, loc))]
+    fn check_pattern(
+        &mut self,
+        pattern: &Pattern,
+        scrutinee: TypeId,
+        loc: Location,
+    ) -> TypeResult<()> {
+        match pattern {
+            Pattern::Literal(literal) => {
+                crunch_shared::trace!("pattern was a literal");
+
+                self.check = Some(scrutinee);
+                let literal_type = self.visit_literal(loc, literal)?;
+                self.unify(scrutinee, literal_type)?;
+
+                self.check.take();
+            }
+
+            &Pattern::Ident(variable) => {
+                crunch_shared::trace!("pattern was an ident");
+
+                let variable_type = self
+                    .db
+                    .hir_type(Type::new(TypeKind::Variable(scrutinee), loc));
+
+                self.insert_variable(Var::User(variable), variable_type, false);
+                self.unify(scrutinee, variable_type)?;
+            }
+
+            Pattern::Wildcard => crunch_shared::trace!("pattern was a wildcard"),
+
+            Pattern::Bind(name, pattern) => {
+                crunch_shared::trace!("pattern was a binding");
+
+                self.check_pattern(&Pattern::Ident(*name), scrutinee, loc)?;
+                self.check_pattern(pattern, scrutinee, loc)?;
+            }
+
+            Pattern::Or(alternatives) => {
+                crunch_shared::trace!("pattern had {} alternatives", alternatives.len());
+
+                let mut expected = Vec::new();
+                if let Some(first) = alternatives.first() {
+                    first.bindings(&mut expected);
+                }
+
+                let mut bound = HashMap::with_hasher(Hasher::default());
+                for alternative in alternatives {
+                    self.check_pattern(alternative, scrutinee, loc)?;
+
+                    let mut bindings = Vec::with_capacity(expected.len());
+                    alternative.bindings(&mut bindings);
+
+                    if let Some(&missing) = expected
+                        .iter()
+                        .chain(bindings.iter())
+                        .find(|var| !bindings.contains(var) || !expected.contains(var))
+                    {
+                        return Err(Locatable::new(
+                            TypeError::UnboundAlternative(
+                                Var::User(missing).to_string(self.db.context().strings()),
+                            )
+                            .into(),
+                            loc,
+                        ));
+                    }
+
+                    // Each alternative must give its bindings the same types
+                    for var in bindings {
+                        let ty = self.var_type(&Var::User(var), loc)?;
+                        if let Some(&prev) = bound.get(&var) {
+                            self.unify(prev, ty)?;
+                        } else {
+                            bound.insert(var, ty);
+                        }
+                    }
+                }
+            }
+
+            Pattern::Slice(elements) => {
+                crunch_shared::trace!("pattern was a slice of {} elements", elements.len());
+
+                let ty = self.concrete_type(scrutinee);
+                let element = match ty.kind {
+                    TypeKind::Array { element, length } => {
+                        let has_rest = elements.iter().any(Self::is_rest_pattern);
+                        let required = elements
+                            .iter()
+                            .filter(|p| !Self::is_rest_pattern(p))
+                            .count() as u64;
+
+                        if (has_rest && required > length) || (!has_rest && required != length) {
+                            return Err(Locatable::new(
+                                TypeError::IncorrectType(format!(
+                                    "Expected an array of {}{} elements, but the array has {}",
+                                    if has_rest { "at least " } else { "" },
+                                    required,
+                                    length,
+                                ))
+                                .into(),
+                                loc,
+                            ));
+                        }
+
+                        element
+                    }
+                    TypeKind::Slice { element } => element,
+
+                    TypeKind::Unknown => {
+                        return Err(Locatable::new(
+                            TypeError::FailedInfer("the matched value".to_owned()).into(),
+                            loc,
+                        ));
+                    }
+                    ref kind => {
+                        return Err(Locatable::new(
+                            TypeError::TypeConflict {
+                                call_type: self.display_type(kind),
+                                def_type: "slice or arr".to_owned(),
+                                def_site: ty.location(),
+                            }
+                            .into(),
+                            loc,
+                        ));
+                    }
+                };
+
+                for pattern in elements {
+                    match pattern {
+                        Pattern::Rest => {}
+
+                        // `rest @ ..` binds the remaining elements as a slice
+                        Pattern::Bind(name, rest) if rest.is_rest() => {
+                            let slice = self
+                                .db
+                                .hir_type(Type::new(TypeKind::Slice { element }, loc));
+                            self.insert_variable(Var::User(*name), slice, false);
+                        }
+
+                        pattern => self.check_pattern(pattern, element, loc)?,
+                    }
+                }
+            }
+
+            Pattern::Range { start, end, .. } => {
+                crunch_shared::trace!("pattern was a range");
+
+                for bound in start.iter().chain(end.iter()) {
+                    self.check_pattern(&Pattern::Literal(bound.clone()), scrutinee, loc)?;
+                }
+            }
+
+            Pattern::Tuple(elements) => {
+                crunch_shared::trace!("pattern was a tuple of {} elements", elements.len());
+
+                let ty = self.concrete_type(scrutinee);
+                let fields = match ty.kind {
+                    TypeKind::Tuple(fields) => fields,
+                    ref kind => {
+                        return Err(Locatable::new(
+                            TypeError::TypeConflict {
+                                call_type: self.display_type(kind),
+                                def_type: "tup".to_owned(),
+                                def_site: ty.location(),
+                            }
+                            .into(),
+                            loc,
+                        ));
+                    }
+                };
+
+                if elements
+                    .iter()
+                    .any(|p| matches!(p, Pattern::Bind(_, rest) if rest.is_rest()))
+                {
+                    return Err(Locatable::new(
+                        TypeError::IncorrectType(
+                            "Only the rest of an array or slice can be bound with `name @ ..`"
+                                .to_owned(),
+                        )
+                        .into(),
+                        loc,
+                    ));
+                }
+
+                // Elements before a `..` match the first fields and the ones after it match
+                // the last fields
+                let rest = elements.iter().position(Pattern::is_rest);
+                let (prefix, suffix) = match rest {
+                    Some(rest) => (&elements[..rest], &elements[rest + 1..]),
+                    None => (&elements[..], &[][..]),
+                };
+
+                let required = prefix.len() + suffix.len();
+                if (rest.is_some() && required > fields.len())
+                    || (rest.is_none() && required != fields.len())
+                {
+                    return Err(Locatable::new(
+                        TypeError::IncorrectType(format!(
+                            "Expected a tuple of {}{} elements, but the tuple has {}",
+                            if rest.is_some() { "at least " } else { "" },
+                            required,
+                            fields.len(),
+                        ))
+                        .into(),
+                        loc,
+                    ));
+                }
+
+                let suffix_fields = &fields[fields.len() - suffix.len()..];
+                for (element, &field) in prefix
+                    .iter()
+                    .zip(&fields)
+                    .chain(suffix.iter().zip(suffix_fields))
+                {
+                    self.check_pattern(element, field, loc)?;
+                }
+            }
+
+            Pattern::ItemPath(path) => {
+                crunch_shared::trace!("pattern was an item path");
+
+                self.check_variant_pattern(path, &[], scrutinee, loc)?;
+            }
+
+            Pattern::Variant { path, elements } => {
+                crunch_shared::trace!("pattern was a variant of {} elements", elements.len());
+
+                self.check_variant_pattern(path, elements, scrutinee, loc)?;
+            }
+
+            Pattern::Struct { path, .. } => {
+                return Err(Locatable::new(
+                    TypeError::UnresolvedPattern(path.to_string(self.db.context().strings()))
+                        .into(),
+                    loc,
+                ));
+            }
+
+            Pattern::Rest => {
+                return Err(Locatable::new(
+                    TypeError::IncorrectType(
+                        "`..` can only be used once, directly within a tuple, slice or struct pattern"
+                            .to_owned(),
+                    )
+                    .into(),
+                    loc,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks a pattern matching one of an enum's variants, `Enum.Variant(elements)`, against the
+    /// payload of the variant
+    fn check_variant_pattern(
+        &mut self,
+        path: &ItemPath,
+        elements: &[Pattern],
+        scrutinee: TypeId,
+        loc: Location,
+    ) -> TypeResult<()> {
+        let strings = self.db.context().strings();
+        let (&variant, enum_path) = path.split_last().expect("Paths are never empty");
+
+        // Variants can be matched through references to them
+        let mut ty = self.concrete_type(scrutinee);
+        while let TypeKind::Reference { referee, .. } = ty.kind {
+            ty = self.concrete_type(referee);
+        }
+
+        let (enum_, generics) = match ty.kind {
+            TypeKind::Enum { id, generics } if *self.enums[&id].name == *enum_path => {
+                (&self.enums[&id], generics)
+            }
+
+            TypeKind::Unknown => {
+                return Err(Locatable::new(
+                    TypeError::FailedInfer("the matched value".to_owned()).into(),
+                    loc,
+                ));
+            }
+
+            ref kind => {
+                let error = match self.enums.values().find(|enum_| *enum_.name == *enum_path) {
+                    Some(enum_) => TypeError::TypeConflict {
+                        call_type: self.display_type(kind),
+                        def_type: enum_.name.to_string(strings),
+                        def_site: ty.location(),
+                    },
+                    None => TypeError::UnresolvedPattern(path.to_string(strings)),
+                };
+
+                return Err(Locatable::new(error.into(), loc));
+            }
+        };
+
+        let payload = match enum_.variant(variant) {
+            Some((_, variant)) => variant.elements.clone(),
+            None => {
+                return Err(Locatable::new(
+                    TypeError::UnknownVariant(
+                        enum_.name.to_string(strings),
+                        strings.resolve(variant).as_ref().to_owned(),
+                    )
+                    .into(),
+                    loc,
+                ));
+            }
+        };
+
+        // The payload's types are given by the generics of the matched value
+        let subst: HashMap<ItemId, TypeId> = enum_
+            .generics
+            .iter()
+            .map(|generic| generic.id)
+            .zip(generics)
+            .collect();
+        let payload: Vec<TypeId> = payload
+            .into_iter()
+            .map(|element| generics::substitute(self.db.context(), element, &subst, None))
+            .collect();
+
+        if payload.len() != elements.len() {
+            return Err(Locatable::new(
+                TypeError::VariantArity {
+                    variant: path.to_string(strings),
+                    expected: payload.len(),
+                    received: elements.len(),
+                }
+                .into(),
+                loc,
+            ));
+        }
+
+        for (pattern, element) in elements.iter().zip(payload) {
+            self.check_pattern(pattern, element, loc)?;
+        }
+
+        Ok(())
+    }
+
+    fn is_rest_pattern(pattern: &Pattern) -> bool {
+        match pattern {
+            Pattern::Rest => true,
+            Pattern::Bind(_, pattern) => pattern.is_rest(),
+            _ => false,
+        }
+    }
+
+    fn display_type(&self, ty: &TypeKind) -> String {
+        generics::display_type(self.db.context(), &self.enums, ty)
+    }
+}
+
+impl<'ctx> ItemVisitor<'ctx> for Engine<'ctx> {
     type Output = TypeResult<()>;
 
     #[crunch_shared::instrument(name = "item", skip(self, item))]
@@ -622,6 +1919,18 @@ impl<'ctx> ItemVisitor<'ctx> for Engine<'ctx> {
                 crunch_shared::trace!("item is an external function, visiting");
                 self.visit_extern_func(func)
             }
+            Item::Enum(enum_) => {
+                crunch_shared::trace!("item is an enum, visiting");
+                self.visit_enum(enum_)
+            }
+            Item::Trait(trait_) => {
+                crunch_shared::trace!("item is a trait, visiting");
+                self.visit_trait(trait_)
+            }
+            Item::ExtendBlock(extend) => {
+                crunch_shared::trace!("item is an extend block, visiting");
+                self.visit_extend_block(extend)
+            }
         }
     }
 
@@ -633,14 +1942,27 @@ impl<'ctx> ItemVisitor<'ctx> for Engine<'ctx> {
     fn visit_func(
         &mut self,
         Function {
-            name, body, args, ..
+            name,
+            generics,
+            body,
+            args,
+            ..
         }: &Function<'ctx>,
     ) -> Self::Output {
-        self.with_scope(|builder| {
+        // Nested functions aren't known until they're reached
+        if !self.functions.contains_key(name) {
+            self.declare_generics(generics);
+        }
+
+        // Nested functions have their own instances to check
+        let outer_instances = mem::take(&mut self.instances);
+        let checked = self.with_scope(|builder| {
             builder.current_func = Some(builder.functions.get(name).unwrap().clone());
 
+            builder.check_defaults(args)?;
+
             for arg in args.iter() {
-                builder.insert_variable(arg.name, arg.kind);
+                builder.insert_variable(arg.name, arg.kind, false);
             }
 
             for stmt in body.iter() {
@@ -650,7 +1972,14 @@ impl<'ctx> ItemVisitor<'ctx> for Engine<'ctx> {
             builder.current_func = None;
 
             Ok(())
-        })
+        });
+
+        let instances = mem::replace(&mut self.instances, outer_instances);
+        if checked.is_ok() {
+            self.check_instances(instances);
+        }
+
+        checked
     }
 
     #[crunch_shared::instrument(
@@ -665,7 +1994,7 @@ impl<'ctx> ItemVisitor<'ctx> for Engine<'ctx> {
             ret,
             ref name,
             ..
-        }: &ExternFunc,
+        }: &ExternFunc<'ctx>,
     ) -> Self::Output {
         let mut missing_arg_ty = None;
 
@@ -706,6 +2035,56 @@ impl<'ctx> ItemVisitor<'ctx> for Engine<'ctx> {
 
         Ok(())
     }
+
+    #[crunch_shared::instrument(
+        name = "enum",
+        skip(self, enum_),
+        fields(name = ?enum_.name.to_string(self.db.context().strings())),
+    )]
+    fn visit_enum(&mut self, enum_: &Enum) -> Self::Output {
+        // Enums declared within function bodies are only known once they've been reached
+        if !self.enums.contains_key(&enum_.id) {
+            self.declare_generics(&enum_.generics);
+            self.enums.insert(enum_.id, enum_.clone());
+        }
+
+        for variant in enum_.variants.iter() {
+            for &element in variant.elements.iter() {
+                if self.concrete_type(element).is_unknown() {
+                    return Err(Locatable::new(
+                        TypeError::MissingType("Types for enum variants".to_owned()).into(),
+                        variant.loc,
+                    ));
+                }
+
+                self.check_supported(element, variant.loc)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Trait methods are only signatures, they're checked against the `extend` blocks
+    // implementing them
+    #[crunch_shared::instrument(
+        name = "trait",
+        skip(self, trait_),
+        fields(name = ?trait_.name.to_string(self.db.context().strings())),
+    )]
+    fn visit_trait(&mut self, trait_: &Trait<'ctx>) -> Self::Output {
+        self.impls.add_trait(trait_);
+
+        Ok(())
+    }
+
+    #[crunch_shared::instrument(name = "extend block", skip(self, extend))]
+    fn visit_extend_block(&mut self, extend: &ExtendBlock<'ctx>) -> Self::Output {
+        for method in extend.methods.iter() {
+            self.visit_func(method)?;
+        }
+
+        Ok(())
+    }
 }
 
 impl<'ctx> StmtVisitor<'ctx> for Engine<'ctx> {
@@ -729,7 +2108,7 @@ impl<'ctx> StmtVisitor<'ctx> for Engine<'ctx> {
 
     #[crunch_shared::instrument(
         name = "variable declaration",
-        skip(self, name, value, ty, loc),
+        skip(self, name, value, mutable, ty, loc),
         fields(name = ?name.to_string(self.db.context().strings()), ty = ?ty),
     )]
     fn visit_var_decl(
@@ -737,13 +2116,14 @@ impl<'ctx> StmtVisitor<'ctx> for Engine<'ctx> {
         &VarDecl {
             name,
             value,
+            mutable,
             ty,
             loc,
-            ..
         }: &VarDecl<'ctx>,
     ) -> <Self as StmtVisitor<'ctx>>::Output {
+        self.check_supported(ty, loc)?;
         let expr = self.visit_expr(value)?;
-        self.insert_variable(name, ty);
+        self.insert_variable(name, ty, mutable);
         self.unify(expr, ty)?;
 
         Ok(Some(self.db.hir_type(Type::new(TypeKind::Unit, loc))))
@@ -770,28 +2150,98 @@ impl<'ctx> ExprVisitor<'ctx> for Engine<'ctx> {
         Ok(self.db.hir_type(Type::new(TypeKind::Absurd, loc)))
     }
 
-    #[crunch_shared::instrument(name = "break", skip(self, _loc, _value))]
-    fn visit_break(&mut self, _loc: Location, _value: &Break<'ctx>) -> Self::Output {
-        todo!()
-    }
+    #[crunch_shared::instrument(name = "break", skip(self, loc, value))]
+    fn visit_break(&mut self, loc: Location, value: &Break<'ctx>) -> Self::Output {
+        // Breaks outside of loops are reported while lowering
+        if let Some(&(loop_ty, _)) = self.loops.last() {
+            self.check = Some(loop_ty);
+
+            let value = if let Some(value) = value.val {
+                self.visit_expr(value)?
+            } else {
+                self.db.hir_type(Type::new(TypeKind::Unit, loc))
+            };
+            self.unify(loop_ty, value)?;
+            self.check.take();
+
+            self.loops.last_mut().unwrap().1 = true;
+        }
+
+        Ok(self.db.hir_type(Type::new(TypeKind::Absurd, loc)))
+    }
+
+    #[crunch_shared::instrument(name = "continue", skip(self, loc))]
+    fn visit_continue(&mut self, loc: Location) -> Self::Output {
+        Ok(self.db.hir_type(Type::new(TypeKind::Absurd, loc)))
+    }
+
+    #[crunch_shared::instrument(name = "loop", skip(self, loc, cond, body, then, else_, ty))]
+    fn visit_loop(
+        &mut self,
+        loc: Location,
+        &Loop {
+            cond,
+            ref body,
+            ref then,
+            ref else_,
+            ty,
+        }: &Loop<'ctx>,
+    ) -> Self::Output {
+        crunch_shared::trace!("visiting a loop with {} body statements", body.len());
+
+        if let Some(cond) = cond {
+            let cond_ty = self.visit_expr(cond)?;
+            let boolean = self.db.hir_type(Type::new(TypeKind::Bool, cond.location()));
+
+            self.unify(cond_ty, boolean)?;
+        }
+
+        self.loops.push((ty, false));
+        let checked = self.with_scope(|builder| {
+            for stmt in body.iter() {
+                builder.visit_stmt(stmt)?;
+            }
+
+            Ok(())
+        });
+        let (_, broken) = self.loops.pop().unwrap();
+        checked?;
+
+        if let Some(cond) = cond {
+            // Loops that exit through their condition take the value of their `then` clause
+            let then_ty = match then {
+                Some(then) => self.with_scope(|builder| {
+                    then.iter()
+                        .filter_map(|s| builder.visit_stmt(s).transpose())
+                        .last()
+                        .unwrap_or_else(|| {
+                            Ok(builder
+                                .db
+                                .hir_type(Type::new(TypeKind::Unit, then.location())))
+                        })
+                })?,
+                None => self.db.hir_type(Type::new(TypeKind::Unit, cond.location())),
+            };
 
-    #[crunch_shared::instrument(name = "continue", skip(self, _loc))]
-    fn visit_continue(&mut self, _loc: Location) -> Self::Output {
-        todo!()
-    }
+            self.unify(ty, then_ty)?;
+        } else if !broken {
+            // Unconditional loops that are never broken out of never finish
+            let absurd = self.db.hir_type(Type::new(TypeKind::Absurd, loc));
+            self.unify(ty, absurd)?;
+        }
 
-    #[crunch_shared::instrument(name = "loop", skip(self, loc, body))]
-    fn visit_loop(&mut self, loc: Location, body: &Block<&'ctx Stmt<'ctx>>) -> Self::Output {
-        crunch_shared::trace!(
-            "visiting an unconditional loop with {} body statements",
-            body.len(),
-        );
+        // The `else` clause is run for its effects, the loop keeps the broken value
+        if let Some(else_) = else_ {
+            self.with_scope(|builder| {
+                for stmt in else_.iter() {
+                    builder.visit_stmt(stmt)?;
+                }
 
-        for stmt in body.iter() {
-            self.visit_stmt(stmt)?;
+                Ok(())
+            })?;
         }
 
-        Ok(self.db.context().hir_type(Type::new(TypeKind::Absurd, loc)))
+        Ok(ty)
     }
 
     #[crunch_shared::instrument(name = "match", skip(self, loc, cond, arms, ty))]
@@ -807,38 +2257,7 @@ impl<'ctx> ExprVisitor<'ctx> for Engine<'ctx> {
             for arm in arms.iter() {
                 crunch_shared::trace_span!("match_arm").in_scope(|| {
                     crunch_shared::trace!("checking match arm pattern");
-                    match &arm.bind.pattern {
-                        Pattern::Literal(literal) => {
-                            crunch_shared::trace!("pattern was a literal");
-
-                            self.check = Some(condition_type);
-                            let literal_type = self.visit_literal(loc, literal)?;
-                            self.unify(condition_type, literal_type)?;
-
-                            self.check.take();
-                        }
-
-                        &Pattern::Ident(variable) => {
-                            crunch_shared::trace!("pattern was an ident");
-
-                            self.check = Some(condition_type);
-                            let variable_type = self
-                                .db
-                                .hir_type(Type::new(TypeKind::Variable(condition_type), loc));
-
-                            self.insert_variable(Var::User(variable), variable_type);
-                            self.unify(condition_type, variable_type)?;
-
-                            self.check.take();
-                        }
-
-                        // TODO: Is this correct?
-                        Pattern::Wildcard => {
-                            crunch_shared::trace!("pattern was a wildcard");
-                            crunch_shared::warn!("Match pattern wildcards are currently ignored");
-                        }
-                        Pattern::ItemPath(..) => todo!(),
-                    }
+                    self.check_pattern(&arm.bind.pattern, condition_type, loc)?;
 
                     crunch_shared::trace!("checking match arm guard");
                     if let Some(guard) = arm.guard {
@@ -892,15 +2311,60 @@ impl<'ctx> ExprVisitor<'ctx> for Engine<'ctx> {
         self.intern_literal(literal, loc)
     }
 
+    #[crunch_shared::instrument(name = "format string", skip(self, loc, segments))]
+    fn visit_format_string(
+        &mut self,
+        loc: Location,
+        segments: &[FormatSegment<'ctx>],
+    ) -> Self::Output {
+        // Any expected type is for the string itself, not the values interpolated into it
+        self.check.take();
+
+        for segment in segments {
+            if let &FormatSegment::Value { expr, .. } = segment {
+                let value = self.visit_expr(expr)?;
+
+                // TODO: Allow user-defined types to be formatted once traits exist
+                match self.concrete_type(value).kind {
+                    TypeKind::Integer { .. } | TypeKind::Bool | TypeKind::String => {}
+
+                    TypeKind::Unknown => {
+                        return Err(Locatable::new(
+                            TypeError::FailedInfer("the interpolated value".to_owned()).into(),
+                            expr.location(),
+                        ));
+                    }
+
+                    kind => {
+                        crunch_shared::error!("attempted to format a value of type {:?}", kind);
+
+                        return Err(Locatable::new(
+                            TypeError::Unformattable(self.display_type(&kind)).into(),
+                            expr.location(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(self.db.hir_type(Type::new(TypeKind::String, loc)))
+    }
+
     #[crunch_shared::instrument(name = "scope", skip(self, loc, body))]
     fn visit_scope(&mut self, loc: Location, body: &Block<&'ctx Stmt<'ctx>>) -> Self::Output {
         crunch_shared::trace!("visiting a scope with {} body statements", body.len());
 
         self.with_scope(|builder| {
-            body.iter()
-                .filter_map(|s| builder.visit_stmt(s).transpose())
-                .last()
-                .unwrap_or_else(|| Ok(builder.db.hir_type(Type::new(TypeKind::Unit, loc))))
+            // Errors are returned as soon as they happen, otherwise later statements would be
+            // checked without the variables they declared
+            let mut last = None;
+            for stmt in body.iter() {
+                if let Some(ty) = builder.visit_stmt(stmt)? {
+                    last = Some(ty);
+                }
+            }
+
+            Ok(last.unwrap_or_else(|| builder.db.hir_type(Type::new(TypeKind::Unit, loc))))
         })
     }
 
@@ -915,49 +2379,135 @@ impl<'ctx> ExprVisitor<'ctx> for Engine<'ctx> {
                     call.func.to_string(self.db.context().strings()),
                 );
 
-                Locatable::new(
-                    TypeError::FuncNotInScope(call.func.to_string(self.db.context().strings()))
-                        .into(),
-                    loc,
-                )
+                let strings = self.db.context().strings();
+                let name = call.func.to_string(strings);
+                let mut error: Error = TypeError::FuncNotInScope(name.clone()).into();
+
+                // The call's location covers its arguments too, so there's nothing to replace
+                let functions: Vec<String> = self
+                    .functions
+                    .keys()
+                    .map(|func| func.to_string(strings))
+                    .collect();
+                if let Some(similar) =
+                    utils::closest_match(&name, functions.iter().map(String::as_str))
+                {
+                    error = error
+                        .with_suggestion(Suggestion::new(format!("did you mean '{}'?", similar)));
+                }
+
+                Locatable::new(error, loc)
             })?
             .clone();
 
-        if func.args.len() != call.args.len() {
-            crunch_shared::error!(
-                "the function {:?} takes {} args but {} were supplied",
-                call.func.to_string(self.db.context().strings()),
-                func.args.len(),
-                call.args.len(),
-            );
+        let func = if func.generics.is_empty() {
+            func
+        } else {
+            self.instantiate(&func, &call.func, &call.generics, loc)?
+        };
 
-            let def_site = if func.args.is_empty() {
-                func.arg_span
-            } else {
-                // If there's 1 or more args trim off the parentheses
-                func.arg_span
-                    .map_span(|span| Span::new(span.start() + 1, span.end() - 1))
-            };
+        let values = call
+            .match_args(&func.args)
+            .map_err(|mismatch| self.arg_mismatch(mismatch, &call.func, &func, loc))?;
 
-            return Err(Locatable::new(
-                TypeError::NotEnoughArgs {
-                    expected: func.args.len(),
-                    received: call.args.len(),
-                    def_site,
-                }
-                .into(),
-                loc,
-            ));
-        }
+        self.check_args(values, &func.args)?;
 
-        for (expr, check) in call.args.iter().zip(func.args.iter().cloned()) {
-            self.check = Some(check);
-            let expr = self.visit_expr(expr)?;
-            self.check.take();
+        Ok(func.ret)
+    }
+
+    #[crunch_shared::instrument(name = "method call", skip(self, loc, call))]
+    fn visit_method_call(&mut self, loc: Location, call: &MethodCall<'ctx>) -> Self::Output {
+        let strings = self.db.context().strings();
+        let method_name = strings.resolve(*call.method).as_ref().to_owned();
+
+        self.check.take();
+        let receiver = self.visit_expr(call.receiver)?;
+        self.unify(call.receiver_ty, receiver)?;
+
+        let resolved = self
+            .impls
+            .resolve(self.db.context(), receiver, *call.method, call.trait_)
+            .map(|method| method.name.clone());
+        let (name, func) = match resolved {
+            Ok(name) => {
+                let func = self.functions[&name].clone();
+                (name, func)
+            }
+
+            // Methods on generics come from the traits they're bound by
+            Err(MethodError::Unknown) if self.generic_method(receiver, &method_name).is_some() => {
+                self.generic_method(receiver, &method_name).unwrap()
+            }
+
+            // Calls restricted to a trait are made by the compiler, so the receiver not
+            // implementing the trait is the user's mistake rather than a missing method
+            Err(MethodError::Unknown) if call.trait_.is_some() => {
+                let trait_ = self
+                    .impls
+                    .get_trait(call.trait_.unwrap())
+                    .expect("Traits are resolved during lowering");
+
+                return Err(Locatable::new(
+                    TypeError::UnsatisfiedBound {
+                        ty: self.display_type(&self.concrete_type(receiver).kind),
+                        trait_: trait_.name.to_string(strings),
+                        def_site: trait_.loc,
+                    }
+                    .into(),
+                    call.method.location(),
+                ));
+            }
+
+            Err(MethodError::Unknown) => {
+                let ty = self.display_type(&self.concrete_type(receiver).kind);
+
+                return Err(Locatable::new(
+                    TypeError::UnknownMethod(ty, method_name).into(),
+                    call.method.location(),
+                ));
+            }
+
+            Err(MethodError::Ambiguous) => {
+                let ty = self.display_type(&self.concrete_type(receiver).kind);
+
+                return Err(Locatable::new(
+                    TypeError::AmbiguousMethod(ty, method_name).into(),
+                    call.method.location(),
+                ));
+            }
+        };
+
+        let self_var = Var::User(strings.intern_static("self"));
+        let (receiver_arg, params) = match func.args.split_first() {
+            Some((receiver_arg, params)) if receiver_arg.name == self_var => (receiver_arg, params),
+
+            _ => {
+                return Err(Locatable::new(
+                    TypeError::NotAMethod(name.to_string(strings)).into(),
+                    call.method.location(),
+                ));
+            }
+        };
+
+        // Methods taking `&self` can be called on values, which are implicitly referenced
+        match (
+            self.concrete_type(receiver_arg.kind).kind,
+            &self.concrete_type(receiver).kind,
+        ) {
+            (TypeKind::Reference { referee, .. }, receiver_kind)
+                if !matches!(receiver_kind, TypeKind::Reference { .. }) =>
+            {
+                self.unify(receiver, referee)?
+            }
 
-            self.unify(expr, check)?;
+            _ => self.unify(receiver, receiver_arg.kind)?,
         }
 
+        let values = call
+            .match_args(params)
+            .map_err(|mismatch| self.arg_mismatch(mismatch, &name, &func, loc))?;
+        self.check_args(values, params)?;
+
         Ok(func.ret)
     }
 
@@ -973,21 +2523,41 @@ impl<'ctx> ExprVisitor<'ctx> for Engine<'ctx> {
         op: CompOp,
         rhs: &'ctx Expr<'ctx>,
     ) -> Self::Output {
-        // FIXME: Make sure the types are comparable
-        crunch_shared::warn!("Comparisons are not checked to be comparable");
-        // FIXME: The type of comparison is ignored, could be important
-        crunch_shared::warn!("Comparison operands are ignored");
-
+        self.check.take();
         let (left, right) = (self.visit_expr(lhs)?, self.visit_expr(rhs)?);
         self.unify(left, right)?;
 
+        // Anything that can be compared for equality can also be ordered, except for booleans
+        match (op, self.concrete_type(left).kind) {
+            (_, TypeKind::Integer { .. }) | (_, TypeKind::Unknown) => {}
+            (CompOp::Equal, TypeKind::Bool) | (CompOp::NotEqual, TypeKind::Bool) => {}
+
+            (op, kind) => {
+                crunch_shared::error!("attempted to compare {:?} with {:?}", kind, op);
+
+                return Err(Locatable::new(
+                    TypeError::InvalidOperator(op.to_string(), self.display_type(&kind)).into(),
+                    loc,
+                ));
+            }
+        }
+
         Ok(self.db.hir_type(Type::new(TypeKind::Bool, loc)))
     }
 
-    #[crunch_shared::instrument(name = "assignment", skip(self, loc, value))]
-    fn visit_assign(&mut self, loc: Location, var: Var, value: &'ctx Expr<'ctx>) -> Self::Output {
+    #[crunch_shared::instrument(name = "assignment", skip(self, loc, place, op, value))]
+    fn visit_assign(
+        &mut self,
+        loc: Location,
+        place: &Place<'ctx>,
+        op: Option<BinaryOp>,
+        value: &'ctx Expr<'ctx>,
+    ) -> Self::Output {
         self.check.take();
-        let expected = self.var_type(&var, loc)?;
+        let (expected, immutable) = self.place_type(place)?;
+        if let Some(error) = immutable {
+            return Err(error);
+        }
 
         self.check = Some(expected);
         let value = self.visit_expr(value)?;
@@ -995,18 +2565,21 @@ impl<'ctx> ExprVisitor<'ctx> for Engine<'ctx> {
         self.check.take();
         self.unify(expected, value)?;
 
+        if let Some(op) = op {
+            self.check_operator(op, expected, loc)?;
+        }
+
         Ok(self.db.hir_type(Type::new(TypeKind::Unit, loc)))
     }
 
-    // TODO: This is sketchy and doesn't check if they're bin-op-able
     #[crunch_shared::instrument(
         name = "binary operation",
-        skip(self, _loc, lhs, op, rhs),
+        skip(self, loc, lhs, op, rhs),
         fields(operand_type = ?op),
     )]
     fn visit_binop(
         &mut self,
-        _loc: Location,
+        loc: Location,
         lhs: &'ctx Expr<'ctx>,
         op: BinaryOp,
         rhs: &'ctx Expr<'ctx>,
@@ -1028,13 +2601,79 @@ impl<'ctx> ExprVisitor<'ctx> for Engine<'ctx> {
             })?;
         }
 
+        self.check_operator(op, lhs, loc)?;
         Ok(lhs)
     }
 
-    #[crunch_shared::instrument(name = "type cast", skip(self, _loc, casted, ty))]
-    fn visit_cast(&mut self, _loc: Location, &Cast { casted, ty }: &Cast<'ctx>) -> Self::Output {
+    #[crunch_shared::instrument(name = "unary operation", skip(self, loc, operand))]
+    fn visit_unary(
+        &mut self,
+        loc: Location,
+        op: UnaryOp,
+        operand: &'ctx Expr<'ctx>,
+    ) -> Self::Output {
+        let check = self.check;
+        let operand_ty = self.visit_expr(operand)?;
+        if let Some(check) = check {
+            self.unify(operand_ty, check)?;
+        }
+
+        match (op, self.concrete_type(operand_ty).kind) {
+            // Negating an integer that could be either signed or unsigned makes it signed
+            (
+                UnaryOp::Negative,
+                TypeKind::Integer {
+                    signed: None,
+                    width,
+                },
+            ) => {
+                let signed = self.db.context().hir_type(Type::new(
+                    TypeKind::Integer {
+                        signed: Some(true),
+                        width,
+                    },
+                    loc,
+                ));
+                self.unify(operand_ty, signed)?;
+            }
+
+            (
+                UnaryOp::Negative,
+                TypeKind::Integer {
+                    signed: Some(true), ..
+                },
+            )
+            | (UnaryOp::Positive, TypeKind::Integer { .. })
+            | (UnaryOp::Not, TypeKind::Integer { .. })
+            | (UnaryOp::Not, TypeKind::Bool) => {}
+
+            (_, TypeKind::Unknown) => {
+                return Err(Locatable::new(
+                    TypeError::FailedInfer("the operand".to_owned()).into(),
+                    operand.location(),
+                ));
+            }
+
+            (op, kind) => {
+                crunch_shared::error!("attempted to apply {:?} to {:?}", op, kind);
+
+                return Err(Locatable::new(
+                    TypeError::InvalidOperator(op.to_string(), self.display_type(&kind)).into(),
+                    operand.location(),
+                ));
+            }
+        }
+
+        Ok(operand_ty)
+    }
+
+    #[crunch_shared::instrument(name = "type cast", skip(self, loc, casted, ty))]
+    fn visit_cast(&mut self, loc: Location, &Cast { casted, ty }: &Cast<'ctx>) -> Self::Output {
         crunch_shared::warn!("type casts are not verified in any way");
+        self.check_supported(ty, loc)?;
         // FIXME: Verify that the types are castable
+        // The casted value's type has nothing to do with the type it's being cast to
+        self.check.take();
         let _casted = self.visit_expr(casted)?;
 
         Ok(ty)
@@ -1053,54 +2692,228 @@ impl<'ctx> ExprVisitor<'ctx> for Engine<'ctx> {
             .hir_type(Type::new(TypeKind::Reference { referee, mutable }, loc)))
     }
 
+    #[crunch_shared::instrument(name = "dereference", skip(self, _loc, pointer))]
+    fn visit_deref(&mut self, _loc: Location, pointer: &'ctx Expr<'ctx>) -> Self::Output {
+        self.check.take();
+        let pointer_ty = self.visit_expr(pointer)?;
+
+        match self.concrete_type(pointer_ty).kind {
+            TypeKind::Reference {
+                referee: pointee, ..
+            }
+            | TypeKind::Pointer { pointee, .. } => Ok(pointee),
+
+            kind => Err(self.invalid_deref(&kind, pointer.location())),
+        }
+    }
+
     #[crunch_shared::instrument(name = "index", skip(self, loc, var, index))]
     fn visit_index(&mut self, loc: Location, var: Var, index: &'ctx Expr<'ctx>) -> Self::Output {
-        let you_size = self.db.hir_type(Type::new(
-            TypeKind::Integer {
-                signed: None,
-                width: None,
+        let indexee = self.var_type(&var, loc)?;
+        self.index_type(indexee, index, loc)
+    }
+
+    #[crunch_shared::instrument(name = "length", skip(self, loc))]
+    fn visit_length(&mut self, loc: Location, var: Var) -> Self::Output {
+        let var_ty = self.var_type(&var, loc)?;
+
+        // Lengths can be taken through references to arrays and slices
+        let mut ty = self.concrete_type(var_ty);
+        while let TypeKind::Reference { referee, .. } = ty.kind {
+            ty = self.concrete_type(referee);
+        }
+
+        match ty.kind {
+            TypeKind::Array { .. } | TypeKind::Slice { .. } => Ok(self.db.hir_type(Type::new(
+                TypeKind::Integer {
+                    signed: Some(false),
+                    width: Some(64),
+                },
+                loc,
+            ))),
+
+            TypeKind::Unknown => Err(Locatable::new(
+                TypeError::FailedInfer("the iterated value".to_owned()).into(),
+                loc,
+            )),
+
+            ref kind => {
+                crunch_shared::error!("attempted to take the length of {:?}", kind);
+
+                Err(Locatable::new(
+                    TypeError::TypeConflict {
+                        call_type: self.display_type(kind),
+                        def_type: "slice or arr".to_owned(),
+                        def_site: ty.location(),
+                    }
+                    .into(),
+                    loc,
+                ))
+            }
+        }
+    }
+
+    #[crunch_shared::instrument(name = "range", skip(self, loc, _range))]
+    fn visit_range(&mut self, loc: Location, _range: &Range<'ctx>) -> Self::Output {
+        // Ranges used as indices are checked by `index_type` and the ranges of `for` loops
+        // are desugared away, so all other ranges would be used as values
+        Err(Locatable::new(TypeError::RangeValue.into(), loc))
+    }
+
+    #[crunch_shared::instrument(name = "variant", skip(self, loc, enum_id, variant, args))]
+    fn visit_variant(
+        &mut self,
+        loc: Location,
+        VariantCtor {
+            enum_id,
+            variant,
+            args,
+            ty,
+        }: &VariantCtor<'ctx>,
+    ) -> Self::Output {
+        let check = self.check.take();
+        let strings = self.db.context().strings();
+
+        let enum_ = self
+            .enums
+            .get(enum_id)
+            .expect("Enums are registered before their variants are used");
+        let payload = match enum_.variant(*variant) {
+            Some((_, variant)) => variant.elements.clone(),
+            None => {
+                return Err(Locatable::new(
+                    TypeError::UnknownVariant(
+                        enum_.name.to_string(strings),
+                        strings.resolve(*variant).as_ref().to_owned(),
+                    )
+                    .into(),
+                    loc,
+                ));
+            }
+        };
+
+        if payload.len() != args.len() {
+            return Err(Locatable::new(
+                TypeError::VariantArity {
+                    variant: enum_.name.join(*variant).to_string(strings),
+                    expected: payload.len(),
+                    received: args.len(),
+                }
+                .into(),
+                loc,
+            ));
+        }
+
+        // Each construction of a generic enum gets new generics to infer
+        let context = self.db.context();
+        let generics: Vec<TypeId> = enum_
+            .generics
+            .iter()
+            .map(|_| context.hir_type(Type::new(TypeKind::Unknown, loc)))
+            .collect();
+        let subst: HashMap<ItemId, TypeId> = enum_
+            .generics
+            .iter()
+            .map(|generic| generic.id)
+            .zip(generics.iter().copied())
+            .collect();
+
+        for (&arg, element) in args.iter().zip(payload) {
+            let element = generics::substitute(context, element, &subst, None);
+            self.check_arg(arg, element)?;
+        }
+
+        let enum_ty = context.hir_type(Type::new(
+            TypeKind::Enum {
+                id: *enum_id,
+                generics,
             },
-            index.location(),
+            loc,
         ));
-        let index = self.visit_expr(index)?;
-        self.unify(you_size, index)?;
+        self.unify(*ty, enum_ty)?;
 
-        let var = self.var_type(&var, loc)?;
-        let arr_ty = self.db.context().get_hir_type(var).unwrap();
+        if let Some(check) = check {
+            self.unify(*ty, check)?;
+        }
 
-        let mut kind = arr_ty.kind;
-        loop {
-            match kind {
-                TypeKind::Array { element, .. } | TypeKind::Slice { element } => {
-                    crunch_shared::trace!(
-                        "indexee type was a slice or array, returning the element type {:?}",
-                        element,
-                    );
+        Ok(*ty)
+    }
 
-                    return Ok(element);
-                }
+    #[crunch_shared::instrument(name = "array", skip(self, loc, elements, element))]
+    fn visit_array(
+        &mut self,
+        loc: Location,
+        elements: &[&'ctx Expr<'ctx>],
+        element: TypeId,
+    ) -> Self::Output {
+        let check = self.check.take();
+
+        // Every element shares the array's element type
+        if let Some(TypeKind::Array {
+            element: expected, ..
+        }) = check.map(|check| self.concrete_type(check).kind)
+        {
+            self.unify(element, expected)?;
+        }
 
-                TypeKind::Variable(ty) => {
-                    crunch_shared::trace!("indexee type was a variable, iterating");
+        for &elem in elements {
+            self.check = Some(element);
+            let elem_type = self.visit_expr(elem)?;
+            self.check.take();
 
-                    kind = self.db.context().get_hir_type(ty).unwrap().kind
-                }
+            self.unify(elem_type, element)?;
+        }
 
-                _ => {
-                    crunch_shared::error!("invalid indexee type: {:?}", kind);
+        let array = self.db.hir_type(Type::new(
+            TypeKind::Array {
+                element,
+                length: elements.len() as u64,
+            },
+            loc,
+        ));
+        if let Some(check) = check {
+            self.unify(array, check)?;
+        }
 
-                    return Err(Locatable::new(
-                        TypeError::TypeConflict {
-                            call_type: self.display_type(&arr_ty.kind),
-                            def_type: "slice or arr".to_owned(),
-                            def_site: loc,
-                        }
-                        .into(),
-                        loc,
-                    ));
-                }
-            }
+        Ok(array)
+    }
+
+    #[crunch_shared::instrument(name = "tuple", skip(self, loc, elements))]
+    fn visit_tuple(&mut self, loc: Location, elements: &[&'ctx Expr<'ctx>]) -> Self::Output {
+        let check = self.check.take();
+        let expected = match check.map(|check| self.concrete_type(check).kind) {
+            Some(TypeKind::Tuple(expected)) if expected.len() == elements.len() => expected,
+            _ => Vec::new(),
+        };
+
+        let mut element_types = Vec::with_capacity(elements.len());
+        for (i, &elem) in elements.iter().enumerate() {
+            self.check = expected.get(i).copied();
+            element_types.push(self.visit_expr(elem)?);
+            self.check.take();
+        }
+
+        let tuple = self
+            .db
+            .hir_type(Type::new(TypeKind::Tuple(element_types), loc));
+        if let Some(check) = check {
+            self.unify(tuple, check)?;
         }
+
+        Ok(tuple)
+    }
+
+    #[crunch_shared::instrument(name = "tuple field", skip(self, loc, tuple))]
+    fn visit_tuple_field(
+        &mut self,
+        loc: Location,
+        tuple: &'ctx Expr<'ctx>,
+        index: u64,
+    ) -> Self::Output {
+        self.check.take();
+        let tuple_ty = self.visit_expr(tuple)?;
+
+        self.field_type(tuple_ty, index, loc, tuple.location())
     }
 }
 
@@ -1110,6 +2923,9 @@ impl fmt::Debug for Engine<'_> {
             .field("errors", &self.errors)
             .field("current_func", &self.current_func)
             .field("functions", &self.functions)
+            .field("enums", &self.enums)
+            .field("generics", &self.generics)
+            .field("instances", &self.instances)
             .field("variables", &self.variables)
             .field("check", &self.check)
             .finish()
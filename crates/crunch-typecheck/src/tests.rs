@@ -0,0 +1,1405 @@
+use crate::{TypecheckDatabase, TypecheckDatabaseStorage};
+use crunch_parser::database::{ParseDatabase, ParseDatabaseStorage};
+use crunch_shared::{
+    config::{ConfigDatabase, ConfigDatabaseStorage},
+    context::{Arenas, Context, ContextDatabase, ContextDatabaseStorage, OwnedArenas, Snapshot},
+    databases::{SourceDatabase, SourceDatabaseStorage},
+    error::{Error, SemanticError, TypeError},
+    files::FileId,
+    salsa::{self, Storage},
+    testing,
+    trees::hir::Item,
+    utils::Upcast,
+    visitors::hir::Fold,
+};
+use ladder::{HirDatabase, HirDatabaseStorage};
+
+#[salsa::database(
+    ConfigDatabaseStorage,
+    ContextDatabaseStorage,
+    SourceDatabaseStorage,
+    ParseDatabaseStorage,
+    HirDatabaseStorage,
+    TypecheckDatabaseStorage
+)]
+#[derive(Default)]
+struct TestDatabase {
+    storage: Storage<Self>,
+}
+
+impl salsa::Database for TestDatabase {}
+
+impl Upcast<dyn ConfigDatabase> for TestDatabase {
+    fn upcast(&self) -> &dyn ConfigDatabase {
+        &*self
+    }
+}
+
+impl Upcast<dyn ContextDatabase> for TestDatabase {
+    fn upcast(&self) -> &dyn ContextDatabase {
+        &*self
+    }
+}
+
+impl Upcast<dyn SourceDatabase> for TestDatabase {
+    fn upcast(&self) -> &dyn SourceDatabase {
+        &*self
+    }
+}
+
+impl Upcast<dyn ParseDatabase> for TestDatabase {
+    fn upcast(&self) -> &dyn ParseDatabase {
+        &*self
+    }
+}
+
+impl Upcast<dyn HirDatabase> for TestDatabase {
+    fn upcast(&self) -> &dyn HirDatabase {
+        &*self
+    }
+}
+
+impl Upcast<dyn TypecheckDatabase> for TestDatabase {
+    fn upcast(&self) -> &dyn TypecheckDatabase {
+        &*self
+    }
+}
+
+/// Runs `query` on a database holding `source`
+fn with_database<T>(source: &str, query: impl FnOnce(&TestDatabase, FileId) -> T) -> T {
+    testing::with_database(source, |database: &mut TestDatabase, file| {
+        database.set_file_edit(file, None);
+        query(database, file)
+    })
+}
+
+/// Type checks `source`, returning the errors it produced
+fn check(source: &str) -> Result<(), Vec<Error>> {
+    with_database(source, |database, file| {
+        database
+            .typecheck(file)
+            .map_err(|errors| errors.errors().map(|error| error.data().clone()).collect())
+    })
+}
+
+/// Type checks `source`, returning the first error it failed with along with the first line of
+/// the source that the error points to
+fn error_at(source: &str) -> (Error, &str) {
+    with_database(source, |database, file| {
+        let errors = match database.typecheck(file) {
+            Ok(()) => panic!("type checking succeeded on {:?}", source),
+            Err(errors) => errors,
+        };
+
+        let error = errors
+            .errors()
+            .next()
+            .expect("type checking failed without any errors");
+        let data = match error.data() {
+            Error::Suggested(error, _) => (**error).clone(),
+            error => error.clone(),
+        };
+
+        let located = source[error.range()].lines().next().unwrap_or_default();
+        (data, located)
+    })
+}
+
+#[test]
+fn slice_patterns() {
+    check(
+        "
+            fn main() -> i32
+                let numbers := arr[1, 2, 3]
+                return match numbers
+                    arr[first, rest @ ..] =>
+                        first + sum(rest)
+                    end
+                end
+            end
+            fn sum(values: slice[i32]) -> i32
+                return match values
+                    slice[first, .., last] =>
+                        first + last
+                    end
+                    _ =>
+                        0
+                    end
+                end
+            end
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        error_at(
+            "
+                fn main() -> i32
+                    return match arr[1, 2]
+                        arr[first, second, third] =>
+                            first
+                        end
+                    end
+                end
+            ",
+        ),
+        (
+            TypeError::IncorrectType(
+                "Expected an array of 3 elements, but the array has 2".to_owned(),
+            )
+            .into(),
+            "arr[1, 2]",
+        ),
+    );
+}
+
+#[test]
+fn tuple_patterns() {
+    check(
+        "
+            fn main() -> i32
+                return match tup[1, true, 2, 3]
+                    tup[first, .., third, last] =>
+                        first + third + last
+                    end
+                end
+            end
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        error_at(
+            "
+                fn main() -> i32
+                    return match tup[1, 2]
+                        tup[first, second, .., third] =>
+                            first
+                        end
+                    end
+                end
+            ",
+        ),
+        (
+            TypeError::IncorrectType(
+                "Expected a tuple of at least 3 elements, but the tuple has 2".to_owned(),
+            )
+            .into(),
+            "tup[1, 2]",
+        ),
+    );
+
+    assert_eq!(
+        error_at(
+            "
+                fn main() -> i32
+                    return match tup[1, 2]
+                        tup[first, rest @ ..] =>
+                            first
+                        end
+                    end
+                end
+            ",
+        ),
+        (
+            TypeError::IncorrectType(
+                "Only the rest of an array or slice can be bound with `name @ ..`".to_owned(),
+            )
+            .into(),
+            "tup[1, 2]",
+        ),
+    );
+}
+
+#[test]
+fn alternatives_bind_the_same_variables() {
+    check(
+        "
+            enum Shape
+                Square(i32)
+                Rect(i32, i32)
+            end
+            fn main() -> i32
+                return match Shape.Rect(1, 2)
+                    Shape.Square(side) | Shape.Rect(side, _) =>
+                        side
+                    end
+                end
+            end
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        error_at(
+            "
+                enum Shape
+                    Square(i32)
+                    Rect(i32, i32)
+                end
+                fn main() -> i32
+                    return match Shape.Rect(1, 2)
+                        Shape.Square(side) | Shape.Rect(_, _) =>
+                            side
+                        end
+                    end
+                end
+            ",
+        ),
+        (
+            TypeError::UnboundAlternative("side".to_owned()).into(),
+            "Shape.Rect(1, 2)",
+        ),
+    );
+}
+
+#[test]
+fn unresolved_patterns() {
+    assert_eq!(
+        error_at(
+            "
+                fn main() -> i32
+                    return match 1
+                        Point { x, .. } =>
+                            x
+                        end
+                    end
+                end
+            ",
+        ),
+        (TypeError::UnresolvedPattern("Point".to_owned()).into(), "1"),
+    );
+
+    assert_eq!(
+        error_at(
+            "
+                enum Shape
+                    Square(i32)
+                end
+                fn main() -> i32
+                    return match Shape.Square(1)
+                        Shape.Square(width, height) =>
+                            width
+                        end
+                    end
+                end
+            ",
+        ),
+        (
+            TypeError::VariantArity {
+                variant: "Shape.Square".to_owned(),
+                expected: 1,
+                received: 2,
+            }
+            .into(),
+            "Shape.Square(1)",
+        ),
+    );
+}
+
+/// Rebuilds trees without changing them
+struct Identity<'ctx> {
+    context: &'ctx Context<'ctx>,
+}
+
+impl<'ctx> Fold<'ctx> for Identity<'ctx> {
+    fn context(&self) -> &'ctx Context<'ctx> {
+        self.context
+    }
+}
+
+#[test]
+fn identity_fold() {
+    let source = "
+        enum Shape
+            Square(i32)
+            Rect(i32, i32)
+        end
+        trait Area
+            fn area(&self) -> i32
+                empty
+            end
+        end
+        extend Shape with Area
+            fn area(&self) -> i32
+                return match self
+                    Shape.Square(side) =>
+                        side * side
+                    end
+                    Shape.Rect(width, height) =>
+                        width * height
+                    end
+                end
+            end
+        end
+        fn largest[T](a: T, b: T) -> T
+            return a
+        end
+        fn main() -> i32
+            let mut total := 0
+            for side in arr[1, 2, 3]
+                total += Shape.Square(side).area()
+            end
+            while total > 100
+                total -= 1
+            end
+            if total == 0
+                return -1
+            end
+            return largest(total, 1)
+        end
+    ";
+
+    with_database(source, |database, file| {
+        let items = database.lower_hir(file).unwrap();
+        let mut identity = Identity {
+            context: database.context(),
+        };
+
+        for item in items.iter() {
+            assert_eq!(&identity.fold_item(item), *item);
+        }
+    });
+}
+
+#[test]
+fn context_snapshot_round_trip() {
+    let source = "
+        enum Shape
+            Square(i32)
+            Rect(i32, i32)
+        end
+        fn area(shape: Shape, scale: i32) -> i32
+            return match shape
+                Shape.Square(side) =>
+                    side * side * scale
+                end
+                Shape.Rect(width, height) =>
+                    width * height * scale
+                end
+            end
+        end
+        fn main() -> i32
+            let message := \"a {1} string\"
+            return area(Shape.Rect(2, 3), 2)
+        end
+    ";
+
+    with_database(source, |database, file| {
+        database.typecheck(file).unwrap();
+
+        let context = database.context();
+        let snapshot = Snapshot {
+            ast: database.parse(file).unwrap().to_vec(),
+            hir: database.lower_hir(file).unwrap().to_vec(),
+        };
+        let saved = context
+            .save(&snapshot, serde_json::value::Serializer)
+            .unwrap();
+
+        let owned_arenas = OwnedArenas::default();
+        let (loaded, loaded_snapshot) = Context::load(Arenas::from(&owned_arenas), saved).unwrap();
+        assert_eq!(loaded_snapshot, snapshot);
+
+        // Every string and type from the saved context means the same thing in the loaded one
+        for item in snapshot.hir.iter() {
+            if let Item::Function(func) = item {
+                assert_eq!(
+                    loaded.strings().resolve(func.name.to_vec()[0]).as_ref(),
+                    context.strings().resolve(func.name.to_vec()[0]).as_ref(),
+                );
+
+                let types = func.args.iter().map(|arg| arg.kind).chain(Some(func.ret));
+                for ty in types {
+                    assert!(context.get_hir_type(ty).is_some());
+                    assert_eq!(loaded.get_hir_type(ty), context.get_hir_type(ty));
+                }
+            }
+        }
+    });
+}
+
+#[test]
+fn enum_variants() {
+    check(
+        "
+            enum Shape
+                Empty
+                Square(i32)
+                Rect(i32, i32)
+            end
+            fn main() -> i32
+                let nothing := Shape.Empty
+                return match Shape.Rect(2, 3)
+                    Shape.Rect(width, height) =>
+                        width * height
+                    end
+                    Shape.Square(side) =>
+                        side
+                    end
+                    Shape.Empty =>
+                        0
+                    end
+                end
+            end
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        error_at(
+            "
+                enum Shape
+                    Square(i32)
+                end
+                fn main() -> i32
+                    let shape := Shape.Circle(1)
+                    return 0
+                end
+            ",
+        ),
+        (
+            TypeError::UnknownVariant("Shape".to_owned(), "Circle".to_owned()).into(),
+            "Shape.Circle(1)",
+        ),
+    );
+
+    assert_eq!(
+        error_at(
+            "
+                enum Shape
+                    Rect(i32, i32)
+                end
+                fn main() -> i32
+                    let shape := Shape.Rect(1)
+                    return 0
+                end
+            ",
+        ),
+        (
+            TypeError::VariantArity {
+                variant: "Shape.Rect".to_owned(),
+                expected: 2,
+                received: 1,
+            }
+            .into(),
+            "Shape.Rect(1)",
+        ),
+    );
+
+    let mismatched = "
+        enum Shape
+            Square(i32)
+        end
+        fn main() -> i32
+            let shape := Shape.Square(true)
+            return 0
+        end
+    ";
+    let (error, located) = error_at(mismatched);
+    assert_eq!(located, "true");
+    assert!(matches!(
+        error,
+        Error::Type(TypeError::TypeConflict { call_type, def_type, def_site })
+            if call_type == "bool" && def_type == "i32" && &mismatched[def_site.range()] == "i32"
+    ));
+}
+
+const SHAPES: &str = "
+    enum Shape
+        Square(i32)
+        Rect(i32, i32)
+    end
+    trait Area
+        fn area(&self) -> i32
+            empty
+        end
+    end
+";
+
+#[test]
+fn trait_methods() {
+    check(&format!(
+        "{}
+            extend Shape with Area
+                fn area(&self) -> i32
+                    return match self
+                        Shape.Square(side) =>
+                            side * side
+                        end
+                        Shape.Rect(width, height) =>
+                            width * height
+                        end
+                    end
+                end
+            end
+            extend Shape
+                fn doubled(self) -> Shape
+                    return match self
+                        Shape.Square(side) =>
+                            Shape.Square(side * 2)
+                        end
+                        Shape.Rect(width, height) =>
+                            Shape.Rect(width * 2, height * 2)
+                        end
+                    end
+                end
+            end
+            fn main() -> i32
+                let pair := tup[Shape.Square(2), 1]
+                return pair.0.doubled().area() + Shape.Rect(1, 2).area()
+            end
+        ",
+        SHAPES,
+    ))
+    .unwrap();
+
+    let missing = format!(
+        "{}
+            extend Shape with Area
+            end
+            fn main() -> i32
+                return 0
+            end
+        ",
+        SHAPES,
+    );
+    assert_eq!(
+        error_at(&missing),
+        (
+            TypeError::MissingTraitMethod {
+                trait_: "Area".to_owned(),
+                ty: "Shape".to_owned(),
+                method: "area".to_owned(),
+            }
+            .into(),
+            "extend Shape with Area",
+        ),
+    );
+
+    let extra = format!(
+        "{}
+            extend Shape with Area
+                fn area(&self) -> i32
+                    return 0
+                end
+                fn perimeter(&self) -> i32
+                    return 0
+                end
+            end
+            fn main() -> i32
+                return 0
+            end
+        ",
+        SHAPES,
+    );
+    assert_eq!(
+        error_at(&extra),
+        (
+            TypeError::NotTraitMethod("perimeter".to_owned(), "Area".to_owned()).into(),
+            "fn perimeter(&self) ->",
+        ),
+    );
+
+    let mismatched = format!(
+        "{}
+            extend Shape with Area
+                fn area(&self) -> bool
+                    return true
+                end
+            end
+            fn main() -> i32
+                return 0
+            end
+        ",
+        SHAPES,
+    );
+    let (error, located) = error_at(&mismatched);
+    assert_eq!(located, "fn area(&self) ->");
+
+    // The definition site is the method's declaration within the trait
+    assert!(matches!(
+        error,
+        Error::Type(TypeError::TraitMethodSignature { method, trait_, def_site })
+            if method == "area"
+                && trait_ == "Area"
+                && def_site.range().end <= SHAPES.len()
+                && &mismatched[def_site.range()] == "fn area(&self) ->"
+    ));
+
+    let unimplemented = format!(
+        "{}
+            fn main() -> i32
+                return Shape.Square(1).area()
+            end
+        ",
+        SHAPES,
+    );
+    assert_eq!(
+        error_at(&unimplemented),
+        (
+            TypeError::UnknownMethod("Shape".to_owned(), "area".to_owned()).into(),
+            "area",
+        ),
+    );
+}
+
+#[test]
+fn with_blocks() {
+    let lock = "
+        enum Lock
+            Held
+        end
+
+        extend Lock with Scoped
+            fn exit(&self)
+                empty
+            end
+        end
+
+        fn main() -> i32
+            with Lock.Held as lock
+                empty
+            end
+
+            return 0
+        end
+    ";
+
+    // `Scoped` comes from the prelude, but files can declare their own in its place
+    check(lock).unwrap();
+    check(&format!(
+        "
+            trait Scoped
+                fn exit(&self)
+                    empty
+                end
+            end
+            {}
+        ",
+        lock,
+    ))
+    .unwrap();
+
+    let (error, located) = error_at(
+        "
+            fn main() -> i32
+                with 1 as one
+                    empty
+                end
+
+                return 0
+            end
+        ",
+    );
+    assert_eq!(located, "1");
+    match error {
+        Error::Type(TypeError::UnsatisfiedBound {
+            ty,
+            trait_,
+            def_site,
+        }) => assert_eq!(
+            (ty.as_str(), trait_.as_str(), def_site.file()),
+            ("{{integer}}", "Scoped", FileId::PRELUDE),
+        ),
+        error => panic!("expected an unsatisfied bound, got {:?}", error),
+    }
+}
+
+#[test]
+fn call_arguments() {
+    let connect = "
+        fn connect(port: i32, retries: i32 := port / 2, hosts: ..i32) -> i32
+            return port + retries
+        end
+    ";
+    let call = |body: &str| {
+        format!(
+            "{}
+                fn main() -> i32
+                    let rest := arr[1, 2]
+                    {}
+                end
+            ",
+            connect, body,
+        )
+    };
+
+    // Named, defaulted and spread arguments
+    check(&call("return connect(retries := 1, port := 3)")).unwrap();
+    check(&call("return connect(80)")).unwrap();
+    check(&call("return connect(80, 1, 2, 3)")).unwrap();
+    check(&call("return connect(80, 1, ...rest[..])")).unwrap();
+
+    // Defaults are evaluated with the callee's bindings, which only hold the arguments before them
+    let (error, located) = error_at(
+        "
+            fn connect(retries: i32 := port, port: i32) -> i32
+                return port + retries
+            end
+        ",
+    );
+    assert_eq!(located, "port");
+    assert!(matches!(error, Error::Type(TypeError::VarNotInScope(var)) if var == "port"));
+
+    let missing = call("return connect(retries := 1)");
+    let (error, located) = error_at(&missing);
+    assert_eq!(located, "connect(retries := 1)");
+    assert!(matches!(error, Error::Type(TypeError::MissingArg(arg)) if arg == "port"));
+
+    let duplicate = call("return connect(80, port := 1)");
+    let (error, located) = error_at(&duplicate);
+    assert_eq!(located, "port");
+    assert!(matches!(error, Error::Type(TypeError::DuplicateArg(arg)) if arg == "port"));
+
+    let unknown = call("return connect(80, host := 1)");
+    let (error, located) = error_at(&unknown);
+    assert_eq!(located, "host");
+    assert!(matches!(
+        error,
+        Error::Type(TypeError::UnknownArg(func, arg)) if func == "connect" && arg == "host"
+    ));
+
+    // Functions without a variadic argument can't be given too many values or a spread
+    let fixed = |body: &str| {
+        format!(
+            "
+                fn connect(port: i32, retries: i32 := 2) -> i32
+                    return port + retries
+                end
+
+                fn main() -> i32
+                    let rest := arr[1, 2]
+                    {}
+                end
+            ",
+            body,
+        )
+    };
+
+    let too_many = fixed("return connect(80, 3, 10)");
+    let (error, located) = error_at(&too_many);
+    assert_eq!(located, "connect(80, 3, 10)");
+    assert!(matches!(
+        error,
+        Error::Type(TypeError::TooManyArgs {
+            expected: 2,
+            received: 3,
+            ..
+        })
+    ));
+
+    let spread = fixed("return connect(80, ...rest)");
+    let (error, located) = error_at(&spread);
+    assert_eq!(located, "rest");
+    assert!(matches!(error, Error::Type(TypeError::UnexpectedSpread(func)) if func == "connect"));
+}
+
+#[test]
+fn invalid_method_chains() {
+    let chained = format!(
+        "{}
+            fn main() -> i32
+                let shape := Shape.Square(1)
+                return shape.side.area()
+            end
+        ",
+        SHAPES,
+    );
+    assert_eq!(
+        error_at(&chained),
+        (SemanticError::UnsupportedFieldAccess.into(), "side"),
+    );
+}
+
+#[test]
+fn generics() {
+    let weights = "
+        enum Maybe[T]
+            Just(T)
+            Nothing
+        end
+        trait Weight
+            fn weight(&self) -> i32
+                empty
+            end
+        end
+        enum Coin
+            Penny
+        end
+        extend Coin with Weight
+            fn weight(&self) -> i32
+                return 1
+            end
+        end
+        fn total[T: Weight](a: T, b: T) -> i32
+            return a.weight() + b.weight()
+        end
+        fn unwrap_or[T](value: Maybe[T], default: T) -> T
+            return match value
+                Maybe.Just(inner) =>
+                    inner
+                end
+                Maybe.Nothing =>
+                    default
+                end
+            end
+        end
+    ";
+
+    check(&format!(
+        "{}
+            fn main() -> i32
+                let some: Maybe[i32] := Maybe.Just(30)
+                return total(Coin.Penny, Coin.Penny) + unwrap_or(some, 0)
+            end
+        ",
+        weights,
+    ))
+    .unwrap();
+
+    let unbounded = format!(
+        "{}
+            fn main() -> i32
+                return total(1, 2)
+            end
+        ",
+        weights,
+    );
+    let (error, located) = error_at(&unbounded);
+    assert_eq!(located, "total(1, 2)");
+    assert!(matches!(
+        error,
+        Error::Type(TypeError::UnsatisfiedBound { ty, trait_, def_site })
+            if ty == "{{integer}}" && trait_ == "Weight" && &unbounded[def_site.range()] == "T: Weight"
+    ));
+
+    let mismatched = format!(
+        "{}
+            fn main() -> i32
+                return unwrap_or(Maybe.Just(true), 0)
+            end
+        ",
+        weights,
+    );
+    let (error, located) = error_at(&mismatched);
+    assert_eq!(located, "0");
+    assert!(matches!(
+        error,
+        Error::Type(TypeError::TypeConflict { call_type, def_type, def_site })
+            if call_type == "{{integer}}" && def_type == "bool" && &mismatched[def_site.range()] == "true"
+    ));
+
+    let uninferred = format!(
+        "{}
+            fn zero[T]() -> i32
+                return 0
+            end
+            fn main() -> i32
+                return zero()
+            end
+        ",
+        weights,
+    );
+    assert_eq!(
+        error_at(&uninferred),
+        (
+            TypeError::UninferredGeneric("T".to_owned()).into(),
+            "zero()"
+        ),
+    );
+
+    let arity = format!(
+        "{}
+            fn main() -> i32
+                let some: Maybe[i32, bool] := Maybe.Just(1)
+                return 0
+            end
+        ",
+        weights,
+    );
+    assert_eq!(
+        error_at(&arity),
+        (
+            SemanticError::GenericArity {
+                name: "Maybe".to_owned(),
+                expected: 1,
+                received: 2,
+            }
+            .into(),
+            "Maybe[i32, bool]",
+        ),
+    );
+}
+
+#[test]
+fn for_loops() {
+    check(
+        "
+            fn main() -> i32
+                let mut total := 0
+                for i in 0..4
+                    total += i
+                end
+                let numbers := arr[1, 2, 3]
+                for number in numbers
+                    total += number
+                then
+                    total += 1
+                else
+                    total -= 1
+                end
+                return total + sum(numbers[1..3])
+            end
+            fn sum(values: slice[i32]) -> i32
+                let mut total := 0
+                for value in values
+                    total += value
+                end
+                return total
+            end
+        ",
+    )
+    .unwrap();
+
+    // The iterated value has to be a range, an array or a slice
+    let not_iterable = "
+        fn main() -> i32
+            let mut total := 0
+            for value in true
+                total += 1
+            end
+            return total
+        end
+    ";
+    let (error, located) = error_at(not_iterable);
+    assert_eq!(located, "true");
+    assert!(matches!(
+        error,
+        Error::Type(TypeError::TypeConflict { call_type, def_type, def_site })
+            if call_type == "bool"
+                && def_type == "slice or arr"
+                && &not_iterable[def_site.range()] == "true"
+    ));
+
+    assert_eq!(
+        error_at(
+            "
+                fn main() -> i32
+                    for i in ..10
+                        return i
+                    end
+                    return 0
+                end
+            ",
+        ),
+        (SemanticError::UnboundedRangeStart.into(), "..10"),
+    );
+}
+
+#[test]
+fn loop_break_values() {
+    check(
+        "
+            fn main() -> i32
+                let mut count := 0
+                let found := loop
+                    count += 1
+                    if count < 3
+                        continue
+                    end
+                    break count * 10
+                else
+                    count += 1
+                end
+                return found + count
+            end
+        ",
+    )
+    .unwrap();
+
+    // Every break gives the loop a value of the same type
+    let mismatched = "
+        fn main() -> i32
+            let found := loop
+                if true
+                    break 1
+                end
+                break false
+            end
+            return 0
+        end
+    ";
+    let (error, located) = error_at(mismatched);
+    assert_eq!(located, "false");
+    assert!(matches!(
+        error,
+        Error::Type(TypeError::TypeConflict { call_type, def_type, def_site })
+            if call_type == "bool"
+                && def_type == "{{integer}}"
+                && &mismatched[def_site.range()] == "1"
+    ));
+
+    assert_eq!(
+        error_at(
+            "
+                fn main() -> i32
+                    continue
+                    return 0
+                end
+            ",
+        ),
+        (
+            SemanticError::OutsideLoop("continue".to_owned()).into(),
+            "continue"
+        ),
+    );
+}
+
+#[test]
+fn arrays_and_tuples() {
+    check(
+        "
+            fn main() -> i32
+                let numbers := arr[1, 2, 3]
+                let pair := tup[numbers[0], true]
+                let rest: slice[i32] := numbers[1..3]
+                return pair.0 + rest[1] + numbers[2]
+            end
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        error_at(
+            "
+                fn main() -> i32
+                    let numbers := arr[1, 2, 3]
+                    return numbers[3]
+                end
+            ",
+        ),
+        (
+            TypeError::IndexOutOfBounds {
+                index: 3,
+                ty: "arr[{{integer}}; 3]".to_owned(),
+                length: 3,
+            }
+            .into(),
+            "3",
+        ),
+    );
+
+    assert_eq!(
+        error_at(
+            "
+                fn main() -> i32
+                    let pair := tup[1, 2]
+                    return pair.2
+                end
+            ",
+        ),
+        (
+            TypeError::NoTupleField("tup[{{integer}}, {{integer}}]".to_owned(), 2).into(),
+            "pair.2",
+        ),
+    );
+
+    // Array elements all share the first element's type
+    let mismatched = "
+        fn main() -> i32
+            let numbers := arr[1, true]
+            return 0
+        end
+    ";
+    let (error, located) = error_at(mismatched);
+    assert_eq!(located, "true");
+    assert!(matches!(
+        error,
+        Error::Type(TypeError::TypeConflict { call_type, def_type, def_site })
+            if call_type == "bool"
+                && def_type == "{{integer}}"
+                && &mismatched[def_site.range()] == "1"
+    ));
+}
+
+#[test]
+fn places() {
+    check(
+        "
+            fn main() -> i32
+                let mut total := 4
+                total += 6
+                let mut pairs := arr[tup[1, 2], tup[3, 4]]
+                pairs[1].0 := 40
+                increment(&mut total)
+                return total + pairs[1].0
+            end
+            fn increment(value: &mut i32)
+                *value += 1
+                return
+            end
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        error_at(
+            "
+                fn main() -> i32
+                    let pair := tup[1, 2]
+                    pair.0 := 10
+                    return pair.0
+                end
+            ",
+        ),
+        (
+            TypeError::ImmutableAssignment("pair".to_owned()).into(),
+            "pair",
+        ),
+    );
+
+    assert_eq!(
+        error_at(
+            "
+                fn main() -> i32
+                    let total := 0
+                    increment(&total)
+                    return total
+                end
+                fn increment(value: &i32)
+                    *value += 1
+                    return
+                end
+            ",
+        ),
+        (TypeError::ImmutableDeref("&i32".to_owned()).into(), "value"),
+    );
+
+    assert_eq!(
+        error_at(
+            "
+                fn main() -> i32
+                    let mut total := 0
+                    *total := 1
+                    return total
+                end
+            ",
+        ),
+        (
+            TypeError::InvalidDeref("{{integer}}".to_owned()).into(),
+            "total",
+        ),
+    );
+
+    assert_eq!(
+        error_at(
+            "
+                fn main() -> i32
+                    1 := 2
+                    return 0
+                end
+            ",
+        ),
+        (SemanticError::InvalidAssignment.into(), "1"),
+    );
+}
+
+#[test]
+fn unary_operators() {
+    check(
+        "
+            fn main() -> i32
+                let value: i32 := 10
+                let inverted := !0
+                return match !false
+                    true =>
+                        -value + +value + inverted
+                    end
+                    false =>
+                        0
+                    end
+                end
+            end
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        error_at(
+            "
+                fn main() -> i32
+                    let length: u32 := 10
+                    let negated := -length
+                    return 0
+                end
+            ",
+        ),
+        (
+            TypeError::InvalidOperator("-".to_owned(), "u32".to_owned()).into(),
+            "length",
+        ),
+    );
+
+    assert_eq!(
+        error_at(
+            "
+                fn main() -> i32
+                    let negated := -true
+                    return 0
+                end
+            ",
+        ),
+        (
+            TypeError::InvalidOperator("-".to_owned(), "bool".to_owned()).into(),
+            "true",
+        ),
+    );
+}
+
+#[test]
+fn binary_operators() {
+    check(
+        "
+            fn main() -> i32
+                let a: i32 := 17
+                let b: i32 := 5
+                let arith := a % b + b ** 2 - (a >> 2) + (1 << 3)
+                let mut bits: u8 := 12
+                bits |= 3
+                bits &= 10
+                bits ^= 6
+                let checked := a > b and (b <= 5 or false) and true ^ false
+                return match checked
+                    true =>
+                        arith + (bits as i32)
+                    end
+                    false =>
+                        0
+                    end
+                end
+            end
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        error_at(
+            "
+                fn main() -> i32
+                    let ordered := true < false
+                    return 0
+                end
+            ",
+        ),
+        (
+            TypeError::InvalidOperator("<".to_owned(), "bool".to_owned()).into(),
+            "true < false",
+        ),
+    );
+
+    // Both sides of a short-circuiting operator share the left side's type
+    let mismatched = "
+        fn main() -> i32
+            let both := 1 and true
+            return 0
+        end
+    ";
+    let (error, located) = error_at(mismatched);
+    assert_eq!(located, "1 and true");
+    assert!(matches!(
+        error,
+        Error::Type(TypeError::TypeConflict { call_type, def_type, def_site })
+            if call_type == "bool"
+                && def_type == "{{integer}}"
+                && &mismatched[def_site.range()] == "1"
+    ));
+
+    assert_eq!(
+        error_at(
+            "
+                fn main() -> i32
+                    let range := 1..10
+                    return 0
+                end
+            ",
+        ),
+        (TypeError::RangeValue.into(), "1..10"),
+    );
+}
+
+#[test]
+fn type_aliases() {
+    check(
+        "
+            alias Score = i32
+            alias Scores = arr[Score; 3]
+            alias Total = Score
+            fn sum(scores: Scores) -> Total
+                return scores[0] + scores[1] + scores[2]
+            end
+            fn main() -> Score
+                let scores: Scores := arr[10, 12, 20]
+                return sum(scores)
+            end
+        ",
+    )
+    .unwrap();
+
+    // Aliases are checked as the type they stand for
+    let mismatched = "
+        alias Flag = bool
+        fn main() -> i32
+            let flag: Flag := 1
+            return 0
+        end
+    ";
+    let (error, located) = error_at(mismatched);
+    assert_eq!(located, "1");
+    assert!(matches!(
+        error,
+        Error::Type(TypeError::TypeConflict { call_type, def_type, def_site })
+            if call_type == "{{integer}}"
+                && def_type == "bool"
+                && &mismatched[def_site.range()] == "bool"
+    ));
+
+    assert_eq!(
+        error_at(
+            "
+                enum User
+                    Named(u32)
+                end
+                enum Admin
+                    Named(u32)
+                end
+                fn count(users: slice[User | Admin]) -> u64
+                    return 0
+                end
+                fn main() -> i32
+                    return 0
+                end
+            ",
+        ),
+        (
+            TypeError::UnsupportedType("slice[User | Admin]".to_owned()).into(),
+            "users",
+        ),
+    );
+
+    assert_eq!(
+        error_at(
+            "
+                alias Node = arr[Tree; 2]
+                alias Tree = Node
+                fn main() -> i32
+                    return 0
+                end
+            ",
+        ),
+        (
+            SemanticError::RecursiveAlias("Node".to_owned()).into(),
+            "Node"
+        ),
+    );
+}
@@ -1,33 +1,38 @@
 extern crate alloc;
 
 use alloc::sync::Arc;
-use crunch_parser::database::ParseDatabase;
+use core::mem;
+use crunch_parser::{database::ParseDatabase, FlattenExternals, Parser};
 use crunch_shared::{
     config::EmissionKind,
     context::{Context, ContextDatabase},
-    error::{ErrorHandler, Locatable, Location},
-    files::FileId,
+    error::{ErrorHandler, Locatable, Location, SemanticError, Warning},
+    files::{CurrentFile, FileCache, FileId, PRELUDE},
     salsa,
     strings::StrT,
     tracing,
     trees::{
         ast::{
             Arm as AstMatchArm, AssignKind, BinaryOp, Binding as AstBinding, Block as AstBlock,
-            CompOp, Dest as AstDest, Exposure as AstExposure, Expr as AstExpr,
-            ExprKind as AstExprKind, For as AstFor, FuncArg as AstFuncArg, If as AstIf,
-            IfCond as AstIfCond, Item as AstItem, Literal as AstLiteral,
+            CallArg as AstCallArg, CompOp, Dest as AstDest, Exposure as AstExposure,
+            Expr as AstExpr, ExprKind as AstExprKind, ExternBlock as AstExternBlock, For as AstFor,
+            FormatSegment as AstFormatSegment, FuncArg as AstFuncArg, If as AstIf,
+            IfCond as AstIfCond, Item as AstItem, ItemKind as AstItemKind, Literal as AstLiteral,
             LiteralVal as AstLiteralVal, Loop as AstLoop, Match as AstMatch, Pattern as AstPattern,
-            Stmt as AstStmt, StmtKind as AstStmtKind, Type as AstType, TypeMember as AstTypeMember,
-            UnaryOp, VarDecl as AstVarDecl, Variant as AstVariant, While as AstWhile,
+            Range as AstRange, Stmt as AstStmt, StmtKind as AstStmtKind, Type as AstType,
+            TypeMember as AstTypeMember, TypeOp, UnaryOp, VarDecl as AstVarDecl,
+            Variant as AstVariant, Vis, While as AstWhile, With as AstWith,
         },
         hir::{
-            Binding, Block, Break, Cast, Expr, ExprKind, ExternFunc, FuncArg, FuncCall, Function,
-            Item, Literal, LiteralVal, Match, MatchArm, Pattern, Reference, Return, Stmt, Type,
-            TypeId, TypeKind, Var, VarDecl,
+            Binding, Block, Break, CallArg, Cast, Enum, Expr, ExprKind, ExtendBlock, ExternFunc,
+            FormatSegment, FuncArg, FuncCall, Function, Generic, Integer, Item, ItemId, Literal,
+            LiteralVal, Loop, Match, MatchArm, MethodCall, Pattern, Place, PlaceKind, Range,
+            RangeKind, Reference, Return, Stmt, Trait, Type, TypeId, TypeKind, Var, VarDecl,
+            Variant, VariantCtor,
         },
-        CallConv, ItemPath, Sided,
+        CallConv, ItemPath, Ref, Sided, Sign,
     },
-    utils::Upcast,
+    utils::{HashMap, HashSet, Hasher, Upcast},
     visitors::ast::{ExprVisitor, ItemVisitor, StmtVisitor, TypeVisitor},
 };
 
@@ -54,13 +59,22 @@ fn lower_hir(
     let config = db.config();
     let ast = db.parse(file)?;
 
+    let hir = crunch_shared::allocator::CRUNCHC_ALLOCATOR
+        .record_region("hir lowering", || Ladder::new(db).lower(&*ast))
+        .map(|(hir, mut warnings)| {
+            warnings.emit(
+                &FileCache::upcast(db),
+                &**db.writer(),
+                &**db.stdout_config(),
+            );
+
+            hir
+        })
+        .map_err(Arc::new)?;
+
     // FIXME: I hate this
-    let hir = unsafe {
-        core::mem::transmute::<Vec<&'_ Item<'_>>, Vec<&'static Item<'static>>>(
-            crunch_shared::allocator::CRUNCHC_ALLOCATOR
-                .record_region("hir lowering", || Ladder::new(db).lower(&*ast)),
-        )
-    };
+    let hir =
+        unsafe { core::mem::transmute::<Vec<&'_ Item<'_>>, Vec<&'static Item<'static>>>(hir) };
 
     if config.emit.contains(&EmissionKind::Hir) {
         let path = db
@@ -79,9 +93,61 @@ fn lower_hir(
     Ok(Arc::new(hir))
 }
 
+#[derive(Debug, Copy, Clone)]
+struct Declaration {
+    vis: Vis,
+    loc: Location,
+    kind: DeclarationKind,
+    /// The number of generic parameters the item takes
+    generics: usize,
+}
+
+/// The kinds of items that paths can be resolved through
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum DeclarationKind {
+    Module,
+    /// An enum, whose variants are accessed as `Enum.Variant`
+    Enum(ItemId),
+    /// A trait, which can be implemented by `extend` blocks
+    Trait(ItemId),
+    /// An alias, whose type is filled in once the alias has been lowered so that aliases
+    /// can be used before they're declared
+    Alias(TypeId),
+    Other,
+}
+
+/// A scope that control flow can jump out of
+#[derive(Debug, Copy, Clone)]
+enum ExitScope {
+    /// A loop, which is exited by `break` and `continue`
+    Loop,
+    /// A `with` block, which must call the exit hook of the `Scoped` trait on the value
+    /// it holds
+    With {
+        value: Var,
+        scoped: ItemId,
+        loc: Location,
+    },
+}
+
 pub struct Ladder<'ctx> {
     db: &'ctx dyn HirDatabase,
     variable_counter: usize,
+    /// The path of the module currently being lowered
+    module: ItemPath,
+    /// Every item declared within the file
+    declarations: HashMap<ItemPath, Declaration>,
+    /// Items lowered from within modules, which are all hoisted to the top level
+    module_items: Vec<&'ctx Item<'ctx>>,
+    /// The loops and `with` blocks enclosing the expression currently being lowered
+    exit_scopes: Vec<ExitScope>,
+    /// The type `Self` refers to within traits and `extend` blocks
+    self_type: Option<TypeKind>,
+    /// The generic parameters of the item currently being lowered
+    generics: Vec<Generic>,
+    /// The generic parameters that have been referred to, used to warn about unused ones
+    used_generics: HashSet<ItemId>,
+    errors: ErrorHandler,
 }
 
 impl<'ctx> Ladder<'ctx> {
@@ -89,16 +155,614 @@ impl<'ctx> Ladder<'ctx> {
         Self {
             db,
             variable_counter: 0,
+            module: ItemPath::default(),
+            declarations: HashMap::with_hasher(Hasher::default()),
+            module_items: Vec::new(),
+            exit_scopes: Vec::new(),
+            self_type: None,
+            generics: Vec::new(),
+            used_generics: HashSet::with_hasher(Hasher::default()),
+            errors: ErrorHandler::new(),
         }
     }
 
-    pub fn lower(&mut self, items: &[&AstItem<'_>]) -> Vec<&'ctx Item<'ctx>> {
-        items
+    /// Lowers the items of a file, returning the lowered items along with any warnings
+    /// encountered while lowering them
+    pub fn lower(
+        &mut self,
+        items: &[&AstItem<'_>],
+    ) -> Result<(Vec<&'ctx Item<'ctx>>, ErrorHandler), ErrorHandler> {
+        self.declare_items(&ItemPath::default(), items);
+        let prelude = self.prelude();
+        self.declare_items(&ItemPath::default(), &prelude);
+
+        let mut lowered: Vec<_> = prelude
             .iter()
+            .chain(items)
             .filter_map(|item| self.visit_item(item))
+            .collect();
+        lowered.append(&mut self.module_items);
+        self.check_alias_cycles();
+
+        if self.errors.is_fatal() {
+            Err(self.errors.take())
+        } else {
+            Ok((lowered, self.errors.take()))
+        }
+    }
+
+    /// Parses the prelude, leaving out any of its items that have been replaced by items of
+    /// the same name declared within the file
+    fn prelude(&self) -> Vec<&'ctx AstItem<'ctx>> {
+        let file = CurrentFile::new(FileId::PRELUDE, PRELUDE.len());
+        let (items, _) = Parser::new(PRELUDE, self.db.config(), file, self.context())
+            .parse()
+            .expect("the prelude should always parse");
+
+        items
+            .into_iter()
+            .filter(|item| match item.name {
+                Some(name) => !self.declarations.contains_key(&ItemPath::new(name)),
+                None => true,
+            })
             .collect()
     }
 
+    /// Records the path and visibility of every item within a module so that paths can be
+    /// resolved before the items they point to have been lowered
+    fn declare_items(&mut self, module: &ItemPath, items: &[&AstItem<'_>]) {
+        for item in items {
+            if let AstItemKind::ExternBlock(AstExternBlock { items }) = &item.kind {
+                self.declare_items(module, items);
+                continue;
+            }
+
+            // Invalid alias names are reported when the alias is lowered
+            if let AstItemKind::Alias { alias, .. } = &item.kind {
+                if let Some(name) = Self::alias_name(**alias) {
+                    self.declare_alias(module.join(name), item, alias.location());
+                }
+
+                continue;
+            }
+
+            if let Some(name) = item.name {
+                let path = module.join(name);
+                let generics = match &item.kind {
+                    AstItemKind::Func { generics, .. }
+                    | AstItemKind::Type { generics, .. }
+                    | AstItemKind::Enum { generics, .. }
+                    | AstItemKind::Trait { generics, .. } => {
+                        generics.as_ref().map_or(0, |generics| generics.len())
+                    }
+                    _ => 0,
+                };
+
+                let declaration = Declaration {
+                    vis: item.vis.unwrap_or_default(),
+                    loc: item.location(),
+                    generics,
+                    kind: match item.kind {
+                        AstItemKind::Module(..) => DeclarationKind::Module,
+                        AstItemKind::Enum { .. } => {
+                            DeclarationKind::Enum(self.context().next_item_id())
+                        }
+                        AstItemKind::Trait { .. } => {
+                            DeclarationKind::Trait(self.context().next_item_id())
+                        }
+                        _ => DeclarationKind::Other,
+                    },
+                };
+
+                self.declare(path.clone(), declaration);
+
+                if let AstItemKind::Module(items) = &item.kind {
+                    self.declare_items(&path, items);
+                }
+            }
+        }
+    }
+
+    /// Records a declaration, reporting an error if the path was already declared
+    fn declare(&mut self, path: ItemPath, declaration: Declaration) {
+        if let Some(first) = self.declarations.insert(path.clone(), declaration) {
+            self.errors.push_err(Locatable::new(
+                SemanticError::Redefinition {
+                    name: path.to_string(self.context().strings()),
+                    first: first.loc,
+                    second: declaration.loc,
+                }
+                .into(),
+                declaration.loc,
+            ));
+        }
+    }
+
+    /// Declares an alias, returning the type that will be filled in with the aliased type
+    fn declare_alias(&mut self, path: ItemPath, item: &AstItem<'_>, loc: Location) -> TypeId {
+        let alias = self.context().hir_type(Type {
+            kind: TypeKind::Unknown,
+            loc,
+        });
+
+        self.declare(
+            path,
+            Declaration {
+                vis: item.vis.unwrap_or_default(),
+                loc,
+                generics: 0,
+                kind: DeclarationKind::Alias(alias),
+            },
+        );
+
+        alias
+    }
+
+    /// Gets the name given to an alias, `alias Name = Type`
+    fn alias_name(alias: &AstType<'_>) -> Option<StrT> {
+        match alias {
+            AstType::ItemPath(path) => match path[..] {
+                [name] => Some(name),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Reports every alias that refers to itself, either directly or through the types
+    /// it's made of, since they would be infinitely large
+    fn check_alias_cycles(&mut self) {
+        let mut aliases: Vec<(ItemPath, Location, TypeId)> = self
+            .declarations
+            .iter()
+            .filter_map(|(path, declaration)| match declaration.kind {
+                DeclarationKind::Alias(alias) => Some((path.clone(), declaration.loc, alias)),
+                _ => None,
+            })
+            .collect();
+        aliases.sort_by_key(|&(_, loc, _)| loc);
+
+        for (path, loc, alias) in aliases {
+            let inner = self
+                .context()
+                .get_hir_type(alias)
+                .unwrap()
+                .kind
+                .inner_types();
+            let mut seen = HashSet::with_hasher(Hasher::default());
+
+            if self.refers_to(inner, alias, &mut seen) {
+                self.errors.push_err(Locatable::new(
+                    SemanticError::RecursiveAlias(path.to_string(self.context().strings())).into(),
+                    loc,
+                ));
+
+                // Break the cycle so that the other aliases within it aren't reported too
+                let unknown = self.context().hir_type(Type {
+                    kind: TypeKind::Unknown,
+                    loc,
+                });
+                self.context().overwrite_hir_type(alias, unknown);
+            }
+        }
+    }
+
+    /// Returns `true` if any of the types are or contain the target type
+    fn refers_to(&self, types: Vec<TypeId>, target: TypeId, seen: &mut HashSet<TypeId>) -> bool {
+        types.into_iter().any(|ty| {
+            ty == target
+                || (seen.insert(ty)
+                    && self.refers_to(
+                        self.context().get_hir_type(ty).unwrap().kind.inner_types(),
+                        target,
+                        seen,
+                    ))
+        })
+    }
+
+    /// Resolves a path used within the current module by searching outwards through each
+    /// of the modules enclosing it, reporting an error if the item isn't visible from here
+    fn resolve_path(&mut self, path: &[StrT], loc: Location) -> ItemPath {
+        if let Some((resolved, _)) = self.lookup(path) {
+            self.check_visibility(&resolved, loc);
+            return resolved;
+        }
+
+        // Unknown items are reported by the typechecker
+        ItemPath::new(path.to_vec())
+    }
+
+    /// Finds the declaration a path used within the current module refers to without
+    /// checking its visibility
+    fn lookup(&self, path: &[StrT]) -> Option<(ItemPath, Declaration)> {
+        (0..=self.module.len()).rev().find_map(|depth| {
+            let candidate = ItemPath::new(self.module[..depth].to_vec()).join(path.to_vec());
+
+            self.declarations
+                .get(&candidate)
+                .map(|&declaration| (candidate, declaration))
+        })
+    }
+
+    /// Checks that every module along the path, along with the item itself, is visible
+    /// from within the current module
+    fn check_visibility(&mut self, path: &ItemPath, loc: Location) {
+        for len in 1..=path.len() {
+            let prefix = ItemPath::new(path[..len].to_vec());
+
+            if let Some(declaration) = self.declarations.get(&prefix) {
+                let parent = &path[..len - 1];
+
+                if declaration.vis == Vis::FileLocal && !self.module.starts_with(parent) {
+                    self.errors.push_err(Locatable::new(
+                        SemanticError::PrivateItem(prefix.to_string(self.context().strings()))
+                            .into(),
+                        loc,
+                    ));
+
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if the given name refers to a module or enum visible from the current
+    /// module, meaning that member accesses on it are paths rather than method calls
+    fn is_namespace(&self, name: StrT) -> bool {
+        self.lookup(&[name]).map_or(false, |(_, declaration)| {
+            matches!(
+                declaration.kind,
+                DeclarationKind::Module | DeclarationKind::Enum(_)
+            )
+        })
+    }
+
+    /// Returns the id of the enum the path refers to, if it refers to one
+    fn enum_id(&self, path: &[StrT]) -> Option<ItemId> {
+        match self.lookup(path) {
+            Some((
+                _,
+                Declaration {
+                    kind: DeclarationKind::Enum(id),
+                    ..
+                },
+            )) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Returns the type an alias will be filled in with, if the path refers to one
+    fn alias_type(&self, path: &[StrT]) -> Option<TypeId> {
+        match self.lookup(path) {
+            Some((
+                _,
+                Declaration {
+                    kind: DeclarationKind::Alias(alias),
+                    ..
+                },
+            )) => Some(alias),
+            _ => None,
+        }
+    }
+
+    /// Returns the id of the trait the path refers to, if it refers to one
+    fn trait_id(&self, path: &[StrT]) -> Option<ItemId> {
+        match self.lookup(path) {
+            Some((
+                _,
+                Declaration {
+                    kind: DeclarationKind::Trait(id),
+                    ..
+                },
+            )) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the path is `Self`, the type being implemented by a trait or
+    /// `extend` block
+    fn is_self_type(&self, path: &[StrT]) -> bool {
+        matches!(path, [name] if self.context().strings().resolve(*name).as_ref() == "Self")
+    }
+
+    /// Returns the number of generic parameters taken by the item the path refers to
+    fn generic_count(&self, path: &[StrT]) -> usize {
+        self.lookup(path)
+            .map_or(0, |(_, declaration)| declaration.generics)
+    }
+
+    /// Creates a new unknown type for each generic parameter of an item, which are then
+    /// inferred by the type checker
+    fn infer_generics(&self, count: usize, loc: Location) -> Vec<TypeId> {
+        (0..count)
+            .map(|_| {
+                self.context().hir_type(Type {
+                    kind: TypeKind::Unknown,
+                    loc,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the generic parameter the path refers to if it refers to one that's in scope
+    fn generic_type(&mut self, path: &[StrT]) -> Option<TypeKind> {
+        let generic = match path {
+            [name] => self.generics.iter().find(|generic| generic.name == *name)?,
+            _ => return None,
+        };
+        self.used_generics.insert(generic.id);
+
+        Some(TypeKind::Generic {
+            id: generic.id,
+            name: generic.name,
+        })
+    }
+
+    /// Lowers the generic parameters of an item, `[T, U: Trait & Other]`
+    fn lower_generics(
+        &mut self,
+        generics: Option<Locatable<&[Locatable<&AstType<'_>>]>>,
+    ) -> Vec<Generic> {
+        let generics = match generics {
+            Some(generics) => generics,
+            None => return Vec::new(),
+        };
+
+        let mut lowered: Vec<Generic> = Vec::with_capacity(generics.len());
+        for generic in generics.iter() {
+            let (path, bounds) = match **generic {
+                AstType::ItemPath(path) => (path, &[][..]),
+                AstType::Bounded { path, bounds } => (path, &bounds[..]),
+                AstType::Const(..) => {
+                    self.errors.push_err(Locatable::new(
+                        SemanticError::UnsupportedGenerics("const parameters".to_owned()).into(),
+                        generic.location(),
+                    ));
+
+                    continue;
+                }
+                _ => {
+                    self.errors.push_err(Locatable::new(
+                        SemanticError::InvalidGeneric.into(),
+                        generic.location(),
+                    ));
+
+                    continue;
+                }
+            };
+
+            let name = match path[..] {
+                [name] => name,
+                _ => {
+                    self.errors.push_err(Locatable::new(
+                        SemanticError::InvalidGeneric.into(),
+                        generic.location(),
+                    ));
+
+                    continue;
+                }
+            };
+
+            if let Some(first) = lowered.iter().find(|generic| generic.name == name) {
+                self.errors.push_err(Locatable::new(
+                    SemanticError::Redefinition {
+                        name: self.context().strings().resolve(name).as_ref().to_owned(),
+                        first: first.loc,
+                        second: generic.location(),
+                    }
+                    .into(),
+                    generic.location(),
+                ));
+
+                continue;
+            }
+
+            let mut resolved = Vec::with_capacity(bounds.len());
+            for bound in bounds {
+                match **bound {
+                    AstType::ItemPath(trait_path) if self.trait_id(trait_path).is_some() => {
+                        self.resolve_path(trait_path, bound.location());
+                        resolved.extend(self.trait_id(trait_path));
+                    }
+
+                    _ => self.errors.push_err(Locatable::new(
+                        SemanticError::UnknownTrait(bound.to_string(self.context().strings()))
+                            .into(),
+                        bound.location(),
+                    )),
+                }
+            }
+
+            lowered.push(Generic {
+                id: self.context().next_item_id(),
+                name,
+                bounds: resolved,
+                loc: generic.location(),
+            });
+        }
+
+        lowered
+    }
+
+    /// Warns about every generic parameter that was never referred to
+    fn check_unused_generics(&mut self, generics: &[Generic]) {
+        for generic in generics {
+            if !self.used_generics.contains(&generic.id) {
+                let name = self.context().strings().resolve(generic.name);
+
+                self.errors.push_warning(Locatable::new(
+                    Warning::UnusedGeneric(name.as_ref().to_owned()),
+                    generic.loc,
+                ));
+            }
+        }
+    }
+
+    /// Resolves the enum of a variant's path, `Enum.Variant`, leaving other paths untouched.
+    /// Patterns don't carry locations, so visibility is only checked where variants are created
+    fn resolve_variant_path(&self, path: &ItemPath) -> ItemPath {
+        if let Some((variant, enum_path)) = path.split_last() {
+            if let Some((resolved, _)) = self
+                .lookup(enum_path)
+                .filter(|(_, declaration)| matches!(declaration.kind, DeclarationKind::Enum(_)))
+            {
+                return resolved.join(*variant);
+            }
+        }
+
+        path.clone()
+    }
+
+    /// Creates an enum's variant from the (positional) arguments given to it
+    fn variant_ctor(
+        &mut self,
+        expr: &AstExpr<'_>,
+        enum_id: ItemId,
+        variant: StrT,
+        args: &[AstCallArg<'_>],
+    ) -> &'ctx Expr<'ctx> {
+        let args = args
+            .iter()
+            .filter_map(|arg| match *arg {
+                AstCallArg::Positional(value) => Some(self.visit_expr(value)),
+                AstCallArg::Named { value, .. } | AstCallArg::Spread(value) => {
+                    self.errors.push_err(Locatable::new(
+                        SemanticError::NonPositionalVariantArg.into(),
+                        value.location(),
+                    ));
+
+                    None
+                }
+            })
+            .collect();
+
+        // Every variant gets its own type since the type checker fills in the enum's generics
+        let ty = self.context().hir_type(Type {
+            kind: TypeKind::Unknown,
+            loc: expr.location(),
+        });
+
+        self.context().hir_expr(Expr {
+            kind: ExprKind::Variant(VariantCtor {
+                enum_id,
+                variant,
+                args,
+                ty,
+            }),
+            loc: expr.location(),
+        })
+    }
+
+    /// The index of a tuple field, the `0` of `tuple.0`
+    fn tuple_index(field: &AstExpr<'_>) -> Option<u64> {
+        match &field.kind {
+            AstExprKind::Literal(literal) => match literal.val {
+                AstLiteralVal::Integer(index) => Some(index.bits as u64),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Lowers the chain of method calls made on `receiver`, `receiver.a().b()`, which are
+    /// parsed as right-nested member calls
+    fn method_chain<'a>(
+        &mut self,
+        mut receiver: &'ctx Expr<'ctx>,
+        mut func: &'a AstExpr<'a>,
+    ) -> &'ctx Expr<'ctx> {
+        loop {
+            let (call, rest) = match &func.kind {
+                AstExprKind::MemberFuncCall { member, func } => (&**member, Some(&**func)),
+                _ => (func, None),
+            };
+
+            let loc = receiver.location().merge(call.location());
+            receiver = match &call.kind {
+                AstExprKind::FuncCall { caller, args } => match caller.kind {
+                    AstExprKind::Variable(method) => self.method_call(loc, receiver, method, args),
+                    _ => self.invalid_expr(SemanticError::InvalidCaller, call.location()),
+                },
+
+                // Tuple fields in the middle of a chain, `tuple.0.method()`
+                _ => match Self::tuple_index(call) {
+                    Some(index) => self.context().hir_expr(Expr {
+                        kind: ExprKind::TupleField {
+                            tuple: receiver,
+                            index,
+                        },
+                        loc,
+                    }),
+                    None => {
+                        self.invalid_expr(SemanticError::UnsupportedFieldAccess, call.location())
+                    }
+                },
+            };
+
+            match rest {
+                Some(rest) => func = rest,
+                None => return receiver,
+            }
+        }
+    }
+
+    fn method_call(
+        &mut self,
+        loc: Location,
+        receiver: &'ctx Expr<'ctx>,
+        method: Locatable<StrT>,
+        args: &[AstCallArg<'_>],
+    ) -> &'ctx Expr<'ctx> {
+        // Every call gets its own type since the type checker fills it in with the
+        // receiver's type
+        let receiver_ty = self.context().hir_type(Type {
+            kind: TypeKind::Unknown,
+            loc: receiver.location(),
+        });
+        let args = self.visit_call_args(args);
+
+        self.context().hir_expr(Expr {
+            kind: ExprKind::MethodCall(MethodCall {
+                receiver,
+                receiver_ty,
+                method,
+                args,
+                trait_: None,
+            }),
+            loc,
+        })
+    }
+
+    fn func_call(
+        &mut self,
+        func: ItemPath,
+        args: &[AstCallArg<'_>],
+        loc: Location,
+    ) -> &'ctx Expr<'ctx> {
+        let generics = self.infer_generics(self.generic_count(&func), loc);
+        let args = self.visit_call_args(args);
+
+        self.context().hir_expr(Expr {
+            kind: ExprKind::FnCall(FuncCall {
+                func,
+                generics,
+                args,
+            }),
+            loc,
+        })
+    }
+
+    /// Reports `error` and stands in for the expression it was found in, so that the rest
+    /// of the file can still be lowered
+    fn invalid_expr(&mut self, error: SemanticError, loc: Location) -> &'ctx Expr<'ctx> {
+        self.errors.push_err(Locatable::new(error.into(), loc));
+
+        self.context().hir_expr(Expr {
+            kind: ExprKind::Scope(Block::new(Vec::new(), loc)),
+            loc,
+        })
+    }
+
     fn next_var(&mut self) -> Var {
         let var = Var::Auto(self.variable_counter);
         self.variable_counter += 1;
@@ -106,6 +770,117 @@ impl<'ctx> Ladder<'ctx> {
         var
     }
 
+    fn visit_func_args(
+        &mut self,
+        args: Locatable<&[AstFuncArg<'_>]>,
+    ) -> Locatable<Vec<FuncArg<'ctx>>> {
+        args.map(|args| {
+            args.iter()
+                .map(
+                    |&AstFuncArg {
+                         name,
+                         ty,
+                         default,
+                         variadic,
+                         loc,
+                     }| FuncArg {
+                        name: Var::User(name),
+                        kind: self.visit_type(ty),
+                        default: default.map(|default| self.visit_expr(default)),
+                        variadic,
+                        loc,
+                    },
+                )
+                .collect()
+        })
+    }
+
+    fn visit_call_args(&mut self, args: &[AstCallArg<'_>]) -> Vec<CallArg<'ctx>> {
+        args.iter()
+            .map(|arg| match *arg {
+                AstCallArg::Positional(value) => CallArg::Positional(self.visit_expr(value)),
+                AstCallArg::Named { name, value } => CallArg::Named {
+                    name,
+                    value: self.visit_expr(value),
+                },
+                AstCallArg::Spread(value) => CallArg::Spread(self.visit_expr(value)),
+            })
+            .collect()
+    }
+
+    /// Calls `Scoped.exit` on the value held by a `with` block, which is dispatched to the
+    /// implementation of `Scoped` for the value's type. `loc` is the location of the held
+    /// value so that it's what any errors point to
+    fn exit_hook(&self, value: Var, scoped: ItemId, loc: Location) -> &'ctx Stmt<'ctx> {
+        // Every hook gets its own types since the type checker fills them in separately
+        let value_ty = self.context().hir_type(Type {
+            kind: TypeKind::Unknown,
+            loc,
+        });
+        let receiver = self.context().hir_expr(Expr {
+            kind: ExprKind::Variable(value, value_ty),
+            loc,
+        });
+        let receiver_ty = self.context().hir_type(Type {
+            kind: TypeKind::Unknown,
+            loc,
+        });
+
+        self.context()
+            .hir_stmt(Stmt::Expr(self.context().hir_expr(Expr {
+                kind: ExprKind::MethodCall(MethodCall {
+                    receiver,
+                    receiver_ty,
+                    method: Locatable::new(self.context().strings().intern("exit"), loc),
+                    args: Vec::new(),
+                    trait_: Some(scoped),
+                }),
+                loc,
+            })))
+    }
+
+    /// Generates the exit hooks for every `with` block being jumped out of, innermost first.
+    /// `break` and `continue` only leave the blocks within the current loop, while `return`
+    /// leaves all of them
+    fn exit_hooks(&self, leaves_loops: bool) -> Vec<&'ctx Stmt<'ctx>> {
+        self.exit_scopes
+            .iter()
+            .rev()
+            .take_while(|scope| leaves_loops || !matches!(scope, ExitScope::Loop))
+            .filter_map(|scope| match *scope {
+                ExitScope::With { value, scoped, loc } => Some(self.exit_hook(value, scoped, loc)),
+                ExitScope::Loop => None,
+            })
+            .collect()
+    }
+
+    /// Reports a `break` or `continue` that isn't within a loop
+    fn check_within_loop(&mut self, keyword: &str, loc: Location) {
+        if !self
+            .exit_scopes
+            .iter()
+            .any(|scope| matches!(scope, ExitScope::Loop))
+        {
+            self.errors.push_err(Locatable::new(
+                SemanticError::OutsideLoop(keyword.to_owned()).into(),
+                loc,
+            ));
+        }
+    }
+
+    /// Lowers the body of a loop, recording it as the target of any `break` or `continue`
+    /// within it
+    fn loop_body(&mut self, body: &AstBlock<'_>) -> Vec<&'ctx Stmt<'ctx>> {
+        self.exit_scopes.push(ExitScope::Loop);
+        let body = body
+            .iter()
+            .filter_map(|stmt| self.visit_stmt(stmt))
+            .collect();
+        self.exit_scopes.pop();
+
+        body
+    }
+
     // FIXME: I hate this
     fn context(&self) -> &'ctx Context<'ctx> {
         unsafe {
@@ -115,205 +890,570 @@ impl<'ctx> Ladder<'ctx> {
         }
     }
 
-    fn block_statement(
+    /// Lowers a loop that runs for as long as `cond` is true, appended onto `scope`.
+    /// `prelude` is run at the start of each iteration once the condition has passed
+    #[allow(clippy::too_many_arguments)]
+    fn conditional_loop(
         &mut self,
-        then_loc: Location,
-        else_loc: Location,
-        loop_broken: Var,
-        then_block: Block<&'ctx Stmt<'ctx>>,
-        else_block: Block<&'ctx Stmt<'ctx>>,
-    ) -> &'ctx Stmt<'ctx> {
-        crunch_shared::warn!("Returning values from loops is currently ignored");
-        let location = Location::merge(then_loc, else_loc);
+        loc: Location,
+        mut scope: Block<&'ctx Stmt<'ctx>>,
+        cond: &'ctx Expr<'ctx>,
+        prelude: Vec<&'ctx Stmt<'ctx>>,
+        ast_body: &AstBlock<'_>,
+        then: &Option<AstBlock<'_>>,
+        else_: &Option<AstBlock<'_>>,
+    ) -> &'ctx Expr<'ctx> {
+        let mut body: Block<&'ctx Stmt<'ctx>> =
+            Block::with_capacity(ast_body.location(), ast_body.len() + prelude.len());
+        body.extend(prelude);
+        body.extend(self.loop_body(ast_body));
 
-        self.context()
-            .hir_stmt(Stmt::Expr(self.context().hir_expr(Expr {
-                kind: ExprKind::Match(Match {
-                    cond: self.context().hir_expr(Expr {
-                        kind: ExprKind::Comparison(Sided {
-                            lhs: self.context().hir_expr(Expr {
-                                kind: ExprKind::Variable(
-                                    loop_broken,
-                                    self.db.hir_type(Type {
-                                        kind: TypeKind::Bool,
-                                        loc: location,
-                                    }),
-                                ),
-                                loc: location,
-                            }),
-                            op: CompOp::Equal,
-                            rhs: self.context().hir_expr(Expr {
-                                kind: ExprKind::Literal(Literal {
-                                    val: LiteralVal::Bool(true),
-                                    ty: self.db.hir_type(Type {
-                                        kind: TypeKind::Bool,
-                                        loc: location,
-                                    }),
-                                    loc: location,
-                                }),
-                                loc: location,
-                            }),
-                        }),
-                        loc: location,
+        let loop_ = Loop {
+            cond: Some(cond),
+            body,
+            then: then.as_ref().map(|then| self.loop_clause(then)),
+            else_: else_.as_ref().map(|else_| self.loop_clause(else_)),
+            ty: self.context().hir_type(Type {
+                kind: TypeKind::Unknown,
+                loc,
+            }),
+        };
+
+        scope.push(
+            self.context()
+                .hir_stmt(Stmt::Expr(self.context().hir_expr(Expr {
+                    kind: ExprKind::Loop(loop_),
+                    loc,
+                }))),
+        );
+
+        self.context().hir_expr(Expr {
+            kind: ExprKind::Scope(scope),
+            loc,
+        })
+    }
+
+    /// Lowers `for var in start..end` into a loop over a hidden counter, which is bound to
+    /// `var` and incremented at the start of each iteration so that `continue` can't skip
+    /// the increment. Ranges without an end loop forever
+    #[allow(clippy::too_many_arguments)]
+    fn range_loop(
+        &mut self,
+        loc: Location,
+        var: Var,
+        &AstRange { start, end, kind }: &AstRange<'_>,
+        range_loc: Location,
+        body: &AstBlock<'_>,
+        then: &Option<AstBlock<'_>>,
+        else_: &Option<AstBlock<'_>>,
+    ) -> &'ctx Expr<'ctx> {
+        let mut scope = Block::with_capacity(loc, 3);
+        let unknown = self.db.hir_type(Type {
+            kind: TypeKind::Unknown,
+            loc: range_loc,
+        });
+
+        let start = if let Some(start) = start {
+            self.visit_expr(start)
+        } else {
+            self.errors.push_err(Locatable::new(
+                SemanticError::UnboundedRangeStart.into(),
+                range_loc,
+            ));
+
+            return self.context().hir_expr(Expr {
+                kind: ExprKind::Scope(scope),
+                loc,
+            });
+        };
+
+        let counter = self.next_var();
+        scope.push(self.context().hir_stmt(Stmt::VarDecl(VarDecl {
+            name: counter,
+            value: start,
+            mutable: true,
+            ty: unknown,
+            loc: start.location(),
+        })));
+        let counter_value = self.context().hir_expr(Expr {
+            kind: ExprKind::Variable(counter, unknown),
+            loc: range_loc,
+        });
+
+        let cond = if let Some(end) = end {
+            let end = self.visit_expr(end);
+            let bound = self.next_var();
+            scope.push(self.context().hir_stmt(Stmt::VarDecl(VarDecl {
+                name: bound,
+                value: end,
+                mutable: false,
+                ty: unknown,
+                loc: end.location(),
+            })));
+
+            // TODO: Inclusive ranges that end at the maximum value of their type overflow
+            //       the counter and never stop
+            let op = match kind {
+                RangeKind::Exclusive => CompOp::Less,
+                RangeKind::Inclusive => CompOp::LessEqual,
+            };
+
+            self.context().hir_expr(Expr {
+                kind: ExprKind::Comparison(Sided {
+                    lhs: counter_value,
+                    op,
+                    rhs: self.context().hir_expr(Expr {
+                        kind: ExprKind::Variable(bound, unknown),
+                        loc: end.location(),
                     }),
-                    arms: vec![
-                        // If the loop was broken, execute the `else` block
-                        MatchArm {
-                            bind: Binding {
-                                reference: false,
-                                mutable: false,
-                                pattern: Pattern::Literal(Literal {
-                                    val: LiteralVal::Bool(true),
-                                    ty: self.db.hir_type(Type {
-                                        kind: TypeKind::Bool,
-                                        loc: location,
-                                    }),
-                                    loc: location,
-                                }),
-                                ty: None,
-                            },
-                            guard: None,
-                            body: else_block,
-                            // TODO: Allow returning values from loops
-                            ty: self.db.hir_type(Type {
-                                kind: TypeKind::Unknown,
-                                loc: location,
-                            }),
-                        },
-                        // If the loop was unbroken, execute the `then` block
-                        MatchArm {
-                            bind: Binding {
-                                reference: false,
-                                mutable: false,
-                                pattern: Pattern::Literal(Literal {
-                                    val: LiteralVal::Bool(false),
-                                    ty: self.db.hir_type(Type {
-                                        kind: TypeKind::Bool,
-                                        loc: location,
-                                    }),
-                                    loc: location,
-                                }),
-                                ty: None,
-                            },
-                            guard: None,
-                            body: then_block,
-                            // TODO: Allow returning values from loops
-                            ty: self.db.hir_type(Type {
-                                kind: TypeKind::Unknown,
-                                loc: location,
-                            }),
-                        },
-                    ],
-                    // TODO: Allow returning values from loops
+                }),
+                loc: range_loc,
+            })
+        } else {
+            self.context().hir_expr(Expr {
+                kind: ExprKind::Literal(Literal {
+                    val: LiteralVal::Bool(true),
                     ty: self.db.hir_type(Type {
-                        kind: TypeKind::Unknown,
-                        loc: location,
+                        kind: TypeKind::Bool,
+                        loc: range_loc,
                     }),
+                    loc: range_loc,
                 }),
-                loc: location,
-            })))
+                loc: range_loc,
+            })
+        };
+
+        let one = self.context().hir_expr(Expr {
+            kind: ExprKind::Literal(Literal {
+                val: LiteralVal::Integer(Integer {
+                    sign: Sign::Positive,
+                    bits: 1,
+                }),
+                ty: self.db.hir_type(Type {
+                    kind: TypeKind::Integer {
+                        signed: None,
+                        width: None,
+                    },
+                    loc: range_loc,
+                }),
+                loc: range_loc,
+            }),
+            loc: range_loc,
+        });
+        let prelude = vec![
+            self.context().hir_stmt(Stmt::VarDecl(VarDecl {
+                name: var,
+                value: counter_value,
+                mutable: false,
+                ty: unknown,
+                loc: range_loc,
+            })),
+            self.context()
+                .hir_stmt(Stmt::Expr(self.context().hir_expr(Expr {
+                    kind: ExprKind::Assign {
+                        place: Place::new(PlaceKind::Variable(counter), range_loc),
+                        op: Some(BinaryOp::Add),
+                        value: one,
+                    },
+                    loc: range_loc,
+                }))),
+        ];
+
+        self.conditional_loop(loc, scope, cond, prelude, body, then, else_)
     }
 
-    fn visit_then_and_else(
+    /// Lowers `for var in elements` over an array or slice into a loop over a hidden index,
+    /// which binds the indexed element to `var` and is incremented at the start of each
+    /// iteration, the same as `range_loop`
+    fn element_loop(
         &mut self,
-        scope: &mut Block<&'ctx Stmt<'ctx>>,
-        loop_broken: Var,
+        loc: Location,
+        var: Var,
+        elements: &AstExpr<'_>,
+        body: &AstBlock<'_>,
         then: &Option<AstBlock<'_>>,
         else_: &Option<AstBlock<'_>>,
-    ) {
-        // TODO: Handle these blocks for *any* breaks, not just condition ones
-        match (then, else_) {
-            (Some(then), Some(else_)) => {
-                let then_block = Block::from_iter(
-                    then.location(),
-                    then.iter().filter_map(|s| self.visit_stmt(s)),
-                );
-                let else_block = Block::from_iter(
-                    else_.location(),
-                    else_.iter().filter_map(|s| self.visit_stmt(s)),
-                );
-
-                scope.push(self.block_statement(
-                    then.location(),
-                    else_.location(),
-                    loop_broken,
-                    then_block,
-                    else_block,
-                ));
-            }
+    ) -> &'ctx Expr<'ctx> {
+        let mut scope = Block::with_capacity(loc, 4);
+        let elements_loc = elements.location();
+        let unknown = || {
+            self.context().hir_type(Type {
+                kind: TypeKind::Unknown,
+                loc: elements_loc,
+            })
+        };
+        let (elements_ty, index_ty, length_ty) = (unknown(), unknown(), unknown());
+
+        // The iterated value is only evaluated once
+        let elements = self.visit_expr(elements);
+        let elements_var = self.next_var();
+        scope.push(self.context().hir_stmt(Stmt::VarDecl(VarDecl {
+            name: elements_var,
+            value: elements,
+            mutable: false,
+            ty: elements_ty,
+            loc: elements_loc,
+        })));
+
+        let index = self.next_var();
+        scope.push(self.context().hir_stmt(Stmt::VarDecl(VarDecl {
+            name: index,
+            value: self.context().hir_expr(Expr {
+                kind: ExprKind::Literal(Literal {
+                    val: LiteralVal::Integer(Integer {
+                        sign: Sign::Positive,
+                        bits: 0,
+                    }),
+                    ty: self.db.hir_type(Type {
+                        kind: TypeKind::Integer {
+                            signed: None,
+                            width: None,
+                        },
+                        loc: elements_loc,
+                    }),
+                    loc: elements_loc,
+                }),
+                loc: elements_loc,
+            }),
+            mutable: true,
+            ty: index_ty,
+            loc: elements_loc,
+        })));
+        let index_value = self.context().hir_expr(Expr {
+            kind: ExprKind::Variable(index, index_ty),
+            loc: elements_loc,
+        });
+
+        let length = self.next_var();
+        scope.push(self.context().hir_stmt(Stmt::VarDecl(VarDecl {
+            name: length,
+            value: self.context().hir_expr(Expr {
+                kind: ExprKind::Length(elements_var),
+                loc: elements_loc,
+            }),
+            mutable: false,
+            ty: length_ty,
+            loc: elements_loc,
+        })));
+
+        let cond = self.context().hir_expr(Expr {
+            kind: ExprKind::Comparison(Sided {
+                lhs: index_value,
+                op: CompOp::Less,
+                rhs: self.context().hir_expr(Expr {
+                    kind: ExprKind::Variable(length, length_ty),
+                    loc: elements_loc,
+                }),
+            }),
+            loc: elements_loc,
+        });
+
+        let element_ty = self.context().hir_type(Type {
+            kind: TypeKind::Unknown,
+            loc: elements_loc,
+        });
+        let one = self.context().hir_expr(Expr {
+            kind: ExprKind::Literal(Literal {
+                val: LiteralVal::Integer(Integer {
+                    sign: Sign::Positive,
+                    bits: 1,
+                }),
+                ty: self.db.hir_type(Type {
+                    kind: TypeKind::Integer {
+                        signed: None,
+                        width: None,
+                    },
+                    loc: elements_loc,
+                }),
+                loc: elements_loc,
+            }),
+            loc: elements_loc,
+        });
+        let prelude = vec![
+            self.context().hir_stmt(Stmt::VarDecl(VarDecl {
+                name: var,
+                value: self.context().hir_expr(Expr {
+                    kind: ExprKind::Index {
+                        var: elements_var,
+                        index: index_value,
+                    },
+                    loc: elements_loc,
+                }),
+                mutable: false,
+                ty: element_ty,
+                loc: elements_loc,
+            })),
+            self.context()
+                .hir_stmt(Stmt::Expr(self.context().hir_expr(Expr {
+                    kind: ExprKind::Assign {
+                        place: Place::new(PlaceKind::Variable(index), elements_loc),
+                        op: Some(BinaryOp::Add),
+                        value: one,
+                    },
+                    loc: elements_loc,
+                }))),
+        ];
+
+        self.conditional_loop(loc, scope, cond, prelude, body, then, else_)
+    }
+
+    /// Lowers the `then` or `else` clause of a loop, which are outside of the loop itself
+    fn loop_clause(&mut self, clause: &AstBlock<'_>) -> Block<&'ctx Stmt<'ctx>> {
+        Block::from_iter(
+            clause.location(),
+            clause.iter().filter_map(|stmt| self.visit_stmt(stmt)),
+        )
+    }
+
+    /// Gets the variable holding `value`, evaluating anything that isn't already a variable
+    /// into a new one declared within `scope`
+    fn bind_value(&mut self, value: &AstExpr<'_>, scope: &mut Vec<&'ctx Stmt<'ctx>>) -> Var {
+        if let AstExprKind::Variable(var) = value.kind {
+            return Var::User(*var);
+        }
+
+        let loc = value.location();
+        let value = self.visit_expr(value);
+        let name = self.next_var();
+        scope.push(self.context().hir_stmt(Stmt::VarDecl(VarDecl {
+            name,
+            value,
+            mutable: false,
+            ty: self.context().hir_type(Type::new(TypeKind::Unknown, loc)),
+            loc,
+        })));
+
+        name
+    }
 
-            (Some(then), None) => {
-                let then_block = Block::from_iter(
-                    then.location(),
-                    then.iter().filter_map(|s| self.visit_stmt(s)),
-                );
-
-                scope.push(self.block_statement(
-                    then.location(),
-                    then.location(),
-                    loop_broken,
-                    then_block,
-                    Block::empty(then.location()),
-                ));
+    /// Lowers the left hand side of an assignment into the place it writes to, reporting
+    /// anything that can't be assigned to
+    fn place(&mut self, expr: &AstExpr<'_>) -> Option<Place<'ctx>> {
+        // Tuple fields, `tuple.0`
+        if let AstExprKind::MemberFuncCall { member, func } = &expr.kind {
+            if let AstExprKind::Literal(literal) = &func.kind {
+                if let AstLiteralVal::Integer(index) = literal.val {
+                    let kind = PlaceKind::Field {
+                        base: Ref::new(self.place(member)?),
+                        index: index.bits as u64,
+                    };
+
+                    return Some(Place::new(kind, expr.location()));
+                }
             }
+        }
+
+        let kind = match &expr.kind {
+            AstExprKind::Variable(var) => PlaceKind::Variable(Var::User(**var)),
+            AstExprKind::Paren(inner) => return self.place(inner),
+            AstExprKind::Index { var, index } => PlaceKind::Index {
+                base: Ref::new(self.place(var)?),
+                index: self.visit_expr(index),
+            },
+            AstExprKind::Deref(pointer) => PlaceKind::Deref(self.visit_expr(pointer)),
 
-            (None, Some(else_)) => {
-                let else_block = Block::from_iter(
-                    else_.location(),
-                    else_.iter().filter_map(|s| self.visit_stmt(s)),
-                );
-
-                scope.push(self.block_statement(
-                    else_.location(),
-                    else_.location(),
-                    loop_broken,
-                    Block::empty(else_.location()),
-                    else_block,
+            _ => {
+                self.errors.push_err(Locatable::new(
+                    SemanticError::InvalidAssignment.into(),
+                    expr.location(),
                 ));
+
+                return None;
             }
+        };
+
+        Some(Place::new(kind, expr.location()))
+    }
+
+    /// Desugars `lhs and rhs` into `match lhs true => rhs, false => false` and `lhs or rhs`
+    /// into `match lhs true => true, false => rhs` so that `rhs` is only evaluated when
+    /// it's needed
+    fn short_circuit(
+        &mut self,
+        expr: &AstExpr<'_>,
+        lhs: &AstExpr<'_>,
+        op: BinaryOp,
+        rhs: &AstExpr<'_>,
+    ) -> &'ctx Expr<'ctx> {
+        let loc = expr.location();
+        let cond = self.visit_expr(lhs);
+        let rhs = self.visit_expr(rhs);
+
+        let (on_true, on_false) = match op {
+            BinaryOp::And => (rhs, self.bool_literal(false, loc)),
+            _ => (self.bool_literal(true, loc), rhs),
+        };
+
+        let arms = vec![
+            self.bool_arm(true, on_true, loc),
+            self.bool_arm(false, on_false, loc),
+        ];
+
+        self.context().hir_expr(Expr {
+            kind: ExprKind::Match(Match {
+                cond,
+                arms,
+                ty: self.db.hir_type(Type {
+                    kind: TypeKind::Bool,
+                    loc,
+                }),
+            }),
+            loc,
+        })
+    }
+
+    fn bool_literal(&mut self, value: bool, loc: Location) -> &'ctx Expr<'ctx> {
+        self.context().hir_expr(Expr {
+            kind: ExprKind::Literal(Literal {
+                val: LiteralVal::Bool(value),
+                ty: self.db.hir_type(Type {
+                    kind: TypeKind::Bool,
+                    loc,
+                }),
+                loc,
+            }),
+            loc,
+        })
+    }
 
-            (None, None) => {}
+    /// A match arm that evaluates to `body` when the matched boolean is `value`
+    fn bool_arm(&mut self, value: bool, body: &'ctx Expr<'ctx>, loc: Location) -> MatchArm<'ctx> {
+        MatchArm {
+            bind: Binding {
+                reference: false,
+                mutable: false,
+                pattern: Pattern::Literal(Literal {
+                    val: LiteralVal::Bool(value),
+                    ty: self.db.hir_type(Type {
+                        kind: TypeKind::Bool,
+                        loc,
+                    }),
+                    loc,
+                }),
+                ty: None,
+            },
+            guard: None,
+            body: Block::new(vec![self.context().hir_stmt(Stmt::Expr(body))], loc),
+            ty: self.db.hir_type(Type {
+                kind: TypeKind::Bool,
+                loc,
+            }),
         }
     }
 }
 
-impl<'ctx> ItemVisitor<'_> for Ladder<'ctx> {
-    type Output = Option<&'ctx Item<'ctx>>;
-
-    fn visit_func(
+impl<'ctx> Ladder<'ctx> {
+    fn lower_func(
         &mut self,
         item: &AstItem<'_>,
-        _generics: Option<Locatable<&[Locatable<&'_ AstType<'_>>]>>,
+        generics: Option<Locatable<&[Locatable<&'_ AstType<'_>>]>>,
         args: Locatable<&[AstFuncArg<'_>]>,
         body: &AstBlock<'_>,
         ret: Locatable<&'_ AstType<'_>>,
         sig: Location,
-    ) -> Self::Output {
-        let name = ItemPath::from(vec![item.name.unwrap()]);
-        let args = args.map(|args| {
-            args.iter()
-                .map(|&AstFuncArg { name, ty, loc }| FuncArg {
-                    name: Var::User(name),
-                    kind: self.visit_type(ty),
-                    loc,
-                })
-                .collect()
-        });
+    ) -> Function<'ctx> {
+        let name = self.module.join(item.name.unwrap());
 
+        // Nested functions can't use the generics of the functions they're declared within
+        let generics = self.lower_generics(generics);
+        let outer_generics = mem::replace(&mut self.generics, generics);
+
+        // Nested functions can't jump out of the scopes they're declared within
+        let exit_scopes = core::mem::take(&mut self.exit_scopes);
+        let args = self.visit_func_args(args);
+        let ret = self.visit_type(ret);
         let body = Block::from_iter(
             body.location(),
             body.iter().filter_map(|stmt| self.visit_stmt(stmt)),
         );
+        self.exit_scopes = exit_scopes;
+
+        let generics = mem::replace(&mut self.generics, outer_generics);
+        self.check_unused_generics(&generics);
 
-        let func = Function {
+        Function {
             name,
             vis: item.vis.expect("Functions should have a visibility"),
+            generics,
             args,
             body,
-            ret: self.visit_type(ret),
+            ret,
             loc: item.location(),
             sig,
-        };
+        }
+    }
+
+    /// Lowers the methods of a trait or `extend` block, which are namespaced under `path`
+    /// and see `Self` as `self_type`
+    fn lower_methods(
+        &mut self,
+        path: ItemPath,
+        self_type: TypeKind,
+        methods: &[&AstItem<'_>],
+        is_trait: bool,
+    ) -> Vec<Function<'ctx>> {
+        let parent = mem::replace(&mut self.module, path);
+        let outer_self = self.self_type.replace(self_type);
+
+        let mut lowered = Vec::with_capacity(methods.len());
+        for method in methods {
+            if let AstItemKind::Func {
+                generics,
+                args,
+                body,
+                ret,
+                sig,
+            } = &method.kind
+            {
+                if let Some(generics) = generics {
+                    self.errors.push_err(Locatable::new(
+                        SemanticError::UnsupportedGenerics("methods".to_owned()).into(),
+                        generics.location(),
+                    ));
+                }
+
+                // Trait methods are only signatures, their bodies are `empty`
+                if is_trait && !body.is_empty() {
+                    self.errors.push_err(Locatable::new(
+                        SemanticError::DefaultTraitMethod(
+                            self.module
+                                .join(method.name.unwrap())
+                                .to_string(self.context().strings()),
+                        )
+                        .into(),
+                        body.location(),
+                    ));
+                }
+
+                lowered.push(self.lower_func(method, None, args.as_deref(), body, *ret, *sig));
+            } else {
+                self.errors.push_err(Locatable::new(
+                    SemanticError::NonMethodItem.into(),
+                    method.location(),
+                ));
+            }
+        }
+
+        self.self_type = outer_self;
+        self.module = parent;
+
+        lowered
+    }
+}
+
+impl<'ctx> ItemVisitor<'_> for Ladder<'ctx> {
+    type Output = Option<&'ctx Item<'ctx>>;
+
+    fn visit_func(
+        &mut self,
+        item: &AstItem<'_>,
+        generics: Option<Locatable<&[Locatable<&'_ AstType<'_>>]>>,
+        args: Locatable<&[AstFuncArg<'_>]>,
+        body: &AstBlock<'_>,
+        ret: Locatable<&'_ AstType<'_>>,
+        sig: Location,
+    ) -> Self::Output {
+        let func = self.lower_func(item, generics, args, body, ret, sig);
 
         Some(self.context().hir_item(Item::Function(func)))
     }
@@ -329,20 +1469,98 @@ impl<'ctx> ItemVisitor<'_> for Ladder<'ctx> {
 
     fn visit_enum(
         &mut self,
-        _item: &AstItem<'_>,
-        _generics: Option<Locatable<&[Locatable<&AstType<'_>>]>>,
-        _variants: &[AstVariant<'_>],
+        item: &AstItem<'_>,
+        generics: Option<Locatable<&[Locatable<&AstType<'_>>]>>,
+        variants: &[AstVariant<'_>],
     ) -> Self::Output {
-        todo!()
+        let name = self.module.join(item.name.unwrap());
+        // Enums declared within function bodies aren't visible to `declare_items`
+        let id = self
+            .enum_id(&name)
+            .unwrap_or_else(|| self.context().next_item_id());
+
+        let generics = self.lower_generics(generics);
+        let outer_generics = mem::replace(&mut self.generics, generics);
+
+        let mut lowered: Vec<Variant> = Vec::with_capacity(variants.len());
+        for variant in variants {
+            let (variant, elements) = match variant {
+                AstVariant::Unit { name, .. } => (*name, Vec::new()),
+                AstVariant::Tuple { name, elms, .. } => (
+                    *name,
+                    elms.iter()
+                        .map(|&element| self.visit_type(element))
+                        .collect(),
+                ),
+            };
+
+            if lowered.iter().any(|other| other.name == variant) {
+                self.errors.push_err(Locatable::new(
+                    SemanticError::Redefinition {
+                        name: name.join(variant).to_string(self.context().strings()),
+                        first: item.location(),
+                        second: item.location(),
+                    }
+                    .into(),
+                    item.location(),
+                ));
+
+                continue;
+            }
+
+            lowered.push(Variant {
+                name: variant,
+                elements,
+                loc: item.location(),
+            });
+        }
+
+        let generics = mem::replace(&mut self.generics, outer_generics);
+        self.check_unused_generics(&generics);
+
+        let enum_ = Enum {
+            id,
+            name,
+            vis: item.vis.expect("Enums should have a visibility"),
+            generics,
+            variants: lowered,
+            loc: item.location(),
+        };
+
+        Some(self.context().hir_item(Item::Enum(enum_)))
     }
 
     fn visit_trait(
         &mut self,
-        _item: &AstItem<'_>,
-        _generics: Option<Locatable<&[Locatable<&AstType<'_>>]>>,
-        _methods: &[&AstItem<'_>],
+        item: &AstItem<'_>,
+        generics: Option<Locatable<&[Locatable<&AstType<'_>>]>>,
+        methods: &[&AstItem<'_>],
     ) -> Self::Output {
-        todo!()
+        if let Some(generics) = generics {
+            self.errors.push_err(Locatable::new(
+                SemanticError::UnsupportedGenerics("traits".to_owned()).into(),
+                generics.location(),
+            ));
+        }
+
+        let name = self.module.join(item.name.unwrap());
+        // Traits declared within function bodies aren't visible to `declare_items`
+        let id = self
+            .trait_id(&name)
+            .unwrap_or_else(|| self.context().next_item_id());
+
+        // The type implementing the trait isn't known, so `Self` is left to be inferred
+        let methods = self.lower_methods(name.clone(), TypeKind::Unknown, methods, true);
+
+        let trait_ = Trait {
+            id,
+            name,
+            vis: item.vis.expect("Traits should have a visibility"),
+            methods,
+            loc: item.location(),
+        };
+
+        Some(self.context().hir_item(Item::Trait(trait_)))
     }
 
     fn visit_import(
@@ -357,21 +1575,93 @@ impl<'ctx> ItemVisitor<'_> for Ladder<'ctx> {
 
     fn visit_extend_block(
         &mut self,
-        _item: &AstItem<'_>,
-        _target: Locatable<&AstType<'_>>,
-        _extender: Option<Locatable<&AstType<'_>>>,
-        _items: &[&AstItem<'_>],
+        item: &AstItem<'_>,
+        target: Locatable<&AstType<'_>>,
+        extender: Option<Locatable<&AstType<'_>>>,
+        items: &[&AstItem<'_>],
     ) -> Self::Output {
-        todo!()
+        let kind = self.visit_type_kind(*target, target.location());
+        let target_id = self.db.hir_type(Type {
+            kind: kind.clone(),
+            loc: target.location(),
+        });
+
+        // Methods are namespaced by the type they extend and the trait they implement so
+        // that they never collide with each other, `Type.Trait.method`
+        let strings = self.context().strings();
+        let mut path = self.module.join(strings.intern(target.to_string(strings)));
+
+        let extender = match extender {
+            Some(extender) => match *extender {
+                AstType::ItemPath(trait_path) if self.trait_id(trait_path).is_some() => {
+                    path = path.join(self.resolve_path(trait_path, extender.location()));
+                    self.trait_id(trait_path)
+                }
+
+                _ => {
+                    self.errors.push_err(Locatable::new(
+                        SemanticError::UnknownTrait(extender.to_string(strings)).into(),
+                        extender.location(),
+                    ));
+
+                    return None;
+                }
+            },
+
+            None => None,
+        };
+
+        let methods = self.lower_methods(path, kind, items, false);
+
+        let extend = ExtendBlock {
+            target: target_id,
+            extender,
+            methods,
+            loc: item.location(),
+        };
+
+        Some(self.context().hir_item(Item::ExtendBlock(extend)))
     }
 
     fn visit_alias(
         &mut self,
-        _item: &AstItem<'_>,
-        _alias: Locatable<&AstType<'_>>,
-        _actual: Locatable<&AstType<'_>>,
+        item: &AstItem<'_>,
+        alias: Locatable<&AstType<'_>>,
+        actual: Locatable<&AstType<'_>>,
     ) -> Self::Output {
-        todo!()
+        let name = match Self::alias_name(*alias) {
+            Some(name) => name,
+            None => {
+                let error = match *alias {
+                    AstType::Bounded { .. } => {
+                        SemanticError::UnsupportedGenerics("aliases".to_owned())
+                    }
+                    _ => SemanticError::InvalidAlias,
+                };
+                self.errors
+                    .push_err(Locatable::new(error.into(), alias.location()));
+
+                return None;
+            }
+        };
+
+        let path = self.module.join(name);
+        let aliased = match self.declarations.get(&path) {
+            Some(&Declaration {
+                kind: DeclarationKind::Alias(aliased),
+                loc,
+                ..
+            }) if loc == alias.location() => aliased,
+
+            // Aliases declared within function bodies aren't visible to `declare_items`
+            _ => self.declare_alias(path, item, alias.location()),
+        };
+
+        // Aliases are replaced by the type they alias, so they don't produce an item
+        let actual = self.visit_type(actual);
+        self.context().overwrite_hir_type(aliased, actual);
+
+        None
     }
 
     fn visit_extern_block(&mut self, _item: &AstItem<'_>, _items: &[&AstItem<'_>]) -> Self::Output {
@@ -388,20 +1678,8 @@ impl<'ctx> ItemVisitor<'_> for Ladder<'ctx> {
         ret: Locatable<&AstType<'_>>,
         callconv: CallConv,
     ) -> Self::Output {
-        let name = ItemPath::from(vec![item.name.unwrap()]);
-        let args = args.map(|args| {
-            args.iter()
-                .map(|&AstFuncArg { name, ty, loc }| {
-                    let kind = self.visit_type(ty);
-
-                    FuncArg {
-                        name: Var::User(name),
-                        kind,
-                        loc,
-                    }
-                })
-                .collect()
-        });
+        let name = self.module.join(item.name.unwrap());
+        let args = self.visit_func_args(args);
 
         let func = ExternFunc {
             name,
@@ -416,6 +1694,21 @@ impl<'ctx> ItemVisitor<'_> for Ladder<'ctx> {
 
         Some(self.context().hir_item(Item::ExternFunc(func)))
     }
+
+    fn visit_module(&mut self, item: &AstItem<'_>, items: &[&AstItem<'_>]) -> Self::Output {
+        let module = self.module.join(item.name.unwrap());
+        let parent = mem::replace(&mut self.module, module);
+
+        for item in FlattenExternals::new().flatten(items.to_vec()) {
+            if let Some(item) = self.visit_item(item) {
+                self.module_items.push(item);
+            }
+        }
+        self.module = parent;
+
+        // Module items are hoisted into `module_items`, so the module itself produces nothing
+        None
+    }
 }
 
 impl<'ctx> StmtVisitor<'_> for Ladder<'ctx> {
@@ -464,6 +1757,7 @@ impl<'ctx> ExprVisitor<'_> for Ladder<'ctx> {
             AstExprKind::Loop(loop_) => self.visit_loop(expr, loop_),
             AstExprKind::For(for_) => self.visit_for(expr, for_),
             AstExprKind::Match(match_) => self.visit_match(expr, match_),
+            AstExprKind::With(with) => self.visit_with(expr, with),
             AstExprKind::Variable(var) => self.visit_variable(expr, *var),
             AstExprKind::Literal(literal) => {
                 let kind = ExprKind::Literal(self.visit_literal(literal));
@@ -473,6 +1767,7 @@ impl<'ctx> ExprVisitor<'_> for Ladder<'ctx> {
                     loc: expr.location(),
                 })
             }
+            AstExprKind::FormatString(segments) => self.visit_format_string(expr, segments),
             AstExprKind::UnaryOp(op, inner) => self.visit_unary(expr, *op, inner),
             AstExprKind::BinaryOp(Sided { lhs, op, rhs }) => {
                 self.visit_binary_op(expr, lhs, *op, rhs)
@@ -484,7 +1779,7 @@ impl<'ctx> ExprVisitor<'_> for Ladder<'ctx> {
             AstExprKind::Paren(inner) => self.visit_paren(expr, inner),
             AstExprKind::Array(elements) => self.visit_array(expr, elements),
             AstExprKind::Tuple(elements) => self.visit_tuple(expr, elements),
-            AstExprKind::Range(start, end) => self.visit_range(expr, start, end),
+            AstExprKind::Range(range) => self.visit_range(expr, range),
             AstExprKind::Index { var, index } => self.visit_index(expr, var, index),
             AstExprKind::FuncCall { caller, args } => self.visit_func_call(expr, caller, args),
             AstExprKind::MemberFuncCall { member, func } => {
@@ -494,6 +1789,7 @@ impl<'ctx> ExprVisitor<'_> for Ladder<'ctx> {
                 mutable,
                 expr: reference,
             } => self.visit_reference(expr, mutable, reference),
+            AstExprKind::Deref(pointer) => self.visit_deref(expr, pointer),
             &AstExprKind::Cast { expr: cast, ty } => self.visit_cast(expr, cast, ty),
         }
     }
@@ -674,35 +1970,105 @@ impl<'ctx> ExprVisitor<'_> for Ladder<'ctx> {
                 self.context().hir_stmt(Stmt::Expr(ret))
             };
 
+            // The returned value is evaluated before any `with` blocks are exited
+            let mut scope = vec![assign];
+            scope.extend(self.exit_hooks(true));
+            scope.push(ret);
+
             self.context().hir_expr(Expr {
-                kind: ExprKind::Scope(Block::new(vec![assign, ret], expr.location())),
+                kind: ExprKind::Scope(Block::new(scope, expr.location())),
                 loc: expr.location(),
             })
         } else {
             let kind = ExprKind::Return(Return { val: None });
-            self.context().hir_expr(Expr {
+            let ret = self.context().hir_expr(Expr {
                 kind,
                 loc: expr.location(),
-            })
+            });
+
+            let mut scope = self.exit_hooks(true);
+            if scope.is_empty() {
+                ret
+            } else {
+                scope.push(self.context().hir_stmt(Stmt::Expr(ret)));
+
+                self.context().hir_expr(Expr {
+                    kind: ExprKind::Scope(Block::new(scope, expr.location())),
+                    loc: expr.location(),
+                })
+            }
         }
     }
 
     fn visit_break(&mut self, expr: &AstExpr<'_>, value: Option<&AstExpr<'_>>) -> Self::Output {
-        let kind = ExprKind::Break(Break {
-            val: value.map(|expr| self.visit_expr(expr)),
+        self.check_within_loop("break", expr.location());
+        let val = value.map(|expr| self.visit_expr(expr));
+
+        let hooks = self.exit_hooks(false);
+        if hooks.is_empty() {
+            return self.context().hir_expr(Expr {
+                kind: ExprKind::Break(Break { val }),
+                loc: expr.location(),
+            });
+        }
+
+        // The broken value is evaluated before any `with` blocks are exited
+        let mut scope = Vec::with_capacity(hooks.len() + 2);
+        let val = val.map(|value| {
+            let name = self.next_var();
+            let ty = self.db.hir_type(Type {
+                kind: TypeKind::Unknown,
+                loc: expr.location(),
+            });
+
+            scope.push(self.context().hir_stmt(Stmt::VarDecl(VarDecl {
+                name,
+                value,
+                mutable: false,
+                ty,
+                loc: expr.location(),
+            })));
+
+            self.context().hir_expr(Expr {
+                kind: ExprKind::Variable(name, ty),
+                loc: expr.location(),
+            })
         });
 
+        scope.extend(hooks);
+        scope.push(
+            self.context()
+                .hir_stmt(Stmt::Expr(self.context().hir_expr(Expr {
+                    kind: ExprKind::Break(Break { val }),
+                    loc: expr.location(),
+                }))),
+        );
+
         self.context().hir_expr(Expr {
-            kind,
+            kind: ExprKind::Scope(Block::new(scope, expr.location())),
             loc: expr.location(),
         })
     }
 
     fn visit_continue(&mut self, expr: &AstExpr<'_>) -> Self::Output {
-        self.context().hir_expr(Expr {
+        self.check_within_loop("continue", expr.location());
+
+        let cont = self.context().hir_expr(Expr {
             kind: ExprKind::Continue,
             loc: expr.location(),
-        })
+        });
+
+        let mut scope = self.exit_hooks(false);
+        if scope.is_empty() {
+            cont
+        } else {
+            scope.push(self.context().hir_stmt(Stmt::Expr(cont)));
+
+            self.context().hir_expr(Expr {
+                kind: ExprKind::Scope(Block::new(scope, expr.location())),
+                loc: expr.location(),
+            })
+        }
     }
 
     fn visit_while(
@@ -710,171 +2076,73 @@ impl<'ctx> ExprVisitor<'_> for Ladder<'ctx> {
         expr: &AstExpr<'_>,
         AstWhile {
             cond,
-            body: ast_body,
+            body,
             then,
             else_,
         }: &AstWhile<'_>,
     ) -> Self::Output {
-        let mut scope: Block<&'ctx Stmt<'ctx>> = Block::with_capacity(
-            expr.location(),
-            2 + (then.is_some() as usize * 2) + (else_.is_some() as usize * 2),
-        );
-
-        let loop_broken = self.next_var();
-        scope.push(self.context().hir_stmt(Stmt::VarDecl(VarDecl {
-            name: loop_broken,
-            value: self.context().hir_expr(Expr {
-                kind: ExprKind::Literal(Literal {
-                    val: LiteralVal::Bool(false),
-                    ty: self.db.hir_type(Type {
-                        kind: TypeKind::Bool,
-                        loc: cond.location(),
-                    }),
-                    loc: cond.location(),
-                }),
-                loc: cond.location(),
-            }),
-            mutable: true,
-            ty: self.db.hir_type(Type {
-                kind: TypeKind::Bool,
-                loc: expr.location(),
-            }),
-            loc: cond.location(),
-        })));
-
-        let mut body: Block<&'ctx Stmt<'ctx>> =
-            Block::with_capacity(ast_body.location(), ast_body.len() + 1);
-
+        let scope = Block::with_capacity(expr.location(), 1);
         let cond = self.visit_expr(cond);
-        body.push(
-            self.context()
-                .hir_stmt(Stmt::Expr(self.context().hir_expr(Expr {
-                    kind: ExprKind::Match(Match {
-                        cond,
-                        arms: vec![
-                            // If the `while` condition is true, do nothing
-                            // TODO: `likely` annotation?
-                            // TODO: `generated` annotation?
-                            MatchArm {
-                                bind: Binding {
-                                    reference: false,
-                                    mutable: false,
-                                    pattern: Pattern::Literal(Literal {
-                                        val: LiteralVal::Bool(true),
-                                        ty: self.db.hir_type(Type {
-                                            kind: TypeKind::Bool,
-                                            loc: cond.location(),
-                                        }),
-                                        loc: cond.location(),
-                                    }),
-                                    ty: None,
-                                },
-                                guard: None,
-                                body: Block::empty(cond.location()),
-                                ty: self.db.hir_type(Type {
-                                    kind: TypeKind::Unknown,
-                                    loc: cond.location(),
-                                }),
-                            },
-                            // If the `while` condition returns false, set the status and break
-                            MatchArm {
-                                bind: Binding {
-                                    reference: false,
-                                    mutable: false,
-                                    pattern: Pattern::Literal(Literal {
-                                        val: LiteralVal::Bool(false),
-                                        ty: self.db.hir_type(Type {
-                                            kind: TypeKind::Bool,
-                                            loc: cond.location(),
-                                        }),
-                                        loc: cond.location(),
-                                    }),
-                                    ty: None,
-                                },
-                                guard: None,
-                                body: Block::from_iter(
-                                    cond.location(),
-                                    vec![
-                                        // Set the loop status to true since we've broken it
-                                        self.context().hir_stmt(Stmt::Expr(
-                                            self.context().hir_expr(Expr {
-                                                kind: ExprKind::Assign(
-                                                    loop_broken,
-                                                    self.context().hir_expr(Expr {
-                                                        kind: ExprKind::Literal(Literal {
-                                                            val: LiteralVal::Bool(true),
-                                                            ty: self.db.hir_type(Type {
-                                                                kind: TypeKind::Bool,
-                                                                loc: cond.location(),
-                                                            }),
-                                                            loc: cond.location(),
-                                                        }),
-                                                        loc: cond.location(),
-                                                    }),
-                                                ),
-                                                loc: cond.location(),
-                                            }),
-                                        )),
-                                        // Break from the loop
-                                        self.context().hir_stmt(Stmt::Expr(
-                                            self.context().hir_expr(Expr {
-                                                kind: ExprKind::Break(Break { val: None }),
-                                                loc: cond.location(),
-                                            }),
-                                        )),
-                                    ],
-                                ),
-                                ty: self.db.hir_type(Type {
-                                    kind: TypeKind::Unknown,
-                                    loc: cond.location(),
-                                }),
-                            },
-                        ],
-                        ty: self.db.hir_type(Type {
-                            kind: TypeKind::Unknown,
-                            loc: cond.location(),
-                        }),
-                    }),
-                    loc: cond.location(),
-                }))),
-        );
 
-        body.extend(ast_body.iter().filter_map(|s| self.visit_stmt(s)));
-
-        scope.push(
-            self.context()
-                .hir_stmt(Stmt::Expr(self.context().hir_expr(Expr {
-                    kind: ExprKind::Loop(body),
-                    loc: expr.location(),
-                }))),
-        );
+        self.conditional_loop(expr.location(), scope, cond, Vec::new(), body, then, else_)
+    }
 
-        self.visit_then_and_else(&mut scope, loop_broken, then, else_);
+    fn visit_loop(
+        &mut self,
+        expr: &AstExpr<'_>,
+        AstLoop { body, else_ }: &AstLoop<'_>,
+    ) -> Self::Output {
+        let loop_ = Loop {
+            cond: None,
+            body: Block::new(self.loop_body(body), body.location()),
+            then: None,
+            else_: else_.as_ref().map(|else_| self.loop_clause(else_)),
+            ty: self.context().hir_type(Type {
+                kind: TypeKind::Unknown,
+                loc: expr.location(),
+            }),
+        };
 
         self.context().hir_expr(Expr {
-            kind: ExprKind::Scope(scope),
+            kind: ExprKind::Loop(loop_),
             loc: expr.location(),
         })
     }
 
-    fn visit_loop(
+    fn visit_for(
         &mut self,
         expr: &AstExpr<'_>,
-        AstLoop { body, else_: _ }: &AstLoop<'_>,
+        AstFor {
+            var,
+            cond,
+            body,
+            then,
+            else_,
+        }: &AstFor<'_>,
     ) -> Self::Output {
-        let kind = ExprKind::Loop(Block::from_iter(
-            body.location(),
-            body.iter().filter_map(|stmt| self.visit_stmt(stmt)),
-        ));
+        let var = if let AstExprKind::Variable(var) = var.kind {
+            Var::User(*var)
+        } else {
+            self.errors.push_err(Locatable::new(
+                SemanticError::InvalidForBinding.into(),
+                var.location(),
+            ));
 
-        self.context().hir_expr(Expr {
-            kind,
-            loc: expr.location(),
-        })
-    }
+            self.next_var()
+        };
 
-    fn visit_for(&mut self, _expr: &AstExpr<'_>, _for: &AstFor<'_>) -> Self::Output {
-        todo!("Desugar `for` conditions to iterators")
+        match &cond.kind {
+            AstExprKind::Range(range) => self.range_loop(
+                expr.location(),
+                var,
+                range,
+                cond.location(),
+                body,
+                then,
+                else_,
+            ),
+            _ => self.element_loop(expr.location(), var, cond, body, then, else_),
+        }
     }
 
     fn visit_match(
@@ -912,6 +2180,68 @@ impl<'ctx> ExprVisitor<'_> for Ladder<'ctx> {
         })
     }
 
+    fn visit_with(
+        &mut self,
+        expr: &AstExpr<'_>,
+        AstWith { value, name, body }: &AstWith<'_>,
+    ) -> Self::Output {
+        let mut scope = Block::with_capacity(expr.location(), body.len() + 3);
+        let unknown = self.db.hir_type(Type {
+            kind: TypeKind::Unknown,
+            loc: expr.location(),
+        });
+
+        // The value is held by a hidden variable so that the hook is still called on it
+        // even if the user's binding is shadowed within the block
+        let held = self.next_var();
+        let value = self.visit_expr(value);
+        scope.push(self.context().hir_stmt(Stmt::VarDecl(VarDecl {
+            name: held,
+            value,
+            mutable: false,
+            ty: unknown,
+            loc: value.location(),
+        })));
+        scope.push(self.context().hir_stmt(Stmt::VarDecl(VarDecl {
+            name: Var::User(**name),
+            value: self.context().hir_expr(Expr {
+                kind: ExprKind::Variable(held, unknown),
+                loc: name.location(),
+            }),
+            mutable: false,
+            ty: unknown,
+            loc: name.location(),
+        })));
+
+        // The exit hook comes from the `Scoped` trait, which has to be in scope
+        let scoped = self.trait_id(&[self.context().strings().intern("Scoped")]);
+        if scoped.is_none() {
+            self.errors.push_err(Locatable::new(
+                SemanticError::UnknownTrait("Scoped".to_owned()).into(),
+                expr.location(),
+            ));
+        }
+
+        if let Some(scoped) = scoped {
+            self.exit_scopes.push(ExitScope::With {
+                value: held,
+                scoped,
+                loc: value.location(),
+            });
+        }
+        scope.extend(body.iter().filter_map(|stmt| self.visit_stmt(stmt)));
+
+        if let Some(scoped) = scoped {
+            self.exit_scopes.pop();
+            scope.push(self.exit_hook(held, scoped, value.location()));
+        }
+
+        self.context().hir_expr(Expr {
+            kind: ExprKind::Scope(scope),
+            loc: expr.location(),
+        })
+    }
+
     fn visit_variable(&mut self, expr: &AstExpr<'_>, var: Locatable<StrT>) -> Self::Output {
         self.context().hir_expr(Expr {
             kind: ExprKind::Variable(
@@ -953,13 +2283,40 @@ impl<'ctx> ExprVisitor<'_> for Ladder<'ctx> {
         }
     }
 
+    fn visit_format_string(
+        &mut self,
+        expr: &AstExpr<'_>,
+        segments: &[AstFormatSegment<'_>],
+    ) -> Self::Output {
+        let segments = segments
+            .iter()
+            .map(|segment| match segment {
+                AstFormatSegment::Text(text) => FormatSegment::Text(text.clone()),
+                &AstFormatSegment::Value { expr, spec } => FormatSegment::Value {
+                    expr: self.visit_expr(expr),
+                    spec,
+                },
+            })
+            .collect();
+
+        self.context().hir_expr(Expr {
+            kind: ExprKind::FormatString(segments),
+            loc: expr.location(),
+        })
+    }
+
     fn visit_unary(
         &mut self,
-        _expr: &AstExpr<'_>,
-        _op: UnaryOp,
-        _inner: &AstExpr<'_>,
+        expr: &AstExpr<'_>,
+        op: UnaryOp,
+        inner: &AstExpr<'_>,
     ) -> Self::Output {
-        todo!()
+        let operand = self.visit_expr(inner);
+
+        self.context().hir_expr(Expr {
+            kind: ExprKind::UnaryOp(op, operand),
+            loc: expr.location(),
+        })
     }
 
     fn visit_binary_op(
@@ -969,6 +2326,10 @@ impl<'ctx> ExprVisitor<'_> for Ladder<'ctx> {
         op: BinaryOp,
         rhs: &AstExpr<'_>,
     ) -> Self::Output {
+        if let BinaryOp::And | BinaryOp::Or = op {
+            return self.short_circuit(expr, lhs, op, rhs);
+        }
+
         let lhs = self.visit_expr(lhs);
         let rhs = self.visit_expr(rhs);
 
@@ -1001,60 +2362,60 @@ impl<'ctx> ExprVisitor<'_> for Ladder<'ctx> {
         op: AssignKind,
         rhs: &AstExpr<'_>,
     ) -> Self::Output {
-        let var = if let AstExpr {
-            kind: AstExprKind::Variable(var),
-            ..
-        } = lhs
-        {
-            Var::User(**var)
-        } else {
-            self.next_var()
+        let place = self.place(lhs);
+        let value = self.visit_expr(rhs);
+        let place = match place {
+            Some(place) => place,
+            None => return value,
+        };
+        let op = match op {
+            AssignKind::Normal => None,
+            AssignKind::BinaryOp(op) => Some(op),
         };
 
-        if let AssignKind::BinaryOp(op) = op {
-            let lhs = self.visit_expr(lhs);
-            let rhs = self.visit_expr(rhs);
-
-            self.context().hir_expr(Expr {
-                kind: ExprKind::Assign(
-                    var,
-                    self.context().hir_expr(Expr {
-                        kind: ExprKind::BinOp(Sided { lhs, op, rhs }),
-                        loc: expr.location(),
-                    }),
-                ),
-                loc: expr.location(),
-            })
-        } else {
-            // FIXME: This is wrong lol
-            let kind = ExprKind::Assign(var, self.visit_expr(rhs));
-
-            self.context().hir_expr(Expr {
-                kind,
-                loc: expr.location(),
-            })
-        }
+        self.context().hir_expr(Expr {
+            kind: ExprKind::Assign { place, op, value },
+            loc: expr.location(),
+        })
     }
 
     fn visit_paren(&mut self, _expr: &AstExpr<'_>, inner: &AstExpr<'_>) -> Self::Output {
         self.visit_expr(inner)
     }
 
-    fn visit_array(&mut self, _expr: &AstExpr<'_>, _elements: &[&AstExpr<'_>]) -> Self::Output {
-        todo!()
+    fn visit_array(&mut self, expr: &AstExpr<'_>, elements: &[&AstExpr<'_>]) -> Self::Output {
+        let elements = elements.iter().map(|elem| self.visit_expr(elem)).collect();
+        let element = self
+            .context()
+            .hir_type(Type::new(TypeKind::Unknown, expr.location()));
+
+        self.context().hir_expr(Expr {
+            kind: ExprKind::Array { elements, element },
+            loc: expr.location(),
+        })
     }
 
-    fn visit_tuple(&mut self, _expr: &AstExpr<'_>, _elements: &[&AstExpr<'_>]) -> Self::Output {
-        todo!()
+    fn visit_tuple(&mut self, expr: &AstExpr<'_>, elements: &[&AstExpr<'_>]) -> Self::Output {
+        let elements = elements.iter().map(|elem| self.visit_expr(elem)).collect();
+
+        self.context().hir_expr(Expr {
+            kind: ExprKind::Tuple(elements),
+            loc: expr.location(),
+        })
     }
 
     fn visit_range(
         &mut self,
-        _expr: &AstExpr<'_>,
-        _start: &AstExpr<'_>,
-        _end: &AstExpr<'_>,
+        expr: &AstExpr<'_>,
+        &AstRange { start, end, kind }: &AstRange<'_>,
     ) -> Self::Output {
-        todo!()
+        let start = start.map(|start| self.visit_expr(start));
+        let end = end.map(|end| self.visit_expr(end));
+
+        self.context().hir_expr(Expr {
+            kind: ExprKind::Range(Range { start, end, kind }),
+            loc: expr.location(),
+        })
     }
 
     fn visit_index(
@@ -1064,72 +2425,18 @@ impl<'ctx> ExprVisitor<'_> for Ladder<'ctx> {
         index: &AstExpr<'_>,
     ) -> Self::Output {
         let mut scope = Vec::with_capacity(2);
-        let indexee_name = if let AstExpr {
-            kind: AstExprKind::Variable(var),
-            ..
-        } = var
-        {
-            Var::User(**var)
-        } else {
-            let value = self.visit_expr(var);
-            let name = self.next_var();
-            let ty = self
-                .db
-                .context()
-                .hir_type(Type::new(TypeKind::Unknown, expr.location()));
-
-            scope.push(self.context().hir_stmt(Stmt::VarDecl(VarDecl {
-                name,
-                value,
-                mutable: false,
-                ty,
-                loc: expr.location(),
-            })));
-
-            name
-        };
-
-        let (index_name, index_ty) = if let AstExpr {
-            kind: AstExprKind::Variable(var),
-            ..
-        } = index
-        {
-            (
-                Var::User(**var),
-                self.db
-                    .context()
-                    .hir_type(Type::new(TypeKind::Unknown, expr.location())),
-            )
-        } else {
-            let value = self.visit_expr(index);
-            let name = self.next_var();
-            let ty = self
-                .db
-                .context()
-                .hir_type(Type::new(TypeKind::Unknown, expr.location()));
-
-            scope.push(self.context().hir_stmt(Stmt::VarDecl(VarDecl {
-                name,
-                value,
-                mutable: false,
-                ty,
-                loc: expr.location(),
-            })));
-
-            (name, ty)
-        };
+        let var = self.bind_value(var, &mut scope);
 
+        // The index is kept as-is so that constant indices can be bounds checked statically
+        let index = self.visit_expr(index);
         let index = self.context().hir_expr(Expr {
-            kind: ExprKind::Variable(index_name, index_ty),
-            loc: expr.location(),
-        });
-        let index = self.context().hir_expr(Expr {
-            kind: ExprKind::Index {
-                var: indexee_name,
-                index,
-            },
+            kind: ExprKind::Index { var, index },
             loc: expr.location(),
         });
+
+        if scope.is_empty() {
+            return index;
+        }
         scope.push(self.context().hir_stmt(Stmt::Expr(index)));
 
         self.context().hir_expr(Expr {
@@ -1142,30 +2449,140 @@ impl<'ctx> ExprVisitor<'_> for Ladder<'ctx> {
         &mut self,
         expr: &AstExpr<'_>,
         caller: &AstExpr<'_>,
-        args: &[&AstExpr<'_>],
+        args: &[AstCallArg<'_>],
     ) -> Self::Output {
-        let args = args.iter().map(|a| self.visit_expr(a)).collect();
+        let func = if let AstExprKind::Variable(path) = caller.kind {
+            self.resolve_path(&[*path], caller.location())
+        } else {
+            return self.invalid_expr(SemanticError::InvalidCaller, expr.location());
+        };
 
-        self.context().hir_expr(Expr {
-            kind: ExprKind::FnCall(FuncCall {
-                func: if let AstExprKind::Variable(path) = caller.kind {
-                    ItemPath::new(*path)
-                } else {
-                    todo!()
-                },
-                args,
-            }),
-            loc: expr.location(),
-        })
+        self.func_call(func, args, expr.location())
     }
 
     fn visit_member_func_call(
         &mut self,
-        _expr: &AstExpr<'_>,
-        _member: &AstExpr<'_>,
-        _func: &AstExpr<'_>,
+        expr: &AstExpr<'_>,
+        member: &AstExpr<'_>,
+        func: &AstExpr<'_>,
     ) -> Self::Output {
-        todo!()
+        // Tuple fields, `tuple.0`
+        if let Some(index) = Self::tuple_index(func) {
+            let tuple = self.visit_expr(member);
+
+            return self.context().hir_expr(Expr {
+                kind: ExprKind::TupleField { tuple, index },
+                loc: expr.location(),
+            });
+        }
+
+        // Calls to functions within modules, `a.b.c()`, and enum variants, `a.Enum.Variant(b)`,
+        // are parsed as chains of member calls
+        let module = match member.kind {
+            AstExprKind::Variable(module) if self.is_namespace(*module) => module,
+
+            // Anything else is the receiver of a chain of method calls, `a.b().c()`
+            _ => {
+                let receiver = self.visit_expr(member);
+                return self.method_chain(receiver, func);
+            }
+        };
+
+        let mut path = vec![*module];
+        let mut func = func;
+        loop {
+            if let Some(enum_id) = self.enum_id(&path) {
+                self.resolve_path(&path, expr.location());
+
+                return match &func.kind {
+                    AstExprKind::Variable(variant) => {
+                        self.variant_ctor(expr, enum_id, **variant, &[])
+                    }
+                    AstExprKind::FuncCall { caller, args } => match caller.kind {
+                        AstExprKind::Variable(variant) => {
+                            self.variant_ctor(expr, enum_id, *variant, args)
+                        }
+                        _ => self.invalid_expr(SemanticError::InvalidCaller, expr.location()),
+                    },
+
+                    // Methods called on a variant, `Enum.Variant(a).method()`
+                    AstExprKind::MemberFuncCall { member, func } => {
+                        let receiver = match &member.kind {
+                            AstExprKind::Variable(variant) => {
+                                self.variant_ctor(member, enum_id, **variant, &[])
+                            }
+                            AstExprKind::FuncCall { caller, args } => match caller.kind {
+                                AstExprKind::Variable(variant) => {
+                                    self.variant_ctor(member, enum_id, *variant, args)
+                                }
+                                _ => self
+                                    .invalid_expr(SemanticError::InvalidCaller, member.location()),
+                            },
+                            _ => {
+                                self.invalid_expr(SemanticError::InvalidVariant, member.location())
+                            }
+                        };
+
+                        self.method_chain(receiver, func)
+                    }
+
+                    _ => self.invalid_expr(SemanticError::InvalidVariant, func.location()),
+                };
+            }
+
+            match &func.kind {
+                AstExprKind::MemberFuncCall {
+                    member,
+                    func: inner,
+                } => {
+                    match &member.kind {
+                        AstExprKind::Variable(segment) => {
+                            path.push(**segment);
+                            func = inner;
+                        }
+
+                        // Methods called on the result of a function, `module.func().method()`
+                        AstExprKind::FuncCall { caller, args } => {
+                            if let AstExprKind::Variable(segment) = caller.kind {
+                                path.push(*segment);
+                            } else {
+                                return self
+                                    .invalid_expr(SemanticError::InvalidCaller, member.location());
+                            }
+
+                            let func = self.resolve_path(&path, member.location());
+                            let receiver = self.func_call(func, args, member.location());
+
+                            return self.method_chain(receiver, inner);
+                        }
+
+                        _ => {
+                            return self.invalid_expr(
+                                SemanticError::UnsupportedFieldAccess,
+                                member.location(),
+                            )
+                        }
+                    }
+                }
+
+                AstExprKind::FuncCall { caller, args } => {
+                    if let AstExprKind::Variable(segment) = caller.kind {
+                        path.push(*segment);
+                    } else {
+                        return self.invalid_expr(SemanticError::InvalidCaller, expr.location());
+                    }
+
+                    let func = self.resolve_path(&path, expr.location());
+
+                    return self.func_call(func, args, expr.location());
+                }
+
+                _ => {
+                    return self
+                        .invalid_expr(SemanticError::UnsupportedFieldAccess, func.location())
+                }
+            }
+        }
     }
 
     fn visit_reference(
@@ -1182,6 +2599,15 @@ impl<'ctx> ExprVisitor<'_> for Ladder<'ctx> {
         })
     }
 
+    fn visit_deref(&mut self, expr: &AstExpr<'_>, pointer: &AstExpr<'_>) -> Self::Output {
+        let pointer = self.visit_expr(pointer);
+
+        self.context().hir_expr(Expr {
+            kind: ExprKind::Deref(pointer),
+            loc: expr.location(),
+        })
+    }
+
     fn visit_cast(
         &mut self,
         expr: &AstExpr<'_>,
@@ -1220,8 +2646,39 @@ impl<'ctx> ExprVisitor<'_> for Ladder<'ctx> {
         match pattern {
             AstPattern::Literal(lit) => Pattern::Literal(self.visit_literal(lit)),
             &AstPattern::Ident(ident) => Pattern::Ident(ident),
-            AstPattern::ItemPath(path) => Pattern::ItemPath(path.clone()),
+            AstPattern::ItemPath(path) => Pattern::ItemPath(self.resolve_variant_path(path)),
             AstPattern::Wildcard => Pattern::Wildcard,
+            AstPattern::Rest => Pattern::Rest,
+
+            AstPattern::Tuple(elements) => {
+                Pattern::Tuple(elements.iter().map(|p| self.visit_pattern(p)).collect())
+            }
+            AstPattern::Slice(elements) => {
+                Pattern::Slice(elements.iter().map(|p| self.visit_pattern(p)).collect())
+            }
+            AstPattern::Struct { path, fields, rest } => Pattern::Struct {
+                path: path.clone(),
+                fields: fields
+                    .iter()
+                    .map(|field| (field.name, self.visit_pattern(&field.pattern)))
+                    .collect(),
+                rest: *rest,
+            },
+            AstPattern::Variant { path, elements } => Pattern::Variant {
+                path: self.resolve_variant_path(path),
+                elements: elements.iter().map(|p| self.visit_pattern(p)).collect(),
+            },
+            AstPattern::Bind(name, pattern) => {
+                Pattern::Bind(*name, Ref::new(self.visit_pattern(pattern)))
+            }
+            AstPattern::Or(alternatives) => {
+                Pattern::Or(alternatives.iter().map(|p| self.visit_pattern(p)).collect())
+            }
+            AstPattern::Range { start, end, kind } => Pattern::Range {
+                start: start.as_ref().map(|start| self.visit_literal(start)),
+                end: end.as_ref().map(|end| self.visit_literal(end)),
+                kind: *kind,
+            },
         }
     }
 }
@@ -1230,7 +2687,7 @@ impl<'ctx> TypeVisitor<'_> for Ladder<'ctx> {
     type Output = TypeId;
 
     fn visit_type(&mut self, r#type: Locatable<&AstType<'_>>) -> TypeId {
-        let kind = self.visit_type_kind(*r#type);
+        let kind = self.visit_type_kind(*r#type, r#type.location());
 
         self.db.hir_type(Type {
             kind,
@@ -1240,12 +2697,13 @@ impl<'ctx> TypeVisitor<'_> for Ladder<'ctx> {
 }
 
 impl<'ctx> Ladder<'ctx> {
-    fn visit_type_kind(&mut self, r#type: &AstType<'_>) -> TypeKind {
+    fn visit_type_kind(&mut self, r#type: &AstType<'_>, loc: Location) -> TypeKind {
         match r#type {
             AstType::Unknown => TypeKind::Unknown,
             AstType::Unit => TypeKind::Unit,
             AstType::Bool => TypeKind::Bool,
             AstType::String => TypeKind::String,
+            AstType::Absurd => TypeKind::Absurd,
             &AstType::Integer { signed, width } => TypeKind::Integer { signed, width },
 
             &AstType::Array { element, length } => {
@@ -1260,6 +2718,19 @@ impl<'ctx> Ladder<'ctx> {
                 TypeKind::Slice { element }
             }
 
+            AstType::Tuple(elements) => TypeKind::Tuple(
+                elements
+                    .iter()
+                    .map(|&element| self.visit_type(element))
+                    .collect(),
+            ),
+
+            &AstType::Range { element } => {
+                let element = self.visit_type(element);
+
+                TypeKind::Range { element }
+            }
+
             &AstType::Pointer { pointee, mutable } => {
                 let pointee = self.visit_type(pointee);
 
@@ -1272,7 +2743,193 @@ impl<'ctx> Ladder<'ctx> {
                 TypeKind::Reference { referee, mutable }
             }
 
-            ty => todo!("{:?}", ty),
+            AstType::ItemPath(path) if self.self_type.is_some() && self.is_self_type(path) => {
+                self.self_type.clone().unwrap()
+            }
+
+            AstType::ItemPath(path) => {
+                if let Some(generic) = self.generic_type(path) {
+                    return generic;
+                }
+
+                if let Some(id) = self.enum_id(path) {
+                    self.resolve_path(path, loc);
+
+                    // Generic enums used without any generics have them inferred
+                    let generics = self.infer_generics(self.generic_count(path), loc);
+                    TypeKind::Enum { id, generics }
+                } else if let Some(alias) = self.alias_type(path) {
+                    self.resolve_path(path, loc);
+                    TypeKind::Variable(alias)
+                } else if let Some(id) = self.trait_id(path) {
+                    let path = self.resolve_path(path, loc);
+                    TypeKind::Trait(vec![(id, path)])
+                } else {
+                    self.errors.push_err(Locatable::new(
+                        SemanticError::UnknownType(path.to_string(self.context().strings())).into(),
+                        loc,
+                    ));
+
+                    TypeKind::Unknown
+                }
+            }
+
+            AstType::Bounded { path, bounds } => match self.enum_id(path) {
+                Some(id) => {
+                    self.resolve_path(path, loc);
+
+                    let expected = self.generic_count(path);
+                    if bounds.len() != expected {
+                        self.errors.push_err(Locatable::new(
+                            SemanticError::GenericArity {
+                                name: path.to_string(self.context().strings()),
+                                expected,
+                                received: bounds.len(),
+                            }
+                            .into(),
+                            loc,
+                        ));
+
+                        return TypeKind::Unknown;
+                    }
+
+                    let generics = bounds.iter().map(|&ty| self.visit_type(ty)).collect();
+                    TypeKind::Enum { id, generics }
+                }
+
+                None => {
+                    self.errors.push_err(Locatable::new(
+                        SemanticError::UnknownType(path.to_string(self.context().strings())).into(),
+                        loc,
+                    ));
+
+                    TypeKind::Unknown
+                }
+            },
+
+            &AstType::Paren(inner) => self.visit_type_kind(*inner, inner.location()),
+            AstType::Rune => TypeKind::Rune,
+            &AstType::Float { width } => TypeKind::Float { width },
+
+            &AstType::Not(ty) => TypeKind::Not(self.visit_type(ty)),
+
+            &AstType::Const(name, ty) => {
+                let ty = self.visit_type(ty);
+
+                TypeKind::Const { name, ty }
+            }
+
+            AstType::Func { params, ret } => {
+                let params = params.iter().map(|&param| self.visit_type(param)).collect();
+                let ret = self.visit_type(*ret);
+
+                TypeKind::Func { params, ret }
+            }
+
+            AstType::Trait(traits) => {
+                let mut resolved = Vec::with_capacity(traits.len());
+                for bound in traits {
+                    match **bound {
+                        AstType::ItemPath(trait_path) if self.trait_id(trait_path).is_some() => {
+                            let id = self.trait_id(trait_path).unwrap();
+                            resolved.push((id, self.resolve_path(trait_path, bound.location())));
+                        }
+
+                        _ => self.errors.push_err(Locatable::new(
+                            SemanticError::UnknownTrait(bound.to_string(self.context().strings()))
+                                .into(),
+                            bound.location(),
+                        )),
+                    }
+                }
+
+                TypeKind::Trait(resolved)
+            }
+
+            AstType::Operand(Sided { lhs, op, rhs }) => {
+                let mut operands = Vec::new();
+                Self::type_operands(*lhs, *op, &mut operands);
+                Self::type_operands(*rhs, *op, &mut operands);
+
+                match op {
+                    TypeOp::Or => TypeKind::Union(
+                        operands
+                            .into_iter()
+                            .map(|operand| self.visit_type(operand))
+                            .collect(),
+                    ),
+                    TypeOp::And => TypeKind::Intersection(self.intersection(operands)),
+                }
+            }
+
+            AstType::IntReg { .. } | AstType::IntPtr { .. } => {
+                self.errors.push_err(Locatable::new(
+                    SemanticError::UnsupportedType(r#type.to_string(self.context().strings()))
+                        .into(),
+                    loc,
+                ));
+
+                TypeKind::Unknown
+            }
+        }
+    }
+
+    /// Flattens chains of the same type operator, so `A | B | C` becomes `[A, B, C]`
+    fn type_operands<'a>(
+        ty: Locatable<&'a AstType<'a>>,
+        op: TypeOp,
+        operands: &mut Vec<Locatable<&'a AstType<'a>>>,
+    ) {
+        match *ty {
+            AstType::Operand(Sided {
+                lhs,
+                op: inner,
+                rhs,
+            }) if *inner == op => {
+                Self::type_operands(*lhs, op, operands);
+                Self::type_operands(*rhs, op, operands);
+            }
+
+            _ => operands.push(ty),
+        }
+    }
+
+    /// Lowers the types of an intersection, where everything but one of them must be a
+    /// trait since nothing can be two different types at once
+    fn intersection(&mut self, operands: Vec<Locatable<&AstType<'_>>>) -> Vec<TypeId> {
+        let mut seen_type = false;
+        let mut lowered = Vec::with_capacity(operands.len());
+
+        for operand in operands {
+            let ty = self.visit_type(operand);
+
+            if !self.is_trait_type(ty) {
+                if seen_type {
+                    self.errors.push_err(Locatable::new(
+                        SemanticError::InvalidIntersection(
+                            operand.to_string(self.context().strings()),
+                        )
+                        .into(),
+                        operand.location(),
+                    ));
+                }
+
+                seen_type = true;
+            }
+
+            lowered.push(ty);
+        }
+
+        lowered
+    }
+
+    /// Returns `true` if the type is a trait, aliases that haven't been lowered yet are
+    /// assumed to be traits
+    fn is_trait_type(&self, ty: TypeId) -> bool {
+        match self.context().get_hir_type(ty).unwrap().kind {
+            TypeKind::Variable(ty) => self.is_trait_type(ty),
+            TypeKind::Trait(_) | TypeKind::Unknown => true,
+            _ => false,
         }
     }
 }